@@ -1,4 +1,5 @@
 use crate::dispatcher::Dispatcher;
+use crate::messages::portfolio::utility_types::Platform;
 use crate::messages::prelude::*;
 
 pub use graphene_core::uuid::*;
@@ -15,15 +16,61 @@ impl Editor {
 		Self { dispatcher: Dispatcher::new() }
 	}
 
+	/// Construct an initialized editor for running without a frontend, such as from a command line tool.
+	/// The uuid seed is fixed so repeated runs produce identical documents. Returns the messages sent to the frontend during initialization.
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn new_headless(platform: Platform) -> (Self, Vec<FrontendMessage>) {
+		set_uuid_seed(0);
+
+		let mut editor = Self::new();
+
+		// See the equivalent comment in `EditorTestUtils::create()` for why this is set directly
+		let _ = GLOBAL_PLATFORM.set(platform);
+
+		editor.load_system_fonts();
+
+		let responses = editor.handle_message(Message::Init);
+		(editor, responses)
+	}
+
+	/// Render text with the fonts installed on the system, which is needed outside the browser where the frontend can't load fonts from the web.
+	/// The fonts are only searched for once text is first rendered.
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn load_system_fonts(&mut self) {
+		use graphene_core::text::{fallback_fonts, LocalFontProvider};
+
+		let provider = std::sync::Arc::new(LocalFontProvider::system());
+		self.dispatcher.message_handlers.portfolio_message_handler.add_font_provider(provider, fallback_fonts());
+	}
+
 	pub fn handle_message<T: Into<Message>>(&mut self, message: T) -> Vec<FrontendMessage> {
 		self.dispatcher.handle_message(message);
 
 		std::mem::take(&mut self.dispatcher.responses)
 	}
 
+	/// Handles a message produced by [`Self::poll_node_graph_evaluation`].
+	/// Unlike [`Self::handle_message`], these messages aren't included in session recordings since replaying a recording evaluates the node graph again.
+	pub fn handle_node_graph_message<T: Into<Message>>(&mut self, message: T) -> Vec<FrontendMessage> {
+		self.dispatcher.handle_unrecorded_message(message);
+
+		std::mem::take(&mut self.dispatcher.responses)
+	}
+
 	pub fn poll_node_graph_evaluation(&mut self, responses: &mut VecDeque<Message>) {
 		self.dispatcher.poll_node_graph_evaluation(responses);
 	}
+
+	/// Run the node graph to completion and handle its results immediately, which is what the frontend's render loop does asynchronously.
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn evaluate_node_graph_blocking(&mut self) -> Vec<FrontendMessage> {
+		futures::executor::block_on(crate::node_graph_executor::run_node_graph());
+
+		let mut messages = VecDeque::new();
+		self.poll_node_graph_evaluation(&mut messages);
+
+		messages.into_iter().flat_map(|message| self.handle_node_graph_message(message)).collect()
+	}
 }
 
 impl Default for Editor {
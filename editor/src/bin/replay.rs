@@ -0,0 +1,46 @@
+//! Replays a session recorded with "Debug: Record Session" headlessly and compares the result against a snapshot.
+//!
+//! Usage: `graphite-replay <recording.json> [snapshot.json] [--update]`
+//!
+//! Without a snapshot path, the resulting snapshot is printed. With one, the replay is compared against it and the process exits with an error on mismatch.
+//! Passing `--update` writes the replay result to the snapshot path instead of comparing against it.
+
+use graphite_editor::messages::debug::utility_types::MessageRecording;
+use graphite_editor::replay::{replay_recording, ReplaySnapshot};
+
+use std::error::Error;
+use std::process::ExitCode;
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+	let update = std::env::args().any(|arg| arg == "--update");
+	let mut paths = std::env::args().skip(1).filter(|arg| arg != "--update");
+
+	let recording_path = paths.next().expect("No recording path provided");
+	let snapshot_path = paths.next();
+
+	let recording = MessageRecording::from_json(&std::fs::read_to_string(&recording_path)?)?;
+	let snapshot = replay_recording(&recording);
+
+	let Some(snapshot_path) = snapshot_path else {
+		println!("{}", snapshot.to_json());
+		return Ok(ExitCode::SUCCESS);
+	};
+
+	if update {
+		std::fs::write(&snapshot_path, snapshot.to_json())?;
+		println!("Updated snapshot {snapshot_path}");
+		return Ok(ExitCode::SUCCESS);
+	}
+
+	let expected = ReplaySnapshot::from_json(&std::fs::read_to_string(&snapshot_path)?)?;
+	match snapshot.first_difference(&expected) {
+		None => {
+			println!("Replay of {recording_path} matches {snapshot_path}");
+			Ok(ExitCode::SUCCESS)
+		}
+		Some(difference) => {
+			eprintln!("Replay of {recording_path} does not match {snapshot_path}:\n{difference}");
+			Ok(ExitCode::FAILURE)
+		}
+	}
+}
@@ -0,0 +1,83 @@
+//! Runs an automation script against a Graphite document without opening the editor.
+//!
+//! Usage: `graphite-script <document.graphite> <script.rhai> [--output <document.graphite>]`
+//!
+//! The modified document is written back to the input path unless `--output` is given. SVG files exported by the script are written next to it.
+
+use graphite_editor::application::Editor;
+use graphite_editor::messages::portfolio::utility_types::Platform;
+use graphite_editor::messages::prelude::*;
+use graphite_editor::scripting::run_script;
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+	let mut args = std::env::args().skip(1);
+	let mut paths = Vec::new();
+	let mut output_path = None;
+	while let Some(arg) = args.next() {
+		if arg == "--output" {
+			output_path = Some(PathBuf::from(args.next().expect("No path provided after --output")));
+		} else {
+			paths.push(PathBuf::from(arg));
+		}
+	}
+	let [document_path, script_path] = paths.as_slice() else {
+		eprintln!("Usage: graphite-script <document.graphite> <script.rhai> [--output <document.graphite>]");
+		return Ok(ExitCode::FAILURE);
+	};
+	let output_path = output_path.unwrap_or_else(|| document_path.clone());
+
+	let document_name = document_path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+	let document_serialized_content = std::fs::read_to_string(document_path)?;
+	let script = std::fs::read_to_string(script_path)?;
+
+	let (mut editor, _) = Editor::new_headless(Platform::Unknown);
+	editor.handle_message(PortfolioMessage::OpenDocumentFile {
+		document_name,
+		document_serialized_content,
+	});
+	editor.evaluate_node_graph_blocking();
+
+	let Some(document) = editor.dispatcher.message_handlers.portfolio_message_handler.active_document() else {
+		eprintln!("Failed to open {}", document_path.display());
+		return Ok(ExitCode::FAILURE);
+	};
+	let script_messages = match run_script(&script, document) {
+		Ok(messages) => messages,
+		Err(error) => {
+			eprintln!("Script {} failed: {error}", script_path.display());
+			return Ok(ExitCode::FAILURE);
+		}
+	};
+
+	let mut responses = editor.handle_message(DocumentMessage::StartTransaction);
+	for message in script_messages {
+		responses.extend(editor.handle_message(message));
+	}
+	responses.extend(editor.handle_message(DocumentMessage::CommitTransaction));
+	responses.extend(editor.evaluate_node_graph_blocking());
+
+	let export_directory = output_path.parent().unwrap_or(Path::new("."));
+	for response in responses {
+		match response {
+			FrontendMessage::TriggerDownloadTextFile { document, name } => {
+				let path = export_directory.join(name);
+				std::fs::write(&path, document)?;
+				println!("Exported {}", path.display());
+			}
+			FrontendMessage::TriggerDownloadRaster { name, .. } => {
+				eprintln!("Skipped exporting {name} because raster exports require the browser to rasterize the document, export as SVG instead");
+			}
+			_ => {}
+		}
+	}
+
+	let document = editor.dispatcher.message_handlers.portfolio_message_handler.active_document().expect("The document was closed by the script");
+	std::fs::write(&output_path, document.serialize_document())?;
+	println!("Saved {}", output_path.display());
+
+	Ok(ExitCode::SUCCESS)
+}
@@ -29,6 +29,18 @@ pub const SNAP_POINT_OVERLAY_FADE_FAR: f64 = 40.;
 pub const SNAP_POINT_UNSNAPPED_OPACITY: f64 = 0.4;
 pub const SNAP_POINT_TOLERANCE: f64 = 5.;
 pub const SNAP_POINT_SIZE: f64 = 5.;
+pub const SNAP_INTERSECTION_ANCHOR_TOLERANCE: f64 = 1e-3;
+
+// Snapping measurements
+pub const SNAP_MEASUREMENT_TICK_SIZE: f64 = 6.;
+pub const SNAP_MEASUREMENT_LABEL_FONT_SIZE: f64 = 11.;
+pub const SNAP_MEASUREMENT_LABEL_OFFSET: f64 = 4.;
+
+// Guides
+pub const GUIDE_SELECTION_TOLERANCE: f64 = 4.;
+
+// Pixel selection
+pub const POLYGONAL_LASSO_CLOSE_TOLERANCE: f64 = 6.;
 
 pub const DRAG_THRESHOLD: f64 = 1.;
 
@@ -73,13 +85,15 @@ pub const SCALE_EFFECT: f64 = 0.5;
 
 // Colors
 pub const COLOR_ACCENT: Color = Color::from_rgbf32_unchecked(0x00 as f32 / 255., 0xA8 as f32 / 255., 0xFF as f32 / 255.);
+pub const COLOR_GUIDE: Color = Color::from_rgbf32_unchecked(0xFF as f32 / 255., 0x3E as f32 / 255., 0xA5 as f32 / 255.);
+pub const COLOR_GRID: Color = Color::from_rgbaf32_unchecked(0x88 as f32 / 255., 0x88 as f32 / 255., 0x88 as f32 / 255., 0.4);
 
 // Fonts
 pub const DEFAULT_FONT_FAMILY: &str = "Merriweather";
 pub const DEFAULT_FONT_STYLE: &str = "Normal (400)";
 
 // Document
-pub const GRAPHITE_DOCUMENT_VERSION: &str = "0.0.18"; // Remember to update the demo artwork in /demos with both this version number and the contents so it remains editable
+pub const GRAPHITE_DOCUMENT_VERSION: &str = "0.0.24"; // Remember to update the demo artwork in /demos with both this version number and the contents so it remains editable
 pub const DEFAULT_DOCUMENT_NAME: &str = "Untitled Document";
 pub const FILE_SAVE_SUFFIX: &str = ".graphite";
 pub const MAX_UNDO_HISTORY_LEN: usize = 100; // TODO: Add this to user preferences
@@ -60,8 +60,19 @@ impl Dispatcher {
 		}
 	}
 
-	#[remain::check]
 	pub fn handle_message<T: Into<Message>>(&mut self, message: T) {
+		let message = message.into();
+
+		// Messages arriving from outside the dispatcher are what make up a session recording
+		self.message_handlers.debug_message_handler.record_message(&message);
+
+		self.handle_unrecorded_message(message);
+	}
+
+	/// Processes a message without adding it to an active session recording.
+	/// This is used for the results of node graph evaluation, which are reproduced by evaluating the graph again when a recording is replayed.
+	#[remain::check]
+	pub fn handle_unrecorded_message<T: Into<Message>>(&mut self, message: T) {
 		use Message::*;
 
 		self.message_queues.push(VecDeque::from_iter([message.into()]));
@@ -109,7 +120,15 @@ impl Dispatcher {
 
 				Broadcast(message) => self.message_handlers.broadcast_message_handler.process_message(message, &mut queue, ()),
 				Debug(message) => {
-					self.message_handlers.debug_message_handler.process_message(message, &mut queue, ());
+					self.message_handlers.debug_message_handler.process_message(
+						message,
+						&mut queue,
+						(
+							&self.message_handlers.portfolio_message_handler,
+							&self.message_handlers.input_preprocessor_message_handler,
+							&self.message_handlers.preferences_message_handler,
+						),
+					);
 				}
 				Dialog(message) => {
 					self.message_handlers.dialog_message_handler.process_message(
@@ -597,4 +616,40 @@ mod test {
 			}
 		}
 	}
+
+	/// Checks that nodes of a type saved with an earlier document version, given by their number of inputs and their proto node, are given the current inputs and implementation of the type
+	fn assert_earlier_version_nodes_are_upgraded(version: &str, name: &str, old_nodes: &[(usize, &'static str)]) {
+		use crate::messages::portfolio::document::node_graph::resolve_document_node_type;
+		use graph_craft::document::DocumentNodeImplementation;
+
+		let node_type = resolve_document_node_type(name).unwrap();
+		let mut document = DocumentMessageHandler::default();
+		for (node_id, &(input_count, identifier)) in old_nodes.iter().enumerate() {
+			let mut node = node_type.to_document_node_default_inputs([], Default::default());
+			node.inputs.truncate(input_count);
+			node.implementation = DocumentNodeImplementation::proto(identifier);
+			document.document_legacy.document_network.nodes.insert(node_id as u64, node);
+		}
+		document.version = version.to_string();
+
+		let document = DocumentMessageHandler::deserialize_document(&document.serialize_document()).expect("The earlier document version should be upgraded");
+		assert_eq!(document.version, crate::consts::GRAPHITE_DOCUMENT_VERSION);
+		for node_id in 0..old_nodes.len() as u64 {
+			let node = &document.document_legacy.document_network.nodes[&node_id];
+			assert_eq!(node.inputs, node_type.to_document_node_default_inputs([], Default::default()).inputs);
+			assert_eq!(node.implementation, node_type.generate_implementation());
+		}
+	}
+
+	#[test]
+	fn earlier_document_versions_are_upgraded() {
+		// Layer nodes saved before the mask and clip inputs were added, and before the effects input was added
+		assert_earlier_version_nodes_are_upgraded("0.0.18", "Layer", &[(8, "graphene_core::ConstructLayerNode<_, _, _, _, _, _, _, _>")]);
+		assert_earlier_version_nodes_are_upgraded("0.0.19", "Layer", &[(10, "graphene_core::ConstructLayerNode<_, _, _, _, _, _, _, _, _, _>")]);
+		// Text nodes saved before the typesetting inputs were added
+		assert_earlier_version_nodes_are_upgraded("0.0.20", "Text", &[(4, "graphene_core::text::TextGenerator<_, _, _>")]);
+		// Stroke nodes saved before the width profile inputs were added, and before the alignment input was added
+		assert_earlier_version_nodes_are_upgraded("0.0.21", "Stroke", &[(8, "graphene_core::vector::SetStrokeNode<_, _, _, _, _, _, _>")]);
+		assert_earlier_version_nodes_are_upgraded("0.0.22", "Stroke", &[(11, "graphene_core::vector::SetStrokeNode<_, _, _, _, _, _, _, _, _, _>")]);
+	}
 }
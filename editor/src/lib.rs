@@ -12,5 +12,8 @@ pub mod consts;
 pub mod dispatcher;
 pub mod messages;
 pub mod node_graph_executor;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod replay;
+pub mod scripting;
 pub mod test_utils;
 pub mod utility_traits;
@@ -1,14 +1,29 @@
-use super::utility_types::MessageLoggingVerbosity;
+use super::utility_types::{MessageLoggingVerbosity, MessageRecorder};
 use crate::messages::prelude::*;
 
 #[derive(Debug, Default)]
 pub struct DebugMessageHandler {
 	pub message_logging_verbosity: MessageLoggingVerbosity,
+	pub message_recorder: Option<MessageRecorder>,
 }
 
-impl MessageHandler<DebugMessage, ()> for DebugMessageHandler {
+impl DebugMessageHandler {
+	/// Adds a message entering the dispatcher to the active session recording, if there is one.
+	pub fn record_message(&mut self, message: &Message) {
+		if let Some(recorder) = &mut self.message_recorder {
+			recorder.record(message);
+		}
+	}
+}
+
+impl MessageHandler<DebugMessage, (&PortfolioMessageHandler, &InputPreprocessorMessageHandler, &PreferencesMessageHandler)> for DebugMessageHandler {
 	#[remain::check]
-	fn process_message(&mut self, message: DebugMessage, responses: &mut VecDeque<Message>, _data: ()) {
+	fn process_message(
+		&mut self,
+		message: DebugMessage,
+		responses: &mut VecDeque<Message>,
+		(portfolio, input, preferences): (&PortfolioMessageHandler, &InputPreprocessorMessageHandler, &PreferencesMessageHandler),
+	) {
 		match message {
 			DebugMessage::ToggleTraceLogs => {
 				if log::max_level() == log::LevelFilter::Debug {
@@ -35,6 +50,50 @@ impl MessageHandler<DebugMessage, ()> for DebugMessageHandler {
 			DebugMessage::MessageContents => {
 				self.message_logging_verbosity = MessageLoggingVerbosity::Contents;
 
+				// Refresh the checkmark beside the menu entry for this
+				responses.add(MenuBarMessage::SendLayout);
+			}
+			DebugMessage::StartRecording => {
+				// Begin with the messages needed to bring a freshly initialized editor into the current state
+				let mut preamble: Vec<Message> = vec![
+					PreferencesMessage::Load {
+						preferences: serde_json::to_string(preferences).expect("Failed to serialize preferences"),
+					}
+					.into(),
+					InputPreprocessorMessage::BoundsOfViewports {
+						bounds_of_viewports: vec![input.viewport_bounds.clone()],
+					}
+					.into(),
+				];
+				if let (Some(document_id), Some(document)) = (portfolio.active_document_id(), portfolio.active_document()) {
+					preamble.push(
+						PortfolioMessage::OpenDocumentFileWithId {
+							document_id,
+							document_name: document.name.clone(),
+							document_is_auto_saved: document.is_auto_saved(),
+							document_is_saved: document.is_saved(),
+							document_serialized_content: document.serialize_document(),
+						}
+						.into(),
+					);
+				}
+
+				self.message_recorder = Some(MessageRecorder::new(preamble));
+
+				// Refresh the checkmark beside the menu entry for this
+				responses.add(MenuBarMessage::SendLayout);
+			}
+			DebugMessage::StopRecording => {
+				let Some(recorder) = self.message_recorder.take() else {
+					warn!("Tried to stop recording the session, but no recording was in progress");
+					return;
+				};
+
+				responses.add(FrontendMessage::TriggerDownloadTextFile {
+					document: recorder.finish().to_json(),
+					name: "Session Recording.json".to_string(),
+				});
+
 				// Refresh the checkmark beside the menu entry for this
 				responses.add(MenuBarMessage::SendLayout);
 			}
@@ -46,5 +105,7 @@ impl MessageHandler<DebugMessage, ()> for DebugMessageHandler {
 		MessageOff,
 		MessageNames,
 		MessageContents,
+		StartRecording,
+		StopRecording,
 	);
 }
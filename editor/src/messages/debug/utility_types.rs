@@ -1,3 +1,8 @@
+use crate::messages::portfolio::utility_types::Platform;
+use crate::messages::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
 #[derive(Debug, Default, Clone, Copy)]
 pub enum MessageLoggingVerbosity {
 	#[default]
@@ -5,3 +10,88 @@ pub enum MessageLoggingVerbosity {
 	Names,
 	Contents,
 }
+
+/// A single message sent into the editor while a session was being recorded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedMessage {
+	/// Milliseconds since the recording was started.
+	pub elapsed_milliseconds: f64,
+	pub message: Message,
+}
+
+/// A sequence of messages that can be replayed into a fresh editor instance to reproduce a session.
+///
+/// The first messages of a recording restore the state the editor was in when recording started (preferences, viewport bounds, and the active document).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MessageRecording {
+	/// The platform the session was recorded on, which determines the keyboard layout used to interpret the recorded input.
+	#[serde(default)]
+	pub platform: Platform,
+	pub messages: Vec<RecordedMessage>,
+}
+
+impl MessageRecording {
+	pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+		serde_json::from_str(json)
+	}
+
+	pub fn to_json(&self) -> String {
+		serde_json::to_string_pretty(self).expect("Failed to serialize the message recording")
+	}
+}
+
+/// Captures every message entering the dispatcher from the outside, together with its timing.
+#[derive(Debug, Clone)]
+pub struct MessageRecorder {
+	start_time: f64,
+	recording: MessageRecording,
+}
+
+impl MessageRecorder {
+	pub fn new(preamble: impl IntoIterator<Item = Message>) -> Self {
+		let messages = preamble.into_iter().map(|message| RecordedMessage { elapsed_milliseconds: 0., message }).collect();
+
+		Self {
+			start_time: current_time_milliseconds(),
+			recording: MessageRecording {
+				platform: GLOBAL_PLATFORM.get().copied().unwrap_or_default(),
+				messages,
+			},
+		}
+	}
+
+	pub fn record(&mut self, message: &Message) {
+		// Controlling the recording itself isn't part of the session being recorded
+		if matches!(message, Message::Debug(DebugMessage::StartRecording | DebugMessage::StopRecording)) {
+			return;
+		}
+
+		self.recording.messages.push(RecordedMessage {
+			elapsed_milliseconds: current_time_milliseconds() - self.start_time,
+			message: message.clone(),
+		});
+	}
+
+	pub fn finish(self) -> MessageRecording {
+		self.recording
+	}
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+fn current_time_milliseconds() -> f64 {
+	js_sys::Date::now()
+}
+
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+fn current_time_milliseconds() -> f64 {
+	#[cfg(not(target_arch = "wasm32"))]
+	{
+		std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|duration| duration.as_secs_f64() * 1000.).unwrap_or_default()
+	}
+
+	// Without access to a clock, messages are recorded without timing information
+	#[cfg(target_arch = "wasm32")]
+	{
+		0.
+	}
+}
@@ -1,8 +1,12 @@
 use super::simple_dialogs::{self, AboutGraphiteDialog, ComingSoonDialog, DemoArtworkDialog, LicensesDialog};
 use crate::messages::layout::utility_types::widget_prelude::*;
+use crate::messages::portfolio::document::node_graph::resolve_document_node_type;
 use crate::messages::prelude::*;
 use crate::messages::tool::common_functionality::graph_modification_utils::is_artboard;
 
+use graph_craft::document::value::TaggedValue;
+use graph_craft::document::NodeInput;
+
 /// Stores the dialogs which require state. These are the ones that have their own message handlers, and are not the ones defined in `simple_dialogs`.
 #[derive(Debug, Default, Clone)]
 pub struct DialogMessageHandler {
@@ -85,10 +89,34 @@ impl MessageHandler<DialogMessage, (&PortfolioMessageHandler, &PreferencesMessag
 						})
 						.collect();
 
+					// Number inputs of the nodes in the network open in the node graph, which can be swept over a range of values
+					let mut sweep_inputs = document
+						.opened_network()
+						.into_iter()
+						.flat_map(|network| network.nodes.iter())
+						.flat_map(|(&node_id, node)| {
+							let node_type = resolve_document_node_type(&node.name);
+							node.inputs.iter().enumerate().filter_map(move |(input_index, input)| {
+								let NodeInput::Value {
+									tagged_value: TaggedValue::F64(_) | TaggedValue::AnimatedF64(_),
+									..
+								} = input
+								else {
+									return None;
+								};
+								let input_name = node_type.and_then(|node_type| node_type.inputs.get(input_index)).map_or("Input", |input| input.name);
+								Some((node_id, input_index, format!("{}: {input_name}", node.name)))
+							})
+						})
+						.collect::<Vec<_>>();
+					sweep_inputs.sort_by(|(_, _, a), (_, _, b)| a.cmp(b));
+
 					self.export_dialog = ExportDialogMessageHandler {
 						scale_factor: 1.,
 						artboards,
 						has_selection: document.selected_layers().next().is_some(),
+						sweep_inputs,
+						frame_count: document.timeline.frame_count(),
 						..Default::default()
 					};
 					self.export_dialog.send_dialog_to_frontend(responses);
@@ -1,4 +1,4 @@
-use crate::messages::frontend::utility_types::{ExportBounds, FileType};
+use crate::messages::frontend::utility_types::{BitDepth, ExportBounds, ExportFrames, FileType, SequenceFileType};
 use crate::messages::prelude::*;
 
 use serde::{Deserialize, Serialize};
@@ -6,10 +6,15 @@ use serde::{Deserialize, Serialize};
 #[impl_message(Message, DialogMessage, ExportDialog)]
 #[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum ExportDialogMessage {
+	BitDepth(BitDepth),
+	EmbedColorProfile(bool),
+	ExportLayers(bool),
 	FileType(FileType),
 	ScaleFactor(f64),
 	TransparentBackground(bool),
 	ExportBounds(ExportBounds),
+	Frames(ExportFrames),
+	SequenceFileType(SequenceFileType),
 
 	Submit,
 }
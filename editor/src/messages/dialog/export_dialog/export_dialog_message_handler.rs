@@ -1,8 +1,9 @@
-use crate::messages::frontend::utility_types::{ExportBounds, FileType};
+use crate::messages::frontend::utility_types::{BitDepth, ExportBounds, ExportFrames, FileType, SequenceFileType};
 use crate::messages::layout::utility_types::widget_prelude::*;
 use crate::messages::prelude::*;
 
 use document_legacy::document_metadata::LayerNodeIdentifier;
+use graph_craft::document::NodeId;
 
 /// A dialog to allow users to customize their file export.
 #[derive(Debug, Clone, Default)]
@@ -13,23 +14,57 @@ pub struct ExportDialogMessageHandler {
 	pub transparent_background: bool,
 	pub artboards: HashMap<LayerNodeIdentifier, String>,
 	pub has_selection: bool,
+	pub frames: ExportFrames,
+	pub sequence_file_type: SequenceFileType,
+	pub bit_depth: BitDepth,
+	pub embed_color_profile: bool,
+	/// Whether the image layers are written as the layers of a TIFF or OpenEXR file instead of being rendered together.
+	pub export_layers: bool,
+	/// The number inputs which can be swept over a range of values, with their display names
+	pub sweep_inputs: Vec<(NodeId, usize, String)>,
+	/// The number of frames in the document timeline
+	pub frame_count: u64,
 }
 
 impl MessageHandler<ExportDialogMessage, &PortfolioMessageHandler> for ExportDialogMessageHandler {
 	fn process_message(&mut self, message: ExportDialogMessage, responses: &mut VecDeque<Message>, portfolio: &PortfolioMessageHandler) {
 		match message {
+			ExportDialogMessage::BitDepth(bit_depth) => self.bit_depth = bit_depth,
+			ExportDialogMessage::EmbedColorProfile(embed_color_profile) => self.embed_color_profile = embed_color_profile,
+			ExportDialogMessage::ExportLayers(export_layers) => self.export_layers = export_layers,
 			ExportDialogMessage::FileType(export_type) => self.file_type = export_type,
 			ExportDialogMessage::ScaleFactor(factor) => self.scale_factor = factor,
 			ExportDialogMessage::TransparentBackground(transparent_background) => self.transparent_background = transparent_background,
 			ExportDialogMessage::ExportBounds(export_area) => self.bounds = export_area,
+			ExportDialogMessage::Frames(frames) => self.frames = frames,
+			ExportDialogMessage::SequenceFileType(sequence_file_type) => self.sequence_file_type = sequence_file_type,
 
-			ExportDialogMessage::Submit => responses.add_front(DocumentMessage::ExportDocument {
-				file_name: portfolio.active_document().map(|document| document.name.clone()).unwrap_or_default(),
-				file_type: self.file_type,
-				scale_factor: self.scale_factor,
-				bounds: self.bounds,
-				transparent_background: self.file_type != FileType::Jpg && self.transparent_background,
-			}),
+			ExportDialogMessage::Submit => {
+				let file_name = portfolio.active_document().map(|document| document.name.clone()).unwrap_or_default();
+				if self.frames == ExportFrames::Single {
+					responses.add_front(DocumentMessage::ExportDocument {
+						file_name,
+						file_type: self.file_type,
+						scale_factor: self.scale_factor,
+						bounds: self.bounds,
+						transparent_background: self.file_type != FileType::Jpg && self.transparent_background,
+						bit_depth: self.bit_depth,
+						embed_color_profile: self.embed_color_profile,
+						export_layers: self.export_layers && self.supports_layers(),
+					})
+				} else {
+					responses.add_front(DocumentMessage::ExportSequence {
+						file_name,
+						file_type: self.sequence_file_type,
+						frames: self.frames,
+						scale_factor: self.scale_factor,
+						bounds: self.bounds,
+						transparent_background: self.sequence_file_type != SequenceFileType::JpgSequence && self.transparent_background,
+						bit_depth: self.bit_depth,
+						embed_color_profile: self.embed_color_profile,
+					})
+				}
+			}
 		}
 
 		self.send_dialog_to_frontend(responses);
@@ -38,6 +73,13 @@ impl MessageHandler<ExportDialogMessage, &PortfolioMessageHandler> for ExportDia
 	advertise_actions! {ExportDialogUpdate;}
 }
 
+impl ExportDialogMessageHandler {
+	/// Whether the chosen file type can hold several layers, which is only the case for single image exports.
+	fn supports_layers(&self) -> bool {
+		self.frames == ExportFrames::Single && matches!(self.file_type, FileType::Tiff | FileType::Exr)
+	}
+}
+
 impl DialogLayoutHolder for ExportDialogMessageHandler {
 	const ICON: &'static str = "File";
 	const TITLE: &'static str = "Export";
@@ -62,17 +104,174 @@ impl DialogLayoutHolder for ExportDialogMessageHandler {
 
 impl LayoutHolder for ExportDialogMessageHandler {
 	fn layout(&self) -> Layout {
-		let entries = [(FileType::Png, "PNG"), (FileType::Jpg, "JPG"), (FileType::Svg, "SVG")]
+		let single = self.frames == ExportFrames::Single;
+
+		let last_frame = self.frame_count.saturating_sub(1);
+		let sweep_default = self.sweep_inputs.first().map(|&(node_id, input_index, _)| ExportFrames::InputSweep {
+			node_id,
+			input_index,
+			start: 0.,
+			end: 1.,
+			steps: 10,
+		});
+		let frames_options = [
+			(Some(ExportFrames::Single), "Single Image", false),
+			(Some(ExportFrames::AllArtboards), "All Artboards", self.artboards.is_empty()),
+			(Some(ExportFrames::FrameRange { first: 0, last: last_frame }), "Frame Range", false),
+			(sweep_default, "Input Sweep", sweep_default.is_none()),
+		];
+		let frames_index = match self.frames {
+			ExportFrames::Single => 0,
+			ExportFrames::AllArtboards => 1,
+			ExportFrames::FrameRange { .. } => 2,
+			ExportFrames::InputSweep { .. } => 3,
+		};
+		let entries = vec![frames_options
 			.into_iter()
-			.map(|(val, name)| RadioEntryData::new(name).on_update(move |_| ExportDialogMessage::FileType(val).into()))
-			.collect();
+			.map(|(frames, name, disabled)| {
+				DropdownEntryData::new(name)
+					.on_update(move |_| frames.map_or(Message::NoOp, |frames| ExportDialogMessage::Frames(frames).into()))
+					.disabled(disabled)
+			})
+			.collect()];
 
-		let export_type = vec![
-			TextLabel::new("File Type").table_align(true).min_width(100).widget_holder(),
+		let export_frames = vec![
+			TextLabel::new("Frames").table_align(true).min_width(100).widget_holder(),
 			Separator::new(SeparatorType::Unrelated).widget_holder(),
-			RadioInput::new(entries).selected_index(Some(self.file_type as u32)).widget_holder(),
+			DropdownInput::new(entries).selected_index(Some(frames_index)).widget_holder(),
 		];
 
+		let export_type = if single {
+			let entries = [
+				(FileType::Png, "PNG"),
+				(FileType::Jpg, "JPG"),
+				(FileType::Svg, "SVG"),
+				(FileType::Tiff, "TIFF"),
+				(FileType::Exr, "EXR"),
+				(FileType::Pdf, "PDF"),
+			]
+			.into_iter()
+			.map(|(val, name)| RadioEntryData::new(name).on_update(move |_| ExportDialogMessage::FileType(val).into()))
+			.collect();
+
+			vec![
+				TextLabel::new("File Type").table_align(true).min_width(100).widget_holder(),
+				Separator::new(SeparatorType::Unrelated).widget_holder(),
+				RadioInput::new(entries).selected_index(Some(self.file_type as u32)).widget_holder(),
+			]
+		} else {
+			let entries = [
+				(SequenceFileType::PngSequence, "PNGs"),
+				(SequenceFileType::JpgSequence, "JPGs"),
+				(SequenceFileType::Gif, "GIF"),
+				(SequenceFileType::Apng, "APNG"),
+				(SequenceFileType::WebP, "WebP"),
+				(SequenceFileType::Zip, "ZIP"),
+				(SequenceFileType::Tiff, "TIFF"),
+				(SequenceFileType::Exr, "EXR"),
+			]
+			.into_iter()
+			.map(|(val, name)| RadioEntryData::new(name).on_update(move |_| ExportDialogMessage::SequenceFileType(val).into()))
+			.collect();
+
+			vec![
+				TextLabel::new("File Type").table_align(true).min_width(100).widget_holder(),
+				Separator::new(SeparatorType::Unrelated).widget_holder(),
+				RadioInput::new(entries).selected_index(Some(self.sequence_file_type as u32)).widget_holder(),
+			]
+		};
+
+		let frame_options = match self.frames {
+			ExportFrames::FrameRange { first, last } => vec![LayoutGroup::Row {
+				widgets: vec![
+					TextLabel::new("Frame Range").table_align(true).min_width(100).widget_holder(),
+					Separator::new(SeparatorType::Unrelated).widget_holder(),
+					NumberInput::new(Some(first as f64))
+						.is_integer(true)
+						.min(0.)
+						.max(last_frame as f64)
+						.on_update(move |number_input: &NumberInput| {
+							let first = number_input.value.unwrap() as u64;
+							ExportDialogMessage::Frames(ExportFrames::FrameRange { first, last: last.max(first) }).into()
+						})
+						.min_width(96)
+						.widget_holder(),
+					Separator::new(SeparatorType::Related).widget_holder(),
+					NumberInput::new(Some(last as f64))
+						.is_integer(true)
+						.min(0.)
+						.max(last_frame as f64)
+						.on_update(move |number_input: &NumberInput| {
+							let last = number_input.value.unwrap() as u64;
+							ExportDialogMessage::Frames(ExportFrames::FrameRange { first: first.min(last), last }).into()
+						})
+						.min_width(96)
+						.widget_holder(),
+				],
+			}],
+			ExportFrames::InputSweep {
+				node_id,
+				input_index,
+				start,
+				end,
+				steps,
+			} => {
+				let sweep = |node_id: NodeId, input_index: usize, start: f64, end: f64, steps: u32| -> Message {
+					ExportDialogMessage::Frames(ExportFrames::InputSweep {
+						node_id,
+						input_index,
+						start,
+						end,
+						steps,
+					})
+					.into()
+				};
+
+				let input_index_in_list = self.sweep_inputs.iter().position(|&(id, index, _)| id == node_id && index == input_index);
+				let entries = vec![self
+					.sweep_inputs
+					.iter()
+					.map(|&(node_id, input_index, ref name)| DropdownEntryData::new(name).on_update(move |_| sweep(node_id, input_index, start, end, steps)))
+					.collect()];
+
+				vec![
+					LayoutGroup::Row {
+						widgets: vec![
+							TextLabel::new("Input").table_align(true).min_width(100).widget_holder(),
+							Separator::new(SeparatorType::Unrelated).widget_holder(),
+							DropdownInput::new(entries).selected_index(input_index_in_list.map(|index| index as u32)).widget_holder(),
+						],
+					},
+					LayoutGroup::Row {
+						widgets: vec![
+							TextLabel::new("Sweep").table_align(true).min_width(100).widget_holder(),
+							Separator::new(SeparatorType::Unrelated).widget_holder(),
+							NumberInput::new(Some(start))
+								.label("From")
+								.on_update(move |number_input: &NumberInput| sweep(node_id, input_index, number_input.value.unwrap(), end, steps))
+								.min_width(96)
+								.widget_holder(),
+							Separator::new(SeparatorType::Related).widget_holder(),
+							NumberInput::new(Some(end))
+								.label("To")
+								.on_update(move |number_input: &NumberInput| sweep(node_id, input_index, start, number_input.value.unwrap(), steps))
+								.min_width(96)
+								.widget_holder(),
+							Separator::new(SeparatorType::Related).widget_holder(),
+							NumberInput::new(Some(steps as f64))
+								.label("Steps")
+								.is_integer(true)
+								.min(1.)
+								.on_update(move |number_input: &NumberInput| sweep(node_id, input_index, start, end, number_input.value.unwrap() as u32))
+								.min_width(96)
+								.widget_holder(),
+						],
+					},
+				]
+			}
+			ExportFrames::Single | ExportFrames::AllArtboards => Vec::new(),
+		};
+
 		let resolution = vec![
 			TextLabel::new("Scale Factor").table_align(true).min_width(100).widget_holder(),
 			Separator::new(SeparatorType::Unrelated).widget_holder(),
@@ -80,7 +279,7 @@ impl LayoutHolder for ExportDialogMessageHandler {
 				.unit("")
 				.min(0.)
 				.max((1u64 << std::f64::MANTISSA_DIGITS) as f64)
-				.disabled(self.file_type == FileType::Svg)
+				.disabled(single && matches!(self.file_type, FileType::Svg | FileType::Pdf))
 				.on_update(|number_input: &NumberInput| ExportDialogMessage::ScaleFactor(number_input.value.unwrap()).into())
 				.min_width(200)
 				.widget_holder(),
@@ -98,26 +297,87 @@ impl LayoutHolder for ExportDialogMessageHandler {
 			.map(|(val, name, disabled)| DropdownEntryData::new(name).on_update(move |_| ExportDialogMessage::ExportBounds(val).into()).disabled(disabled))
 			.collect()];
 
+		// PDF files have a page for each artboard, or a single page fitting all of the artwork
+		let pages_are_bounds = single && self.file_type == FileType::Pdf;
 		let export_area = vec![
 			TextLabel::new("Bounds").table_align(true).min_width(100).widget_holder(),
 			Separator::new(SeparatorType::Unrelated).widget_holder(),
-			DropdownInput::new(entries).selected_index(Some(index as u32)).widget_holder(),
+			DropdownInput::new(entries)
+				.selected_index(Some(index as u32))
+				.disabled(self.frames == ExportFrames::AllArtboards || pages_are_bounds)
+				.widget_holder(),
+		];
+
+		let supports_bit_depth = match single {
+			true => matches!(self.file_type, FileType::Png | FileType::Tiff),
+			false => matches!(self.sequence_file_type, SequenceFileType::PngSequence | SequenceFileType::Zip | SequenceFileType::Tiff),
+		};
+		let sixteen_bit_tooltip = "Only an image layer exported on its own as the selection keeps more than 8 bits of precision, since everything else is rendered at 8 bits";
+		let bit_depth_entries = [(BitDepth::Eight, "8-bit", ""), (BitDepth::Sixteen, "16-bit", sixteen_bit_tooltip)]
+			.into_iter()
+			.map(|(val, name, tooltip)| RadioEntryData::new(name).tooltip(tooltip).on_update(move |_| ExportDialogMessage::BitDepth(val).into()))
+			.collect();
+		let bit_depth = vec![
+			TextLabel::new("Bit Depth").table_align(true).min_width(100).widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			RadioInput::new(bit_depth_entries)
+				.selected_index(Some(self.bit_depth as u32))
+				.disabled(!supports_bit_depth)
+				.widget_holder(),
+		];
+
+		let supports_color_profile = match single {
+			true => matches!(self.file_type, FileType::Png | FileType::Jpg),
+			false => matches!(
+				self.sequence_file_type,
+				SequenceFileType::PngSequence | SequenceFileType::JpgSequence | SequenceFileType::Apng | SequenceFileType::Zip
+			),
+		};
+		let color_profile = vec![
+			TextLabel::new("Color Profile").table_align(true).min_width(100).widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			CheckboxInput::new(self.embed_color_profile)
+				.tooltip("Embed an ICC profile describing the colors of the file. An image layer exported on its own keeps the document's working space, and everything else is rendered in sRGB.")
+				.disabled(!supports_color_profile)
+				.on_update(move |value: &CheckboxInput| ExportDialogMessage::EmbedColorProfile(value.checked).into())
+				.widget_holder(),
 		];
 
+		let layers = vec![
+			TextLabel::new("Layers").table_align(true).min_width(100).widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			CheckboxInput::new(self.export_layers)
+				.tooltip("Write each image layer as a layer of the file with its name, instead of rendering the artwork into a single image. Layers which aren't images are left out.")
+				.disabled(!self.supports_layers())
+				.on_update(move |value: &CheckboxInput| ExportDialogMessage::ExportLayers(value.checked).into())
+				.widget_holder(),
+		];
+
+		let supports_transparency = match single {
+			true => !matches!(self.file_type, FileType::Jpg | FileType::Pdf),
+			false => self.sequence_file_type != SequenceFileType::JpgSequence,
+		};
 		let transparent_background = vec![
 			TextLabel::new("Transparency").table_align(true).min_width(100).widget_holder(),
 			Separator::new(SeparatorType::Unrelated).widget_holder(),
 			CheckboxInput::new(self.transparent_background)
-				.disabled(self.file_type == FileType::Jpg)
+				.disabled(!supports_transparency)
 				.on_update(move |value: &CheckboxInput| ExportDialogMessage::TransparentBackground(value.checked).into())
 				.widget_holder(),
 		];
 
-		Layout::WidgetLayout(WidgetLayout::new(vec![
+		let mut layout = vec![LayoutGroup::Row { widgets: export_frames }];
+		layout.extend(frame_options);
+		layout.extend([
 			LayoutGroup::Row { widgets: export_type },
 			LayoutGroup::Row { widgets: resolution },
 			LayoutGroup::Row { widgets: export_area },
+			LayoutGroup::Row { widgets: bit_depth },
+			LayoutGroup::Row { widgets: color_profile },
+			LayoutGroup::Row { widgets: layers },
 			LayoutGroup::Row { widgets: transparent_background },
-		]))
+		]);
+
+		Layout::WidgetLayout(WidgetLayout::new(layout))
 	}
 }
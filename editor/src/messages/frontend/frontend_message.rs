@@ -8,7 +8,7 @@ use crate::messages::tool::utility_types::HintData;
 use document_legacy::LayerId;
 use graph_craft::document::NodeId;
 use graphene_core::raster::color::Color;
-use graphene_core::text::Font;
+use graphene_core::text::{Font, TextAlignment};
 
 use serde::{Deserialize, Serialize};
 
@@ -32,6 +32,11 @@ pub enum FrontendMessage {
 		line_width: Option<f64>,
 		#[serde(rename = "fontSize")]
 		font_size: f64,
+		#[serde(rename = "lineHeightRatio")]
+		line_height_ratio: f64,
+		#[serde(rename = "characterSpacing")]
+		character_spacing: f64,
+		align: TextAlignment,
 		color: Color,
 		url: String,
 		transform: [f64; 6],
@@ -50,6 +55,11 @@ pub enum FrontendMessage {
 		#[serde(rename = "blobUrl")]
 		blob_url: String,
 	},
+	TriggerDownloadBinaryFile {
+		name: String,
+		mime: String,
+		data: Vec<u8>,
+	},
 	TriggerDownloadBlobUrl {
 		#[serde(rename = "layerName")]
 		layer_name: String,
@@ -89,7 +99,9 @@ pub enum FrontendMessage {
 	},
 	TriggerLoadAutoSaveDocuments,
 	TriggerLoadPreferences,
+	TriggerLoadProofProfile,
 	TriggerOpenDocument,
+	TriggerOpenScript,
 	TriggerPaste,
 	TriggerRasterizeRegionBelowLayer {
 		#[serde(rename = "documentId")]
@@ -99,6 +111,12 @@ pub enum FrontendMessage {
 		svg: String,
 		size: glam::DVec2,
 	},
+	TriggerRasterizeSequenceFrame {
+		#[serde(rename = "frameIndex")]
+		frame_index: usize,
+		svg: String,
+		size: glam::DVec2,
+	},
 	TriggerRefreshBoundsOfViewports,
 	TriggerRevokeBlobUrl {
 		url: String,
@@ -47,6 +47,9 @@ pub enum FileType {
 	Png,
 	Jpg,
 	Svg,
+	Tiff,
+	Exr,
+	Pdf,
 }
 
 impl FileType {
@@ -55,10 +58,21 @@ impl FileType {
 			FileType::Png => "image/png",
 			FileType::Jpg => "image/jpeg",
 			FileType::Svg => "image/svg+xml",
+			FileType::Tiff => "image/tiff",
+			FileType::Exr => "image/x-exr",
+			FileType::Pdf => "application/pdf",
 		}
 	}
 }
 
+/// The number of bits per channel written by the raster exports which support more than one.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize, specta::Type)]
+pub enum BitDepth {
+	#[default]
+	Eight,
+	Sixteen,
+}
+
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize, specta::Type)]
 pub enum ExportBounds {
 	#[default]
@@ -66,3 +80,52 @@ pub enum ExportBounds {
 	Selection,
 	Artboard(LayerNodeIdentifier),
 }
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize, specta::Type)]
+pub enum SequenceFileType {
+	#[default]
+	PngSequence,
+	JpgSequence,
+	Gif,
+	Apng,
+	WebP,
+	Zip,
+	/// A single TIFF file with one page per frame.
+	Tiff,
+	/// A single multi-part OpenEXR file with one part per frame.
+	Exr,
+}
+
+impl SequenceFileType {
+	pub fn to_mime(self) -> &'static str {
+		match self {
+			SequenceFileType::PngSequence | SequenceFileType::Apng => "image/png",
+			SequenceFileType::JpgSequence => "image/jpeg",
+			SequenceFileType::Gif => "image/gif",
+			SequenceFileType::WebP => "image/webp",
+			SequenceFileType::Zip => "application/zip",
+			SequenceFileType::Tiff => "image/tiff",
+			SequenceFileType::Exr => "image/x-exr",
+		}
+	}
+}
+
+/// Which frames are rendered by an export.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, specta::Type)]
+pub enum ExportFrames {
+	/// A single image of the chosen bounds.
+	#[default]
+	Single,
+	/// One frame per artboard.
+	AllArtboards,
+	/// The frames of the document timeline from `first` to `last`, inclusive.
+	FrameRange { first: u64, last: u64 },
+	/// One frame per value of a number input, stepped evenly from `start` to `end`.
+	InputSweep {
+		node_id: graph_craft::document::NodeId,
+		input_index: usize,
+		start: f64,
+		end: f64,
+		steps: u32,
+	},
+}
@@ -6,6 +6,7 @@ use crate::messages::input_mapper::utility_types::macros::*;
 use crate::messages::input_mapper::utility_types::misc::MappingEntry;
 use crate::messages::input_mapper::utility_types::misc::{KeyMappingEntries, Mapping};
 use crate::messages::portfolio::document::utility_types::clipboards::Clipboard;
+use crate::messages::portfolio::document::utility_types::transformation::TypedUnit;
 use crate::messages::prelude::*;
 use crate::messages::tool::tool_messages::brush_tool::BrushToolMessageOptionsUpdate;
 
@@ -40,6 +41,26 @@ pub fn default_mapping() -> Mapping {
 		entry!(KeyDown(Lmb);  action_dispatch=NavigationMessage::TransformFromMenuEnd { commit_key: Key::Lmb }),
 		entry!(KeyDown(Mmb);  action_dispatch=NavigationMessage::TransformFromMenuEnd { commit_key: Key::Mmb }),
 		entry!(KeyDown(Rmb);  action_dispatch=NavigationMessage::TransformFromMenuEnd { commit_key: Key::Rmb }),
+		//
+		// GuidesMessage
+		entry!(PointerMove; action_dispatch=GuidesMessage::PointerMove),
+		entry!(KeyDown(Lmb); action_dispatch=GuidesMessage::DragStart),
+		// Guides dragged out of a ruler begin with the button press being reported once the drag has started
+		entry!(KeyDown(Lmb); action_dispatch=GuidesMessage::PointerMove),
+		entry!(KeyUp(Lmb); action_dispatch=GuidesMessage::DragStop),
+		entry!(KeyDown(Rmb); action_dispatch=GuidesMessage::Abort),
+		entry!(KeyDown(Escape); action_dispatch=GuidesMessage::Abort),
+		//
+		// PixelSelectionMessage
+		entry!(PointerMove; action_dispatch=PixelSelectionMessage::PointerMove),
+		entry!(KeyDown(Lmb); action_dispatch=PixelSelectionMessage::DragStart { add: Shift, subtract: Alt }),
+		entry!(KeyUp(Lmb); action_dispatch=PixelSelectionMessage::DragStop),
+		entry!(KeyDown(Enter); action_dispatch=PixelSelectionMessage::Confirm),
+		entry!(KeyDown(Rmb); action_dispatch=PixelSelectionMessage::Abort),
+		entry!(KeyDown(Escape); action_dispatch=PixelSelectionMessage::Abort),
+		entry!(KeyDown(KeyA); modifiers=[Accel], action_dispatch=PixelSelectionMessage::SelectAll),
+		entry!(KeyDown(KeyD); modifiers=[Accel], action_dispatch=PixelSelectionMessage::Clear),
+		entry!(KeyDown(KeyI); modifiers=[Accel, Shift], action_dispatch=PixelSelectionMessage::Invert),
 		// NORMAL PRIORITY:
 		//
 		// NodeGraphMessage
@@ -60,7 +81,20 @@ pub fn default_mapping() -> Mapping {
 		entry!(KeyDown(KeyX); action_dispatch=TransformLayerMessage::ConstrainX),
 		entry!(KeyDown(KeyY); action_dispatch=TransformLayerMessage::ConstrainY),
 		entry!(KeyDown(Backspace); action_dispatch=TransformLayerMessage::TypeBackspace),
-		entry!(KeyDown(Minus); action_dispatch=TransformLayerMessage::TypeNegate),
+		entry!(KeyDown(Minus); action_dispatch=TransformLayerMessage::TypeOperator { operator: '-' }),
+		entry!(KeyDown(Equal); modifiers=[Shift], action_dispatch=TransformLayerMessage::TypeOperator { operator: '+' }),
+		entry!(KeyDown(NumpadAdd); action_dispatch=TransformLayerMessage::TypeOperator { operator: '+' }),
+		entry!(KeyDown(Digit8); modifiers=[Shift], action_dispatch=TransformLayerMessage::TypeOperator { operator: '*' }),
+		entry!(KeyDown(NumpadMultiply); action_dispatch=TransformLayerMessage::TypeOperator { operator: '*' }),
+		entry!(KeyDown(Slash); action_dispatch=TransformLayerMessage::TypeOperator { operator: '/' }),
+		entry!(KeyDown(Digit9); modifiers=[Shift], action_dispatch=TransformLayerMessage::TypeOperator { operator: '(' }),
+		entry!(KeyDown(NumpadParenLeft); action_dispatch=TransformLayerMessage::TypeOperator { operator: '(' }),
+		entry!(KeyDown(Digit0); modifiers=[Shift], action_dispatch=TransformLayerMessage::TypeOperator { operator: ')' }),
+		entry!(KeyDown(NumpadParenRight); action_dispatch=TransformLayerMessage::TypeOperator { operator: ')' }),
+		entry!(KeyDown(Digit5); modifiers=[Shift], action_dispatch=TransformLayerMessage::TypeUnit { unit: TypedUnit::Percent }),
+		entry!(KeyDown(KeyP); action_dispatch=TransformLayerMessage::TypeUnit { unit: TypedUnit::Pixels }),
+		entry!(KeyDown(KeyD); action_dispatch=TransformLayerMessage::TypeUnit { unit: TypedUnit::Degrees }),
+		entry!(KeyDown(Tab); action_dispatch=TransformLayerMessage::CycleTypedField),
 		entry!(KeyDown(Comma); action_dispatch=TransformLayerMessage::TypeDecimalPoint),
 		entry!(KeyDown(Period); action_dispatch=TransformLayerMessage::TypeDecimalPoint),
 		entry!(PointerMove; refresh_keys=[Shift, Control], action_dispatch=TransformLayerMessage::PointerMove { slow_key: Shift, snap_key: Control }),
@@ -262,6 +296,8 @@ pub fn default_mapping() -> Mapping {
 		entry!(KeyDown(Delete); action_dispatch=DocumentMessage::DeleteSelectedLayers),
 		entry!(KeyDown(Backspace); action_dispatch=DocumentMessage::DeleteSelectedLayers),
 		entry!(KeyDown(KeyP); modifiers=[Alt], action_dispatch=DocumentMessage::DebugPrintDocument),
+		entry!(KeyDown(Comma); modifiers=[Alt], action_dispatch=DocumentMessage::StepAnimationFrame { forward: false }),
+		entry!(KeyDown(Period); modifiers=[Alt], action_dispatch=DocumentMessage::StepAnimationFrame { forward: true }),
 		entry!(KeyDown(KeyZ); modifiers=[Accel, Shift], action_dispatch=DocumentMessage::Redo),
 		entry!(KeyDown(KeyZ); modifiers=[Accel], action_dispatch=DocumentMessage::Undo),
 		entry!(KeyDown(KeyA); modifiers=[Accel, Shift], action_dispatch=DocumentMessage::DeselectAllLayers),
@@ -356,6 +392,8 @@ pub fn default_mapping() -> Mapping {
 		entry!(KeyDown(Digit0); modifiers=[Alt], action_dispatch=DebugMessage::MessageOff),
 		entry!(KeyDown(Digit1); modifiers=[Alt], action_dispatch=DebugMessage::MessageNames),
 		entry!(KeyDown(Digit2); modifiers=[Alt], action_dispatch=DebugMessage::MessageContents),
+		entry!(KeyDown(KeyR); modifiers=[Alt, Shift], action_dispatch=DebugMessage::StopRecording),
+		entry!(KeyDown(KeyR); modifiers=[Alt], action_dispatch=DebugMessage::StartRecording),
 	];
 	let (mut key_up, mut key_down, mut key_up_no_repeat, mut key_down_no_repeat, mut double_click, mut wheel_scroll, mut pointer_move) = mappings;
 
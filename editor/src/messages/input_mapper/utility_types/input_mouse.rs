@@ -65,6 +65,8 @@ pub struct MouseState {
 	pub position: ViewportPosition,
 	pub mouse_keys: MouseKeys,
 	pub scroll_delta: ScrollDelta,
+	/// The pressure of a pen from 0 to 1, or `None` for pointers which don't report it
+	pub pressure: Option<f64>,
 }
 
 impl MouseState {
@@ -77,6 +79,7 @@ impl MouseState {
 			position: (x, y).into(),
 			mouse_keys: MouseKeys::default(),
 			scroll_delta: ScrollDelta::default(),
+			pressure: None,
 		}
 	}
 
@@ -87,6 +90,7 @@ impl MouseState {
 			position,
 			mouse_keys,
 			scroll_delta: ScrollDelta::default(),
+			pressure: None,
 		}
 	}
 
@@ -103,6 +107,8 @@ pub struct EditorMouseState {
 	pub editor_position: EditorPosition,
 	pub mouse_keys: MouseKeys,
 	pub scroll_delta: ScrollDelta,
+	/// The pressure of a pen from 0 to 1, or `None` for pointers which don't report it
+	pub pressure: Option<f64>,
 }
 
 impl EditorMouseState {
@@ -115,6 +121,7 @@ impl EditorMouseState {
 			editor_position: (x, y).into(),
 			mouse_keys: MouseKeys::default(),
 			scroll_delta: ScrollDelta::default(),
+			pressure: None,
 		}
 	}
 
@@ -125,6 +132,7 @@ impl EditorMouseState {
 			editor_position,
 			mouse_keys,
 			scroll_delta: ScrollDelta::default(),
+			pressure: None,
 		}
 	}
 
@@ -133,6 +141,7 @@ impl EditorMouseState {
 			position: self.editor_position - active_viewport_bounds.top_left,
 			mouse_keys: self.mouse_keys,
 			scroll_delta: self.scroll_delta,
+			pressure: self.pressure,
 		}
 	}
 }
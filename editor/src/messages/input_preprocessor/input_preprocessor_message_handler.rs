@@ -36,6 +36,7 @@ impl MessageHandler<InputPreprocessorMessage, KeyboardPlatformLayout> for InputP
 
 				let mouse_state = editor_mouse_state.to_mouse_state(&self.viewport_bounds);
 				self.mouse.position = mouse_state.position;
+				self.mouse.pressure = mouse_state.pressure;
 
 				for key in mouse_state.mouse_keys {
 					responses.add(InputMapperMessage::DoubleClick(match key {
@@ -67,6 +68,7 @@ impl MessageHandler<InputPreprocessorMessage, KeyboardPlatformLayout> for InputP
 
 				let mouse_state = editor_mouse_state.to_mouse_state(&self.viewport_bounds);
 				self.mouse.position = mouse_state.position;
+				self.mouse.pressure = mouse_state.pressure;
 
 				self.translate_mouse_event(mouse_state, true, responses);
 			}
@@ -75,6 +77,7 @@ impl MessageHandler<InputPreprocessorMessage, KeyboardPlatformLayout> for InputP
 
 				let mouse_state = editor_mouse_state.to_mouse_state(&self.viewport_bounds);
 				self.mouse.position = mouse_state.position;
+				self.mouse.pressure = mouse_state.pressure;
 
 				responses.add(InputMapperMessage::PointerMove);
 
@@ -86,6 +89,7 @@ impl MessageHandler<InputPreprocessorMessage, KeyboardPlatformLayout> for InputP
 
 				let mouse_state = editor_mouse_state.to_mouse_state(&self.viewport_bounds);
 				self.mouse.position = mouse_state.position;
+				self.mouse.pressure = mouse_state.pressure;
 
 				self.translate_mouse_event(mouse_state, false, responses);
 			}
@@ -94,6 +98,7 @@ impl MessageHandler<InputPreprocessorMessage, KeyboardPlatformLayout> for InputP
 
 				let mouse_state = editor_mouse_state.to_mouse_state(&self.viewport_bounds);
 				self.mouse.position = mouse_state.position;
+				self.mouse.pressure = mouse_state.pressure;
 				self.mouse.scroll_delta = mouse_state.scroll_delta;
 
 				responses.add(InputMapperMessage::WheelScroll);
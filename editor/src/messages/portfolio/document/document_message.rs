@@ -1,7 +1,8 @@
-use crate::messages::frontend::utility_types::{ExportBounds, FileType};
+use crate::messages::frontend::utility_types::{BitDepth, ExportBounds, ExportFrames, FileType, SequenceFileType};
 use crate::messages::input_mapper::utility_types::input_keyboard::Key;
 use crate::messages::portfolio::document::utility_types::layer_panel::LayerMetadata;
-use crate::messages::portfolio::document::utility_types::misc::{AlignAggregate, AlignAxis, FlipAxis};
+use crate::messages::portfolio::document::utility_types::layered_images::LayeredImage;
+use crate::messages::portfolio::document::utility_types::misc::{AlignAggregate, AlignAxis, DocumentMode, FlipAxis, SnappingOptions};
 use crate::messages::prelude::*;
 
 use document_legacy::document::Document as DocumentLegacy;
@@ -9,9 +10,9 @@ use document_legacy::layers::blend_mode::BlendMode;
 use document_legacy::layers::style::ViewMode;
 use document_legacy::LayerId;
 use document_legacy::Operation as DocumentOperation;
+use graph_craft::document::value::TaggedValue;
 use graph_craft::document::NodeId;
-use graphene_core::raster::Image;
-use graphene_core::Color;
+use graphene_core::raster::color_management::WorkingSpace;
 use serde::{Deserialize, Serialize};
 
 #[remain::sorted]
@@ -36,6 +37,12 @@ pub enum DocumentMessage {
 	#[remain::unsorted]
 	#[child]
 	GraphOperation(GraphOperationMessage),
+	#[remain::unsorted]
+	#[child]
+	Guides(GuidesMessage),
+	#[remain::unsorted]
+	#[child]
+	PixelSelection(PixelSelectionMessage),
 
 	// Messages
 	AbortTransaction,
@@ -50,7 +57,9 @@ pub enum DocumentMessage {
 		document: DocumentLegacy,
 		layer_metadata: HashMap<Vec<LayerId>, LayerMetadata>,
 	},
+	CaptureSequenceFrame,
 	ClearLayerTree,
+	ClearProofProfile,
 	CommitTransaction,
 	CopyToClipboardLayerImageOutput {
 		layer_path: Vec<LayerId>,
@@ -79,7 +88,21 @@ pub enum DocumentMessage {
 		scale_factor: f64,
 		bounds: ExportBounds,
 		transparent_background: bool,
+		bit_depth: BitDepth,
+		embed_color_profile: bool,
+		export_layers: bool,
+	},
+	ExportSequence {
+		file_name: String,
+		file_type: SequenceFileType,
+		frames: ExportFrames,
+		scale_factor: f64,
+		bounds: ExportBounds,
+		transparent_background: bool,
+		bit_depth: BitDepth,
+		embed_color_profile: bool,
 	},
+	ExportSequenceStep,
 	FlipSelectedLayers {
 		flip_axis: FlipAxis,
 	},
@@ -105,6 +128,9 @@ pub enum DocumentMessage {
 	LayerChanged {
 		affected_layer_path: Vec<LayerId>,
 	},
+	LoadProofProfile {
+		data: Vec<u8>,
+	},
 	MoveSelectedLayersTo {
 		folder_path: Vec<LayerId>,
 		insert_index: isize,
@@ -117,7 +143,11 @@ pub enum DocumentMessage {
 		resize_opposite_corner: Key,
 	},
 	PasteImage {
-		image: Image<Color>,
+		image_frame: TaggedValue,
+		mouse: Option<(f64, f64)>,
+	},
+	PasteLayeredImage {
+		image: LayeredImage,
 		mouse: Option<(f64, f64)>,
 	},
 	Redo,
@@ -132,6 +162,10 @@ pub enum DocumentMessage {
 	RenderRulers,
 	RenderScrollbars,
 	RollbackTransaction,
+	RunScript {
+		script_name: String,
+		script: String,
+	},
 	SaveDocument,
 	SelectAllLayers,
 	SelectedLayersLower,
@@ -146,9 +180,25 @@ pub enum DocumentMessage {
 		ctrl: bool,
 		shift: bool,
 	},
+	SequenceFrameRasterized {
+		frame_index: usize,
+		width: u32,
+		height: u32,
+		rgba: Vec<u8>,
+	},
+	SetAnimationFrame {
+		frame: u64,
+	},
+	SetAnimationTimeline {
+		frame_rate: f64,
+		duration: f64,
+	},
 	SetBlendModeForSelectedLayers {
 		blend_mode: BlendMode,
 	},
+	SetDocumentMode {
+		document_mode: DocumentMode,
+	},
 	SetImageBlobUrl {
 		layer_path: Vec<LayerId>,
 		blob_url: String,
@@ -173,14 +223,22 @@ pub enum DocumentMessage {
 		replacement_selected_layers: Vec<Vec<LayerId>>,
 	},
 	SetSnapping {
-		snapping_enabled: Option<bool>,
-		bounding_box_snapping: Option<bool>,
-		node_snapping: Option<bool>,
+		snapping_enabled: bool,
+	},
+	SetSnappingOption {
+		option: SnappingOptions,
+		enabled: bool,
 	},
 	SetViewMode {
 		view_mode: ViewMode,
 	},
+	SetWorkingSpace {
+		working_space: WorkingSpace,
+	},
 	StartTransaction,
+	StepAnimationFrame {
+		forward: bool,
+	},
 	ToggleLayerExpansion {
 		layer_path: Vec<LayerId>,
 	},
@@ -3,17 +3,24 @@ use super::utility_types::misc::{DocumentRenderMode, SnappingOptions, SnappingSt
 use crate::application::generate_uuid;
 use crate::consts::{ASYMPTOTIC_EFFECT, DEFAULT_DOCUMENT_NAME, FILE_SAVE_SUFFIX, GRAPHITE_DOCUMENT_VERSION, SCALE_EFFECT, SCROLLBAR_SPACING, VIEWPORT_ZOOM_TO_FIT_PADDING_SCALE_FACTOR};
 use crate::messages::frontend::utility_types::ExportBounds;
-use crate::messages::frontend::utility_types::FileType;
+use crate::messages::frontend::utility_types::{BitDepth, ExportFrames, FileType, SequenceFileType};
 use crate::messages::input_mapper::utility_types::macros::action_keys;
 use crate::messages::layout::utility_types::widget_prelude::*;
+use crate::messages::portfolio::document::guides::GuidesMessageHandlerData;
 use crate::messages::portfolio::document::node_graph::NodeGraphHandlerData;
+use crate::messages::portfolio::document::node_graph::{is_upgradable_document_version, upgrade_network};
+use crate::messages::portfolio::document::pixel_selection::PixelSelectionMessageHandlerData;
 use crate::messages::portfolio::document::properties_panel::utility_types::PropertiesPanelMessageHandlerData;
 use crate::messages::portfolio::document::utility_types::clipboards::Clipboard;
+use crate::messages::portfolio::document::utility_types::image_files;
 use crate::messages::portfolio::document::utility_types::layer_panel::{LayerMetadata, LayerPanelEntry, RawBuffer};
+use crate::messages::portfolio::document::utility_types::layered_images::{self, ImageLayer, LayeredImage};
 use crate::messages::portfolio::document::utility_types::misc::{AlignAggregate, AlignAxis, DocumentMode, DocumentSave, FlipAxis};
+use crate::messages::portfolio::document::utility_types::sequence_export::{self, RasterFrame, SequenceExport, SequenceExportSetup, SequenceExportStep};
 use crate::messages::portfolio::document::utility_types::vectorize_layer_metadata;
 use crate::messages::portfolio::utility_types::PersistentData;
 use crate::messages::prelude::*;
+use crate::messages::tool::common_functionality::graph_modification_utils::{self, is_artboard};
 use crate::messages::tool::utility_types::ToolType;
 use crate::node_graph_executor::NodeGraphExecutor;
 
@@ -21,15 +28,18 @@ use document_legacy::document::Document as DocumentLegacy;
 use document_legacy::document_metadata::LayerNodeIdentifier;
 use document_legacy::layers::blend_mode::BlendMode;
 
-use document_legacy::layers::layer_info::{LayerDataType, LayerDataTypeDiscriminant};
+use document_legacy::layers::layer_info::{Layer, LayerDataType, LayerDataTypeDiscriminant};
 use document_legacy::layers::style::{RenderData, ViewMode};
 use document_legacy::{DocumentError, DocumentResponse, LayerId, Operation as DocumentOperation};
 use graph_craft::document::value::TaggedValue;
-use graph_craft::document::{NodeInput, NodeNetwork};
-use graphene_core::raster::ImageFrame;
+use graph_craft::document::{NodeId, NodeInput, NodeNetwork};
+use graphene_core::animation::Timeline;
+use graphene_core::raster::color_management::{IccProfile, WorkingSpace};
+use graphene_std::color_profile;
 
-use glam::{DAffine2, DVec2};
+use glam::{DAffine2, DVec2, IVec2, UVec2};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DocumentMessageHandler {
@@ -41,6 +51,14 @@ pub struct DocumentMessageHandler {
 
 	pub document_mode: DocumentMode,
 	pub view_mode: ViewMode,
+	#[serde(default)]
+	pub timeline: Timeline,
+	/// The color space which the linear values of the document's colors are in
+	#[serde(default)]
+	pub working_space: WorkingSpace,
+	/// The output device, such as a printer, whose colors are simulated when soft proofing
+	#[serde(default)]
+	pub proof_profile: Option<Arc<IccProfile>>,
 	#[serde(skip)]
 	pub snapping_state: SnappingState,
 	pub overlays_visible: bool,
@@ -58,11 +76,20 @@ pub struct DocumentMessageHandler {
 	layer_range_selection_reference: Vec<LayerId>,
 
 	navigation_handler: NavigationMessageHandler,
+	/// The guides and grid, which are saved with the document
+	#[serde(default)]
+	pub guides_handler: GuidesMessageHandler,
+	/// The pixel selection drawn in Select Mode, which is saved with the document
+	#[serde(default)]
+	pub pixel_selection_handler: PixelSelectionMessageHandler,
 	#[serde(skip)]
 	overlays_message_handler: OverlaysMessageHandler,
 	properties_panel_message_handler: PropertiesPanelMessageHandler,
 	#[serde(skip)]
 	node_graph_handler: NodeGraphMessageHandler,
+	/// The export of several frames which is currently in progress, if any
+	#[serde(skip)]
+	pub sequence_export: Option<SequenceExport>,
 }
 
 impl Default for DocumentMessageHandler {
@@ -80,6 +107,9 @@ impl Default for DocumentMessageHandler {
 
 			document_mode: DocumentMode::DesignMode,
 			view_mode: ViewMode::default(),
+			timeline: Timeline::default(),
+			working_space: WorkingSpace::default(),
+			proof_profile: None,
 			snapping_state: SnappingState::default(),
 			overlays_visible: true,
 
@@ -91,9 +121,12 @@ impl Default for DocumentMessageHandler {
 			layer_range_selection_reference: Vec::new(),
 
 			navigation_handler: NavigationMessageHandler::default(),
+			guides_handler: GuidesMessageHandler::default(),
+			pixel_selection_handler: PixelSelectionMessageHandler::default(),
 			overlays_message_handler: OverlaysMessageHandler::default(),
 			properties_panel_message_handler: PropertiesPanelMessageHandler::default(),
 			node_graph_handler: Default::default(),
+			sequence_export: None,
 		}
 	}
 }
@@ -177,6 +210,12 @@ impl MessageHandler<DocumentMessage, DocumentInputs<'_>> for DocumentMessageHand
 			}
 			#[remain::unsorted]
 			Overlays(message) => {
+				if matches!(message, OverlaysMessage::ClearAllOverlays) {
+					self.guides_handler.forget_overlays();
+					self.pixel_selection_handler.forget_overlays();
+					responses.add(GuidesMessage::RenderOverlays);
+					responses.add(PixelSelectionMessage::RenderOverlays);
+				}
 				self.overlays_message_handler.process_message(message, responses, (self.overlays_visible, persistent_data, ipp));
 			}
 			#[remain::unsorted]
@@ -187,6 +226,7 @@ impl MessageHandler<DocumentMessage, DocumentInputs<'_>> for DocumentMessageHand
 					selected_layers: &mut self.layer_metadata.iter().filter_map(|(path, data)| data.selected.then_some(path.as_slice())),
 					node_graph_message_handler: &self.node_graph_handler,
 					executor,
+					timeline: self.timeline,
 				};
 				self.properties_panel_message_handler
 					.process_message(message, responses, (persistent_data, properties_panel_message_handler_data));
@@ -201,11 +241,23 @@ impl MessageHandler<DocumentMessage, DocumentInputs<'_>> for DocumentMessageHand
 						document_id,
 						document_name: self.name.as_str(),
 						input: ipp,
+						timeline: self.timeline,
 					},
 				);
 			}
 			#[remain::unsorted]
 			GraphOperation(message) => GraphOperationMessageHandler.process_message(message, responses, (&mut self.document_legacy, &mut self.node_graph_handler)),
+			#[remain::unsorted]
+			Guides(message) => {
+				let document_to_viewport = self.metadata().document_to_viewport;
+				self.guides_handler.process_message(message, responses, GuidesMessageHandlerData { document_to_viewport, ipp });
+			}
+			#[remain::unsorted]
+			PixelSelection(message) => {
+				let document_to_viewport = self.metadata().document_to_viewport;
+				self.pixel_selection_handler
+					.process_message(message, responses, PixelSelectionMessageHandlerData { document_to_viewport, ipp });
+			}
 
 			// Messages
 			AbortTransaction => {
@@ -261,6 +313,25 @@ impl MessageHandler<DocumentMessage, DocumentInputs<'_>> for DocumentMessageHand
 				responses.add(BroadcastEvent::DocumentIsDirty);
 			}
 			BackupDocument { document, layer_metadata } => self.backup_with_document(document, layer_metadata, responses),
+			CaptureSequenceFrame => {
+				let Some(sequence_export) = &self.sequence_export else { return };
+				let frame_index = sequence_export.frames.len();
+				let Some(step) = sequence_export.steps.get(frame_index) else { return };
+				let (export_bounds, scale_factor, transparent_background) = (step.bounds, sequence_export.scale_factor, sequence_export.transparent_background);
+
+				let old_artwork_transform = self.remove_document_transform();
+
+				let bounds = self.export_bounds(export_bounds, &render_data);
+				let size = bounds[1] - bounds[0];
+				let transform = (DAffine2::from_translation(bounds[0]) * DAffine2::from_scale(size)).inverse();
+
+				let svg = self.render_document(size, transform, transparent_background, persistent_data, DocumentRenderMode::Root);
+
+				self.restore_document_transform(old_artwork_transform);
+
+				let size = (size * scale_factor).round().max(DVec2::ONE);
+				responses.add(FrontendMessage::TriggerRasterizeSequenceFrame { frame_index, svg, size });
+			}
 			ClearLayerTree => {
 				// Send an empty layer tree
 				let data_buffer: RawBuffer = Self::default().serialize_root().as_slice().into();
@@ -272,6 +343,14 @@ impl MessageHandler<DocumentMessage, DocumentInputs<'_>> for DocumentMessageHand
 					layout_target: LayoutTarget::LayerTreeOptions,
 				});
 			}
+			ClearProofProfile => {
+				self.proof_profile = None;
+				if self.view_mode == ViewMode::SoftProof {
+					self.view_mode = ViewMode::Normal;
+				}
+				responses.add(NodeGraphMessage::RunDocumentGraph);
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
 			CommitTransaction => (),
 			CopyToClipboardLayerImageOutput { layer_path } => {
 				let layer = self.document_legacy.layer(&layer_path).ok();
@@ -361,16 +440,31 @@ impl MessageHandler<DocumentMessage, DocumentInputs<'_>> for DocumentMessageHand
 				scale_factor,
 				bounds,
 				transparent_background,
+				bit_depth,
+				embed_color_profile,
+				export_layers,
 			} => {
-				let old_artwork_transform = self.remove_document_transform();
+				// PDF files are written from the artwork output by the document graph, with a page for each artboard
+				if file_type == FileType::Pdf {
+					responses.add(PortfolioMessage::SubmitGraphExport { document_id, file_name, file_type });
+					return;
+				}
+
+				if export_layers && matches!(file_type, FileType::Tiff | FileType::Exr) {
+					self.export_layered_image(&file_name, file_type, scale_factor, bounds, responses);
+					return;
+				}
 
-				// Calculate the bounding box of the region to be exported
-				let bounds = match bounds {
-					ExportBounds::AllArtwork => self.all_layer_bounds(&render_data),
-					ExportBounds::Selection => self.metadata().selected_visible_layers_bounding_box_viewport(),
-					ExportBounds::Artboard(id) => self.metadata().bounding_box_document(id),
+				// The browser only rasterizes to 8 bits, can't write TIFF or OpenEXR, and doesn't embed color profiles, so these exports are encoded by the editor instead
+				let embeds_profile = embed_color_profile && matches!(file_type, FileType::Png | FileType::Jpg);
+				if matches!(file_type, FileType::Tiff | FileType::Exr) || (file_type == FileType::Png && bit_depth == BitDepth::Sixteen) || embeds_profile {
+					self.export_encoded_image(file_name, file_type, scale_factor, bounds, transparent_background, bit_depth, embeds_profile, responses);
+					return;
 				}
-				.unwrap_or_default();
+
+				let old_artwork_transform = self.remove_document_transform();
+
+				let bounds = self.export_bounds(bounds, &render_data);
 				let size = bounds[1] - bounds[0];
 				let transform = (DAffine2::from_translation(bounds[0]) * DAffine2::from_scale(size)).inverse();
 
@@ -392,6 +486,98 @@ impl MessageHandler<DocumentMessage, DocumentInputs<'_>> for DocumentMessageHand
 					responses.add(FrontendMessage::TriggerDownloadRaster { svg: document, name, mime, size });
 				}
 			}
+			ExportSequence {
+				file_name,
+				file_type,
+				frames,
+				scale_factor,
+				bounds,
+				transparent_background,
+				bit_depth,
+				embed_color_profile,
+			} => {
+				let step = |bounds, setup| SequenceExportStep { bounds, setup };
+				let steps: Vec<_> = match frames {
+					ExportFrames::Single => vec![step(bounds, SequenceExportSetup::Unchanged)],
+					ExportFrames::AllArtboards => self
+						.metadata()
+						.all_layers()
+						.filter(|&layer| is_artboard(layer, &self.document_legacy))
+						.map(|artboard| step(ExportBounds::Artboard(artboard), SequenceExportSetup::Unchanged))
+						.collect(),
+					ExportFrames::FrameRange { first, last } => (first..=last.max(first))
+						.map(|frame| step(bounds, SequenceExportSetup::Time(self.timeline.time_of_frame(frame))))
+						.collect(),
+					ExportFrames::InputSweep { start, end, steps, .. } => (0..steps)
+						.map(|index| {
+							let factor = if steps > 1 { index as f64 / (steps - 1) as f64 } else { 0. };
+							step(bounds, SequenceExportSetup::InputValue(start + (end - start) * factor))
+						})
+						.collect(),
+				};
+				if steps.is_empty() {
+					responses.add(DialogMessage::DisplayDialogError {
+						title: "Nothing to export".to_string(),
+						description: "The chosen frames don't include anything to export.".to_string(),
+					});
+					return;
+				}
+
+				// The swept input is changed by node graph messages, so it's found in the network they edit
+				let swept_input = match frames {
+					ExportFrames::InputSweep { node_id, input_index, .. } => self
+						.opened_network()
+						.and_then(|network| network.nodes.get(&node_id))
+						.and_then(|node| node.inputs.get(input_index))
+						.map(|input| (node_id, input_index, input.clone())),
+					_ => None,
+				};
+
+				self.sequence_export = Some(SequenceExport {
+					file_name,
+					file_type,
+					scale_factor,
+					transparent_background,
+					bit_depth,
+					embed_color_profile,
+					frame_rate: self.timeline.frame_rate,
+					steps,
+					frames: Vec::new(),
+					awaiting_evaluation: false,
+					original_time: self.timeline.time,
+					swept_input,
+				});
+				responses.add(ExportSequenceStep);
+			}
+			ExportSequenceStep => {
+				let Some(sequence_export) = self.sequence_export.as_mut() else { return };
+				let Some(step) = sequence_export.steps.get(sequence_export.frames.len()) else {
+					self.finish_sequence_export(true, responses);
+					return;
+				};
+
+				match step.setup {
+					SequenceExportSetup::Unchanged => {
+						responses.add(CaptureSequenceFrame);
+						return;
+					}
+					SequenceExportSetup::Time(time) => self.timeline.time = time,
+					SequenceExportSetup::InputValue(value) => {
+						// The swept input is restored when the export finishes, so no undo step is recorded for these changes
+						if let Some(&(node_id, input_index, NodeInput::Value { exposed, .. })) = sequence_export.swept_input.as_ref() {
+							let input = NodeInput::Value {
+								tagged_value: TaggedValue::F64(value),
+								exposed,
+							};
+							responses.add(NodeGraphMessage::SetNodeInput { node_id, input_index, input });
+						}
+					}
+				}
+
+				// The frame is captured once the portfolio sees that the node graph has been evaluated with the new setup
+				sequence_export.awaiting_evaluation = true;
+				responses.add(NodeGraphMessage::RunDocumentGraph);
+			}
 			FlipSelectedLayers { flip_axis } => {
 				self.backup(responses);
 				let scale = match flip_axis {
@@ -496,6 +682,18 @@ impl MessageHandler<DocumentMessage, DocumentInputs<'_>> for DocumentMessageHand
 				responses.add(PropertiesPanelMessage::CheckSelectedWasUpdated { path: affected_layer_path });
 				self.update_layer_tree_options_bar_widgets(responses, &render_data);
 			}
+			LoadProofProfile { data } => match IccProfile::parse(data) {
+				Ok(profile) => {
+					self.proof_profile = Some(Arc::new(profile));
+					self.view_mode = ViewMode::SoftProof;
+					responses.add(NodeGraphMessage::RunDocumentGraph);
+					responses.add(PortfolioMessage::UpdateDocumentWidgets);
+				}
+				Err(description) => responses.add(DialogMessage::DisplayDialogError {
+					title: "Unable to load color profile".to_string(),
+					description,
+				}),
+			},
 			MoveSelectedLayersTo {
 				folder_path,
 				insert_index,
@@ -566,10 +764,12 @@ impl MessageHandler<DocumentMessage, DocumentInputs<'_>> for DocumentMessageHand
 				}
 				responses.add(BroadcastEvent::DocumentIsDirty);
 			}
-			PasteImage { image, mouse } => {
-				// All the image's pixels have been converted to 0..=1, linear, and premultiplied by `Color::from_rgba8_srgb`
-
-				let image_size = DVec2::new(image.width as f64, image.height as f64);
+			PasteImage { image_frame, mouse } => {
+				let Some(image_size) = image_files::image_frame_size(&image_frame) else {
+					warn!("Pasted an image without an image frame");
+					return;
+				};
+				let image_size = image_size.as_dvec2();
 
 				// Align the layer with the mouse or center of viewport
 				let viewport_location = mouse.map_or(ipp.viewport_bounds.center(), |pos| pos.into());
@@ -586,10 +786,7 @@ impl MessageHandler<DocumentMessage, DocumentInputs<'_>> for DocumentMessageHand
 
 				responses.add(DocumentMessage::StartTransaction);
 
-				let image_frame = ImageFrame { image, transform: DAffine2::IDENTITY };
-
 				let layer_path = self.get_path_for_new_layer();
-				use crate::messages::tool::common_functionality::graph_modification_utils;
 				graph_modification_utils::new_image_layer(image_frame, layer_path.clone(), responses);
 
 				responses.add(DocumentMessage::SetSelectedLayers {
@@ -608,6 +805,83 @@ impl MessageHandler<DocumentMessage, DocumentInputs<'_>> for DocumentMessageHand
 				// Force chosen tool to be Select Tool after importing image.
 				responses.add(ToolMessage::ActivateTool { tool_type: ToolType::Select });
 			}
+			PasteLayeredImage { image, mouse } => {
+				if image.layers.is_empty() {
+					warn!("Pasted a layered image without any layers");
+					return;
+				}
+				let canvas_size = image.size.max(UVec2::ONE).as_dvec2();
+
+				// Align the canvas with the mouse or center of viewport
+				let viewport_location = mouse.map_or(ipp.viewport_bounds.center(), |pos| pos.into());
+				let center_in_viewport = DAffine2::from_translation(viewport_location - ipp.viewport_bounds.top_left);
+
+				// Scale the canvas to fit into a 512x512 box, keeping the layers in their places on it
+				let scale = 1. / (canvas_size.max_element() / 512.).max(1.);
+				let canvas_top_left = canvas_size * scale / -2.;
+
+				responses.add(DocumentMessage::StartTransaction);
+
+				// Each new layer is placed above the previous one in its folder, so the layers are added from the bottom up, and each folder is added along with its bottom layer
+				let mut folder_ids: Vec<Option<NodeId>> = vec![None; image.groups.len()];
+				let mut root_layer_paths = Vec::new();
+				let mut image_layer_paths = Vec::with_capacity(image.layers.len());
+				for layer in image.layers {
+					let Some(layer_size) = image_files::image_frame_size(&layer.image_frame) else { continue };
+					let layer_size = layer_size.as_dvec2() * scale;
+					let fit_layer = DAffine2::from_scale_angle_translation(layer_size, 0., canvas_top_left + layer.offset.as_dvec2() * scale);
+
+					// The folders which the layer is in but which haven't been added yet, from the innermost one
+					let mut new_folders = Vec::new();
+					let mut group = layer.group;
+					while let Some(index) = group.filter(|&index| folder_ids[index].is_none()) {
+						new_folders.push(index);
+						group = image.groups[index].parent;
+					}
+					for index in new_folders.into_iter().rev() {
+						let folder = &image.groups[index];
+						let folder_path = self.get_path_for_new_layer();
+						let id = *folder_path.last().unwrap();
+						let parent = folder.parent.and_then(|parent| folder_ids[parent]);
+						responses.add(GraphOperationMessage::NewFolder { id, parent });
+						Self::set_layer_properties(id, folder.name.clone(), folder.blend_mode, folder.opacity, folder.visible, responses);
+						folder_ids[index] = Some(id);
+						if parent.is_none() {
+							root_layer_paths.push(folder_path);
+						}
+					}
+
+					let layer_path = self.get_path_for_new_layer();
+					let id = *layer_path.last().unwrap();
+					let parent = layer.group.and_then(|index| folder_ids[index]);
+					responses.add(GraphOperationMessage::NewBitmapLayer {
+						id,
+						parent,
+						image_frame: layer.image_frame,
+					});
+					Self::set_layer_properties(id, layer.name, layer.blend_mode, layer.opacity, layer.visible, responses);
+					responses.add(GraphOperationMessage::TransformSet {
+						layer: layer_path.clone(),
+						transform: center_in_viewport * fit_layer,
+						transform_in: TransformIn::Local,
+						skip_rerender: false,
+					});
+					if parent.is_none() {
+						root_layer_paths.push(layer_path.clone());
+					}
+					image_layer_paths.push(layer_path);
+				}
+
+				responses.add(DocumentMessage::SetSelectedLayers {
+					replacement_selected_layers: root_layer_paths,
+				});
+				for layer_path in image_layer_paths {
+					responses.add(DocumentMessage::InputFrameRasterizeRegionBelowLayer { layer_path });
+				}
+
+				// Force chosen tool to be Select Tool after importing image.
+				responses.add(ToolMessage::ActivateTool { tool_type: ToolType::Select });
+			}
 			Redo => {
 				responses.add(SelectToolMessage::Abort);
 				responses.add(DocumentHistoryForward);
@@ -639,6 +913,8 @@ impl MessageHandler<DocumentMessage, DocumentInputs<'_>> for DocumentMessageHand
 					spacing: ruler_spacing,
 					interval: ruler_interval,
 				});
+				responses.add(GuidesMessage::RenderOverlays);
+				responses.add(PixelSelectionMessage::RenderOverlays);
 			}
 			RenderScrollbars => {
 				let document_transform_scale = self.navigation_handler.snapped_scale();
@@ -665,6 +941,17 @@ impl MessageHandler<DocumentMessage, DocumentInputs<'_>> for DocumentMessageHand
 				self.rollback(responses).unwrap_or_else(|e| warn!("{e}"));
 				responses.extend([RenderDocument.into(), DocumentStructureChanged.into()]);
 			}
+			RunScript { script_name, script } => match crate::scripting::run_script(&script, self) {
+				Ok(script_messages) => {
+					responses.add(StartTransaction);
+					responses.extend(script_messages);
+					responses.add(CommitTransaction);
+				}
+				Err(error) => responses.add(DialogMessage::DisplayDialogError {
+					title: format!("Failed to run script \"{script_name}\""),
+					description: error,
+				}),
+			},
 			SaveDocument => {
 				self.set_save_state(true);
 				responses.add(PortfolioMessage::AutoSaveActiveDocument);
@@ -739,12 +1026,45 @@ impl MessageHandler<DocumentMessage, DocumentInputs<'_>> for DocumentMessageHand
 					}
 				}
 			}
+			SequenceFrameRasterized { frame_index, width, height, rgba } => {
+				let Some(sequence_export) = self.sequence_export.as_mut() else { return };
+				if frame_index != sequence_export.frames.len() {
+					warn!("Received an unexpected frame {frame_index} for the sequence export");
+					return;
+				}
+				// The frontend sends an empty frame if it failed to rasterize it
+				if width == 0 || height == 0 || rgba.len() != width as usize * height as usize * 4 {
+					self.finish_sequence_export(false, responses);
+					return;
+				}
+
+				sequence_export.frames.push(RasterFrame { width, height, rgba });
+				responses.add(ExportSequenceStep);
+			}
+			SetAnimationFrame { frame } => {
+				self.timeline.set_frame(frame);
+				responses.add(NodeGraphMessage::RunDocumentGraph);
+				responses.add(PropertiesPanelMessage::ResendActiveProperties);
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
+			SetAnimationTimeline { frame_rate, duration } => {
+				let frame = self.timeline.frame();
+				self.timeline.frame_rate = frame_rate.max(1.);
+				self.timeline.duration = duration.max(0.);
+				responses.add(SetAnimationFrame { frame });
+			}
 			SetBlendModeForSelectedLayers { blend_mode } => {
 				self.backup(responses);
 				for path in self.selected_layers() {
 					responses.add(DocumentOperation::SetLayerBlendMode { path: path.to_vec(), blend_mode });
 				}
 			}
+			SetDocumentMode { document_mode } => {
+				self.document_mode = document_mode;
+				responses.add(BroadcastEvent::ToolAbort);
+				responses.add(PixelSelectionMessage::Abort);
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
 			SetImageBlobUrl {
 				layer_path,
 				blob_url,
@@ -812,26 +1132,43 @@ impl MessageHandler<DocumentMessage, DocumentInputs<'_>> for DocumentMessageHand
 				let additional_layers = replacement_selected_layers;
 				responses.add_front(AddSelectedLayers { additional_layers });
 			}
-			SetSnapping {
-				snapping_enabled,
-				bounding_box_snapping,
-				node_snapping,
-			} => {
-				if let Some(state) = snapping_enabled {
-					self.snapping_state.snapping_enabled = state
-				};
-				if let Some(state) = bounding_box_snapping {
-					self.snapping_state.bounding_box_snapping = state
-				}
-				if let Some(state) = node_snapping {
-					self.snapping_state.node_snapping = state
-				};
+			SetSnapping { snapping_enabled } => {
+				self.snapping_state.snapping_enabled = snapping_enabled;
+			}
+			SetSnappingOption { option, enabled } => {
+				self.snapping_state.set_option(option, enabled);
+				self.update_document_widgets(responses);
 			}
 			SetViewMode { view_mode } => {
+				// Soft proofing needs a profile to simulate, so the user is asked for one first
+				if view_mode == ViewMode::SoftProof && self.proof_profile.is_none() {
+					responses.add(FrontendMessage::TriggerLoadProofProfile);
+					responses.add(PortfolioMessage::UpdateDocumentWidgets);
+					return;
+				}
+				let proofing_changed = (self.view_mode == ViewMode::SoftProof) != (view_mode == ViewMode::SoftProof);
 				self.view_mode = view_mode;
 				responses.add_front(DocumentMessage::DirtyRenderDocument);
+				if proofing_changed {
+					responses.add(NodeGraphMessage::RunDocumentGraph);
+				}
+			}
+			SetWorkingSpace { working_space } => {
+				self.working_space = working_space;
+				responses.add(NodeGraphMessage::RunDocumentGraph);
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
 			}
 			StartTransaction => self.backup(responses),
+			StepAnimationFrame { forward } => {
+				// Stepping past either end of the animation wraps around to the other end
+				let frame_count = self.timeline.frame_count();
+				let frame = if forward {
+					(self.timeline.frame() + 1) % frame_count
+				} else {
+					(self.timeline.frame() + frame_count - 1) % frame_count
+				};
+				responses.add(SetAnimationFrame { frame });
+			}
 			ToggleLayerExpansion { layer_path } => {
 				self.layer_metadata_mut(&layer_path).expanded ^= true;
 				responses.add(DocumentStructureChanged);
@@ -928,8 +1265,10 @@ impl DocumentMessageHandler {
 			DeselectAllLayers,
 			RenderDocument,
 			ExportDocument,
+			ExportSequence,
 			SaveDocument,
 			SetSnapping,
+			StepAnimationFrame,
 			DebugPrintDocument,
 			ZoomCanvasToFitAll,
 			ZoomCanvasTo100Percent,
@@ -952,6 +1291,8 @@ impl DocumentMessageHandler {
 			common.extend(select);
 		}
 		common.extend(self.navigation_handler.actions());
+		common.extend(self.guides_handler.actions_with_guide_mode(self.document_mode == DocumentMode::GuideMode));
+		common.extend(self.pixel_selection_handler.actions_with_select_mode(self.document_mode == DocumentMode::SelectMode));
 		common.extend(self.node_graph_handler.actions_with_node_graph_open(graph_open));
 		common
 	}
@@ -961,10 +1302,266 @@ impl DocumentMessageHandler {
 	pub fn network(&self) -> &NodeNetwork {
 		&self.document_legacy.document_network
 	}
+	/// The network open in the node graph, which is the one edited by node graph messages such as `NodeGraphMessage::SetInputValue`
+	pub fn opened_network(&self) -> Option<&NodeNetwork> {
+		self.network().nested_network(&self.node_graph_handler.network)
+	}
 	pub fn metadata(&self) -> &document_legacy::document_metadata::DocumentMetadata {
 		&self.document_legacy.metadata
 	}
 
+	/// Calculate the bounding box of the region to be exported
+	fn export_bounds(&self, bounds: ExportBounds, render_data: &RenderData) -> [DVec2; 2] {
+		match bounds {
+			ExportBounds::AllArtwork => self.all_layer_bounds(render_data),
+			ExportBounds::Selection => self.metadata().selected_visible_layers_bounding_box_viewport(),
+			ExportBounds::Artboard(id) => self.metadata().bounding_box_document(id),
+		}
+		.unwrap_or_default()
+	}
+
+	/// Restore the timeline and swept input changed by the sequence export, then encode and download its frames if all were rendered
+	fn finish_sequence_export(&mut self, completed: bool, responses: &mut VecDeque<Message>) {
+		let Some(sequence_export) = self.sequence_export.take() else { return };
+
+		self.timeline.time = sequence_export.original_time;
+		if let Some((node_id, input_index, input)) = sequence_export.swept_input {
+			responses.add(NodeGraphMessage::SetNodeInput { node_id, input_index, input });
+		}
+		if sequence_export.steps.iter().any(|step| step.setup != SequenceExportSetup::Unchanged) {
+			responses.add(NodeGraphMessage::RunDocumentGraph);
+		}
+
+		if !completed {
+			responses.add(DialogMessage::DisplayDialogError {
+				title: "Export failed".to_string(),
+				description: format!("Frame {} of the export couldn't be rendered.", sequence_export.frames.len() + 1),
+			});
+			return;
+		}
+
+		let file_stem = sequence_export.file_name.strip_suffix(FILE_SAVE_SUFFIX).unwrap_or(&sequence_export.file_name);
+		// The rendered frames are displayed in sRGB, whatever the working space of the document
+		let color_profile = sequence_export.embed_color_profile.then_some(WorkingSpace::Srgb);
+		match sequence_export::encode_sequence(
+			file_stem,
+			sequence_export.file_type,
+			&sequence_export.frames,
+			sequence_export.frame_rate,
+			sequence_export.bit_depth,
+			color_profile,
+		) {
+			Ok(files) => {
+				for file in files {
+					responses.add(FrontendMessage::TriggerDownloadBinaryFile {
+						name: file.name,
+						mime: file.mime,
+						data: file.data,
+					});
+				}
+			}
+			Err(description) => responses.add(DialogMessage::DisplayDialogError {
+				title: "Export failed".to_string(),
+				description,
+			}),
+		}
+	}
+
+	/// Exports a single image which is encoded by the editor rather than the browser.
+	///
+	/// An image layer exported on its own is written from its embedded pixels at their own resolution and bit depth, ignoring the scale factor, so 16 bit and HDR images keep all of their precision.
+	/// Anything else is rendered like a one frame sequence, which is rasterized at 8 bits and widened to the requested bit depth.
+	#[allow(clippy::too_many_arguments)]
+	fn export_encoded_image(
+		&self,
+		file_name: String,
+		file_type: FileType,
+		scale_factor: f64,
+		bounds: ExportBounds,
+		transparent_background: bool,
+		bit_depth: BitDepth,
+		embed_color_profile: bool,
+		responses: &mut VecDeque<Message>,
+	) {
+		let mut selected_layers = self.metadata().selected_layers();
+		let single_selected_layer = selected_layers.next().filter(|_| selected_layers.next().is_none());
+		let image_layer = single_selected_layer
+			.filter(|_| bounds == ExportBounds::Selection)
+			.and_then(|layer| Some((layer, graph_modification_utils::get_unmodified_image_frame(layer, &self.document_legacy)?)));
+		let encoded = image_layer.and_then(|(layer, image_frame)| match file_type {
+			// OpenEXR files store linear colors along with the chromaticities of the working space, so the pixels are written unchanged
+			FileType::Exr => {
+				let size = image_files::image_frame_size(image_frame)?;
+				let layers = vec![ImageLayer::new(self.layer_name(layer), image_frame.clone(), IVec2::ZERO)];
+				Some(layered_images::encode_exr(&LayeredImage { size, layers, groups: Vec::new() }, self.working_space))
+			}
+			_ => {
+				let image = image_files::image_frame_to_image(&image_files::export_colors(image_frame, self.working_space, embed_color_profile), bit_depth)?;
+				let encoded = match file_type {
+					FileType::Tiff => image_files::encode_tiff(&[image]),
+					FileType::Jpg => image_files::encode_jpg(&image),
+					_ => image_files::encode_png(&image),
+				};
+				// The pixels of an image layer are written in the working space, so they are described by its profile
+				let working_space = self.working_space;
+				Some(encoded.and_then(|data| match embed_color_profile {
+					true => color_profile::embed_profile(&data, &working_space.icc_profile(), working_space.name()),
+					false => Ok(data),
+				}))
+			}
+		});
+		if let Some(encoded) = encoded {
+			Self::download_encoded_file(&file_name, file_type, encoded, responses);
+			return;
+		}
+
+		let file_type = match file_type {
+			FileType::Tiff => SequenceFileType::Tiff,
+			FileType::Exr => SequenceFileType::Exr,
+			FileType::Jpg => SequenceFileType::JpgSequence,
+			_ => SequenceFileType::PngSequence,
+		};
+		responses.add(DocumentMessage::ExportSequence {
+			file_name,
+			file_type,
+			frames: ExportFrames::Single,
+			scale_factor,
+			bounds,
+			transparent_background,
+			bit_depth,
+			embed_color_profile,
+		});
+	}
+
+	/// Exports the image layers within the bounds as the layers of a TIFF or OpenEXR file, named like them and placed where they are in the document.
+	///
+	/// Each layer is resampled to the size it is displayed at times the scale factor, from the bounding box of its transform, so rotation and skew aren't kept.
+	/// Layers which aren't image layers would have to be rendered and are left out, and are named in a message shown along with the download.
+	fn export_layered_image(&self, file_name: &str, file_type: FileType, scale_factor: f64, bounds: ExportBounds, responses: &mut VecDeque<Message>) {
+		let metadata = self.metadata();
+		let layers: Vec<_> = match bounds {
+			ExportBounds::AllArtwork => metadata.all_layers().collect(),
+			ExportBounds::Selection => metadata.all_layers().filter(|&layer| metadata.selected_layers_contains(layer)).collect(),
+			ExportBounds::Artboard(artboard) => artboard.decendants(metadata).collect(),
+		};
+
+		// Groups and artboards are exported as the layers inside them
+		let (image_layers, skipped_layers): (Vec<_>, Vec<_>) = layers
+			.into_iter()
+			.filter(|&layer| !layer.has_children(metadata) && !graph_modification_utils::is_artboard(layer, &self.document_legacy))
+			.map(|layer| (layer, graph_modification_utils::get_unmodified_image_frame(layer, &self.document_legacy)))
+			.partition(|(_, image_frame)| image_frame.is_some());
+		let skipped_names: Vec<_> = skipped_layers
+			.into_iter()
+			.map(|(layer, _)| match self.layer_name(layer) {
+				name if name.is_empty() => "\"Untitled\"".to_string(),
+				name => format!("\"{name}\""),
+			})
+			.collect();
+
+		// The layers are listed from the top down, and layered files store them from the bottom up
+		let placed_layers: Vec<_> = image_layers
+			.into_iter()
+			.rev()
+			.filter_map(|(layer, image_frame)| {
+				let image_frame = image_frame?;
+				let [min, max] = metadata.bounding_box_document(layer)?;
+				let [min, max] = [(min * scale_factor).round().as_ivec2(), (max * scale_factor).round().as_ivec2()];
+				max.cmpgt(min).all().then(|| (self.layer_name(layer), image_frame, min, max))
+			})
+			.collect();
+		if placed_layers.is_empty() {
+			responses.add(DialogMessage::DisplayDialogError {
+				title: "Nothing to export".to_string(),
+				description: "Only image layers can be exported as the layers of a file, and there are none in the chosen bounds.".to_string(),
+			});
+			return;
+		}
+
+		let canvas_min = placed_layers.iter().map(|&(_, _, min, _)| min).reduce(IVec2::min).unwrap_or_default();
+		let canvas_max = placed_layers.iter().map(|&(_, _, _, max)| max).reduce(IVec2::max).unwrap_or_default();
+		let layers = placed_layers
+			.into_iter()
+			.filter_map(|(name, image_frame, min, max)| {
+				let image_frame = image_files::resize_image_frame(image_frame, (max - min).as_uvec2())?;
+				// TIFF files are gamma encoded with the sRGB transfer function, so their colors are converted to sRGB
+				let image_frame = match file_type {
+					FileType::Tiff => image_files::export_colors(&image_frame, self.working_space, false).into_owned(),
+					_ => image_frame,
+				};
+				let offset = min - canvas_min;
+				Some(ImageLayer::new(name, image_frame, offset))
+			})
+			.collect();
+		let image = LayeredImage {
+			size: (canvas_max - canvas_min).as_uvec2(),
+			layers,
+			groups: Vec::new(),
+		};
+
+		let encoded = match file_type {
+			FileType::Exr => layered_images::encode_exr(&image, self.working_space),
+			_ => layered_images::encode_layered_tiff(&image),
+		};
+		let exported = encoded.is_ok();
+		Self::download_encoded_file(file_name, file_type, encoded, responses);
+		if exported && !skipped_names.is_empty() {
+			responses.add(DialogMessage::DisplayDialogError {
+				title: "Some layers weren't exported".to_string(),
+				description: format!(
+					"Only image layers can be exported as the layers of a file, so these layers were left out: {}.",
+					skipped_names.join(", ")
+				),
+			});
+		}
+	}
+
+	/// Downloads a file encoded by the editor, or shows why it couldn't be encoded.
+	pub(crate) fn download_encoded_file(file_name: &str, file_type: FileType, encoded: Result<Vec<u8>, String>, responses: &mut VecDeque<Message>) {
+		let file_stem = file_name.strip_suffix(FILE_SAVE_SUFFIX).unwrap_or(file_name);
+		let extension = format!("{file_type:?}").to_lowercase();
+		match encoded {
+			Ok(data) => responses.add(FrontendMessage::TriggerDownloadBinaryFile {
+				name: format!("{file_stem}.{extension}"),
+				mime: file_type.to_mime().to_string(),
+				data,
+			}),
+			Err(description) => responses.add(DialogMessage::DisplayDialogError {
+				title: "Export failed".to_string(),
+				description,
+			}),
+		}
+	}
+
+	/// The name of a layer, as set in the Name input of its layer node.
+	pub fn layer_name(&self, layer: LayerNodeIdentifier) -> String {
+		let name_input = self.network().nodes.get(&layer.to_node()).and_then(|node| node.inputs.get(1));
+		match name_input {
+			Some(NodeInput::Value {
+				tagged_value: TaggedValue::String(name),
+				..
+			}) => name.clone(),
+			_ => String::new(),
+		}
+	}
+
+	/// Sets the name, blend mode, opacity from 0 to 1, and visibility inputs of a layer node.
+	fn set_layer_properties(layer_id: NodeId, name: String, blend_mode: graphene_core::raster::BlendMode, opacity: f64, visible: bool, responses: &mut VecDeque<Message>) {
+		let inputs = [
+			TaggedValue::String(name),
+			TaggedValue::BlendMode(blend_mode),
+			TaggedValue::F32(opacity as f32 * 100.),
+			TaggedValue::Bool(visible),
+		];
+		for (input_index, value) in (1..).zip(inputs) {
+			responses.add(NodeGraphMessage::SetNodeInput {
+				node_id: layer_id,
+				input_index,
+				input: NodeInput::value(value, false),
+			});
+		}
+	}
+
 	/// Remove the artwork and artboard pan/tilt/zoom to render it without the user's viewport navigation, and save it to be restored at the end
 	pub(crate) fn remove_document_transform(&mut self) -> DAffine2 {
 		let old_artwork_transform = self.metadata().document_to_viewport;
@@ -1017,9 +1614,13 @@ impl DocumentMessageHandler {
 	pub fn deserialize_document(serialized_content: &str) -> Result<Self, DocumentError> {
 		let deserialized_result: Result<Self, DocumentError> = serde_json::from_str(serialized_content).map_err(|e| DocumentError::InvalidFile(e.to_string()));
 		match deserialized_result {
-			Ok(document) => {
+			Ok(mut document) => {
 				if document.version == GRAPHITE_DOCUMENT_VERSION {
 					Ok(document)
+				} else if is_upgradable_document_version(&document.version) {
+					document.upgrade_networks();
+					document.version = GRAPHITE_DOCUMENT_VERSION.to_string();
+					Ok(document)
 				} else {
 					Err(DocumentError::InvalidFile("Graphite document version mismatch".to_string()))
 				}
@@ -1028,6 +1629,20 @@ impl DocumentMessageHandler {
 		}
 	}
 
+	/// Upgrades the node networks of a document saved with an earlier document version.
+	fn upgrade_networks(&mut self) {
+		fn upgrade_layer(layer: &mut Layer, version: &str) {
+			match &mut layer.data {
+				LayerDataType::Folder(folder) => folder.layers.iter_mut().for_each(|layer| upgrade_layer(layer, version)),
+				LayerDataType::Layer(layer) => upgrade_network(&mut layer.network, version),
+				LayerDataType::Shape(_) => {}
+			}
+		}
+
+		upgrade_layer(&mut self.document_legacy.root, &self.version);
+		upgrade_network(&mut self.document_legacy.document_network, &self.version);
+	}
+
 	pub fn with_name(name: String, ipp: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) -> Self {
 		let mut document = Self { name, ..Self::default() };
 		let transform = document.navigation_handler.calculate_offset_transform(ipp.viewport_bounds.size() / 2.);
@@ -1458,65 +2073,38 @@ impl DocumentMessageHandler {
 	}
 
 	pub fn update_document_widgets(&self, responses: &mut VecDeque<Message>) {
-		let snapping_state = self.snapping_state.clone();
+		let snapping_options = SnappingOptions::ALL
+			.into_iter()
+			.map(|option| LayoutGroup::Row {
+				widgets: vec![
+					CheckboxInput::new(self.snapping_state.option_enabled(option))
+						.tooltip(option.to_string())
+						.on_update(move |input: &CheckboxInput| DocumentMessage::SetSnappingOption { option, enabled: input.checked }.into())
+						.widget_holder(),
+					Separator::new(SeparatorType::Unrelated).widget_holder(),
+					TextLabel::new(option.to_string()).table_align(false).min_width(60).widget_holder(),
+				],
+			})
+			.collect();
 		let mut widgets = vec![
-			OptionalInput::new(snapping_state.snapping_enabled, "Snapping")
+			OptionalInput::new(self.snapping_state.snapping_enabled, "Snapping")
 				.tooltip("Snapping")
-				.on_update(move |optional_input: &OptionalInput| {
-					let snapping_enabled = optional_input.checked;
+				.on_update(|optional_input: &OptionalInput| {
 					DocumentMessage::SetSnapping {
-						snapping_enabled: Some(snapping_enabled),
-						bounding_box_snapping: Some(snapping_state.bounding_box_snapping),
-						node_snapping: Some(snapping_state.node_snapping),
+						snapping_enabled: optional_input.checked,
 					}
 					.into()
 				})
 				.widget_holder(),
-			PopoverButton::new("Snapping", "Snap customization settings")
-				.options_widget(vec![
-					LayoutGroup::Row {
-						widgets: vec![
-							CheckboxInput::new(snapping_state.bounding_box_snapping)
-								.tooltip(SnappingOptions::BoundingBoxes.to_string())
-								.on_update(move |input: &CheckboxInput| {
-									DocumentMessage::SetSnapping {
-										snapping_enabled: None,
-										bounding_box_snapping: Some(input.checked),
-										node_snapping: None,
-									}
-									.into()
-								})
-								.widget_holder(),
-							Separator::new(SeparatorType::Unrelated).widget_holder(),
-							TextLabel::new(SnappingOptions::BoundingBoxes.to_string()).table_align(false).min_width(60).widget_holder(),
-							Separator::new(SeparatorType::Related).widget_holder(),
-						],
-					},
-					LayoutGroup::Row {
-						widgets: vec![
-							CheckboxInput::new(self.snapping_state.node_snapping)
-								.tooltip(SnappingOptions::Points.to_string())
-								.on_update(|input: &CheckboxInput| {
-									DocumentMessage::SetSnapping {
-										snapping_enabled: None,
-										bounding_box_snapping: None,
-										node_snapping: Some(input.checked),
-									}
-									.into()
-								})
-								.widget_holder(),
-							Separator::new(SeparatorType::Unrelated).widget_holder(),
-							TextLabel::new(SnappingOptions::Points.to_string()).table_align(false).min_width(60).widget_holder(),
-						],
-					},
-				])
-				.widget_holder(),
+			PopoverButton::new("Snapping", "Snap customization settings").options_widget(snapping_options).widget_holder(),
 			Separator::new(SeparatorType::Unrelated).widget_holder(),
-			OptionalInput::new(true, "Grid")
+			OptionalInput::new(self.guides_handler.grid_visible, "Grid")
 				.tooltip("Grid")
-				.on_update(|_| DialogMessage::RequestComingSoonDialog { issue: Some(318) }.into())
+				.on_update(|optional_input: &OptionalInput| GuidesMessage::SetGridVisible { visible: optional_input.checked }.into())
+				.widget_holder(),
+			PopoverButton::new("Grid", "Grid customization settings")
+				.options_widget(self.guides_handler.grid_layout())
 				.widget_holder(),
-			PopoverButton::new("Grid", "Coming soon").widget_holder(),
 			Separator::new(SeparatorType::Unrelated).widget_holder(),
 			OptionalInput::new(self.overlays_visible, "Overlays")
 				.tooltip("Overlays")
@@ -1540,13 +2128,113 @@ impl DocumentMessageHandler {
 					.icon("ViewModePixels")
 					.tooltip("View Mode: Pixels")
 					.on_update(|_| DialogMessage::RequestComingSoonDialog { issue: Some(320) }.into()),
+				RadioEntryData::default()
+					.value("soft-proof")
+					.icon("ViewModeSoftProof")
+					.tooltip("View Mode: Soft Proof")
+					.on_update(|_| DocumentMessage::SetViewMode { view_mode: ViewMode::SoftProof }.into()),
 			])
 			.selected_index(match self.view_mode {
 				ViewMode::Normal => Some(0),
+				ViewMode::SoftProof => Some(3),
 				_ => Some(1),
 			})
 			.widget_holder(),
-			PopoverButton::new("View Mode", "Coming soon").widget_holder(),
+			PopoverButton::new("View Mode", "Color management settings")
+				.options_widget(vec![
+					LayoutGroup::Row {
+						widgets: vec![
+							TextLabel::new("Working Space").table_align(true).widget_holder(),
+							Separator::new(SeparatorType::Unrelated).widget_holder(),
+							DropdownInput::new(vec![WorkingSpace::ALL
+								.into_iter()
+								.map(|working_space| DropdownEntryData::new(working_space.name()).on_update(move |_| DocumentMessage::SetWorkingSpace { working_space }.into()))
+								.collect()])
+							.selected_index(WorkingSpace::ALL.iter().position(|&working_space| working_space == self.working_space).map(|index| index as u32))
+							.tooltip("The color space which the document's colors are in. Changing it keeps the color values, so colors appear differently.")
+							.widget_holder(),
+						],
+					},
+					LayoutGroup::Row {
+						widgets: vec![
+							TextLabel::new("Proof Profile").table_align(true).widget_holder(),
+							Separator::new(SeparatorType::Unrelated).widget_holder(),
+							TextLabel::new(self.proof_profile.as_ref().map_or("None", |profile| profile.description())).widget_holder(),
+						],
+					},
+					LayoutGroup::Row {
+						widgets: vec![
+							TextButton::new("Load Profile")
+								.tooltip("Load an ICC profile of the output device, such as a printer, whose colors are simulated in the Soft Proof view mode")
+								.on_update(|_| FrontendMessage::TriggerLoadProofProfile.into())
+								.widget_holder(),
+							Separator::new(SeparatorType::Related).widget_holder(),
+							TextButton::new("Clear")
+								.disabled(self.proof_profile.is_none())
+								.on_update(|_| DocumentMessage::ClearProofProfile.into())
+								.widget_holder(),
+						],
+					},
+				])
+				.widget_holder(),
+			Separator::new(SeparatorType::Section).widget_holder(),
+			NumberInput::new(Some(self.timeline.frame() as f64))
+				.label("Frame")
+				.tooltip("Frame of the animation shown in the viewport")
+				.is_integer(true)
+				.min(0.)
+				.max((self.timeline.frame_count() - 1) as f64)
+				.on_update(|number_input: &NumberInput| {
+					DocumentMessage::SetAnimationFrame {
+						frame: number_input.value.unwrap() as u64,
+					}
+					.into()
+				})
+				.widget_holder(),
+			PopoverButton::new("Timeline", "Animation playback settings")
+				.options_widget(vec![
+					LayoutGroup::Row {
+						widgets: vec![
+							TextLabel::new("Frame Rate").table_align(true).widget_holder(),
+							Separator::new(SeparatorType::Unrelated).widget_holder(),
+							NumberInput::new(Some(self.timeline.frame_rate))
+								.unit(" fps")
+								.min(1.)
+								.on_update({
+									let duration = self.timeline.duration;
+									move |number_input: &NumberInput| {
+										DocumentMessage::SetAnimationTimeline {
+											frame_rate: number_input.value.unwrap(),
+											duration,
+										}
+										.into()
+									}
+								})
+								.widget_holder(),
+						],
+					},
+					LayoutGroup::Row {
+						widgets: vec![
+							TextLabel::new("Duration").table_align(true).widget_holder(),
+							Separator::new(SeparatorType::Unrelated).widget_holder(),
+							NumberInput::new(Some(self.timeline.duration))
+								.unit(" s")
+								.min(0.)
+								.on_update({
+									let frame_rate = self.timeline.frame_rate;
+									move |number_input: &NumberInput| {
+										DocumentMessage::SetAnimationTimeline {
+											frame_rate,
+											duration: number_input.value.unwrap(),
+										}
+										.into()
+									}
+								})
+								.widget_holder(),
+						],
+					},
+				])
+				.widget_holder(),
 			Separator::new(SeparatorType::Section).widget_holder(),
 			IconButton::new("ZoomIn", 24)
 				.tooltip("Zoom In")
@@ -1606,25 +2294,45 @@ impl DocumentMessageHandler {
 		]);
 		let document_bar_layout = WidgetLayout::new(vec![LayoutGroup::Row { widgets }]);
 
-		let document_mode_layout = WidgetLayout::new(vec![LayoutGroup::Row {
-			widgets: vec![
-				DropdownInput::new(
-					vec![vec![
-						DropdownEntryData::new(DocumentMode::DesignMode.to_string()).icon(DocumentMode::DesignMode.icon_name()),
-						DropdownEntryData::new(DocumentMode::SelectMode.to_string())
-							.icon(DocumentMode::SelectMode.icon_name())
-							.on_update(|_| DialogMessage::RequestComingSoonDialog { issue: Some(330) }.into()),
-						DropdownEntryData::new(DocumentMode::GuideMode.to_string())
-							.icon(DocumentMode::GuideMode.icon_name())
-							.on_update(|_| DialogMessage::RequestComingSoonDialog { issue: Some(331) }.into()),
-					]])
-					.selected_index(Some(self.document_mode as u32))
-					.draw_icon( true)
-					.interactive( false) // TODO: set to true when dialogs are not spawned
+		let mut document_mode_widgets = vec![
+			DropdownInput::new(vec![vec![
+				DropdownEntryData::new(DocumentMode::DesignMode.to_string()).icon(DocumentMode::DesignMode.icon_name()).on_update(|_| {
+					DocumentMessage::SetDocumentMode {
+						document_mode: DocumentMode::DesignMode,
+					}
+					.into()
+				}),
+				DropdownEntryData::new(DocumentMode::SelectMode.to_string()).icon(DocumentMode::SelectMode.icon_name()).on_update(|_| {
+					DocumentMessage::SetDocumentMode {
+						document_mode: DocumentMode::SelectMode,
+					}
+					.into()
+				}),
+				DropdownEntryData::new(DocumentMode::GuideMode.to_string()).icon(DocumentMode::GuideMode.icon_name()).on_update(|_| {
+					DocumentMessage::SetDocumentMode {
+						document_mode: DocumentMode::GuideMode,
+					}
+					.into()
+				}),
+			]])
+			.selected_index(Some(self.document_mode as u32))
+			.draw_icon(true)
+			.interactive(true)
+			.widget_holder(),
+			Separator::new(SeparatorType::Section).widget_holder(),
+		];
+		if self.document_mode == DocumentMode::GuideMode {
+			document_mode_widgets.extend([
+				PopoverButton::new("Guides", "Drag guides out of the rulers, or position them here")
+					.options_widget(self.guides_handler.guides_layout())
 					.widget_holder(),
 				Separator::new(SeparatorType::Section).widget_holder(),
-			],
-		}]);
+			]);
+		}
+		if self.document_mode == DocumentMode::SelectMode {
+			document_mode_widgets.extend(self.pixel_selection_handler.document_bar_widgets());
+		}
+		let document_mode_layout = WidgetLayout::new(vec![LayoutGroup::Row { widgets: document_mode_widgets }]);
 
 		responses.add(LayoutMessage::SendLayout {
 			layout: Layout::WidgetLayout(document_bar_layout),
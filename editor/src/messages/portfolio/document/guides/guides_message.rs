@@ -0,0 +1,25 @@
+use crate::messages::portfolio::document::utility_types::guides::{GridSettings, GuideDirection};
+use crate::messages::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+#[remain::sorted]
+#[impl_message(Message, DocumentMessage, Guides)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum GuidesMessage {
+	// Messages
+	Abort,
+	AddGuide { direction: GuideDirection },
+	ClearGuides,
+	DeleteGuide { id: u64 },
+	DragFromRuler { direction: GuideDirection },
+	DragStart,
+	DragStop,
+	PointerMove,
+	RenderOverlays,
+	SetGrid { grid: GridSettings },
+	SetGridVisible { visible: bool },
+	SetGuidePosition { id: u64, position: f64 },
+	SetGuidesLocked { locked: bool },
+	SetGuidesVisible { visible: bool },
+}
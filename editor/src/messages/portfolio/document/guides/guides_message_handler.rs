@@ -0,0 +1,465 @@
+use crate::application::generate_uuid;
+use crate::consts::{COLOR_GRID, COLOR_GUIDE, GUIDE_SELECTION_TOLERANCE};
+use crate::messages::frontend::utility_types::MouseCursorIcon;
+use crate::messages::input_mapper::utility_types::input_keyboard::{Key, MouseMotion};
+use crate::messages::layout::utility_types::widget_prelude::*;
+use crate::messages::portfolio::document::utility_types::guides::{GridSettings, GridType, Guide, GuideDirection};
+use crate::messages::prelude::*;
+use crate::messages::tool::utility_types::{HintData, HintGroup, HintInfo};
+
+use document_legacy::layers::style::{self, Stroke};
+use document_legacy::{LayerId, Operation};
+use graphene_core::uuid::ManipulatorGroupId;
+use graphene_core::vector::Subpath;
+
+use glam::{DAffine2, DVec2};
+use serde::{Deserialize, Serialize};
+
+/// A guide being dragged, either out of a ruler or from its original position
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct GuideDrag {
+	id: u64,
+	original_position: Option<f64>,
+}
+
+pub struct GuidesMessageHandlerData<'a> {
+	pub document_to_viewport: DAffine2,
+	pub ipp: &'a InputPreprocessorMessageHandler,
+}
+
+/// Handles the guides and the grid of a document, which are saved with it but only ever shown as overlays
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuidesMessageHandler {
+	pub guides: Vec<Guide>,
+	pub guides_locked: bool,
+	pub guides_visible: bool,
+	pub grid: GridSettings,
+	pub grid_visible: bool,
+
+	#[serde(skip)]
+	drag: Option<GuideDrag>,
+	#[serde(skip)]
+	guide_overlay_paths: Vec<Vec<LayerId>>,
+	#[serde(skip)]
+	grid_overlay_path: Option<Vec<LayerId>>,
+}
+
+impl Default for GuidesMessageHandler {
+	fn default() -> Self {
+		Self {
+			guides: Vec::new(),
+			guides_locked: false,
+			guides_visible: true,
+			grid: GridSettings::default(),
+			grid_visible: false,
+
+			drag: None,
+			guide_overlay_paths: Vec::new(),
+			grid_overlay_path: None,
+		}
+	}
+}
+
+impl MessageHandler<GuidesMessage, GuidesMessageHandlerData<'_>> for GuidesMessageHandler {
+	#[remain::check]
+	fn process_message(&mut self, message: GuidesMessage, responses: &mut VecDeque<Message>, data: GuidesMessageHandlerData) {
+		use GuidesMessage::*;
+
+		let GuidesMessageHandlerData { document_to_viewport, ipp } = data;
+		let viewport_to_document = document_to_viewport.inverse();
+
+		#[remain::sorted]
+		match message {
+			Abort => {
+				let Some(drag) = self.drag.take() else { return };
+				match drag.original_position {
+					Some(position) => self.guides.iter_mut().filter(|guide| guide.id == drag.id).for_each(|guide| guide.position = position),
+					None => self.guides.retain(|guide| guide.id != drag.id),
+				}
+				self.end_drag(responses);
+			}
+			AddGuide { direction } => {
+				let center = viewport_to_document.transform_point2(ipp.viewport_bounds.size() / 2.);
+				self.guides.push(Guide {
+					id: generate_uuid(),
+					direction,
+					position: center[direction.axis()],
+				});
+				self.guides_visible = true;
+				responses.add(RenderOverlays);
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
+			ClearGuides => {
+				self.guides.clear();
+				responses.add(RenderOverlays);
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
+			DeleteGuide { id } => {
+				self.guides.retain(|guide| guide.id != id);
+				responses.add(RenderOverlays);
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
+			DragFromRuler { direction } => {
+				if self.guides_locked {
+					return;
+				}
+
+				let id = generate_uuid();
+				let position = viewport_to_document.transform_point2(ipp.mouse.position)[direction.axis()];
+				self.guides.push(Guide { id, direction, position });
+				self.guides_visible = true;
+				self.begin_drag(GuideDrag { id, original_position: None }, direction, responses);
+			}
+			DragStart => {
+				if self.guides_locked || !self.guides_visible {
+					return;
+				}
+
+				let mouse = ipp.mouse.position;
+				let document_mouse = viewport_to_document.transform_point2(mouse);
+				let distance = |guide: &Guide| document_to_viewport.transform_point2(guide.closest_point(document_mouse)).distance(mouse);
+				let Some(&guide) = self
+					.guides
+					.iter()
+					.filter(|guide| distance(guide) <= GUIDE_SELECTION_TOLERANCE)
+					.min_by(|a, b| distance(a).total_cmp(&distance(b)))
+				else {
+					return;
+				};
+
+				let drag = GuideDrag {
+					id: guide.id,
+					original_position: Some(guide.position),
+				};
+				self.begin_drag(drag, guide.direction, responses);
+			}
+			DragStop => {
+				let Some(drag) = self.drag.take() else { return };
+
+				// Guides dropped back onto the rulers, or anywhere else outside the viewport, are removed
+				let mouse = ipp.mouse.position;
+				let size = ipp.viewport_bounds.size();
+				if mouse.cmplt(DVec2::ZERO).any() || mouse.cmpgt(size).any() {
+					self.guides.retain(|guide| guide.id != drag.id);
+				}
+				self.end_drag(responses);
+			}
+			PointerMove => {
+				let Some(drag) = self.drag else { return };
+				let document_mouse = viewport_to_document.transform_point2(ipp.mouse.position);
+				if let Some(guide) = self.guides.iter_mut().find(|guide| guide.id == drag.id) {
+					guide.position = document_mouse[guide.direction.axis()];
+				}
+				responses.add(RenderOverlays);
+			}
+			RenderOverlays => {
+				let viewport_size = ipp.viewport_bounds.size();
+				let corners = [DVec2::ZERO, DVec2::new(viewport_size.x, 0.), viewport_size, DVec2::new(0., viewport_size.y)].map(|corner| viewport_to_document.transform_point2(corner));
+				let document_bounds = [corners.into_iter().reduce(DVec2::min).unwrap(), corners.into_iter().reduce(DVec2::max).unwrap()];
+
+				let guide_lines: Vec<_> = if self.guides_visible {
+					self.guides
+						.iter()
+						.map(|guide| {
+							let [mut start, mut end] = document_bounds;
+							start[guide.direction.axis()] = guide.position;
+							end[guide.direction.axis()] = guide.position;
+							[start, end].map(|point| document_to_viewport.transform_point2(point))
+						})
+						.collect()
+				} else {
+					Vec::new()
+				};
+				for (index, line) in guide_lines.iter().enumerate() {
+					let subpath = Subpath::new_line(line[0], line[1]);
+					match self.guide_overlay_paths.get(index).cloned() {
+						Some(path) => responses.add(DocumentMessage::Overlays(Operation::SetShapePath { path, subpath }.into())),
+						None => self.guide_overlay_paths.push(add_line_overlay(subpath, COLOR_GUIDE, responses)),
+					}
+				}
+				while self.guide_overlay_paths.len() > guide_lines.len() {
+					let path = self.guide_overlay_paths.pop().unwrap();
+					responses.add(DocumentMessage::Overlays(Operation::DeleteLayer { path }.into()));
+				}
+
+				let grid_lines = if self.grid_visible { self.grid.lines(document_bounds) } else { Vec::new() };
+				let grid_lines: Vec<_> = grid_lines
+					.into_iter()
+					.map(|[start, end]| bezier_rs::Subpath::<ManipulatorGroupId>::new_line(document_to_viewport.transform_point2(start), document_to_viewport.transform_point2(end)))
+					.collect();
+				let subpath = Subpath::from_bezier_rs(grid_lines.iter());
+				match self.grid_overlay_path.clone() {
+					Some(path) => responses.add(DocumentMessage::Overlays(Operation::SetShapePath { path, subpath }.into())),
+					None => self.grid_overlay_path = Some(add_line_overlay(subpath, COLOR_GRID, responses)),
+				}
+			}
+			SetGrid { grid } => {
+				self.grid = grid;
+				responses.add(RenderOverlays);
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
+			SetGridVisible { visible } => {
+				self.grid_visible = visible;
+				responses.add(RenderOverlays);
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
+			SetGuidePosition { id, position } => {
+				if let Some(guide) = self.guides.iter_mut().find(|guide| guide.id == id) {
+					guide.position = position;
+				}
+				responses.add(RenderOverlays);
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
+			SetGuidesLocked { locked } => {
+				self.guides_locked = locked;
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
+			SetGuidesVisible { visible } => {
+				self.guides_visible = visible;
+				responses.add(RenderOverlays);
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
+		}
+	}
+
+	fn actions(&self) -> ActionList {
+		if self.drag.is_some() {
+			actions!(GuidesMessageDiscriminant;
+				PointerMove,
+				DragStop,
+				Abort,
+			)
+		} else {
+			vec![]
+		}
+	}
+}
+
+impl GuidesMessageHandler {
+	/// The actions available to the input mapper, where existing guides can only be picked up in Guide Mode
+	pub fn actions_with_guide_mode(&self, guide_mode: bool) -> ActionList {
+		let mut common = self.actions();
+		if guide_mode && self.drag.is_none() {
+			common.extend(actions!(GuidesMessageDiscriminant; DragStart));
+		}
+		common
+	}
+
+	/// The visible guides, for snapping
+	pub fn visible_guides(&self) -> impl Iterator<Item = &Guide> {
+		self.guides.iter().filter(move |guide| self.guides_visible && self.drag.map_or(true, |drag| drag.id != guide.id))
+	}
+
+	/// The grid, for snapping, if it is visible
+	pub fn visible_grid(&self) -> Option<&GridSettings> {
+		self.grid_visible.then_some(&self.grid)
+	}
+
+	/// Forgets the overlays drawn so far, after the overlays document has been cleared, so they are drawn anew
+	pub fn forget_overlays(&mut self) {
+		self.guide_overlay_paths.clear();
+		self.grid_overlay_path = None;
+	}
+
+	fn begin_drag(&mut self, drag: GuideDrag, direction: GuideDirection, responses: &mut VecDeque<Message>) {
+		self.drag = Some(drag);
+
+		let cursor = match direction {
+			GuideDirection::Horizontal => MouseCursorIcon::NSResize,
+			GuideDirection::Vertical => MouseCursorIcon::EWResize,
+		};
+		responses.add(FrontendMessage::UpdateMouseCursor { cursor });
+		responses.add(FrontendMessage::UpdateInputHints {
+			hint_data: HintData(vec![
+				HintGroup(vec![HintInfo::mouse(MouseMotion::Rmb, "Cancel"), HintInfo::keys([Key::Escape], "Cancel")]),
+				HintGroup(vec![HintInfo::mouse(MouseMotion::LmbDrag, "Drop Outside the Viewport to Remove")]),
+			]),
+		});
+		responses.add(GuidesMessage::RenderOverlays);
+	}
+
+	fn end_drag(&mut self, responses: &mut VecDeque<Message>) {
+		responses.add(GuidesMessage::RenderOverlays);
+		responses.add(PortfolioMessage::UpdateDocumentWidgets);
+		responses.add(ToolMessage::UpdateCursor);
+		responses.add(ToolMessage::UpdateHints);
+	}
+
+	/// The popover menu listing the guides to be positioned numerically, locked, hidden or cleared
+	pub fn guides_layout(&self) -> Vec<LayoutGroup> {
+		let mut layout = vec![LayoutGroup::Row {
+			widgets: vec![
+				CheckboxInput::new(self.guides_visible)
+					.tooltip("Show Guides")
+					.on_update(|input: &CheckboxInput| GuidesMessage::SetGuidesVisible { visible: input.checked }.into())
+					.widget_holder(),
+				Separator::new(SeparatorType::Unrelated).widget_holder(),
+				TextLabel::new("Show Guides").table_align(false).min_width(60).widget_holder(),
+				Separator::new(SeparatorType::Section).widget_holder(),
+				CheckboxInput::new(self.guides_locked)
+					.tooltip("Lock Guides")
+					.on_update(|input: &CheckboxInput| GuidesMessage::SetGuidesLocked { locked: input.checked }.into())
+					.widget_holder(),
+				Separator::new(SeparatorType::Unrelated).widget_holder(),
+				TextLabel::new("Lock Guides").table_align(false).min_width(60).widget_holder(),
+			],
+		}];
+
+		layout.extend(self.guides.iter().map(|guide| {
+			let id = guide.id;
+			LayoutGroup::Row {
+				widgets: vec![
+					NumberInput::new(Some(guide.position))
+						.label(match guide.direction {
+							GuideDirection::Horizontal => "Y",
+							GuideDirection::Vertical => "X",
+						})
+						.unit(" px")
+						.tooltip(format!("Position of this {} guide in the document", guide.direction.to_string().to_lowercase()))
+						.disabled(self.guides_locked)
+						.on_update(move |number_input: &NumberInput| {
+							GuidesMessage::SetGuidePosition {
+								id,
+								position: number_input.value.unwrap(),
+							}
+							.into()
+						})
+						.widget_holder(),
+					Separator::new(SeparatorType::Related).widget_holder(),
+					IconButton::new("Remove", 16)
+						.tooltip("Remove this guide")
+						.disabled(self.guides_locked)
+						.on_update(move |_| GuidesMessage::DeleteGuide { id }.into())
+						.widget_holder(),
+				],
+			}
+		}));
+
+		layout.push(LayoutGroup::Row {
+			widgets: vec![
+				TextButton::new("Add Horizontal")
+					.tooltip("Add a horizontal guide across the middle of the viewport")
+					.disabled(self.guides_locked)
+					.on_update(|_| {
+						GuidesMessage::AddGuide {
+							direction: GuideDirection::Horizontal,
+						}
+						.into()
+					})
+					.widget_holder(),
+				Separator::new(SeparatorType::Related).widget_holder(),
+				TextButton::new("Add Vertical")
+					.tooltip("Add a vertical guide across the middle of the viewport")
+					.disabled(self.guides_locked)
+					.on_update(|_| GuidesMessage::AddGuide { direction: GuideDirection::Vertical }.into())
+					.widget_holder(),
+				Separator::new(SeparatorType::Related).widget_holder(),
+				TextButton::new("Clear")
+					.tooltip("Remove all guides")
+					.disabled(self.guides_locked || self.guides.is_empty())
+					.on_update(|_| GuidesMessage::ClearGuides.into())
+					.widget_holder(),
+			],
+		});
+
+		layout
+	}
+
+	/// The popover menu configuring the type, spacing and origin of the grid
+	pub fn grid_layout(&self) -> Vec<LayoutGroup> {
+		let grid = self.grid;
+		let set_grid = |update: fn(&mut GridSettings, f64)| {
+			move |number_input: &NumberInput| {
+				let mut grid = grid;
+				update(&mut grid, number_input.value.unwrap());
+				GuidesMessage::SetGrid { grid }.into()
+			}
+		};
+		let number_row = |label: &str, value: f64, unit: &str, update: fn(&mut GridSettings, f64)| LayoutGroup::Row {
+			widgets: vec![
+				TextLabel::new(label).table_align(true).widget_holder(),
+				Separator::new(SeparatorType::Unrelated).widget_holder(),
+				NumberInput::new(Some(value)).unit(unit).on_update(set_grid(update)).widget_holder(),
+			],
+		};
+
+		let is_isometric = matches!(grid.grid_type, GridType::Isometric { .. });
+		let mut layout = vec![
+			LayoutGroup::Row {
+				widgets: vec![
+					TextLabel::new("Type").table_align(true).widget_holder(),
+					Separator::new(SeparatorType::Unrelated).widget_holder(),
+					RadioInput::new(vec![
+						RadioEntryData::default().value("rectangular").label("Rectangular").on_update(move |_| {
+							let grid_type = GridType::RECTANGULAR;
+							GuidesMessage::SetGrid {
+								grid: GridSettings { grid_type, ..grid },
+							}
+							.into()
+						}),
+						RadioEntryData::default().value("isometric").label("Isometric").on_update(move |_| {
+							let grid_type = GridType::ISOMETRIC;
+							GuidesMessage::SetGrid {
+								grid: GridSettings { grid_type, ..grid },
+							}
+							.into()
+						}),
+					])
+					.selected_index(Some(is_isometric as u32))
+					.widget_holder(),
+				],
+			},
+			number_row("Origin X", grid.origin.x, " px", |grid, value| grid.origin.x = value),
+			number_row("Origin Y", grid.origin.y, " px", |grid, value| grid.origin.y = value),
+		];
+
+		match grid.grid_type {
+			GridType::Rectangular { spacing } => layout.extend([
+				number_row("Spacing X", spacing.x, " px", |grid, value| {
+					if let GridType::Rectangular { spacing } = &mut grid.grid_type {
+						spacing.x = value.max(1.);
+					}
+				}),
+				number_row("Spacing Y", spacing.y, " px", |grid, value| {
+					if let GridType::Rectangular { spacing } = &mut grid.grid_type {
+						spacing.y = value.max(1.);
+					}
+				}),
+			]),
+			GridType::Isometric { y_axis_spacing, angle_a, angle_b } => layout.extend([
+				number_row("Y Spacing", y_axis_spacing, " px", |grid, value| {
+					if let GridType::Isometric { y_axis_spacing, .. } = &mut grid.grid_type {
+						*y_axis_spacing = value.max(1.);
+					}
+				}),
+				number_row("Angle A", angle_a, "°", |grid, value| {
+					if let GridType::Isometric { angle_a, .. } = &mut grid.grid_type {
+						*angle_a = value.clamp(0., 89.);
+					}
+				}),
+				number_row("Angle B", angle_b, "°", |grid, value| {
+					if let GridType::Isometric { angle_b, .. } = &mut grid.grid_type {
+						*angle_b = value.clamp(0., 89.);
+					}
+				}),
+			]),
+		}
+
+		layout
+	}
+}
+
+/// Adds an overlay stroking the given path, returning its layer path
+fn add_line_overlay(subpath: Subpath, color: graphene_core::raster::color::Color, responses: &mut VecDeque<Message>) -> Vec<LayerId> {
+	let path = vec![generate_uuid()];
+	let operation = Operation::AddShape {
+		path: path.clone(),
+		transform: DAffine2::IDENTITY.to_cols_array(),
+		style: style::PathStyle::new(Some(Stroke::new(Some(color), 1.)), style::Fill::None),
+		insert_index: -1,
+		subpath,
+	};
+	responses.add(DocumentMessage::Overlays(operation.into()));
+	path
+}
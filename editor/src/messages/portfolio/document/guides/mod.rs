@@ -0,0 +1,7 @@
+mod guides_message;
+mod guides_message_handler;
+
+#[doc(inline)]
+pub use guides_message::{GuidesMessage, GuidesMessageDiscriminant};
+#[doc(inline)]
+pub use guides_message_handler::{GuidesMessageHandler, GuidesMessageHandlerData};
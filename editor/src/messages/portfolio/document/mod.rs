@@ -1,9 +1,11 @@
 mod document_message;
 mod document_message_handler;
 
+pub mod guides;
 pub mod navigation;
 pub mod node_graph;
 pub mod overlays;
+pub mod pixel_selection;
 pub mod properties_panel;
 pub mod utility_types;
 
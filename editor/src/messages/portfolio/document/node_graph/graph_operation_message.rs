@@ -1,14 +1,15 @@
 use crate::messages::prelude::*;
 
 use bezier_rs::Subpath;
+use graph_craft::document::value::TaggedValue;
 use graph_craft::document::NodeId;
-use graphene_core::raster::ImageFrame;
-use graphene_core::text::Font;
+use graphene_core::raster::flood_fill::FloodFill;
+use graphene_core::text::{Font, TypesettingConfig};
 use graphene_core::uuid::ManipulatorGroupId;
 use graphene_core::vector::brush_stroke::BrushStroke;
 use graphene_core::vector::style::{Fill, Stroke};
 use graphene_core::vector::ManipulatorPointId;
-use graphene_core::{Artboard, Color};
+use graphene_core::Artboard;
 
 use glam::{DAffine2, DVec2, IVec2};
 
@@ -56,6 +57,22 @@ pub enum GraphOperationMessage {
 		layer: LayerIdentifier,
 		strokes: Vec<BrushStroke>,
 	},
+	FloodFill {
+		layer: LayerIdentifier,
+		fill: FloodFill,
+	},
+	/// Replaces the strokes painted onto the layer's raster mask
+	MaskBrush {
+		layer: LayerIdentifier,
+		strokes: Vec<BrushStroke>,
+	},
+	/// Gives the layer a raster mask which can be painted with the brush, revealing everything within the layer's bounds to begin with
+	AddRasterMask {
+		layer: LayerIdentifier,
+	},
+	RemoveMask {
+		layer: LayerIdentifier,
+	},
 
 	NewArtboard {
 		id: NodeId,
@@ -63,7 +80,15 @@ pub enum GraphOperationMessage {
 	},
 	NewBitmapLayer {
 		id: NodeId,
-		image_frame: ImageFrame<Color>,
+		/// The folder layer to create the layer in, or `None` for the root of the document
+		parent: Option<NodeId>,
+		/// Any of the image frame variants of [`TaggedValue`]
+		image_frame: TaggedValue,
+	},
+	NewFolder {
+		id: NodeId,
+		/// The folder layer to create the folder in, or `None` for the root of the document
+		parent: Option<NodeId>,
 	},
 	NewVectorLayer {
 		id: NodeId,
@@ -73,7 +98,13 @@ pub enum GraphOperationMessage {
 		id: NodeId,
 		text: String,
 		font: Font,
-		size: f64,
+		typesetting: TypesettingConfig,
+	},
+	NewRegionFillLayer {
+		id: NodeId,
+		source_layer: LayerIdentifier,
+		point: DVec2,
+		fill: Fill,
 	},
 	ResizeArtboard {
 		id: NodeId,
@@ -7,12 +7,13 @@ use document_legacy::document_metadata::{DocumentMetadata, LayerNodeIdentifier};
 use document_legacy::{LayerId, Operation};
 use graph_craft::document::value::TaggedValue;
 use graph_craft::document::{generate_uuid, DocumentNode, NodeId, NodeInput, NodeNetwork, NodeOutput};
-use graphene_core::raster::ImageFrame;
-use graphene_core::text::Font;
+use graphene_core::raster::flood_fill::FloodFill;
+use graphene_core::raster::{Image, ImageFrame};
+use graphene_core::text::{Font, TypesettingConfig};
 use graphene_core::uuid::ManipulatorGroupId;
 use graphene_core::vector::brush_stroke::BrushStroke;
 use graphene_core::vector::style::{Fill, FillType, Stroke};
-use graphene_core::{Artboard, Color};
+use graphene_core::{Artboard, Color, GraphicGroup};
 use transform_utils::LayerBounds;
 
 use glam::{DAffine2, DVec2, IVec2};
@@ -133,6 +134,25 @@ impl<'a> ModifyInputsContext<'a> {
 		new_id
 	}
 
+	/// Creates a layer directly below the layer being modified, in the same folder.
+	fn create_layer_below(&mut self, new_id: NodeId) -> Option<NodeId> {
+		assert!(!self.network.nodes.contains_key(&new_id), "Creating already existing layer");
+		let layer_node = self.layer_node?;
+
+		let node = resolve_document_node_type("Layer").expect("Layer node").default_document_node();
+		let new_id = if let NodeInput::Node { node_id, output_index, .. } = self.network.nodes.get(&layer_node)?.inputs[7] {
+			self.insert_between(new_id, NodeOutput::new(node_id, output_index), NodeOutput::new(layer_node, 7), node, 7, 0, IVec2::new(0, 3))?
+		} else {
+			self.insert_node_before(new_id, layer_node, 7, node, IVec2::new(0, 3))?
+		};
+
+		let new_child = LayerNodeIdentifier::new(new_id, self.network);
+		LayerNodeIdentifier::new(layer_node, self.network).add_after(self.document_metadata, new_child);
+		self.responses.add(DocumentMessage::DocumentStructureChanged);
+
+		Some(new_id)
+	}
+
 	fn insert_artboard(&mut self, artboard: Artboard, layer: NodeId) -> Option<NodeId> {
 		let artboard_node = resolve_document_node_type("Artboard").expect("Node").to_document_node_default_inputs(
 			[
@@ -171,13 +191,46 @@ impl<'a> ModifyInputsContext<'a> {
 		self.responses.add(NodeGraphMessage::SendGraph { should_rerender: true });
 	}
 
-	fn insert_text(&mut self, text: String, font: Font, size: f64, layer: NodeId) {
+	/// Fills the region enclosed by the layer's paths around `point`, which stays up to date as the paths are edited.
+	fn insert_region_fill(&mut self, source_layer: NodeId, point: DVec2, fill: Fill, layer: NodeId) {
+		let Some(content) = self
+			.network
+			.nodes
+			.get(&source_layer)
+			.and_then(|node| node.inputs.first())
+			.filter(|input| matches!(input, NodeInput::Node { .. }))
+			.cloned()
+		else {
+			return;
+		};
+		let region = resolve_document_node_type("Enclosed Region")
+			.expect("Enclosed Region node does not exist")
+			.to_document_node_default_inputs([Some(content), Some(NodeInput::value(TaggedValue::DVec2(point), false))], Default::default());
+		let fill_node = resolve_document_node_type("Fill").expect("Fill node does not exist").default_document_node();
+
+		let fill_id = generate_uuid();
+		self.insert_node_before(fill_id, layer, 0, fill_node, IVec2::new(-8, 0));
+		self.insert_node_before(generate_uuid(), fill_id, 0, region, IVec2::new(-8, 0));
+
+		self.layer_node = Some(layer);
+		self.fill_set(fill);
+		self.responses.add(NodeGraphMessage::SendGraph { should_rerender: true });
+	}
+
+	fn insert_text(&mut self, text: String, font: Font, typesetting: TypesettingConfig, layer: NodeId) {
 		let text = resolve_document_node_type("Text").expect("Text node does not exist").to_document_node(
 			[
 				NodeInput::Network(graph_craft::concrete!(graphene_std::wasm_application_io::WasmEditorApi)),
 				NodeInput::value(TaggedValue::String(text), false),
 				NodeInput::value(TaggedValue::Font(font), false),
-				NodeInput::value(TaggedValue::F64(size), false),
+				NodeInput::value(TaggedValue::F64(typesetting.font_size), false),
+				NodeInput::value(TaggedValue::F64(typesetting.line_height_ratio), false),
+				NodeInput::value(TaggedValue::F64(typesetting.character_spacing), false),
+				NodeInput::value(TaggedValue::F64(typesetting.word_spacing), false),
+				NodeInput::value(TaggedValue::F64(typesetting.paragraph_spacing), false),
+				NodeInput::value(TaggedValue::F64(typesetting.max_width.unwrap_or_default()), false),
+				NodeInput::value(TaggedValue::TextAlignment(typesetting.align), false),
+				NodeInput::value(TaggedValue::FontFeatures(typesetting.features), false),
 			],
 			Default::default(),
 		);
@@ -199,11 +252,8 @@ impl<'a> ModifyInputsContext<'a> {
 		self.responses.add(NodeGraphMessage::SendGraph { should_rerender: true });
 	}
 
-	fn insert_image_data(&mut self, image_frame: ImageFrame<Color>, layer: NodeId) {
-		let image = {
-			let node_type = resolve_document_node_type("Image").expect("Image node does not exist");
-			node_type.to_document_node_default_inputs([Some(NodeInput::value(TaggedValue::ImageFrame(image_frame), false))], Default::default())
-		};
+	fn insert_image_data(&mut self, image_frame: TaggedValue, layer: NodeId) {
+		let image = resolve_document_node_type("Image").expect("Image node does not exist").to_document_node_default_inputs([Some(NodeInput::value(image_frame, false))], Default::default());
 		let sample = resolve_document_node_type("Sample").expect("Sample node does not exist").default_document_node();
 		let transform = resolve_document_node_type("Transform").expect("Transform node does not exist").default_document_node();
 
@@ -331,6 +381,10 @@ impl<'a> ModifyInputsContext<'a> {
 			inputs[5] = NodeInput::value(TaggedValue::LineCap(stroke.line_cap), false);
 			inputs[6] = NodeInput::value(TaggedValue::LineJoin(stroke.line_join), false);
 			inputs[7] = NodeInput::value(TaggedValue::F32(stroke.line_join_miter_limit as f32), false);
+			inputs[8] = NodeInput::value(TaggedValue::VecDVec2(stroke.profile.widths().to_vec()), false);
+			inputs[9] = NodeInput::value(TaggedValue::F64(stroke.profile.taper_start), false);
+			inputs[10] = NodeInput::value(TaggedValue::F64(stroke.profile.taper_end), false);
+			inputs[11] = NodeInput::value(TaggedValue::StrokeAlign(stroke.align), false);
 		});
 	}
 
@@ -444,6 +498,100 @@ impl<'a> ModifyInputsContext<'a> {
 		});
 	}
 
+	/// The node connected to the mask input of the layer node, if it has a mask
+	fn mask_node(&self) -> Option<NodeId> {
+		self.network.nodes.get(&self.layer_node?)?.inputs.get(8)?.as_node()
+	}
+
+	fn mask_brush_modify(&mut self, strokes: Vec<BrushStroke>) {
+		let Some(mask_node) = self.mask_node() else { return };
+		let Some(brush_node) = self.network.primary_flow_from_opt(Some(mask_node)).find(|(node, _)| node.name == "Brush").map(|(_, id)| id) else {
+			warn!("The layer's mask isn't painted");
+			return;
+		};
+		self.modify_existing_node_inputs(brush_node, |inputs, _node_id, _metadata| {
+			inputs[2] = NodeInput::value(TaggedValue::BrushStrokes(strokes), false);
+		});
+
+		self.node_graph.network.clear();
+		self.responses.add(PropertiesPanelMessage::ResendActiveProperties);
+		let layer_path = self.layer.to_vec();
+		self.responses.add(DocumentMessage::InputFrameRasterizeRegionBelowLayer { layer_path });
+	}
+
+	/// Connects a Brush node painting on an opaque white image covering the layer to the mask input of the layer node.
+	fn add_raster_mask(&mut self) {
+		let Some(layer_node) = self.layer_node else { return };
+		if self.mask_node().is_some() {
+			return;
+		}
+		let Some([bounds_min, bounds_max]) = self.document_metadata.bounding_box_document(LayerNodeIdentifier::new(layer_node, self.network)) else {
+			return;
+		};
+
+		let size = (bounds_max - bounds_min).ceil().max(DVec2::ONE);
+		let background = ImageFrame {
+			image: Image::new(size.x as u32, size.y as u32, Color::WHITE),
+			transform: DAffine2::from_scale_angle_translation(size, 0., bounds_min),
+		};
+		let brush = resolve_document_node_type("Brush")
+			.expect("Brush node does not exist")
+			.to_document_node_default_inputs([Some(NodeInput::value(TaggedValue::ImageFrame(background), false))], Default::default());
+		self.insert_node_before(generate_uuid(), layer_node, 8, brush, IVec2::new(-8, 3));
+		self.responses.add(NodeGraphMessage::SendGraph { should_rerender: true });
+	}
+
+	/// Disconnects the mask from the layer node, deleting the nodes which only produced the mask.
+	fn remove_mask(&mut self) {
+		let (Some(layer_node), Some(mask_node)) = (self.layer_node, self.mask_node()) else { return };
+
+		let mut delete_nodes = Vec::new();
+		for (_node, id) in self.network.primary_flow_from_opt(Some(mask_node)) {
+			if !self.outwards_links.get(&id).is_some_and(|outwards| outwards.len() == 1) {
+				break;
+			}
+			delete_nodes.push(id);
+		}
+
+		if let Some(node) = self.network.nodes.get_mut(&layer_node) {
+			node.inputs[8] = NodeInput::value(TaggedValue::GraphicGroup(GraphicGroup::EMPTY), true);
+		}
+		for node_id in &delete_nodes {
+			self.network.nodes.remove(node_id);
+		}
+		self.responses.add(self.document_metadata.retain_selected_nodes(|id| !delete_nodes.contains(id)));
+		self.responses.add(NodeGraphMessage::SendGraph { should_rerender: true });
+	}
+
+	/// Adds a fill to the layer's Flood Fill node, which is created the first time the layer is filled.
+	fn flood_fill(&mut self, fill: FloodFill) {
+		let sample_source = if fill.sample_merged { self.painted_content_below() } else { None };
+		self.modify_inputs("Flood Fill", false, |inputs, _node_id, _metadata| {
+			if let (Some(sample_source), NodeInput::Value { .. }) = (sample_source, &inputs[1]) {
+				inputs[1] = sample_source;
+			}
+			if let NodeInput::Value {
+				tagged_value: TaggedValue::FloodFills(fills),
+				..
+			} = &mut inputs[2]
+			{
+				fills.push(fill);
+			}
+		});
+	}
+
+	/// The image drawn by the layer below this one, if it is a painted layer.
+	/// Other raster layers sample the document through their footprint, so they can't be connected to the Flood Fill node's sample source.
+	fn painted_content_below(&self) -> Option<NodeInput> {
+		let layer = LayerNodeIdentifier::new(self.layer_node?, self.network);
+		let below = layer.next_sibling(self.document_metadata)?;
+		if !self.network.primary_flow_from_opt(Some(below.to_node())).any(|(node, _)| node.name == "Brush") {
+			return None;
+		}
+		let content = self.network.nodes.get(&below.to_node())?.inputs.first()?;
+		matches!(content, NodeInput::Node { .. }).then(|| content.clone())
+	}
+
 	fn resize_artboard(&mut self, location: IVec2, dimensions: IVec2) {
 		self.modify_inputs("Artboard", false, |inputs, _node_id, _metadata| {
 			inputs[1] = NodeInput::value(TaggedValue::IVec2(location), false);
@@ -565,28 +713,65 @@ impl MessageHandler<GraphOperationMessage, (&mut Document, &mut NodeGraphMessage
 					modify_inputs.brush_modify(strokes);
 				}
 			}
+			GraphOperationMessage::FloodFill { layer, fill } => {
+				if let Some(mut modify_inputs) = ModifyInputsContext::new_layer(&layer, document, node_graph, responses) {
+					modify_inputs.flood_fill(fill);
+				}
+			}
+			GraphOperationMessage::MaskBrush { layer, strokes } => {
+				if let Some(mut modify_inputs) = ModifyInputsContext::new_layer(&layer, document, node_graph, responses) {
+					modify_inputs.mask_brush_modify(strokes);
+				}
+			}
+			GraphOperationMessage::AddRasterMask { layer } => {
+				if let Some(mut modify_inputs) = ModifyInputsContext::new_layer(&layer, document, node_graph, responses) {
+					modify_inputs.add_raster_mask();
+				}
+			}
+			GraphOperationMessage::RemoveMask { layer } => {
+				if let Some(mut modify_inputs) = ModifyInputsContext::new_layer(&layer, document, node_graph, responses) {
+					modify_inputs.remove_mask();
+				}
+			}
 			GraphOperationMessage::NewArtboard { id, artboard } => {
 				let mut modify_inputs = ModifyInputsContext::new(document, node_graph, responses);
 				if let Some(layer) = modify_inputs.create_layer(id, modify_inputs.network.original_outputs()[0].node_id, 0) {
 					modify_inputs.insert_artboard(artboard, layer);
 				}
 			}
-			GraphOperationMessage::NewBitmapLayer { id, image_frame } => {
+			GraphOperationMessage::NewBitmapLayer { id, parent, image_frame } => {
 				let mut modify_inputs = ModifyInputsContext::new(document, node_graph, responses);
-				if let Some(layer) = modify_inputs.create_layer(id, modify_inputs.network.original_outputs()[0].node_id, 0) {
+				let parent = parent.unwrap_or(modify_inputs.network.original_outputs()[0].node_id);
+				if let Some(layer) = modify_inputs.create_layer(id, parent, 0) {
 					modify_inputs.insert_image_data(image_frame, layer);
 				}
 			}
+			GraphOperationMessage::NewFolder { id, parent } => {
+				let mut modify_inputs = ModifyInputsContext::new(document, node_graph, responses);
+				// The layers in a folder are stacked into the primary input of its layer node
+				let parent = parent.unwrap_or(modify_inputs.network.original_outputs()[0].node_id);
+				if modify_inputs.create_layer(id, parent, 0).is_some() {
+					modify_inputs.responses.add(NodeGraphMessage::SendGraph { should_rerender: true });
+				}
+			}
 			GraphOperationMessage::NewVectorLayer { id, subpaths } => {
 				let mut modify_inputs = ModifyInputsContext::new(document, node_graph, responses);
 				if let Some(layer) = modify_inputs.create_layer(id, modify_inputs.network.original_outputs()[0].node_id, 0) {
 					modify_inputs.insert_vector_data(subpaths, layer);
 				}
 			}
-			GraphOperationMessage::NewTextLayer { id, text, font, size } => {
+			GraphOperationMessage::NewTextLayer { id, text, font, typesetting } => {
 				let mut modify_inputs = ModifyInputsContext::new(document, node_graph, responses);
 				if let Some(layer) = modify_inputs.create_layer(id, modify_inputs.network.original_outputs()[0].node_id, 0) {
-					modify_inputs.insert_text(text, font, size, layer);
+					modify_inputs.insert_text(text, font, typesetting, layer);
+				}
+			}
+			GraphOperationMessage::NewRegionFillLayer { id, source_layer, point, fill } => {
+				if let Some(mut modify_inputs) = ModifyInputsContext::new_layer(&source_layer, document, node_graph, responses) {
+					let source_layer = modify_inputs.layer_node;
+					if let Some((source_layer, layer)) = source_layer.zip(modify_inputs.create_layer_below(id)) {
+						modify_inputs.insert_region_fill(source_layer, point, fill, layer);
+					}
 				}
 			}
 			GraphOperationMessage::ResizeArtboard { id, location, dimensions } => {
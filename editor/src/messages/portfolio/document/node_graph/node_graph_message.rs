@@ -92,6 +92,10 @@ pub enum NodeGraphMessage {
 	ShiftNode {
 		node_id: NodeId,
 	},
+	ToggleInputAnimation {
+		node_id: NodeId,
+		input_index: usize,
+	},
 	ToggleHidden,
 	SetHidden {
 		node_id: NodeId,
@@ -8,6 +8,7 @@ use document_legacy::document::Document;
 use document_legacy::LayerId;
 use graph_craft::document::value::TaggedValue;
 use graph_craft::document::{DocumentNode, NodeId, NodeInput, NodeNetwork, NodeOutput};
+use graphene_core::animation::Timeline;
 use graphene_core::*;
 mod document_node_types;
 mod node_properties;
@@ -47,7 +48,12 @@ impl FrontendGraphDataType {
 			TaggedValue::Bool(_) => Self::Boolean,
 			TaggedValue::DVec2(_) | TaggedValue::IVec2(_) => Self::Vector,
 			TaggedValue::Image(_) => Self::Raster,
-			TaggedValue::ImageFrame(_) => Self::Raster,
+			TaggedValue::ImageFrame(_)
+			| TaggedValue::ImageFrameSRGBA8(_)
+			| TaggedValue::ImageFrameSRGBA16(_)
+			| TaggedValue::ImageFrameRGBA16F(_)
+			| TaggedValue::ImageFrameLumaAlpha(_)
+			| TaggedValue::ImageFrameLuma(_) => Self::Raster,
 			TaggedValue::Color(_) => Self::Color,
 			TaggedValue::RcSubpath(_) | TaggedValue::Subpaths(_) | TaggedValue::VectorData(_) => Self::Subpath,
 			TaggedValue::GraphicGroup(_) => Self::GraphicGroup,
@@ -55,6 +61,27 @@ impl FrontendGraphDataType {
 			_ => Self::General,
 		}
 	}
+
+	/// Picks the data type of a connector from the Rust type flowing through it, for nodes which have no default value to inspect.
+	pub fn with_type(ty: &Type) -> Self {
+		let data_types = [
+			(concrete!(String), Self::Text),
+			(concrete!(f32), Self::Number),
+			(concrete!(f64), Self::Number),
+			(concrete!(u32), Self::Number),
+			(concrete!(glam::DAffine2), Self::Number),
+			(concrete!(bool), Self::Boolean),
+			(concrete!(glam::DVec2), Self::Vector),
+			(concrete!(IVec2), Self::Vector),
+			(concrete!(raster::Image<Color>), Self::Raster),
+			(concrete!(raster::ImageFrame<Color>), Self::Raster),
+			(concrete!(Color), Self::Color),
+			(concrete!(vector::VectorData), Self::Subpath),
+			(concrete!(GraphicGroup), Self::GraphicGroup),
+			(concrete!(Artboard), Self::Artboard),
+		];
+		data_types.into_iter().find(|(data_type_ty, _)| data_type_ty == ty).map_or(Self::General, |(_, data_type)| data_type)
+	}
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
@@ -439,6 +466,7 @@ pub struct NodeGraphHandlerData<'a> {
 	pub document_id: u64,
 	pub document_name: &'a str,
 	pub input: &'a InputPreprocessorMessageHandler,
+	pub timeline: Timeline,
 }
 
 impl<'a> MessageHandler<NodeGraphMessage, NodeGraphHandlerData<'a>> for NodeGraphMessageHandler {
@@ -761,7 +789,16 @@ impl<'a> MessageHandler<NodeGraphMessage, NodeGraphHandlerData<'a>> for NodeGrap
 					if let Some(node) = network.nodes.get(&node_id) {
 						responses.add(DocumentMessage::StartTransaction);
 
-						let input = NodeInput::Value { tagged_value: value, exposed: false };
+						// Changing a keyframed input sets a keyframe at the current time instead of replacing the animation
+						let mut tagged_value = value;
+						if let Some(NodeInput::Value { tagged_value: existing, .. }) = node.inputs.get(input_index) {
+							let mut animated = existing.clone();
+							if animated.insert_keyframe(data.timeline.time, &tagged_value) {
+								tagged_value = animated;
+							}
+						}
+
+						let input = NodeInput::Value { tagged_value, exposed: false };
 						responses.add(NodeGraphMessage::SetNodeInput { node_id, input_index, input });
 						responses.add(PropertiesPanelMessage::ResendActiveProperties);
 						if (node.name != "Imaginate" || input_index == 0) && network.connected_to_output(node_id) {
@@ -893,6 +930,24 @@ impl<'a> MessageHandler<NodeGraphMessage, NodeGraphHandlerData<'a>> for NodeGrap
 				}
 				self.update_selection_action_buttons(document, responses);
 			}
+			NodeGraphMessage::ToggleInputAnimation { node_id, input_index } => {
+				let Some(network) = document.document_network.nested_network(&self.network) else { return };
+				let Some(NodeInput::Value { tagged_value, exposed }) = network.nodes.get(&node_id).and_then(|node| node.inputs.get(input_index)) else {
+					return;
+				};
+
+				// Animated values become constants holding their value at the current time, and constants become animations holding their value
+				let Some(tagged_value) = tagged_value.sample_animation(data.timeline.time).or_else(|| tagged_value.clone().into_animated()) else {
+					warn!("Tried to animate input {input_index} of node {node_id}, which has a type that can't be animated");
+					return;
+				};
+				let input = NodeInput::Value { tagged_value, exposed: *exposed };
+
+				responses.add(DocumentMessage::StartTransaction);
+				responses.add(NodeGraphMessage::SetNodeInput { node_id, input_index, input });
+				responses.add(PropertiesPanelMessage::ResendActiveProperties);
+				responses.add(NodeGraphMessage::RunDocumentGraph);
+			}
 			NodeGraphMessage::TogglePreview { node_id } => {
 				responses.add(DocumentMessage::StartTransaction);
 				responses.add(NodeGraphMessage::TogglePreviewImpl { node_id });
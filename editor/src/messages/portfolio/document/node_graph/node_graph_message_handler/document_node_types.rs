@@ -11,8 +11,9 @@ use graph_craft::NodeIdentifier;
 #[cfg(feature = "gpu")]
 use graphene_core::application_io::SurfaceHandle;
 use graphene_core::raster::brush_cache::BrushCache;
+use graphene_core::raster::pixel_selection::PixelSelection;
 use graphene_core::raster::{BlendMode, Color, Image, ImageFrame, LuminanceCalculation, NoiseType, RedGreenBlue, RelativeAbsolute, SelectiveColorChoice};
-use graphene_core::text::Font;
+use graphene_core::text::{Font, FontFeatures, TextAlignment};
 use graphene_core::transform::Footprint;
 use graphene_core::vector::VectorData;
 use graphene_core::*;
@@ -72,6 +73,7 @@ pub struct NodePropertiesContext<'a> {
 	pub nested_path: &'a [NodeId],
 	pub executor: &'a mut NodeGraphExecutor,
 	pub network: &'a NodeNetwork,
+	pub timeline: graphene_core::animation::Timeline,
 }
 
 #[derive(Clone)]
@@ -129,7 +131,7 @@ static DOCUMENT_NODE_TYPES: once_cell::sync::Lazy<Vec<DocumentNodeBlueprint>> =
 /// Defines the "signature" or "header file"-like metadata for the document nodes, but not the implementation (which is defined in the node registry).
 /// The document node is the instance while these are the "class" (or "blueprint").
 fn static_nodes() -> Vec<DocumentNodeBlueprint> {
-	vec![
+	let mut node_types = vec![
 		DocumentNodeBlueprint {
 			name: "Boolean",
 			category: "Inputs",
@@ -157,6 +159,19 @@ fn static_nodes() -> Vec<DocumentNodeBlueprint> {
 			properties: node_properties::color_properties,
 			..Default::default()
 		},
+		DocumentNodeBlueprint {
+			name: "Time",
+			category: "Inputs",
+			identifier: NodeImplementation::proto("graphene_core::animation::TimeNode"),
+			inputs: vec![DocumentInputType {
+				name: "In",
+				data_type: FrontendGraphDataType::General,
+				default: NodeInput::Network(concrete!(WasmEditorApi)),
+			}],
+			outputs: vec![DocumentOutputType::new("Seconds", FrontendGraphDataType::Number)],
+			properties: |_document_node, _node_id, _context| node_properties::string_properties("The current time of the document's animation timeline, in seconds"),
+			..Default::default()
+		},
 		DocumentNodeBlueprint {
 			name: "Identity",
 			category: "Structural",
@@ -187,7 +202,7 @@ fn static_nodes() -> Vec<DocumentNodeBlueprint> {
 			name: "Layer",
 			category: "General",
 			identifier: NodeImplementation::DocumentNode(NodeNetwork {
-				inputs: vec![0, 2, 2, 2, 2, 2, 2, 2],
+				inputs: vec![0, 2, 2, 2, 2, 2, 2, 2, 3, 2, 2],
 				outputs: vec![NodeOutput::new(2, 0)],
 				nodes: [
 					(
@@ -224,8 +239,21 @@ fn static_nodes() -> Vec<DocumentNodeBlueprint> {
 								NodeInput::Network(concrete!(bool)),
 								NodeInput::Network(concrete!(bool)),
 								NodeInput::Network(graphene_core::Type::Fn(Box::new(concrete!(Footprint)), Box::new(concrete!(graphene_core::GraphicGroup)))),
+								NodeInput::node(3, 0),
+								NodeInput::Network(concrete!(bool)),
+								NodeInput::Network(concrete!(Vec<graphene_core::layer_effects::LayerEffect>)),
 							],
-							implementation: DocumentNodeImplementation::proto("graphene_core::ConstructLayerNode<_, _, _, _, _, _, _, _>"),
+							implementation: DocumentNodeImplementation::proto("graphene_core::ConstructLayerNode<_, _, _, _, _, _, _, _, _, _, _>"),
+							..Default::default()
+						},
+					),
+					// The mask is converted like the layer's content, so it may be vector data, an image or a group
+					(
+						3,
+						DocumentNode {
+							name: "To Graphic Element".to_string(),
+							inputs: vec![NodeInput::Network(generic!(T))],
+							implementation: DocumentNodeImplementation::proto("graphene_core::ToGraphicElementData"),
 							..Default::default()
 						},
 					),
@@ -242,6 +270,9 @@ fn static_nodes() -> Vec<DocumentNodeBlueprint> {
 				DocumentInputType::value("Locked", TaggedValue::Bool(false), false),
 				DocumentInputType::value("Collapsed", TaggedValue::Bool(false), false),
 				DocumentInputType::value("Stack", TaggedValue::GraphicGroup(GraphicGroup::EMPTY), true),
+				DocumentInputType::value("Mask", TaggedValue::GraphicGroup(GraphicGroup::EMPTY), true),
+				DocumentInputType::value("Clip", TaggedValue::Bool(false), false),
+				DocumentInputType::value("Effects", TaggedValue::LayerEffects(Vec::new()), false),
 			],
 			outputs: vec![DocumentOutputType::new("Out", FrontendGraphDataType::GraphicGroup)],
 			properties: node_properties::layer_properties,
@@ -282,7 +313,7 @@ fn static_nodes() -> Vec<DocumentNodeBlueprint> {
 			name: "Load Image",
 			category: "Structural",
 			identifier: NodeImplementation::DocumentNode(NodeNetwork {
-				inputs: vec![0, 0],
+				inputs: vec![0, 0, 1],
 				outputs: vec![NodeOutput::new(1, 0)],
 				nodes: [
 					DocumentNode {
@@ -293,8 +324,8 @@ fn static_nodes() -> Vec<DocumentNodeBlueprint> {
 					},
 					DocumentNode {
 						name: "Decode Image".to_string(),
-						inputs: vec![NodeInput::node(0, 0)],
-						implementation: DocumentNodeImplementation::Unresolved(NodeIdentifier::new("graphene_std::wasm_application_io::DecodeImageNode")),
+						inputs: vec![NodeInput::node(0, 0), NodeInput::Network(concrete!(WasmEditorApi))],
+						implementation: DocumentNodeImplementation::Unresolved(NodeIdentifier::new("graphene_std::wasm_application_io::DecodeImageNode<_>")),
 						..Default::default()
 					},
 				]
@@ -315,6 +346,12 @@ fn static_nodes() -> Vec<DocumentNodeBlueprint> {
 					data_type: FrontendGraphDataType::General,
 					default: NodeInput::value(TaggedValue::String("graphite:null".to_string()), false),
 				},
+				// Gives the decoder the document's working space
+				DocumentInputType {
+					name: "api",
+					data_type: FrontendGraphDataType::General,
+					default: NodeInput::Network(concrete!(WasmEditorApi)),
+				},
 			],
 			outputs: vec![DocumentOutputType {
 				name: "Image Frame",
@@ -595,6 +632,31 @@ fn static_nodes() -> Vec<DocumentNodeBlueprint> {
 			properties: node_properties::mask_properties,
 			..Default::default()
 		},
+		DocumentNodeBlueprint {
+			name: "Selection Mask",
+			category: "Image Adjustments",
+			identifier: NodeImplementation::proto("graphene_std::raster::SelectionMaskNode<_>"),
+			inputs: vec![
+				DocumentInputType::value("Image", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("Selection", TaggedValue::PixelSelection(PixelSelection::default()), false),
+			],
+			outputs: vec![DocumentOutputType::new("Mask", FrontendGraphDataType::Raster)],
+			properties: node_properties::selection_mask_properties,
+			..Default::default()
+		},
+		DocumentNodeBlueprint {
+			name: "Limit to Mask",
+			category: "Image Adjustments",
+			identifier: NodeImplementation::proto("graphene_std::raster::LimitToMaskNode<_, _>"),
+			inputs: vec![
+				DocumentInputType::value("Image", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("Original", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("Mask", TaggedValue::ImageFrameLuma(ImageFrame::empty()), true),
+			],
+			outputs: vec![DocumentOutputType::new("Image", FrontendGraphDataType::Raster)],
+			properties: node_properties::limit_to_mask_properties,
+			..Default::default()
+		},
 		DocumentNodeBlueprint {
 			name: "Insert Channel",
 			category: "Image Adjustments",
@@ -861,6 +923,19 @@ fn static_nodes() -> Vec<DocumentNodeBlueprint> {
 			}],
 			..Default::default()
 		},
+		DocumentNodeBlueprint {
+			name: "Flood Fill",
+			category: "Brush",
+			identifier: NodeImplementation::proto("graphene_std::raster::FloodFillNode<_, _>"),
+			inputs: vec![
+				DocumentInputType::value("Image", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("Sample Source", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("Fills", TaggedValue::FloodFills(Vec::new()), false),
+			],
+			outputs: vec![DocumentOutputType::new("Image", FrontendGraphDataType::Raster)],
+			properties: node_properties::flood_fill_properties,
+			..Default::default()
+		},
 		DocumentNodeBlueprint {
 			name: "Extract Vector Points",
 			category: "Brush",
@@ -884,7 +959,8 @@ fn static_nodes() -> Vec<DocumentNodeBlueprint> {
 		DocumentNodeBlueprint {
 			name: "Image",
 			category: "Ignore",
-			identifier: NodeImplementation::proto("graphene_core::ops::IdNode"),
+			// Images may be stored in any pixel format, and are converted to `Color` when the graph is evaluated
+			identifier: NodeImplementation::proto("graphene_core::ops::IntoNode<_, ImageFrame<Color>>"),
 			inputs: vec![DocumentInputType::value("Image", TaggedValue::ImageFrame(ImageFrame::empty()), false)],
 			outputs: vec![DocumentOutputType::new("Image", FrontendGraphDataType::Raster)],
 			properties: |_document_node, _node_id, _context| node_properties::string_properties("A bitmap image embedded in this node"),
@@ -2103,17 +2179,47 @@ fn static_nodes() -> Vec<DocumentNodeBlueprint> {
 		DocumentNodeBlueprint {
 			name: "Text",
 			category: "Vector",
-			identifier: NodeImplementation::proto("graphene_core::text::TextGenerator<_, _, _>"),
+			identifier: NodeImplementation::proto("graphene_core::text::TextGenerator<_, _, _, _, _, _, _, _, _, _>"),
 			inputs: vec![
 				DocumentInputType::none(),
 				DocumentInputType::value("Text", TaggedValue::String("hello world".to_string()), false),
 				DocumentInputType::value("Font", TaggedValue::Font(Font::new(DEFAULT_FONT_FAMILY.into(), DEFAULT_FONT_STYLE.into())), false),
 				DocumentInputType::value("Size", TaggedValue::F64(24.), false),
+				DocumentInputType::value("Line Height", TaggedValue::F64(1.), false),
+				DocumentInputType::value("Character Spacing", TaggedValue::F64(0.), false),
+				DocumentInputType::value("Word Spacing", TaggedValue::F64(0.), false),
+				DocumentInputType::value("Paragraph Spacing", TaggedValue::F64(0.), false),
+				DocumentInputType::value("Max Width", TaggedValue::F64(0.), false),
+				DocumentInputType::value("Alignment", TaggedValue::TextAlignment(TextAlignment::default()), false),
+				DocumentInputType::value("Font Features", TaggedValue::FontFeatures(FontFeatures::default()), false),
 			],
 			outputs: vec![DocumentOutputType::new("Vector", FrontendGraphDataType::Subpath)],
 			properties: node_properties::node_section_font,
 			..Default::default()
 		},
+		DocumentNodeBlueprint {
+			name: "Text on Path",
+			category: "Vector",
+			identifier: NodeImplementation::proto("graphene_core::text::TextOnPathNode<_, _, _, _, _, _, _, _>"),
+			inputs: vec![
+				DocumentInputType {
+					name: "In",
+					data_type: FrontendGraphDataType::General,
+					default: NodeInput::Network(concrete!(WasmEditorApi)),
+				},
+				DocumentInputType::value("Path", TaggedValue::VectorData(VectorData::empty()), true),
+				DocumentInputType::value("Text", TaggedValue::String("hello world".to_string()), false),
+				DocumentInputType::value("Font", TaggedValue::Font(Font::new(DEFAULT_FONT_FAMILY.into(), DEFAULT_FONT_STYLE.into())), false),
+				DocumentInputType::value("Size", TaggedValue::F64(24.), false),
+				DocumentInputType::value("Start Offset", TaggedValue::F64(0.), false),
+				DocumentInputType::value("Alignment", TaggedValue::TextAlignment(TextAlignment::default()), false),
+				DocumentInputType::value("Flip Side", TaggedValue::Bool(false), false),
+				DocumentInputType::value("Character Spacing", TaggedValue::F64(0.), false),
+			],
+			outputs: vec![DocumentOutputType::new("Vector", FrontendGraphDataType::Subpath)],
+			properties: node_properties::node_section_text_on_path,
+			..Default::default()
+		},
 		DocumentNodeBlueprint {
 			name: "Transform",
 			category: "Transform",
@@ -2194,7 +2300,7 @@ fn static_nodes() -> Vec<DocumentNodeBlueprint> {
 		DocumentNodeBlueprint {
 			name: "Stroke",
 			category: "Vector",
-			identifier: NodeImplementation::proto("graphene_core::vector::SetStrokeNode<_, _, _, _, _, _, _>"),
+			identifier: NodeImplementation::proto("graphene_core::vector::SetStrokeNode<_, _, _, _, _, _, _, _, _, _, _>"),
 			inputs: vec![
 				DocumentInputType::value("Vector Data", TaggedValue::VectorData(graphene_core::vector::VectorData::empty()), true),
 				DocumentInputType::value("Color", TaggedValue::OptionalColor(Some(Color::BLACK)), false),
@@ -2204,6 +2310,10 @@ fn static_nodes() -> Vec<DocumentNodeBlueprint> {
 				DocumentInputType::value("Line Cap", TaggedValue::LineCap(graphene_core::vector::style::LineCap::Butt), false),
 				DocumentInputType::value("Line Join", TaggedValue::LineJoin(graphene_core::vector::style::LineJoin::Miter), false),
 				DocumentInputType::value("Miter Limit", TaggedValue::F32(4.), false),
+				DocumentInputType::value("Width Profile", TaggedValue::VecDVec2(Vec::new()), false),
+				DocumentInputType::value("Taper Start", TaggedValue::F64(0.), false),
+				DocumentInputType::value("Taper End", TaggedValue::F64(0.), false),
+				DocumentInputType::value("Align", TaggedValue::StrokeAlign(graphene_core::vector::style::StrokeAlign::Center), false),
 			],
 			outputs: vec![DocumentOutputType::new("Vector", FrontendGraphDataType::Subpath)],
 			properties: node_properties::stroke_properties,
@@ -2245,6 +2355,23 @@ fn static_nodes() -> Vec<DocumentNodeBlueprint> {
 			properties: node_properties::circular_repeat_properties,
 			..Default::default()
 		},
+		DocumentNodeBlueprint {
+			name: "Copy to Points",
+			category: "Vector",
+			identifier: NodeImplementation::proto("graphene_core::vector::CopyToPointsNode<_, _, _, _, _, _>"),
+			inputs: vec![
+				DocumentInputType::value("Points", TaggedValue::VectorData(graphene_core::vector::VectorData::empty()), true),
+				DocumentInputType::value("Instance", TaggedValue::VectorData(graphene_core::vector::VectorData::empty()), true),
+				DocumentInputType::value("Align to Path", TaggedValue::Bool(false), false),
+				DocumentInputType::value("Random Scale Min", TaggedValue::F64(1.), false),
+				DocumentInputType::value("Random Scale Max", TaggedValue::F64(1.), false),
+				DocumentInputType::value("Random Rotation", TaggedValue::F64(0.), false),
+				DocumentInputType::value("Seed", TaggedValue::U32(0), false),
+			],
+			outputs: vec![DocumentOutputType::new("Vector", FrontendGraphDataType::Subpath)],
+			properties: node_properties::copy_to_points_properties,
+			..Default::default()
+		},
 		DocumentNodeBlueprint {
 			name: "Resample Points",
 			category: "Vector",
@@ -2316,7 +2443,46 @@ fn static_nodes() -> Vec<DocumentNodeBlueprint> {
 			properties: node_properties::color_overlay_properties,
 			..Default::default()
 		},
-	]
+	];
+	node_types.extend(registered_node_types());
+	node_types
+}
+
+/// Builds the blueprints of the nodes which describe themselves through `#[node_macro::node_fn]`, sorted by name since they are collected in no particular order.
+fn registered_node_types() -> Vec<DocumentNodeBlueprint> {
+	let mut node_types = graphene_core::registry::registered_nodes()
+		.filter_map(|metadata| {
+			let inputs = metadata
+				.inputs
+				.iter()
+				.map(|input| match TaggedValue::try_from_any((input.default_value)()) {
+					Ok(TaggedValue::None) => Some(DocumentInputType::none()),
+					Ok(tagged_value) => Some(DocumentInputType::value(input.name, tagged_value, input.exposed)),
+					Err(error) => {
+						log::warn!(
+							"Skipping the \"{}\" node because the default value of its \"{}\" input can't be stored: {error}",
+							metadata.display_name,
+							input.name
+						);
+						None
+					}
+				})
+				.collect::<Option<Vec<_>>>()?;
+			let output_type = (metadata.node_io)().output;
+
+			Some(DocumentNodeBlueprint {
+				name: metadata.display_name,
+				category: metadata.category,
+				identifier: NodeImplementation::proto(metadata.identifier),
+				inputs,
+				outputs: vec![DocumentOutputType::new(metadata.output_name, FrontendGraphDataType::with_type(&output_type))],
+				properties: node_properties::registered_node_properties,
+				..Default::default()
+			})
+		})
+		.collect::<Vec<_>>();
+	node_types.sort_by_key(|node_type| node_type.name);
+	node_types
 }
 
 pub static IMAGINATE_NODE: Lazy<DocumentNodeBlueprint> = Lazy::new(|| DocumentNodeBlueprint {
@@ -2401,6 +2567,52 @@ pub fn resolve_document_node_type(name: &str) -> Option<&DocumentNodeBlueprint>
 	DOCUMENT_NODE_TYPES.iter().find(|node| node.name == name)
 }
 
+/// Each node type which gained inputs, with the document version saved before the change, in the order the versions were replaced.
+/// Changing the inputs of a node type bumps `GRAPHITE_DOCUMENT_VERSION` and adds an entry with the version it replaces, so documents saved with any listed version can still be opened.
+const NODE_TYPES_WITH_ADDED_INPUTS: [(&str, &str); 6] = [
+	("0.0.18", "Layer"),
+	("0.0.19", "Layer"),
+	("0.0.20", "Text"),
+	("0.0.21", "Stroke"),
+	("0.0.22", "Stroke"),
+	("0.0.23", "Load Image"),
+];
+
+/// Whether a document saved with this earlier version can be opened after upgrading its networks with [upgrade_network].
+pub fn is_upgradable_document_version(version: &str) -> bool {
+	NODE_TYPES_WITH_ADDED_INPUTS.iter().any(|&(saved_version, _)| saved_version == version)
+}
+
+/// Upgrades a network saved with an earlier document version, including the networks nested in its nodes.
+///
+/// Nodes of a type which has gained inputs since that version are given the current implementation of their type and the default value of each input they are missing.
+pub fn upgrade_network(network: &mut NodeNetwork, version: &str) {
+	let Some(first_change) = NODE_TYPES_WITH_ADDED_INPUTS.iter().position(|&(saved_version, _)| saved_version == version) else {
+		return;
+	};
+	let node_types = NODE_TYPES_WITH_ADDED_INPUTS[first_change..].iter().map(|&(_, name)| name).collect::<Vec<_>>();
+	upgrade_nodes(network, &node_types);
+}
+
+fn upgrade_nodes(network: &mut NodeNetwork, node_types: &[&str]) {
+	for node in network.nodes.values_mut() {
+		let node_type = node_types.contains(&node.name.as_str()).then(|| resolve_document_node_type(&node.name)).flatten();
+		match node_type {
+			Some(node_type) if node.inputs.len() < node_type.inputs.len() => {
+				let existing_inputs = node.inputs.len();
+				node.inputs.extend(node_type.inputs[existing_inputs..].iter().map(|input| input.default.clone()));
+				node.implementation = node_type.generate_implementation();
+				node.manual_composition = node_type.manual_composition.clone();
+			}
+			_ => {
+				if let DocumentNodeImplementation::Network(nested_network) = &mut node.implementation {
+					upgrade_nodes(nested_network, node_types);
+				}
+			}
+		}
+	}
+}
+
 pub fn collect_node_types() -> Vec<FrontendNodeType> {
 	DOCUMENT_NODE_TYPES
 		.iter()
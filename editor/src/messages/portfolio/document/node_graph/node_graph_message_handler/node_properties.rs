@@ -1,6 +1,6 @@
 #![allow(clippy::too_many_arguments)]
 
-use super::document_node_types::NodePropertiesContext;
+use super::document_node_types::{DocumentNodeBlueprint, NodePropertiesContext};
 use super::FrontendGraphDataType;
 use crate::messages::layout::utility_types::widget_prelude::*;
 use crate::messages::prelude::*;
@@ -8,11 +8,14 @@ use crate::messages::prelude::*;
 use document_legacy::{layers::layer_info::LayerDataTypeDiscriminant, Operation};
 use graph_craft::concrete;
 use graph_craft::document::value::TaggedValue;
-use graph_craft::document::{DocumentNode, NodeId, NodeInput};
+use graph_craft::document::{DocumentNode, DocumentNodeImplementation, NodeId, NodeInput};
 use graph_craft::imaginate_input::{ImaginateMaskStartingFill, ImaginateSamplingMethod, ImaginateServerStatus, ImaginateStatus};
+use graphene_core::layer_effects::{LayerEffect, LayerEffectKind};
+use graphene_core::raster::flood_fill::FloodFill;
+use graphene_core::raster::pixel_selection::PixelSelection;
 use graphene_core::raster::{BlendMode, Color, ImageFrame, LuminanceCalculation, NoiseType, RedGreenBlue, RelativeAbsolute, SelectiveColorChoice};
-use graphene_core::text::Font;
-use graphene_core::vector::style::{FillType, GradientType, LineCap, LineJoin};
+use graphene_core::text::{Font, FontFeatures, TextAlignment};
+use graphene_core::vector::style::{FillType, GradientType, LineCap, LineJoin, StrokeAlign};
 
 use glam::{DVec2, IVec2};
 
@@ -437,6 +440,26 @@ fn line_cap_widget(document_node: &DocumentNode, node_id: u64, index: usize, nam
 	LayoutGroup::Row { widgets }
 }
 
+fn stroke_align_widget(document_node: &DocumentNode, node_id: u64, index: usize, name: &str, blank_assist: bool) -> LayoutGroup {
+	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, blank_assist);
+	if let &NodeInput::Value {
+		tagged_value: TaggedValue::StrokeAlign(align),
+		exposed: false,
+	} = &document_node.inputs[index]
+	{
+		let entries = [("Center", StrokeAlign::Center), ("Inside", StrokeAlign::Inside), ("Outside", StrokeAlign::Outside)]
+			.into_iter()
+			.map(|(name, val)| RadioEntryData::new(name).on_update(update_value(move |_| TaggedValue::StrokeAlign(val), node_id, index)))
+			.collect();
+
+		widgets.extend_from_slice(&[
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			RadioInput::new(entries).selected_index(Some(align as u32)).widget_holder(),
+		]);
+	}
+	LayoutGroup::Row { widgets }
+}
+
 fn line_join_widget(document_node: &DocumentNode, node_id: u64, index: usize, name: &str, blank_assist: bool) -> LayoutGroup {
 	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, blank_assist);
 	if let &NodeInput::Value {
@@ -777,6 +800,52 @@ pub fn mask_properties(document_node: &DocumentNode, node_id: NodeId, _context:
 	vec![mask]
 }
 
+pub fn selection_mask_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let selection_index = 1;
+	let NodeInput::Value {
+		tagged_value: TaggedValue::PixelSelection(selection),
+		exposed: false,
+	} = &document_node.inputs[selection_index]
+	else {
+		return vec![];
+	};
+
+	let summary = match selection.edits.len() {
+		0 => "Everything is selected".to_string(),
+		1 => "1 selection edit".to_string(),
+		edits => format!("{edits} selection edits"),
+	};
+	let widgets = vec![
+		TextLabel::new(summary).widget_holder(),
+		Separator::new(SeparatorType::Unrelated).widget_holder(),
+		TextButton::new("Use Document Selection")
+			.tooltip("Replace this node's selection with the one made in Select Mode")
+			.on_update(move |_| {
+				PixelSelectionMessage::ApplyToNode {
+					node_id,
+					input_index: selection_index,
+				}
+				.into()
+			})
+			.widget_holder(),
+		Separator::new(SeparatorType::Related).widget_holder(),
+		TextButton::new("Clear")
+			.tooltip("Select everything")
+			.disabled(selection.is_empty())
+			.on_update(update_value(|_: &TextButton| TaggedValue::PixelSelection(PixelSelection::default()), node_id, selection_index))
+			.widget_holder(),
+	];
+
+	vec![LayoutGroup::Row { widgets }]
+}
+
+pub fn limit_to_mask_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let original = start_widgets(document_node, node_id, 1, "Original", FrontendGraphDataType::Raster, true);
+	let mask = start_widgets(document_node, node_id, 2, "Mask", FrontendGraphDataType::Raster, true);
+
+	vec![LayoutGroup::Row { widgets: original }, LayoutGroup::Row { widgets: mask }]
+}
+
 pub fn blend_mode_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
 	vec![blend_mode(document_node, node_id, 0, "Blend Mode", true)]
 }
@@ -1237,9 +1306,108 @@ pub fn node_section_font(document_node: &DocumentNode, node_id: NodeId, _context
 		result.push(LayoutGroup::Row { widgets: style });
 	}
 	result.push(LayoutGroup::Row { widgets: size });
+
+	let line_height = number_widget(document_node, node_id, 4, "Line Height", NumberInput::default().unit("x").min(0.).step(0.1), true);
+	let character_spacing = number_widget(document_node, node_id, 5, "Character Spacing", NumberInput::default().unit(" px").step(0.5), true);
+	let word_spacing = number_widget(document_node, node_id, 6, "Word Spacing", NumberInput::default().unit(" px").step(0.5), true);
+	let paragraph_spacing = number_widget(document_node, node_id, 7, "Paragraph Spacing", NumberInput::default().unit(" px"), true);
+	let max_width = number_widget(document_node, node_id, 8, "Max Width", NumberInput::default().unit(" px").min(0.), true);
+	result.extend([
+		LayoutGroup::Row { widgets: line_height },
+		LayoutGroup::Row { widgets: character_spacing },
+		LayoutGroup::Row { widgets: word_spacing },
+		LayoutGroup::Row { widgets: paragraph_spacing },
+		LayoutGroup::Row { widgets: max_width }.with_tooltip("The width at which lines wrap, or 0 to only wrap at line breaks"),
+		text_alignment_widget(document_node, node_id, 9, "Alignment", true),
+	]);
+	result.extend(font_features_widgets(document_node, node_id, 10));
 	result
 }
 
+pub fn node_section_text_on_path(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let text = text_area_widget(document_node, node_id, 2, "Text", true);
+	let (font, style) = font_inputs(document_node, node_id, 3, "Font", true);
+	let size = number_widget(document_node, node_id, 4, "Size", NumberInput::default().unit(" px").min(1.), true);
+	let start_offset = number_widget(document_node, node_id, 5, "Start Offset", NumberInput::default().unit(" px"), true);
+	let flip = bool_widget(document_node, node_id, 7, "Flip Side", true);
+	let character_spacing = number_widget(document_node, node_id, 8, "Character Spacing", NumberInput::default().unit(" px").step(0.5), true);
+
+	let mut result = vec![LayoutGroup::Row { widgets: text }, LayoutGroup::Row { widgets: font }];
+	if let Some(style) = style {
+		result.push(LayoutGroup::Row { widgets: style });
+	}
+	result.extend([
+		LayoutGroup::Row { widgets: size },
+		LayoutGroup::Row { widgets: start_offset }.with_tooltip("The distance along the path before the text starts, after it is aligned"),
+		text_alignment_widget(document_node, node_id, 6, "Alignment", true),
+		LayoutGroup::Row { widgets: flip }.with_tooltip("Places the text on the other side of the path, running in the opposite direction"),
+		LayoutGroup::Row { widgets: character_spacing },
+	]);
+	result
+}
+
+fn text_alignment_widget(document_node: &DocumentNode, node_id: NodeId, index: usize, name: &str, blank_assist: bool) -> LayoutGroup {
+	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, blank_assist);
+	if let &NodeInput::Value {
+		tagged_value: TaggedValue::TextAlignment(align),
+		exposed: false,
+	} = &document_node.inputs[index]
+	{
+		let entries = TextAlignment::list()
+			.into_iter()
+			.map(|val| RadioEntryData::new(val.to_string()).on_update(update_value(move |_| TaggedValue::TextAlignment(val), node_id, index)))
+			.collect();
+
+		widgets.extend_from_slice(&[
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			RadioInput::new(entries).selected_index(Some(align as u32)).widget_holder(),
+		]);
+	}
+	LayoutGroup::Row { widgets }
+}
+
+/// A checkbox row for each OpenType feature that can be toggled
+fn font_features_widgets(document_node: &DocumentNode, node_id: NodeId, index: usize) -> Vec<LayoutGroup> {
+	let widgets = start_widgets(document_node, node_id, index, "OpenType Features", FrontendGraphDataType::General, true);
+	let &NodeInput::Value {
+		tagged_value: TaggedValue::FontFeatures(features),
+		exposed: false,
+	} = &document_node.inputs[index]
+	else {
+		return vec![LayoutGroup::Row { widgets }];
+	};
+
+	let feature = |name: &str, tooltip: &str, checked: bool, modify: fn(&mut FontFeatures, bool)| {
+		let mut widgets = vec![TextLabel::new(name).widget_holder()];
+		add_blank_assist(&mut widgets);
+		widgets.extend_from_slice(&[
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			CheckboxInput::new(checked)
+				.on_update(update_value(
+					move |input: &CheckboxInput| {
+						let mut features = features;
+						modify(&mut features, input.checked);
+						TaggedValue::FontFeatures(features)
+					},
+					node_id,
+					index,
+				))
+				.widget_holder(),
+		]);
+		LayoutGroup::Row { widgets }.with_tooltip(tooltip)
+	};
+
+	vec![
+		LayoutGroup::Row { widgets },
+		feature("Ligatures", "Standard ligatures (liga)", features.ligatures, |features, checked| features.ligatures = checked),
+		feature("Kerning", "Kerning (kern)", features.kerning, |features, checked| features.kerning = checked),
+		feature("Small Caps", "Small capitals (smcp)", features.small_caps, |features, checked| features.small_caps = checked),
+		feature("Tabular Numbers", "Tabular figures (tnum)", features.tabular_numbers, |features, checked| {
+			features.tabular_numbers = checked
+		}),
+	]
+}
+
 pub fn imaginate_properties(document_node: &DocumentNode, node_id: NodeId, context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
 	let imaginate_node = [context.nested_path, &[node_id]].concat();
 
@@ -1769,6 +1937,107 @@ fn unknown_node_properties(document_node: &DocumentNode) -> Vec<LayoutGroup> {
 	string_properties(format!("Node '{}' cannot be found in library", document_node.name))
 }
 
+/// Makes a widget's update replace the list of fills with a copy where only the fill at `index` is modified.
+fn update_flood_fill<T>(fills: &[FloodFill], index: usize, modify: impl Fn(&mut FloodFill, &T) + 'static + Send + Sync) -> impl Fn(&T) -> TaggedValue + 'static + Send + Sync {
+	let fills = fills.to_vec();
+	move |input: &T| {
+		let mut fills = fills.clone();
+		modify(&mut fills[index], input);
+		TaggedValue::FloodFills(fills)
+	}
+}
+
+pub fn flood_fill_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let sample_source = start_widgets(document_node, node_id, 1, "Sample Source", FrontendGraphDataType::Raster, true);
+	let mut layout = vec![LayoutGroup::Row { widgets: sample_source }];
+
+	let fills_index = 2;
+	let NodeInput::Value {
+		tagged_value: TaggedValue::FloodFills(fills),
+		exposed: false,
+	} = &document_node.inputs[fills_index]
+	else {
+		return layout;
+	};
+	if fills.is_empty() {
+		layout.push(LayoutGroup::Row {
+			widgets: vec![TextLabel::new("Click on the layer with the Fill tool to add a fill").widget_holder()],
+		});
+		return layout;
+	}
+
+	for (index, fill) in fills.iter().enumerate() {
+		let mut widgets = vec![TextLabel::new(format!("Fill {}", index + 1)).widget_holder()];
+		add_blank_assist(&mut widgets);
+
+		let on_remove = {
+			let fills = fills.clone();
+			move |_: &IconButton| {
+				let mut fills = fills.clone();
+				fills.remove(index);
+				TaggedValue::FloodFills(fills)
+			}
+		};
+		widgets.extend_from_slice(&[
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			ColorInput::new(Some(fill.color))
+				.allow_none(false)
+				.on_update(update_value(
+					update_flood_fill(fills, index, |fill, input: &ColorInput| fill.color = input.value.unwrap_or(fill.color)),
+					node_id,
+					fills_index,
+				))
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			NumberInput::new(Some(fill.tolerance))
+				.label("Tolerance")
+				.unit("%")
+				.min(0.)
+				.max(100.)
+				.mode_range()
+				.on_update(update_value(
+					update_flood_fill(fills, index, |fill, input: &NumberInput| fill.tolerance = input.value.unwrap_or(fill.tolerance)),
+					node_id,
+					fills_index,
+				))
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			CheckboxInput::new(fill.contiguous)
+				.tooltip("Contiguous")
+				.on_update(update_value(
+					update_flood_fill(fills, index, |fill, input: &CheckboxInput| fill.contiguous = input.checked),
+					node_id,
+					fills_index,
+				))
+				.widget_holder(),
+			CheckboxInput::new(fill.anti_alias)
+				.tooltip("Anti-aliasing")
+				.on_update(update_value(
+					update_flood_fill(fills, index, |fill, input: &CheckboxInput| fill.anti_alias = input.checked),
+					node_id,
+					fills_index,
+				))
+				.widget_holder(),
+			CheckboxInput::new(fill.sample_merged)
+				.tooltip("Sample Merged")
+				.on_update(update_value(
+					update_flood_fill(fills, index, |fill, input: &CheckboxInput| fill.sample_merged = input.checked),
+					node_id,
+					fills_index,
+				))
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			IconButton::new("Remove", 16)
+				.tooltip("Remove this fill")
+				.on_update(update_value(on_remove, node_id, fills_index))
+				.widget_holder(),
+		]);
+		layout.push(LayoutGroup::Row { widgets });
+	}
+
+	layout
+}
+
 pub fn no_properties(_document_node: &DocumentNode, _node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
 	string_properties("Node has no properties")
 }
@@ -1782,12 +2051,51 @@ pub fn index_properties(document_node: &DocumentNode, node_id: NodeId, _context:
 pub fn generate_node_properties(document_node: &DocumentNode, node_id: NodeId, context: &mut NodePropertiesContext) -> LayoutGroup {
 	let name = document_node.name.clone();
 	let layout = match super::document_node_types::resolve_document_node_type(&name) {
-		Some(document_node_type) => (document_node_type.properties)(document_node, node_id, context),
+		Some(document_node_type) => {
+			// The widgets show and edit keyframed inputs through their value at the current time
+			let mut sampled_node = document_node.clone();
+			for input in &mut sampled_node.inputs {
+				if let NodeInput::Value { tagged_value, .. } = input {
+					if let Some(sampled) = tagged_value.sample_animation(context.timeline.time) {
+						*tagged_value = sampled;
+					}
+				}
+			}
+
+			let mut layout = (document_node_type.properties)(&sampled_node, node_id, context);
+			layout.extend(animation_widgets(document_node, node_id, document_node_type));
+			layout
+		}
 		None => unknown_node_properties(document_node),
 	};
 	LayoutGroup::Section { name, layout }
 }
 
+/// A row of toggles for keyframing each of the node's inputs whose value can be animated.
+fn animation_widgets(document_node: &DocumentNode, node_id: NodeId, document_node_type: &DocumentNodeBlueprint) -> Option<LayoutGroup> {
+	let mut widgets = vec![TextLabel::new("Animate").widget_holder()];
+	for (input_index, input) in document_node.inputs.iter().enumerate() {
+		let NodeInput::Value { tagged_value, exposed: false } = input else { continue };
+		if tagged_value.clone().into_animated().is_none() {
+			continue;
+		}
+		let animated = tagged_value.sample_animation(0.).is_some();
+		let name = document_node_type.inputs.get(input_index).map(|input| input.name).unwrap_or_default();
+
+		widgets.extend_from_slice(&[
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			CheckboxInput::new(animated)
+				.tooltip(format!("Set keyframes for {name} when its value is changed"))
+				.on_update(move |_: &CheckboxInput| NodeGraphMessage::ToggleInputAnimation { node_id, input_index }.into())
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			TextLabel::new(name).widget_holder(),
+		]);
+	}
+
+	(widgets.len() > 1).then_some(LayoutGroup::Row { widgets })
+}
+
 pub fn stroke_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
 	let color_index = 1;
 	let weight_index = 2;
@@ -1805,7 +2113,7 @@ pub fn stroke_properties(document_node: &DocumentNode, node_id: NodeId, _context
 	let line_join = line_join_widget(document_node, node_id, line_join_index, "Line Join", true);
 	let miter_limit = number_widget(document_node, node_id, miter_limit_index, "Miter Limit", NumberInput::default().min(0.), true);
 
-	vec![
+	let mut result = vec![
 		color,
 		LayoutGroup::Row { widgets: weight },
 		LayoutGroup::Row { widgets: dash_lengths },
@@ -1813,7 +2121,19 @@ pub fn stroke_properties(document_node: &DocumentNode, node_id: NodeId, _context
 		line_cap,
 		line_join,
 		LayoutGroup::Row { widgets: miter_limit },
-	]
+	];
+
+	let width_profile = vec_dvec2_input(document_node, node_id, 8, "Width Profile", TextInput::default().centered(true), true);
+	let taper_start = number_widget(document_node, node_id, 9, "Taper Start", NumberInput::default().min(0.).max(1.).step(0.05).mode_range(), true);
+	let taper_end = number_widget(document_node, node_id, 10, "Taper End", NumberInput::default().min(0.).max(1.).step(0.05).mode_range(), true);
+	let align = stroke_align_widget(document_node, node_id, 11, "Align", true);
+	result.extend([
+		LayoutGroup::Row { widgets: width_profile }.with_tooltip("Pairs of a position along the path from 0 to 1 and the multiple of the weight there, such as (0, 1), (0.5, 2), (1, 1)"),
+		LayoutGroup::Row { widgets: taper_start }.with_tooltip("The fraction of the path's length over which the stroke narrows to a point at its start"),
+		LayoutGroup::Row { widgets: taper_end }.with_tooltip("The fraction of the path's length over which the stroke narrows to a point at its end"),
+		align.with_tooltip("Where the stroke sits relative to closed paths, which open paths ignore since they are always stroked along the center"),
+	]);
+	result
 }
 
 pub fn repeat_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
@@ -1831,12 +2151,90 @@ pub fn circular_repeat_properties(document_node: &DocumentNode, node_id: NodeId,
 	vec![LayoutGroup::Row { widgets: angle_offset }, LayoutGroup::Row { widgets: radius }, LayoutGroup::Row { widgets: count }]
 }
 
+pub fn copy_to_points_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let align_to_path = bool_widget(document_node, node_id, 2, "Align to Path", true);
+	let random_scale_min = number_widget(document_node, node_id, 3, "Random Scale Min", NumberInput::default().min(0.), true);
+	let random_scale_max = number_widget(document_node, node_id, 4, "Random Scale Max", NumberInput::default().min(0.), true);
+	let random_rotation = number_widget(document_node, node_id, 5, "Random Rotation", NumberInput::default().unit("°").min(0.).max(360.), true);
+	let seed = number_widget(document_node, node_id, 6, "Seed", NumberInput::default().int().min(0.), true);
+
+	vec![
+		LayoutGroup::Row { widgets: align_to_path },
+		LayoutGroup::Row { widgets: random_scale_min },
+		LayoutGroup::Row { widgets: random_scale_max },
+		LayoutGroup::Row { widgets: random_rotation },
+		LayoutGroup::Row { widgets: seed },
+	]
+}
+
 pub fn resample_points_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
 	let spacing = number_widget(document_node, node_id, 1, "Spacing", NumberInput::default().min(1.), true);
 
 	vec![LayoutGroup::Row { widgets: spacing }]
 }
 
+/// Properties of the nodes registered through `#[node_macro::node_fn]`, with a widget picked for each input from the type of its default value.
+pub fn registered_node_properties(document_node: &DocumentNode, node_id: NodeId, context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let DocumentNodeImplementation::Unresolved(identifier) = &document_node.implementation else {
+		return unknown_node_properties(document_node);
+	};
+	let Some(metadata) = graphene_core::registry::node_metadata(&identifier.name) else {
+		return unknown_node_properties(document_node);
+	};
+
+	let mut layout = Vec::new();
+	for (index, input) in metadata.inputs.iter().enumerate().skip(1) {
+		if index >= document_node.inputs.len() {
+			break;
+		}
+
+		let number_props = || {
+			let mut number_props = NumberInput::default().unit(input.unit.unwrap_or_default());
+			if let Some(min) = input.min {
+				number_props = number_props.min(min);
+			}
+			if let Some(max) = input.max {
+				number_props = number_props.max(max);
+			}
+			number_props
+		};
+		let name = input.name;
+		let group = match TaggedValue::try_from_any((input.default_value)()) {
+			Ok(TaggedValue::F64(_) | TaggedValue::F32(_)) => LayoutGroup::Row {
+				widgets: number_widget(document_node, node_id, index, name, number_props(), true),
+			},
+			Ok(TaggedValue::U32(_)) => LayoutGroup::Row {
+				widgets: number_widget(document_node, node_id, index, name, number_props().is_integer(true), true),
+			},
+			Ok(TaggedValue::Bool(_)) => LayoutGroup::Row {
+				widgets: bool_widget(document_node, node_id, index, name, true),
+			},
+			Ok(TaggedValue::String(_)) => LayoutGroup::Row {
+				widgets: text_widget(document_node, node_id, index, name, true),
+			},
+			Ok(TaggedValue::DVec2(_)) => vec2_widget(document_node, node_id, index, name, "X", "Y", input.unit.unwrap_or_default(), add_blank_assist),
+			Ok(TaggedValue::VecF32(_)) => LayoutGroup::Row {
+				widgets: vec_f32_input(document_node, node_id, index, name, TextInput::default().centered(true), true),
+			},
+			Ok(TaggedValue::Color(_) | TaggedValue::OptionalColor(_)) => color_widget(document_node, node_id, index, name, ColorInput::default(), true),
+			Ok(TaggedValue::BlendMode(_)) => blend_mode(document_node, node_id, index, name, true),
+			Ok(TaggedValue::LineCap(_)) => line_cap_widget(document_node, node_id, index, name, true),
+			Ok(TaggedValue::LineJoin(_)) => line_join_widget(document_node, node_id, index, name, true),
+			Ok(TaggedValue::StrokeAlign(_)) => stroke_align_widget(document_node, node_id, index, name, true),
+			// Inputs without a suitable widget can still be exposed to the graph
+			_ => LayoutGroup::Row {
+				widgets: start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, true),
+			},
+		};
+		layout.push(group);
+	}
+
+	if layout.is_empty() {
+		return no_properties(document_node, node_id, context);
+	}
+	layout
+}
+
 /// Fill Node Widgets LayoutGroup
 pub fn fill_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
 	let fill_type_index = 1;
@@ -1875,7 +2273,7 @@ pub fn fill_properties(document_node: &DocumentNode, node_id: NodeId, _context:
 	widgets
 }
 
-pub fn layer_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+pub fn layer_properties(document_node: &DocumentNode, node_id: NodeId, context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
 	let name = text_widget(document_node, node_id, 1, "Name", true);
 	let blend_mode = blend_mode(document_node, node_id, 2, "Blend Mode", true);
 	let opacity = number_widget(document_node, node_id, 3, "Opacity", NumberInput::default().percentage(), true);
@@ -1883,14 +2281,236 @@ pub fn layer_properties(document_node: &DocumentNode, node_id: NodeId, _context:
 	let locked = bool_widget(document_node, node_id, 5, "Locked", true);
 	let collapsed = bool_widget(document_node, node_id, 6, "Collapsed", true);
 
-	vec![
+	let mut layout = vec![
 		LayoutGroup::Row { widgets: name },
 		blend_mode,
 		LayoutGroup::Row { widgets: opacity },
 		LayoutGroup::Row { widgets: visible },
 		LayoutGroup::Row { widgets: locked },
 		LayoutGroup::Row { widgets: collapsed },
-	]
+	];
+
+	let mut mask = start_widgets(document_node, node_id, 8, "Mask", FrontendGraphDataType::General, true);
+	let mask_node = document_node.inputs[8].as_node().and_then(|mask_node| context.network.nodes.get(&mask_node));
+	let description = match mask_node {
+		Some(mask_node) if mask_node.name == "Brush" => "Raster mask".to_string(),
+		Some(mask_node) => format!("From the {} node", mask_node.name),
+		None => "None".to_string(),
+	};
+	mask.extend_from_slice(&[
+		Separator::new(SeparatorType::Unrelated).widget_holder(),
+		TextLabel::new(description).widget_holder(),
+		Separator::new(SeparatorType::Unrelated).widget_holder(),
+		TextButton::new("Add Raster Mask")
+			.tooltip("Add a mask to paint with the brush, where black hides the layer and white reveals it")
+			.disabled(mask_node.is_some())
+			.on_update(move |_| GraphOperationMessage::AddRasterMask { layer: vec![node_id] }.into())
+			.widget_holder(),
+		Separator::new(SeparatorType::Related).widget_holder(),
+		TextButton::new("Remove")
+			.tooltip("Remove the mask, along with the nodes which only produce it")
+			.disabled(mask_node.is_none())
+			.on_update(move |_| GraphOperationMessage::RemoveMask { layer: vec![node_id] }.into())
+			.widget_holder(),
+	]);
+	layout.push(LayoutGroup::Row { widgets: mask });
+
+	let clip = bool_widget(document_node, node_id, 9, "Clip to Layer Below", true);
+	layout.push(LayoutGroup::Row { widgets: clip });
+
+	layout.extend(layer_effects_widgets(document_node, node_id, 10));
+
+	layout
+}
+
+/// Makes a widget's update replace the list of effects with a copy where only the effect at `index` is modified.
+fn update_layer_effect<T>(effects: &[LayerEffect], index: usize, modify: impl Fn(&mut LayerEffect, &T) + 'static + Send + Sync) -> impl Fn(&T) -> TaggedValue + 'static + Send + Sync {
+	let effects = effects.to_vec();
+	move |input: &T| {
+		let mut effects = effects.clone();
+		modify(&mut effects[index], input);
+		TaggedValue::LayerEffects(effects)
+	}
+}
+
+/// A row listing the kinds of effect which can be added, followed by the rows editing each effect with only the parameters its kind uses.
+fn layer_effects_widgets(document_node: &DocumentNode, node_id: NodeId, effects_index: usize) -> Vec<LayoutGroup> {
+	let mut add_effect = start_widgets(document_node, node_id, effects_index, "Effects", FrontendGraphDataType::General, true);
+	let NodeInput::Value {
+		tagged_value: TaggedValue::LayerEffects(effects),
+		exposed: false,
+	} = &document_node.inputs[effects_index]
+	else {
+		return vec![LayoutGroup::Row { widgets: add_effect }];
+	};
+
+	let entries = LayerEffectKind::list()
+		.into_iter()
+		.map(|kind| {
+			let effects = effects.clone();
+			DropdownEntryData::new(kind.to_string()).on_update(update_value(
+				move |_| {
+					let mut effects = effects.clone();
+					effects.push(LayerEffect::new(kind));
+					TaggedValue::LayerEffects(effects)
+				},
+				node_id,
+				effects_index,
+			))
+		})
+		.collect();
+	add_effect.extend_from_slice(&[
+		Separator::new(SeparatorType::Unrelated).widget_holder(),
+		TextLabel::new("Add").widget_holder(),
+		Separator::new(SeparatorType::Related).widget_holder(),
+		DropdownInput::new(vec![entries]).tooltip("Add an effect, drawn over the effects already added").widget_holder(),
+	]);
+	let mut layout = vec![LayoutGroup::Row { widgets: add_effect }];
+
+	let row_start = |name: &str| {
+		let mut widgets = vec![TextLabel::new(name).widget_holder()];
+		add_blank_assist(&mut widgets);
+		widgets.push(Separator::new(SeparatorType::Unrelated).widget_holder());
+		widgets
+	};
+	for (index, effect) in effects.iter().enumerate() {
+		let kind = effect.kind;
+		let update = |modify: fn(&mut LayerEffect, &NumberInput)| update_value(update_layer_effect(effects, index, modify), node_id, effects_index);
+
+		let on_remove = {
+			let effects = effects.clone();
+			move |_: &IconButton| {
+				let mut effects = effects.clone();
+				effects.remove(index);
+				TaggedValue::LayerEffects(effects)
+			}
+		};
+		let blend_modes = BlendMode::list()
+			.iter()
+			.map(|category| {
+				category
+					.iter()
+					.map(|&mode| {
+						let update = update_layer_effect(effects, index, move |effect, _: &()| effect.blend_mode = mode);
+						DropdownEntryData::new(mode.to_string()).on_update(update_value(update, node_id, effects_index))
+					})
+					.collect()
+			})
+			.collect();
+		let mut widgets = row_start(&kind.to_string());
+		widgets.extend_from_slice(&[
+			CheckboxInput::new(effect.enabled)
+				.tooltip("Enabled")
+				.on_update(update_value(
+					update_layer_effect(effects, index, |effect, input: &CheckboxInput| effect.enabled = input.checked),
+					node_id,
+					effects_index,
+				))
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			DropdownInput::new(blend_modes)
+				.selected_index(Some(effect.blend_mode as u32))
+				.tooltip("Formula used for blending the effect")
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			IconButton::new("Remove", 16)
+				.tooltip("Remove this effect")
+				.on_update(update_value(on_remove, node_id, effects_index))
+				.widget_holder(),
+		]);
+		layout.push(LayoutGroup::Row { widgets });
+
+		let mut widgets = row_start("Color");
+		widgets.push(
+			ColorInput::new(Some(effect.color))
+				.allow_none(false)
+				.on_update(update_value(
+					update_layer_effect(effects, index, |effect, input: &ColorInput| effect.color = input.value.unwrap_or(effect.color)),
+					node_id,
+					effects_index,
+				))
+				.widget_holder(),
+		);
+		if kind.uses_secondary_color() {
+			widgets.extend_from_slice(&[
+				Separator::new(SeparatorType::Related).widget_holder(),
+				ColorInput::new(Some(effect.secondary_color))
+					.allow_none(false)
+					.on_update(update_value(
+						update_layer_effect(effects, index, |effect, input: &ColorInput| effect.secondary_color = input.value.unwrap_or(effect.secondary_color)),
+						node_id,
+						effects_index,
+					))
+					.widget_holder(),
+			]);
+		}
+		widgets.extend_from_slice(&[
+			Separator::new(SeparatorType::Related).widget_holder(),
+			NumberInput::new(Some(effect.opacity))
+				.label("Opacity")
+				.percentage()
+				.on_update(update(|effect, input| effect.opacity = input.value.unwrap_or(effect.opacity)))
+				.widget_holder(),
+		]);
+		layout.push(LayoutGroup::Row { widgets });
+
+		if kind.uses_offset() {
+			let mut widgets = row_start("Offset");
+			widgets.extend_from_slice(&[
+				NumberInput::new(Some(effect.offset.x))
+					.label("X")
+					.unit(" px")
+					.on_update(update(|effect, input| effect.offset.x = input.value.unwrap_or(effect.offset.x)))
+					.widget_holder(),
+				Separator::new(SeparatorType::Related).widget_holder(),
+				NumberInput::new(Some(effect.offset.y))
+					.label("Y")
+					.unit(" px")
+					.on_update(update(|effect, input| effect.offset.y = input.value.unwrap_or(effect.offset.y)))
+					.widget_holder(),
+			]);
+			layout.push(LayoutGroup::Row { widgets });
+		}
+
+		if kind == LayerEffectKind::GradientOverlay {
+			let mut widgets = row_start("Angle");
+			widgets.push(
+				NumberInput::new(Some(effect.angle))
+					.unit("°")
+					.mode_range()
+					.range_min(Some(-180.))
+					.range_max(Some(180.))
+					.on_update(update(|effect, input| effect.angle = input.value.unwrap_or(effect.angle)))
+					.widget_holder(),
+			);
+			layout.push(LayoutGroup::Row { widgets });
+		}
+
+		if kind.uses_blur() {
+			let mut widgets = row_start(if kind == LayerEffectKind::Bevel { "Size" } else { "Blur" });
+			widgets.push(
+				NumberInput::new(Some(effect.blur))
+					.unit(" px")
+					.min(0.)
+					.on_update(update(|effect, input| effect.blur = input.value.unwrap_or(effect.blur)))
+					.widget_holder(),
+			);
+			if kind.uses_spread() {
+				widgets.extend_from_slice(&[
+					Separator::new(SeparatorType::Related).widget_holder(),
+					NumberInput::new(Some(effect.spread))
+						.label(if kind == LayerEffectKind::Stroke { "Width" } else { "Spread" })
+						.unit(" px")
+						.min(0.)
+						.on_update(update(|effect, input| effect.spread = input.value.unwrap_or(effect.spread)))
+						.widget_holder(),
+				]);
+			}
+			layout.push(LayoutGroup::Row { widgets });
+		}
+	}
+
+	layout
 }
 pub fn artboard_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
 	let location = vec2_widget(document_node, node_id, 1, "Location", "X", "Y", " px", add_blank_assist);
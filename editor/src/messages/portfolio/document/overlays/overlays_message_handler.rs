@@ -1,3 +1,5 @@
+use super::utility_types::OverlayLabel;
+use crate::consts::COLOR_ACCENT;
 use crate::messages::portfolio::utility_types::PersistentData;
 use crate::messages::prelude::*;
 
@@ -7,6 +9,8 @@ use document_legacy::layers::style::{RenderData, ViewMode};
 #[derive(Debug, Clone, Default)]
 pub struct OverlaysMessageHandler {
 	pub overlays_document: DocumentLegacy,
+	/// Text drawn over the overlay layers, which the legacy document has no layer type for
+	pub labels: Vec<OverlayLabel>,
 }
 
 impl MessageHandler<OverlaysMessage, (bool, &PersistentData, &InputPreprocessorMessageHandler)> for OverlaysMessageHandler {
@@ -26,6 +30,7 @@ impl MessageHandler<OverlaysMessage, (bool, &PersistentData, &InputPreprocessorM
 			// Messages
 			ClearAllOverlays => {
 				self.overlays_document = DocumentLegacy::default();
+				self.labels.clear();
 			}
 			Rerender =>
 			// Render overlays
@@ -33,12 +38,19 @@ impl MessageHandler<OverlaysMessage, (bool, &PersistentData, &InputPreprocessorM
 				responses.add(FrontendMessage::UpdateDocumentOverlays {
 					svg: if overlays_visible {
 						let render_data = RenderData::new(&persistent_data.font_cache, ViewMode::Normal, Some(ipp.document_bounds()));
-						self.overlays_document.render_root(&render_data)
+						let mut svg = self.overlays_document.render_root(&render_data);
+						let color = COLOR_ACCENT.rgb_hex();
+						svg.extend(self.labels.iter().map(|label| label.render(&color)));
+						svg
 					} else {
 						String::from("")
 					},
 				})
 			}
+			SetLabels { labels } => {
+				self.labels = labels;
+				responses.add(OverlaysMessage::Rerender);
+			}
 		}
 	}
 
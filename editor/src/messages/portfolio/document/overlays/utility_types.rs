@@ -0,0 +1,22 @@
+use glam::DVec2;
+use serde::{Deserialize, Serialize};
+
+/// A short piece of text drawn over the document, such as the length of a snapping measurement.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct OverlayLabel {
+	pub text: String,
+	/// The viewport position of the center of the text
+	pub position: DVec2,
+	pub font_size: f64,
+}
+
+impl OverlayLabel {
+	/// The SVG text element of the label, filled with the given hex color.
+	pub fn render(&self, color: &str) -> String {
+		let text = self.text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+		format!(
+			r##"<text x="{}" y="{}" fill="#{color}" font-size="{}px" text-anchor="middle" dominant-baseline="central">{text}</text>"##,
+			self.position.x, self.position.y, self.font_size
+		)
+	}
+}
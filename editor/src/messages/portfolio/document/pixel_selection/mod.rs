@@ -0,0 +1,7 @@
+mod pixel_selection_message;
+mod pixel_selection_message_handler;
+
+#[doc(inline)]
+pub use pixel_selection_message::{PixelSelectionMessage, PixelSelectionMessageDiscriminant};
+#[doc(inline)]
+pub use pixel_selection_message_handler::{PixelSelectionMessageHandler, PixelSelectionMessageHandlerData};
@@ -0,0 +1,35 @@
+use crate::messages::input_mapper::utility_types::input_keyboard::Key;
+use crate::messages::portfolio::document::utility_types::misc::SelectionTool;
+use crate::messages::prelude::*;
+
+use graph_craft::document::NodeId;
+use graphene_core::raster::pixel_selection::SelectionMode;
+
+use serde::{Deserialize, Serialize};
+
+#[remain::sorted]
+#[impl_message(Message, DocumentMessage, PixelSelection)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum PixelSelectionMessage {
+	// Messages
+	Abort,
+	ApplyToNode { node_id: NodeId, input_index: usize },
+	Clear,
+	Confirm,
+	DragStart { add: Key, subtract: Key },
+	DragStop,
+	Feather,
+	Grow,
+	Invert,
+	PointerMove,
+	RenderOverlays,
+	SelectAll,
+	SetAntiAlias { anti_alias: bool },
+	SetContiguous { contiguous: bool },
+	SetFeatherRadius { radius: f64 },
+	SetGrowAmount { amount: f64 },
+	SetMode { mode: SelectionMode },
+	SetTolerance { tolerance: f64 },
+	SetTool { tool: SelectionTool },
+	Shrink,
+}
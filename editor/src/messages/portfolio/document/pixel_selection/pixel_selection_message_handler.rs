@@ -0,0 +1,526 @@
+use crate::application::generate_uuid;
+use crate::consts::{COLOR_ACCENT, POLYGONAL_LASSO_CLOSE_TOLERANCE};
+use crate::messages::frontend::utility_types::MouseCursorIcon;
+use crate::messages::input_mapper::utility_types::input_keyboard::{Key, MouseMotion};
+use crate::messages::layout::utility_types::widget_prelude::*;
+use crate::messages::portfolio::document::utility_types::misc::SelectionTool;
+use crate::messages::prelude::*;
+use crate::messages::tool::utility_types::{HintData, HintGroup, HintInfo};
+
+use document_legacy::layers::style::{self, Stroke};
+use document_legacy::{LayerId, Operation};
+use graph_craft::document::value::TaggedValue;
+use graphene_core::raster::pixel_selection::{PixelSelection, SelectionEdit, SelectionMode, SelectionShape};
+use graphene_core::uuid::ManipulatorGroupId;
+use graphene_core::vector::Subpath;
+
+use glam::{DAffine2, DVec2};
+use serde::{Deserialize, Serialize};
+
+/// The radius of the markers drawn where the magic wand was clicked, in viewport pixels
+const MAGIC_WAND_MARKER_RADIUS: f64 = 4.;
+
+/// A selection being drawn, with its points in document space
+#[derive(Debug, Clone, PartialEq)]
+struct SelectionDrag {
+	tool: SelectionTool,
+	mode: SelectionMode,
+	/// The two corners of a marquee, the path of a lasso, or the clicked points of a polygonal lasso followed by the point under the pointer
+	points: Vec<DVec2>,
+}
+
+pub struct PixelSelectionMessageHandlerData<'a> {
+	pub document_to_viewport: DAffine2,
+	pub ipp: &'a InputPreprocessorMessageHandler,
+}
+
+/// Handles the pixel selection of a document, drawn in Select Mode, which masks the brush and any Selection Mask nodes it is applied to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PixelSelectionMessageHandler {
+	pub selection: PixelSelection,
+	pub tool: SelectionTool,
+	pub mode: SelectionMode,
+	pub anti_alias: bool,
+	/// The magic wand's color tolerance, as a percentage
+	pub tolerance: f64,
+	pub contiguous: bool,
+	pub feather_radius: f64,
+	pub grow_amount: f64,
+
+	#[serde(skip)]
+	drag: Option<SelectionDrag>,
+	#[serde(skip)]
+	overlay_path: Option<Vec<LayerId>>,
+}
+
+impl Default for PixelSelectionMessageHandler {
+	fn default() -> Self {
+		Self {
+			selection: PixelSelection::default(),
+			tool: SelectionTool::default(),
+			mode: SelectionMode::default(),
+			anti_alias: true,
+			tolerance: 10.,
+			contiguous: true,
+			feather_radius: 5.,
+			grow_amount: 5.,
+
+			drag: None,
+			overlay_path: None,
+		}
+	}
+}
+
+impl MessageHandler<PixelSelectionMessage, PixelSelectionMessageHandlerData<'_>> for PixelSelectionMessageHandler {
+	#[remain::check]
+	fn process_message(&mut self, message: PixelSelectionMessage, responses: &mut VecDeque<Message>, data: PixelSelectionMessageHandlerData) {
+		use PixelSelectionMessage::*;
+
+		let PixelSelectionMessageHandlerData { document_to_viewport, ipp } = data;
+		let document_mouse = document_to_viewport.inverse().transform_point2(ipp.mouse.position);
+
+		#[remain::sorted]
+		match message {
+			Abort => {
+				if self.drag.take().is_some() {
+					self.end_drag(responses);
+				}
+			}
+			ApplyToNode { node_id, input_index } => {
+				let value = TaggedValue::PixelSelection(self.selection.clone());
+				responses.add(NodeGraphMessage::SetInputValue { node_id, input_index, value });
+			}
+			Clear => {
+				self.selection = PixelSelection::default();
+				self.selection_changed(responses);
+			}
+			Confirm => {
+				let Some(mut drag) = self.drag.take() else { return };
+				if drag.tool == SelectionTool::PolygonalLasso {
+					drag.points.pop();
+					self.commit(drag);
+				}
+				self.end_drag(responses);
+			}
+			DragStart { add, subtract } => {
+				// Each click of the polygonal lasso adds a point, until the first point is clicked again
+				if let Some(drag) = &mut self.drag {
+					let first = document_to_viewport.transform_point2(drag.points[0]);
+					if drag.points.len() > 3 && first.distance(ipp.mouse.position) <= POLYGONAL_LASSO_CLOSE_TOLERANCE {
+						responses.add(Confirm);
+					} else {
+						drag.points.push(document_mouse);
+						responses.add(RenderOverlays);
+					}
+					return;
+				}
+
+				let mode = match (ipp.keyboard.key(add), ipp.keyboard.key(subtract)) {
+					(true, true) => SelectionMode::Intersect,
+					(true, false) => SelectionMode::Add,
+					(false, true) => SelectionMode::Subtract,
+					(false, false) => self.mode,
+				};
+
+				if self.tool == SelectionTool::MagicWand {
+					self.push_edit(SelectionEdit::MagicWand {
+						position: document_mouse,
+						tolerance: self.tolerance,
+						contiguous: self.contiguous,
+						anti_alias: self.anti_alias,
+						mode,
+					});
+					self.selection_changed(responses);
+					return;
+				}
+
+				self.drag = Some(SelectionDrag {
+					tool: self.tool,
+					mode,
+					points: vec![document_mouse, document_mouse],
+				});
+				self.begin_drag(responses);
+			}
+			DragStop => {
+				let Some(drag) = self.drag.take() else { return };
+				if drag.tool == SelectionTool::PolygonalLasso {
+					self.drag = Some(drag);
+					return;
+				}
+
+				// Clicking without dragging a marquee deselects, as long as the selection isn't being added to or subtracted from
+				let [start, end] = [drag.points[0], drag.points[drag.points.len() - 1]].map(|point| document_to_viewport.transform_point2(point));
+				let is_marquee = matches!(drag.tool, SelectionTool::RectangularMarquee | SelectionTool::EllipticalMarquee);
+				if is_marquee && (start - end).abs().cmplt(DVec2::ONE).any() {
+					if drag.mode == SelectionMode::Replace {
+						self.selection = PixelSelection::default();
+					}
+				} else {
+					self.commit(drag);
+				}
+				self.end_drag(responses);
+			}
+			Feather => {
+				if !self.selection.is_empty() && self.feather_radius > 0. {
+					self.selection.push(SelectionEdit::Feather { radius: self.feather_radius });
+					self.selection_changed(responses);
+				}
+			}
+			Grow => {
+				if !self.selection.is_empty() && self.grow_amount > 0. {
+					self.selection.push(SelectionEdit::Grow { amount: self.grow_amount });
+					self.selection_changed(responses);
+				}
+			}
+			Invert => {
+				// Without a selection, everything is already editable and there is nothing to invert
+				if !self.selection.is_empty() {
+					self.selection.push(SelectionEdit::Invert);
+					self.selection_changed(responses);
+				}
+			}
+			PointerMove => {
+				let Some(drag) = &mut self.drag else { return };
+				match drag.tool {
+					SelectionTool::Lasso => {
+						let last = document_to_viewport.transform_point2(drag.points[drag.points.len() - 1]);
+						if last.distance(ipp.mouse.position) >= 1. {
+							drag.points.push(document_mouse);
+						}
+					}
+					_ => {
+						if let Some(last) = drag.points.last_mut() {
+							*last = document_mouse;
+						}
+					}
+				}
+				responses.add(RenderOverlays);
+			}
+			RenderOverlays => {
+				let to_viewport = |points: Vec<DVec2>| points.into_iter().map(|point| document_to_viewport.transform_point2(point));
+
+				let mut outlines = Vec::new();
+				for edit in &self.selection.edits {
+					match edit {
+						SelectionEdit::Shape { shape, .. } => outlines.push(bezier_rs::Subpath::<ManipulatorGroupId>::from_anchors(to_viewport(shape.outline()), true)),
+						SelectionEdit::MagicWand { position, .. } => {
+							let center = document_to_viewport.transform_point2(*position);
+							let radius = DVec2::splat(MAGIC_WAND_MARKER_RADIUS);
+							outlines.push(bezier_rs::Subpath::new_ellipse(center - radius, center + radius));
+						}
+						_ => {}
+					}
+				}
+				if let Some(drag) = &self.drag {
+					let preview = drag_shape(drag).map(|shape| shape.outline()).unwrap_or_else(|| drag.points.clone());
+					outlines.push(bezier_rs::Subpath::from_anchors(to_viewport(preview), drag.tool != SelectionTool::PolygonalLasso));
+				}
+
+				let subpath = Subpath::from_bezier_rs(outlines.iter());
+				match self.overlay_path.clone() {
+					Some(path) => responses.add(DocumentMessage::Overlays(Operation::SetShapePath { path, subpath }.into())),
+					None => {
+						let path = vec![generate_uuid()];
+						let mut stroke = Stroke::new(Some(COLOR_ACCENT), 1.);
+						stroke.dash_lengths = vec![4., 4.];
+						let operation = Operation::AddShape {
+							path: path.clone(),
+							transform: DAffine2::IDENTITY.to_cols_array(),
+							style: style::PathStyle::new(Some(stroke), style::Fill::None),
+							insert_index: -1,
+							subpath,
+						};
+						responses.add(DocumentMessage::Overlays(operation.into()));
+						self.overlay_path = Some(path);
+					}
+				}
+			}
+			SelectAll => {
+				self.selection = PixelSelection::default();
+				self.selection.push(SelectionEdit::Invert);
+				self.selection_changed(responses);
+			}
+			SetAntiAlias { anti_alias } => {
+				self.anti_alias = anti_alias;
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
+			SetContiguous { contiguous } => {
+				self.contiguous = contiguous;
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
+			SetFeatherRadius { radius } => {
+				self.feather_radius = radius.max(0.);
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
+			SetGrowAmount { amount } => {
+				self.grow_amount = amount.max(0.);
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
+			SetMode { mode } => {
+				self.mode = mode;
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
+			SetTolerance { tolerance } => {
+				self.tolerance = tolerance.clamp(0., 100.);
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
+			SetTool { tool } => {
+				if self.drag.take().is_some() {
+					self.end_drag(responses);
+				}
+				self.tool = tool;
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
+			Shrink => {
+				if !self.selection.is_empty() && self.grow_amount > 0. {
+					self.selection.push(SelectionEdit::Grow { amount: -self.grow_amount });
+					self.selection_changed(responses);
+				}
+			}
+		}
+	}
+
+	fn actions(&self) -> ActionList {
+		match &self.drag {
+			Some(drag) if drag.tool == SelectionTool::PolygonalLasso => actions!(PixelSelectionMessageDiscriminant;
+				PointerMove,
+				DragStart,
+				DragStop,
+				Confirm,
+				Abort,
+			),
+			Some(_) => actions!(PixelSelectionMessageDiscriminant;
+				PointerMove,
+				DragStop,
+				Abort,
+			),
+			None => vec![],
+		}
+	}
+}
+
+impl PixelSelectionMessageHandler {
+	/// The actions available to the input mapper, where selections can only be drawn and changed by shortcuts in Select Mode
+	pub fn actions_with_select_mode(&self, select_mode: bool) -> ActionList {
+		let mut common = self.actions();
+		if select_mode && self.drag.is_none() {
+			common.extend(actions!(PixelSelectionMessageDiscriminant;
+				DragStart,
+				SelectAll,
+				Clear,
+				Invert,
+			));
+		}
+		common
+	}
+
+	/// The selection which should mask edits, if anything has been selected
+	pub fn active_selection(&self) -> Option<&PixelSelection> {
+		(!self.selection.is_empty()).then_some(&self.selection)
+	}
+
+	/// Forgets the overlay drawn so far, after the overlays document has been cleared, so it is drawn anew
+	pub fn forget_overlays(&mut self) {
+		self.overlay_path = None;
+	}
+
+	fn commit(&mut self, drag: SelectionDrag) {
+		let shape = drag_shape(&drag).or_else(|| (drag.points.len() >= 3).then(|| SelectionShape::Polygon { points: drag.points }));
+		let Some(shape) = shape else { return };
+
+		let edit = SelectionEdit::Shape {
+			shape,
+			mode: drag.mode,
+			anti_alias: self.anti_alias,
+		};
+		self.push_edit(edit);
+	}
+
+	/// Adds an edit to the selection, where having nothing selected is treated like having everything selected
+	fn push_edit(&mut self, mut edit: SelectionEdit) {
+		if self.selection.is_empty() {
+			let mode = match &mut edit {
+				SelectionEdit::Shape { mode, .. } | SelectionEdit::MagicWand { mode, .. } => mode,
+				_ => return,
+			};
+			match *mode {
+				SelectionMode::Subtract => return,
+				SelectionMode::Intersect => *mode = SelectionMode::Replace,
+				_ => {}
+			}
+		}
+		self.selection.push(edit);
+	}
+
+	fn selection_changed(&self, responses: &mut VecDeque<Message>) {
+		responses.add(PixelSelectionMessage::RenderOverlays);
+		responses.add(PortfolioMessage::UpdateDocumentWidgets);
+	}
+
+	fn begin_drag(&mut self, responses: &mut VecDeque<Message>) {
+		let mut hint_data = vec![HintGroup(vec![HintInfo::mouse(MouseMotion::Rmb, "Cancel"), HintInfo::keys([Key::Escape], "Cancel")])];
+		if self.tool == SelectionTool::PolygonalLasso {
+			hint_data.push(HintGroup(vec![HintInfo::mouse(MouseMotion::Lmb, "Add Point"), HintInfo::keys([Key::Enter], "Close Polygon")]));
+		}
+		responses.add(FrontendMessage::UpdateMouseCursor { cursor: MouseCursorIcon::Crosshair });
+		responses.add(FrontendMessage::UpdateInputHints { hint_data: HintData(hint_data) });
+		responses.add(PixelSelectionMessage::RenderOverlays);
+	}
+
+	fn end_drag(&mut self, responses: &mut VecDeque<Message>) {
+		self.selection_changed(responses);
+		responses.add(ToolMessage::UpdateCursor);
+		responses.add(ToolMessage::UpdateHints);
+	}
+
+	/// The popover menu with the options of the selection tools and the commands refining the selection
+	pub fn selection_layout(&self) -> Vec<LayoutGroup> {
+		let has_selection = !self.selection.is_empty();
+		let number_row = |label: &str, input: NumberInput, button: TextButton| LayoutGroup::Row {
+			widgets: vec![
+				TextLabel::new(label).table_align(true).widget_holder(),
+				Separator::new(SeparatorType::Unrelated).widget_holder(),
+				input.widget_holder(),
+				Separator::new(SeparatorType::Related).widget_holder(),
+				button.widget_holder(),
+			],
+		};
+
+		vec![
+			LayoutGroup::Row {
+				widgets: vec![
+					CheckboxInput::new(self.anti_alias)
+						.tooltip("Partially select the pixels along the edges of new selections")
+						.on_update(|input: &CheckboxInput| PixelSelectionMessage::SetAntiAlias { anti_alias: input.checked }.into())
+						.widget_holder(),
+					Separator::new(SeparatorType::Unrelated).widget_holder(),
+					TextLabel::new("Anti-aliasing").table_align(false).min_width(60).widget_holder(),
+					Separator::new(SeparatorType::Section).widget_holder(),
+					CheckboxInput::new(self.contiguous)
+						.tooltip("Only select the pixels connected to the one clicked with the magic wand")
+						.disabled(self.tool != SelectionTool::MagicWand)
+						.on_update(|input: &CheckboxInput| PixelSelectionMessage::SetContiguous { contiguous: input.checked }.into())
+						.widget_holder(),
+					Separator::new(SeparatorType::Unrelated).widget_holder(),
+					TextLabel::new("Contiguous").table_align(false).min_width(60).widget_holder(),
+				],
+			},
+			LayoutGroup::Row {
+				widgets: vec![
+					TextLabel::new("Tolerance").table_align(true).widget_holder(),
+					Separator::new(SeparatorType::Unrelated).widget_holder(),
+					NumberInput::new(Some(self.tolerance))
+						.unit("%")
+						.min(0.)
+						.max(100.)
+						.mode_range()
+						.tooltip("How different a pixel's color may be from the one clicked with the magic wand to still be selected")
+						.disabled(self.tool != SelectionTool::MagicWand)
+						.on_update(|input: &NumberInput| PixelSelectionMessage::SetTolerance { tolerance: input.value.unwrap() }.into())
+						.widget_holder(),
+				],
+			},
+			number_row(
+				"Feather",
+				NumberInput::new(Some(self.feather_radius))
+					.unit(" px")
+					.min(0.)
+					.on_update(|input: &NumberInput| PixelSelectionMessage::SetFeatherRadius { radius: input.value.unwrap() }.into()),
+				TextButton::new("Apply")
+					.tooltip("Soften the edge of the selection over this radius")
+					.disabled(!has_selection)
+					.on_update(|_| PixelSelectionMessage::Feather.into()),
+			),
+			LayoutGroup::Row {
+				widgets: vec![
+					TextLabel::new("Grow/Shrink").table_align(true).widget_holder(),
+					Separator::new(SeparatorType::Unrelated).widget_holder(),
+					NumberInput::new(Some(self.grow_amount))
+						.unit(" px")
+						.min(0.)
+						.on_update(|input: &NumberInput| PixelSelectionMessage::SetGrowAmount { amount: input.value.unwrap() }.into())
+						.widget_holder(),
+					Separator::new(SeparatorType::Related).widget_holder(),
+					TextButton::new("Grow")
+						.tooltip("Expand the selection by this distance")
+						.disabled(!has_selection)
+						.on_update(|_| PixelSelectionMessage::Grow.into())
+						.widget_holder(),
+					Separator::new(SeparatorType::Related).widget_holder(),
+					TextButton::new("Shrink")
+						.tooltip("Contract the selection by this distance")
+						.disabled(!has_selection)
+						.on_update(|_| PixelSelectionMessage::Shrink.into())
+						.widget_holder(),
+				],
+			},
+			LayoutGroup::Row {
+				widgets: vec![
+					TextButton::new("Select All").on_update(|_| PixelSelectionMessage::SelectAll.into()).widget_holder(),
+					Separator::new(SeparatorType::Related).widget_holder(),
+					TextButton::new("Deselect").disabled(!has_selection).on_update(|_| PixelSelectionMessage::Clear.into()).widget_holder(),
+					Separator::new(SeparatorType::Related).widget_holder(),
+					TextButton::new("Invert").disabled(!has_selection).on_update(|_| PixelSelectionMessage::Invert.into()).widget_holder(),
+				],
+			},
+		]
+	}
+
+	/// The document bar widgets choosing the selection tool and how new selections combine with the existing one
+	pub fn document_bar_widgets(&self) -> Vec<WidgetHolder> {
+		let tool_entries = SelectionTool::ALL
+			.into_iter()
+			.map(|tool| {
+				RadioEntryData::default()
+					.value(tool.label())
+					.label(tool.label())
+					.tooltip(tool.to_string())
+					.on_update(move |_| PixelSelectionMessage::SetTool { tool }.into())
+			})
+			.collect();
+
+		let modes = [
+			(SelectionMode::Replace, "New", "New Selection"),
+			(SelectionMode::Add, "Add", "Add to Selection (Shift)"),
+			(SelectionMode::Subtract, "Subtract", "Subtract from Selection (Alt)"),
+			(SelectionMode::Intersect, "Intersect", "Intersect with Selection (Shift+Alt)"),
+		];
+		let mode_entries = modes
+			.into_iter()
+			.map(|(mode, label, tooltip)| {
+				RadioEntryData::default()
+					.value(label)
+					.label(label)
+					.tooltip(tooltip)
+					.on_update(move |_| PixelSelectionMessage::SetMode { mode }.into())
+			})
+			.collect();
+
+		vec![
+			RadioInput::new(tool_entries)
+				.selected_index(SelectionTool::ALL.iter().position(|&tool| tool == self.tool).map(|index| index as u32))
+				.widget_holder(),
+			Separator::new(SeparatorType::Section).widget_holder(),
+			RadioInput::new(mode_entries)
+				.selected_index(modes.iter().position(|&(mode, ..)| mode == self.mode).map(|index| index as u32))
+				.widget_holder(),
+			Separator::new(SeparatorType::Section).widget_holder(),
+			PopoverButton::new("Selection", "Options of the selection tools, and refinements of the selection")
+				.options_widget(self.selection_layout())
+				.widget_holder(),
+			Separator::new(SeparatorType::Section).widget_holder(),
+		]
+	}
+}
+
+/// The marquee shape being dragged out between its two corners, if the drag is of a marquee
+fn drag_shape(drag: &SelectionDrag) -> Option<SelectionShape> {
+	let (start, end) = (drag.points[0], drag.points[drag.points.len() - 1]);
+	let transform = DAffine2::from_scale_angle_translation(end - start, 0., start);
+	match drag.tool {
+		SelectionTool::RectangularMarquee => Some(SelectionShape::Rectangle { transform }),
+		SelectionTool::EllipticalMarquee => Some(SelectionShape::Ellipse { transform }),
+		_ => None,
+	}
+}
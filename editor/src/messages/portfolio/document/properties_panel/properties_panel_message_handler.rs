@@ -27,6 +27,7 @@ impl<'a> MessageHandler<PropertiesPanelMessage, (&PersistentData, PropertiesPane
 			selected_layers,
 			node_graph_message_handler,
 			executor,
+			timeline,
 		} = data;
 		let render_data = RenderData::new(&persistent_data.font_cache, ViewMode::Normal, None);
 
@@ -132,7 +133,7 @@ impl<'a> MessageHandler<PropertiesPanelMessage, (&PersistentData, PropertiesPane
 			ResendActiveProperties => {
 				if let Some(path) = self.active_selection.clone() {
 					let layer = artwork_document.layer(&path).unwrap();
-					register_artwork_layer_properties(artwork_document, path, layer, responses, persistent_data, node_graph_message_handler, executor);
+					register_artwork_layer_properties(artwork_document, path, layer, responses, persistent_data, node_graph_message_handler, executor, timeline);
 				} else {
 					let context = crate::messages::portfolio::document::node_graph::NodePropertiesContext {
 						persistent_data,
@@ -142,6 +143,7 @@ impl<'a> MessageHandler<PropertiesPanelMessage, (&PersistentData, PropertiesPane
 						layer_path: &[],
 						executor,
 						network: &artwork_document.document_network,
+						timeline,
 					};
 					register_document_graph_properties(context, node_graph_message_handler, document_name);
 				}
@@ -9,6 +9,7 @@ use crate::node_graph_executor::NodeGraphExecutor;
 use document_legacy::document::Document;
 use document_legacy::layers::layer_info::{Layer, LayerDataType};
 use document_legacy::layers::style::{Fill, Gradient, GradientType, LineCap, LineJoin, RenderData, Stroke, ViewMode};
+use graphene_core::animation::Timeline;
 use graphene_core::raster::color::Color;
 
 use glam::{DAffine2, DVec2};
@@ -64,6 +65,7 @@ pub fn apply_transform_operation(layer: &Layer, transform_op: TransformOp, value
 	((pivot * delta * pivot.inverse()) * layer.transform).to_cols_array()
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn register_artwork_layer_properties(
 	document: &Document,
 	layer_path: Vec<document_legacy::LayerId>,
@@ -72,6 +74,7 @@ pub fn register_artwork_layer_properties(
 	persistent_data: &PersistentData,
 	node_graph_message_handler: &NodeGraphMessageHandler,
 	executor: &mut NodeGraphExecutor,
+	timeline: Timeline,
 ) {
 	let options_bar = vec![LayoutGroup::Row {
 		widgets: vec![
@@ -112,6 +115,7 @@ pub fn register_artwork_layer_properties(
 				layer_path: &layer_path,
 				executor,
 				network: &layer.network,
+				timeline,
 			};
 			node_graph_message_handler.collate_properties(&mut context, &mut properties_sections);
 
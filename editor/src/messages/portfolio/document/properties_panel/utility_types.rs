@@ -1,5 +1,6 @@
 use document_legacy::document::Document as DocumentLegacy;
 use document_legacy::LayerId;
+use graphene_core::animation::Timeline;
 
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +12,7 @@ pub struct PropertiesPanelMessageHandlerData<'a> {
 	pub selected_layers: &'a mut dyn Iterator<Item = &'a [LayerId]>,
 	pub node_graph_message_handler: &'a NodeGraphMessageHandler,
 	pub executor: &'a mut NodeGraphExecutor,
+	pub timeline: Timeline,
 }
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize, specta::Type)]
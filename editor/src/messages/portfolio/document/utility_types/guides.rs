@@ -0,0 +1,240 @@
+use glam::DVec2;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The most lines of each family drawn for the grid, beyond which it is too dense to be useful and is hidden.
+const GRID_MAX_LINES: f64 = 500.;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize, Hash, specta::Type)]
+pub enum GuideDirection {
+	/// A guide along the x axis, dragged from the top ruler
+	Horizontal,
+	/// A guide along the y axis, dragged from the left ruler
+	Vertical,
+}
+
+impl GuideDirection {
+	/// The axis whose coordinate is fixed by a guide in this direction
+	pub fn axis(self) -> usize {
+		match self {
+			GuideDirection::Horizontal => 1,
+			GuideDirection::Vertical => 0,
+		}
+	}
+
+	/// The document space direction along which a guide in this direction extends
+	pub fn line_direction(self) -> DVec2 {
+		match self {
+			GuideDirection::Horizontal => DVec2::X,
+			GuideDirection::Vertical => DVec2::Y,
+		}
+	}
+}
+
+impl fmt::Display for GuideDirection {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			GuideDirection::Horizontal => write!(f, "Horizontal"),
+			GuideDirection::Vertical => write!(f, "Vertical"),
+		}
+	}
+}
+
+/// A guide line spanning the whole canvas, positioned in document space
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Guide {
+	pub id: u64,
+	pub direction: GuideDirection,
+	pub position: f64,
+}
+
+impl Guide {
+	/// The document space point on the guide closest to the given one
+	pub fn closest_point(&self, point: DVec2) -> DVec2 {
+		let mut closest = point;
+		closest[self.direction.axis()] = self.position;
+		closest
+	}
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum GridType {
+	Rectangular {
+		spacing: DVec2,
+	},
+	/// Vertical lines crossed by two families of diagonals, at `angle_a` degrees below and `angle_b` degrees above the horizontal.
+	/// The diagonals of each family cross the vertical lines every `y_axis_spacing` units.
+	Isometric {
+		y_axis_spacing: f64,
+		angle_a: f64,
+		angle_b: f64,
+	},
+}
+
+impl GridType {
+	pub const RECTANGULAR: GridType = GridType::Rectangular { spacing: DVec2::splat(10.) };
+	pub const ISOMETRIC: GridType = GridType::Isometric {
+		y_axis_spacing: 10.,
+		angle_a: 30.,
+		angle_b: 30.,
+	};
+}
+
+impl Default for GridType {
+	fn default() -> Self {
+		Self::RECTANGULAR
+	}
+}
+
+/// The document grid, which is only ever drawn as an overlay and is never part of the artwork or its exports
+#[derive(PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct GridSettings {
+	pub origin: DVec2,
+	pub grid_type: GridType,
+}
+
+impl GridSettings {
+	/// The document space grid intersection closest to the given point
+	pub fn closest_point(&self, point: DVec2) -> DVec2 {
+		let relative = point - self.origin;
+		match self.grid_type {
+			GridType::Rectangular { spacing } => {
+				if spacing.cmple(DVec2::ZERO).any() {
+					return point;
+				}
+				self.origin + (relative / spacing).round() * spacing
+			}
+			GridType::Isometric { y_axis_spacing, angle_a, angle_b } => {
+				let Some(column_spacing) = isometric_column_spacing(y_axis_spacing, angle_a, angle_b) else {
+					return point;
+				};
+				let tan_a = angle_a.to_radians().tan();
+
+				// Intersections lie on the vertical lines, offset along each one by the diagonal passing through the origin
+				let column = relative.x / column_spacing;
+				[column.floor(), column.ceil()]
+					.into_iter()
+					.map(|column| {
+						let x = column * column_spacing;
+						let offset = x * tan_a;
+						let y = offset + ((relative.y - offset) / y_axis_spacing).round() * y_axis_spacing;
+						self.origin + DVec2::new(x, y)
+					})
+					.min_by(|a, b| a.distance_squared(point).total_cmp(&b.distance_squared(point)))
+					.unwrap_or(point)
+			}
+		}
+	}
+
+	/// The document space lines of the grid which cross the given document space bounds, or none if there would be too many to draw
+	pub fn lines(&self, [min, max]: [DVec2; 2]) -> Vec<[DVec2; 2]> {
+		let (min, max) = (min - self.origin, max - self.origin);
+		let line = |start: DVec2, end: DVec2| [self.origin + start, self.origin + end];
+		let range = |start: f64, end: f64, spacing: f64| {
+			let (first, last) = ((start / spacing).ceil(), (end / spacing).floor());
+			(last - first < GRID_MAX_LINES).then(|| (first as i64..=last as i64).map(move |index| index as f64 * spacing))
+		};
+
+		match self.grid_type {
+			GridType::Rectangular { spacing } => {
+				if spacing.cmple(DVec2::ZERO).any() {
+					return Vec::new();
+				}
+				let (Some(columns), Some(rows)) = (range(min.x, max.x, spacing.x), range(min.y, max.y, spacing.y)) else {
+					return Vec::new();
+				};
+
+				let vertical = columns.map(|x| line(DVec2::new(x, min.y), DVec2::new(x, max.y)));
+				let horizontal = rows.map(|y| line(DVec2::new(min.x, y), DVec2::new(max.x, y)));
+				vertical.chain(horizontal).collect()
+			}
+			GridType::Isometric { y_axis_spacing, angle_a, angle_b } => {
+				let Some(column_spacing) = isometric_column_spacing(y_axis_spacing, angle_a, angle_b) else {
+					return Vec::new();
+				};
+				let Some(columns) = range(min.x, max.x, column_spacing) else { return Vec::new() };
+				let mut lines: Vec<_> = columns.map(|x| line(DVec2::new(x, min.y), DVec2::new(x, max.y))).collect();
+
+				// Each diagonal family is clipped to the horizontal extent of the bounds
+				for slope in [angle_a.to_radians().tan(), -angle_b.to_radians().tan()] {
+					let (low, high) = if slope >= 0. { (min.x * slope, max.x * slope) } else { (max.x * slope, min.x * slope) };
+					let Some(intercepts) = range(min.y - high, max.y - low, y_axis_spacing) else { return Vec::new() };
+					lines.extend(intercepts.map(|y| line(DVec2::new(min.x, y + min.x * slope), DVec2::new(max.x, y + max.x * slope))));
+				}
+				lines
+			}
+		}
+	}
+}
+
+/// The horizontal distance between the vertical lines of an isometric grid, where the two diagonal families intersect
+fn isometric_column_spacing(y_axis_spacing: f64, angle_a: f64, angle_b: f64) -> Option<f64> {
+	let slope_sum = angle_a.to_radians().tan() + angle_b.to_radians().tan();
+	(y_axis_spacing > 0. && slope_sum > 0. && slope_sum.is_finite()).then(|| y_axis_spacing / slope_sum)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn rectangular_closest_point() {
+		let grid = GridSettings {
+			origin: DVec2::new(5., 0.),
+			grid_type: GridType::Rectangular { spacing: DVec2::new(10., 20.) },
+		};
+		assert_eq!(grid.closest_point(DVec2::new(13., 31.)), DVec2::new(15., 40.));
+		assert_eq!(grid.closest_point(DVec2::new(-6., -9.)), DVec2::new(-5., 0.));
+	}
+
+	#[test]
+	fn isometric_closest_point() {
+		let grid = GridSettings {
+			origin: DVec2::ZERO,
+			grid_type: GridType::Isometric {
+				y_axis_spacing: 10.,
+				angle_a: 45.,
+				angle_b: 45.,
+			},
+		};
+		// With both angles at 45 degrees, the columns are 5 units apart and alternate columns are offset by 5 units
+		let closest = grid.closest_point(DVec2::new(5.5, 4.));
+		assert!(closest.abs_diff_eq(DVec2::new(5., 5.), 1e-9), "{closest}");
+		let closest = grid.closest_point(DVec2::new(9., 1.));
+		assert!(closest.abs_diff_eq(DVec2::new(10., 0.), 1e-9), "{closest}");
+	}
+
+	#[test]
+	fn grid_lines() {
+		let grid = GridSettings::default();
+		let lines = grid.lines([DVec2::new(-1., -1.), DVec2::new(25., 15.)]);
+		// Columns at 0, 10, 20 and rows at 0, 10
+		assert_eq!(lines.len(), 5);
+		assert_eq!(lines[0], [DVec2::new(0., -1.), DVec2::new(0., 15.)]);
+		assert_eq!(lines[4], [DVec2::new(-1., 10.), DVec2::new(25., 10.)]);
+
+		assert!(grid.lines([DVec2::ZERO, DVec2::splat(1e6)]).is_empty());
+	}
+
+	#[test]
+	fn isometric_lines_pass_through_intersections() {
+		let grid = GridSettings {
+			origin: DVec2::new(3., 2.),
+			grid_type: GridType::ISOMETRIC,
+		};
+		let intersection = grid.closest_point(DVec2::new(40., 40.));
+		let lines = grid.lines([DVec2::ZERO, DVec2::splat(80.)]);
+		let on_line = |[start, end]: [DVec2; 2]| (end - start).perp_dot(intersection - start).abs() < 1e-6 * (end - start).length();
+		assert_eq!(lines.iter().filter(|&&line| on_line(line)).count(), 3);
+	}
+
+	#[test]
+	fn guide_closest_point() {
+		let guide = Guide {
+			id: 0,
+			direction: GuideDirection::Horizontal,
+			position: 12.,
+		};
+		assert_eq!(guide.closest_point(DVec2::new(3., 4.)), DVec2::new(3., 12.));
+	}
+}
@@ -0,0 +1,429 @@
+use super::layered_images;
+use crate::messages::frontend::utility_types::BitDepth;
+
+use graph_craft::document::value::TaggedValue;
+use graphene_core::raster::color_management::{ToneCurve, WorkingSpace};
+use graphene_core::raster::{Color, Image, ImageFrame, Luma, LumaAlpha, Pixel, SRGBA16, SRGBA8};
+use graphene_std::color_profile::ProfileConversion;
+
+use glam::{DAffine2, UVec2};
+use image::{DynamicImage, ImageBuffer, ImageOutputFormat};
+use std::borrow::Cow;
+use std::io::Cursor;
+
+/// Decodes an image file into an image frame which keeps the file's pixel format where the graph has a matching one, instead of widening every image to [`Color`].
+///
+/// The colors are converted from the ICC profile embedded in the file, or from sRGB if it has none, to the working space of the document.
+/// OpenEXR files, whose half float channels become `RGBA16F`, are read by [`layered_images::decode_layered_image`] and give their top layer.
+pub fn decode_image(data: &[u8], working_space: WorkingSpace) -> Result<TaggedValue, String> {
+	if data.starts_with(layered_images::EXR_MAGIC_NUMBER) {
+		let image = layered_images::decode_layered_image(data, working_space)?;
+		return image.layers.into_iter().last().map(|layer| layer.image_frame).ok_or_else(|| "The file contains no layers".to_string());
+	}
+	let image = image::load_from_memory(data).map_err(|error| format!("Failed to decode image: {error}"))?;
+	let mut decoded = image_to_image_frame(image);
+	convert_to_working_space(&mut decoded, ProfileConversion::from_embedded_profile(data, working_space).as_ref(), working_space);
+	Ok(decoded)
+}
+
+/// Converts a decoded image to an image frame in the matching pixel format.
+///
+/// 8 bit color images become `SRGBA8` and 16 bit color images `SRGBA16`, both keeping the file's samples unchanged, grayscale images become `Luma` or `LumaAlpha`, and floating point images `Color`.
+/// Integer channels are taken to be gamma encoded and floating point channels linear, as image files store them.
+pub fn image_to_image_frame(image: DynamicImage) -> TaggedValue {
+	let to_linear = Color::srgb_to_linear;
+	let (width, height) = (image.width(), image.height());
+
+	match image {
+		DynamicImage::ImageLuma8(image) => TaggedValue::ImageFrameLuma(frame(width, height, image.pixels().map(|pixel| Luma(to_linear(pixel.0[0] as f32 / 255.))).collect())),
+		DynamicImage::ImageLuma16(image) => TaggedValue::ImageFrameLuma(frame(width, height, image.pixels().map(|pixel| Luma(to_linear(pixel.0[0] as f32 / 65535.))).collect())),
+		DynamicImage::ImageLumaA8(image) => TaggedValue::ImageFrameLumaAlpha(frame(
+			width,
+			height,
+			image
+				.pixels()
+				.map(|pixel| LumaAlpha::from_unassociated_alpha(to_linear(pixel.0[0] as f32 / 255.), pixel.0[1] as f32 / 255.))
+				.collect(),
+		)),
+		DynamicImage::ImageLumaA16(image) => TaggedValue::ImageFrameLumaAlpha(frame(
+			width,
+			height,
+			image
+				.pixels()
+				.map(|pixel| LumaAlpha::from_unassociated_alpha(to_linear(pixel.0[0] as f32 / 65535.), pixel.0[1] as f32 / 65535.))
+				.collect(),
+		)),
+		DynamicImage::ImageRgb16(_) | DynamicImage::ImageRgba16(_) => {
+			let image = image.into_rgba16();
+			TaggedValue::ImageFrameSRGBA16(frame(
+				width,
+				height,
+				image.pixels().map(|&image::Rgba([red, green, blue, alpha])| SRGBA16::new(red, green, blue, alpha)).collect(),
+			))
+		}
+		DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => {
+			let image = image.into_rgba32f();
+			TaggedValue::ImageFrame(frame(
+				width,
+				height,
+				image
+					.pixels()
+					.map(|&image::Rgba([red, green, blue, alpha])| Color::from_unassociated_alpha(red, green, blue, alpha))
+					.collect(),
+			))
+		}
+		image => {
+			let image = image.into_rgba8();
+			TaggedValue::ImageFrameSRGBA8(frame(
+				width,
+				height,
+				image.pixels().map(|&image::Rgba([red, green, blue, alpha])| SRGBA8::new(red, green, blue, alpha)).collect(),
+			))
+		}
+	}
+}
+
+/// Converts the colors of a decoded image frame from the profile of its file, or from sRGB without one, to the working space.
+/// Floating point frames don't use the profile, since its transfer function describes gamma encoded values.
+pub fn convert_to_working_space(image_frame: &mut TaggedValue, profile: Option<&ProfileConversion>, working_space: WorkingSpace) {
+	let is_float = matches!(image_frame, TaggedValue::ImageFrame(_) | TaggedValue::ImageFrameRGBA16F(_));
+	if let Some(conversion) = profile.filter(|_| !is_float) {
+		// Re-encoding the channels gives back the values stored in the file, which the profile describes
+		map_image_frame_colors(image_frame, |[red, green, blue]| {
+			let encoded = [red, green, blue].map(|channel| Color::linear_to_srgb(channel) as f64);
+			conversion.convert(encoded).as_vec3().to_array()
+		});
+	} else if working_space != WorkingSpace::Srgb {
+		let matrix = (working_space.from_xyz_matrix() * WorkingSpace::Srgb.to_xyz_matrix()).as_mat3();
+		map_image_frame_colors(image_frame, |channels| (matrix * glam::Vec3::from_array(channels)).to_array());
+	}
+}
+
+/// An image frame at the origin with the given pixels.
+pub fn frame<P: Pixel>(width: u32, height: u32, data: Vec<P>) -> ImageFrame<P> {
+	ImageFrame {
+		image: Image { width, height, data },
+		transform: DAffine2::IDENTITY,
+	}
+}
+
+/// Replaces the linear, unassociated red, green and blue channels of every pixel of an image frame held by any of the image frame variants of [`TaggedValue`].
+pub fn map_image_frame_colors(image_frame: &mut TaggedValue, map: impl Fn([f32; 3]) -> [f32; 3]) {
+	fn map_pixels<P: Pixel + Into<Color> + From<Color>>(image: &mut Image<P>, map: impl Fn([f32; 3]) -> [f32; 3]) {
+		for pixel in &mut image.data {
+			let color: Color = (*pixel).into();
+			let color = color.to_unassociated_alpha();
+			let [red, green, blue] = map([color.r(), color.g(), color.b()]);
+			*pixel = Color::from_unassociated_alpha(red, green, blue, color.a()).into();
+		}
+	}
+
+	match image_frame {
+		TaggedValue::ImageFrame(frame) => map_pixels(&mut frame.image, map),
+		TaggedValue::ImageFrameSRGBA8(frame) => map_pixels(&mut frame.image, map),
+		TaggedValue::ImageFrameSRGBA16(frame) => map_pixels(&mut frame.image, map),
+		TaggedValue::ImageFrameRGBA16F(frame) => map_pixels(&mut frame.image, map),
+		TaggedValue::ImageFrameLumaAlpha(frame) => map_pixels(&mut frame.image, map),
+		TaggedValue::ImageFrameLuma(frame) => map_pixels(&mut frame.image, map),
+		_ => {}
+	}
+}
+
+/// Prepares the colors of an image frame in a working space to be written to a file, which always gamma encodes them with the sRGB transfer function.
+///
+/// With an embedded profile of the working space, the channels are adjusted so they are encoded with its own transfer function instead.
+/// Without one, the colors are converted to sRGB, which files without a profile are assumed to be in.
+pub fn export_colors(image_frame: &TaggedValue, working_space: WorkingSpace, embedded_profile: bool) -> Cow<'_, TaggedValue> {
+	let transfer = working_space.transfer();
+	let map: Box<dyn Fn([f32; 3]) -> [f32; 3]> = match embedded_profile {
+		true if transfer == ToneCurve::srgb() => return Cow::Borrowed(image_frame),
+		true => Box::new(move |channels: [f32; 3]| channels.map(|channel| Color::srgb_to_linear(transfer.invert(channel as f64) as f32))),
+		false if working_space == WorkingSpace::Srgb => return Cow::Borrowed(image_frame),
+		false => {
+			let matrix = (WorkingSpace::Srgb.from_xyz_matrix() * working_space.to_xyz_matrix()).as_mat3();
+			Box::new(move |channels: [f32; 3]| (matrix * glam::Vec3::from_array(channels)).to_array())
+		}
+	};
+	let mut image_frame = image_frame.clone();
+	map_image_frame_colors(&mut image_frame, map);
+	Cow::Owned(image_frame)
+}
+
+/// The size in pixels of an image frame held by any of the image frame variants of [`TaggedValue`].
+pub fn image_frame_size(image_frame: &TaggedValue) -> Option<UVec2> {
+	let (width, height) = match image_frame {
+		TaggedValue::ImageFrame(frame) => (frame.image.width, frame.image.height),
+		TaggedValue::ImageFrameSRGBA8(frame) => (frame.image.width, frame.image.height),
+		TaggedValue::ImageFrameSRGBA16(frame) => (frame.image.width, frame.image.height),
+		TaggedValue::ImageFrameRGBA16F(frame) => (frame.image.width, frame.image.height),
+		TaggedValue::ImageFrameLumaAlpha(frame) => (frame.image.width, frame.image.height),
+		TaggedValue::ImageFrameLuma(frame) => (frame.image.width, frame.image.height),
+		_ => return None,
+	};
+	Some(UVec2::new(width, height))
+}
+
+/// Widens the pixels of an image frame held by any of the image frame variants of [`TaggedValue`] to [`Color`].
+pub fn image_frame_to_colors(image_frame: &TaggedValue) -> Option<Image<Color>> {
+	fn colors<P: Pixel + Into<Color>>(image: &Image<P>) -> Image<Color> {
+		Image {
+			width: image.width,
+			height: image.height,
+			data: image.data.iter().map(|&pixel| pixel.into()).collect(),
+		}
+	}
+
+	Some(match image_frame {
+		TaggedValue::ImageFrame(frame) => frame.image.clone(),
+		TaggedValue::ImageFrameSRGBA8(frame) => colors(&frame.image),
+		TaggedValue::ImageFrameSRGBA16(frame) => colors(&frame.image),
+		TaggedValue::ImageFrameRGBA16F(frame) => colors(&frame.image),
+		TaggedValue::ImageFrameLumaAlpha(frame) => colors(&frame.image),
+		TaggedValue::ImageFrameLuma(frame) => colors(&frame.image),
+		_ => return None,
+	})
+}
+
+/// Resamples an image frame to the given size, keeping its pixel format. The premultiplied channels are filtered, so transparent pixels don't bleed their color into their neighbors.
+pub fn resize_image_frame(image_frame: &TaggedValue, size: UVec2) -> Option<TaggedValue> {
+	fn resized<P: Pixel + From<Color>>(size: UVec2, pixels: &[Color]) -> ImageFrame<P> {
+		frame(size.x, size.y, pixels.iter().map(|&color| color.into()).collect())
+	}
+
+	if image_frame_size(image_frame)? == size {
+		return Some(image_frame.clone());
+	}
+	let colors = image_frame_to_colors(image_frame)?;
+	let samples = colors.data.iter().flat_map(|color| [color.r(), color.g(), color.b(), color.a()]).collect();
+	let image: image::Rgba32FImage = ImageBuffer::from_raw(colors.width, colors.height, samples)?;
+	let image = image::imageops::resize(&image, size.x.max(1), size.y.max(1), image::imageops::FilterType::Triangle);
+	let pixels: Vec<Color> = image
+		.pixels()
+		.map(|&image::Rgba([red, green, blue, alpha])| Color::from_rgbaf32_unchecked(red, green, blue, alpha))
+		.collect();
+	let size = UVec2::new(image.width(), image.height());
+
+	Some(match image_frame {
+		TaggedValue::ImageFrame(_) => TaggedValue::ImageFrame(resized(size, &pixels)),
+		TaggedValue::ImageFrameSRGBA8(_) => TaggedValue::ImageFrameSRGBA8(resized(size, &pixels)),
+		TaggedValue::ImageFrameSRGBA16(_) => TaggedValue::ImageFrameSRGBA16(resized(size, &pixels)),
+		TaggedValue::ImageFrameRGBA16F(_) => TaggedValue::ImageFrameRGBA16F(resized(size, &pixels)),
+		TaggedValue::ImageFrameLumaAlpha(_) => TaggedValue::ImageFrameLumaAlpha(resized(size, &pixels)),
+		TaggedValue::ImageFrameLuma(_) => TaggedValue::ImageFrameLuma(resized(size, &pixels)),
+		_ => return None,
+	})
+}
+
+/// Converts an image frame to an image of the given bit depth at its own resolution.
+/// Grayscale frames stay grayscale, and an 8 bit export of an `SRGBA8` frame or a 16 bit export of an `SRGBA16` frame writes its samples unchanged.
+pub fn image_frame_to_image(image_frame: &TaggedValue, bit_depth: BitDepth) -> Option<DynamicImage> {
+	match (image_frame, bit_depth) {
+		(TaggedValue::ImageFrameSRGBA8(frame), BitDepth::Eight) => {
+			let data = frame.image.data.iter().flat_map(|pixel| pixel.to_array()).collect();
+			return ImageBuffer::from_raw(frame.image.width, frame.image.height, data).map(DynamicImage::ImageRgba8);
+		}
+		(TaggedValue::ImageFrameSRGBA16(frame), BitDepth::Sixteen) => {
+			let data = frame.image.data.iter().flat_map(|pixel| pixel.to_array()).collect();
+			return ImageBuffer::from_raw(frame.image.width, frame.image.height, data).map(DynamicImage::ImageRgba16);
+		}
+		_ => {}
+	}
+	let grayscale = matches!(image_frame, TaggedValue::ImageFrameLumaAlpha(_) | TaggedValue::ImageFrameLuma(_));
+	let Image { width, height, data: colors } = image_frame_to_colors(image_frame)?;
+	let opaque = colors.iter().all(|color| color.a() >= 1.);
+
+	// Quantize to 16 bits first, which gives the gamma encoded, unassociated channels of the file
+	let pixels = colors.into_iter().map(|color| SRGBA16::from(color).to_array());
+	let samples: Vec<u16> = match (grayscale, opaque) {
+		(true, true) => pixels.map(|[gray, ..]| gray).collect(),
+		(true, false) => pixels.flat_map(|[gray, _, _, alpha]| [gray, alpha]).collect(),
+		(false, _) => pixels.flatten().collect(),
+	};
+
+	let image = match bit_depth {
+		BitDepth::Sixteen => match (grayscale, opaque) {
+			(true, true) => DynamicImage::ImageLuma16(ImageBuffer::from_raw(width, height, samples)?),
+			(true, false) => DynamicImage::ImageLumaA16(ImageBuffer::from_raw(width, height, samples)?),
+			(false, _) => DynamicImage::ImageRgba16(ImageBuffer::from_raw(width, height, samples)?),
+		},
+		BitDepth::Eight => {
+			let samples = samples.into_iter().map(|sample| ((sample as u32 * 255 + 32767) / 65535) as u8).collect();
+			match (grayscale, opaque) {
+				(true, true) => DynamicImage::ImageLuma8(ImageBuffer::from_raw(width, height, samples)?),
+				(true, false) => DynamicImage::ImageLumaA8(ImageBuffer::from_raw(width, height, samples)?),
+				(false, _) => DynamicImage::ImageRgba8(ImageBuffer::from_raw(width, height, samples)?),
+			}
+		}
+	};
+	Some(image)
+}
+
+/// Converts an 8 bit RGBA image to the given bit depth. Widening it to 16 bits adds no detail, but gives the file the requested format.
+pub fn rgba8_to_image(width: u32, height: u32, rgba: Vec<u8>, bit_depth: BitDepth) -> Option<DynamicImage> {
+	let image = DynamicImage::ImageRgba8(ImageBuffer::from_raw(width, height, rgba)?);
+	Some(match bit_depth {
+		BitDepth::Eight => image,
+		BitDepth::Sixteen => DynamicImage::ImageRgba16(image.into_rgba16()),
+	})
+}
+
+pub fn encode_png(image: &DynamicImage) -> Result<Vec<u8>, String> {
+	let mut data = Vec::new();
+	image
+		.write_to(&mut Cursor::new(&mut data), ImageOutputFormat::Png)
+		.map_err(|error| format!("Failed to encode PNG: {error}"))?;
+	Ok(data)
+}
+
+/// Encodes the image as a JPG file, which has no transparency, so the alpha channel is dropped.
+pub fn encode_jpg(image: &DynamicImage) -> Result<Vec<u8>, String> {
+	let mut data = Vec::new();
+	DynamicImage::ImageRgb8(image.to_rgb8())
+		.write_to(&mut Cursor::new(&mut data), ImageOutputFormat::Jpeg(92))
+		.map_err(|error| format!("Failed to encode JPG: {error}"))?;
+	Ok(data)
+}
+
+/// Encodes the images as the pages of a single TIFF file.
+pub fn encode_tiff(pages: &[DynamicImage]) -> Result<Vec<u8>, String> {
+	encode_tiff_pages(pages.iter().map(|page| (page, None)))
+}
+
+/// The tags of a TIFF page which hold its name and position on the canvas, as written for the layers of a layered file.
+pub const PAGE_NAME: u16 = 285;
+pub const X_POSITION: u16 = 286;
+pub const Y_POSITION: u16 = 287;
+
+/// Encodes the images as the pages of a single TIFF file, giving those with a layer name and position the `PageName`, `XPosition` and `YPosition` tags which layered files describe their layers with.
+/// Floating point images are written with linear 32 bit float channels.
+pub fn encode_tiff_pages<'a>(pages: impl IntoIterator<Item = (&'a DynamicImage, Option<(&'a str, UVec2)>)>) -> Result<Vec<u8>, String> {
+	use tiff::encoder::{colortype, Rational, TiffEncoder, TiffValue};
+	use tiff::tags::Tag;
+
+	fn write_page<C: colortype::ColorType>(encoder: &mut TiffEncoder<Cursor<&mut Vec<u8>>>, width: u32, height: u32, data: &[C::Inner], layer: Option<(&str, UVec2)>) -> tiff::TiffResult<()>
+	where
+		[C::Inner]: TiffValue,
+	{
+		let mut image = encoder.new_image::<C>(width, height)?;
+		if let Some((name, position)) = layer {
+			// The resolution written by the encoder is one pixel per unit, so the position is in pixels
+			let name: String = name.chars().filter(|character| character.is_ascii() && *character != '\0').collect();
+			image.encoder().write_tag(Tag::Unknown(PAGE_NAME), name.as_str())?;
+			image.encoder().write_tag(Tag::Unknown(X_POSITION), Rational { n: position.x, d: 1 })?;
+			image.encoder().write_tag(Tag::Unknown(Y_POSITION), Rational { n: position.y, d: 1 })?;
+		}
+		if C::BITS_PER_SAMPLE.len() == 4 {
+			// The fourth sample is unassociated alpha
+			image.encoder().write_tag(Tag::ExtraSamples, 2_u16)?;
+		}
+		image.write_data(data)
+	}
+
+	let error = |error: tiff::TiffError| format!("Failed to encode TIFF: {error}");
+	let mut data = Vec::new();
+	{
+		let mut encoder = TiffEncoder::new(Cursor::new(&mut data)).map_err(error)?;
+		for (page, layer) in pages {
+			let (width, height) = (page.width(), page.height());
+			let written = match page {
+				DynamicImage::ImageLuma8(image) => write_page::<colortype::Gray8>(&mut encoder, width, height, image.as_raw(), layer),
+				DynamicImage::ImageLuma16(image) => write_page::<colortype::Gray16>(&mut encoder, width, height, image.as_raw(), layer),
+				DynamicImage::ImageRgba8(image) => write_page::<colortype::RGBA8>(&mut encoder, width, height, image.as_raw(), layer),
+				// TIFF readers rarely support gray with alpha, so it is written as RGBA
+				DynamicImage::ImageLumaA8(_) => write_page::<colortype::RGBA8>(&mut encoder, width, height, page.to_rgba8().as_raw(), layer),
+				DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => write_page::<colortype::RGBA32Float>(&mut encoder, width, height, page.to_rgba32f().as_raw(), layer),
+				_ => write_page::<colortype::RGBA16>(&mut encoder, width, height, page.to_rgba16().as_raw(), layer),
+			};
+			written.map_err(error)?;
+		}
+	}
+	Ok(data)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn sixteen_bit_png_round_trip() {
+		let samples: Vec<u16> = (0..4 * 3 * 2).map(|index| (index * 2731 + 1) as u16).collect();
+		let png = encode_png(&DynamicImage::ImageRgba16(ImageBuffer::from_raw(3, 2, samples.clone()).unwrap())).unwrap();
+
+		let image_frame = decode_image(&png, WorkingSpace::Srgb).unwrap();
+		assert!(matches!(image_frame, TaggedValue::ImageFrameSRGBA16(_)));
+		assert_eq!(image_frame_size(&image_frame), Some(UVec2::new(3, 2)));
+
+		let DynamicImage::ImageRgba16(exported) = image_frame_to_image(&image_frame, BitDepth::Sixteen).unwrap() else {
+			panic!("Expected a 16 bit RGBA image");
+		};
+		assert_eq!(exported.into_raw(), samples);
+	}
+
+	#[test]
+	fn translucent_eight_bit_png_round_trip() {
+		let samples: Vec<u8> = vec![255, 128, 0, 1, 12, 34, 56, 100, 200, 150, 100, 255, 0, 0, 0, 0];
+		let png = encode_png(&DynamicImage::ImageRgba8(ImageBuffer::from_raw(2, 2, samples.clone()).unwrap())).unwrap();
+
+		let image_frame = decode_image(&png, WorkingSpace::Srgb).unwrap();
+		assert!(matches!(image_frame, TaggedValue::ImageFrameSRGBA8(_)));
+
+		let DynamicImage::ImageRgba8(exported) = image_frame_to_image(&image_frame, BitDepth::Eight).unwrap() else {
+			panic!("Expected an 8 bit RGBA image");
+		};
+		assert_eq!(exported.into_raw(), samples);
+	}
+
+	#[test]
+	fn half_float_exr_becomes_rgba16f() {
+		let color = Color::from_rgbaf32_unchecked(2., 0.5, 0.25, 0.5);
+		let image = layered_images::LayeredImage {
+			size: UVec2::new(2, 1),
+			layers: vec![layered_images::ImageLayer::new(
+				"Half".to_string(),
+				TaggedValue::ImageFrameRGBA16F(frame(2, 1, vec![color.into(); 2])),
+				glam::IVec2::ZERO,
+			)],
+			groups: Vec::new(),
+		};
+		let exr = layered_images::encode_exr(&image, WorkingSpace::Srgb).unwrap();
+
+		let image_frame = decode_image(&exr, WorkingSpace::Srgb).unwrap();
+		assert!(matches!(image_frame, TaggedValue::ImageFrameRGBA16F(_)));
+		assert_eq!(image_frame_to_colors(&image_frame).unwrap().data, [color; 2]);
+	}
+
+	#[test]
+	fn grayscale_stays_grayscale() {
+		let samples: Vec<u16> = vec![0, 1, 1000, 30000, 50000, 65535];
+		let png = encode_png(&DynamicImage::ImageLuma16(ImageBuffer::from_raw(3, 2, samples.clone()).unwrap())).unwrap();
+
+		let image_frame = decode_image(&png, WorkingSpace::Srgb).unwrap();
+		assert!(matches!(image_frame, TaggedValue::ImageFrameLuma(_)));
+
+		let DynamicImage::ImageLuma16(exported) = image_frame_to_image(&image_frame, BitDepth::Sixteen).unwrap() else {
+			panic!("Expected a 16 bit grayscale image");
+		};
+		assert_eq!(exported.into_raw(), samples);
+	}
+
+	#[test]
+	fn resizing_keeps_the_pixel_format() {
+		let image_frame = TaggedValue::ImageFrameSRGBA16(frame(4, 2, vec![Color::from_rgba8_srgb(255, 128, 0, 255).into(); 8]));
+		let resized = resize_image_frame(&image_frame, UVec2::new(2, 1)).unwrap();
+		assert!(matches!(resized, TaggedValue::ImageFrameSRGBA16(_)));
+		assert_eq!(image_frame_size(&resized), Some(UVec2::new(2, 1)));
+		assert_eq!(image_frame_to_colors(&resized).unwrap().data[0], image_frame_to_colors(&image_frame).unwrap().data[0]);
+	}
+
+	#[test]
+	fn tiff_pages() {
+		let page = rgba8_to_image(2, 2, vec![255; 16], BitDepth::Sixteen).unwrap();
+		let tiff = encode_tiff(&[page.clone(), page]).unwrap();
+
+		let mut decoder = tiff::decoder::Decoder::new(Cursor::new(tiff)).unwrap();
+		assert_eq!(decoder.colortype().unwrap(), tiff::ColorType::RGBA(16));
+		assert!(decoder.more_images());
+		decoder.next_image().unwrap();
+		assert!(!decoder.more_images());
+	}
+}
@@ -0,0 +1,491 @@
+//! Reading and writing image files made of several named layers: the parts and layers of OpenEXR files, the pages of TIFF files, and the layers of Photoshop documents.
+//!
+//! OpenEXR files keep their half and float channels, which are linear like [`Color`], so their HDR values survive the round trip through the document.
+
+use super::image_files::{self, frame};
+use super::psd;
+use crate::messages::frontend::utility_types::BitDepth;
+
+use graph_craft::document::value::TaggedValue;
+use graphene_core::raster::color_management::{chromaticity_to_xyz, rgb_to_xyz_matrix, IccProfile, WorkingSpace, D65_CHROMATICITY};
+use graphene_core::raster::{BlendMode, Color, Luma, LumaAlpha, RGBA16F};
+use graphene_std::color_profile::ProfileConversion;
+
+use glam::{IVec2, Mat3, UVec2, Vec3};
+use image::{DynamicImage, ImageBuffer};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+
+pub const EXR_MAGIC_NUMBER: &[u8] = &[0x76, 0x2F, 0x31, 0x01];
+const TIFF_LITTLE_ENDIAN: &[u8] = b"II*\0";
+const TIFF_BIG_ENDIAN: &[u8] = b"MM\0*";
+/// The TIFF tag holding the ICC profile of a page.
+const TIFF_ICC_PROFILE: u16 = 34675;
+
+/// A layer of a layered image file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImageLayer {
+	pub name: String,
+	/// Any of the image frame variants of [`TaggedValue`]
+	pub image_frame: TaggedValue,
+	/// The position of the top left corner of the layer on the canvas, in pixels.
+	pub offset: IVec2,
+	pub blend_mode: BlendMode,
+	/// From 0 to 1.
+	pub opacity: f64,
+	pub visible: bool,
+	/// The index of the group in [`LayeredImage::groups`] which the layer is in, or `None` if it isn't in a group.
+	pub group: Option<usize>,
+}
+
+impl ImageLayer {
+	/// A visible, fully opaque layer with the normal blend mode, outside of any group.
+	pub fn new(name: String, image_frame: TaggedValue, offset: IVec2) -> Self {
+		Self {
+			name,
+			image_frame,
+			offset,
+			blend_mode: BlendMode::Normal,
+			opacity: 1.,
+			visible: true,
+			group: None,
+		}
+	}
+}
+
+/// A group of layers, which can itself be in a group.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LayerGroup {
+	pub name: String,
+	pub blend_mode: BlendMode,
+	/// From 0 to 1.
+	pub opacity: f64,
+	pub visible: bool,
+	/// The index of the group in [`LayeredImage::groups`] which this group is in, which always comes before this group.
+	pub parent: Option<usize>,
+}
+
+/// The layers of an image file and the size of the canvas they are placed on.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LayeredImage {
+	pub size: UVec2,
+	/// From the bottom layer to the top one. The layers of a group come one after the other.
+	pub layers: Vec<ImageLayer>,
+	pub groups: Vec<LayerGroup>,
+}
+
+/// Whether the file is an OpenEXR, TIFF or Photoshop file, which are decoded by [`decode_layered_image`] since they can hold several layers.
+pub fn is_layered_format(data: &[u8]) -> bool {
+	[EXR_MAGIC_NUMBER, TIFF_LITTLE_ENDIAN, TIFF_BIG_ENDIAN].iter().any(|magic_number| data.starts_with(magic_number)) || psd::is_psd(data)
+}
+
+/// Decodes the layers of an OpenEXR, TIFF or Photoshop file, converting their colors to the working space.
+pub fn decode_layered_image(data: &[u8], working_space: WorkingSpace) -> Result<LayeredImage, String> {
+	let image = if data.starts_with(EXR_MAGIC_NUMBER) {
+		decode_exr(data, working_space)?
+	} else if psd::is_psd(data) {
+		psd::decode_psd(data, working_space)?
+	} else {
+		decode_tiff(data, working_space)?
+	};
+	if image.layers.is_empty() {
+		return Err("The file contains no layers with color or grayscale channels".to_string());
+	}
+	Ok(image)
+}
+
+/// The channels of a layer of an OpenEXR file, with their names after the layer name.
+type ExrChannels<'a> = Vec<(String, &'a exr::prelude::AnyChannel<exr::prelude::FlatSamples>)>;
+
+/// Reads every layer of every part of an OpenEXR file. Within a part, channels are grouped into layers by the prefix before the last `.` of their names,
+/// so `diffuse.R`, `diffuse.G` and `diffuse.B` become the layer `diffuse`. Groups with `R`, `G` and `B` channels become color layers,
+/// and groups with a `Y` channel, or only a single channel such as a depth pass, become grayscale layers.
+fn decode_exr(data: &[u8], working_space: WorkingSpace) -> Result<LayeredImage, String> {
+	use exr::prelude::*;
+
+	let image = read()
+		.no_deep_data()
+		.largest_resolution_level()
+		.all_channels()
+		.all_layers()
+		.all_attributes()
+		.from_buffered(Cursor::new(data))
+		.map_err(|error| format!("Failed to decode EXR: {error}"))?;
+
+	let display_window = image.attributes.display_window;
+	// Files without chromaticities are in the primaries of sRGB
+	let xy = |chromaticity: Vec2<f32>| (chromaticity.0 as f64, chromaticity.1 as f64);
+	let (primaries, white) = image.attributes.chromaticities.map_or((WorkingSpace::Srgb.primaries(), D65_CHROMATICITY), |chromaticities| {
+		([xy(chromaticities.red), xy(chromaticities.green), xy(chromaticities.blue)], xy(chromaticities.white))
+	});
+	// Matching the chromaticities to a working space avoids the rounding of the values written in the file adding a slight tint
+	let matches = |a: (f64, f64), b: (f64, f64)| (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3;
+	let file_space = WorkingSpace::ALL
+		.into_iter()
+		.find(|space| matches(white, D65_CHROMATICITY) && space.primaries().into_iter().zip(primaries).all(|(a, b)| matches(a, b)));
+	let to_xyz = file_space.map_or_else(|| rgb_to_xyz_matrix(primaries, chromaticity_to_xyz(white)), WorkingSpace::to_xyz_matrix);
+	let to_working_space = match file_space == Some(working_space) {
+		true => Mat3::IDENTITY,
+		false => (working_space.from_xyz_matrix() * to_xyz).as_mat3(),
+	};
+
+	let mut layers = Vec::new();
+	for part in &image.layer_data {
+		let part_name = part.attributes.layer_name.as_ref().map(Text::to_string);
+		let position = part.attributes.layer_position;
+		let offset = IVec2::new(position.0 - display_window.position.0, position.1 - display_window.position.1);
+		let size = UVec2::new(part.size.0 as u32, part.size.1 as u32);
+
+		// Subsampled channels, such as the chroma of luminance and chroma images, don't have a sample for every pixel
+		let channels = part.channel_data.list.iter().filter(|channel| channel.sampling == Vec2(1, 1));
+		let mut groups: Vec<(Option<String>, ExrChannels)> = Vec::new();
+		for channel in channels {
+			let full_name = channel.name.to_string();
+			let (group, name) = match full_name.rsplit_once('.') {
+				Some((group, name)) => (Some(group.to_string()), name.to_string()),
+				None => (None, full_name),
+			};
+			match groups.iter_mut().find(|(existing, _)| *existing == group) {
+				Some((_, group_channels)) => group_channels.push((name, channel)),
+				None => groups.push((group, vec![(name, channel)])),
+			}
+		}
+
+		for (group, channels) in groups {
+			let Some(image_frame) = exr_channels_to_image_frame(size, &channels, to_working_space) else {
+				continue;
+			};
+			let name = match (&part_name, &group) {
+				(Some(part_name), Some(group)) => format!("{part_name}.{group}"),
+				(Some(name), None) | (None, Some(name)) => name.clone(),
+				(None, None) => format!("Layer {}", layers.len() + 1),
+			};
+			layers.push(ImageLayer::new(name, image_frame, offset));
+		}
+	}
+
+	let size = display_window.size;
+	Ok(LayeredImage {
+		size: UVec2::new(size.0 as u32, size.1 as u32),
+		layers,
+		groups: Vec::new(),
+	})
+}
+
+/// Combines the channels of a layer of an OpenEXR file into an image frame, which is `RGBA16F` if every channel holds half floats and `Color` otherwise.
+/// Alpha is premultiplied in OpenEXR files, like the pixels of the graph.
+fn exr_channels_to_image_frame(size: UVec2, channels: &[(String, &exr::prelude::AnyChannel<exr::prelude::FlatSamples>)], to_working_space: Mat3) -> Option<TaggedValue> {
+	use exr::prelude::FlatSamples;
+
+	let find = |name: &str| {
+		channels
+			.iter()
+			.find(|(channel_name, _)| channel_name == name)
+			.map(|(_, channel)| channel.sample_data.values_as_f32().collect::<Vec<_>>())
+	};
+	let pixel_count = size.x as usize * size.y as usize;
+	let alpha = find("A");
+	let alpha = |index: usize| alpha.as_ref().map_or(1., |alpha| alpha[index]);
+
+	if let (Some(red), Some(green), Some(blue)) = (find("R"), find("G"), find("B")) {
+		// The matrix is linear, so it applies to the premultiplied channels the same as to unassociated ones
+		let colors = (0..pixel_count).map(|index| {
+			let [red, green, blue] = (to_working_space * Vec3::new(red[index], green[index], blue[index])).to_array();
+			Color::from_rgbaf32_unchecked(red, green, blue, alpha(index))
+		});
+		let half = channels.iter().all(|(_, channel)| matches!(channel.sample_data, FlatSamples::F16(_)));
+		return Some(match half {
+			true => TaggedValue::ImageFrameRGBA16F(frame(size.x, size.y, colors.map(RGBA16F::from).collect())),
+			false => TaggedValue::ImageFrame(frame(size.x, size.y, colors.collect())),
+		});
+	}
+
+	let (gray, has_alpha) = match (find("Y"), channels) {
+		(Some(gray), _) => (gray, channels.iter().any(|(name, _)| name == "A")),
+		(None, [(_, channel)]) => (channel.sample_data.values_as_f32().collect(), false),
+		_ => return None,
+	};
+	Some(match has_alpha {
+		true => TaggedValue::ImageFrameLumaAlpha(frame(size.x, size.y, (0..pixel_count).map(|index| LumaAlpha::new(gray[index], alpha(index))).collect())),
+		false => TaggedValue::ImageFrameLuma(frame(size.x, size.y, gray.into_iter().map(Luma).collect())),
+	})
+}
+
+/// Reads every page of a TIFF file as a layer, named by its `PageName` tag and placed by its `XPosition` and `YPosition` tags if it has them.
+/// Pages in a color format which the graph has no pixel format for, such as CMYK or palette pages, are skipped.
+fn decode_tiff(data: &[u8], working_space: WorkingSpace) -> Result<LayeredImage, String> {
+	use tiff::decoder::{ifd::Value, Decoder, DecodingResult, Limits};
+	use tiff::tags::Tag;
+	use tiff::ColorType;
+
+	let error = |error: tiff::TiffError| format!("Failed to decode TIFF: {error}");
+	let mut decoder = Decoder::new(Cursor::new(data)).map_err(error)?.with_limits(Limits::unlimited());
+
+	let mut layers = Vec::new();
+	let mut size = UVec2::ZERO;
+	loop {
+		let (width, height) = decoder.dimensions().map_err(error)?;
+		let page_number = layers.len() + 1;
+		let name = decoder.get_tag_ascii_string(Tag::Unknown(image_files::PAGE_NAME)).ok().filter(|name| !name.is_empty());
+		let resolution = |decoder: &mut Decoder<_>, tag| match decoder.find_tag(tag) {
+			Ok(Some(Value::Rational(numerator, denominator))) if denominator != 0 => numerator as f64 / denominator as f64,
+			_ => 1.,
+		};
+		let mut position = |position_tag, resolution_tag| match decoder.find_tag(Tag::Unknown(position_tag)) {
+			// Positions are in the resolution unit of the page, so they are converted to pixels
+			Ok(Some(Value::Rational(numerator, denominator))) if denominator != 0 => (numerator as f64 / denominator as f64 * resolution(&mut decoder, resolution_tag)).round() as i32,
+			_ => 0,
+		};
+		let offset = IVec2::new(position(image_files::X_POSITION, Tag::XResolution), position(image_files::Y_POSITION, Tag::YResolution));
+		let profile = decoder.get_tag_u8_vec(Tag::Unknown(TIFF_ICC_PROFILE)).ok();
+
+		let color_type = decoder.colortype().map_err(error)?;
+		let decoded = match decoder.read_image().map_err(error)? {
+			DecodingResult::F64(samples) => DecodingResult::F32(samples.into_iter().map(|sample| sample as f32).collect()),
+			decoded => decoded,
+		};
+		let image = match (color_type, decoded) {
+			(ColorType::Gray(_), DecodingResult::U8(samples)) => ImageBuffer::from_raw(width, height, samples).map(DynamicImage::ImageLuma8),
+			(ColorType::GrayA(_), DecodingResult::U8(samples)) => ImageBuffer::from_raw(width, height, samples).map(DynamicImage::ImageLumaA8),
+			(ColorType::RGB(_), DecodingResult::U8(samples)) => ImageBuffer::from_raw(width, height, samples).map(DynamicImage::ImageRgb8),
+			(ColorType::RGBA(_), DecodingResult::U8(samples)) => ImageBuffer::from_raw(width, height, samples).map(DynamicImage::ImageRgba8),
+			(ColorType::Gray(_), DecodingResult::U16(samples)) => ImageBuffer::from_raw(width, height, samples).map(DynamicImage::ImageLuma16),
+			(ColorType::GrayA(_), DecodingResult::U16(samples)) => ImageBuffer::from_raw(width, height, samples).map(DynamicImage::ImageLumaA16),
+			(ColorType::RGB(_), DecodingResult::U16(samples)) => ImageBuffer::from_raw(width, height, samples).map(DynamicImage::ImageRgb16),
+			(ColorType::RGBA(_), DecodingResult::U16(samples)) => ImageBuffer::from_raw(width, height, samples).map(DynamicImage::ImageRgba16),
+			// There are no floating point grayscale images, so gray pages are widened to RGB
+			(ColorType::Gray(_), DecodingResult::F32(samples)) => ImageBuffer::from_raw(width, height, samples.into_iter().flat_map(|gray| [gray; 3]).collect()).map(DynamicImage::ImageRgb32F),
+			(ColorType::GrayA(_), DecodingResult::F32(samples)) => {
+				let samples = samples.chunks_exact(2).flat_map(|gray_alpha| [gray_alpha[0], gray_alpha[0], gray_alpha[0], gray_alpha[1]]).collect();
+				ImageBuffer::from_raw(width, height, samples).map(DynamicImage::ImageRgba32F)
+			}
+			(ColorType::RGB(_), DecodingResult::F32(samples)) => ImageBuffer::from_raw(width, height, samples).map(DynamicImage::ImageRgb32F),
+			(ColorType::RGBA(_), DecodingResult::F32(samples)) => ImageBuffer::from_raw(width, height, samples).map(DynamicImage::ImageRgba32F),
+			(color_type, _) => {
+				log::warn!("Skipped page {page_number} of a TIFF file in the unsupported color type {color_type:?}");
+				None
+			}
+		};
+
+		if let Some(image) = image {
+			let mut image_frame = image_files::image_to_image_frame(image);
+			let conversion = profile.and_then(|profile| ProfileConversion::new(IccProfile::parse(profile).ok()?, working_space).ok());
+			image_files::convert_to_working_space(&mut image_frame, conversion.as_ref(), working_space);
+
+			size = size.max((offset.max(IVec2::ZERO) + IVec2::new(width as i32, height as i32)).as_uvec2());
+			let name = name.unwrap_or_else(|| format!("Page {page_number}"));
+			layers.push(ImageLayer::new(name, image_frame, offset));
+		}
+
+		if !decoder.more_images() {
+			break;
+		}
+		decoder.next_image().map_err(error)?;
+	}
+
+	// The first page of a file is its top layer
+	layers.reverse();
+	Ok(LayeredImage { size, layers, groups: Vec::new() })
+}
+
+/// Unique names for the layers, since the layers of an OpenEXR file are told apart by their names.
+fn unique_layer_names(image: &LayeredImage) -> Vec<String> {
+	let mut names: Vec<String> = Vec::with_capacity(image.layers.len());
+	for (index, layer) in image.layers.iter().enumerate() {
+		let base_name = match layer.name.trim() {
+			"" => format!("Layer {}", index + 1),
+			name => name.to_string(),
+		};
+		let mut name = base_name.clone();
+		let mut suffix = 2;
+		while names.contains(&name) {
+			name = format!("{base_name} {suffix}");
+			suffix += 1;
+		}
+		names.push(name);
+	}
+	names
+}
+
+/// Encodes the layers as the parts of a multi-part OpenEXR file, whose chromaticities are those of the working space the colors are in.
+///
+/// `Color` layers are written with 32 bit float channels and all others with half float channels. Grayscale layers are written as a `Y` channel,
+/// and an `A` channel is only written for layers which aren't fully opaque.
+pub fn encode_exr(image: &LayeredImage, working_space: WorkingSpace) -> Result<Vec<u8>, String> {
+	use exr::meta::attribute::Chromaticities;
+	use exr::prelude::*;
+
+	let layers: Layers<AnyChannels<FlatSamples>> = image
+		.layers
+		.iter()
+		.zip(unique_layer_names(image))
+		.filter_map(|(layer, name)| {
+			let grayscale = matches!(layer.image_frame, TaggedValue::ImageFrameLuma(_) | TaggedValue::ImageFrameLumaAlpha(_));
+			let float = matches!(layer.image_frame, TaggedValue::ImageFrame(_));
+			let colors = image_files::image_frame_to_colors(&layer.image_frame)?;
+
+			let samples = |channel: fn(&Color) -> f32| match float {
+				true => FlatSamples::F32(colors.data.iter().map(channel).collect()),
+				false => FlatSamples::F16(colors.data.iter().map(|color| f16::from_f32(channel(color))).collect()),
+			};
+			let mut channels = SmallVec::new();
+			match grayscale {
+				true => channels.push(AnyChannel::new("Y", samples(|color| color.r()))),
+				false => channels.extend([
+					AnyChannel::new("R", samples(Color::r)),
+					AnyChannel::new("G", samples(Color::g)),
+					AnyChannel::new("B", samples(Color::b)),
+				]),
+			}
+			if colors.data.iter().any(|color| color.a() < 1.) {
+				channels.push(AnyChannel::new("A", samples(Color::a)));
+			}
+
+			let attributes = LayerAttributes {
+				layer_position: Vec2(layer.offset.x, layer.offset.y),
+				..LayerAttributes::named(name.as_str())
+			};
+			let size = Vec2(colors.width as usize, colors.height as usize);
+			Some(Layer::new(size, attributes, Encoding::SMALL_LOSSLESS, AnyChannels::sort(channels)))
+		})
+		.collect();
+	if layers.is_empty() {
+		return Err("There are no image layers to export".to_string());
+	}
+
+	let xy = |(x, y): (f64, f64)| Vec2(x as f32, y as f32);
+	let [red, green, blue] = working_space.primaries().map(xy);
+	let mut attributes = ImageAttributes::new(IntegerBounds::from_dimensions(Vec2(image.size.x as usize, image.size.y as usize)));
+	attributes.chromaticities = Some(Chromaticities {
+		red,
+		green,
+		blue,
+		white: xy(D65_CHROMATICITY),
+	});
+
+	let mut data = Vec::new();
+	Image::from_layers(attributes, layers)
+		.write()
+		.to_buffered(Cursor::new(&mut data))
+		.map_err(|error| format!("Failed to encode EXR: {error}"))?;
+	Ok(data)
+}
+
+/// Encodes the layers as the pages of a TIFF file, from the top layer to the bottom one, with their names and positions.
+/// Floating point layers are written with linear 32 bit float channels and all others with 16 bit channels, gamma encoded with the sRGB transfer function.
+pub fn encode_layered_tiff(image: &LayeredImage) -> Result<Vec<u8>, String> {
+	let pages = image
+		.layers
+		.iter()
+		.rev()
+		.filter_map(|layer| {
+			let page = match layer.image_frame {
+				TaggedValue::ImageFrame(_) | TaggedValue::ImageFrameRGBA16F(_) => {
+					let colors = image_files::image_frame_to_colors(&layer.image_frame)?;
+					let samples = colors.data.iter().flat_map(|color| {
+						let color = color.to_unassociated_alpha();
+						[color.r(), color.g(), color.b(), color.a()]
+					});
+					DynamicImage::ImageRgba32F(ImageBuffer::from_raw(colors.width, colors.height, samples.collect())?)
+				}
+				_ => image_files::image_frame_to_image(&layer.image_frame, BitDepth::Sixteen)?,
+			};
+			Some((page, layer.name.as_str(), layer.offset.max(IVec2::ZERO).as_uvec2()))
+		})
+		.collect::<Vec<_>>();
+	if pages.is_empty() {
+		return Err("There are no image layers to export".to_string());
+	}
+
+	image_files::encode_tiff_pages(pages.iter().map(|(page, name, offset)| (page, Some((*name, *offset)))))
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn layer(name: &str, image_frame: TaggedValue, offset: IVec2) -> ImageLayer {
+		ImageLayer::new(name.to_string(), image_frame, offset)
+	}
+
+	#[test]
+	fn exr_layers_round_trip() {
+		let hdr = Color::from_rgbaf32_unchecked(4., 0.5, 0.25, 1.);
+		let translucent = Color::from_rgbaf32_unchecked(0.25, 0.125, 0., 0.5);
+		let image = LayeredImage {
+			size: UVec2::new(4, 3),
+			layers: vec![
+				layer("Background", TaggedValue::ImageFrame(frame(4, 3, vec![hdr; 12])), IVec2::ZERO),
+				layer("Glow", TaggedValue::ImageFrameRGBA16F(frame(2, 1, vec![translucent.into(); 2])), IVec2::new(1, 2)),
+				layer("Depth", TaggedValue::ImageFrameLuma(frame(1, 2, vec![Luma(10.), Luma(0.5)])), IVec2::new(3, 0)),
+			],
+			groups: Vec::new(),
+		};
+
+		let exr = encode_exr(&image, WorkingSpace::Srgb).unwrap();
+		assert!(is_layered_format(&exr));
+		let decoded = decode_layered_image(&exr, WorkingSpace::Srgb).unwrap();
+		assert_eq!(decoded.size, image.size);
+
+		let close = |a: &TaggedValue, b: &TaggedValue| {
+			let (a, b) = (image_files::image_frame_to_colors(a).unwrap(), image_files::image_frame_to_colors(b).unwrap());
+			a.data
+				.iter()
+				.zip(&b.data)
+				.all(|(a, b)| (a.r() - b.r()).abs() + (a.g() - b.g()).abs() + (a.b() - b.b()).abs() + (a.a() - b.a()).abs() < 1e-5)
+		};
+		for (decoded, original) in decoded.layers.iter().zip(&image.layers) {
+			assert_eq!(decoded.name, original.name);
+			assert_eq!(decoded.offset, original.offset);
+			assert_eq!(std::mem::discriminant(&decoded.image_frame), std::mem::discriminant(&original.image_frame));
+			assert!(close(&decoded.image_frame, &original.image_frame), "The colors of {} changed", original.name);
+		}
+	}
+
+	#[test]
+	fn exr_channel_groups_become_layers() {
+		use exr::prelude::*;
+
+		let samples = |value: f32| FlatSamples::F32(vec![value; 4]);
+		let channels = ["diffuse.B", "diffuse.G", "diffuse.R", "depth.Z", "normal.X"].map(|name| AnyChannel::new(name, samples(0.5)));
+		let part = Layer::new(
+			Vec2(2, 2),
+			LayerAttributes::named("beauty"),
+			Encoding::UNCOMPRESSED,
+			AnyChannels::sort(SmallVec::from_vec(channels.to_vec())),
+		);
+		let mut exr = Vec::new();
+		Image::from_layer(part).write().to_buffered(Cursor::new(&mut exr)).unwrap();
+
+		let decoded = decode_layered_image(&exr, WorkingSpace::Srgb).unwrap();
+		let names = decoded.layers.iter().map(|layer| layer.name.as_str()).collect::<Vec<_>>();
+		assert_eq!(names, ["beauty.depth", "beauty.diffuse", "beauty.normal"]);
+		assert!(matches!(decoded.layers[0].image_frame, TaggedValue::ImageFrameLuma(_)));
+		assert!(matches!(decoded.layers[1].image_frame, TaggedValue::ImageFrame(_)));
+	}
+
+	#[test]
+	fn tiff_pages_round_trip_as_layers() {
+		let image = LayeredImage {
+			size: UVec2::new(3, 3),
+			layers: vec![
+				layer("Bottom", TaggedValue::ImageFrame(frame(3, 3, vec![Color::from_rgbaf32_unchecked(2., 1., 0.5, 1.); 9])), IVec2::ZERO),
+				layer(
+					"Top",
+					TaggedValue::ImageFrameSRGBA16(frame(1, 2, vec![Color::from_rgba8_srgb(255, 128, 0, 255).into(); 2])),
+					IVec2::new(2, 1),
+				),
+			],
+			groups: Vec::new(),
+		};
+
+		let tiff = encode_layered_tiff(&image).unwrap();
+		assert!(is_layered_format(&tiff));
+		let decoded = decode_layered_image(&tiff, WorkingSpace::Srgb).unwrap();
+		assert_eq!(decoded.size, image.size);
+		let names = decoded.layers.iter().map(|layer| (layer.name.as_str(), layer.offset)).collect::<Vec<_>>();
+		assert_eq!(names, [("Bottom", IVec2::ZERO), ("Top", IVec2::new(2, 1))]);
+		assert_eq!(decoded.layers[0].image_frame, image.layers[0].image_frame);
+		assert_eq!(decoded.layers[1].image_frame, image.layers[1].image_frame);
+	}
+}
@@ -59,6 +59,53 @@ impl DocumentMode {
 	}
 }
 
+/// The way the pixel selection is drawn in Select Mode
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum SelectionTool {
+	#[default]
+	RectangularMarquee,
+	EllipticalMarquee,
+	/// Selects the area enclosed by a freehand path
+	Lasso,
+	/// Selects the area enclosed by straight segments between clicked points
+	PolygonalLasso,
+	/// Selects the pixels with a similar color to the clicked one
+	MagicWand,
+}
+
+impl SelectionTool {
+	pub const ALL: [SelectionTool; 5] = [
+		SelectionTool::RectangularMarquee,
+		SelectionTool::EllipticalMarquee,
+		SelectionTool::Lasso,
+		SelectionTool::PolygonalLasso,
+		SelectionTool::MagicWand,
+	];
+
+	/// The short name shown in the document bar
+	pub fn label(&self) -> &'static str {
+		match self {
+			SelectionTool::RectangularMarquee => "Rectangle",
+			SelectionTool::EllipticalMarquee => "Ellipse",
+			SelectionTool::Lasso => "Lasso",
+			SelectionTool::PolygonalLasso => "Polygon",
+			SelectionTool::MagicWand => "Wand",
+		}
+	}
+}
+
+impl fmt::Display for SelectionTool {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			SelectionTool::RectangularMarquee => write!(f, "Rectangular Marquee"),
+			SelectionTool::EllipticalMarquee => write!(f, "Elliptical Marquee"),
+			SelectionTool::Lasso => write!(f, "Lasso"),
+			SelectionTool::PolygonalLasso => write!(f, "Polygonal Lasso"),
+			SelectionTool::MagicWand => write!(f, "Magic Wand"),
+		}
+	}
+}
+
 pub enum DocumentRenderMode<'a> {
 	Root,
 	OnlyBelowLayerInFolder(&'a [LayerId]),
@@ -66,11 +113,18 @@ pub enum DocumentRenderMode<'a> {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 /// SnappingState determines the current individual snapping states
 pub struct SnappingState {
 	pub snapping_enabled: bool,
 	pub bounding_box_snapping: bool,
 	pub node_snapping: bool,
+	pub distribution_snapping: bool,
+	pub intersection_snapping: bool,
+	pub path_snapping: bool,
+	pub artboard_snapping: bool,
+	pub guide_snapping: bool,
+	pub grid_snapping: bool,
 }
 
 impl Default for SnappingState {
@@ -79,14 +133,69 @@ impl Default for SnappingState {
 			snapping_enabled: true,
 			bounding_box_snapping: true,
 			node_snapping: true,
+			distribution_snapping: true,
+			intersection_snapping: true,
+			path_snapping: true,
+			artboard_snapping: true,
+			guide_snapping: true,
+			grid_snapping: true,
 		}
 	}
 }
 
+impl SnappingState {
+	pub fn option_enabled(&self, option: SnappingOptions) -> bool {
+		match option {
+			SnappingOptions::BoundingBoxes => self.bounding_box_snapping,
+			SnappingOptions::Points => self.node_snapping,
+			SnappingOptions::Distribution => self.distribution_snapping,
+			SnappingOptions::Intersections => self.intersection_snapping,
+			SnappingOptions::Paths => self.path_snapping,
+			SnappingOptions::Artboards => self.artboard_snapping,
+			SnappingOptions::Guides => self.guide_snapping,
+			SnappingOptions::Grid => self.grid_snapping,
+		}
+	}
+
+	pub fn set_option(&mut self, option: SnappingOptions, enabled: bool) {
+		let state = match option {
+			SnappingOptions::BoundingBoxes => &mut self.bounding_box_snapping,
+			SnappingOptions::Points => &mut self.node_snapping,
+			SnappingOptions::Distribution => &mut self.distribution_snapping,
+			SnappingOptions::Intersections => &mut self.intersection_snapping,
+			SnappingOptions::Paths => &mut self.path_snapping,
+			SnappingOptions::Artboards => &mut self.artboard_snapping,
+			SnappingOptions::Guides => &mut self.guide_snapping,
+			SnappingOptions::Grid => &mut self.grid_snapping,
+		};
+		*state = enabled;
+	}
+}
+
 // TODO: implement icons for SnappingOptions eventually
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize, Hash)]
 pub enum SnappingOptions {
 	BoundingBoxes,
 	Points,
+	Distribution,
+	Intersections,
+	Paths,
+	Artboards,
+	Guides,
+	Grid,
+}
+
+impl SnappingOptions {
+	pub const ALL: [SnappingOptions; 8] = [
+		SnappingOptions::BoundingBoxes,
+		SnappingOptions::Points,
+		SnappingOptions::Distribution,
+		SnappingOptions::Intersections,
+		SnappingOptions::Paths,
+		SnappingOptions::Artboards,
+		SnappingOptions::Guides,
+		SnappingOptions::Grid,
+	];
 }
 
 impl fmt::Display for SnappingOptions {
@@ -94,6 +203,12 @@ impl fmt::Display for SnappingOptions {
 		match self {
 			SnappingOptions::BoundingBoxes => write!(f, "Bounding Boxes"),
 			SnappingOptions::Points => write!(f, "Points"),
+			SnappingOptions::Distribution => write!(f, "Equal Spacing"),
+			SnappingOptions::Intersections => write!(f, "Intersections"),
+			SnappingOptions::Paths => write!(f, "Along Paths"),
+			SnappingOptions::Artboards => write!(f, "Artboards"),
+			SnappingOptions::Guides => write!(f, "Guides"),
+			SnappingOptions::Grid => write!(f, "Grid"),
 		}
 	}
 }
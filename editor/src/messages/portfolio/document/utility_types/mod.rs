@@ -1,6 +1,11 @@
 pub mod clipboards;
 pub mod error;
+pub mod guides;
+pub mod image_files;
 pub mod layer_panel;
+pub mod layered_images;
 pub mod misc;
+pub mod psd;
+pub mod sequence_export;
 pub mod transformation;
 pub mod vectorize_layer_metadata;
@@ -0,0 +1,846 @@
+//! Reading Photoshop documents (PSD) and large documents (PSB) as layered images.
+//!
+//! Each pixel layer becomes an image layer keeping its name, blend mode, opacity and visibility, and layer groups become the groups of the image.
+//! Text, shape and smart object layers are read from the pixels Photoshop stores for them, so they come in as bitmaps.
+//! Adjustment and fill layers, layer masks and layer effects aren't read.
+
+use super::image_files;
+use super::layered_images::{ImageLayer, LayerGroup, LayeredImage};
+
+use graphene_core::raster::color_management::{IccProfile, WorkingSpace};
+use graphene_core::raster::BlendMode;
+use graphene_std::color_profile::ProfileConversion;
+
+use glam::{IVec2, UVec2};
+use image::{DynamicImage, ImageBuffer};
+use std::io::Read;
+
+const PSD_SIGNATURE: &[u8] = b"8BPS";
+/// The image resource holding the ICC profile of the document.
+const ICC_PROFILE_RESOURCE: u16 = 1039;
+/// The most pixels in an image of the file that is opened, since each pixel takes 16 bytes once it becomes a color of the document.
+const MAX_PIXELS: usize = 1 << 26;
+/// The keys of additional layer information whose length is 8 bytes long in PSB files.
+const LARGE_LENGTH_KEYS: [&[u8; 4]; 13] = [b"LMsk", b"Lr16", b"Lr32", b"Layr", b"Mt16", b"Mt32", b"Mtrn", b"Alph", b"FMsk", b"lnk2", b"FEid", b"FXid", b"PxSD"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ColorMode {
+	Grayscale,
+	Rgb,
+	Cmyk,
+}
+
+impl ColorMode {
+	fn channels(self) -> usize {
+		match self {
+			ColorMode::Grayscale => 1,
+			ColorMode::Rgb => 3,
+			ColorMode::Cmyk => 4,
+		}
+	}
+}
+
+/// The header of a document, which describes the pixels of all of its layers.
+#[derive(Clone, Copy, Debug)]
+struct Header {
+	/// Whether the file is a PSB file, which has longer lengths in some places.
+	large: bool,
+	channels: usize,
+	size: UVec2,
+	depth: u16,
+	color_mode: ColorMode,
+}
+
+/// Reads the big endian values of a file, failing once it runs out of data.
+struct Reader<'a> {
+	data: &'a [u8],
+	position: usize,
+}
+
+impl<'a> Reader<'a> {
+	fn new(data: &'a [u8]) -> Self {
+		Self { data, position: 0 }
+	}
+
+	fn remaining(&self) -> usize {
+		self.data.len() - self.position
+	}
+
+	fn bytes(&mut self, length: usize) -> Result<&'a [u8], String> {
+		let bytes = self.data.get(self.position..self.position.saturating_add(length)).ok_or("The PSD file is truncated")?;
+		self.position += length;
+		Ok(bytes)
+	}
+
+	fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+		Ok(self.bytes(N)?.try_into().unwrap())
+	}
+
+	fn u8(&mut self) -> Result<u8, String> {
+		Ok(self.array::<1>()?[0])
+	}
+
+	fn u16(&mut self) -> Result<u16, String> {
+		Ok(u16::from_be_bytes(self.array()?))
+	}
+
+	fn i16(&mut self) -> Result<i16, String> {
+		Ok(i16::from_be_bytes(self.array()?))
+	}
+
+	fn u32(&mut self) -> Result<u32, String> {
+		Ok(u32::from_be_bytes(self.array()?))
+	}
+
+	fn i32(&mut self) -> Result<i32, String> {
+		Ok(i32::from_be_bytes(self.array()?))
+	}
+
+	/// A length which is 4 bytes long in PSD files and 8 bytes long in PSB files.
+	fn length(&mut self, large: bool) -> Result<usize, String> {
+		match large {
+			true => Ok(u64::from_be_bytes(self.array()?) as usize),
+			false => Ok(self.u32()? as usize),
+		}
+	}
+
+	/// The byte count of a row of image data compressed with PackBits, which is 2 bytes long in PSD files and 4 bytes long in PSB files.
+	fn length_of_row(&mut self, large: bool) -> Result<usize, String> {
+		match large {
+			true => Ok(self.u32()? as usize),
+			false => Ok(self.u16()? as usize),
+		}
+	}
+
+	/// A section which starts with its length, read as a reader of its own.
+	fn section(&mut self, large: bool) -> Result<Reader<'a>, String> {
+		let length = self.length(large)?;
+		Ok(Reader::new(self.bytes(length)?))
+	}
+}
+
+/// What a layer record is, told by its section divider setting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LayerKind {
+	Pixels,
+	/// The top of a group, which holds the properties of the group.
+	GroupStart,
+	/// The hidden layer below the layers of a group.
+	GroupEnd,
+}
+
+/// The properties of a layer from its layer record.
+#[derive(Clone, Debug)]
+struct LayerRecord {
+	top: i32,
+	left: i32,
+	bottom: i32,
+	right: i32,
+	/// The ID and length of each channel, in the order of their image data.
+	channels: Vec<(i16, usize)>,
+	blend_mode: BlendMode,
+	opacity: u8,
+	visible: bool,
+	name: String,
+	kind: LayerKind,
+}
+
+/// Whether the file is a Photoshop document, which is decoded by [`decode_psd`].
+pub fn is_psd(data: &[u8]) -> bool {
+	data.starts_with(PSD_SIGNATURE)
+}
+
+/// Decodes the layers and groups of a PSD or PSB file, converting their colors from the document's ICC profile to the working space.
+///
+/// Grayscale, RGB and CMYK documents at 8, 16 and 32 bits per channel are supported. CMYK colors are converted without their profile.
+/// Documents saved without layers are read from their flattened image.
+pub fn decode_psd(data: &[u8], working_space: WorkingSpace) -> Result<LayeredImage, String> {
+	let mut reader = Reader::new(data);
+	let header = read_header(&mut reader)?;
+
+	let _color_mode_data = reader.section(false)?;
+	let profile = read_icc_profile(reader.section(false)?);
+	let conversion = match header.color_mode {
+		ColorMode::Cmyk => None,
+		_ => profile.and_then(|profile| ProfileConversion::new(IccProfile::parse(profile).ok()?, working_space).ok()),
+	};
+	let layer_and_mask_information = reader.section(header.large)?;
+
+	let mut image = read_layers(layer_and_mask_information, header)?;
+	if image.layers.is_empty() {
+		image.layers.push(read_flattened_image(&mut reader, header)?);
+	}
+	for layer in &mut image.layers {
+		image_files::convert_to_working_space(&mut layer.image_frame, conversion.as_ref(), working_space);
+	}
+	Ok(image)
+}
+
+fn read_header(reader: &mut Reader) -> Result<Header, String> {
+	if reader.bytes(4)? != PSD_SIGNATURE {
+		return Err("The file isn't a PSD file".to_string());
+	}
+	let large = match reader.u16()? {
+		1 => false,
+		2 => true,
+		version => return Err(format!("Unsupported PSD version {version}")),
+	};
+	reader.bytes(6)?;
+	let channels = reader.u16()? as usize;
+	let height = reader.u32()?;
+	let width = reader.u32()?;
+	let depth = reader.u16()?;
+	if ![8, 16, 32].contains(&depth) {
+		return Err(format!("PSD files with {depth} bits per channel aren't supported"));
+	}
+	let color_mode = match reader.u16()? {
+		1 => ColorMode::Grayscale,
+		3 => ColorMode::Rgb,
+		4 => ColorMode::Cmyk,
+		0 => return Err("Bitmap PSD files aren't supported".to_string()),
+		2 => return Err("Indexed color PSD files aren't supported".to_string()),
+		9 => return Err("Lab color PSD files aren't supported".to_string()),
+		mode => return Err(format!("PSD files in the color mode {mode} aren't supported")),
+	};
+	let size = UVec2::new(width, height);
+	Ok(Header {
+		large,
+		channels,
+		size,
+		depth,
+		color_mode,
+	})
+}
+
+/// Finds the ICC profile among the image resources of the document.
+fn read_icc_profile(mut resources: Reader) -> Option<Vec<u8>> {
+	while resources.remaining() > 0 {
+		if resources.bytes(4).ok()? != b"8BIM" {
+			return None;
+		}
+		let id = resources.u16().ok()?;
+		// The name is a Pascal string padded to an even length
+		let name_length = resources.u8().ok()? as usize;
+		resources.bytes(name_length + (name_length + 1) % 2).ok()?;
+		let length = resources.u32().ok()? as usize;
+		let data = resources.bytes(length).ok()?;
+		resources.bytes(length % 2).ok()?;
+
+		if id == ICC_PROFILE_RESOURCE {
+			return Some(data.to_vec());
+		}
+	}
+	None
+}
+
+/// Reads the layers of the layer and mask information section, which in 16 and 32 bit documents are kept in additional layer information at its end.
+fn read_layers(mut section: Reader, header: Header) -> Result<LayeredImage, String> {
+	let mut image = LayeredImage {
+		size: header.size,
+		layers: Vec::new(),
+		groups: Vec::new(),
+	};
+	if section.remaining() == 0 {
+		return Ok(image);
+	}
+
+	let layer_info = section.section(header.large)?;
+	if layer_info.data.is_empty() {
+		let _global_layer_mask_info = section.section(false)?;
+		for (key, data) in read_additional_information(section, header.large) {
+			if [b"Layr", b"Lr16", b"Lr32"].contains(&&key) {
+				read_layer_info(Reader::new(data), header, &mut image)?;
+			}
+		}
+	} else {
+		read_layer_info(layer_info, header, &mut image)?;
+	}
+	Ok(image)
+}
+
+/// Reads the layer records followed by the image data of their channels, from the bottom layer to the top one.
+fn read_layer_info(mut layer_info: Reader, header: Header, image: &mut LayeredImage) -> Result<(), String> {
+	// A negative count means that the first alpha channel holds the transparency of the flattened image
+	let layer_count = layer_info.i16()?.unsigned_abs();
+	let records = (0..layer_count).map(|_| read_layer_record(&mut layer_info, header.large)).collect::<Result<Vec<_>, _>>()?;
+
+	// The groups which the layers are currently in, from the outermost to the innermost
+	let mut open_groups = Vec::new();
+	for record in records {
+		let channels = record.channels.iter().map(|&(id, length)| Ok((id, layer_info.bytes(length)?))).collect::<Result<Vec<_>, String>>()?;
+
+		match record.kind {
+			LayerKind::GroupEnd => {
+				// The properties of the group are filled in once its top is reached
+				image.groups.push(LayerGroup {
+					name: String::new(),
+					blend_mode: BlendMode::Normal,
+					opacity: 1.,
+					visible: true,
+					parent: open_groups.last().copied(),
+				});
+				open_groups.push(image.groups.len() - 1);
+			}
+			LayerKind::GroupStart => {
+				let Some(index) = open_groups.pop() else {
+					log::warn!("Skipped the group \"{}\" of a PSD file which has no end", record.name);
+					continue;
+				};
+				let group = &mut image.groups[index];
+				group.name = record.name;
+				group.blend_mode = record.blend_mode;
+				group.opacity = record.opacity as f64 / 255.;
+				group.visible = record.visible;
+			}
+			LayerKind::Pixels => {
+				let (Some(width), Some(height)) = (record.right.checked_sub(record.left), record.bottom.checked_sub(record.top)) else {
+					return Err(format!("The layer \"{}\" of the PSD file is too large to be opened", record.name));
+				};
+				let size = IVec2::new(width, height);
+				// Empty layers such as adjustment layers have no pixels
+				if size.cmple(IVec2::ZERO).any() {
+					continue;
+				}
+				let Some(pixels) = layer_pixels(&channels, size.as_uvec2(), header)? else {
+					log::warn!("Skipped the layer \"{}\" of a PSD file which is missing some of its color channels", record.name);
+					continue;
+				};
+				image.layers.push(ImageLayer {
+					blend_mode: record.blend_mode,
+					opacity: record.opacity as f64 / 255.,
+					visible: record.visible,
+					group: open_groups.last().copied(),
+					..ImageLayer::new(record.name, image_files::image_to_image_frame(pixels), IVec2::new(record.left, record.top))
+				});
+			}
+		}
+	}
+	Ok(())
+}
+
+fn read_layer_record(reader: &mut Reader, large: bool) -> Result<LayerRecord, String> {
+	let [top, left, bottom, right] = [reader.i32()?, reader.i32()?, reader.i32()?, reader.i32()?];
+	let channel_count = reader.u16()?;
+	let channels = (0..channel_count).map(|_| Ok((reader.i16()?, reader.length(large)?))).collect::<Result<Vec<_>, String>>()?;
+
+	if reader.bytes(4)? != b"8BIM" {
+		return Err("The PSD file has an invalid layer record".to_string());
+	}
+	let blend_mode = blend_mode(&reader.array()?);
+	let opacity = reader.u8()?;
+	let _clipping = reader.u8()?;
+	let flags = reader.u8()?;
+	let _filler = reader.u8()?;
+
+	let mut extra_data = reader.section(false)?;
+	let _layer_mask_data = extra_data.section(false)?;
+	let _blending_ranges = extra_data.section(false)?;
+	// The name is a Pascal string padded to a multiple of 4 bytes
+	let name_length = extra_data.u8()? as usize;
+	let name_bytes = extra_data.bytes(name_length)?;
+	extra_data.bytes((4 - (name_length + 1) % 4) % 4)?;
+	// The name is stored in the Mac OS Roman encoding, which shares its printable ASCII characters with Latin-1
+	let mut name = name_bytes.iter().map(|&byte| byte as char).collect::<String>();
+
+	let mut kind = LayerKind::Pixels;
+	for (key, data) in read_additional_information(extra_data, large) {
+		match &key {
+			// The name in Unicode, as a count of UTF-16 code units and the units themselves
+			b"luni" => {
+				let mut data = Reader::new(data);
+				if let Ok(length) = data.u32() {
+					let units = (0..length).map_while(|_| data.u16().ok()).collect::<Vec<_>>();
+					name = String::from_utf16_lossy(&units).trim_end_matches('\0').to_string();
+				}
+			}
+			b"lsct" | b"lsdk" => {
+				kind = match Reader::new(data).u32() {
+					Ok(1 | 2) => LayerKind::GroupStart,
+					Ok(3) => LayerKind::GroupEnd,
+					_ => LayerKind::Pixels,
+				}
+			}
+			_ => {}
+		}
+	}
+
+	Ok(LayerRecord {
+		top,
+		left,
+		bottom,
+		right,
+		channels,
+		blend_mode,
+		opacity,
+		// This flag is set for hidden layers
+		visible: flags & 0b10 == 0,
+		name,
+		kind,
+	})
+}
+
+/// Reads the blocks of additional information as their keys and data.
+fn read_additional_information<'a>(mut reader: Reader<'a>, large: bool) -> Vec<([u8; 4], &'a [u8])> {
+	let mut blocks = Vec::new();
+	while reader.remaining() >= 12 {
+		let Ok(signature) = reader.array::<4>() else { break };
+		if &signature != b"8BIM" && &signature != b"8B64" {
+			break;
+		}
+		let Ok(key) = reader.array::<4>() else { break };
+		let Ok(length) = reader.length(large && LARGE_LENGTH_KEYS.contains(&&key)) else { break };
+		let Ok(data) = reader.bytes(length) else { break };
+		blocks.push((key, data));
+
+		// Some writers pad the data to an even length and others to a multiple of 4 bytes, without counting the padding in the length
+		let padding = (0..4).find(|&padding| matches!(reader.data.get(reader.position + padding..reader.position + padding + 4), Some(b"8BIM" | b"8B64")));
+		match padding {
+			Some(padding) => reader.position += padding,
+			None => break,
+		}
+	}
+	blocks
+}
+
+/// Reads the flattened image stored after the layers, for documents saved without layers, as an opaque layer.
+fn read_flattened_image(reader: &mut Reader, header: Header) -> Result<ImageLayer, String> {
+	let compression = reader.u16()?;
+	let (width, height) = (header.size.x as usize, header.size.y as usize);
+	pixel_count(width, height)?;
+	let bytes_per_row = byte_length(width, 1, header.depth as usize / 8)?;
+	let channel_length = byte_length(bytes_per_row, height, 1)?;
+	let color_channels = header.color_mode.channels().min(header.channels);
+
+	let mut channels = Vec::with_capacity(color_channels);
+	match compression {
+		// The byte counts of the rows of every channel come before the data of all of the channels
+		1 => {
+			let counts = (0..byte_length(header.channels, height, 1)?)
+				.map(|_| reader.length_of_row(header.large))
+				.collect::<Result<Vec<_>, String>>()?;
+			for channel in 0..color_channels {
+				let mut samples = Vec::with_capacity(channel_length);
+				for &count in &counts[channel * height..(channel + 1) * height] {
+					unpack_bits(reader.bytes(count)?, &mut samples, bytes_per_row)?;
+				}
+				channels.push((channel as i16, samples));
+			}
+		}
+		0 => {
+			for channel in 0..color_channels {
+				channels.push((channel as i16, reader.bytes(channel_length)?.to_vec()));
+			}
+		}
+		compression => return Err(format!("The flattened image of the PSD file has the unsupported compression {compression}")),
+	}
+
+	let pixels = channels_to_image(&channels, header.size, header)?.ok_or("The flattened image of the PSD file is missing some of its color channels")?;
+	Ok(ImageLayer::new("Background".to_string(), image_files::image_to_image_frame(pixels), IVec2::ZERO))
+}
+
+/// Decodes the channels of a layer into an image, or `None` if some of its color channels are missing.
+fn layer_pixels(channels: &[(i16, &[u8])], size: UVec2, header: Header) -> Result<Option<DynamicImage>, String> {
+	let (width, height) = (size.x as usize, size.y as usize);
+	let decoded = channels
+		.iter()
+		// Layer masks have a size of their own, so only the color and transparency channels are decoded
+		.filter(|&&(id, _)| id >= -1)
+		.map(|&(id, data)| Ok((id, decode_channel(data, width, height, header)?)))
+		.collect::<Result<Vec<_>, String>>()?;
+	channels_to_image(&decoded, size, header)
+}
+
+/// Decodes the compressed image data of a channel of a layer into big endian samples.
+fn decode_channel(data: &[u8], width: usize, height: usize, header: Header) -> Result<Vec<u8>, String> {
+	pixel_count(width, height)?;
+	let mut reader = Reader::new(data);
+	let compression = reader.u16()?;
+	let bytes_per_sample = header.depth as usize / 8;
+	let bytes_per_row = byte_length(width, 1, bytes_per_sample)?;
+	let channel_length = byte_length(bytes_per_row, height, 1)?;
+
+	let samples = match compression {
+		0 => reader.bytes(channel_length)?.to_vec(),
+		1 => {
+			let counts = (0..height).map(|_| reader.length_of_row(header.large)).collect::<Result<Vec<_>, String>>()?;
+			let mut samples = Vec::with_capacity(channel_length);
+			for count in counts {
+				unpack_bits(reader.bytes(count)?, &mut samples, bytes_per_row)?;
+			}
+			samples
+		}
+		2 | 3 => {
+			let mut samples = Vec::with_capacity(channel_length);
+			flate2::read::ZlibDecoder::new(&data[2..])
+				.read_to_end(&mut samples)
+				.map_err(|error| format!("Failed to decompress a layer of the PSD file: {error}"))?;
+			if compression == 3 {
+				for row in samples.chunks_exact_mut(bytes_per_row) {
+					undo_prediction(row, width, bytes_per_sample);
+				}
+			}
+			samples
+		}
+		compression => return Err(format!("A layer of the PSD file has the unsupported compression {compression}")),
+	};
+	if samples.len() < channel_length {
+		return Err("A layer of the PSD file has too little image data".to_string());
+	}
+	Ok(samples)
+}
+
+/// The number of pixels in an image of the given size, or an error if it has more than [MAX_PIXELS], which is checked before the image is decoded.
+fn pixel_count(width: usize, height: usize) -> Result<usize, String> {
+	width
+		.checked_mul(height)
+		.filter(|&count| count <= MAX_PIXELS)
+		.ok_or_else(|| format!("The PSD file has an image of {width}x{height} pixels, which is more than the {MAX_PIXELS} pixels an image can have to be opened"))
+}
+
+/// The number of bytes in an image of the given size, or an error if it is too large to be allocated.
+fn byte_length(width: usize, height: usize, bytes_per_sample: usize) -> Result<usize, String> {
+	width
+		.checked_mul(height)
+		.and_then(|samples| samples.checked_mul(bytes_per_sample))
+		.ok_or_else(|| "The PSD file has an image too large to be opened".to_string())
+}
+
+/// Decompresses a row compressed with the PackBits run length encoding.
+fn unpack_bits(mut packed: &[u8], samples: &mut Vec<u8>, row_length: usize) -> Result<(), String> {
+	let end = samples.len() + row_length;
+	while let [header, rest @ ..] = packed {
+		match *header as i8 {
+			// A literal run of the following bytes
+			count @ 0.. => {
+				let count = count as usize + 1;
+				samples.extend(rest.get(..count).ok_or("A layer of the PSD file has invalid compressed data")?);
+				packed = &rest[count..];
+			}
+			// No operation
+			-128 => packed = rest,
+			// The following byte repeated
+			count => {
+				let &byte = rest.first().ok_or("A layer of the PSD file has invalid compressed data")?;
+				samples.resize(samples.len() + (1 - count as isize) as usize, byte);
+				packed = &rest[1..];
+			}
+		}
+	}
+	samples.resize(end, 0);
+	Ok(())
+}
+
+/// Undoes the delta encoding of a row compressed with ZIP with prediction.
+/// 32 bit rows have the bytes of their samples split into planes, from the most significant bytes of every sample to the least significant ones.
+fn undo_prediction(row: &mut [u8], width: usize, bytes_per_sample: usize) {
+	match bytes_per_sample {
+		1 => {
+			for index in 1..row.len() {
+				row[index] = row[index].wrapping_add(row[index - 1]);
+			}
+		}
+		2 => {
+			for index in 1..width {
+				let previous = u16::from_be_bytes([row[index * 2 - 2], row[index * 2 - 1]]);
+				let current = u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]);
+				row[index * 2..index * 2 + 2].copy_from_slice(&current.wrapping_add(previous).to_be_bytes());
+			}
+		}
+		_ => {
+			for index in 1..row.len() {
+				row[index] = row[index].wrapping_add(row[index - 1]);
+			}
+			let planes = row.to_vec();
+			for (index, sample) in row.chunks_exact_mut(bytes_per_sample).enumerate() {
+				for (plane, byte) in sample.iter_mut().enumerate() {
+					*byte = planes[plane * width + index];
+				}
+			}
+		}
+	}
+}
+
+/// Combines the decoded channels, identified by their IDs, into an image with transparency. Color channels are numbered from 0 and transparency is -1.
+fn channels_to_image(channels: &[(i16, Vec<u8>)], size: UVec2, header: Header) -> Result<Option<DynamicImage>, String> {
+	let pixel_count = pixel_count(size.x as usize, size.y as usize)?;
+	let channel = |id: i16| channels.iter().find(|(channel_id, _)| *channel_id == id).map(|(_, samples)| samples.as_slice());
+	let Some(color) = (0..header.color_mode.channels() as i16).map(channel).collect::<Option<Vec<_>>>() else {
+		return Ok(None);
+	};
+	let alpha = channel(-1);
+
+	// The samples of every channel from 0 to 1, with the transparency after the color channels
+	let sample = |samples: &[u8], index: usize| -> f32 {
+		match header.depth {
+			8 => samples[index] as f32 / 255.,
+			16 => u16::from_be_bytes([samples[index * 2], samples[index * 2 + 1]]) as f32 / 65535.,
+			_ => f32::from_be_bytes(samples[index * 4..index * 4 + 4].try_into().unwrap()),
+		}
+	};
+	let alpha_at = |index: usize| alpha.map_or(1., |alpha| sample(alpha, index));
+	let rgb_at = |index: usize| -> [f32; 3] {
+		match header.color_mode {
+			ColorMode::Grayscale => [sample(color[0], index); 3],
+			ColorMode::Rgb => [sample(color[0], index), sample(color[1], index), sample(color[2], index)],
+			// CMYK channels are stored inverted, so 1 is no ink
+			ColorMode::Cmyk => {
+				let black = sample(color[3], index);
+				[0, 1, 2].map(|channel| sample(color[channel], index) * black)
+			}
+		}
+	};
+
+	let (width, height) = (size.x, size.y);
+	let image = match (header.depth, header.color_mode) {
+		(8, ColorMode::Grayscale) => {
+			ImageBuffer::from_raw(width, height, (0..pixel_count).flat_map(|index| [color[0][index], alpha.map_or(255, |alpha| alpha[index])]).collect()).map(DynamicImage::ImageLumaA8)
+		}
+		(16, ColorMode::Grayscale) => {
+			let to_u16 = |value: f32| (value * 65535.).round() as u16;
+			ImageBuffer::from_raw(width, height, (0..pixel_count).flat_map(|index| [to_u16(sample(color[0], index)), to_u16(alpha_at(index))]).collect()).map(DynamicImage::ImageLumaA16)
+		}
+		(8, _) => {
+			let to_u8 = |value: f32| (value * 255.).round() as u8;
+			let pixels = (0..pixel_count).flat_map(|index| {
+				let [red, green, blue] = rgb_at(index);
+				[red, green, blue, alpha_at(index)].map(to_u8)
+			});
+			ImageBuffer::from_raw(width, height, pixels.collect()).map(DynamicImage::ImageRgba8)
+		}
+		(16, _) => {
+			let to_u16 = |value: f32| (value * 65535.).round() as u16;
+			let pixels = (0..pixel_count).flat_map(|index| {
+				let [red, green, blue] = rgb_at(index);
+				[red, green, blue, alpha_at(index)].map(to_u16)
+			});
+			ImageBuffer::from_raw(width, height, pixels.collect()).map(DynamicImage::ImageRgba16)
+		}
+		// 32 bit documents are linear, like floating point images in other formats
+		_ => {
+			let pixels = (0..pixel_count).flat_map(|index| {
+				let [red, green, blue] = rgb_at(index);
+				[red, green, blue, alpha_at(index)]
+			});
+			ImageBuffer::from_raw(width, height, pixels.collect()).map(DynamicImage::ImageRgba32F)
+		}
+	};
+	image.map(Some).ok_or_else(|| "A layer of the PSD file has too little image data".to_string())
+}
+
+/// Maps the key of a Photoshop blend mode to the matching blend mode. Dissolve, which has no match, and the pass through mode of groups become Normal.
+fn blend_mode(key: &[u8; 4]) -> BlendMode {
+	match key {
+		b"dark" => BlendMode::Darken,
+		b"mul " => BlendMode::Multiply,
+		b"idiv" => BlendMode::ColorBurn,
+		b"lbrn" => BlendMode::LinearBurn,
+		b"dkCl" => BlendMode::DarkerColor,
+		b"lite" => BlendMode::Lighten,
+		b"scrn" => BlendMode::Screen,
+		b"div " => BlendMode::ColorDodge,
+		b"lddg" => BlendMode::LinearDodge,
+		b"lgCl" => BlendMode::LighterColor,
+		b"over" => BlendMode::Overlay,
+		b"sLit" => BlendMode::SoftLight,
+		b"hLit" => BlendMode::HardLight,
+		b"vLit" => BlendMode::VividLight,
+		b"lLit" => BlendMode::LinearLight,
+		b"pLit" => BlendMode::PinLight,
+		b"hMix" => BlendMode::HardMix,
+		b"diff" => BlendMode::Difference,
+		b"smud" => BlendMode::Exclusion,
+		b"fsub" => BlendMode::Subtract,
+		b"fdiv" => BlendMode::Divide,
+		b"hue " => BlendMode::Hue,
+		b"sat " => BlendMode::Saturation,
+		b"colr" => BlendMode::Color,
+		b"lum " => BlendMode::Luminosity,
+		_ => BlendMode::Normal,
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use graph_craft::document::value::TaggedValue;
+	use graphene_core::raster::Color;
+
+	struct TestLayer {
+		name: &'static str,
+		/// The top, left, bottom and right edges of the layer
+		rect: [i32; 4],
+		blend_key: &'static [u8; 4],
+		opacity: u8,
+		visible: bool,
+		section_divider: Option<u32>,
+		pixels: Vec<[u8; 4]>,
+	}
+
+	fn layer(name: &'static str, rect: [i32; 4], pixel: [u8; 4]) -> TestLayer {
+		let pixel_count = ((rect[2] - rect[0]) * (rect[3] - rect[1])) as usize;
+		TestLayer {
+			name,
+			rect,
+			blend_key: b"norm",
+			opacity: 255,
+			visible: true,
+			section_divider: None,
+			pixels: vec![pixel; pixel_count],
+		}
+	}
+
+	fn divider(name: &'static str, section_divider: u32) -> TestLayer {
+		TestLayer {
+			blend_key: b"pass",
+			section_divider: Some(section_divider),
+			..layer(name, [0; 4], [0; 4])
+		}
+	}
+
+	/// An 8 bit RGB document with the layers from the bottom one to the top one, whose channels are stored uncompressed.
+	fn psd_file(size: UVec2, layers: &[TestLayer]) -> Vec<u8> {
+		let mut file = b"8BPS".to_vec();
+		file.extend(1_u16.to_be_bytes());
+		file.extend([0; 6]);
+		file.extend(3_u16.to_be_bytes());
+		file.extend(size.y.to_be_bytes());
+		file.extend(size.x.to_be_bytes());
+		file.extend(8_u16.to_be_bytes());
+		file.extend(3_u16.to_be_bytes());
+		// The color mode data and the image resources
+		file.extend([0; 8]);
+
+		let mut layer_info = (layers.len() as i16).to_be_bytes().to_vec();
+		let mut image_data = Vec::new();
+		for layer in layers {
+			layer_info.extend(layer.rect.iter().flat_map(|edge| edge.to_be_bytes()));
+			layer_info.extend(4_u16.to_be_bytes());
+			for (id, index) in [(-1_i16, 3), (0, 0), (1, 1), (2, 2)] {
+				let channel = [0, 0].into_iter().chain(layer.pixels.iter().map(|pixel| pixel[index])).collect::<Vec<_>>();
+				layer_info.extend(id.to_be_bytes());
+				layer_info.extend((channel.len() as u32).to_be_bytes());
+				image_data.extend(channel);
+			}
+			layer_info.extend(b"8BIM");
+			layer_info.extend(layer.blend_key);
+			layer_info.extend([layer.opacity, 0, if layer.visible { 0 } else { 0b10 }, 0]);
+
+			let mut extra_data = vec![0; 8];
+			extra_data.push(layer.name.len() as u8);
+			extra_data.extend(layer.name.as_bytes());
+			extra_data.resize(8 + (layer.name.len() + 4) / 4 * 4, 0);
+			if let Some(section_divider) = layer.section_divider {
+				extra_data.extend(b"8BIMlsct");
+				extra_data.extend(4_u32.to_be_bytes());
+				extra_data.extend(section_divider.to_be_bytes());
+			}
+			layer_info.extend((extra_data.len() as u32).to_be_bytes());
+			layer_info.extend(extra_data);
+		}
+		layer_info.extend(image_data);
+
+		let mut layer_and_mask_information = (layer_info.len() as u32).to_be_bytes().to_vec();
+		layer_and_mask_information.extend(layer_info);
+		layer_and_mask_information.extend([0; 4]);
+		file.extend((layer_and_mask_information.len() as u32).to_be_bytes());
+		file.extend(layer_and_mask_information);
+
+		// The flattened image, which is white
+		file.extend(0_u16.to_be_bytes());
+		file.extend(vec![255; (size.x * size.y * 3) as usize]);
+		file
+	}
+
+	fn srgba8_frame(width: u32, height: u32, [red, green, blue, alpha]: [u8; 4]) -> TaggedValue {
+		TaggedValue::ImageFrameSRGBA8(image_files::frame(
+			width,
+			height,
+			vec![Color::from_rgba8_srgb(red, green, blue, alpha).into(); (width * height) as usize],
+		))
+	}
+
+	#[test]
+	fn layers_and_groups_are_read() {
+		let file = psd_file(
+			UVec2::new(3, 2),
+			&[
+				layer("Background", [0, 0, 2, 3], [255, 0, 0, 255]),
+				divider("</Layer group>", 3),
+				TestLayer {
+					blend_key: b"mul ",
+					opacity: 128,
+					..layer("Shadow", [1, 1, 2, 3], [0, 0, 255, 255])
+				},
+				TestLayer {
+					visible: false,
+					..divider("Group", 1)
+				},
+				TestLayer {
+					blend_key: b"scrn",
+					..layer("Title", [0, 2, 1, 3], [0, 255, 0, 128])
+				},
+			],
+		);
+		assert!(is_psd(&file));
+		let image = decode_psd(&file, WorkingSpace::Srgb).unwrap();
+
+		assert_eq!(image.size, UVec2::new(3, 2));
+		let layers = image.layers.iter().map(|layer| (layer.name.as_str(), layer.offset, layer.blend_mode, layer.group)).collect::<Vec<_>>();
+		assert_eq!(
+			layers,
+			[
+				("Background", IVec2::ZERO, BlendMode::Normal, None),
+				("Shadow", IVec2::new(1, 1), BlendMode::Multiply, Some(0)),
+				("Title", IVec2::new(2, 0), BlendMode::Screen, None),
+			]
+		);
+		assert!((image.layers[1].opacity - 128. / 255.).abs() < 1e-6);
+		assert_eq!(image.layers[0].image_frame, srgba8_frame(3, 2, [255, 0, 0, 255]));
+		assert_eq!(image.layers[2].image_frame, srgba8_frame(1, 1, [0, 255, 0, 128]));
+
+		assert_eq!(image.groups.len(), 1);
+		assert_eq!((image.groups[0].name.as_str(), image.groups[0].visible, image.groups[0].parent), ("Group", false, None));
+	}
+
+	#[test]
+	fn documents_without_layers_use_the_flattened_image() {
+		let image = decode_psd(&psd_file(UVec2::new(2, 2), &[]), WorkingSpace::Srgb).unwrap();
+		assert_eq!(image.layers.len(), 1);
+		assert_eq!(image.layers[0].image_frame, srgba8_frame(2, 2, [255; 4]));
+	}
+
+	#[test]
+	fn images_with_too_many_pixels_are_rejected() {
+		let mut file = psd_file(UVec2::ONE, &[]);
+		// The height and width in the header
+		file[14..22].copy_from_slice(&[u32::MAX.to_be_bytes(), u32::MAX.to_be_bytes()].concat());
+		assert!(decode_psd(&file, WorkingSpace::Srgb).is_err());
+
+		let file = psd_file(
+			UVec2::ONE,
+			&[TestLayer {
+				rect: [i32::MIN, i32::MIN, i32::MAX, i32::MAX],
+				..layer("Huge", [0; 4], [0; 4])
+			}],
+		);
+		assert!(decode_psd(&file, WorkingSpace::Srgb).is_err());
+
+		// Small enough to be allocated, but too large to be opened
+		let file = psd_file(
+			UVec2::ONE,
+			&[TestLayer {
+				rect: [0, 0, 10_000, 10_000],
+				..layer("Large", [0; 4], [0; 4])
+			}],
+		);
+		let error = decode_psd(&file, WorkingSpace::Srgb).map(|_| ()).unwrap_err();
+		assert!(error.contains("10000x10000 pixels"), "{error}");
+	}
+
+	#[test]
+	fn packbits_rows_are_unpacked() {
+		let mut samples = Vec::new();
+		unpack_bits(&[0xFE, 7, 0x80, 0x01, 1, 2], &mut samples, 5).unwrap();
+		assert_eq!(samples, [7, 7, 7, 1, 2]);
+	}
+}
@@ -0,0 +1,402 @@
+use super::image_files;
+use super::layered_images::{self, ImageLayer, LayeredImage};
+use crate::messages::frontend::utility_types::{BitDepth, ExportBounds, SequenceFileType};
+
+use graph_craft::document::{NodeId, NodeInput};
+use graphene_core::raster::color_management::WorkingSpace;
+use graphene_std::color_profile;
+
+use glam::{IVec2, UVec2};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::codecs::webp::WebPEncoder;
+use image::{Delay, DynamicImage, Frame, RgbaImage};
+use std::io::{Cursor, Write};
+use std::time::Duration;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// The pixels of one rasterized frame of a sequence export, in row-major RGBA order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RasterFrame {
+	pub width: u32,
+	pub height: u32,
+	pub rgba: Vec<u8>,
+}
+
+/// What is changed in the document before a frame of a sequence export is rendered.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SequenceExportSetup {
+	Unchanged,
+	/// Moves the timeline to the given time in seconds.
+	Time(f64),
+	/// Sets the swept input to the given value.
+	InputValue(f64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SequenceExportStep {
+	pub bounds: ExportBounds,
+	pub setup: SequenceExportSetup,
+}
+
+/// The state of an export of several frames, which are rendered one at a time because each may need the node graph to be evaluated first.
+#[derive(Clone, Debug)]
+pub struct SequenceExport {
+	pub file_name: String,
+	pub file_type: SequenceFileType,
+	pub scale_factor: f64,
+	pub transparent_background: bool,
+	/// Bits per channel of the PNG and TIFF files.
+	pub bit_depth: BitDepth,
+	/// Whether the PNG and JPG files describe their colors with an embedded sRGB profile.
+	pub embed_color_profile: bool,
+	/// Frames per second of the animated file types.
+	pub frame_rate: f64,
+	pub steps: Vec<SequenceExportStep>,
+	pub frames: Vec<RasterFrame>,
+	/// Set while waiting for the node graph evaluation of the current step before it can be rendered.
+	pub awaiting_evaluation: bool,
+	/// The timeline position to return to once the export is finished.
+	pub original_time: f64,
+	/// The swept input (node, input index, and its value before the export) to restore once the export is finished.
+	pub swept_input: Option<(NodeId, usize, NodeInput)>,
+}
+
+/// A file produced by a sequence export, ready to be downloaded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportedFile {
+	pub name: String,
+	pub mime: String,
+	pub data: Vec<u8>,
+}
+
+/// Encodes the rendered frames into the files of the chosen type. Frames of differing sizes are placed in the top left of a canvas large enough for all of them.
+/// The bit depth applies to the PNG and TIFF files, whose frames are widened from the 8 bit rendering when 16 bits are requested. OpenEXR files store the frames in half floats.
+/// The profile of the given color space is embedded in the PNG and JPG files.
+pub fn encode_sequence(
+	file_stem: &str,
+	file_type: SequenceFileType,
+	frames: &[RasterFrame],
+	frame_rate: f64,
+	bit_depth: BitDepth,
+	color_profile: Option<WorkingSpace>,
+) -> Result<Vec<ExportedFile>, String> {
+	if frames.is_empty() {
+		return Err("The export contains no frames".to_string());
+	}
+
+	let single_file = |data, extension: &str| {
+		Ok(vec![ExportedFile {
+			name: format!("{file_stem}.{extension}"),
+			mime: file_type.to_mime().to_string(),
+			data,
+		}])
+	};
+	let embed_profile = |data: Vec<u8>| match color_profile {
+		Some(working_space) => color_profile::embed_profile(&data, &working_space.icc_profile(), working_space.name()),
+		None => Ok(data),
+	};
+	let numbered_files = |extension: &str, mime: &str, encode: &dyn Fn(&RasterFrame) -> Result<Vec<u8>, String>| {
+		frames
+			.iter()
+			.enumerate()
+			.map(|(index, frame)| {
+				let name = match frames.len() {
+					1 => format!("{file_stem}.{extension}"),
+					_ => format!("{file_stem}_{index:04}.{extension}"),
+				};
+				Ok(ExportedFile {
+					name,
+					mime: mime.to_string(),
+					data: embed_profile(encode(frame)?)?,
+				})
+			})
+			.collect::<Result<Vec<_>, String>>()
+	};
+	let numbered_png_files = || numbered_files("png", "image/png", &|frame| image_files::encode_png(&frame_image(frame, bit_depth)?));
+
+	let frame_duration = Duration::from_secs_f64(1. / frame_rate.max(f64::EPSILON));
+	match file_type {
+		SequenceFileType::PngSequence => numbered_png_files(),
+		SequenceFileType::JpgSequence => numbered_files("jpg", "image/jpeg", &|frame| image_files::encode_jpg(&frame_image(frame, BitDepth::Eight)?)),
+		SequenceFileType::Gif => single_file(encode_gif(&pad_to_common_size(frames), frame_duration)?, "gif"),
+		SequenceFileType::Apng => single_file(embed_profile(encode_apng(&pad_to_common_size(frames), frame_rate)?)?, "png"),
+		SequenceFileType::WebP => single_file(encode_animated_webp(&pad_to_common_size(frames), frame_duration)?, "webp"),
+		SequenceFileType::Zip => single_file(write_zip(&numbered_png_files()?)?, "zip"),
+		SequenceFileType::Tiff => {
+			let pages = frames.iter().map(|frame| frame_image(frame, bit_depth)).collect::<Result<Vec<_>, String>>()?;
+			single_file(image_files::encode_tiff(&pages)?, "tiff")
+		}
+		SequenceFileType::Exr => {
+			let layers = frames
+				.iter()
+				.enumerate()
+				.map(|(index, frame)| {
+					let image_frame = image_files::image_to_image_frame(frame_image(frame, BitDepth::Eight)?);
+					Ok(ImageLayer::new(format!("Frame {}", index + 1), image_frame, IVec2::ZERO))
+				})
+				.collect::<Result<Vec<_>, String>>()?;
+			let size = frames.iter().fold(UVec2::ZERO, |size, frame| size.max(UVec2::new(frame.width, frame.height)));
+			// The frames are rendered in sRGB, whatever the working space of the document
+			single_file(layered_images::encode_exr(&LayeredImage { size, layers, groups: Vec::new() }, WorkingSpace::Srgb)?, "exr")
+		}
+	}
+}
+
+fn pad_to_common_size(frames: &[RasterFrame]) -> Vec<RasterFrame> {
+	let width = frames.iter().map(|frame| frame.width).max().unwrap_or_default();
+	let height = frames.iter().map(|frame| frame.height).max().unwrap_or_default();
+
+	frames
+		.iter()
+		.map(|frame| {
+			if frame.width == width && frame.height == height {
+				return frame.clone();
+			}
+
+			let mut rgba = vec![0; width as usize * height as usize * 4];
+			for (row_index, row) in frame.rgba.chunks_exact(frame.width as usize * 4).enumerate() {
+				let start = row_index * width as usize * 4;
+				rgba[start..start + row.len()].copy_from_slice(row);
+			}
+			RasterFrame { width, height, rgba }
+		})
+		.collect()
+}
+
+fn frame_image(frame: &RasterFrame, bit_depth: BitDepth) -> Result<DynamicImage, String> {
+	image_files::rgba8_to_image(frame.width, frame.height, frame.rgba.clone(), bit_depth).ok_or_else(|| "Frame pixel data doesn't match its size".to_string())
+}
+
+fn encode_gif(frames: &[RasterFrame], frame_duration: Duration) -> Result<Vec<u8>, String> {
+	let mut data = Vec::new();
+	{
+		let mut encoder = GifEncoder::new(&mut data);
+		encoder.set_repeat(Repeat::Infinite).map_err(|error| format!("Failed to encode GIF: {error}"))?;
+
+		let delay = Delay::from_saturating_duration(frame_duration);
+		let gif_frames = frames.iter().map(|frame| {
+			let image = RgbaImage::from_raw(frame.width, frame.height, frame.rgba.clone()).expect("Frame pixel data doesn't match its size");
+			Frame::from_parts(image, 0, 0, delay)
+		});
+		encoder.encode_frames(gif_frames).map_err(|error| format!("Failed to encode GIF: {error}"))?;
+	}
+	Ok(data)
+}
+
+fn encode_apng(frames: &[RasterFrame], frame_rate: f64) -> Result<Vec<u8>, String> {
+	let error = |error: png::EncodingError| format!("Failed to encode APNG: {error}");
+	let (width, height) = (frames[0].width, frames[0].height);
+
+	let mut data = Vec::new();
+	{
+		let mut encoder = png::Encoder::new(&mut data, width, height);
+		encoder.set_color(png::ColorType::Rgba);
+		encoder.set_depth(png::BitDepth::Eight);
+		encoder.set_animated(frames.len() as u32, 0).map_err(error)?;
+		// The delay is stored as a fraction of a second with 16 bit parts, so hundredths of a frame are kept
+		encoder.set_frame_delay(100, (frame_rate * 100.).round().clamp(1., u16::MAX as f64) as u16).map_err(error)?;
+
+		let mut writer = encoder.write_header().map_err(error)?;
+		for frame in frames {
+			writer.write_image_data(&frame.rgba).map_err(error)?;
+		}
+		writer.finish().map_err(error)?;
+	}
+	Ok(data)
+}
+
+/// Builds an animated WebP by wrapping the frames, losslessly encoded by the `image` crate which only encodes still images, in the animation chunks of the extended file format.
+fn encode_animated_webp(frames: &[RasterFrame], frame_duration: Duration) -> Result<Vec<u8>, String> {
+	let (width, height) = (frames[0].width, frames[0].height);
+	let duration_milliseconds = (frame_duration.as_millis() as u32).min(0xFF_FFFF);
+
+	let mut body = b"WEBP".to_vec();
+
+	// VP8X: the animation and alpha flags followed by the canvas size
+	let mut vp8x = vec![0b0001_0010, 0, 0, 0];
+	vp8x.extend_from_slice(&u24_bytes(width - 1)?);
+	vp8x.extend_from_slice(&u24_bytes(height - 1)?);
+	push_riff_chunk(&mut body, b"VP8X", &vp8x)?;
+
+	// ANIM: a transparent background color and infinite looping
+	push_riff_chunk(&mut body, b"ANIM", &[0, 0, 0, 0, 0, 0])?;
+
+	for frame in frames {
+		let mut encoded = Vec::new();
+		WebPEncoder::new_lossless(&mut encoded)
+			.encode(&frame.rgba, frame.width, frame.height, image::ColorType::Rgba8)
+			.map_err(|error| format!("Failed to encode WebP: {error}"))?;
+		let bitstream = riff_chunks(&encoded)
+			.find(|(id, _)| id == b"VP8L")
+			.map(|(_, payload)| payload)
+			.ok_or_else(|| "Failed to encode WebP: the encoded frame has no lossless bitstream".to_string())?;
+
+		// ANMF: the frame offset, size, duration, and flags (don't blend, don't dispose) followed by the frame's bitstream chunk
+		let mut anmf = Vec::new();
+		anmf.extend_from_slice(&u24_bytes(0)?);
+		anmf.extend_from_slice(&u24_bytes(0)?);
+		anmf.extend_from_slice(&u24_bytes(frame.width - 1)?);
+		anmf.extend_from_slice(&u24_bytes(frame.height - 1)?);
+		anmf.extend_from_slice(&u24_bytes(duration_milliseconds)?);
+		anmf.push(0b0000_0010);
+		push_riff_chunk(&mut anmf, b"VP8L", bitstream)?;
+		push_riff_chunk(&mut body, b"ANMF", &anmf)?;
+	}
+
+	let mut data = Vec::new();
+	push_riff_chunk(&mut data, b"RIFF", &body)?;
+	Ok(data)
+}
+
+fn u24_bytes(value: u32) -> Result<[u8; 3], String> {
+	let [a, b, c, d] = value.to_le_bytes();
+	if d != 0 {
+		return Err("Failed to encode WebP: the animation is too large for the file format".to_string());
+	}
+	Ok([a, b, c])
+}
+
+/// Appends a chunk, failing rather than writing a corrupt file when the payload is too large for the 32 bit size of RIFF chunks.
+fn push_riff_chunk(data: &mut Vec<u8>, id: &[u8; 4], payload: &[u8]) -> Result<(), String> {
+	let size = u32::try_from(payload.len())
+		.ok()
+		.filter(|&size| size < u32::MAX)
+		.ok_or("Failed to encode WebP: the animation is too large for the file format")?;
+
+	data.extend_from_slice(id);
+	data.extend_from_slice(&size.to_le_bytes());
+	data.extend_from_slice(payload);
+	// Chunks are padded to an even length
+	if payload.len() % 2 == 1 {
+		data.push(0);
+	}
+	Ok(())
+}
+
+/// Iterates over the chunks inside a RIFF WebP file, yielding each chunk's identifier and payload.
+fn riff_chunks(data: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> {
+	// Skip the "RIFF", file size, and "WEBP" header
+	let mut remaining = data.get(12..).unwrap_or_default();
+	std::iter::from_fn(move || {
+		let id: [u8; 4] = remaining.get(0..4)?.try_into().ok()?;
+		let size = u32::from_le_bytes(remaining.get(4..8)?.try_into().ok()?) as usize;
+		let payload = remaining.get(8..8 + size)?;
+		remaining = remaining.get(8 + size + size % 2..).unwrap_or_default();
+		Some((id, payload))
+	})
+}
+
+/// Writes a zip archive which stores the given files without compression, since the exported images are already compressed.
+/// Files and archives beyond 4 GiB are written with the zip64 extensions.
+fn write_zip(files: &[ExportedFile]) -> Result<Vec<u8>, String> {
+	let error = |error: zip::result::ZipError| format!("Failed to write zip archive: {error}");
+
+	let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+	for file in files {
+		let options = FileOptions::default()
+			.compression_method(CompressionMethod::Stored)
+			.large_file(file.data.len() as u64 >= u32::MAX as u64);
+		writer.start_file(file.name.as_str(), options).map_err(error)?;
+		writer.write_all(&file.data).map_err(|error| format!("Failed to write zip archive: {error}"))?;
+	}
+	Ok(writer.finish().map_err(error)?.into_inner())
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	use image::codecs::gif::GifDecoder;
+	use image::AnimationDecoder;
+
+	fn test_frames() -> Vec<RasterFrame> {
+		vec![
+			RasterFrame {
+				width: 2,
+				height: 2,
+				rgba: [255, 0, 0, 255].repeat(4),
+			},
+			RasterFrame {
+				width: 1,
+				height: 1,
+				rgba: vec![0, 0, 255, 255],
+			},
+		]
+	}
+
+	#[test]
+	fn png_sequence_is_numbered() {
+		let files = encode_sequence("Animation", SequenceFileType::PngSequence, &test_frames(), 30., BitDepth::Eight, None).unwrap();
+		let names = files.iter().map(|file| file.name.as_str()).collect::<Vec<_>>();
+		assert_eq!(names, ["Animation_0000.png", "Animation_0001.png"]);
+
+		let second = image::load_from_memory(&files[1].data).unwrap();
+		assert_eq!((second.width(), second.height()), (1, 1));
+	}
+
+	#[test]
+	fn single_sixteen_bit_png() {
+		let files = encode_sequence("Image", SequenceFileType::PngSequence, &test_frames()[..1], 30., BitDepth::Sixteen, None).unwrap();
+		assert_eq!(files[0].name, "Image.png");
+
+		let DynamicImage::ImageRgba16(image) = image::load_from_memory(&files[0].data).unwrap() else {
+			panic!("Expected a 16 bit RGBA image");
+		};
+		assert_eq!(image.get_pixel(1, 1).0, [65535, 0, 0, 65535]);
+	}
+
+	#[test]
+	fn jpg_sequence_embeds_profile() {
+		let files = encode_sequence("Animation", SequenceFileType::JpgSequence, &test_frames(), 30., BitDepth::Eight, Some(WorkingSpace::Srgb)).unwrap();
+		assert_eq!(files[0].name, "Animation_0000.jpg");
+		assert_eq!(color_profile::read_embedded_profile(&files[1].data), Some(WorkingSpace::Srgb.icc_profile()));
+		assert!(image::load_from_memory(&files[1].data).is_ok());
+	}
+
+	#[test]
+	fn exr_has_a_part_per_frame() {
+		let files = encode_sequence("Animation", SequenceFileType::Exr, &test_frames(), 30., BitDepth::Eight, None).unwrap();
+		assert_eq!(files[0].name, "Animation.exr");
+
+		let image = layered_images::decode_layered_image(&files[0].data, WorkingSpace::Srgb).unwrap();
+		assert_eq!(image.size, UVec2::new(2, 2));
+		let names = image.layers.iter().map(|layer| layer.name.as_str()).collect::<Vec<_>>();
+		assert_eq!(names, ["Frame 1", "Frame 2"]);
+	}
+
+	#[test]
+	fn gif_frames_are_padded() {
+		let files = encode_sequence("Animation", SequenceFileType::Gif, &test_frames(), 10., BitDepth::Eight, None).unwrap();
+		let frames = GifDecoder::new(files[0].data.as_slice()).unwrap().into_frames().collect_frames().unwrap();
+		assert_eq!(frames.len(), 2);
+		assert_eq!(frames[1].buffer().dimensions(), (2, 2));
+		assert_eq!(frames[1].buffer().get_pixel(1, 1).0[3], 0);
+		assert_eq!(frames[0].delay(), Delay::from_numer_denom_ms(100, 1));
+	}
+
+	#[test]
+	fn animated_webp_contains_every_frame() {
+		let files = encode_sequence("Animation", SequenceFileType::WebP, &test_frames(), 30., BitDepth::Eight, None).unwrap();
+		let data = &files[0].data;
+		assert_eq!(&data[0..4], b"RIFF");
+		assert_eq!(u32::from_le_bytes(data[4..8].try_into().unwrap()) as usize, data.len() - 8);
+
+		let chunk_ids = riff_chunks(data).map(|(id, _)| id).collect::<Vec<_>>();
+		assert_eq!(chunk_ids, [*b"VP8X", *b"ANIM", *b"ANMF", *b"ANMF"]);
+	}
+
+	#[test]
+	fn zip_lists_all_files() {
+		let files = encode_sequence("Animation", SequenceFileType::Zip, &test_frames(), 30., BitDepth::Eight, None).unwrap();
+		assert_eq!(files[0].name, "Animation.zip");
+
+		let mut archive = zip::ZipArchive::new(Cursor::new(&files[0].data)).unwrap();
+		let names = archive.file_names().collect::<std::collections::BTreeSet<_>>();
+		assert_eq!(names.into_iter().collect::<Vec<_>>(), ["Animation_0000.png", "Animation_0001.png"]);
+
+		let mut second = Vec::new();
+		std::io::Read::read_to_end(&mut archive.by_name("Animation_0001.png").unwrap(), &mut second).unwrap();
+		assert_eq!(image::load_from_memory(&second).unwrap().width(), 1);
+	}
+}
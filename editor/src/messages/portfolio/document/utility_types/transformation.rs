@@ -9,7 +9,8 @@ use document_legacy::document_metadata::LayerNodeIdentifier;
 use graphene_core::renderer::Quad;
 use graphene_core::vector::{ManipulatorPointId, SelectedType};
 
-use glam::{DAffine2, DVec2};
+use glam::{DAffine2, DMat2, DVec2};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 
 #[derive(Debug, PartialEq, Clone)]
@@ -80,17 +81,43 @@ pub enum Axis {
 	Both,
 	X,
 	Y,
+	LocalX,
+	LocalY,
 }
 
 impl Axis {
 	pub fn set_or_toggle(&mut self, target: Axis) {
-		// If constrained to an axis and target is requesting the same axis, toggle back to Both
-		if *self == target {
-			*self = Axis::Both;
+		*self = match (*self, target) {
+			// Pressing the key of the constrained global axis again switches to the local axis of the layer
+			(Axis::X, Axis::X) => Axis::LocalX,
+			(Axis::Y, Axis::Y) => Axis::LocalY,
+			// Pressing it a third time removes the constraint
+			(Axis::LocalX, Axis::X) | (Axis::LocalY, Axis::Y) => Axis::Both,
+			// If current axis is different from the target axis, switch to the target
+			_ => target,
+		};
+	}
+
+	pub fn is_local(self) -> bool {
+		matches!(self, Axis::LocalX | Axis::LocalY)
+	}
+
+	/// The rotation of the coordinate frame the constraint is measured in, which is the layer's own rotation for the local axes.
+	pub fn frame_angle(self, local_angle: f64) -> f64 {
+		if self.is_local() {
+			local_angle
+		} else {
+			0.
 		}
-		// If current axis is different from the target axis, switch to the target
-		else {
-			*self = target;
+	}
+
+	fn label(self) -> &'static str {
+		match self {
+			Axis::Both => "",
+			Axis::X => "X",
+			Axis::Y => "Y",
+			Axis::LocalX => "Local X",
+			Axis::LocalY => "Local Y",
 		}
 	}
 }
@@ -98,27 +125,34 @@ impl Axis {
 #[derive(Default, Debug, Clone, PartialEq, Copy)]
 pub struct Translation {
 	pub dragged_distance: DVec2,
-	pub typed_distance: Option<f64>,
+	pub typed_distance: Option<DVec2>,
 	pub constraint: Axis,
 }
 
 impl Translation {
-	pub fn to_dvec(self) -> DVec2 {
+	/// The distance moved, measured along the axes of the constraint's coordinate frame.
+	pub fn constrained(self, local_angle: f64) -> DVec2 {
 		if let Some(value) = self.typed_distance {
-			if self.constraint == Axis::Y {
-				return DVec2::new(0., value);
-			} else {
-				return DVec2::new(value, 0.);
-			}
+			// A single axis is typed into the first field
+			return match self.constraint {
+				Axis::Both => value,
+				Axis::X | Axis::LocalX => DVec2::new(value.x, 0.),
+				Axis::Y | Axis::LocalY => DVec2::new(0., value.x),
+			};
 		}
 
+		let dragged_distance = DMat2::from_angle(-self.constraint.frame_angle(local_angle)) * self.dragged_distance;
 		match self.constraint {
-			Axis::Both => self.dragged_distance,
-			Axis::X => DVec2::new(self.dragged_distance.x, 0.),
-			Axis::Y => DVec2::new(0., self.dragged_distance.y),
+			Axis::Both => dragged_distance,
+			Axis::X | Axis::LocalX => DVec2::new(dragged_distance.x, 0.),
+			Axis::Y | Axis::LocalY => DVec2::new(0., dragged_distance.y),
 		}
 	}
 
+	pub fn to_dvec(self, local_angle: f64) -> DVec2 {
+		DMat2::from_angle(self.constraint.frame_angle(local_angle)) * self.constrained(local_angle)
+	}
+
 	#[must_use]
 	pub fn increment_amount(self, delta: DVec2) -> Self {
 		Self {
@@ -172,7 +206,7 @@ impl Rotation {
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub struct Scale {
 	pub dragged_factor: f64,
-	pub typed_factor: Option<f64>,
+	pub typed_factor: Option<DVec2>,
 	pub constraint: Axis,
 }
 
@@ -187,14 +221,16 @@ impl Default for Scale {
 }
 
 impl Scale {
+	/// The scale factors along the axes of the constraint's coordinate frame.
 	pub fn to_dvec(self, snap: bool) -> DVec2 {
-		let factor = if let Some(value) = self.typed_factor { value } else { self.dragged_factor };
+		let factor = self.typed_factor.unwrap_or(DVec2::splat(self.dragged_factor));
 		let factor = if snap { (factor / SCALE_SNAP_INTERVAL).round() * SCALE_SNAP_INTERVAL } else { factor };
 
+		// A single axis is typed into the first field
 		match self.constraint {
-			Axis::Both => DVec2::splat(factor),
-			Axis::X => DVec2::new(factor, 1.),
-			Axis::Y => DVec2::new(1., factor),
+			Axis::Both => factor,
+			Axis::X | Axis::LocalX => DVec2::new(factor.x, 1.),
+			Axis::Y | Axis::LocalY => DVec2::new(1., factor.x),
 		}
 	}
 
@@ -226,21 +262,51 @@ pub enum TransformOperation {
 }
 
 impl TransformOperation {
-	pub fn apply_transform_operation(&self, selected: &mut Selected, snapping: bool, axis_constraint: Axis) {
+	pub fn axis_constraint(&self) -> Axis {
+		match self {
+			TransformOperation::Grabbing(translation) => translation.constraint,
+			TransformOperation::Scaling(scale) => scale.constraint,
+			_ => Axis::Both,
+		}
+	}
+
+	/// The number of values that can be typed, which are cycled through with <kbd>Tab</kbd>.
+	pub fn field_count(&self) -> usize {
+		match self {
+			TransformOperation::None => 0,
+			TransformOperation::Grabbing(_) | TransformOperation::Scaling(_) if self.axis_constraint() == Axis::Both => 2,
+			_ => 1,
+		}
+	}
+
+	/// The factor converting a typed value in the given unit into the units of this operation, or `None` if the unit doesn't apply to it.
+	pub fn unit_scale(&self, unit: TypedUnit) -> Option<f64> {
+		match (self, unit) {
+			(TransformOperation::Grabbing(_), TypedUnit::Pixels) => Some(1.),
+			(TransformOperation::Rotating(_), TypedUnit::Degrees) => Some(1.),
+			(TransformOperation::Scaling(_), TypedUnit::Percent) => Some(0.01),
+			_ => None,
+		}
+	}
+
+	pub fn apply_transform_operation(&self, selected: &mut Selected, snapping: bool, local_angle: f64, typing: &Typing) {
 		if self != &TransformOperation::None {
 			let transformation = match self {
-				TransformOperation::Grabbing(translation) => DAffine2::from_translation(translation.to_dvec()),
+				TransformOperation::Grabbing(translation) => DAffine2::from_translation(translation.to_dvec(local_angle)),
 				TransformOperation::Rotating(rotation) => DAffine2::from_angle(rotation.to_f64(snapping)),
-				TransformOperation::Scaling(scale) => DAffine2::from_scale(scale.to_dvec(snapping)),
+				TransformOperation::Scaling(scale) => {
+					let frame = DAffine2::from_angle(scale.constraint.frame_angle(local_angle));
+					frame * DAffine2::from_scale(scale.to_dvec(snapping)) * frame.inverse()
+				}
 				TransformOperation::None => unreachable!(),
 			};
 
 			selected.update_transforms(transformation);
-			self.hints(snapping, axis_constraint, selected.responses);
+			self.hints(snapping, local_angle, typing, selected.responses);
 		}
 	}
 
-	pub fn constrain_axis(&mut self, axis: Axis, selected: &mut Selected, snapping: bool) {
+	pub fn constrain_axis(&mut self, axis: Axis, selected: &mut Selected, snapping: bool, local_angle: f64, typing: &Typing) {
 		match self {
 			TransformOperation::None => (),
 			TransformOperation::Grabbing(translation) => translation.constraint.set_or_toggle(axis),
@@ -248,49 +314,71 @@ impl TransformOperation {
 			TransformOperation::Scaling(scale) => scale.constraint.set_or_toggle(axis),
 		};
 
-		self.apply_transform_operation(selected, snapping, axis);
+		self.apply_transform_operation(selected, snapping, local_angle, typing);
 	}
 
-	pub fn grs_typed(&mut self, typed: Option<f64>, selected: &mut Selected, snapping: bool) {
+	pub fn grs_typed(&mut self, typing: &Typing, selected: &mut Selected, snapping: bool, local_angle: f64) {
+		let [first, second] = [0, 1].map(|field| typing.evaluate(field, |unit| self.unit_scale(unit)));
+		let any_typed = first.is_some() || second.is_some();
+
 		match self {
 			TransformOperation::None => (),
-			TransformOperation::Grabbing(translation) => translation.typed_distance = typed,
-			TransformOperation::Rotating(rotation) => rotation.typed_angle = typed,
-			TransformOperation::Scaling(scale) => scale.typed_factor = typed,
-		};
-
-		let axis_constraint = match self {
-			TransformOperation::Grabbing(grabbing) => grabbing.constraint,
-			TransformOperation::Scaling(scaling) => scaling.constraint,
-			_ => Axis::Both,
+			TransformOperation::Grabbing(translation) => translation.typed_distance = any_typed.then(|| DVec2::new(first.unwrap_or(0.), second.unwrap_or(0.))),
+			TransformOperation::Rotating(rotation) => rotation.typed_angle = first,
+			TransformOperation::Scaling(scale) => {
+				// A single typed factor scales uniformly
+				let x = first.unwrap_or(1.);
+				scale.typed_factor = any_typed.then(|| DVec2::new(x, second.unwrap_or(x)));
+			}
 		};
 
-		self.apply_transform_operation(selected, snapping, axis_constraint);
+		self.apply_transform_operation(selected, snapping, local_angle, typing);
 	}
 
-	pub fn hints(&self, snapping: bool, axis_constraint: Axis, responses: &mut VecDeque<Message>) {
+	pub fn hints(&self, snapping: bool, local_angle: f64, typing: &Typing, responses: &mut VecDeque<Message>) {
 		use crate::messages::input_mapper::utility_types::input_keyboard::Key;
 		use crate::messages::tool::utility_types::{HintData, HintGroup, HintInfo};
 
 		let mut hints = Vec::new();
 
+		let axis_constraint = self.axis_constraint();
+
+		// Shows the expression typed into a field next to its result, with brackets around the field being typed into
+		let field = |index: usize, value: f64| {
+			let expression = &typing.fields[index];
+			let text = if expression.is_empty() {
+				value.to_string()
+			} else if typing.evaluate(index, |unit| self.unit_scale(unit)).is_some() {
+				format!("{expression} = {value}")
+			} else {
+				expression.clone()
+			};
+			if !typing.is_empty() && typing.active_field == index {
+				format!("[{text}]")
+			} else {
+				text
+			}
+		};
 		let axis_str = |vector: DVec2, separate: bool| match axis_constraint {
 			Axis::Both => {
 				if separate {
-					format!("X: {}, Y: {}", vector.x, vector.y)
+					format!("X: {}, Y: {}", field(0, vector.x), field(1, vector.y))
 				} else {
-					vector.x.to_string()
+					field(0, vector.x)
 				}
 			}
-			Axis::X => format!("X: {}", vector.x),
-			Axis::Y => format!("Y: {}", vector.y),
+			Axis::X | Axis::LocalX => format!("{}: {}", axis_constraint.label(), field(0, vector.x)),
+			Axis::Y | Axis::LocalY => format!("{}: {}", axis_constraint.label(), field(0, vector.y)),
 		};
 
 		let value_str = match self {
 			TransformOperation::None => String::new(),
-			TransformOperation::Grabbing(translation) => format!("Translate {}", axis_str(translation.to_dvec(), true)),
-			TransformOperation::Rotating(rotation) => format!("Rotate {}°", rotation.to_f64(snapping) * 360. / std::f64::consts::TAU),
-			TransformOperation::Scaling(scale) => format!("Scale {}", axis_str(scale.to_dvec(snapping), false)),
+			TransformOperation::Grabbing(translation) => format!("Translate {}", axis_str(translation.constrained(local_angle), true)),
+			TransformOperation::Rotating(rotation) => format!("Rotate {}°", field(0, rotation.to_f64(snapping).to_degrees())),
+			TransformOperation::Scaling(scale) => {
+				let factor = scale.to_dvec(snapping);
+				format!("Scale {}", axis_str(factor, factor.x != factor.y || !typing.fields[1].is_empty()))
+			}
 		};
 		hints.push(HintInfo::label(value_str));
 		hints.push(HintInfo::keys([Key::Shift], "Precision Mode"));
@@ -298,11 +386,25 @@ impl TransformOperation {
 			hints.push(HintInfo::keys([Key::Control], "Snap"));
 		}
 		if matches!(self, TransformOperation::Grabbing(_) | TransformOperation::Scaling(_)) {
-			hints.push(HintInfo::keys([Key::KeyX], "X Axis"));
-			hints.push(HintInfo::keys([Key::KeyY], "Y Axis"));
+			hints.push(HintInfo::keys([Key::KeyX], "X Axis (Twice for Local)"));
+			hints.push(HintInfo::keys([Key::KeyY], "Y Axis (Twice for Local)"));
+		}
+		if self.field_count() > 1 {
+			hints.push(HintInfo::keys([Key::Tab], "Next Field"));
 		}
 
-		let hint_data = HintData(vec![HintGroup(hints)]);
+		let mut switch_hints = Vec::new();
+		if !matches!(self, TransformOperation::Grabbing(_)) {
+			switch_hints.push(HintInfo::keys([Key::KeyG], "Grab"));
+		}
+		if !matches!(self, TransformOperation::Rotating(_)) {
+			switch_hints.push(HintInfo::keys([Key::KeyR], "Rotate"));
+		}
+		if !matches!(self, TransformOperation::Scaling(_)) {
+			switch_hints.push(HintInfo::keys([Key::KeyS], "Scale"));
+		}
+
+		let hint_data = HintData(vec![HintGroup(hints), HintGroup(switch_hints)]);
 		responses.add(FrontendMessage::UpdateInputHints { hint_data });
 	}
 }
@@ -366,6 +468,13 @@ impl<'a> Selected<'a> {
 		(min + max) / 2.
 	}
 
+	/// The viewport angle of the first selected layer's X axis, which the local axis constraints are measured along.
+	pub fn local_axis_angle(&self) -> f64 {
+		let Some(&layer) = self.selected.first() else { return 0. };
+		let x_axis = self.document.metadata.transform_to_viewport(layer).matrix2.x_axis;
+		x_axis.y.atan2(x_axis.x)
+	}
+
 	pub fn update_transforms(&mut self, delta: DAffine2) {
 		if !self.selected.is_empty() {
 			let pivot = DAffine2::from_translation(*self.pivot);
@@ -455,83 +564,268 @@ impl<'a> Selected<'a> {
 	}
 }
 
+/// A unit which can follow a number typed during a transform operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TypedUnit {
+	Pixels,
+	Percent,
+	Degrees,
+}
+
+impl TypedUnit {
+	const ALL: [TypedUnit; 3] = [TypedUnit::Pixels, TypedUnit::Percent, TypedUnit::Degrees];
+
+	pub fn symbol(self) -> &'static str {
+		match self {
+			TypedUnit::Pixels => "px",
+			TypedUnit::Percent => "%",
+			TypedUnit::Degrees => "deg",
+		}
+	}
+}
+
+/// The expressions typed during a transform operation, with one field for each axis that can be typed.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct Typing {
-	pub digits: Vec<u8>,
-	pub contains_decimal: bool,
-	pub negative: bool,
+	pub fields: [String; 2],
+	pub active_field: usize,
 }
 
-const DECIMAL_POINT: u8 = 10;
-
 impl Typing {
-	pub fn type_number(&mut self, number: u8) -> Option<f64> {
-		self.digits.push(number);
+	pub fn is_empty(&self) -> bool {
+		self.fields.iter().all(String::is_empty)
+	}
+
+	fn active(&mut self) -> &mut String {
+		&mut self.fields[self.active_field]
+	}
 
-		self.evaluate()
+	fn ends_with_unit(&self) -> bool {
+		let field = &self.fields[self.active_field];
+		TypedUnit::ALL.iter().any(|unit| field.ends_with(unit.symbol()))
 	}
 
-	pub fn type_backspace(&mut self) -> Option<f64> {
-		if self.digits.is_empty() {
-			return None;
+	/// Whether the last character typed ends a value, so it can be followed by an operator or unit.
+	fn ends_with_value(&self) -> bool {
+		let field = &self.fields[self.active_field];
+		field.ends_with(|c: char| c.is_ascii_digit() || c == '.' || c == ')') || self.ends_with_unit()
+	}
+
+	pub fn type_number(&mut self, number: u8) {
+		if self.ends_with_unit() || self.fields[self.active_field].ends_with(')') {
+			return;
 		}
+		self.active().push(char::from(b'0' + number));
+	}
 
-		match self.digits.pop() {
-			Some(DECIMAL_POINT) => self.contains_decimal = false,
-			Some(_) => (),
-			None => self.negative = false,
+	pub fn type_backspace(&mut self) {
+		// Units are removed as a whole
+		if let Some(unit) = TypedUnit::ALL.into_iter().find(|unit| self.fields[self.active_field].ends_with(unit.symbol())) {
+			let length = self.fields[self.active_field].len() - unit.symbol().len();
+			self.active().truncate(length);
+		} else {
+			self.active().pop();
 		}
+	}
 
-		self.evaluate()
+	pub fn type_decimal_point(&mut self) {
+		let field = &self.fields[self.active_field];
+		let number = field.rsplit(|c: char| !c.is_ascii_digit() && c != '.').next().unwrap_or_default();
+		if number.contains('.') || self.ends_with_unit() || field.ends_with(')') {
+			return;
+		}
+		if number.is_empty() {
+			self.active().push('0');
+		}
+		self.active().push('.');
 	}
 
-	pub fn type_decimal_point(&mut self) -> Option<f64> {
-		if !self.contains_decimal {
-			self.contains_decimal = true;
-			self.digits.push(DECIMAL_POINT);
+	/// Types one of `+`, `-`, `*`, `/`, `(` or `)`. A minus sign which doesn't follow a value negates the value after it.
+	pub fn type_operator(&mut self, operator: char) {
+		let valid = match operator {
+			'+' | '*' | '/' | ')' => self.ends_with_value(),
+			'-' => true,
+			'(' => !self.ends_with_value(),
+			_ => false,
+		};
+		let open_parentheses = self.fields[self.active_field].matches('(').count() > self.fields[self.active_field].matches(')').count();
+		if valid && (operator != ')' || open_parentheses) {
+			self.active().push(operator);
 		}
+	}
 
-		self.evaluate()
+	pub fn type_unit(&mut self, unit: TypedUnit) {
+		let field = &self.fields[self.active_field];
+		if field.ends_with(|c: char| c.is_ascii_digit() || c == '.') && !self.ends_with_unit() {
+			self.active().push_str(unit.symbol());
+		}
 	}
 
-	pub fn type_negate(&mut self) -> Option<f64> {
-		self.negative = !self.negative;
+	/// Moves to the next of the operation's `field_count` fields, wrapping around to the first.
+	pub fn cycle_field(&mut self, field_count: usize) {
+		self.active_field = (self.active_field + 1) % field_count.clamp(1, self.fields.len());
+	}
 
-		self.evaluate()
+	/// Returns to the first field if the active one is beyond the operation's `field_count` fields.
+	pub fn limit_fields(&mut self, field_count: usize) {
+		if self.active_field >= field_count {
+			self.active_field = 0;
+		}
 	}
 
-	pub fn evaluate(&self) -> Option<f64> {
-		if self.digits.is_empty() {
-			return None;
+	/// Evaluates the expression in the given field, converting each number followed by a unit with `unit_scale`.
+	/// Returns `None` if the field is empty, the expression is incomplete, or it uses a unit which `unit_scale` rejects.
+	pub fn evaluate(&self, field: usize, unit_scale: impl Fn(TypedUnit) -> Option<f64>) -> Option<f64> {
+		let mut parser = ExpressionParser {
+			remaining: self.fields[field].as_str(),
+			unit_scale: &unit_scale,
+		};
+		let result = parser.expression()?;
+		(parser.remaining.is_empty() && result.is_finite()).then_some(result)
+	}
+
+	pub fn clear(&mut self) {
+		self.fields = Default::default();
+		self.active_field = 0;
+	}
+}
+
+/// A recursive descent parser for the arithmetic typed during a transform operation.
+struct ExpressionParser<'a> {
+	remaining: &'a str,
+	unit_scale: &'a dyn Fn(TypedUnit) -> Option<f64>,
+}
+
+impl ExpressionParser<'_> {
+	fn eat(&mut self, character: char) -> bool {
+		if let Some(rest) = self.remaining.strip_prefix(character) {
+			self.remaining = rest;
+			true
+		} else {
+			false
 		}
+	}
 
-		let mut result = 0_f64;
-		let mut running_decimal_place = 0_i32;
+	fn expression(&mut self) -> Option<f64> {
+		let mut result = self.term()?;
+		loop {
+			if self.eat('+') {
+				result += self.term()?;
+			} else if self.eat('-') {
+				result -= self.term()?;
+			} else {
+				return Some(result);
+			}
+		}
+	}
 
-		for digit in &self.digits {
-			if *digit == DECIMAL_POINT {
-				if running_decimal_place == 0 {
-					running_decimal_place = 1;
-				}
-			} else if running_decimal_place == 0 {
-				result *= 10.;
-				result += *digit as f64;
+	fn term(&mut self) -> Option<f64> {
+		let mut result = self.factor()?;
+		loop {
+			if self.eat('*') {
+				result *= self.factor()?;
+			} else if self.eat('/') {
+				result /= self.factor()?;
 			} else {
-				result += *digit as f64 * 0.1_f64.powi(running_decimal_place);
-				running_decimal_place += 1;
+				return Some(result);
 			}
 		}
+	}
 
-		if self.negative {
-			result = -result;
+	fn factor(&mut self) -> Option<f64> {
+		if self.eat('-') {
+			return self.factor().map(|value| -value);
+		}
+		if self.eat('(') {
+			let result = self.expression()?;
+			// Parentheses still open at the end are closed implicitly so the value updates while typing
+			if !self.eat(')') && !self.remaining.is_empty() {
+				return None;
+			}
+			return Some(result);
 		}
 
-		Some(result)
+		let length = self.remaining.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(self.remaining.len());
+		let value = self.remaining[..length].parse::<f64>().ok()?;
+		self.remaining = &self.remaining[length..];
+
+		match TypedUnit::ALL.into_iter().find(|unit| self.remaining.starts_with(unit.symbol())) {
+			Some(unit) => {
+				self.remaining = &self.remaining[unit.symbol().len()..];
+				(self.unit_scale)(unit).map(|scale| value * scale)
+			}
+			None => Some(value),
+		}
 	}
+}
 
-	pub fn clear(&mut self) {
-		self.digits.clear();
-		self.contains_decimal = false;
-		self.negative = false;
+#[cfg(test)]
+mod test {
+	use super::{TypedUnit, Typing};
+
+	fn typed(text: &str) -> Typing {
+		let mut typing = Typing::default();
+		for character in text.chars() {
+			match character {
+				'0'..='9' => typing.type_number(character as u8 - b'0'),
+				'.' => typing.type_decimal_point(),
+				'%' => typing.type_unit(TypedUnit::Percent),
+				'p' => typing.type_unit(TypedUnit::Pixels),
+				'd' => typing.type_unit(TypedUnit::Degrees),
+				'\t' => typing.cycle_field(2),
+				'<' => typing.type_backspace(),
+				_ => typing.type_operator(character),
+			}
+		}
+		typing
+	}
+
+	fn evaluate(text: &str) -> Option<f64> {
+		typed(text).evaluate(0, |unit| match unit {
+			TypedUnit::Percent => Some(0.01),
+			TypedUnit::Degrees => Some(1.),
+			TypedUnit::Pixels => None,
+		})
+	}
+
+	#[test]
+	fn arithmetic_follows_precedence() {
+		assert_eq!(evaluate("45*2"), Some(90.));
+		assert_eq!(evaluate("1+2*3"), Some(7.));
+		assert_eq!(evaluate("(1+2)*3"), Some(9.));
+		assert_eq!(evaluate("10/4-1"), Some(1.5));
+	}
+
+	#[test]
+	fn negative_and_decimal_values() {
+		assert_eq!(evaluate("-2.5"), Some(-2.5));
+		assert_eq!(evaluate("3*-2"), Some(-6.));
+		assert_eq!(evaluate(".5"), Some(0.5));
+		assert_eq!(typed("1.2.3").fields[0], "1.23");
+	}
+
+	#[test]
+	fn incomplete_expressions() {
+		assert_eq!(evaluate(""), None);
+		assert_eq!(evaluate("45*"), None);
+		assert_eq!(evaluate("2*(3+1"), Some(8.));
+		assert_eq!(evaluate("1/0"), None);
+		assert_eq!(typed("2)").fields[0], "2");
+	}
+
+	#[test]
+	fn units_are_converted() {
+		assert_eq!(evaluate("50%"), Some(0.5));
+		assert_eq!(evaluate("45d*2"), Some(90.));
+		assert_eq!(evaluate("10p"), None);
+		assert_eq!(typed("45d").fields[0], "45deg");
+		assert_eq!(typed("45d<").fields[0], "45");
+	}
+
+	#[test]
+	fn tab_cycles_fields() {
+		let typing = typed("10\t20");
+		assert_eq!(typing.fields, ["10".to_string(), "20".to_string()]);
+		assert_eq!(typed("1\t2\t3").fields[0], "13");
 	}
 }
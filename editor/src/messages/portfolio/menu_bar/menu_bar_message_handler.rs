@@ -99,6 +99,13 @@ impl LayoutHolder for MenuBarMessageHandler {
 							..MenuBarEntry::default()
 						},
 					],
+					vec![MenuBarEntry {
+						label: "Run Script…".into(),
+						shortcut: action_keys!(PortfolioMessageDiscriminant::OpenScript),
+						action: MenuBarEntry::create_action(|_| PortfolioMessage::OpenScript.into()),
+						disabled: no_active_document,
+						..MenuBarEntry::default()
+					}],
 					vec![MenuBarEntry {
 						label: "Preferences…".into(),
 						icon: Some("Settings".into()),
@@ -392,6 +399,25 @@ impl LayoutHolder for MenuBarMessageHandler {
 							action: MenuBarEntry::create_action(|_| DocumentMessage::DebugPrintDocument.into()),
 							..MenuBarEntry::default()
 						},
+						MenuBarEntry {
+							label: "Debug: Record Session".into(),
+							action: MenuBarEntry::no_action(),
+							children: MenuBarEntryChildren(vec![vec![
+								MenuBarEntry {
+									label: "Start Recording".into(),
+									shortcut: action_keys!(DebugMessageDiscriminant::StartRecording),
+									action: MenuBarEntry::create_action(|_| DebugMessage::StartRecording.into()),
+									..MenuBarEntry::default()
+								},
+								MenuBarEntry {
+									label: "Stop and Save Recording".into(),
+									shortcut: action_keys!(DebugMessageDiscriminant::StopRecording),
+									action: MenuBarEntry::create_action(|_| DebugMessage::StopRecording.into()),
+									..MenuBarEntry::default()
+								},
+							]]),
+							..MenuBarEntry::default()
+						},
 						MenuBarEntry {
 							label: "Debug: Panic (DANGER)".into(),
 							action: MenuBarEntry::create_action(|_| panic!()),
@@ -1,3 +1,4 @@
+use crate::messages::frontend::utility_types::FileType;
 use crate::messages::portfolio::document::utility_types::clipboards::Clipboard;
 use crate::messages::prelude::*;
 
@@ -87,6 +88,7 @@ pub enum PortfolioMessage {
 		document_is_saved: bool,
 		document_serialized_content: String,
 	},
+	OpenScript,
 	// TODO: Paste message is unused, delete it?
 	Paste {
 		clipboard: Clipboard,
@@ -113,6 +115,11 @@ pub enum PortfolioMessage {
 		blob_url: String,
 		resolution: (f64, f64),
 	},
+	SubmitGraphExport {
+		document_id: u64,
+		file_name: String,
+		file_type: FileType,
+	},
 	SubmitGraphRender {
 		document_id: u64,
 		layer_path: Vec<LayerId>,
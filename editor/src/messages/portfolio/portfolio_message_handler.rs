@@ -14,7 +14,7 @@ use crate::node_graph_executor::NodeGraphExecutor;
 use document_legacy::layers::style::RenderData;
 use document_legacy::Operation as DocumentOperation;
 use graph_craft::document::NodeId;
-use graphene_core::text::Font;
+use graphene_core::text::{Font, FontProvider};
 
 use std::sync::Arc;
 
@@ -300,7 +300,7 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 				}
 			}
 			PortfolioMessage::LoadFont { font, is_default } => {
-				if !self.persistent_data.font_cache.loaded_font(&font) {
+				if !self.persistent_data.font_cache.available(&font) {
 					responses.add_front(FrontendMessage::TriggerFontLoad { font, is_default });
 				}
 			}
@@ -364,6 +364,12 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 					}
 				}
 			}
+			PortfolioMessage::OpenScript => {
+				// This portfolio message wraps the frontend message so it can be listed as an action, which isn't possible for frontend messages
+				if self.active_document().is_some() {
+					responses.add(FrontendMessage::TriggerOpenScript);
+				}
+			}
 			// TODO: Paste message is unused, delete it?
 			PortfolioMessage::Paste { clipboard } => {
 				let shallowest_common_folder = self.active_document().map(|document| {
@@ -510,6 +516,15 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 				};
 				responses.add(PortfolioMessage::DocumentPassMessage { document_id, message });
 			}
+			PortfolioMessage::SubmitGraphExport { document_id, file_name, file_type } => {
+				let Some(document) = self.documents.get(&document_id) else { return };
+				if let Err(description) = self.executor.submit_document_export((document_id, document), file_name, file_type) {
+					responses.add(DialogMessage::DisplayDialogError {
+						title: "Export failed".to_string(),
+						description,
+					});
+				}
+			}
 			PortfolioMessage::SubmitGraphRender { document_id, layer_path } => {
 				let result = self.executor.submit_node_graph_evaluation(
 					(document_id, self.documents.get_mut(&document_id).expect("Tried to render no existent Document")),
@@ -557,6 +572,7 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 			Import,
 			NextDocument,
 			OpenDocument,
+			OpenScript,
 			Paste,
 			PasteIntoFolder,
 			PrevDocument,
@@ -583,6 +599,13 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 }
 
 impl PortfolioMessageHandler {
+	/// Adds a source of fonts which don't need to be requested from the frontend, along with fallback fonts for characters missing from the requested font
+	pub fn add_font_provider(&mut self, provider: Arc<dyn FontProvider>, fallback_fonts: Vec<Font>) {
+		self.persistent_data.font_cache.add_provider(provider);
+		self.persistent_data.font_cache.set_fallback_fonts(fallback_fonts);
+		self.executor.update_font_cache(self.persistent_data.font_cache.clone());
+	}
+
 	pub fn introspect_node(&self, node_path: &[NodeId]) -> Option<Arc<dyn std::any::Any>> {
 		self.executor.introspect_node(node_path)
 	}
@@ -689,5 +712,13 @@ impl PortfolioMessageHandler {
 		self.executor.poll_node_graph_evaluation(&mut active_document.document_legacy, responses).unwrap_or_else(|e| {
 			log::error!("Error while evaluating node graph: {e}");
 		});
+
+		// Capture the next frame of a sequence export once the graph has been evaluated with that frame's setup
+		if let Some(sequence_export) = active_document.sequence_export.as_mut().filter(|sequence_export| sequence_export.awaiting_evaluation) {
+			if !self.executor.is_evaluating() {
+				sequence_export.awaiting_evaluation = false;
+				responses.add(DocumentMessage::CaptureSequenceFrame);
+			}
+		}
 	}
 }
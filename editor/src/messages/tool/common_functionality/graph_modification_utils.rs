@@ -4,8 +4,7 @@ use crate::messages::prelude::*;
 use bezier_rs::{ManipulatorGroup, Subpath};
 use document_legacy::{document::Document, document_metadata::LayerNodeIdentifier, LayerId, Operation};
 use graph_craft::document::{value::TaggedValue, DocumentNode, NodeId, NodeInput, NodeNetwork};
-use graphene_core::raster::ImageFrame;
-use graphene_core::text::Font;
+use graphene_core::text::{Font, TypesettingConfig};
 use graphene_core::uuid::ManipulatorGroupId;
 use graphene_core::vector::style::{FillType, Gradient};
 use graphene_core::Color;
@@ -20,10 +19,11 @@ pub fn new_vector_layer(subpaths: Vec<Subpath<ManipulatorGroupId>>, layer_path:
 	responses.add(NodeGraphMessage::SetSelectedNodes { nodes: vec![id] })
 }
 
-/// Create a new bitmap layer from an [`graphene_core::raster::ImageFrame<Color>`]
-pub fn new_image_layer(image_frame: ImageFrame<Color>, layer_path: Vec<LayerId>, responses: &mut VecDeque<Message>) {
+/// Create a new bitmap layer from an image frame in any of the pixel formats held by [`TaggedValue`]
+pub fn new_image_layer(image_frame: TaggedValue, layer_path: Vec<LayerId>, responses: &mut VecDeque<Message>) {
 	responses.add(GraphOperationMessage::NewBitmapLayer {
 		id: *layer_path.last().unwrap(),
+		parent: None,
 		image_frame,
 	});
 }
@@ -137,7 +137,7 @@ pub fn get_fill_id(layer: LayerNodeIdentifier, document: &Document) -> Option<No
 }
 
 /// Gets properties from the text node
-pub fn get_text(layer: LayerNodeIdentifier, document: &Document) -> Option<(&String, &Font, f64)> {
+pub fn get_text(layer: LayerNodeIdentifier, document: &Document) -> Option<(&String, &Font, TypesettingConfig)> {
 	let inputs = NodeGraphLayer::new(layer, document)?.find_node_inputs("Text")?;
 	let NodeInput::Value {
 		tagged_value: TaggedValue::String(text),
@@ -163,7 +163,52 @@ pub fn get_text(layer: LayerNodeIdentifier, document: &Document) -> Option<(&Str
 		return None;
 	};
 
-	Some((text, font, font_size))
+	let f64_input = |index: usize| match inputs[index] {
+		NodeInput::Value {
+			tagged_value: TaggedValue::F64(value),
+			..
+		} => Some(value),
+		_ => None,
+	};
+
+	let NodeInput::Value {
+		tagged_value: TaggedValue::TextAlignment(align),
+		..
+	} = inputs[9]
+	else {
+		return None;
+	};
+
+	let NodeInput::Value {
+		tagged_value: TaggedValue::FontFeatures(features),
+		..
+	} = inputs[10]
+	else {
+		return None;
+	};
+
+	let max_width = f64_input(8)?;
+	let typesetting = TypesettingConfig {
+		font_size,
+		line_height_ratio: f64_input(4)?,
+		character_spacing: f64_input(5)?,
+		word_spacing: f64_input(6)?,
+		paragraph_spacing: f64_input(7)?,
+		max_width: (max_width > 0.).then_some(max_width),
+		align,
+		features,
+	};
+
+	Some((text, font, typesetting))
+}
+
+/// Gets the image embedded in an image layer whose nodes only place it in the document, so its pixels can be used without rendering the layer
+pub fn get_unmodified_image_frame(layer: LayerNodeIdentifier, document: &Document) -> Option<&TaggedValue> {
+	let layer = NodeGraphLayer::new(layer, document)?;
+	if !layer.primary_layer_flow().all(|(node, _)| matches!(node.name.as_str(), "Layer" | "Transform" | "Sample" | "Image")) {
+		return None;
+	}
+	layer.find_input("Image", 0)
 }
 
 /// Is a specified layer an artboard?
@@ -181,6 +226,16 @@ pub fn is_text_layer(layer: LayerNodeIdentifier, document: &Document) -> bool {
 	NodeGraphLayer::new(layer, document).is_some_and(|layer| layer.uses_node("Text"))
 }
 
+/// Is a specified layer a bitmap, either painted or embedded?
+pub fn is_raster_layer(layer: LayerNodeIdentifier, document: &Document) -> bool {
+	NodeGraphLayer::new(layer, document).is_some_and(|layer| layer.uses_node("Brush") || layer.uses_node("Image"))
+}
+
+/// Is a specified layer the fill of a region enclosed by another layer's paths?
+pub fn is_region_fill_layer(layer: LayerNodeIdentifier, document: &Document) -> bool {
+	NodeGraphLayer::new(layer, document).is_some_and(|layer| layer.uses_node("Enclosed Region"))
+}
+
 /// Convert subpaths to an iterator of manipulator groups
 pub fn get_manipulator_groups(subpaths: &[Subpath<ManipulatorGroupId>]) -> impl Iterator<Item = &bezier_rs::ManipulatorGroup<ManipulatorGroupId>> + DoubleEndedIterator {
 	subpaths.iter().flat_map(|subpath| subpath.manipulator_groups())
@@ -235,6 +290,13 @@ impl<'a> NodeGraphLayer<'a> {
 		self.node_graph.primary_flow_from_opt(Some(self.layer_node))
 	}
 
+	/// Return an iterator up the primary flow of the nodes producing the layer's mask, which is empty if the layer has no mask
+	pub fn mask_flow(&self) -> impl Iterator<Item = (&'a DocumentNode, u64)> {
+		let node_graph = self.node_graph;
+		let mask_node = node_graph.nodes.get(&self.layer_node).and_then(|node| node.inputs.get(8)).and_then(NodeInput::as_node);
+		mask_node.into_iter().flat_map(move |mask_node| node_graph.primary_flow_from_opt(Some(mask_node)))
+	}
+
 	/// Does a node exist in the layer's primary flow
 	pub fn uses_node(&self, node_name: &str) -> bool {
 		self.primary_layer_flow().any(|(node, _id)| node.name == node_name)
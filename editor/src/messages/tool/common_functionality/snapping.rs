@@ -1,16 +1,23 @@
+use super::graph_modification_utils::{get_subpaths, is_artboard};
 use super::shape_editor::ManipulatorPointInfo;
 use crate::application::generate_uuid;
 use crate::consts::{
-	COLOR_ACCENT, SNAP_AXIS_OVERLAY_FADE_DISTANCE, SNAP_AXIS_TOLERANCE, SNAP_AXIS_UNSNAPPED_OPACITY, SNAP_POINT_OVERLAY_FADE_FAR, SNAP_POINT_OVERLAY_FADE_NEAR, SNAP_POINT_SIZE, SNAP_POINT_TOLERANCE,
-	SNAP_POINT_UNSNAPPED_OPACITY,
+	COLOR_ACCENT, SNAP_AXIS_OVERLAY_FADE_DISTANCE, SNAP_AXIS_TOLERANCE, SNAP_AXIS_UNSNAPPED_OPACITY, SNAP_INTERSECTION_ANCHOR_TOLERANCE, SNAP_MEASUREMENT_LABEL_FONT_SIZE,
+	SNAP_MEASUREMENT_LABEL_OFFSET, SNAP_MEASUREMENT_TICK_SIZE, SNAP_POINT_OVERLAY_FADE_FAR, SNAP_POINT_OVERLAY_FADE_NEAR, SNAP_POINT_SIZE, SNAP_POINT_TOLERANCE, SNAP_POINT_UNSNAPPED_OPACITY,
 };
+use crate::messages::portfolio::document::overlays::utility_types::OverlayLabel;
+use crate::messages::portfolio::document::utility_types::guides::GridSettings;
+use crate::messages::portfolio::document::utility_types::misc::SnappingState;
 use crate::messages::prelude::*;
 
+use bezier_rs::{SubpathTValue, TValue};
 use document_legacy::document_metadata::LayerNodeIdentifier;
 use document_legacy::layers::layer_info::Layer;
 use document_legacy::layers::style::{self, Stroke};
 use document_legacy::{LayerId, Operation};
-use graphene_core::vector::{ManipulatorPointId, SelectedType};
+use graphene_core::renderer::Quad;
+use graphene_core::uuid::ManipulatorGroupId;
+use graphene_core::vector::{ManipulatorPointId, SelectedType, Subpath};
 
 use glam::{DAffine2, DVec2};
 use std::f64::consts::PI;
@@ -20,8 +27,12 @@ use std::f64::consts::PI;
 struct SnapOverlays {
 	axis_overlay_paths: Vec<Vec<LayerId>>,
 	point_overlay_paths: Vec<Vec<LayerId>>,
+	measurement_overlay_paths: Vec<Vec<LayerId>>,
 	axis_index: usize,
 	point_index: usize,
+	measurement_index: usize,
+	/// The measurement labels sent to the overlays, which are only sent again when they change
+	labels: Vec<OverlayLabel>,
 }
 
 impl SnapOverlays {
@@ -64,6 +75,45 @@ impl SnapOverlays {
 		}
 	}
 
+	/// Draws a polyline overlay through the given viewport points, reusing a shape from the pool if available.
+	fn add_stroke_overlay(responses: &mut VecDeque<Message>, points: Vec<DVec2>, index: usize, overlay_paths: &mut Vec<Vec<LayerId>>) {
+		let subpath = Subpath::new_poly_line(points);
+		if index >= overlay_paths.len() {
+			let layer_path = vec![generate_uuid()];
+			responses.add(DocumentMessage::Overlays(
+				Operation::AddShape {
+					path: layer_path.clone(),
+					transform: DAffine2::IDENTITY.to_cols_array(),
+					style: style::PathStyle::new(Some(Stroke::new(Some(COLOR_ACCENT), 1.0)), style::Fill::None),
+					insert_index: -1,
+					subpath,
+				}
+				.into(),
+			));
+			overlay_paths.push(layer_path);
+		} else {
+			let path = overlay_paths[index].clone();
+			responses.add(DocumentMessage::Overlays(Operation::SetShapePath { path, subpath }.into()));
+		}
+	}
+
+	/// Draws a measurement line between two viewport points, labelled with its length in document units.
+	fn draw_measurement(&mut self, [start, end]: [DVec2; 2], document_scale: f64, labels: &mut Vec<OverlayLabel>, responses: &mut VecDeque<Message>) {
+		let length = start.distance(end);
+		if length < 1. {
+			return;
+		}
+
+		Self::add_stroke_overlay(responses, measurement_stroke(start, end), self.measurement_index, &mut self.measurement_overlay_paths);
+		self.measurement_index += 1;
+
+		labels.push(OverlayLabel {
+			text: format!("{}", (length / document_scale).round()),
+			position: distance_label_position(start, end),
+			font_size: SNAP_MEASUREMENT_LABEL_FONT_SIZE,
+		});
+	}
+
 	/// Draw the alignment lines for an axis
 	/// Note: horizontal refers to the overlay line being horizontal and the snap being along the Y axis
 	fn draw_alignment_lines(&mut self, is_horizontal: bool, distances: impl Iterator<Item = (DVec2, DVec2, f64)>, responses: &mut VecDeque<Message>, closest_distance: DVec2) {
@@ -124,24 +174,52 @@ impl SnapOverlays {
 
 	/// Updates the snapping overlays with the specified distances.
 	/// `positions_and_distances` is a tuple of `x`, `y` & `point` iterators,, each with `(position, goal, distance)` values.
-	fn update_overlays<X, Y, P>(&mut self, responses: &mut VecDeque<Message>, positions_and_distances: (X, Y, P), closest_distance: DVec2, snapped_to_point: bool)
-	where
+	/// `snapped_point` is the point snapped to, if any, and `measurements` are the lines to label with their lengths.
+	fn update_overlays<X, Y, P>(
+		&mut self,
+		responses: &mut VecDeque<Message>,
+		positions_and_distances: (X, Y, P),
+		closest_distance: DVec2,
+		snapped_point: Option<DVec2>,
+		measurements: &[[DVec2; 2]],
+		document_scale: f64,
+	) where
 		X: Iterator<Item = (DVec2, DVec2, f64)>,
 		Y: Iterator<Item = (DVec2, DVec2, f64)>,
 		P: Iterator<Item = (DVec2, DVec2, f64)>,
 	{
 		self.axis_index = 0;
 		self.point_index = 0;
+		self.measurement_index = 0;
 
 		let (x, y, points) = positions_and_distances;
-		if !snapped_to_point {
+		if let Some(point) = snapped_point {
+			let size = DVec2::splat(SNAP_POINT_SIZE);
+			let transform = DAffine2::from_scale_angle_translation(size, 0., point - size / 2.).to_cols_array();
+			Self::add_overlay(false, responses, transform, Some(1.), self.point_index, &mut self.point_overlay_paths);
+			self.point_index += 1;
+		} else {
 			self.draw_alignment_lines(true, y, responses, closest_distance);
 			self.draw_alignment_lines(false, x, responses, closest_distance);
 			self.draw_snap_points(points, responses, closest_distance);
 		}
+		let mut labels = Vec::new();
+		for &measurement in measurements {
+			self.draw_measurement(measurement, document_scale, &mut labels, responses);
+		}
+		self.set_labels(labels, responses);
 
 		Self::remove_unused_overlays(&mut self.axis_overlay_paths, responses, self.axis_index);
 		Self::remove_unused_overlays(&mut self.point_overlay_paths, responses, self.point_index);
+		Self::remove_unused_overlays(&mut self.measurement_overlay_paths, responses, self.measurement_index);
+	}
+
+	/// Sends the measurement labels to the overlays if they differ from those already shown.
+	fn set_labels(&mut self, labels: Vec<OverlayLabel>, responses: &mut VecDeque<Message>) {
+		if labels != self.labels {
+			self.labels = labels.clone();
+			responses.add(DocumentMessage::Overlays(OverlaysMessage::SetLabels { labels }));
+		}
 	}
 
 	/// Remove overlays from the pool beyond a given index. Pool entries up through that index will be kept.
@@ -155,6 +233,8 @@ impl SnapOverlays {
 	fn cleanup(&mut self, responses: &mut VecDeque<Message>) {
 		Self::remove_unused_overlays(&mut self.axis_overlay_paths, responses, 0);
 		Self::remove_unused_overlays(&mut self.point_overlay_paths, responses, 0);
+		Self::remove_unused_overlays(&mut self.measurement_overlay_paths, responses, 0);
+		self.set_labels(Vec::new(), responses);
 	}
 }
 
@@ -163,56 +243,157 @@ impl SnapOverlays {
 pub struct SnapManager {
 	point_targets: Option<Vec<DVec2>>,
 	bound_targets: Option<Vec<DVec2>>,
+	/// Bounding boxes of the other layers, used to find equal spacing
+	distribution_targets: Option<Vec<[DVec2; 2]>>,
+	/// Viewport space paths, with their bounding boxes, which can be snapped to anywhere along their segments
+	path_targets: Option<Vec<(bezier_rs::Subpath<ManipulatorGroupId>, [DVec2; 2])>>,
+	/// The segments of the path targets which overlap the viewport, with their bounding boxes, for finding intersections with paths added later
+	visible_segments: Option<Vec<(bezier_rs::Bezier, [DVec2; 2])>>,
+	/// Viewport coordinates of the guides which are vertical and horizontal in the viewport, for each axis
+	axis_guide_targets: Option<[Vec<f64>; 2]>,
+	/// Guides which are slanted in the viewport when the canvas is rotated, as a viewport point and direction
+	slanted_guide_targets: Option<Vec<[DVec2; 2]>>,
+	/// The visible grid and the transform from document to viewport space
+	grid_target: Option<(GridSettings, DAffine2)>,
 	snap_overlays: SnapOverlays,
 	snap_x: bool,
 	snap_y: bool,
 }
 
 impl SnapManager {
-	/// Computes the necessary translation to the layer to snap it (as well as updating necessary overlays)
-	fn calculate_snap<R>(&mut self, targets: R, responses: &mut VecDeque<Message>) -> DVec2
+	/// Computes the necessary translation to the layer to snap it (as well as updating necessary overlays).
+	/// `moving_bounds` are the bounds of the dragged layers, which enables equal spacing snapping.
+	fn calculate_snap<R>(&mut self, document_message_handler: &DocumentMessageHandler, targets: R, moving_bounds: Option<[DVec2; 2]>, responses: &mut VecDeque<Message>) -> DVec2
 	where
 		R: Iterator<Item = DVec2> + Clone,
 	{
+		let snapping_state = &document_message_handler.snapping_state;
 		let empty = Vec::new();
 		let snap_points = self.snap_x && self.snap_y;
 
+		let empty_guides = Vec::new();
 		let axis = self.bound_targets.as_ref().unwrap_or(&empty);
 		let points = if snap_points { self.point_targets.as_ref().unwrap_or(&empty) } else { &empty };
+		let [x_guides, y_guides] = self.axis_guide_targets.as_ref().map_or([&empty_guides; 2], |[x, y]| [x, y]);
 
 		let x_axis = if self.snap_x { axis } else { &empty }
 			.iter()
-			.flat_map(|&pos| targets.clone().map(move |goal| (pos, goal, (pos - goal).x)));
+			.flat_map(|&pos| targets.clone().map(move |goal| (pos, goal, (pos - goal).x)))
+			.chain(
+				if self.snap_x { x_guides } else { &empty_guides }
+					.iter()
+					.flat_map(|&x| targets.clone().map(move |goal| (DVec2::new(x, goal.y), goal, x - goal.x))),
+			);
 		let y_axis = if self.snap_y { axis } else { &empty }
 			.iter()
-			.flat_map(|&pos| targets.clone().map(move |goal| (pos, goal, (pos - goal).y)));
+			.flat_map(|&pos| targets.clone().map(move |goal| (pos, goal, (pos - goal).y)))
+			.chain(
+				if self.snap_y { y_guides } else { &empty_guides }
+					.iter()
+					.flat_map(|&y| targets.clone().map(move |goal| (DVec2::new(goal.x, y), goal, y - goal.y))),
+			);
 		let points = points.iter().flat_map(|&pos| targets.clone().map(move |goal| (pos, pos - goal, (pos - goal).length())));
 
 		let min_x = x_axis.clone().min_by(|a, b| a.2.abs().partial_cmp(&b.2.abs()).expect("Could not compare position."));
 		let min_y = y_axis.clone().min_by(|a, b| a.2.abs().partial_cmp(&b.2.abs()).expect("Could not compare position."));
 		let min_points = points.clone().min_by(|a, b| a.2.abs().partial_cmp(&b.2.abs()).expect("Could not compare position."));
 
-		// Snap to a point if possible
-		let (clamped_closest_distance, snapped_to_point) = if let Some(min_points) = min_points.filter(|&(_, _, dist)| dist <= SNAP_POINT_TOLERANCE) {
-			(min_points.1, true)
+		// Snap to a point if possible, otherwise to the closest position along a path, then to the grid and slanted guides
+		let snapped_point = min_points
+			.filter(|&(_, _, dist)| dist <= SNAP_POINT_TOLERANCE)
+			.map(|(pos, offset, _)| (pos, offset))
+			.or_else(|| {
+				let snap_along_paths = snap_points && snapping_state.path_snapping && moving_bounds.is_none();
+				snap_along_paths.then(|| self.closest_point_on_paths(targets.clone())).flatten()
+			})
+			.or_else(|| snap_points.then(|| self.closest_point_on_grid(targets.clone())).flatten())
+			.or_else(|| snap_points.then(|| self.closest_point_on_slanted_guides(targets.clone())).flatten());
+
+		let mut measurements = Vec::new();
+		let clamped_closest_distance = if let Some((_, offset)) = snapped_point {
+			offset
 		} else {
 			// Do not move if over snap tolerance
-			let closest_distance = DVec2::new(min_x.unwrap_or_default().2, min_y.unwrap_or_default().2);
-			(
-				DVec2::new(
-					if closest_distance.x.abs() > SNAP_AXIS_TOLERANCE { 0. } else { closest_distance.x },
-					if closest_distance.y.abs() > SNAP_AXIS_TOLERANCE { 0. } else { closest_distance.y },
-				),
-				false,
-			)
+			let aligned = [min_x, min_y].map(|min| min.map(|(_, _, distance)| distance).filter(|distance| distance.abs() <= SNAP_AXIS_TOLERANCE));
+			let spaced = match (moving_bounds, &self.distribution_targets) {
+				(Some(moving_bounds), Some(others)) if snapping_state.distribution_snapping => [0, 1].map(|axis| equal_spacing_snap(moving_bounds, others, axis)),
+				_ => [None, None],
+			};
+
+			let mut closest_distance = DVec2::ZERO;
+			for (axis, enabled) in [self.snap_x, self.snap_y].into_iter().enumerate() {
+				match (aligned[axis], &spaced[axis]) {
+					// Equal spacing wins over alignment when it is closer
+					(aligned, Some(spacing)) if enabled && aligned.map_or(true, |aligned| spacing.offset.abs() < aligned.abs()) => {
+						closest_distance[axis] = spacing.offset;
+						measurements.extend(spacing.gaps.iter().copied());
+					}
+					(Some(aligned), _) => closest_distance[axis] = aligned,
+					_ => {}
+				}
+			}
+
+			// Measure from the snapped position to the nearest point it is aligned with
+			let aligned_measurement = |candidates: &mut dyn Iterator<Item = (DVec2, DVec2, f64)>, axis: usize| {
+				candidates
+					.filter(|&(_, _, distance)| aligned[axis].is_some() && (distance - closest_distance[axis]).abs() < 1.)
+					.map(|(target, goal, _)| [target, goal + closest_distance])
+					.filter(|[target, goal]| target.distance(*goal) >= 1.)
+					.min_by(|a, b| a[0].distance_squared(a[1]).total_cmp(&b[0].distance_squared(b[1])))
+			};
+			measurements.extend(aligned_measurement(&mut x_axis.clone(), 0));
+			measurements.extend(aligned_measurement(&mut y_axis.clone(), 1));
+
+			closest_distance
 		};
 
-		self.snap_overlays.update_overlays(responses, (x_axis, y_axis, points), clamped_closest_distance, snapped_to_point);
+		let document_scale = document_message_handler.metadata().document_to_viewport.matrix2.x_axis.length();
+		let document_scale = if document_scale > 0. { document_scale } else { 1. };
+		self.snap_overlays.update_overlays(
+			responses,
+			(x_axis, y_axis, points),
+			clamped_closest_distance,
+			snapped_point.map(|(point, _)| point),
+			&measurements,
+			document_scale,
+		);
 
 		clamped_closest_distance
 	}
 
+	/// Finds the closest position on any of the path targets to any of the goals, returning the position and the offset from its goal.
+	fn closest_point_on_paths(&self, goals: impl Iterator<Item = DVec2>) -> Option<(DVec2, DVec2)> {
+		let paths = self.path_targets.as_ref()?;
+		let near_bounds = |goal: DVec2, [min, max]: [DVec2; 2]| goal.clamp(min, max).distance(goal) <= SNAP_POINT_TOLERANCE;
+
+		closest_candidate(goals, |goal| {
+			paths.iter().filter(move |(_, bounds)| near_bounds(goal, *bounds)).filter_map(move |(path, _)| {
+				let (segment_index, t) = path.project(goal, None)?;
+				Some(path.evaluate(SubpathTValue::Parametric { segment_index, t }))
+			})
+		})
+	}
+
+	/// Finds the closest grid intersection to any of the goals, returning the position and the offset from its goal.
+	fn closest_point_on_grid(&self, goals: impl Iterator<Item = DVec2>) -> Option<(DVec2, DVec2)> {
+		let (grid, document_to_viewport) = self.grid_target?;
+		let viewport_to_document = document_to_viewport.inverse();
+
+		closest_candidate(goals, |goal| {
+			let closest = grid.closest_point(viewport_to_document.transform_point2(goal));
+			std::iter::once(document_to_viewport.transform_point2(closest))
+		})
+	}
+
+	/// Finds the closest position on any of the slanted guides to any of the goals, returning the position and the offset from its goal.
+	fn closest_point_on_slanted_guides(&self, goals: impl Iterator<Item = DVec2>) -> Option<(DVec2, DVec2)> {
+		let guides = self.slanted_guide_targets.as_ref()?;
+
+		closest_candidate(goals, |goal| guides.iter().map(move |&[point, direction]| point + direction * (goal - point).dot(direction)))
+	}
+
 	/// Gets a list of snap targets for the X and Y axes (if specified) in Viewport coords for the target layers (usually all layers or all non-selected layers.)
+	/// Artboard edges and corners are included when artboard snapping is enabled.
 	/// This should be called at the start of a drag.
 	pub fn start_snap(
 		&mut self,
@@ -222,21 +403,71 @@ impl SnapManager {
 		snap_x: bool,
 		snap_y: bool,
 	) {
-		let snapping_enabled = document_message_handler.snapping_state.snapping_enabled;
-		let bounding_box_snapping = document_message_handler.snapping_state.bounding_box_snapping;
-		if snapping_enabled && bounding_box_snapping {
-			self.snap_x = snap_x;
-			self.snap_y = snap_y;
-
-			// Could be made into sorted Vec or a HashSet for more performant lookups.
-			self.bound_targets = Some(
-				bounding_boxes
-					.flat_map(expand_bounds)
-					.filter(|&pos| pos.x >= 0. && pos.y >= 0. && pos.x < input.viewport_bounds.size().x && pos.y <= input.viewport_bounds.size().y)
-					.collect(),
-			);
-			self.point_targets = None;
+		let snapping_state = &document_message_handler.snapping_state;
+		if !snapping_state.snapping_enabled {
+			return;
 		}
+
+		self.snap_x = snap_x;
+		self.snap_y = snap_y;
+
+		let viewport_size = input.viewport_bounds.size();
+		let in_viewport = |pos: &DVec2| pos.x >= 0. && pos.y >= 0. && pos.x < viewport_size.x && pos.y <= viewport_size.y;
+
+		let bounding_boxes: Vec<_> = if snapping_state.bounding_box_snapping || snapping_state.distribution_snapping {
+			bounding_boxes.collect()
+		} else {
+			Vec::new()
+		};
+
+		// Could be made into sorted Vec or a HashSet for more performant lookups.
+		let mut bound_targets: Vec<_> = if snapping_state.bounding_box_snapping {
+			bounding_boxes.iter().copied().flat_map(expand_bounds).filter(in_viewport).collect()
+		} else {
+			Vec::new()
+		};
+		self.point_targets = None;
+
+		if snapping_state.artboard_snapping {
+			let metadata = document_message_handler.metadata();
+			let artboards: Vec<_> = metadata
+				.all_layers()
+				.filter(|&layer| is_artboard(layer, &document_message_handler.document_legacy))
+				.filter_map(|layer| metadata.bounding_box_viewport(layer))
+				.collect();
+
+			bound_targets.extend(artboards.iter().copied().flat_map(expand_bounds).filter(in_viewport));
+			let corners = artboards.iter().flat_map(|&[min, max]| [min, DVec2::new(max.x, min.y), max, DVec2::new(min.x, max.y)]);
+			self.point_targets = Some(corners.filter(in_viewport).collect());
+		}
+
+		self.bound_targets = Some(bound_targets);
+		self.distribution_targets = snapping_state.distribution_snapping.then_some(bounding_boxes);
+		self.path_targets = None;
+		self.visible_segments = None;
+
+		let guides = &document_message_handler.guides_handler;
+		let document_to_viewport = document_message_handler.metadata().document_to_viewport;
+		self.axis_guide_targets = None;
+		self.slanted_guide_targets = None;
+		if snapping_state.guide_snapping {
+			let mut axis_guides = [Vec::new(), Vec::new()];
+			let mut slanted_guides = Vec::new();
+			for guide in guides.visible_guides() {
+				let point = document_to_viewport.transform_point2(guide.closest_point(DVec2::ZERO));
+				let direction = document_to_viewport.transform_vector2(guide.direction.line_direction()).normalize_or_zero();
+				if direction.x.abs() < 1e-6 {
+					axis_guides[0].push(point.x);
+				} else if direction.y.abs() < 1e-6 {
+					axis_guides[1].push(point.y);
+				} else {
+					slanted_guides.push([point, direction]);
+				}
+			}
+			self.axis_guide_targets = Some(axis_guides);
+			self.slanted_guide_targets = Some(slanted_guides);
+		}
+		self.grid_target = snapping_state.grid_snapping.then(|| guides.visible_grid().map(|&grid| (grid, document_to_viewport))).flatten();
 	}
 
 	/// Add arbitrary snapping points
@@ -269,11 +500,22 @@ impl SnapManager {
 	) {
 		let Some(vector_data) = &layer.as_vector_data() else { return };
 
-		if !document_message_handler.snapping_state.node_snapping {
+		let snapping_state = &document_message_handler.snapping_state;
+		let transform = document_message_handler.document_legacy.multiply_transforms(path).unwrap();
+
+		if snapping_state.snapping_enabled && (snapping_state.path_snapping || snapping_state.intersection_snapping) {
+			let subpaths = vector_data.subpaths.iter().map(|subpath| {
+				let mut subpath = subpath.clone();
+				subpath.apply_transform(transform);
+				subpath
+			});
+			self.add_path_geometry(snapping_state, input, subpaths);
+		}
+
+		if !snapping_state.node_snapping {
 			return;
 		};
 
-		let transform = document_message_handler.document_legacy.multiply_transforms(path).unwrap();
 		let snap_points = vector_data
 			.manipulator_groups()
 			.flat_map(|group| {
@@ -298,6 +540,79 @@ impl SnapManager {
 		self.add_snap_points(document_message_handler, input, snap_points);
 	}
 
+	/// Adds viewport space paths to snap along, and the points where they intersect themselves and the paths added before them.
+	/// Only the segments overlapping the viewport are intersected, so the cost depends on what is visible rather than on the size of the document.
+	///
+	/// This should be called after start_snap
+	fn add_path_geometry(&mut self, snapping_state: &SnappingState, input: &InputPreprocessorMessageHandler, subpaths: impl Iterator<Item = bezier_rs::Subpath<ManipulatorGroupId>>) {
+		let viewport = [DVec2::ZERO, input.viewport_bounds.size()];
+		let overlapping = |[min_a, max_a]: [DVec2; 2], [min_b, max_b]: [DVec2; 2]| min_a.cmple(max_b).all() && min_b.cmple(max_a).all();
+		let paths = self.path_targets.get_or_insert_with(Vec::new);
+		let segments = self.visible_segments.get_or_insert_with(Vec::new);
+
+		let mut intersections = Vec::new();
+		for subpath in subpaths {
+			let Some(bounds) = subpath.bounding_box() else { continue };
+
+			if snapping_state.intersection_snapping && overlapping(bounds, viewport) {
+				let visible: Vec<_> = subpath
+					.iter()
+					.map(|segment| (segment, segment.bounding_box()))
+					.filter(|&(_, segment_bounds)| overlapping(segment_bounds, viewport))
+					.collect();
+				let evaluate = |segment: &bezier_rs::Bezier, t: f64| segment.evaluate(TValue::Parametric(t));
+
+				for (index, (segment, segment_bounds)) in visible.iter().enumerate() {
+					intersections.extend(segment.self_intersections(None).into_iter().map(|[t, _]| evaluate(segment, t)));
+
+					// Segments of the same subpath meet at their shared anchors, which are not intersections
+					for (other, _) in visible[..index].iter().filter(|(_, other_bounds)| overlapping(*segment_bounds, *other_bounds)) {
+						let crossings = segment
+							.intersections(other, None, None)
+							.into_iter()
+							.filter(|&t| t > SNAP_INTERSECTION_ANCHOR_TOLERANCE && 1. - t > SNAP_INTERSECTION_ANCHOR_TOLERANCE);
+						intersections.extend(crossings.map(|t| evaluate(segment, t)));
+					}
+					for (other, _) in segments.iter().filter(|(_, other_bounds)| overlapping(*segment_bounds, *other_bounds)) {
+						intersections.extend(segment.intersections(other, None, None).into_iter().map(|t| evaluate(segment, t)));
+					}
+				}
+
+				segments.extend(visible);
+			}
+
+			paths.push((subpath, bounds));
+		}
+
+		let in_viewport = |pos: &DVec2| pos.x >= 0. && pos.y >= 0. && pos.x < viewport[1].x && pos.y <= viewport[1].y;
+		self.point_targets.get_or_insert_with(Vec::new).extend(intersections.into_iter().filter(in_viewport));
+	}
+
+	/// Adds the paths of all layers in the document's node graph, other than the excluded ones, to snap along and to their intersections.
+	///
+	/// This should be called after start_snap
+	pub fn add_layer_paths(&mut self, document_message_handler: &DocumentMessageHandler, input: &InputPreprocessorMessageHandler, exclude: &[LayerNodeIdentifier]) {
+		let snapping_state = &document_message_handler.snapping_state;
+		if !snapping_state.snapping_enabled || !(snapping_state.path_snapping || snapping_state.intersection_snapping) {
+			return;
+		}
+
+		let document = &document_message_handler.document_legacy;
+		let metadata = document_message_handler.metadata();
+		let subpaths = metadata
+			.all_layers()
+			.filter(|layer| !exclude.contains(layer))
+			.filter_map(|layer| Some((get_subpaths(layer, document)?, metadata.transform_to_viewport(layer))))
+			.flat_map(|(subpaths, transform)| {
+				subpaths.iter().map(move |subpath| {
+					let mut subpath = subpath.clone();
+					subpath.apply_transform(transform);
+					subpath
+				})
+			});
+		self.add_path_geometry(snapping_state, input, subpaths);
+	}
+
 	/// Adds all of the shape handles in the document, including bézier handles of the points specified
 	pub fn add_all_document_handles(
 		&mut self,
@@ -317,9 +632,11 @@ impl SnapManager {
 
 	/// Finds the closest snap from an array of layers to the specified snap targets in viewport coords.
 	/// Returns 0 for each axis that there is no snap less than the snap tolerance.
+	/// The anchors are also used to find the bounds of the layers for equal spacing snapping.
 	pub fn snap_layers(&mut self, responses: &mut VecDeque<Message>, document_message_handler: &DocumentMessageHandler, snap_anchors: Vec<DVec2>, mouse_delta: DVec2) -> DVec2 {
 		if document_message_handler.snapping_state.snapping_enabled {
-			self.calculate_snap(snap_anchors.iter().map(move |&snap| mouse_delta + snap), responses)
+			let moving_bounds = snap_anchors.iter().map(|&snap| [mouse_delta + snap; 2]).reduce(Quad::combine_bounds);
+			self.calculate_snap(document_message_handler, snap_anchors.iter().map(move |&snap| mouse_delta + snap), moving_bounds, responses)
 		} else {
 			DVec2::ZERO
 		}
@@ -328,7 +645,7 @@ impl SnapManager {
 	/// Handles snapping of a viewport position, returning another viewport position.
 	pub fn snap_position(&mut self, responses: &mut VecDeque<Message>, document_message_handler: &DocumentMessageHandler, position_viewport: DVec2) -> DVec2 {
 		if document_message_handler.snapping_state.snapping_enabled {
-			self.calculate_snap([position_viewport].into_iter(), responses) + position_viewport
+			self.calculate_snap(document_message_handler, [position_viewport].into_iter(), None, responses) + position_viewport
 		} else {
 			position_viewport
 		}
@@ -339,9 +656,23 @@ impl SnapManager {
 		self.snap_overlays.cleanup(responses);
 		self.bound_targets = None;
 		self.point_targets = None;
+		self.distribution_targets = None;
+		self.path_targets = None;
+		self.visible_segments = None;
+		self.axis_guide_targets = None;
+		self.slanted_guide_targets = None;
+		self.grid_target = None;
 	}
 }
 
+/// Finds the closest of the candidate positions for any of the goals within the snapping tolerance, returning the position and the offset from its goal.
+fn closest_candidate<I: Iterator<Item = DVec2>>(goals: impl Iterator<Item = DVec2>, candidates: impl Fn(DVec2) -> I) -> Option<(DVec2, DVec2)> {
+	goals
+		.flat_map(|goal| candidates(goal).map(move |position| (position, position - goal)))
+		.filter(|(_, offset)| offset.length() <= SNAP_POINT_TOLERANCE)
+		.min_by(|a, b| a.1.length_squared().total_cmp(&b.1.length_squared()))
+}
+
 /// Converts a bounding box into a set of points for snapping
 ///
 /// Puts a point in the middle of each edge (top, bottom, left, right)
@@ -353,3 +684,134 @@ pub fn expand_bounds([bound1, bound2]: [DVec2; 2]) -> [DVec2; 4] {
 		DVec2::new(bound2.x, (bound1.y + bound2.y) / 2.),
 	]
 }
+
+/// An offset along one axis which places the moving bounds at an equal spacing from two other bounding boxes.
+#[derive(Debug, Clone, PartialEq)]
+struct SpacingSnap {
+	offset: f64,
+	/// The equal gaps between the boxes once snapped, in viewport space
+	gaps: Vec<[DVec2; 2]>,
+}
+
+/// Finds the smallest offset along the `axis` (0 for x, 1 for y) that spaces the `moving` bounds equally with a pair of adjacent `others`,
+/// either centred between the pair or continuing the pair's gap before or after it.
+/// Only boxes overlapping the moving bounds on the other axis (i.e. in the same row or column) are considered.
+fn equal_spacing_snap(moving: [DVec2; 2], others: &[[DVec2; 2]], axis: usize) -> Option<SpacingSnap> {
+	let cross = 1 - axis;
+	let mut row: Vec<_> = others.iter().filter(|bounds| bounds[0][cross] <= moving[1][cross] && moving[0][cross] <= bounds[1][cross]).collect();
+	row.sort_by(|a, b| a[0][axis].total_cmp(&b[0][axis]));
+
+	let size = moving[1][axis] - moving[0][axis];
+	// The cross axis position at which to draw the gaps, the middle of the moving bounds
+	let cross_position = (moving[0][cross] + moving[1][cross]) / 2.;
+	let gap = |start: f64, end: f64| {
+		let mut points = [DVec2::splat(cross_position); 2];
+		points[0][axis] = start;
+		points[1][axis] = end;
+		points
+	};
+
+	let mut best: Option<SpacingSnap> = None;
+	for pair in row.windows(2) {
+		let (first, second) = (pair[0], pair[1]);
+		let pair_gap = second[0][axis] - first[1][axis];
+		if pair_gap <= 0. {
+			continue;
+		}
+
+		let mut candidates = Vec::new();
+
+		// Centred between the pair
+		let centred_gap = (pair_gap - size) / 2.;
+		if centred_gap > 0. {
+			let start = first[1][axis] + centred_gap;
+			candidates.push((start, vec![gap(first[1][axis], start), gap(start + size, second[0][axis])]));
+		}
+
+		// After the pair
+		let start = second[1][axis] + pair_gap;
+		candidates.push((start, vec![gap(first[1][axis], second[0][axis]), gap(second[1][axis], start)]));
+
+		// Before the pair
+		let start = first[0][axis] - pair_gap - size;
+		candidates.push((start, vec![gap(start + size, first[0][axis]), gap(first[1][axis], second[0][axis])]));
+
+		for (start, gaps) in candidates {
+			let offset = start - moving[0][axis];
+			if offset.abs() <= SNAP_AXIS_TOLERANCE && best.as_ref().map_or(true, |best| offset.abs() < best.offset.abs()) {
+				best = Some(SpacingSnap { offset, gaps });
+			}
+		}
+	}
+
+	best
+}
+
+/// The polyline for a measurement line between two points, with a perpendicular tick at each end.
+fn measurement_stroke(start: DVec2, end: DVec2) -> Vec<DVec2> {
+	let tick = (end - start).normalize_or_zero().perp() * SNAP_MEASUREMENT_TICK_SIZE / 2.;
+	vec![start + tick, start - tick, start, end, end + tick, end - tick]
+}
+
+/// The viewport position of the center of a distance label, beside the middle of the measurement line so it remains legible.
+fn distance_label_position(start: DVec2, end: DVec2) -> DVec2 {
+	let normal = (end - start).normalize_or_zero().perp();
+	let normal = if normal == DVec2::ZERO { DVec2::NEG_Y } else { normal };
+	(start + end) / 2. - normal * (SNAP_MEASUREMENT_LABEL_OFFSET + SNAP_MEASUREMENT_LABEL_FONT_SIZE / 2.)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn bounds(min: (f64, f64), max: (f64, f64)) -> [DVec2; 2] {
+		[DVec2::new(min.0, min.1), DVec2::new(max.0, max.1)]
+	}
+
+	#[test]
+	fn equal_spacing_between_pair() {
+		let others = [bounds((0., 0.), (10., 10.)), bounds((50., 0.), (60., 10.))];
+		let snap = equal_spacing_snap(bounds((24., 0.), (40., 10.)), &others, 0).unwrap();
+		assert_eq!(snap.offset, -2.);
+		assert_eq!(snap.gaps, vec![bounds((10., 5.), (22., 5.)), bounds((38., 5.), (50., 5.))]);
+	}
+
+	#[test]
+	fn equal_spacing_after_pair() {
+		let others = [bounds((0., 0.), (10., 10.)), bounds((0., 30.), (10., 40.))];
+		let snap = equal_spacing_snap(bounds((2., 63.), (8., 73.)), &others, 1).unwrap();
+		assert_eq!(snap.offset, -3.);
+		assert_eq!(snap.gaps[1], [DVec2::new(5., 40.), DVec2::new(5., 60.)]);
+	}
+
+	#[test]
+	fn equal_spacing_ignores_other_rows() {
+		let others = [bounds((0., 100.), (10., 110.)), bounds((50., 100.), (60., 110.))];
+		assert_eq!(equal_spacing_snap(bounds((20., 0.), (40., 10.)), &others, 0), None);
+	}
+
+	#[test]
+	fn measurement_ticks() {
+		let stroke = measurement_stroke(DVec2::ZERO, DVec2::new(10., 0.));
+		let tick = SNAP_MEASUREMENT_TICK_SIZE / 2.;
+		assert_eq!(
+			stroke,
+			vec![
+				DVec2::new(0., tick),
+				DVec2::new(0., -tick),
+				DVec2::ZERO,
+				DVec2::new(10., 0.),
+				DVec2::new(10., tick),
+				DVec2::new(10., -tick)
+			]
+		);
+	}
+
+	#[test]
+	fn distance_label_beside_line() {
+		let offset = SNAP_MEASUREMENT_LABEL_OFFSET + SNAP_MEASUREMENT_LABEL_FONT_SIZE / 2.;
+		assert_eq!(distance_label_position(DVec2::ZERO, DVec2::new(100., 0.)), DVec2::new(50., -offset));
+		assert_eq!(distance_label_position(DVec2::ZERO, DVec2::new(0., 100.)), DVec2::new(offset, 50.));
+		assert_eq!(distance_label_position(DVec2::ONE, DVec2::ONE), DVec2::new(1., 1. + offset));
+	}
+}
@@ -1,7 +1,7 @@
 use super::tool_prelude::*;
 use crate::messages::portfolio::document::node_graph::transform_utils::get_current_transform;
 use crate::messages::tool::common_functionality::color_selector::{ToolColorOptions, ToolColorType};
-use crate::messages::tool::common_functionality::graph_modification_utils;
+use crate::messages::tool::common_functionality::graph_modification_utils::{self, NodeGraphLayer};
 
 use document_legacy::layers::layer_layer::CachedOutputData;
 use document_legacy::LayerId;
@@ -66,6 +66,7 @@ pub struct BrushOptions {
 	color: ToolColorOptions,
 	blend_mode: BlendMode,
 	draw_mode: DrawMode,
+	paint_mask: bool,
 }
 
 impl Default for BrushOptions {
@@ -78,6 +79,7 @@ impl Default for BrushOptions {
 			color: ToolColorOptions::default(),
 			blend_mode: BlendMode::Normal,
 			draw_mode: DrawMode::Draw,
+			paint_mask: false,
 		}
 	}
 }
@@ -110,6 +112,7 @@ pub enum BrushToolMessageOptionsUpdate {
 	DrawMode(DrawMode),
 	Flow(f64),
 	Hardness(f64),
+	PaintMask(bool),
 	Spacing(f64),
 	WorkingColors(Option<Color>, Option<Color>),
 }
@@ -180,6 +183,16 @@ impl LayoutHolder for BrushTool {
 			.collect();
 		widgets.push(RadioInput::new(draw_mode_entries).selected_index(Some(self.options.draw_mode as u32)).widget_holder());
 
+		widgets.push(Separator::new(SeparatorType::Unrelated).widget_holder());
+		widgets.push(TextLabel::new("Paint Mask").widget_holder());
+		widgets.push(Separator::new(SeparatorType::Related).widget_holder());
+		widgets.push(
+			CheckboxInput::new(self.options.paint_mask)
+				.tooltip("Paint the raster mask of the selected layer instead of the layer, where black hides the layer and white reveals it")
+				.on_update(|checkbox_input: &CheckboxInput| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::PaintMask(checkbox_input.checked)).into())
+				.widget_holder(),
+		);
+
 		widgets.push(Separator::new(SeparatorType::Section).widget_holder());
 
 		widgets.append(&mut self.options.color.create_widgets(
@@ -240,6 +253,7 @@ impl<'a> MessageHandler<ToolMessage, &mut ToolActionHandlerData<'a>> for BrushTo
 			BrushToolMessageOptionsUpdate::Diameter(diameter) => self.options.diameter = diameter,
 			BrushToolMessageOptionsUpdate::DrawMode(draw_mode) => self.options.draw_mode = draw_mode,
 			BrushToolMessageOptionsUpdate::Hardness(hardness) => self.options.hardness = hardness,
+			BrushToolMessageOptionsUpdate::PaintMask(paint_mask) => self.options.paint_mask = paint_mask,
 			BrushToolMessageOptionsUpdate::Flow(flow) => self.options.flow = flow,
 			BrushToolMessageOptionsUpdate::Spacing(spacing) => self.options.spacing = spacing,
 			BrushToolMessageOptionsUpdate::Color(color) => {
@@ -291,6 +305,7 @@ struct BrushToolData {
 	strokes: Vec<BrushStroke>,
 	layer_path: Vec<LayerId>,
 	transform: DAffine2,
+	painting_mask: bool,
 }
 
 impl BrushToolData {
@@ -325,10 +340,37 @@ impl BrushToolData {
 		matches!(layer.cached_output_data, CachedOutputData::BlobURL(_) | CachedOutputData::SurfaceId(_)).then_some(&self.layer_path)
 	}
 
+	/// Loads the strokes painted onto the raster mask of the selected layer, which is painted in the space of the layer's parent.
+	/// Returns whether the layer already has a raster mask, or `None` if there isn't a single selected layer or its mask isn't painted.
+	fn load_existing_mask_strokes(&mut self, document: &DocumentMessageHandler) -> Option<bool> {
+		self.transform = DAffine2::IDENTITY;
+		if document.selected_layers().count() != 1 {
+			return None;
+		}
+		self.layer_path = document.selected_layers().next()?.to_vec();
+		let layer = NodeGraphLayer::new_from_path(&self.layer_path, &document.document_legacy)?;
+
+		let mut mask_flow = layer.mask_flow().peekable();
+		if mask_flow.peek().is_none() {
+			return Some(false);
+		}
+		let (brush_node, _) = mask_flow.find(|(node, _)| node.name == "Brush")?;
+		let Some(TaggedValue::BrushStrokes(strokes)) = brush_node.inputs.get(2).and_then(NodeInput::as_value) else {
+			return None;
+		};
+		self.strokes = strokes.clone();
+
+		Some(true)
+	}
+
 	fn update_strokes(&self, responses: &mut VecDeque<Message>) {
 		let layer = self.layer_path.clone();
 		let strokes = self.strokes.clone();
-		responses.add(GraphOperationMessage::Brush { layer, strokes });
+		if self.painting_mask {
+			responses.add(GraphOperationMessage::MaskBrush { layer, strokes });
+		} else {
+			responses.add(GraphOperationMessage::Brush { layer, strokes });
+		}
 	}
 }
 
@@ -350,12 +392,25 @@ impl Fsm for BrushToolFsmState {
 		match (self, event) {
 			(BrushToolFsmState::Ready, BrushToolMessage::DragStart) => {
 				responses.add(DocumentMessage::StartTransaction);
-				let layer_path = tool_data.load_existing_strokes(document);
-				let new_layer = layer_path.is_none();
-				if new_layer {
-					responses.add(DocumentMessage::DeselectAllLayers);
-					tool_data.layer_path = document.get_path_for_new_layer();
-				}
+				tool_data.painting_mask = tool_options.paint_mask;
+				let new_layer = if tool_data.painting_mask {
+					match tool_data.load_existing_mask_strokes(document) {
+						Some(true) => {}
+						Some(false) => responses.add(GraphOperationMessage::AddRasterMask { layer: tool_data.layer_path.clone() }),
+						None => {
+							responses.add(DocumentMessage::AbortTransaction);
+							return BrushToolFsmState::Ready;
+						}
+					}
+					false
+				} else {
+					let new_layer = tool_data.load_existing_strokes(document).is_none();
+					if new_layer {
+						responses.add(DocumentMessage::DeselectAllLayers);
+						tool_data.layer_path = document.get_path_for_new_layer();
+					}
+					new_layer
+				};
 				let layer_position = tool_data.transform.inverse().transform_point2(document_position);
 				// TODO: Also scale it based on the input image ('Background' parameter).
 				// TODO: Resizing the input image results in a different brush size from the chosen diameter.
@@ -379,6 +434,10 @@ impl Fsm for BrushToolFsmState {
 						spacing: tool_options.spacing,
 						blend_mode,
 					},
+					selection: document
+						.pixel_selection_handler
+						.active_selection()
+						.map(|selection| selection.transformed(tool_data.transform.inverse())),
 				});
 
 				if new_layer {
@@ -1,18 +1,53 @@
 use super::tool_prelude::*;
+use crate::application::generate_uuid;
+use crate::messages::tool::common_functionality::graph_modification_utils;
+use document_legacy::document_metadata::LayerNodeIdentifier;
 use document_legacy::layers::style::Fill;
+use graphene_core::raster::flood_fill::FloodFill;
+use graphene_core::vector::arrangement::PlanarArrangement;
 
 #[derive(Default)]
 pub struct FillTool {
 	fsm_state: FillToolFsmState,
+	options: FillOptions,
+}
+
+/// Options of the paint bucket used on raster layers, as vector layers are always filled entirely.
+pub struct FillOptions {
+	tolerance: f64,
+	contiguous: bool,
+	anti_alias: bool,
+	sample_merged: bool,
+}
+
+impl Default for FillOptions {
+	fn default() -> Self {
+		Self {
+			tolerance: 10.,
+			contiguous: true,
+			anti_alias: true,
+			sample_merged: false,
+		}
+	}
 }
 
 #[remain::sorted]
 #[impl_message(Message, ToolMessage, Fill)]
-#[derive(PartialEq, Eq, Clone, Debug, Hash, Serialize, Deserialize, specta::Type)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize, specta::Type)]
 pub enum FillToolMessage {
 	// Tool-specific messages
 	LeftPointerDown,
 	RightPointerDown,
+	UpdateOptions(FillOptionsUpdate),
+}
+
+#[remain::sorted]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize, specta::Type)]
+pub enum FillOptionsUpdate {
+	AntiAlias(bool),
+	Contiguous(bool),
+	SampleMerged(bool),
+	Tolerance(f64),
 }
 
 impl ToolMetadata for FillTool {
@@ -29,18 +64,63 @@ impl ToolMetadata for FillTool {
 
 impl LayoutHolder for FillTool {
 	fn layout(&self) -> Layout {
-		Layout::WidgetLayout(WidgetLayout::default())
+		let widgets = vec![
+			NumberInput::new(Some(self.options.tolerance))
+				.label("Tolerance")
+				.tooltip("How different a pixel's color may be from the clicked pixel's color to be filled (raster layers only)")
+				.min(0.)
+				.max(100.)
+				.mode_range()
+				.unit("%")
+				.on_update(|number_input: &NumberInput| FillToolMessage::UpdateOptions(FillOptionsUpdate::Tolerance(number_input.value.unwrap())).into())
+				.widget_holder(),
+			Separator::new(SeparatorType::Section).widget_holder(),
+			TextLabel::new("Contiguous").widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			CheckboxInput::new(self.options.contiguous)
+				.tooltip("Only fill the pixels connected to the clicked pixel, rather than every similar pixel in the layer")
+				.on_update(|checkbox_input: &CheckboxInput| FillToolMessage::UpdateOptions(FillOptionsUpdate::Contiguous(checkbox_input.checked)).into())
+				.widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			TextLabel::new("Anti-aliasing").widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			CheckboxInput::new(self.options.anti_alias)
+				.tooltip("Smooth the edge of the filled region")
+				.on_update(|checkbox_input: &CheckboxInput| FillToolMessage::UpdateOptions(FillOptionsUpdate::AntiAlias(checkbox_input.checked)).into())
+				.widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			TextLabel::new("Sample Merged").widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			CheckboxInput::new(self.options.sample_merged)
+				.tooltip("Find the region to fill from the layer combined with the painted layer below it, rather than from the layer alone")
+				.on_update(|checkbox_input: &CheckboxInput| FillToolMessage::UpdateOptions(FillOptionsUpdate::SampleMerged(checkbox_input.checked)).into())
+				.widget_holder(),
+		];
+
+		Layout::WidgetLayout(WidgetLayout::new(vec![LayoutGroup::Row { widgets }]))
 	}
 }
 
 impl<'a> MessageHandler<ToolMessage, &mut ToolActionHandlerData<'a>> for FillTool {
 	fn process_message(&mut self, message: ToolMessage, responses: &mut VecDeque<Message>, tool_data: &mut ToolActionHandlerData<'a>) {
-		self.fsm_state.process_event(message, &mut (), tool_data, &(), responses, true);
+		let ToolMessage::Fill(FillToolMessage::UpdateOptions(action)) = message else {
+			self.fsm_state.process_event(message, &mut (), tool_data, &self.options, responses, true);
+			return;
+		};
+		match action {
+			FillOptionsUpdate::AntiAlias(anti_alias) => self.options.anti_alias = anti_alias,
+			FillOptionsUpdate::Contiguous(contiguous) => self.options.contiguous = contiguous,
+			FillOptionsUpdate::SampleMerged(sample_merged) => self.options.sample_merged = sample_merged,
+			FillOptionsUpdate::Tolerance(tolerance) => self.options.tolerance = tolerance,
+		}
+
+		self.send_layout(responses, LayoutTarget::ToolOptions);
 	}
 
 	advertise_actions!(FillToolMessageDiscriminant;
 		LeftPointerDown,
 		RightPointerDown,
+		UpdateOptions,
 	);
 }
 
@@ -58,9 +138,9 @@ enum FillToolFsmState {
 
 impl Fsm for FillToolFsmState {
 	type ToolData = ();
-	type ToolOptions = ();
+	type ToolOptions = FillOptions;
 
-	fn transition(self, event: ToolMessage, _tool_data: &mut Self::ToolData, handler_data: &mut ToolActionHandlerData, _tool_options: &Self::ToolOptions, responses: &mut VecDeque<Message>) -> Self {
+	fn transition(self, event: ToolMessage, _tool_data: &mut Self::ToolData, handler_data: &mut ToolActionHandlerData, tool_options: &Self::ToolOptions, responses: &mut VecDeque<Message>) -> Self {
 		let ToolActionHandlerData {
 			document, global_tool_data, input, ..
 		} = handler_data;
@@ -68,22 +148,53 @@ impl Fsm for FillToolFsmState {
 		let ToolMessage::Fill(event) = event else {
 			return self;
 		};
-		let Some(layer_identifier) = document.metadata().click(input.mouse.position, &document.document_legacy.document_network) else {
-			return self;
-		};
-		let layer = layer_identifier.to_path();
-
 		let color = match event {
 			FillToolMessage::LeftPointerDown => global_tool_data.primary_color,
 			FillToolMessage::RightPointerDown => global_tool_data.secondary_color,
+			FillToolMessage::UpdateOptions(_) => return self,
 		};
-		let fill = Fill::Solid(color);
+
+		let clicked = document.metadata().click(input.mouse.position, &document.document_legacy.document_network);
+		// Clicking a layer fills the whole layer, so a region enclosed by crossing paths is only filled when the click misses every layer
+		let region_target = clicked.is_none().then(|| enclosed_region_target(document, input.mouse.position)).flatten();
+		if let Some((source_layer, point)) = region_target {
+			let id = generate_uuid();
+			responses.add(DocumentMessage::StartTransaction);
+			responses.add(GraphOperationMessage::NewRegionFillLayer {
+				id,
+				source_layer: source_layer.to_path(),
+				point,
+				fill: Fill::Solid(color),
+			});
+			responses.add(NodeGraphMessage::SetSelectedNodes { nodes: vec![id] });
+			responses.add(DocumentMessage::CommitTransaction);
+			return FillToolFsmState::Ready;
+		}
+
+		let Some(layer_identifier) = clicked else {
+			return self;
+		};
+		let layer = layer_identifier.to_path();
 
 		responses.add(DocumentMessage::StartTransaction);
 		responses.add(DocumentMessage::SetSelectedLayers {
 			replacement_selected_layers: vec![layer.clone()],
 		});
-		responses.add(GraphOperationMessage::FillSet { layer, fill });
+		if graph_modification_utils::is_raster_layer(layer_identifier, &document.document_legacy) {
+			// The Flood Fill node is the last node before the layer, so like the Brush tool, it works in document space
+			let position = document.metadata().document_to_viewport.inverse().transform_point2(input.mouse.position);
+			let fill = FloodFill {
+				position,
+				color,
+				tolerance: tool_options.tolerance,
+				contiguous: tool_options.contiguous,
+				anti_alias: tool_options.anti_alias,
+				sample_merged: tool_options.sample_merged,
+			};
+			responses.add(GraphOperationMessage::FloodFill { layer, fill });
+		} else {
+			responses.add(GraphOperationMessage::FillSet { layer, fill: Fill::Solid(color) });
+		}
 		responses.add(DocumentMessage::CommitTransaction);
 
 		FillToolFsmState::Ready
@@ -104,3 +215,27 @@ impl Fsm for FillToolFsmState {
 		responses.add(FrontendMessage::UpdateMouseCursor { cursor: MouseCursorIcon::Default });
 	}
 }
+
+/// Finds the shape layer whose crossing paths enclose the clicked location, along with that location in the space of the layer's paths.
+/// Only layers whose bounds contain the location are arranged into regions. Layers made of a single closed path and existing region fills aren't region targets, since they enclose nothing beyond their own inside.
+fn enclosed_region_target(document: &DocumentMessageHandler, viewport_position: DVec2) -> Option<(LayerNodeIdentifier, DVec2)> {
+	let metadata = document.metadata();
+	metadata.all_layers().find_map(|layer| {
+		let document_legacy = &document.document_legacy;
+		if !graph_modification_utils::is_shape_layer(layer, document_legacy) || graph_modification_utils::is_region_fill_layer(layer, document_legacy) {
+			return None;
+		}
+		let [min, max] = metadata.bounding_box_viewport(layer)?;
+		if viewport_position.cmplt(min).any() || viewport_position.cmpgt(max).any() {
+			return None;
+		}
+
+		let subpaths = graph_modification_utils::get_subpaths(layer, document_legacy)?;
+		let arrangement = PlanarArrangement::new(subpaths);
+		if arrangement.faces().count() < 2 && subpaths.iter().all(|subpath| subpath.closed()) {
+			return None;
+		}
+		let point = metadata.transform_to_viewport(layer).inverse().transform_point2(viewport_position);
+		arrangement.region_at(point).map(|_| (layer, point))
+	})
+}
@@ -4,7 +4,7 @@ use crate::messages::tool::common_functionality::color_selector::{ToolColorOptio
 use crate::messages::tool::common_functionality::graph_modification_utils;
 
 use document_legacy::LayerId;
-use graphene_core::vector::style::{Fill, Stroke};
+use graphene_core::vector::style::{Fill, Stroke, StrokeProfile};
 use graphene_core::Color;
 
 use bezier_rs::ManipulatorGroup;
@@ -20,6 +20,8 @@ pub struct FreehandTool {
 
 pub struct FreehandOptions {
 	line_weight: f64,
+	/// Whether the pressure of a pen varies the width of the stroke, up to the line weight at full pressure
+	pressure: bool,
 	fill: ToolColorOptions,
 	stroke: ToolColorOptions,
 }
@@ -28,6 +30,7 @@ impl Default for FreehandOptions {
 	fn default() -> Self {
 		Self {
 			line_weight: 5.,
+			pressure: true,
 			fill: ToolColorOptions::new_none(),
 			stroke: ToolColorOptions::new_primary(),
 		}
@@ -57,6 +60,7 @@ pub enum FreehandOptionsUpdate {
 	FillColor(Option<Color>),
 	FillColorType(ToolColorType),
 	LineWeight(f64),
+	Pressure(bool),
 	StrokeColor(Option<Color>),
 	StrokeColorType(ToolColorType),
 	WorkingColors(Option<Color>, Option<Color>),
@@ -112,6 +116,15 @@ impl LayoutHolder for FreehandTool {
 		));
 		widgets.push(Separator::new(SeparatorType::Unrelated).widget_holder());
 		widgets.push(create_weight_widget(self.options.line_weight));
+		widgets.push(Separator::new(SeparatorType::Unrelated).widget_holder());
+		widgets.push(TextLabel::new("Pressure").widget_holder());
+		widgets.push(Separator::new(SeparatorType::Related).widget_holder());
+		widgets.push(
+			CheckboxInput::new(self.options.pressure)
+				.tooltip("Vary the width of the stroke with the pressure of a pen, reaching the weight at full pressure")
+				.on_update(|checkbox_input: &CheckboxInput| FreehandToolMessage::UpdateOptions(FreehandOptionsUpdate::Pressure(checkbox_input.checked)).into())
+				.widget_holder(),
+		);
 
 		Layout::WidgetLayout(WidgetLayout::new(vec![LayoutGroup::Row { widgets }]))
 	}
@@ -130,6 +143,7 @@ impl<'a> MessageHandler<ToolMessage, &mut ToolActionHandlerData<'a>> for Freehan
 			}
 			FreehandOptionsUpdate::FillColorType(color_type) => self.options.fill.color_type = color_type,
 			FreehandOptionsUpdate::LineWeight(line_weight) => self.options.line_weight = line_weight,
+			FreehandOptionsUpdate::Pressure(pressure) => self.options.pressure = pressure,
 			FreehandOptionsUpdate::StrokeColor(color) => {
 				self.options.stroke.custom_color = color;
 				self.options.stroke.color_type = ToolColorType::Custom;
@@ -179,9 +193,43 @@ struct FreehandToolData {
 	last_point: DVec2,
 	dragged: bool,
 	weight: f64,
+	stroke_color: Option<Color>,
+	/// The position and pen pressure of each point drawn, which are only recorded while drawing with pressure
+	pressure_samples: Vec<(DVec2, f64)>,
 	layer_path: Option<Vec<LayerId>>,
 }
 
+impl FreehandToolData {
+	fn record_pressure(&mut self, position: DVec2, pressure: Option<f64>, use_pressure: bool) {
+		if let Some(pressure) = pressure.filter(|_| use_pressure) {
+			self.pressure_samples.push((position, pressure));
+		}
+	}
+
+	/// The stroke, with a width profile following the pen pressure along the length of the drawn polyline if it was recorded
+	fn stroke(&self) -> Stroke {
+		let stroke = Stroke::new(self.stroke_color, self.weight);
+		if self.pressure_samples.len() < 2 {
+			return stroke;
+		}
+
+		let mut length = 0.;
+		let mut widths = Vec::with_capacity(self.pressure_samples.len());
+		for (index, &(position, pressure)) in self.pressure_samples.iter().enumerate() {
+			if index > 0 {
+				length += position.distance(self.pressure_samples[index - 1].0);
+			}
+			widths.push(DVec2::new(length, pressure));
+		}
+		if length <= 0. {
+			return stroke;
+		}
+
+		widths.iter_mut().for_each(|width| width.x /= length);
+		stroke.with_profile(StrokeProfile::new(widths, 0., 0.))
+	}
+}
+
 impl Fsm for FreehandToolFsmState {
 	type ToolData = FreehandToolData;
 	type ToolOptions = FreehandOptions;
@@ -208,8 +256,11 @@ impl Fsm for FreehandToolFsmState {
 				tool_data.last_point = pos;
 
 				tool_data.weight = tool_options.line_weight;
+				tool_data.stroke_color = tool_options.stroke.active_color();
+				tool_data.pressure_samples.clear();
+				tool_data.record_pressure(pos, input.mouse.pressure, tool_options.pressure);
 
-				add_polyline([pos], tool_data, tool_options.stroke.active_color(), tool_options.fill.active_color(), responses);
+				add_polyline([pos], tool_data, tool_options.fill.active_color(), responses);
 
 				FreehandToolFsmState::Drawing
 			}
@@ -220,9 +271,14 @@ impl Fsm for FreehandToolFsmState {
 					if let Some(layer) = tool_data.layer_path.clone() {
 						let manipulator_group = ManipulatorGroup::new_anchor(pos);
 						let modification = VectorDataModification::AddEndManipulatorGroup { subpath_index: 0, manipulator_group };
-						responses.add(GraphOperationMessage::Vector { layer, modification });
+						responses.add(GraphOperationMessage::Vector { layer: layer.clone(), modification });
 						tool_data.dragged = true;
 						tool_data.last_point = pos;
+
+						tool_data.record_pressure(pos, input.mouse.pressure, tool_options.pressure);
+						if !tool_data.pressure_samples.is_empty() {
+							responses.add(GraphOperationMessage::StrokeSet { layer, stroke: tool_data.stroke() });
+						}
 					}
 				}
 
@@ -236,6 +292,7 @@ impl Fsm for FreehandToolFsmState {
 				}
 
 				tool_data.layer_path = None;
+				tool_data.pressure_samples.clear();
 
 				FreehandToolFsmState::Ready
 			}
@@ -264,7 +321,7 @@ impl Fsm for FreehandToolFsmState {
 	}
 }
 
-fn add_polyline(anchors: impl IntoIterator<Item = DVec2>, data: &FreehandToolData, stroke_color: Option<Color>, fill_color: Option<Color>, responses: &mut VecDeque<Message>) {
+fn add_polyline(anchors: impl IntoIterator<Item = DVec2>, data: &FreehandToolData, fill_color: Option<Color>, responses: &mut VecDeque<Message>) {
 	let subpath = bezier_rs::Subpath::from_anchors(anchors, false);
 
 	let layer_path = data.layer_path.clone().unwrap();
@@ -277,6 +334,6 @@ fn add_polyline(anchors: impl IntoIterator<Item = DVec2>, data: &FreehandToolDat
 
 	responses.add(GraphOperationMessage::StrokeSet {
 		layer: layer_path,
-		stroke: Stroke::new(stroke_color, data.weight),
+		stroke: data.stroke(),
 	});
 }
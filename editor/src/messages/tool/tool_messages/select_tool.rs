@@ -4,6 +4,7 @@ use crate::consts::{ROTATE_SNAP_ANGLE, SELECTION_TOLERANCE};
 use crate::messages::input_mapper::utility_types::input_mouse::ViewportPosition;
 use crate::messages::portfolio::document::utility_types::misc::{AlignAggregate, AlignAxis, FlipAxis};
 use crate::messages::portfolio::document::utility_types::transformation::Selected;
+use crate::messages::tool::common_functionality::graph_modification_utils::is_artboard;
 use crate::messages::tool::common_functionality::graph_modification_utils::is_shape_layer;
 use crate::messages::tool::common_functionality::graph_modification_utils::is_text_layer;
 use crate::messages::tool::common_functionality::path_outline::*;
@@ -560,6 +561,10 @@ impl Fsm for SelectToolFsmState {
 				};
 				tool_data.not_duplicated_layers = None;
 
+				if state == SelectToolFsmState::Dragging {
+					start_drag_snap(tool_data, document, input);
+				}
+
 				state
 			}
 			(SelectToolFsmState::Dragging, SelectToolMessage::PointerMove { axis_align, duplicate, .. }) => {
@@ -899,6 +904,25 @@ impl Fsm for SelectToolFsmState {
 	}
 }
 
+/// Begins snapping the dragged layers to the layers which aren't moving with them (excluding artboards, which are handled by artboard snapping).
+fn start_drag_snap(tool_data: &mut SelectToolData, document: &DocumentMessageHandler, input: &InputPreprocessorMessageHandler) {
+	let metadata = document.metadata();
+	let moves_with_drag = |layer: LayerNodeIdentifier| {
+		tool_data
+			.layers_dragging
+			.iter()
+			.any(|&dragging| layer.starts_with(dragging, metadata) || dragging.starts_with(layer, metadata))
+	};
+	let excluded: Vec<_> = metadata.all_layers().filter(|&layer| moves_with_drag(layer)).collect();
+
+	let bounding_boxes = metadata
+		.all_layers()
+		.filter(|layer| !excluded.contains(layer) && !is_artboard(*layer, &document.document_legacy))
+		.filter_map(|layer| metadata.bounding_box_viewport(layer));
+	tool_data.snap_manager.start_snap(document, input, bounding_boxes, true, true);
+	tool_data.snap_manager.add_layer_paths(document, input, &excluded);
+}
+
 fn drag_shallowest_manipulation(responses: &mut VecDeque<Message>, selected: Vec<LayerNodeIdentifier>, tool_data: &mut SelectToolData, document: &DocumentMessageHandler) {
 	let layer = selected[0];
 	let ancestor = layer.ancestors(document.metadata()).find(|&ancestor| document.metadata().selected_layers_contains(ancestor));
@@ -11,7 +11,7 @@ use document_legacy::layers::style::{self, Fill, RenderData, Stroke};
 use document_legacy::LayerId;
 use document_legacy::Operation;
 use graph_craft::document::value::TaggedValue;
-use graphene_core::text::{load_face, Font};
+use graphene_core::text::{Font, TextAlignment, TypesettingConfig};
 use graphene_core::Color;
 
 #[derive(Default)]
@@ -23,6 +23,9 @@ pub struct TextTool {
 
 pub struct TextOptions {
 	font_size: u32,
+	line_height_ratio: f64,
+	character_spacing: f64,
+	align: TextAlignment,
 	font_name: String,
 	font_style: String,
 	fill: ToolColorOptions,
@@ -32,6 +35,9 @@ impl Default for TextOptions {
 	fn default() -> Self {
 		Self {
 			font_size: 24,
+			line_height_ratio: 1.,
+			character_spacing: 0.,
+			align: TextAlignment::default(),
 			font_name: "Merriweather".into(),
 			font_style: "Normal (400)".into(),
 			fill: ToolColorOptions::new_primary(),
@@ -67,10 +73,13 @@ pub enum TextToolMessage {
 #[remain::sorted]
 #[derive(PartialEq, Clone, Debug, Serialize, Deserialize, specta::Type)]
 pub enum TextOptionsUpdate {
+	Align(TextAlignment),
+	CharacterSpacing(f64),
 	FillColor(Option<Color>),
 	FillColorType(ToolColorType),
 	Font { family: String, style: String },
 	FontSize(u32),
+	LineHeightRatio(f64),
 	WorkingColors(Option<Color>, Option<Color>),
 }
 
@@ -115,12 +124,36 @@ fn create_text_widgets(tool: &TextTool) -> Vec<WidgetHolder> {
 		.max((1u64 << std::f64::MANTISSA_DIGITS) as f64)
 		.on_update(|number_input: &NumberInput| TextToolMessage::UpdateOptions(TextOptionsUpdate::FontSize(number_input.value.unwrap() as u32)).into())
 		.widget_holder();
+	let line_height_ratio = NumberInput::new(Some(tool.options.line_height_ratio))
+		.label("Line Height")
+		.unit("x")
+		.min(0.)
+		.step(0.1)
+		.on_update(|number_input: &NumberInput| TextToolMessage::UpdateOptions(TextOptionsUpdate::LineHeightRatio(number_input.value.unwrap())).into())
+		.widget_holder();
+	let character_spacing = NumberInput::new(Some(tool.options.character_spacing))
+		.label("Tracking")
+		.unit(" px")
+		.step(0.5)
+		.on_update(|number_input: &NumberInput| TextToolMessage::UpdateOptions(TextOptionsUpdate::CharacterSpacing(number_input.value.unwrap())).into())
+		.widget_holder();
+	let align_entries = TextAlignment::list()
+		.into_iter()
+		.map(|align| RadioEntryData::new(align.to_string()).on_update(move |_| TextToolMessage::UpdateOptions(TextOptionsUpdate::Align(align)).into()))
+		.collect();
+	let align = RadioInput::new(align_entries).selected_index(Some(tool.options.align as u32)).widget_holder();
 	vec![
 		font,
 		Separator::new(SeparatorType::Related).widget_holder(),
 		style,
 		Separator::new(SeparatorType::Related).widget_holder(),
 		size,
+		Separator::new(SeparatorType::Related).widget_holder(),
+		line_height_ratio,
+		Separator::new(SeparatorType::Related).widget_holder(),
+		character_spacing,
+		Separator::new(SeparatorType::Unrelated).widget_holder(),
+		align,
 	]
 }
 
@@ -156,6 +189,9 @@ impl<'a> MessageHandler<ToolMessage, &mut ToolActionHandlerData<'a>> for TextToo
 				self.send_layout(responses, LayoutTarget::ToolOptions);
 			}
 			TextOptionsUpdate::FontSize(font_size) => self.options.font_size = font_size,
+			TextOptionsUpdate::LineHeightRatio(line_height_ratio) => self.options.line_height_ratio = line_height_ratio,
+			TextOptionsUpdate::CharacterSpacing(character_spacing) => self.options.character_spacing = character_spacing,
+			TextOptionsUpdate::Align(align) => self.options.align = align,
 			TextOptionsUpdate::FillColor(color) => {
 				self.options.fill.custom_color = color;
 				self.options.fill.color_type = ToolColorType::Custom;
@@ -208,7 +244,7 @@ enum TextToolFsmState {
 pub struct EditingText {
 	text: String,
 	font: Font,
-	font_size: f64,
+	typesetting: TypesettingConfig,
 	color: Option<Color>,
 	transform: DAffine2,
 }
@@ -231,8 +267,11 @@ impl TextToolData {
 		if let Some(editing_text) = self.editing_text.as_ref().filter(|_| editable) {
 			responses.add(FrontendMessage::DisplayEditableTextbox {
 				text: editing_text.text.clone(),
-				line_width: None,
-				font_size: editing_text.font_size,
+				line_width: editing_text.typesetting.max_width,
+				font_size: editing_text.typesetting.font_size,
+				line_height_ratio: editing_text.typesetting.line_height_ratio,
+				character_spacing: editing_text.typesetting.character_spacing,
+				align: editing_text.typesetting.align,
 				color: editing_text.color.unwrap_or(Color::BLACK),
 				url: render_data.font_cache.get_preview_url(&editing_text.font).cloned().unwrap_or_default(),
 				transform: editing_text.transform.to_cols_array(),
@@ -245,11 +284,11 @@ impl TextToolData {
 	fn load_layer_text_node(&mut self, document: &DocumentMessageHandler) -> Option<()> {
 		let transform = document.metadata().transform_to_viewport(self.layer);
 		let color = graph_modification_utils::get_fill_color(self.layer, &document.document_legacy).unwrap_or(Color::BLACK);
-		let (text, font, font_size) = graph_modification_utils::get_text(self.layer, &document.document_legacy)?;
+		let (text, font, typesetting) = graph_modification_utils::get_text(self.layer, &document.document_legacy)?;
 		self.editing_text = Some(EditingText {
 			text: text.clone(),
 			font: font.clone(),
-			font_size,
+			typesetting,
 			color: Some(color),
 			transform,
 		});
@@ -289,7 +328,7 @@ impl TextToolData {
 				id: self.layer.to_node(),
 				text: String::new(),
 				font: editing_text.font.clone(),
-				size: editing_text.font_size,
+				typesetting: editing_text.typesetting,
 			});
 			responses.add(GraphOperationMessage::FillSet {
 				layer: self.layer.to_path(),
@@ -321,8 +360,9 @@ impl TextToolData {
 		resize_overlays(&mut self.overlays, responses, 1);
 
 		let editing_text = self.editing_text.as_ref()?;
-		let buzz_face = render_data.font_cache.get(&editing_text.font).map(|data| load_face(data));
-		let far = graphene_core::text::bounding_box(&self.new_text, buzz_face, editing_text.font_size, None);
+		let fonts = render_data.font_cache.get_with_fallbacks(&editing_text.font);
+		let faces = fonts.iter().map(|font| font.face()).collect::<Vec<_>>();
+		let far = graphene_core::text::bounding_box(&self.new_text, &faces, &editing_text.typesetting);
 		let quad = Quad::from_box([DVec2::ZERO, far]);
 
 		let transformed_quad = document.metadata().transform_to_viewport(self.layer) * quad;
@@ -338,8 +378,9 @@ impl TextToolData {
 
 	fn get_bounds(&self, text: &str, render_data: &RenderData) -> Option<[DVec2; 2]> {
 		let editing_text = self.editing_text.as_ref()?;
-		let buzz_face = render_data.font_cache.get(&editing_text.font).map(|data| load_face(data));
-		let subpaths = graphene_core::text::to_path(text, buzz_face, editing_text.font_size, None);
+		let fonts = render_data.font_cache.get_with_fallbacks(&editing_text.font);
+		let faces = fonts.iter().map(|font| font.face()).collect::<Vec<_>>();
+		let subpaths = graphene_core::text::to_path(text, &faces, &editing_text.typesetting);
 		let bounds = subpaths.iter().filter_map(|subpath| subpath.bounding_box());
 		let combined_bounds = bounds.reduce(|a, b| [a[0].min(b[0]), a[1].max(b[1])]).unwrap_or_default();
 		Some(combined_bounds)
@@ -380,9 +421,10 @@ fn resize_overlays(overlays: &mut Vec<Vec<LayerId>>, responses: &mut VecDeque<Me
 
 fn update_overlays(document: &DocumentMessageHandler, tool_data: &mut TextToolData, responses: &mut VecDeque<Message>, render_data: &RenderData) {
 	let get_bounds = |layer: LayerNodeIdentifier, document: &DocumentMessageHandler, render_data: &RenderData| {
-		let (text, font, font_size) = graph_modification_utils::get_text(layer, &document.document_legacy)?;
-		let buzz_face = render_data.font_cache.get(font).map(|data| load_face(data));
-		let far = graphene_core::text::bounding_box(text, buzz_face, font_size, None);
+		let (text, font, typesetting) = graph_modification_utils::get_text(layer, &document.document_legacy)?;
+		let fonts = render_data.font_cache.get_with_fallbacks(font);
+		let faces = fonts.iter().map(|font| font.face()).collect::<Vec<_>>();
+		let far = graphene_core::text::bounding_box(text, &faces, &typesetting);
 		let quad = Quad::from_box([DVec2::ZERO, far]);
 		let multiplied = document.metadata().transform_to_viewport(layer) * quad;
 		Some(multiplied.bounding_box())
@@ -450,7 +492,13 @@ impl Fsm for TextToolFsmState {
 				tool_data.editing_text = Some(EditingText {
 					text: String::new(),
 					transform: DAffine2::from_translation(input.mouse.position),
-					font_size: tool_options.font_size as f64,
+					typesetting: TypesettingConfig {
+						font_size: tool_options.font_size as f64,
+						line_height_ratio: tool_options.line_height_ratio,
+						character_spacing: tool_options.character_spacing,
+						align: tool_options.align,
+						..Default::default()
+					},
 					font: Font::new(tool_options.font_name.clone(), tool_options.font_style.clone()),
 					color: tool_options.fill.active_color(),
 				});
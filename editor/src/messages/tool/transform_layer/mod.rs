@@ -1,7 +1,10 @@
 //! Handles Blender inspired layer transformation with the <kbd>G</kbd> <kbd>R</kbd> and <kbd>S</kbd> keys for grabbing, rotating and scaling.
 //!
 //! Other features include
-//! - Typing a number for a precise transformation
+//! - Typing a number or arithmetic expression, optionally with a `px`, `%` or `deg` unit, for a precise transformation
+//! - <kbd>Tab</kbd> to switch between typing the X and Y values
+//! - <kbd>X</kbd> or <kbd>Y</kbd> to constrain to an axis, pressed again for the layer's local axis
+//! - <kbd>G</kbd> <kbd>R</kbd> or <kbd>S</kbd> while transforming to switch to that operation about the same pivot
 //! - <kbd>Shift</kbd> to slow transformation
 //! - <kbd>Ctrl</kbd> to snap angles to 15°
 //! - Escape or right click to cancel
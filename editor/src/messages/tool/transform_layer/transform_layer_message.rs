@@ -1,4 +1,5 @@
 use crate::messages::input_mapper::utility_types::input_keyboard::Key;
+use crate::messages::portfolio::document::utility_types::transformation::TypedUnit;
 use crate::messages::prelude::*;
 
 use serde::{Deserialize, Serialize};
@@ -15,10 +16,12 @@ pub enum TransformLayerMessage {
 	CancelTransformOperation,
 	ConstrainX,
 	ConstrainY,
+	CycleTypedField,
 	PointerMove { slow_key: Key, snap_key: Key },
 	SelectionChanged,
 	TypeBackspace,
 	TypeDecimalPoint,
 	TypeDigit { digit: u8 },
-	TypeNegate,
+	TypeOperator { operator: char },
+	TypeUnit { unit: TypedUnit },
 }
@@ -22,18 +22,14 @@ pub struct TransformLayerMessageHandler {
 
 	original_transforms: OriginalTransforms,
 	pivot: DVec2,
+	local_angle: f64,
 }
 impl TransformLayerMessageHandler {
 	pub fn is_transforming(&self) -> bool {
 		self.transform_operation != TransformOperation::None
 	}
 	pub fn hints(&self, responses: &mut VecDeque<Message>) {
-		let axis_constraint = match self.transform_operation {
-			TransformOperation::Grabbing(grabbing) => grabbing.constraint,
-			TransformOperation::Scaling(scaling) => scaling.constraint,
-			_ => Axis::Both,
-		};
-		self.transform_operation.hints(self.snap, axis_constraint, responses);
+		self.transform_operation.hints(self.snap, self.local_angle, &self.typing, responses);
 	}
 }
 
@@ -58,7 +54,7 @@ impl<'a> MessageHandler<TransformLayerMessage, TransformData<'a>> for TransformL
 			&tool_data.active_tool_type,
 		);
 
-		let mut begin_operation = |operation: TransformOperation, typing: &mut Typing, mouse_position: &mut DVec2, start_mouse: &mut DVec2| {
+		let mut begin_operation = |operation: TransformOperation, typing: &mut Typing, local_angle: &mut f64, mouse_position: &mut DVec2, start_mouse: &mut DVec2| {
 			if operation != TransformOperation::None {
 				selected.revert_operation();
 				typing.clear();
@@ -90,6 +86,7 @@ impl<'a> MessageHandler<TransformLayerMessage, TransformData<'a>> for TransformL
 				*selected.pivot = selected.mean_average_of_pivots();
 			}
 
+			*local_angle = selected.local_axis_angle();
 			*mouse_position = ipp.mouse.position;
 			*start_mouse = ipp.mouse.position;
 			selected.original_transforms.clear();
@@ -120,7 +117,7 @@ impl<'a> MessageHandler<TransformLayerMessage, TransformData<'a>> for TransformL
 					return;
 				}
 
-				begin_operation(self.transform_operation, &mut self.typing, &mut self.mouse_position, &mut self.start_mouse);
+				begin_operation(self.transform_operation, &mut self.typing, &mut self.local_angle, &mut self.mouse_position, &mut self.start_mouse);
 
 				self.transform_operation = TransformOperation::Grabbing(Default::default());
 
@@ -137,7 +134,7 @@ impl<'a> MessageHandler<TransformLayerMessage, TransformData<'a>> for TransformL
 					return;
 				}
 
-				begin_operation(self.transform_operation, &mut self.typing, &mut self.mouse_position, &mut self.start_mouse);
+				begin_operation(self.transform_operation, &mut self.typing, &mut self.local_angle, &mut self.mouse_position, &mut self.start_mouse);
 
 				self.transform_operation = TransformOperation::Rotating(Default::default());
 
@@ -154,7 +151,7 @@ impl<'a> MessageHandler<TransformLayerMessage, TransformData<'a>> for TransformL
 					return;
 				}
 
-				begin_operation(self.transform_operation, &mut self.typing, &mut self.mouse_position, &mut self.start_mouse);
+				begin_operation(self.transform_operation, &mut self.typing, &mut self.local_angle, &mut self.mouse_position, &mut self.start_mouse);
 
 				self.transform_operation = TransformOperation::Scaling(Default::default());
 
@@ -172,32 +169,36 @@ impl<'a> MessageHandler<TransformLayerMessage, TransformData<'a>> for TransformL
 				responses.add(ToolMessage::UpdateHints);
 				responses.add(BroadcastEvent::DocumentIsDirty);
 			}
-			ConstrainX => self.transform_operation.constrain_axis(Axis::X, &mut selected, self.snap),
-			ConstrainY => self.transform_operation.constrain_axis(Axis::Y, &mut selected, self.snap),
+			ConstrainX => {
+				self.transform_operation.constrain_axis(Axis::X, &mut selected, self.snap, self.local_angle, &self.typing);
+				self.typing.limit_fields(self.transform_operation.field_count());
+			}
+			ConstrainY => {
+				self.transform_operation.constrain_axis(Axis::Y, &mut selected, self.snap, self.local_angle, &self.typing);
+				self.typing.limit_fields(self.transform_operation.field_count());
+			}
+			CycleTypedField => {
+				self.typing.cycle_field(self.transform_operation.field_count());
+				self.transform_operation.hints(self.snap, self.local_angle, &self.typing, selected.responses);
+			}
 			PointerMove { slow_key, snap_key } => {
 				self.slow = ipp.keyboard.get(slow_key as usize);
 
 				let new_snap = ipp.keyboard.get(snap_key as usize);
 				if new_snap != self.snap {
 					self.snap = new_snap;
-					let axis_constraint = match self.transform_operation {
-						TransformOperation::Grabbing(grabbing) => grabbing.constraint,
-						TransformOperation::Scaling(scaling) => scaling.constraint,
-						_ => Axis::Both,
-					};
-					self.transform_operation.apply_transform_operation(&mut selected, self.snap, axis_constraint);
+					self.transform_operation.apply_transform_operation(&mut selected, self.snap, self.local_angle, &self.typing);
 				}
 
-				if self.typing.digits.is_empty() {
+				if self.typing.is_empty() {
 					let delta_pos = ipp.mouse.position - self.mouse_position;
 
 					match self.transform_operation {
 						TransformOperation::None => unreachable!(),
 						TransformOperation::Grabbing(translation) => {
 							let change = if self.slow { delta_pos / SLOWING_DIVISOR } else { delta_pos };
-							let axis_constraint = translation.constraint;
 							self.transform_operation = TransformOperation::Grabbing(translation.increment_amount(change));
-							self.transform_operation.apply_transform_operation(&mut selected, self.snap, axis_constraint);
+							self.transform_operation.apply_transform_operation(&mut selected, self.snap, self.local_angle, &self.typing);
 						}
 						TransformOperation::Rotating(rotation) => {
 							let start_offset = *selected.pivot - self.mouse_position;
@@ -207,7 +208,7 @@ impl<'a> MessageHandler<TransformLayerMessage, TransformData<'a>> for TransformL
 							let change = if self.slow { angle / SLOWING_DIVISOR } else { angle };
 
 							self.transform_operation = TransformOperation::Rotating(rotation.increment_amount(change));
-							self.transform_operation.apply_transform_operation(&mut selected, self.snap, Axis::Both);
+							self.transform_operation.apply_transform_operation(&mut selected, self.snap, self.local_angle, &self.typing);
 						}
 						TransformOperation::Scaling(scale) => {
 							let change = {
@@ -219,9 +220,8 @@ impl<'a> MessageHandler<TransformLayerMessage, TransformData<'a>> for TransformL
 							};
 
 							let change = if self.slow { change / SLOWING_DIVISOR } else { change };
-							let axis_constraint = scale.constraint;
 							self.transform_operation = TransformOperation::Scaling(scale.increment_amount(change));
-							self.transform_operation.apply_transform_operation(&mut selected, self.snap, axis_constraint);
+							self.transform_operation.apply_transform_operation(&mut selected, self.snap, self.local_angle, &self.typing);
 						}
 					};
 				}
@@ -231,10 +231,26 @@ impl<'a> MessageHandler<TransformLayerMessage, TransformData<'a>> for TransformL
 				let target_layers = document.metadata().selected_layers().collect();
 				shape_editor.set_selected_layers(target_layers);
 			}
-			TypeBackspace => self.transform_operation.grs_typed(self.typing.type_backspace(), &mut selected, self.snap),
-			TypeDecimalPoint => self.transform_operation.grs_typed(self.typing.type_decimal_point(), &mut selected, self.snap),
-			TypeDigit { digit } => self.transform_operation.grs_typed(self.typing.type_number(digit), &mut selected, self.snap),
-			TypeNegate => self.transform_operation.grs_typed(self.typing.type_negate(), &mut selected, self.snap),
+			TypeBackspace => {
+				self.typing.type_backspace();
+				self.transform_operation.grs_typed(&self.typing, &mut selected, self.snap, self.local_angle);
+			}
+			TypeDecimalPoint => {
+				self.typing.type_decimal_point();
+				self.transform_operation.grs_typed(&self.typing, &mut selected, self.snap, self.local_angle);
+			}
+			TypeDigit { digit } => {
+				self.typing.type_number(digit);
+				self.transform_operation.grs_typed(&self.typing, &mut selected, self.snap, self.local_angle);
+			}
+			TypeOperator { operator } => {
+				self.typing.type_operator(operator);
+				self.transform_operation.grs_typed(&self.typing, &mut selected, self.snap, self.local_angle);
+			}
+			TypeUnit { unit } => {
+				self.typing.type_unit(unit);
+				self.transform_operation.grs_typed(&self.typing, &mut selected, self.snap, self.local_angle);
+			}
 		}
 	}
 
@@ -253,9 +269,11 @@ impl<'a> MessageHandler<TransformLayerMessage, TransformData<'a>> for TransformL
 				TypeDigit,
 				TypeBackspace,
 				TypeDecimalPoint,
-				TypeNegate,
+				TypeOperator,
+				TypeUnit,
 				ConstrainX,
 				ConstrainY,
+				CycleTypedField,
 			);
 			common.extend(active);
 		}
@@ -1,4 +1,4 @@
-use crate::messages::frontend::utility_types::FrontendImageData;
+use crate::messages::frontend::utility_types::{FileType, FrontendImageData};
 use crate::messages::portfolio::document::node_graph::wrap_network_in_scope;
 use crate::messages::portfolio::document::utility_types::misc::{LayerMetadata, LayerPanelEntry};
 use crate::messages::prelude::*;
@@ -13,9 +13,11 @@ use graph_craft::document::{generate_uuid, DocumentNodeImplementation, NodeId, N
 use graph_craft::graphene_compiler::Compiler;
 use graph_craft::imaginate_input::ImaginatePreferences;
 use graph_craft::{concrete, Type};
-use graphene_core::application_io::{ApplicationIo, NodeGraphUpdateMessage, NodeGraphUpdateSender, RenderConfig};
+use graphene_core::animation::Timeline;
+use graphene_core::application_io::{ApplicationIo, ExportFormat, NodeGraphUpdateMessage, NodeGraphUpdateSender, RenderConfig};
+use graphene_core::raster::color_management::{DisplayTransform, IccProfile, WorkingSpace};
 use graphene_core::raster::Image;
-use graphene_core::renderer::{ClickTarget, GraphicElementRendered, SvgSegment, SvgSegmentList};
+use graphene_core::renderer::{ClickTarget, GraphicElementRendered, Quad, SvgSegment, SvgSegmentList};
 use graphene_core::text::FontCache;
 use graphene_core::transform::{Footprint, Transform};
 use graphene_core::vector::style::ViewMode;
@@ -53,6 +55,8 @@ pub struct NodeRuntime {
 	sender: InternalNodeGraphUpdateSender,
 	wasm_io: Option<WasmApplicationIo>,
 	imaginate_preferences: ImaginatePreferences,
+	/// Converts the colors of the document's working space for display, or `None` when they are already sRGB and nothing is proofed.
+	display_transform: Option<DisplayTransform>,
 	pub(crate) thumbnails: HashMap<NodeId, SvgSegmentList>,
 	pub(crate) click_targets: HashMap<NodeId, Vec<ClickTarget>>,
 	pub(crate) transforms: HashMap<NodeId, DAffine2>,
@@ -72,6 +76,12 @@ pub(crate) struct GenerationRequest {
 	path: Vec<LayerId>,
 	transform: DAffine2,
 	viewport_resolution: UVec2,
+	timeline: Timeline,
+	working_space: WorkingSpace,
+	/// Only given when the document is viewed in soft proofing mode.
+	proof_profile: Option<Arc<IccProfile>>,
+	/// Given when the output is written to a file rather than displayed.
+	export_format: Option<ExportFormat>,
 }
 
 pub(crate) struct GenerationResponse {
@@ -119,6 +129,7 @@ impl NodeRuntime {
 			sender: InternalNodeGraphUpdateSender(sender),
 			font_cache: FontCache::default(),
 			imaginate_preferences: Default::default(),
+			display_transform: None,
 			thumbnails: Default::default(),
 			wasm_io: None,
 			canvas_cache: HashMap::new(),
@@ -133,7 +144,7 @@ impl NodeRuntime {
 		// This should be avoided in the future.
 		requests.reverse();
 		requests.dedup_by_key(|x| match x {
-			NodeRuntimeMessage::GenerationRequest(x) => Some(x.path.clone()),
+			NodeRuntimeMessage::GenerationRequest(x) => Some((x.path.clone(), x.export_format)),
 			_ => None,
 		});
 		requests.reverse();
@@ -147,12 +158,20 @@ impl NodeRuntime {
 					transform,
 					path,
 					viewport_resolution,
+					timeline,
+					working_space,
+					proof_profile,
+					export_format,
 					..
 				}) => {
-					let (result, monitor_nodes) = self.execute_network(&path, graph, transform, viewport_resolution).await;
+					self.update_display_transform(working_space, proof_profile);
+					let (result, monitor_nodes) = self.execute_network(&path, graph, transform, viewport_resolution, timeline, working_space, export_format).await;
 					let mut responses = VecDeque::new();
-					self.update_thumbnails(&path, &monitor_nodes, &mut responses);
-					self.update_upstream_transforms(&monitor_nodes);
+					// Exports are evaluated with another footprint, so only the layers as they are displayed update the thumbnails and transforms
+					if export_format.is_none() {
+						self.update_thumbnails(&path, &monitor_nodes, &mut responses);
+						self.update_upstream_transforms(&monitor_nodes);
+					}
 					let response = GenerationResponse {
 						generation_id,
 						result,
@@ -169,7 +188,29 @@ impl NodeRuntime {
 		}
 	}
 
-	async fn execute_network<'a>(&'a mut self, path: &[LayerId], graph: NodeNetwork, transform: DAffine2, viewport_resolution: UVec2) -> (Result<TaggedValue, String>, MonitorNodes) {
+	/// Rebuilds the display transform only when its settings change, since building one which proofs a profile samples it many times.
+	fn update_display_transform(&mut self, working_space: WorkingSpace, proof_profile: Option<Arc<IccProfile>>) {
+		let current = self.display_transform.as_ref();
+		let unchanged = match current {
+			Some(transform) => transform.working_space() == working_space && transform.proof_profile().map(Arc::as_ptr) == proof_profile.as_ref().map(Arc::as_ptr),
+			None => working_space == WorkingSpace::Srgb && proof_profile.is_none(),
+		};
+		if !unchanged {
+			let transform = DisplayTransform::new(working_space, proof_profile);
+			self.display_transform = (!transform.is_identity()).then_some(transform);
+		}
+	}
+
+	async fn execute_network<'a>(
+		&'a mut self,
+		path: &[LayerId],
+		mut graph: NodeNetwork,
+		transform: DAffine2,
+		viewport_resolution: UVec2,
+		timeline: Timeline,
+		working_space: WorkingSpace,
+		export_format: Option<ExportFormat>,
+	) -> (Result<TaggedValue, String>, MonitorNodes) {
 		if self.wasm_io.is_none() {
 			self.wasm_io = Some(WasmApplicationIo::new().await);
 		}
@@ -185,11 +226,14 @@ impl NodeRuntime {
 					..Default::default()
 				},
 				#[cfg(any(feature = "resvg", feature = "vello"))]
-				export_format: graphene_core::application_io::ExportFormat::Canvas,
+				export_format: export_format.unwrap_or(ExportFormat::Canvas),
 				#[cfg(not(any(feature = "resvg", feature = "vello")))]
-				export_format: graphene_core::application_io::ExportFormat::Svg,
+				export_format: export_format.unwrap_or(ExportFormat::Svg),
+				timeline,
 			},
 			image_frame: None,
+			working_space,
+			display_transform: self.display_transform.as_ref(),
 		};
 
 		use std::collections::hash_map::DefaultHasher;
@@ -198,7 +242,13 @@ impl NodeRuntime {
 		// Required to ensure that the appropriate protonodes are reinserted when the Editor API changes.
 		let mut graph_input_hash = DefaultHasher::new();
 		editor_api.font_cache.hash(&mut graph_input_hash);
+		if let Some(display_transform) = editor_api.display_transform {
+			display_transform.working_space().hash(&mut graph_input_hash);
+			display_transform.proof_profile().map(|profile| profile.data()).hash(&mut graph_input_hash);
+		}
 
+		// Keyframed values are replaced by their value at the current time, so the compiled network only contains constants
+		graph.sample_animated_inputs(timeline.time);
 		let scoped_network = wrap_network_in_scope(graph, graph_input_hash.finish());
 
 		let monitor_nodes = scoped_network
@@ -265,7 +315,8 @@ impl NodeRuntime {
 			};
 			use graphene_core::renderer::*;
 			let bounds = graphic_element_data.bounding_box(DAffine2::IDENTITY);
-			let render_params = RenderParams::new(ViewMode::Normal, ImageRenderMode::BlobUrl, bounds, true);
+			let mut render_params = RenderParams::new(ViewMode::Normal, ImageRenderMode::BlobUrl, bounds, true);
+			render_params.display_transform = self.display_transform.clone();
 			let mut render = SvgRender::new();
 			graphic_element_data.render_svg(&mut render, &render_params);
 			let [min, max] = bounds.unwrap_or_default();
@@ -369,6 +420,8 @@ pub struct NodeGraphExecutor {
 struct ExecutionContext {
 	layer_path: Vec<LayerId>,
 	document_id: u64,
+	/// The file which the output is written to, when it is exported rather than displayed
+	export: Option<(String, FileType)>,
 }
 
 impl Default for NodeGraphExecutor {
@@ -391,7 +444,15 @@ impl Default for NodeGraphExecutor {
 
 impl NodeGraphExecutor {
 	/// Execute the network by flattening it and creating a borrow stack.
-	fn queue_execution(&self, network: NodeNetwork, layer_path: Vec<LayerId>, transform: DAffine2, viewport_resolution: UVec2) -> u64 {
+	fn queue_execution(
+		&self,
+		network: NodeNetwork,
+		layer_path: Vec<LayerId>,
+		transform: DAffine2,
+		viewport_resolution: UVec2,
+		document: &DocumentMessageHandler,
+		export_format: Option<ExportFormat>,
+	) -> u64 {
 		let generation_id = generate_uuid();
 		let request = GenerationRequest {
 			path: layer_path,
@@ -399,6 +460,10 @@ impl NodeGraphExecutor {
 			generation_id,
 			transform,
 			viewport_resolution,
+			timeline: document.timeline,
+			working_space: document.working_space,
+			proof_profile: (document.view_mode == ViewMode::SoftProof && export_format.is_none()).then(|| document.proof_profile.clone()).flatten(),
+			export_format,
 		};
 		self.sender.send(NodeRuntimeMessage::GenerationRequest(request)).expect("Failed to send generation request");
 
@@ -495,13 +560,56 @@ impl NodeGraphExecutor {
 		let document_transform = document.document_legacy.metadata.document_to_viewport;
 
 		// Execute the node graph
-		let generation_id = self.queue_execution(network, layer_path.clone(), document_transform, viewport_resolution);
+		let generation_id = self.queue_execution(network, layer_path.clone(), document_transform, viewport_resolution, document, None);
+
+		self.futures.insert(
+			generation_id,
+			ExecutionContext {
+				layer_path,
+				document_id,
+				export: None,
+			},
+		);
+
+		Ok(())
+	}
+
+	/// Evaluates the document graph to write its output to a file, which is downloaded once the evaluation is done.
+	///
+	/// The graph is given a footprint of the whole artwork at one pixel per unit, so nothing is culled and its rasterized content is sampled at the document's resolution.
+	pub fn submit_document_export(&mut self, (document_id, document): (u64, &DocumentMessageHandler), file_name: String, file_type: FileType) -> Result<(), String> {
+		let export_format = match file_type {
+			FileType::Pdf => ExportFormat::Pdf,
+			_ => return Err(format!("{file_type:?} files aren't exported from the document graph")),
+		};
 
-		self.futures.insert(generation_id, ExecutionContext { layer_path, document_id });
+		let metadata = document.metadata();
+		let bounds = metadata
+			.all_layers()
+			.filter_map(|layer| metadata.bounding_box_document(layer))
+			.reduce(Quad::combine_bounds)
+			.ok_or_else(|| "The document doesn't have any artwork to export.".to_string())?;
+		let transform = DAffine2::from_translation(-bounds[0]);
+		let resolution = (bounds[1] - bounds[0]).ceil().as_uvec2().max(UVec2::ONE);
+
+		let generation_id = self.queue_execution(document.network().clone(), Vec::new(), transform, resolution, document, Some(export_format));
+		self.futures.insert(
+			generation_id,
+			ExecutionContext {
+				layer_path: Vec::new(),
+				document_id,
+				export: Some((file_name, file_type)),
+			},
+		);
 
 		Ok(())
 	}
 
+	/// Whether any submitted node graph evaluation hasn't yet returned its result
+	pub fn is_evaluating(&self) -> bool {
+		!self.futures.is_empty()
+	}
+
 	pub fn poll_node_graph_evaluation(&mut self, document: &mut DocumentLegacy, responses: &mut VecDeque<Message>) -> Result<(), String> {
 		let results = self.receiver.try_iter().collect::<Vec<_>>();
 		for response in results {
@@ -516,11 +624,21 @@ impl NodeGraphExecutor {
 					new_upstream_transforms,
 					transform,
 				}) => {
+					let execution_context = self.futures.remove(&generation_id).ok_or_else(|| "Invalid generation ID".to_string())?;
+					if let Some((file_name, file_type)) = execution_context.export {
+						let data = match result {
+							Ok(TaggedValue::RenderOutput(graphene_std::wasm_application_io::RenderOutput::Pdf(data))) => data,
+							Ok(output) => Err(format!("The document graph output {} instead of a file.", output.ty())),
+							Err(e) => Err(format!("Node graph evaluation failed: {e:?}")),
+						};
+						DocumentMessageHandler::download_encoded_file(&file_name, file_type, data, responses);
+						continue;
+					}
+
 					self.thumbnails = new_thumbnails;
 					document.metadata.update_transforms(new_transforms, new_upstream_transforms);
 					document.metadata.update_click_targets(new_click_targets);
 					let node_graph_output = result.map_err(|e| format!("Node graph evaluation failed: {e:?}"))?;
-					let execution_context = self.futures.remove(&generation_id).ok_or_else(|| "Invalid generation ID".to_string())?;
 					responses.extend(updates);
 					self.process_node_graph_output(node_graph_output, execution_context.layer_path.clone(), transform, responses)?;
 					responses.add(DocumentMessage::LayerChanged {
@@ -0,0 +1,111 @@
+use crate::application::Editor;
+use crate::messages::debug::utility_types::MessageRecording;
+use crate::messages::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+/// The observable outcome of replaying a [`MessageRecording`], which is saved alongside the recording so later replays can be checked against it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplaySnapshot {
+	/// The serialized active document at the end of the replay.
+	pub document: Option<serde_json::Value>,
+	/// Every message the editor sent to the frontend during the replay.
+	pub frontend_messages: Vec<serde_json::Value>,
+}
+
+impl ReplaySnapshot {
+	pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+		serde_json::from_str(json)
+	}
+
+	pub fn to_json(&self) -> String {
+		serde_json::to_string_pretty(self).expect("Failed to serialize the replay snapshot")
+	}
+
+	/// Describes the first difference from an expected snapshot, or returns `None` if they are identical.
+	pub fn first_difference(&self, expected: &ReplaySnapshot) -> Option<String> {
+		if self.document != expected.document {
+			return Some("The final document differs from the snapshot".to_string());
+		}
+
+		let differing_message = self.frontend_messages.iter().zip(expected.frontend_messages.iter()).position(|(actual, expected)| actual != expected);
+		if let Some(index) = differing_message {
+			return Some(format!(
+				"Frontend message {index} differs from the snapshot.\nExpected: {}\nActual: {}",
+				expected.frontend_messages[index], self.frontend_messages[index]
+			));
+		}
+
+		if self.frontend_messages.len() != expected.frontend_messages.len() {
+			return Some(format!(
+				"Expected {} frontend messages but the replay produced {}",
+				expected.frontend_messages.len(),
+				self.frontend_messages.len()
+			));
+		}
+
+		None
+	}
+}
+
+/// Feeds a recording into a fresh editor instance without a frontend, evaluating the node graph after every message.
+/// The recorded timing is ignored so the replay runs as fast as possible.
+pub fn replay_recording(recording: &MessageRecording) -> ReplaySnapshot {
+	let (mut editor, mut frontend_messages) = Editor::new_headless(recording.platform);
+	for recorded in &recording.messages {
+		frontend_messages.extend(editor.evaluate_node_graph_blocking());
+		frontend_messages.extend(editor.handle_message(recorded.message.clone()));
+	}
+	frontend_messages.extend(editor.evaluate_node_graph_blocking());
+
+	let document = editor.dispatcher.message_handlers.portfolio_message_handler.active_document().map(|document| {
+		let serialized = document.serialize_document();
+		serde_json::from_str(&serialized).expect("Failed to parse the serialized document")
+	});
+	let frontend_messages = frontend_messages
+		.iter()
+		.map(|message| serde_json::to_value(message).expect("Failed to serialize frontend message"))
+		.collect();
+
+	ReplaySnapshot { document, frontend_messages }
+}
+
+#[cfg(test)]
+mod test {
+	use super::replay_recording;
+	use crate::application::Editor;
+	use crate::messages::debug::utility_types::MessageRecording;
+	use crate::messages::prelude::*;
+	use crate::test_utils::EditorTestUtils;
+
+	use graphene_core::raster::color::Color;
+
+	#[test]
+	fn recorded_session_replays_to_same_document() {
+		let mut editor = Editor::create();
+		editor.new_document();
+
+		editor.handle_message(DebugMessage::StartRecording);
+		editor.select_primary_color(Color::RED);
+		editor.draw_rect(100., 200., 300., 400.);
+		editor.draw_ellipse(50., 50., 150., 250.);
+		let responses = editor.handle_message(DebugMessage::StopRecording);
+
+		let recording = responses
+			.iter()
+			.find_map(|response| match response {
+				FrontendMessage::TriggerDownloadTextFile { document, .. } => Some(MessageRecording::from_json(document).unwrap()),
+				_ => None,
+			})
+			.expect("Stopping the recording should download it");
+		assert!(recording.messages.iter().all(|recorded| !matches!(recorded.message, Message::Debug(_))));
+
+		let first = replay_recording(&recording);
+		let second = replay_recording(&recording);
+		assert_eq!(first.first_difference(&second), None);
+
+		let nodes = first.document.as_ref().unwrap()["document_legacy"]["document_network"]["nodes"].as_object().unwrap();
+		let layers = nodes.values().filter(|node| node["name"] == "Layer").count();
+		assert_eq!(layers, 2);
+	}
+}
@@ -0,0 +1,466 @@
+use crate::messages::frontend::utility_types::{BitDepth, ExportBounds, FileType};
+use crate::messages::portfolio::document::node_graph::{resolve_document_node_type, TransformIn};
+use crate::messages::prelude::*;
+use crate::messages::tool::common_functionality::graph_modification_utils;
+
+use document_legacy::document_metadata::LayerNodeIdentifier;
+use graph_craft::document::value::TaggedValue;
+use graph_craft::document::{generate_uuid, NodeId, NodeInput};
+use graphene_core::text::{Font, TypesettingConfig};
+use graphene_core::vector::style::{Fill, Stroke};
+use graphene_core::Color;
+
+use glam::{DAffine2, DVec2};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, ImmutableString, INT};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Upper bound on the number of operations a script may perform, so a runaway loop can't freeze the editor.
+const MAX_SCRIPT_OPERATIONS: u64 = 50_000_000;
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// The state of a single layer as seen by a script.
+#[derive(Debug, Clone)]
+struct ScriptLayer {
+	id: NodeId,
+	name: String,
+	fill: Option<Color>,
+	is_shape: bool,
+	is_text: bool,
+}
+
+/// The types of number a node input can hold, which the numbers of scripts are converted to since Rhai only has `i64` integers and `f64` floats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NumberType {
+	U32,
+	F32,
+	F64,
+}
+
+impl NumberType {
+	fn of_input(input: &NodeInput) -> Option<Self> {
+		match input {
+			NodeInput::Value { tagged_value, .. } => match tagged_value {
+				TaggedValue::U32(_) => Some(Self::U32),
+				TaggedValue::F32(_) => Some(Self::F32),
+				TaggedValue::F64(_) | TaggedValue::AnimatedF64(_) => Some(Self::F64),
+				_ => None,
+			},
+			_ => None,
+		}
+	}
+
+	/// The number type of each of the inputs of a node
+	fn of_inputs<'a>(inputs: impl Iterator<Item = &'a NodeInput>) -> Vec<Option<Self>> {
+		inputs.map(Self::of_input).collect()
+	}
+}
+
+/// Read-only view of the document taken when the script starts.
+/// Scripts only queue up messages, so queries don't observe the script's own changes until after it has finished running.
+#[derive(Debug, Default)]
+struct ScriptDocument {
+	layers: Vec<ScriptLayer>,
+	selected_layers: Vec<NodeId>,
+	file_name: String,
+	/// The number types of the inputs of the nodes in the network open in the node graph, which `set_input` edits
+	number_inputs: HashMap<NodeId, Vec<Option<NumberType>>>,
+}
+
+impl ScriptDocument {
+	fn new(document: &DocumentMessageHandler) -> Self {
+		let layers = document
+			.metadata()
+			.all_layers()
+			.map(|layer| ScriptLayer {
+				id: layer.to_node(),
+				name: document.layer_name(layer),
+				fill: graph_modification_utils::get_fill_color(layer, &document.document_legacy),
+				is_shape: graph_modification_utils::is_shape_layer(layer, &document.document_legacy),
+				is_text: graph_modification_utils::is_text_layer(layer, &document.document_legacy),
+			})
+			.collect();
+		let selected_layers = document.metadata().selected_layers().map(LayerNodeIdentifier::to_node).collect();
+		let number_inputs = document
+			.opened_network()
+			.map(|network| network.nodes.iter().map(|(&id, node)| (id, NumberType::of_inputs(node.inputs.iter()))).collect())
+			.unwrap_or_default();
+
+		Self {
+			layers,
+			selected_layers,
+			file_name: document.name.clone(),
+			number_inputs,
+		}
+	}
+
+	fn layer(&self, id: INT) -> ScriptResult<&ScriptLayer> {
+		let id = to_node_id(id);
+		self.layers.iter().find(|layer| layer.id == id).ok_or_else(|| format!("There is no layer with the id {id}").into())
+	}
+}
+
+/// Node and layer ids are `u64` but Rhai integers are `i64`, so ids cross the boundary by reinterpreting their bits.
+fn to_script_id(id: NodeId) -> INT {
+	id as INT
+}
+
+fn to_node_id(id: INT) -> NodeId {
+	id as NodeId
+}
+
+fn ids_to_array(ids: impl Iterator<Item = NodeId>) -> Array {
+	ids.map(|id| Dynamic::from(to_script_id(id))).collect()
+}
+
+/// Parses a hex color in the `RRGGBB` or `RRGGBBAA` format, with or without a leading `#`.
+fn parse_color(color: &str) -> ScriptResult<Color> {
+	let hex = color.trim().trim_start_matches('#');
+	let parsed = match hex.len() {
+		6 => Color::from_rgb_str(hex),
+		8 => Color::from_rgba_str(hex),
+		_ => None,
+	};
+	parsed.ok_or_else(|| format!("'{color}' is not a valid hex color, expected RRGGBB or RRGGBBAA").into())
+}
+
+/// Converts a script value to the value of a node input, giving numbers the type of the input they're set to, or `f64` if the input doesn't hold a number.
+fn tagged_value_from_dynamic(value: Dynamic, number_type: Option<NumberType>) -> ScriptResult<TaggedValue> {
+	let number = value.clone().try_cast::<rhai::FLOAT>().or_else(|| value.clone().try_cast::<INT>().map(|value| value as rhai::FLOAT));
+	if let Some(number) = number {
+		return match number_type {
+			Some(NumberType::U32) if number.fract() == 0. && (0. ..=u32::MAX as f64).contains(&number) => Ok(TaggedValue::U32(number as u32)),
+			Some(NumberType::U32) => Err(format!("The input takes whole numbers from 0 to {} but was set to {number}", u32::MAX).into()),
+			Some(NumberType::F32) => Ok(TaggedValue::F32(number as f32)),
+			Some(NumberType::F64) | None => Ok(TaggedValue::F64(number)),
+		};
+	}
+	if let Some(value) = value.clone().try_cast::<bool>() {
+		return Ok(TaggedValue::Bool(value));
+	}
+	if let Some(value) = value.clone().try_cast::<ImmutableString>() {
+		return Ok(TaggedValue::String(value.to_string()));
+	}
+	Err(format!("Node inputs can't be set to a value of type '{}'", value.type_name()).into())
+}
+
+/// Runs an automation script against a document and returns the messages it produced, ready to be dispatched.
+///
+/// Scripts are written in [Rhai](https://rhai.rs). They can only reach the document through the functions registered here,
+/// which queue up `DocumentMessage`s, `GraphOperationMessage`s and `NodeGraphMessage`s rather than mutating anything directly.
+pub fn run_script(script: &str, document: &DocumentMessageHandler) -> Result<Vec<Message>, String> {
+	let snapshot = Rc::new(ScriptDocument::new(document));
+	let messages = Rc::new(RefCell::new(Vec::<Message>::new()));
+
+	let mut engine = Engine::new();
+	engine.set_max_operations(MAX_SCRIPT_OPERATIONS);
+	engine.set_max_call_levels(64);
+	engine.set_max_expr_depths(64, 64);
+	engine.on_print(|text| info!("Script: {text}"));
+	engine.on_debug(|text, _, position| debug!("Script ({position}): {text}"));
+
+	// Queries
+	let document = snapshot.clone();
+	engine.register_fn("layers", move || ids_to_array(document.layers.iter().map(|layer| layer.id)));
+	let document = snapshot.clone();
+	engine.register_fn("selected_layers", move || ids_to_array(document.selected_layers.iter().copied()));
+	let document = snapshot.clone();
+	engine.register_fn("layer_name", move |layer: INT| -> ScriptResult<String> { Ok(document.layer(layer)?.name.clone()) });
+	let document = snapshot.clone();
+	engine.register_fn("is_shape", move |layer: INT| -> ScriptResult<bool> { Ok(document.layer(layer)?.is_shape) });
+	let document = snapshot.clone();
+	engine.register_fn("is_text", move |layer: INT| -> ScriptResult<bool> { Ok(document.layer(layer)?.is_text) });
+	let document = snapshot.clone();
+	engine.register_fn("fill_color", move |layer: INT| -> ScriptResult<String> {
+		Ok(document.layer(layer)?.fill.map(|color| color.to_gamma_srgb().rgba_hex()).unwrap_or_default())
+	});
+
+	// Layer creation
+	let queue = messages.clone();
+	engine.register_fn("new_rectangle", move |x: rhai::FLOAT, y: rhai::FLOAT, width: rhai::FLOAT, height: rhai::FLOAT| {
+		let id = generate_uuid();
+		let subpath = bezier_rs::Subpath::new_rect(DVec2::new(x, y), DVec2::new(x + width, y + height));
+		queue.borrow_mut().push(GraphOperationMessage::NewVectorLayer { id, subpaths: vec![subpath] }.into());
+		to_script_id(id)
+	});
+	let queue = messages.clone();
+	engine.register_fn("new_ellipse", move |x: rhai::FLOAT, y: rhai::FLOAT, width: rhai::FLOAT, height: rhai::FLOAT| {
+		let id = generate_uuid();
+		let subpath = bezier_rs::Subpath::new_ellipse(DVec2::new(x, y), DVec2::new(x + width, y + height));
+		queue.borrow_mut().push(GraphOperationMessage::NewVectorLayer { id, subpaths: vec![subpath] }.into());
+		to_script_id(id)
+	});
+	let queue = messages.clone();
+	engine.register_fn("new_text", move |text: &str, size: rhai::FLOAT| {
+		let id = generate_uuid();
+		let font = Font::new(crate::consts::DEFAULT_FONT_FAMILY.into(), crate::consts::DEFAULT_FONT_STYLE.into());
+		queue.borrow_mut().push(
+			GraphOperationMessage::NewTextLayer {
+				id,
+				text: text.to_string(),
+				font,
+				typesetting: TypesettingConfig {
+					font_size: size,
+					..Default::default()
+				},
+			}
+			.into(),
+		);
+		to_script_id(id)
+	});
+	let queue = messages.clone();
+	engine.register_fn("delete_layer", move |layer: INT| {
+		queue.borrow_mut().push(GraphOperationMessage::DeleteLayer { id: to_node_id(layer) }.into());
+	});
+
+	// Layer styling and placement
+	let queue = messages.clone();
+	engine.register_fn("set_fill", move |layer: INT, color: &str| -> ScriptResult<()> {
+		let fill = Fill::Solid(parse_color(color)?);
+		queue.borrow_mut().push(GraphOperationMessage::FillSet { layer: vec![to_node_id(layer)], fill }.into());
+		Ok(())
+	});
+	let queue = messages.clone();
+	engine.register_fn("clear_fill", move |layer: INT| {
+		queue.borrow_mut().push(
+			GraphOperationMessage::FillSet {
+				layer: vec![to_node_id(layer)],
+				fill: Fill::None,
+			}
+			.into(),
+		);
+	});
+	let queue = messages.clone();
+	engine.register_fn("set_stroke", move |layer: INT, color: &str, weight: rhai::FLOAT| -> ScriptResult<()> {
+		let stroke = Stroke::new(Some(parse_color(color)?), weight);
+		queue.borrow_mut().push(
+			GraphOperationMessage::StrokeSet {
+				layer: vec![to_node_id(layer)],
+				stroke,
+			}
+			.into(),
+		);
+		Ok(())
+	});
+	let queue = messages.clone();
+	engine.register_fn("translate", move |layer: INT, x: rhai::FLOAT, y: rhai::FLOAT| {
+		queue.borrow_mut().push(
+			GraphOperationMessage::TransformChange {
+				layer: vec![to_node_id(layer)],
+				transform: DAffine2::from_translation(DVec2::new(x, y)),
+				transform_in: TransformIn::Local,
+				skip_rerender: false,
+			}
+			.into(),
+		);
+	});
+	let queue = messages.clone();
+	engine.register_fn("scale", move |layer: INT, x: rhai::FLOAT, y: rhai::FLOAT| {
+		queue.borrow_mut().push(
+			GraphOperationMessage::TransformChange {
+				layer: vec![to_node_id(layer)],
+				transform: DAffine2::from_scale(DVec2::new(x, y)),
+				transform_in: TransformIn::Local,
+				skip_rerender: false,
+			}
+			.into(),
+		);
+	});
+	let queue = messages.clone();
+	engine.register_fn("rotate", move |layer: INT, degrees: rhai::FLOAT| {
+		queue.borrow_mut().push(
+			GraphOperationMessage::TransformChange {
+				layer: vec![to_node_id(layer)],
+				transform: DAffine2::from_angle(degrees.to_radians()),
+				transform_in: TransformIn::Local,
+				skip_rerender: false,
+			}
+			.into(),
+		);
+	});
+	let queue = messages.clone();
+	engine.register_fn("set_selected_layers", move |layers: Array| -> ScriptResult<()> {
+		let nodes = layers
+			.into_iter()
+			.map(|layer| {
+				layer
+					.as_int()
+					.map(to_node_id)
+					.map_err(|type_name| format!("Expected a layer id but got a value of type '{type_name}'").into())
+			})
+			.collect::<ScriptResult<Vec<_>>>()?;
+		queue.borrow_mut().push(NodeGraphMessage::SetSelectedNodes { nodes }.into());
+		Ok(())
+	});
+
+	// Node graph
+	// The number types of the inputs of the nodes added by the script, which aren't in the snapshot
+	let added_number_inputs = Rc::new(RefCell::new(HashMap::<NodeId, Vec<Option<NumberType>>>::new()));
+	let queue = messages.clone();
+	let added = added_number_inputs.clone();
+	engine.register_fn("add_node", move |node_type: &str, x: INT, y: INT| {
+		let node_id = generate_uuid();
+		if let Some(document_node_type) = resolve_document_node_type(node_type) {
+			added.borrow_mut().insert(node_id, NumberType::of_inputs(document_node_type.inputs.iter().map(|input| &input.default)));
+		}
+		queue.borrow_mut().push(
+			NodeGraphMessage::CreateNode {
+				node_id: Some(node_id),
+				node_type: node_type.to_string(),
+				x: x as i32,
+				y: y as i32,
+			}
+			.into(),
+		);
+		to_script_id(node_id)
+	});
+	let queue = messages.clone();
+	let document = snapshot.clone();
+	let added = added_number_inputs.clone();
+	engine.register_fn("set_input", move |node: INT, input_index: INT, value: Dynamic| -> ScriptResult<()> {
+		let number_type = |inputs: &HashMap<NodeId, Vec<Option<NumberType>>>| inputs.get(&to_node_id(node))?.get(input_index as usize).copied().flatten();
+		let number_type = number_type(&document.number_inputs).or_else(|| number_type(&added.borrow()));
+		let value = tagged_value_from_dynamic(value, number_type)?;
+		queue.borrow_mut().push(
+			NodeGraphMessage::SetInputValue {
+				node_id: to_node_id(node),
+				input_index: input_index as usize,
+				value,
+			}
+			.into(),
+		);
+		Ok(())
+	});
+	let queue = messages.clone();
+	engine.register_fn("set_input_color", move |node: INT, input_index: INT, color: &str| -> ScriptResult<()> {
+		let value = TaggedValue::Color(parse_color(color)?);
+		queue.borrow_mut().push(
+			NodeGraphMessage::SetInputValue {
+				node_id: to_node_id(node),
+				input_index: input_index as usize,
+				value,
+			}
+			.into(),
+		);
+		Ok(())
+	});
+	let queue = messages.clone();
+	engine.register_fn("connect", move |output_node: INT, input_node: INT, input_index: INT| {
+		queue.borrow_mut().push(
+			NodeGraphMessage::SetNodeInput {
+				node_id: to_node_id(input_node),
+				input_index: input_index as usize,
+				input: NodeInput::node(to_node_id(output_node), 0),
+			}
+			.into(),
+		);
+		queue.borrow_mut().push(NodeGraphMessage::SendGraph { should_rerender: true }.into());
+	});
+
+	// Export
+	let queue = messages.clone();
+	let document = snapshot.clone();
+	engine.register_fn("export", move |file_type: &str| -> ScriptResult<()> {
+		let file_type = match file_type.to_lowercase().as_str() {
+			"svg" => FileType::Svg,
+			"png" => FileType::Png,
+			"jpg" | "jpeg" => FileType::Jpg,
+			"tif" | "tiff" => FileType::Tiff,
+			"exr" => FileType::Exr,
+			"pdf" => FileType::Pdf,
+			_ => return Err(format!("Unknown export format '{file_type}', expected svg, png, jpg, tiff, exr, or pdf").into()),
+		};
+		queue.borrow_mut().push(
+			DocumentMessage::ExportDocument {
+				file_name: document.file_name.clone(),
+				file_type,
+				scale_factor: 1.,
+				bounds: ExportBounds::AllArtwork,
+				transparent_background: false,
+				bit_depth: BitDepth::Eight,
+				embed_color_profile: false,
+				export_layers: false,
+			}
+			.into(),
+		);
+		Ok(())
+	});
+
+	engine.run(script).map_err(|error| error.to_string())?;
+
+	// Release the engine's clones of the message queue so it can be unwrapped
+	drop(engine);
+	let messages = Rc::try_unwrap(messages).map(RefCell::into_inner).unwrap_or_else(|messages| messages.borrow().clone());
+	Ok(messages)
+}
+
+#[cfg(test)]
+mod test {
+	use super::run_script;
+	use crate::messages::prelude::*;
+
+	use graph_craft::document::value::TaggedValue;
+	use graphene_core::vector::style::Fill;
+	use graphene_core::Color;
+
+	#[test]
+	fn script_queues_messages() {
+		let document = DocumentMessageHandler::default();
+
+		let messages = run_script(
+			r##"
+				let layer = new_rectangle(0.0, 0.0, 100.0, 50.0);
+				set_fill(layer, "#FF0000");
+				print(`created ${layer}`);
+			"##,
+			&document,
+		)
+		.unwrap();
+
+		assert_eq!(messages.len(), 2);
+		assert!(matches!(
+			&messages[0],
+			Message::Portfolio(PortfolioMessage::Document(DocumentMessage::GraphOperation(GraphOperationMessage::NewVectorLayer { .. })))
+		));
+		let Message::Portfolio(PortfolioMessage::Document(DocumentMessage::GraphOperation(GraphOperationMessage::FillSet { fill: Fill::Solid(color), .. }))) = &messages[1] else {
+			panic!("Expected a fill to be set, got {:?}", messages[1]);
+		};
+		assert_eq!(*color, Color::from_rgb_str("FF0000").unwrap());
+	}
+
+	#[test]
+	fn numbers_take_the_type_of_the_input() {
+		let document = DocumentMessageHandler::default();
+
+		let messages = run_script(
+			r#"
+				let noise = add_node("Pixel Noise", 0, 0);
+				set_input(noise, 2, 7);
+				set_input(noise, 2, 8.0);
+			"#,
+			&document,
+		)
+		.unwrap();
+		let values: Vec<_> = messages
+			.iter()
+			.filter_map(|message| match message {
+				Message::Portfolio(PortfolioMessage::Document(DocumentMessage::NodeGraph(NodeGraphMessage::SetInputValue { value, .. }))) => Some(value.clone()),
+				_ => None,
+			})
+			.collect();
+		assert_eq!(values, [TaggedValue::U32(7), TaggedValue::U32(8)]);
+
+		assert!(run_script(r#"set_input(add_node("Pixel Noise", 0, 0), 2, -1);"#, &document).is_err());
+		assert!(run_script(r#"set_input(add_node("Pixel Noise", 0, 0), 2, 0.5);"#, &document).is_err());
+	}
+
+	#[test]
+	fn script_errors_are_reported() {
+		let document = DocumentMessageHandler::default();
+
+		assert!(run_script("set_fill(0, \"not a color\");", &document).is_err());
+		assert!(run_script("this is not valid syntax", &document).is_err());
+	}
+}
@@ -69,6 +69,7 @@ impl EditorTestUtils for Editor {
 			editor_position: (x2, y2).into(),
 			mouse_keys: MouseKeys::empty(),
 			scroll_delta: ScrollDelta::default(),
+			pressure: None,
 		});
 	}
 
@@ -94,6 +95,7 @@ impl EditorTestUtils for Editor {
 			editor_position: (x, y).into(),
 			mouse_keys: MouseKeys::LEFT,
 			scroll_delta: ScrollDelta::default(),
+			pressure: None,
 		});
 	}
 
@@ -58,7 +58,11 @@ async fn main() {
 
 	*(IMAGES.lock().unwrap()) = Some(HashMap::new());
 	graphite_editor::application::set_uuid_seed(0);
-	EDITOR.with(|editor| editor.borrow_mut().replace(Editor::new()));
+	EDITOR.with(|editor| {
+		let mut new_editor = Editor::new();
+		new_editor.load_system_fonts();
+		editor.borrow_mut().replace(new_editor);
+	});
 	let app = Router::new().route("/", get(|| async { "Hello, World!" })).route("/image/:id", get(respond_to));
 
 	// run it with hyper on localhost:3000
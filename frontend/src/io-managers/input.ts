@@ -6,7 +6,7 @@ import { type FullscreenState } from "@graphite/state-providers/fullscreen";
 import { type PortfolioState } from "@graphite/state-providers/portfolio";
 import { makeKeyboardModifiersBitfield, textInputCleanup, getLocalizedScanCode } from "@graphite/utility-functions/keyboard-entry";
 import { platformIsMac } from "@graphite/utility-functions/platform";
-import { extractPixelData } from "@graphite/utility-functions/rasterization";
+import { isImageFile, pasteImageFile } from "@graphite/utility-functions/rasterization";
 import { stripIndents } from "@graphite/utility-functions/strip-indents";
 import { type Editor } from "@graphite/wasm-communication/editor";
 import { TriggerPaste } from "@graphite/wasm-communication/messages";
@@ -146,7 +146,7 @@ export function createInputManager(editor: Editor, dialog: DialogState, portfoli
 		}
 
 		const modifiers = makeKeyboardModifiersBitfield(e);
-		editor.instance.onMouseMove(e.clientX, e.clientY, e.buttons, modifiers);
+		editor.instance.onMouseMove(e.clientX, e.clientY, e.buttons, modifiers, penPressure(e));
 	}
 
 	function onMouseDown(e: MouseEvent): void {
@@ -173,7 +173,7 @@ export function createInputManager(editor: Editor, dialog: DialogState, portfoli
 
 		if (viewportPointerInteractionOngoing) {
 			const modifiers = makeKeyboardModifiersBitfield(e);
-			editor.instance.onMouseDown(e.clientX, e.clientY, e.buttons, modifiers);
+			editor.instance.onMouseDown(e.clientX, e.clientY, e.buttons, modifiers, penPressure(e));
 		}
 	}
 
@@ -284,11 +284,7 @@ export function createInputManager(editor: Editor, dialog: DialogState, portfoli
 			}
 
 			const file = item.getAsFile();
-			if (file?.type.startsWith("image")) {
-				extractPixelData(file).then((imageData): void => {
-					editor.instance.pasteImage(new Uint8Array(imageData.data), imageData.width, imageData.height);
-				});
-			}
+			if (file && isImageFile(file)) pasteImageFile(editor, file);
 		});
 	}
 
@@ -331,10 +327,7 @@ export function createInputManager(editor: Editor, dialog: DialogState, portfoli
 					const blob = await item.getType(imageType);
 					const reader = new FileReader();
 					reader.onload = async (): Promise<void> => {
-						if (reader.result instanceof ArrayBuffer) {
-							const imageData = await extractPixelData(new Blob([reader.result], { type: imageType }));
-							editor.instance.pasteImage(new Uint8Array(imageData.data), imageData.width, imageData.height);
-						}
+						if (reader.result instanceof ArrayBuffer) await pasteImageFile(editor, new Blob([reader.result], { type: imageType }));
 					};
 					reader.readAsArrayBuffer(blob);
 				}
@@ -376,3 +369,8 @@ export function createInputManager(editor: Editor, dialog: DialogState, portfoli
 function targetIsTextField(target: EventTarget | HTMLElement | undefined): boolean {
 	return target instanceof HTMLElement && (target.nodeName === "INPUT" || target.nodeName === "TEXTAREA" || target.isContentEditable);
 }
+
+// Mice report a constant pressure while a button is held, so only pens report their pressure to the backend
+function penPressure(e: PointerEvent): number | undefined {
+	return e.pointerType === "pen" ? e.pressure : undefined;
+}
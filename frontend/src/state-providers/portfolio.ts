@@ -3,18 +3,22 @@
 import { writable } from "svelte/store";
 
 import { downloadFileText, downloadFileBlob, upload, downloadFileURL } from "@graphite/utility-functions/files";
-import { extractPixelData, imageToPNG, rasterizeSVG, rasterizeSVGCanvas } from "@graphite/utility-functions/rasterization";
+import { imageToPNG, pasteImageFile, rasterizeSVG, rasterizeSVGCanvas } from "@graphite/utility-functions/rasterization";
 import { type Editor } from "@graphite/wasm-communication/editor";
 import {
 	type FrontendDocumentDetails,
 	TriggerCopyToClipboardBlobUrl,
 	TriggerFetchAndOpenDocument,
+	TriggerDownloadBinaryFile,
 	TriggerDownloadBlobUrl,
 	TriggerDownloadRaster,
 	TriggerDownloadTextFile,
 	TriggerImport,
+	TriggerLoadProofProfile,
 	TriggerOpenDocument,
+	TriggerOpenScript,
 	TriggerRasterizeRegionBelowLayer,
+	TriggerRasterizeSequenceFrame,
 	TriggerRevokeBlobUrl,
 	UpdateActiveDocument,
 	UpdateImageData,
@@ -63,14 +67,26 @@ export function createPortfolioState(editor: Editor) {
 		const data = await upload(extension, "text");
 		editor.instance.openDocumentFile(data.filename, data.content);
 	});
+	editor.subscriptions.subscribeJsMessage(TriggerOpenScript, async () => {
+		const data = await upload(".rhai", "text");
+		editor.instance.runScript(data.filename, data.content);
+	});
 	editor.subscriptions.subscribeJsMessage(TriggerImport, async () => {
-		const data = await upload("image/*", "data");
-		const imageData = await extractPixelData(new Blob([data.content], { type: data.type }));
-		editor.instance.pasteImage(new Uint8Array(imageData.data), imageData.width, imageData.height);
+		const data = await upload("image/*,.exr,.psd,.psb", "data");
+		await pasteImageFile(editor, new Blob([data.content], { type: data.type }));
+	});
+	editor.subscriptions.subscribeJsMessage(TriggerLoadProofProfile, async () => {
+		const data = await upload(".icc,.icm", "data");
+		editor.instance.loadProofProfile(data.content);
 	});
 	editor.subscriptions.subscribeJsMessage(TriggerDownloadTextFile, (triggerFileDownload) => {
 		downloadFileText(triggerFileDownload.name, triggerFileDownload.document);
 	});
+	editor.subscriptions.subscribeJsMessage(TriggerDownloadBinaryFile, (triggerDownloadBinaryFile) => {
+		const { name, mime, data } = triggerDownloadBinaryFile;
+
+		downloadFileBlob(name, new Blob([new Uint8Array(data)], { type: mime }));
+	});
 	editor.subscriptions.subscribeJsMessage(TriggerDownloadBlobUrl, async (triggerDownloadBlobUrl) => {
 		const data = await fetch(triggerDownloadBlobUrl.blobUrl);
 		const blob = await data.blob();
@@ -133,6 +149,21 @@ export function createPortfolioState(editor: Editor) {
 		}
 
 	});
+	editor.subscriptions.subscribeJsMessage(TriggerRasterizeSequenceFrame, async (triggerRasterizeSequenceFrame) => {
+		const { frameIndex, svg, size } = triggerRasterizeSequenceFrame;
+
+		try {
+			const imageData = (await rasterizeSVGCanvas(svg, size[0], size[1])).getContext("2d")?.getImageData(0, 0, size[0], size[1]);
+			if (!imageData) throw new Error("Can't read the pixels of the rasterized sequence frame");
+
+			editor.instance.sequenceFrameRasterized(frameIndex, new Uint8Array(imageData.data), imageData.width, imageData.height);
+		}
+		// An empty frame tells the backend to abandon the export
+		catch (e) {
+			console.error("Failed to rasterize a frame of the sequence export:", e);
+			editor.instance.sequenceFrameRasterized(frameIndex, new Uint8Array(), 0, 0);
+		}
+	});
 	editor.subscriptions.subscribeJsMessage(TriggerRevokeBlobUrl, async (triggerRevokeBlobUrl) => {
 		URL.revokeObjectURL(triggerRevokeBlobUrl.url);
 	});
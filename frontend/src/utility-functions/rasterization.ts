@@ -1,4 +1,5 @@
 import { replaceBlobURLsWithBase64 } from "@graphite/utility-functions/files";
+import { type Editor } from "@graphite/wasm-communication/editor";
 
 // Rasterize the string of an SVG document at a given width and height and return the canvas it was drawn onto during the rasterization process
 export async function rasterizeSVGCanvas(svg: string, width: number, height: number, backgroundColor?: string): Promise<HTMLCanvasElement> {
@@ -65,6 +66,21 @@ export async function extractPixelData(imageData: ImageBitmapSource): Promise<Im
 	return canvasContext.getImageData(0, 0, width, height);
 }
 
+// Whether a file can be imported as an image, including OpenEXR and Photoshop files which browsers may not give an image MIME type
+export function isImageFile(file: File): boolean {
+	const name = file.name.toLowerCase();
+	return file.type.startsWith("image") || [".exr", ".psd", ".psb"].some((extension) => name.endsWith(extension));
+}
+
+// Pass an image file to the editor, which decodes it itself to keep its bit depth and channels, falling back to the browser's 8-bit decoding for formats the editor can't read
+export async function pasteImageFile(editor: Editor, file: Blob, mouseX?: number, mouseY?: number): Promise<void> {
+	const data = new Uint8Array(await file.arrayBuffer());
+	if (editor.instance.pasteImageFile(data, mouseX, mouseY)) return;
+
+	const imageData = await extractPixelData(file);
+	editor.instance.pasteImage(new Uint8Array(imageData.data), imageData.width, imageData.height, mouseX, mouseY);
+}
+
 /// Convert an image source (e.g. BMP document) into a PNG blob
 export async function imageToPNG(imageData: ImageBitmapSource): Promise<Blob> {
 	const canvasContext = await imageToCanvasContext(imageData);
@@ -510,12 +510,16 @@ export class TriggerLoadAutoSaveDocuments extends JsMessage { }
 
 export class TriggerLoadPreferences extends JsMessage { }
 
+export class TriggerLoadProofProfile extends JsMessage { }
+
 export class TriggerFetchAndOpenDocument extends JsMessage {
 	readonly url!: string;
 }
 
 export class TriggerOpenDocument extends JsMessage { }
 
+export class TriggerOpenScript extends JsMessage { }
+
 export class TriggerImport extends JsMessage { }
 
 export class TriggerPaste extends JsMessage { }
@@ -530,6 +534,14 @@ export class TriggerDownloadBlobUrl extends JsMessage {
 	readonly blobUrl!: string;
 }
 
+export class TriggerDownloadBinaryFile extends JsMessage {
+	readonly name!: string;
+
+	readonly mime!: string;
+
+	readonly data!: number[];
+}
+
 export class TriggerDownloadRaster extends JsMessage {
 	readonly svg!: string;
 
@@ -557,6 +569,14 @@ export class TriggerRasterizeRegionBelowLayer extends JsMessage {
 	readonly size!: [number, number];
 }
 
+export class TriggerRasterizeSequenceFrame extends JsMessage {
+	readonly frameIndex!: number;
+
+	readonly svg!: string;
+
+	readonly size!: [number, number];
+}
+
 export class TriggerRefreshBoundsOfViewports extends JsMessage { }
 
 export class TriggerRevokeBlobUrl extends JsMessage {
@@ -634,6 +654,8 @@ export function newUpdateDocumentLayerTreeStructure(input: { dataBuffer: DataBuf
 	return currentFolder;
 }
 
+export type TextAlignment = "Left" | "Center" | "Right" | "Justify";
+
 export class DisplayEditableTextbox extends JsMessage {
 	readonly text!: string;
 
@@ -641,6 +663,12 @@ export class DisplayEditableTextbox extends JsMessage {
 
 	readonly fontSize!: number;
 
+	readonly lineHeightRatio!: number;
+
+	readonly characterSpacing!: number;
+
+	readonly align!: TextAlignment;
+
 	@Type(() => Color)
 	readonly color!: Color;
 
@@ -1385,6 +1413,7 @@ export const messageMakers: Record<string, MessageMaker> = {
 	TriggerAboutGraphiteLocalizedCommitDate,
 	TriggerCopyToClipboardBlobUrl,
 	TriggerFetchAndOpenDocument,
+	TriggerDownloadBinaryFile,
 	TriggerDownloadBlobUrl,
 	TriggerDownloadRaster,
 	TriggerDownloadTextFile,
@@ -1395,9 +1424,12 @@ export const messageMakers: Record<string, MessageMaker> = {
 	TriggerIndexedDbWriteDocument,
 	TriggerLoadAutoSaveDocuments,
 	TriggerLoadPreferences,
+	TriggerLoadProofProfile,
 	TriggerOpenDocument,
+	TriggerOpenScript,
 	TriggerPaste,
 	TriggerRasterizeRegionBelowLayer,
+	TriggerRasterizeSequenceFrame,
 	TriggerRefreshBoundsOfViewports,
 	TriggerRevokeBlobUrl,
 	TriggerSavePreferences,
@@ -11,10 +11,14 @@ use editor::application::Editor;
 use editor::consts::{FILE_SAVE_SUFFIX, GRAPHITE_DOCUMENT_VERSION};
 use editor::messages::input_mapper::utility_types::input_keyboard::ModifierKeys;
 use editor::messages::input_mapper::utility_types::input_mouse::{EditorMouseState, ScrollDelta, ViewportBounds};
+use editor::messages::portfolio::document::utility_types::guides::GuideDirection;
+use editor::messages::portfolio::document::utility_types::{image_files, layered_images};
 use editor::messages::portfolio::utility_types::Platform;
 use editor::messages::prelude::*;
+use graph_craft::document::value::TaggedValue;
 use graph_craft::document::NodeId;
 use graphene_core::raster::color::Color;
+use graphene_core::raster::ImageFrame;
 
 use serde::Serialize;
 use serde_wasm_bindgen::{self, from_value};
@@ -87,7 +91,7 @@ async fn poll_node_graph_evaluation() {
 
 						let mut responses = Vec::new();
 						for message in messages.into_iter() {
-							responses.extend(editor.handle_message(message));
+							responses.extend(editor.handle_node_graph_message(message));
 						}
 
 						for response in responses.into_iter() {
@@ -329,6 +333,19 @@ impl JsEditorHandle {
 		self.dispatch(message);
 	}
 
+	#[wasm_bindgen(js_name = runScript)]
+	pub fn run_script(&self, script_name: String, script: String) {
+		let message = DocumentMessage::RunScript { script_name, script };
+		self.dispatch(message);
+	}
+
+	/// Loads an ICC profile for soft proofing the active document
+	#[wasm_bindgen(js_name = loadProofProfile)]
+	pub fn load_proof_profile(&self, data: Vec<u8>) {
+		let message = DocumentMessage::LoadProofProfile { data };
+		self.dispatch(message);
+	}
+
 	#[wasm_bindgen(js_name = openAutoSavedDocument)]
 	pub fn open_auto_saved_document(&self, document_id: u64, document_name: String, document_is_saved: bool, document_serialized_content: String) {
 		let message = PortfolioMessage::OpenDocumentFileWithId {
@@ -374,8 +391,9 @@ impl JsEditorHandle {
 
 	/// Mouse movement within the screenspace bounds of the viewport
 	#[wasm_bindgen(js_name = onMouseMove)]
-	pub fn on_mouse_move(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8) {
-		let editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+	pub fn on_mouse_move(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8, pressure: Option<f64>) {
+		let mut editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+		editor_mouse_state.pressure = pressure;
 
 		let modifier_keys = ModifierKeys::from_bits(modifiers).expect("Invalid modifier keys");
 
@@ -397,8 +415,9 @@ impl JsEditorHandle {
 
 	/// A mouse button depressed within screenspace the bounds of the viewport
 	#[wasm_bindgen(js_name = onMouseDown)]
-	pub fn on_mouse_down(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8) {
-		let editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+	pub fn on_mouse_down(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8, pressure: Option<f64>) {
+		let mut editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+		editor_mouse_state.pressure = pressure;
 
 		let modifier_keys = ModifierKeys::from_bits(modifiers).expect("Invalid modifier keys");
 
@@ -575,6 +594,14 @@ impl JsEditorHandle {
 		self.dispatch(message);
 	}
 
+	/// Begins dragging a new guide out of the top (horizontal) or left (vertical) ruler
+	#[wasm_bindgen(js_name = dragGuideFromRuler)]
+	pub fn drag_guide_from_ruler(&self, horizontal: bool) {
+		let direction = if horizontal { GuideDirection::Horizontal } else { GuideDirection::Vertical };
+		let message = GuidesMessage::DragFromRuler { direction };
+		self.dispatch(message);
+	}
+
 	/// Sends the blob URL generated by JS to the Image layer
 	#[wasm_bindgen(js_name = setImageBlobURL)]
 	pub fn set_image_blob_url(&self, document_id: u64, layer_path: Vec<LayerId>, node_id: Option<NodeId>, blob_url: String, width: f64, height: f64, transform: Option<js_sys::Float64Array>) {
@@ -603,6 +630,13 @@ impl JsEditorHandle {
 		self.dispatch(message);
 	}
 
+	/// Sends the pixels of a frame of a sequence export, rasterized by JS, back to the document being exported
+	#[wasm_bindgen(js_name = sequenceFrameRasterized)]
+	pub fn sequence_frame_rasterized(&self, frame_index: usize, rgba: Vec<u8>, width: u32, height: u32) {
+		let message = DocumentMessage::SequenceFrameRasterized { frame_index, width, height, rgba };
+		self.dispatch(message);
+	}
+
 	/// Notifies the backend that the user connected a node's primary output to one of another node's inputs
 	#[wasm_bindgen(js_name = connectNodesByLink)]
 	pub fn connect_nodes_by_link(&self, output_node: u64, output_node_connector_index: usize, input_node: u64, input_node_connector_index: usize) {
@@ -694,8 +728,45 @@ impl JsEditorHandle {
 	pub fn paste_image(&self, image_data: Vec<u8>, width: u32, height: u32, mouse_x: Option<f64>, mouse_y: Option<f64>) {
 		let mouse = mouse_x.and_then(|x| mouse_y.map(|y| (x, y)));
 		let image = graphene_core::raster::Image::from_image_data(&image_data, width, height);
-		let message = DocumentMessage::PasteImage { image, mouse };
+		let image_frame = TaggedValue::ImageFrame(ImageFrame { image, ..ImageFrame::identity() });
+		let message = DocumentMessage::PasteImage { image_frame, mouse };
+		self.dispatch(message);
+	}
+
+	/// Pastes an image file, decoded by the editor so its bit depth and channels are kept. Returns false if the file's format can't be decoded, in which case the browser should decode it.
+	/// OpenEXR, TIFF and Photoshop files are pasted as a layer for each of their layers or pages, and Photoshop layer groups as folders.
+	#[wasm_bindgen(js_name = pasteImageFile)]
+	pub fn paste_image_file(&self, file_data: Vec<u8>, mouse_x: Option<f64>, mouse_y: Option<f64>) -> bool {
+		let mouse = mouse_x.and_then(|x| mouse_y.map(|y| (x, y)));
+		// The colors are converted to the working space of the document they are pasted into
+		let working_space = EDITOR_INSTANCES.with(|instances| {
+			let editors = instances.try_borrow().ok()?;
+			let portfolio = &editors.get(&self.editor_id)?.dispatcher.message_handlers.portfolio_message_handler;
+			Some(portfolio.active_document()?.working_space)
+		});
+		let working_space = working_space.unwrap_or_default();
+
+		if layered_images::is_layered_format(&file_data) {
+			let image = match layered_images::decode_layered_image(&file_data, working_space) {
+				Ok(image) => image,
+				Err(description) => {
+					// Browsers can't decode these formats either, so the error is shown instead of falling back to them
+					let title = "Failed to import the image".to_string();
+					self.dispatch(DialogMessage::DisplayDialogError { title, description });
+					return true;
+				}
+			};
+			let message = DocumentMessage::PasteLayeredImage { image, mouse };
+			self.dispatch(message);
+			return true;
+		}
+
+		let Ok(image_frame) = image_files::decode_image(&file_data, working_space) else {
+			return false;
+		};
+		let message = DocumentMessage::PasteImage { image_frame, mouse };
 		self.dispatch(message);
+		true
 	}
 
 	/// Toggle visibility of a layer from the layer list
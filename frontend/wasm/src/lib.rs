@@ -13,6 +13,7 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::panic;
 use std::sync::atomic::AtomicBool;
+use std::sync::Once;
 use wasm_bindgen::prelude::*;
 
 // Set up the persistent editor backend state
@@ -32,4 +33,28 @@ pub fn init_graphite() {
 	// Set up the logger with a default level of debug
 	log::set_logger(&LOGGER).expect("Failed to set logger");
 	log::set_max_level(log::LevelFilter::Debug);
+
+	collect_registered_nodes();
+}
+
+/// Collects the nodes registered through `#[node_macro::node_fn]`, whose constructors the Wasm linker doesn't always run on its own
+fn collect_registered_nodes() {
+	static COLLECTED: Once = Once::new();
+	COLLECTED.call_once(|| {
+		// Only run the constructors if the start function didn't already, since running them twice would submit every node again
+		#[cfg(target_family = "wasm")]
+		if graphene_core::registry::registered_nodes().next().is_none() {
+			// SAFETY: The constructors only submit the registered nodes, and none have been submitted so they haven't run yet
+			unsafe { __wasm_call_ctors() };
+		}
+		if graphene_core::registry::registered_nodes().next().is_none() {
+			error!("No nodes were registered through `node_fn`, the Rust toolchain may be too old to run Wasm constructors");
+		}
+	});
+}
+
+#[cfg(target_family = "wasm")]
+extern "C" {
+	/// Synthesized by the Wasm linker to run the constructors placed in `.init_array`.
+	fn __wasm_call_ctors();
 }
@@ -1,10 +1,13 @@
-// #![cfg(target_arch = "wasm32")]
+#![cfg(target_arch = "wasm32")]
 
-// use wasm_bindgen_test::*;
+use wasm_bindgen_test::*;
 
-// wasm_bindgen_test_configure!(run_in_browser);
+// Run with `wasm-pack test --node frontend/wasm`
+#[wasm_bindgen_test]
+fn node_fn_registered_nodes_are_collected_once() {
+	graphite_wasm::init_graphite();
 
-// #[wasm_bindgen_test]
-// fn pass() {
-// 	assert_eq!(1 + 1, 2);
-// }
+	let identifier = "graphene_core::vector::vector_nodes::OffsetPathNode<_, _, _>";
+	let registrations = graphene_core::registry::registered_nodes().filter(|metadata| metadata.identifier == identifier).count();
+	assert_eq!(registrations, 1);
+}
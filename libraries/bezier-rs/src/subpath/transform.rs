@@ -360,7 +360,7 @@ impl<ManipulatorGroupId: crate::Identifier> Subpath<ManipulatorGroupId> {
 	/// The intersections of segments of the subpath are joined using the method specified by the `join` argument.
 	/// <iframe frameBorder="0" width="100%" height="400px" src="https://graphite.rs/libraries/bezier-rs#subpath/offset/solo" title="Offset Demo"></iframe>
 	pub fn offset(&self, distance: f64, join: Join) -> Subpath<ManipulatorGroupId> {
-		assert!(self.len_segments() > 1, "Cannot offset an empty Subpath.");
+		assert!(self.len_segments() > 0, "Cannot offset an empty Subpath.");
 
 		// An offset at a distance 0 from the curve is simply the same curve
 		// An offset of a single point is not defined
@@ -368,11 +368,46 @@ impl<ManipulatorGroupId: crate::Identifier> Subpath<ManipulatorGroupId> {
 			return self.clone();
 		}
 
-		let mut subpaths = self
+		let subpaths = self
 			.iter()
 			.filter(|bezier| !bezier.is_point())
 			.map(|bezier| bezier.offset(distance))
 			.collect::<Vec<Subpath<ManipulatorGroupId>>>();
+		// Every segment is a point, so like a single point the subpath has no offset
+		if subpaths.is_empty() {
+			return self.clone();
+		}
+		let join_distances = vec![distance; subpaths.len()];
+		self.join_offset_segments(subpaths, &join_distances, join)
+	}
+
+	/// Version of the `offset` function where the distance of each segment graduates from the first to the second of its pair of `distances`, as in [Bezier::graduated_offset].
+	/// The distances of consecutive segments should match where they meet, so that the offset stays continuous.
+	/// - `distances` - The start and end distance of each segment of the subpath, where positive values offset in the direction of the normals.
+	pub fn graduated_offset(&self, distances: &[(f64, f64)], join: Join) -> Subpath<ManipulatorGroupId> {
+		assert!(self.len_segments() > 0, "Cannot offset an empty Subpath.");
+		assert_eq!(distances.len(), self.len_segments(), "There must be a pair of distances for each segment.");
+
+		if distances.iter().all(|&(start, end)| start == 0. && end == 0.) || self.len() == 1 {
+			return self.clone();
+		}
+
+		let (subpaths, join_distances): (Vec<_>, Vec<_>) = self
+			.iter()
+			.zip(distances)
+			.filter(|(bezier, _)| !bezier.is_point())
+			.map(|(bezier, &(start, end))| (bezier.graduated_offset(start, end), start))
+			.filter(|(subpath, _)| !subpath.is_empty())
+			.unzip();
+		if subpaths.is_empty() {
+			return self.clone();
+		}
+		self.join_offset_segments(subpaths, &join_distances, join)
+	}
+
+	/// Clips or joins the offsets of consecutive segments, then merges them into one subpath.
+	/// - `join_distances` - The offset distance at the start of each segment, which determines the side of the path a join is on.
+	fn join_offset_segments(&self, mut subpaths: Vec<Subpath<ManipulatorGroupId>>, join_distances: &[f64], join: Join) -> Subpath<ManipulatorGroupId> {
 		let mut drop_common_point = vec![true; self.len()];
 
 		// Clip or join consecutive Subpaths
@@ -395,6 +430,7 @@ impl<ManipulatorGroupId: crate::Identifier> Subpath<ManipulatorGroupId> {
 			let angle = out_tangent.angle_between(in_tangent);
 
 			// The angle is concave. The Subpath overlap and must be clipped
+			let distance = join_distances[j];
 			let mut apply_join = true;
 			if (angle > 0. && distance > 0.) || (angle < 0. && distance < 0.) {
 				// If the distance is large enough, there may still be no intersections. Also, if the angle is close enough to zero,
@@ -434,6 +470,7 @@ impl<ManipulatorGroupId: crate::Identifier> Subpath<ManipulatorGroupId> {
 			let in_tangent = self.get_segment(0).unwrap().tangent(TValue::Parametric(0.));
 			let angle = out_tangent.angle_between(in_tangent);
 
+			let distance = join_distances[0];
 			let mut apply_join = true;
 			if (angle > 0. && distance > 0.) || (angle < 0. && distance < 0.) {
 				if let Some((clipped_subpath1, clipped_subpath2)) = Subpath::clip_simple_subpaths(&subpaths[subpaths.len() - 1], &subpaths[0]) {
@@ -490,33 +527,44 @@ impl<ManipulatorGroupId: crate::Identifier> Subpath<ManipulatorGroupId> {
 
 	/// Helper function to combine the two offsets that make up an outline.
 	pub(crate) fn combine_outline(&self, other: &Subpath<ManipulatorGroupId>, cap: Cap) -> Subpath<ManipulatorGroupId> {
+		self.combine_outline_with_caps(other, cap, cap)
+	}
+
+	/// Version of `combine_outline` with a separate cap at each end, where `self` is the offset running from the start of the outlined subpath to its end and `other` runs back.
+	pub(crate) fn combine_outline_with_caps(&self, other: &Subpath<ManipulatorGroupId>, start_cap: Cap, end_cap: Cap) -> Subpath<ManipulatorGroupId> {
 		let mut result_manipulator_groups: Vec<ManipulatorGroup<ManipulatorGroupId>> = vec![];
 		result_manipulator_groups.extend_from_slice(self.manipulator_groups());
-		match cap {
-			Cap::Butt => {
-				result_manipulator_groups.extend_from_slice(other.manipulator_groups());
-			}
+
+		// Cap the end of the subpath, going from this offset to the other
+		let mut other_in_handle = None;
+		match end_cap {
+			Cap::Butt => {}
 			Cap::Round => {
 				let last_index = result_manipulator_groups.len() - 1;
 				let (out_handle, round_point, in_handle) = self.round_cap(other);
 				result_manipulator_groups[last_index].out_handle = Some(out_handle);
 				result_manipulator_groups.push(round_point);
-				result_manipulator_groups.extend_from_slice(&other.manipulator_groups);
-				result_manipulator_groups[last_index + 2].in_handle = Some(in_handle);
+				other_in_handle = Some(in_handle);
+			}
+			Cap::Square => result_manipulator_groups.extend_from_slice(&self.square_cap(other)),
+		}
+		let other_start = result_manipulator_groups.len();
+		result_manipulator_groups.extend_from_slice(other.manipulator_groups());
+		if let Some(in_handle) = other_in_handle {
+			result_manipulator_groups[other_start].in_handle = Some(in_handle);
+		}
 
+		// Cap the start of the subpath, going from the other offset back to this one
+		match start_cap {
+			Cap::Butt => {}
+			Cap::Round => {
 				let last_index = result_manipulator_groups.len() - 1;
 				let (out_handle, round_point, in_handle) = other.round_cap(self);
 				result_manipulator_groups[last_index].out_handle = Some(out_handle);
 				result_manipulator_groups.push(round_point);
 				result_manipulator_groups[0].in_handle = Some(in_handle);
 			}
-			Cap::Square => {
-				let square_points = self.square_cap(other);
-				result_manipulator_groups.extend_from_slice(&square_points);
-				result_manipulator_groups.extend_from_slice(other.manipulator_groups());
-				let square_points = other.square_cap(self);
-				result_manipulator_groups.extend_from_slice(&square_points);
-			}
+			Cap::Square => result_manipulator_groups.extend_from_slice(&other.square_cap(self)),
 		}
 		Subpath::new(result_manipulator_groups, true)
 	}
@@ -545,6 +593,28 @@ impl<ManipulatorGroupId: crate::Identifier> Subpath<ManipulatorGroupId> {
 
 		(pos_offset.combine_outline(&neg_offset, cap), None)
 	}
+
+	/// Version of the `outline` function where the distance of the outline from each segment graduates from the first to the second of its pair of `distances`,
+	/// which allows strokes to vary in width along the subpath. See [Subpath::graduated_offset] for the restrictions on the distances.
+	/// The cap is left out at an end where the distance is zero, since the outline already comes to a point there, while the other end keeps it.
+	pub fn graduated_outline(&self, distances: &[(f64, f64)], join: Join, cap: Cap) -> (Subpath<ManipulatorGroupId>, Option<Subpath<ManipulatorGroupId>>) {
+		if self.is_point() || distances.is_empty() {
+			let distance = distances.first().map_or(0., |&(start, _)| start);
+			return self.outline(distance, join, cap);
+		}
+
+		let reversed_distances = distances.iter().rev().map(|&(start, end)| (end, start)).collect::<Vec<_>>();
+		let pos_offset = self.graduated_offset(distances, join);
+		let neg_offset = self.reverse().graduated_offset(&reversed_distances, join);
+
+		if self.closed {
+			return (pos_offset, Some(neg_offset));
+		}
+
+		let start_cap = if distances.first().map_or(false, |&(start, _)| start == 0.) { Cap::Butt } else { cap };
+		let end_cap = if distances.last().map_or(false, |&(_, end)| end == 0.) { Cap::Butt } else { cap };
+		(pos_offset.combine_outline_with_caps(&neg_offset, start_cap, end_cap), None)
+	}
 }
 
 #[cfg(test)]
@@ -1045,6 +1115,81 @@ mod tests {
 		assert_eq!(result.manipulator_groups.len(), 1);
 	}
 
+	#[test]
+	fn offset_single_segment() {
+		let subpath: Subpath<EmptyId> = Subpath::from_anchors([DVec2::new(0., 0.), DVec2::new(100., 0.)], false);
+		let offset = subpath.offset(10., Join::Miter(None));
+		let anchors = offset.anchors();
+		assert_eq!(offset.len_segments(), 1);
+		// Like other offsets this is an approximation, which lands within a fraction of a pixel of the exact offset
+		assert!(anchors[0].abs_diff_eq(DVec2::new(0., 10.), 0.1));
+		assert!(anchors[1].abs_diff_eq(DVec2::new(100., 10.), 0.1));
+	}
+
+	#[test]
+	fn offset_of_segments_collapsed_to_points() {
+		let p = DVec2::new(25., 25.);
+		let subpath: Subpath<EmptyId> = Subpath::from_anchors([p, p, p], false);
+		assert_eq!(subpath.offset(10., Join::Round), subpath);
+		assert_eq!(subpath.graduated_offset(&[(5., 10.), (10., 5.)], Join::Round), subpath);
+	}
+
+	#[test]
+	fn graduated_offset_of_polyline() {
+		let subpath: Subpath<EmptyId> = Subpath::from_anchors([DVec2::new(0., 0.), DVec2::new(100., 0.), DVec2::new(100., 100.)], false);
+
+		// Offsets are approximations, which land within a fraction of a pixel of the exact offset for these lines
+		let near = |a: DVec2, b: DVec2| a.abs_diff_eq(b, 0.1);
+
+		// A constant distance matches the regular offset
+		let constant = subpath.graduated_offset(&[(10., 10.), (10., 10.)], Join::Miter(None));
+		let regular = subpath.offset(10., Join::Miter(None));
+		assert!(constant.anchors().into_iter().zip(regular.anchors()).all(|(a, b)| near(a, b)));
+
+		let graduated = subpath.graduated_offset(&[(0., 10.), (10., 20.)], Join::Bevel);
+		let anchors = graduated.anchors();
+		assert!(near(anchors[0], DVec2::new(0., 0.)));
+		assert!(near(*anchors.last().unwrap(), DVec2::new(80., 100.)));
+	}
+
+	#[test]
+	fn graduated_outline_tapers_to_points() {
+		let subpath: Subpath<EmptyId> = Subpath::from_anchors([DVec2::new(0., 0.), DVec2::new(50., 0.), DVec2::new(100., 0.)], false);
+		let (outline, inner) = subpath.graduated_outline(&[(0., 10.), (10., 0.)], Join::Round, Cap::Round);
+		assert_eq!(inner, None);
+		assert!(outline.closed());
+
+		// The tapered ends aren't capped, so the outline meets the ends of the path
+		let anchors = outline.anchors();
+		let has_anchor_near = |point: DVec2| anchors.iter().any(|anchor| anchor.abs_diff_eq(point, 0.1));
+		assert!(anchors.iter().all(|anchor| anchor.is_finite()));
+		assert!(has_anchor_near(DVec2::new(0., 0.)));
+		assert!(has_anchor_near(DVec2::new(100., 0.)));
+		assert!(has_anchor_near(DVec2::new(50., 10.)));
+		assert!(has_anchor_near(DVec2::new(50., -10.)));
+	}
+
+	#[test]
+	fn graduated_outline_keeps_the_cap_of_the_untapered_end() {
+		let subpath: Subpath<EmptyId> = Subpath::from_anchors([DVec2::new(0., 0.), DVec2::new(50., 0.), DVec2::new(100., 0.)], false);
+		let (outline, _) = subpath.graduated_outline(&[(0., 10.), (10., 10.)], Join::Round, Cap::Round);
+
+		// Only the tapered start comes to a point, the end is rounded past the end of the path
+		let anchors = outline.anchors();
+		assert!(anchors.iter().any(|anchor| anchor.abs_diff_eq(DVec2::new(0., 0.), 0.1)));
+		assert!(anchors.iter().any(|anchor| anchor.abs_diff_eq(DVec2::new(110., 0.), 0.1)));
+		assert!(anchors.iter().all(|anchor| anchor.x > -0.1));
+	}
+
+	#[test]
+	fn graduated_outline_of_closed_subpath() {
+		let subpath: Subpath<EmptyId> = Subpath::from_anchors([DVec2::new(0., 0.), DVec2::new(100., 0.), DVec2::new(100., 100.), DVec2::new(0., 100.)], true);
+		let distances = [(5., 10.), (10., 10.), (10., 5.), (5., 5.)];
+		let (outer, inner) = subpath.graduated_outline(&distances, Join::Miter(None), Cap::Butt);
+		assert!(outer.closed());
+		assert!(inner.is_some_and(|inner| inner.closed()));
+	}
+
 	#[test]
 	fn outline_single_point_circle() {
 		let ellipse: Subpath<EmptyId> = Subpath::new_ellipse(DVec2::new(0., 0.), DVec2::new(50., 50.)).reverse();
@@ -0,0 +1,352 @@
+use crate::application_io::EditorApi;
+use crate::{Color, Node};
+
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+use glam::{DAffine2, DMat2, DVec2};
+
+/// The playback settings of a document and the moment in time currently being displayed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Timeline {
+	/// Frames per second.
+	pub frame_rate: f64,
+	/// The length of the animation in seconds.
+	pub duration: f64,
+	/// The current time in seconds.
+	pub time: f64,
+}
+
+impl Default for Timeline {
+	fn default() -> Self {
+		Self {
+			frame_rate: 30.,
+			duration: 5.,
+			time: 0.,
+		}
+	}
+}
+
+impl Hash for Timeline {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.frame_rate.to_bits().hash(state);
+		self.duration.to_bits().hash(state);
+		self.time.to_bits().hash(state);
+	}
+}
+
+impl Timeline {
+	/// The number of whole frames in the animation.
+	pub fn frame_count(&self) -> u64 {
+		(self.duration * self.frame_rate).round().max(1.) as u64
+	}
+
+	/// The index of the frame being displayed at the current time.
+	pub fn frame(&self) -> u64 {
+		// The small offset prevents the start time of a frame from being rounded down to the previous frame
+		((self.time * self.frame_rate + 1e-6).floor().max(0.) as u64).min(self.frame_count() - 1)
+	}
+
+	/// The time in seconds at which the given frame starts.
+	pub fn time_of_frame(&self, frame: u64) -> f64 {
+		frame as f64 / self.frame_rate
+	}
+
+	/// Moves the current time to the start of the given frame, clamped to the length of the animation.
+	pub fn set_frame(&mut self, frame: u64) {
+		self.time = self.time_of_frame(frame.min(self.frame_count() - 1));
+	}
+}
+
+/// How a value changes between a keyframe and the one after it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Interpolation {
+	/// Keeps the value of the keyframe until the next keyframe is reached.
+	Constant,
+	#[default]
+	Linear,
+	EaseIn,
+	EaseOut,
+	EaseInOut,
+	/// A timing curve defined by the two inner control points of a cubic bezier from (0, 0) to (1, 1), like the CSS `cubic-bezier()` function.
+	CubicBezier { x1: f64, y1: f64, x2: f64, y2: f64 },
+}
+
+impl Interpolation {
+	/// Maps the linear progress `t` (from 0 to 1) between two keyframes to the eased progress used to blend their values.
+	pub fn ease(&self, t: f64) -> f64 {
+		let t = t.clamp(0., 1.);
+		match *self {
+			Interpolation::Constant => 0.,
+			Interpolation::Linear => t,
+			Interpolation::EaseIn => cubic_bezier_timing(t, 0.42, 0., 1., 1.),
+			Interpolation::EaseOut => cubic_bezier_timing(t, 0., 0., 0.58, 1.),
+			Interpolation::EaseInOut => cubic_bezier_timing(t, 0.42, 0., 0.58, 1.),
+			Interpolation::CubicBezier { x1, y1, x2, y2 } => cubic_bezier_timing(t, x1, y1, x2, y2),
+		}
+	}
+}
+
+fn cubic_bezier_timing(t: f64, x1: f64, y1: f64, x2: f64, y2: f64) -> f64 {
+	let bezier = |s: f64, p1: f64, p2: f64| 3. * (1. - s) * (1. - s) * s * p1 + 3. * (1. - s) * s * s * p2 + s * s * s;
+	// The x coordinates are clamped so the curve is monotonic in x, which allows finding the parameter by bisection
+	let (x1, x2) = (x1.clamp(0., 1.), x2.clamp(0., 1.));
+
+	let (mut low, mut high) = (0., 1.);
+	for _ in 0..32 {
+		let middle = (low + high) / 2.;
+		if bezier(middle, x1, x2) < t {
+			low = middle;
+		} else {
+			high = middle;
+		}
+	}
+	bezier((low + high) / 2., y1, y2)
+}
+
+/// A value which can be blended with another value of the same type, allowing it to be animated with keyframes.
+pub trait Animatable: Clone {
+	/// Blends from `self` at `t = 0` to `other` at `t = 1`. Eased curves may produce `t` slightly outside of that range.
+	fn interpolate(&self, other: &Self, t: f64) -> Self;
+}
+
+impl Animatable for f64 {
+	fn interpolate(&self, other: &Self, t: f64) -> Self {
+		self + (other - self) * t
+	}
+}
+
+impl Animatable for DVec2 {
+	fn interpolate(&self, other: &Self, t: f64) -> Self {
+		self.lerp(*other, t)
+	}
+}
+
+impl Animatable for Color {
+	fn interpolate(&self, other: &Self, t: f64) -> Self {
+		let t = t as f32;
+		let channel = |from: f32, to: f32| (from + (to - from) * t).clamp(0., 1.);
+		Color::from_rgbaf32_unchecked(channel(self.r(), other.r()), channel(self.g(), other.g()), channel(self.b(), other.b()), channel(self.a(), other.a()))
+	}
+}
+
+impl Animatable for DAffine2 {
+	/// Interpolates the translation, rotation, scale, and skew separately so a rotating transform doesn't shrink halfway through like it would if the matrices were blended directly.
+	fn interpolate(&self, other: &Self, t: f64) -> Self {
+		let (from_angle, from_scale, from_skew) = decompose_matrix2(self.matrix2);
+		let (to_angle, to_scale, to_skew) = decompose_matrix2(other.matrix2);
+
+		// Rotate along the shortest way around
+		let mut angle_difference = (to_angle - from_angle) % core::f64::consts::TAU;
+		if angle_difference > core::f64::consts::PI {
+			angle_difference -= core::f64::consts::TAU;
+		} else if angle_difference < -core::f64::consts::PI {
+			angle_difference += core::f64::consts::TAU;
+		}
+
+		let angle = from_angle + angle_difference * t;
+		let scale = from_scale.lerp(to_scale, t);
+		let skew = from_skew.interpolate(&to_skew, t);
+		let matrix2 = DMat2::from_angle(angle) * DMat2::from_cols(DVec2::new(scale.x, 0.), DVec2::new(skew, scale.y));
+
+		DAffine2::from_mat2_translation(matrix2, self.translation.lerp(other.translation, t))
+	}
+}
+
+/// Splits a matrix into `rotation * [[scale.x, skew], [0, scale.y]]`, returning the rotation angle, scale, and skew.
+fn decompose_matrix2(matrix2: DMat2) -> (f64, DVec2, f64) {
+	let angle = matrix2.x_axis.y.atan2(matrix2.x_axis.x);
+	let scale_x = matrix2.x_axis.length();
+	let unrotated_y_axis = DMat2::from_angle(-angle) * matrix2.y_axis;
+	(angle, DVec2::new(scale_x, unrotated_y_axis.y), unrotated_y_axis.x)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Keyframe<T> {
+	/// The time in seconds at which the value is reached.
+	pub time: f64,
+	pub value: T,
+	/// How the value changes from this keyframe to the next one.
+	pub interpolation: Interpolation,
+}
+
+/// The keyframes of an animated value, kept sorted by time.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyframeTrack<T> {
+	keyframes: Vec<Keyframe<T>>,
+}
+
+impl<T> Default for KeyframeTrack<T> {
+	fn default() -> Self {
+		Self { keyframes: Vec::new() }
+	}
+}
+
+impl<T: Animatable> KeyframeTrack<T> {
+	/// Creates a track holding a single value from the start of the animation.
+	pub fn new(value: T) -> Self {
+		let mut track = Self::default();
+		track.insert(0., value, Interpolation::default());
+		track
+	}
+
+	pub fn keyframes(&self) -> &[Keyframe<T>] {
+		&self.keyframes
+	}
+
+	/// Adds a keyframe, replacing the value of any existing keyframe at the same time.
+	pub fn insert(&mut self, time: f64, value: T, interpolation: Interpolation) {
+		match self.keyframes.binary_search_by(|keyframe| keyframe.time.total_cmp(&time)) {
+			Ok(index) => self.keyframes[index] = Keyframe { time, value, interpolation },
+			Err(index) => self.keyframes.insert(index, Keyframe { time, value, interpolation }),
+		}
+	}
+
+	/// Removes the keyframe at the given time, returning it if there was one.
+	pub fn remove(&mut self, time: f64) -> Option<Keyframe<T>> {
+		let index = self.keyframes.binary_search_by(|keyframe| keyframe.time.total_cmp(&time)).ok()?;
+		Some(self.keyframes.remove(index))
+	}
+
+	/// Evaluates the animated value at the given time. Before the first keyframe and after the last one, the value is held constant.
+	pub fn sample(&self, time: f64) -> Option<T> {
+		let next_index = self.keyframes.partition_point(|keyframe| keyframe.time <= time);
+		let Some(previous) = next_index.checked_sub(1).map(|index| &self.keyframes[index]) else {
+			return self.keyframes.first().map(|keyframe| keyframe.value.clone());
+		};
+		let Some(next) = self.keyframes.get(next_index) else {
+			return Some(previous.value.clone());
+		};
+
+		let progress = (time - previous.time) / (next.time - previous.time);
+		Some(previous.value.interpolate(&next.value, previous.interpolation.ease(progress)))
+	}
+}
+
+impl<T> Hash for KeyframeTrack<T>
+where
+	Keyframe<T>: Hash,
+{
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.keyframes.hash(state);
+	}
+}
+
+impl Hash for Interpolation {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		core::mem::discriminant(self).hash(state);
+		if let Interpolation::CubicBezier { x1, y1, x2, y2 } = self {
+			[x1, y1, x2, y2].iter().for_each(|x| x.to_bits().hash(state));
+		}
+	}
+}
+
+impl Hash for Keyframe<f64> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.time.to_bits().hash(state);
+		self.value.to_bits().hash(state);
+		self.interpolation.hash(state);
+	}
+}
+
+impl Hash for Keyframe<DVec2> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.time.to_bits().hash(state);
+		self.value.to_array().iter().for_each(|x| x.to_bits().hash(state));
+		self.interpolation.hash(state);
+	}
+}
+
+impl Hash for Keyframe<Color> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.time.to_bits().hash(state);
+		self.value.hash(state);
+		self.interpolation.hash(state);
+	}
+}
+
+impl Hash for Keyframe<DAffine2> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.time.to_bits().hash(state);
+		self.value.to_cols_array().iter().for_each(|x| x.to_bits().hash(state));
+		self.interpolation.hash(state);
+	}
+}
+
+/// Outputs the current time of the document timeline in seconds.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeNode;
+
+impl<'a: 'input, 'input, T> Node<'input, EditorApi<'a, T>> for TimeNode {
+	type Output = f64;
+	fn eval(&'input self, editor_api: EditorApi<'a, T>) -> Self::Output {
+		editor_api.render_config.timeline.time
+	}
+}
+
+impl TimeNode {
+	pub fn new() -> Self {
+		Self
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn sample_holds_outside_of_keyframes() {
+		let mut track = KeyframeTrack::new(1.);
+		track.insert(2., 3., Interpolation::Linear);
+		assert_eq!(track.sample(-1.), Some(1.));
+		assert_eq!(track.sample(1.), Some(2.));
+		assert_eq!(track.sample(5.), Some(3.));
+		assert_eq!(KeyframeTrack::<f64>::default().sample(0.), None);
+	}
+
+	#[test]
+	fn constant_interpolation_steps() {
+		let mut track = KeyframeTrack::default();
+		track.insert(1., DVec2::ONE, Interpolation::Constant);
+		track.insert(0., DVec2::ZERO, Interpolation::Constant);
+		assert_eq!(track.keyframes()[0].time, 0.);
+		assert_eq!(track.sample(0.99), Some(DVec2::ZERO));
+		assert_eq!(track.sample(1.), Some(DVec2::ONE));
+	}
+
+	#[test]
+	fn eased_interpolation_keeps_endpoints() {
+		for interpolation in [Interpolation::EaseIn, Interpolation::EaseOut, Interpolation::EaseInOut] {
+			assert!(interpolation.ease(0.).abs() < 1e-6);
+			assert!((interpolation.ease(1.) - 1.).abs() < 1e-6);
+			assert!((interpolation.ease(0.5) - 0.5).abs() < 0.5);
+		}
+		assert!(Interpolation::EaseIn.ease(0.25) < 0.25);
+		assert!(Interpolation::EaseOut.ease(0.25) > 0.25);
+	}
+
+	#[test]
+	fn transform_rotates_along_shortest_path() {
+		let from = DAffine2::from_angle(170_f64.to_radians());
+		let to = DAffine2::from_scale_angle_translation(DVec2::splat(3.), -170_f64.to_radians(), DVec2::new(10., 0.));
+		let halfway = from.interpolate(&to, 0.5);
+		let (angle, scale, skew) = decompose_matrix2(halfway.matrix2);
+		assert!((angle.abs() - core::f64::consts::PI).abs() < 1e-9);
+		assert!((scale - DVec2::splat(2.)).length() < 1e-9);
+		assert!(skew.abs() < 1e-9);
+		assert!((halfway.translation - DVec2::new(5., 0.)).length() < 1e-9);
+	}
+
+	#[test]
+	fn timeline_frames() {
+		let mut timeline = Timeline::default();
+		assert_eq!(timeline.frame_count(), 150);
+		timeline.time = 1.01;
+		assert_eq!(timeline.frame(), 30);
+		timeline.set_frame(1000);
+		assert_eq!(timeline.frame(), 149);
+	}
+}
@@ -1,3 +1,5 @@
+use crate::animation::Timeline;
+use crate::raster::color_management::{DisplayTransform, WorkingSpace};
 use crate::raster::ImageFrame;
 use crate::text::FontCache;
 use crate::transform::{Footprint, Transform, TransformMut};
@@ -145,12 +147,15 @@ pub enum ExportFormat {
 	},
 	Jpeg,
 	Canvas,
+	/// A PDF document with a page for each artboard
+	Pdf,
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct RenderConfig {
 	pub viewport: Footprint,
 	pub export_format: ExportFormat,
+	pub timeline: Timeline,
 }
 
 pub struct EditorApi<'a, Io> {
@@ -160,6 +165,10 @@ pub struct EditorApi<'a, Io> {
 	pub node_graph_message_sender: &'a dyn NodeGraphUpdateSender,
 	pub imaginate_preferences: &'a dyn GetImaginatePreferences,
 	pub render_config: RenderConfig,
+	/// The color space of the document, which decoded images are converted into
+	pub working_space: WorkingSpace,
+	/// Converts the rendered colors from the document's working space for display, or simulates a print of them in the soft proof view mode
+	pub display_transform: Option<&'a DisplayTransform>,
 }
 
 impl<'a, Io> Clone for EditorApi<'a, Io> {
@@ -171,13 +180,15 @@ impl<'a, Io> Clone for EditorApi<'a, Io> {
 			node_graph_message_sender: self.node_graph_message_sender,
 			imaginate_preferences: self.imaginate_preferences,
 			render_config: self.render_config,
+			working_space: self.working_space,
+			display_transform: self.display_transform,
 		}
 	}
 }
 
 impl<'a, T> PartialEq for EditorApi<'a, T> {
 	fn eq(&self, other: &Self) -> bool {
-		self.image_frame == other.image_frame && self.font_cache == other.font_cache
+		self.image_frame == other.image_frame && self.font_cache == other.font_cache && self.render_config.timeline == other.render_config.timeline && self.working_space == other.working_space
 	}
 }
 
@@ -185,6 +196,8 @@ impl<'a, T> Hash for EditorApi<'a, T> {
 	fn hash<H: Hasher>(&self, state: &mut H) {
 		self.image_frame.hash(state);
 		self.font_cache.hash(state);
+		self.render_config.timeline.hash(state);
+		self.working_space.hash(state);
 	}
 }
 
@@ -10,8 +10,12 @@ use core::future::Future;
 use core::ops::{Deref, DerefMut};
 use glam::{DAffine2, DVec2, IVec2, UVec2};
 
+pub mod layer_effects;
 pub mod renderer;
 
+use layer_effects::{CompositeOperator, FilterInput, FilterPrimitive, LayerEffect};
+use renderer::GraphicElementRendered;
+
 /// A list of [`GraphicElement`]s
 #[derive(Clone, Debug, Hash, PartialEq, DynAny, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -28,7 +32,7 @@ pub enum GraphicElementData {
 	Artboard(Artboard),
 }
 
-/// A named [`GraphicElementData`] with a blend mode, opacity, as well as visibility, locked, and collapsed states.
+/// A named [`GraphicElementData`] with a blend mode, opacity, mask, effects, as well as visibility, locked, and collapsed states.
 #[derive(Clone, Debug, PartialEq, DynAny)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GraphicElement {
@@ -40,6 +44,15 @@ pub struct GraphicElement {
 	pub locked: bool,
 	pub collapsed: bool,
 	pub graphic_element_data: GraphicElementData,
+	/// Hides the parts of the element outside of the shape of a vector mask, or where a raster mask is dark or transparent
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub mask: Option<Box<GraphicElementData>>,
+	/// Clips the element to the nearest element below it which isn't clipped itself, forming a clipping group
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub clip: bool,
+	/// Shadows, glows and other effects drawn from the shape of the masked content, in order from the bottom up
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub effects: Vec<LayerEffect>,
 }
 
 impl Default for GraphicElement {
@@ -52,6 +65,9 @@ impl Default for GraphicElement {
 			locked: false,
 			collapsed: false,
 			graphic_element_data: GraphicElementData::VectorShape(Box::new(VectorData::empty())),
+			mask: None,
+			clip: false,
+			effects: Vec::new(),
 		}
 	}
 }
@@ -80,7 +96,7 @@ impl Artboard {
 	}
 }
 
-pub struct ConstructLayerNode<GraphicElementData, Name, BlendMode, Opacity, Visible, Locked, Collapsed, Stack> {
+pub struct ConstructLayerNode<GraphicElementData, Name, BlendMode, Opacity, Visible, Locked, Collapsed, Stack, Mask, Clip, Effects> {
 	graphic_element_data: GraphicElementData,
 	name: Name,
 	blend_mode: BlendMode,
@@ -89,10 +105,13 @@ pub struct ConstructLayerNode<GraphicElementData, Name, BlendMode, Opacity, Visi
 	locked: Locked,
 	collapsed: Collapsed,
 	stack: Stack,
+	mask: Mask,
+	clip: Clip,
+	effects: Effects,
 }
 
 #[node_fn(ConstructLayerNode)]
-async fn construct_layer<Data: Into<GraphicElementData>, Fut1: Future<Output = Data>, Fut2: Future<Output = GraphicGroup>>(
+async fn construct_layer<Data: Into<GraphicElementData>, Fut1: Future<Output = Data>, Fut2: Future<Output = GraphicGroup>, Fut3: Future<Output = GraphicElementData>>(
 	footprint: crate::transform::Footprint,
 	graphic_element_data: impl Node<crate::transform::Footprint, Output = Fut1>,
 	name: String,
@@ -102,9 +121,17 @@ async fn construct_layer<Data: Into<GraphicElementData>, Fut1: Future<Output = D
 	locked: bool,
 	collapsed: bool,
 	mut stack: impl Node<crate::transform::Footprint, Output = Fut2>,
+	mask: impl Node<crate::transform::Footprint, Output = Fut3>,
+	clip: bool,
+	effects: Vec<LayerEffect>,
 ) -> GraphicGroup {
 	let graphic_element_data = self.graphic_element_data.eval(footprint).await;
 	let mut stack = self.stack.eval(footprint).await;
+	// An empty group is what the mask input holds when nothing is connected to it
+	let mask = match self.mask.eval(footprint).await {
+		GraphicElementData::GraphicGroup(group) if group.is_empty() => None,
+		mask => Some(Box::new(mask)),
+	};
 	stack.push(GraphicElement {
 		name,
 		blend_mode,
@@ -113,6 +140,9 @@ async fn construct_layer<Data: Into<GraphicElementData>, Fut1: Future<Output = D
 		locked,
 		collapsed,
 		graphic_element_data: graphic_element_data.into(),
+		mask,
+		clip,
+		effects,
 	});
 	stack
 }
@@ -163,6 +193,26 @@ impl From<Artboard> for GraphicElementData {
 	}
 }
 
+impl GraphicElementData {
+	/// Applies a transform on top of the transforms of the data, including those of every element and mask within a group.
+	/// Artboards stay in place, since their bounds are aligned to the document's pixel grid, and text has no transform.
+	pub fn apply_transform(&mut self, transform: DAffine2) {
+		match self {
+			GraphicElementData::VectorShape(vector_data) => vector_data.transform = transform * vector_data.transform,
+			GraphicElementData::ImageFrame(image_frame) => image_frame.transform = transform * image_frame.transform,
+			GraphicElementData::GraphicGroup(graphic_group) => {
+				for element in graphic_group.iter_mut() {
+					element.graphic_element_data.apply_transform(transform);
+					if let Some(mask) = &mut element.mask {
+						mask.apply_transform(transform);
+					}
+				}
+			}
+			GraphicElementData::Text(_) | GraphicElementData::Artboard(_) => {}
+		}
+	}
+}
+
 impl Deref for GraphicGroup {
 	type Target = Vec<GraphicElement>;
 	fn deref(&self) -> &Self::Target {
@@ -200,6 +250,15 @@ where
 impl GraphicGroup {
 	pub const EMPTY: Self = Self(Vec::new());
 
+	/// The index of the element which the element at `index` is clipped to, being the nearest element below it that isn't clipped itself.
+	/// An element set to clip with nothing below it to clip to is drawn unclipped.
+	pub fn clip_base(&self, index: usize) -> Option<usize> {
+		if !self.0[index].clip {
+			return None;
+		}
+		self.0[..index].iter().rposition(|element| !element.clip)
+	}
+
 	pub fn to_usvg_tree(&self, resolution: UVec2, viewbox: [DVec2; 2]) -> usvg::Tree {
 		let root_node = usvg::Node::new(usvg::NodeKind::Group(usvg::Group::default()));
 		let tree = usvg::Tree {
@@ -211,47 +270,279 @@ impl GraphicGroup {
 			root: root_node.clone(),
 		};
 
-		for element in self.0.iter() {
-			root_node.append(element.to_usvg_node());
-		}
+		self.append_usvg_nodes(&root_node);
 		tree
 	}
+
+	/// Appends the visible elements to a usvg node, with their masks and clipping groups
+	fn append_usvg_nodes(&self, parent: &usvg::Node) {
+		for (index, element) in self.0.iter().enumerate().filter(|(_, element)| element.visible) {
+			let node = match self.clip_base(index).map(|base| &self.0[base]) {
+				// Hiding the element at the base of a clipping group hides the whole group
+				Some(base) if !base.visible => continue,
+				Some(base) => {
+					let content = element.to_usvg_node_with_effects();
+					let Some(rect) = usvg_rect(base.graphic_element_data.bounding_box(DAffine2::IDENTITY)) else {
+						continue;
+					};
+					let mask = usvg::Mask {
+						id: String::new(),
+						units: usvg::Units::UserSpaceOnUse,
+						content_units: usvg::Units::UserSpaceOnUse,
+						rect,
+						kind: usvg::MaskType::Alpha,
+						mask: None,
+						root: wrap_usvg_node(base.to_masked_usvg_node(), usvg::Group::default()),
+					};
+					wrap_usvg_node(
+						content,
+						usvg::Group {
+							mask: Some(std::rc::Rc::new(mask)),
+							..Default::default()
+						},
+					)
+				}
+				None => element.to_usvg_node_with_effects(),
+			};
+			parent.append(node);
+		}
+	}
+}
+
+fn to_usvg_transform(transform: DAffine2) -> usvg::Transform {
+	let cols = transform.to_cols_array();
+	usvg::Transform::from_row(cols[0] as f32, cols[1] as f32, cols[2] as f32, cols[3] as f32, cols[4] as f32, cols[5] as f32)
+}
+
+fn usvg_rect(bounds: Option<[DVec2; 2]>) -> Option<usvg::NonZeroRect> {
+	let [min, max] = bounds?;
+	usvg::NonZeroRect::from_ltrb(min.x as f32, min.y as f32, max.x as f32, max.y as f32)
+}
+
+/// Places the node in a new group node with the given properties, such as a mask or clip path
+fn wrap_usvg_node(node: usvg::Node, group: usvg::Group) -> usvg::Node {
+	let group = usvg::Node::new(usvg::NodeKind::Group(group));
+	group.append(node);
+	group
+}
+
+/// The path of the vector data, in the space of its parent
+fn usvg_path(vector_data: &VectorData) -> Option<usvg::Path> {
+	use usvg::tiny_skia_path::PathBuilder;
+	let mut builder = PathBuilder::new();
+
+	for subpath in vector_data.subpaths.iter().filter(|subpath| !subpath.is_empty()) {
+		let start = subpath[0].anchor;
+		builder.move_to(start.x as f32, start.y as f32);
+		for bezier in subpath.iter() {
+			let end = bezier.end;
+			match bezier.handles {
+				BezierHandles::Linear => builder.line_to(end.x as f32, end.y as f32),
+				BezierHandles::Quadratic { handle } => builder.quad_to(handle.x as f32, handle.y as f32, end.x as f32, end.y as f32),
+				BezierHandles::Cubic { handle_start, handle_end } => {
+					builder.cubic_to(handle_start.x as f32, handle_start.y as f32, handle_end.x as f32, handle_end.y as f32, end.x as f32, end.y as f32)
+				}
+			}
+		}
+		if subpath.closed {
+			builder.close()
+		}
+	}
+	let mut path = usvg::Path::new(builder.finish()?.into());
+	path.transform = to_usvg_transform(vector_data.transform);
+	Some(path)
+}
+
+fn usvg_color(color: Color) -> usvg::Color {
+	usvg::Color::new_rgb((color.r() * 255.) as u8, (color.g() * 255.) as u8, (color.b() * 255.) as u8)
+}
+
+fn usvg_blend_mode(blend_mode: BlendMode) -> usvg::BlendMode {
+	match blend_mode {
+		BlendMode::Multiply => usvg::BlendMode::Multiply,
+		BlendMode::Darken => usvg::BlendMode::Darken,
+		BlendMode::ColorBurn => usvg::BlendMode::ColorBurn,
+		BlendMode::Screen => usvg::BlendMode::Screen,
+		BlendMode::Lighten => usvg::BlendMode::Lighten,
+		BlendMode::ColorDodge => usvg::BlendMode::ColorDodge,
+		BlendMode::Overlay => usvg::BlendMode::Overlay,
+		BlendMode::SoftLight => usvg::BlendMode::SoftLight,
+		BlendMode::HardLight => usvg::BlendMode::HardLight,
+		BlendMode::Difference => usvg::BlendMode::Difference,
+		BlendMode::Exclusion => usvg::BlendMode::Exclusion,
+		BlendMode::Hue => usvg::BlendMode::Hue,
+		BlendMode::Saturation => usvg::BlendMode::Saturation,
+		BlendMode::Color => usvg::BlendMode::Color,
+		BlendMode::Luminosity => usvg::BlendMode::Luminosity,
+		// Like in the SVG render, blend modes which SVG doesn't have are drawn as normal
+		_ => usvg::BlendMode::Normal,
+	}
+}
+
+/// The usvg filter primitive for a step of a layer effects filter, whose result is named after its index
+fn usvg_filter_primitive(index: usize, primitive: &FilterPrimitive) -> usvg::filter::Primitive {
+	use usvg::filter::{self, Input, Kind};
+
+	let input = |input: &FilterInput| match input {
+		FilterInput::SourceGraphic => Input::SourceGraphic,
+		FilterInput::SourceAlpha => Input::SourceAlpha,
+		FilterInput::Result(index) => Input::Reference(format!("effect-{index}")),
+	};
+	let positive = |value: f64| usvg::PositiveF32::new(value as f32).unwrap_or(usvg::PositiveF32::ZERO);
+
+	let mut subregion = None;
+	let kind = match primitive {
+		FilterPrimitive::Flood { color, opacity } => Kind::Flood(filter::Flood {
+			color: usvg_color(*color),
+			opacity: usvg::Opacity::new_clamped(color.a() * *opacity as f32),
+		}),
+		FilterPrimitive::GaussianBlur { input: blurred, std_deviation } => Kind::GaussianBlur(filter::GaussianBlur {
+			input: input(blurred),
+			std_dev_x: positive(*std_deviation),
+			std_dev_y: positive(*std_deviation),
+		}),
+		FilterPrimitive::Offset { input: moved, offset } => Kind::Offset(filter::Offset {
+			input: input(moved),
+			dx: offset.x as f32,
+			dy: offset.y as f32,
+		}),
+		FilterPrimitive::Dilate { input: grown, radius } => Kind::Morphology(filter::Morphology {
+			input: input(grown),
+			operator: filter::MorphologyOperator::Dilate,
+			radius_x: positive(*radius),
+			radius_y: positive(*radius),
+		}),
+		FilterPrimitive::InvertAlpha { input: inverted } => Kind::ComponentTransfer(filter::ComponentTransfer {
+			input: input(inverted),
+			func_r: filter::TransferFunction::Identity,
+			func_g: filter::TransferFunction::Identity,
+			func_b: filter::TransferFunction::Identity,
+			func_a: filter::TransferFunction::Table(vec![1., 0.]),
+		}),
+		FilterPrimitive::Composite { input: top, input2: bottom, operator } => Kind::Composite(filter::Composite {
+			input1: input(top),
+			input2: input(bottom),
+			operator: match operator {
+				CompositeOperator::Over => filter::CompositeOperator::Over,
+				CompositeOperator::In => filter::CompositeOperator::In,
+			},
+		}),
+		FilterPrimitive::Blend { input: top, input2: bottom, mode } => Kind::Blend(filter::Blend {
+			input1: input(top),
+			input2: input(bottom),
+			mode: usvg_blend_mode(*mode),
+		}),
+		FilterPrimitive::Image { image, bounds } => {
+			subregion = Some(*bounds);
+			Kind::Image(filter::Image {
+				aspect: usvg::AspectRatio {
+					align: usvg::Align::None,
+					slice: false,
+					defer: false,
+				},
+				rendering_mode: usvg::ImageRendering::OptimizeQuality,
+				data: filter::ImageKind::Image(usvg::ImageKind::PNG(image.to_png().into())),
+			})
+		}
+	};
+	filter::Primitive {
+		x: subregion.map(|[min, _]| min.x as f32),
+		y: subregion.map(|[min, _]| min.y as f32),
+		width: subregion.map(|[min, max]| positive(max.x - min.x)),
+		height: subregion.map(|[min, max]| positive(max.y - min.y)),
+		color_interpolation: filter::ColorInterpolation::SRGB,
+		result: format!("effect-{index}"),
+		kind,
+	}
 }
 
 impl GraphicElement {
-	fn to_usvg_node(&self) -> usvg::Node {
-		fn to_transform(transform: DAffine2) -> usvg::Transform {
-			let cols = transform.to_cols_array();
-			usvg::Transform::from_row(cols[0] as f32, cols[1] as f32, cols[2] as f32, cols[3] as f32, cols[4] as f32, cols[5] as f32)
+	/// The element's masked content drawn with its enabled effects, using a filter whose blurs resvg draws on the CPU
+	fn to_usvg_node_with_effects(&self) -> usvg::Node {
+		let content = self.to_masked_usvg_node();
+		let effects: Vec<_> = self.effects.iter().filter(|effect| effect.enabled).cloned().collect();
+		if effects.is_empty() {
+			return content;
+		}
+		let Some(bounds) = self.graphic_element_data.bounding_box(DAffine2::IDENTITY) else {
+			return content;
+		};
+		let primitives = layer_effects::filter_primitives(&effects, 1., bounds);
+		let extent = effects.iter().map(LayerEffect::extent).fold(0., f64::max) + 1.;
+		let Some(rect) = usvg_rect(Some([bounds[0] - extent, bounds[1] + extent])) else {
+			return content;
+		};
+		if primitives.is_empty() {
+			return content;
 		}
 
-		match &self.graphic_element_data {
+		let filter = usvg::filter::Filter {
+			id: String::new(),
+			units: usvg::Units::UserSpaceOnUse,
+			primitive_units: usvg::Units::UserSpaceOnUse,
+			rect,
+			primitives: primitives.iter().enumerate().map(|(index, primitive)| usvg_filter_primitive(index, primitive)).collect(),
+		};
+		wrap_usvg_node(
+			content,
+			usvg::Group {
+				filters: vec![std::rc::Rc::new(filter)],
+				..Default::default()
+			},
+		)
+	}
+
+	/// The element's content, clipped by its vector mask or masked by the luminance of its raster mask
+	fn to_masked_usvg_node(&self) -> usvg::Node {
+		let content = self.to_usvg_node();
+		let Some(mask) = &self.mask else { return content };
+
+		let group = match &**mask {
 			GraphicElementData::VectorShape(vector_data) => {
-				use usvg::tiny_skia_path::PathBuilder;
-				let mut builder = PathBuilder::new();
-
-				let transform = to_transform(vector_data.transform);
-				for subpath in vector_data.subpaths.iter() {
-					let start = vector_data.transform.transform_point2(subpath[0].anchor);
-					builder.move_to(start.x as f32, start.y as f32);
-					for bezier in subpath.iter() {
-						bezier.apply_transformation(|pos| vector_data.transform.transform_point2(pos));
-						let end = bezier.end;
-						match bezier.handles {
-							BezierHandles::Linear => builder.line_to(end.x as f32, end.y as f32),
-							BezierHandles::Quadratic { handle } => builder.quad_to(handle.x as f32, handle.y as f32, end.x as f32, end.y as f32),
-							BezierHandles::Cubic { handle_start, handle_end } => {
-								builder.cubic_to(handle_start.x as f32, handle_start.y as f32, handle_end.x as f32, handle_end.y as f32, end.x as f32, end.y as f32)
-							}
-						}
-					}
-					if subpath.closed {
-						builder.close()
-					}
+				let root = usvg::Node::new(usvg::NodeKind::Group(usvg::Group::default()));
+				if let Some(mut path) = usvg_path(vector_data) {
+					path.fill = Some(usvg::Fill::default());
+					root.append(usvg::Node::new(usvg::NodeKind::Path(path)));
 				}
-				let path = builder.finish().unwrap();
-				let mut path = usvg::Path::new(path.into());
-				path.transform = transform;
+				let clip_path = usvg::ClipPath { root, ..Default::default() };
+				usvg::Group {
+					clip_path: Some(std::rc::Rc::new(clip_path)),
+					..Default::default()
+				}
+			}
+			mask => {
+				// Nothing of the element is left where the mask has no content
+				let Some(rect) = usvg_rect(mask.bounding_box(DAffine2::IDENTITY)) else {
+					return usvg::Node::new(usvg::NodeKind::Group(usvg::Group::default()));
+				};
+				let mask_element = GraphicElement {
+					graphic_element_data: mask.clone(),
+					..Default::default()
+				};
+				let mask = usvg::Mask {
+					id: String::new(),
+					units: usvg::Units::UserSpaceOnUse,
+					content_units: usvg::Units::UserSpaceOnUse,
+					rect,
+					kind: usvg::MaskType::Luminance,
+					mask: None,
+					root: wrap_usvg_node(mask_element.to_usvg_node(), usvg::Group::default()),
+				};
+				usvg::Group {
+					mask: Some(std::rc::Rc::new(mask)),
+					..Default::default()
+				}
+			}
+		};
+		wrap_usvg_node(content, group)
+	}
+
+	fn to_usvg_node(&self) -> usvg::Node {
+		match &self.graphic_element_data {
+			GraphicElementData::VectorShape(vector_data) => {
+				let Some(mut path) = usvg_path(vector_data) else {
+					return usvg::Node::new(usvg::NodeKind::Group(usvg::Group::default()));
+				};
 				// TODO: use proper style
 				path.fill = None;
 				path.stroke = Some(usvg::Stroke::default());
@@ -264,7 +555,7 @@ impl GraphicElement {
 				let png = image_frame.image.to_png();
 				usvg::Node::new(usvg::NodeKind::Image(usvg::Image {
 					id: String::new(),
-					transform: to_transform(image_frame.transform),
+					transform: to_usvg_transform(image_frame.transform),
 					visibility: usvg::Visibility::Visible,
 					view_box: usvg::ViewBox {
 						rect: usvg::NonZeroRect::from_xywh(0., 0., 1., 1.).unwrap(),
@@ -292,10 +583,7 @@ impl GraphicElement {
 			})),
 			GraphicElementData::GraphicGroup(group) => {
 				let group_element = usvg::Node::new(usvg::NodeKind::Group(usvg::Group::default()));
-
-				for element in group.0.iter() {
-					group_element.append(element.to_usvg_node());
-				}
+				group.append_usvg_nodes(&group_element);
 				group_element
 			}
 			// TODO
@@ -313,5 +601,8 @@ impl core::hash::Hash for GraphicElement {
 		self.locked.hash(state);
 		self.collapsed.hash(state);
 		self.graphic_element_data.hash(state);
+		self.mask.hash(state);
+		self.clip.hash(state);
+		self.effects.hash(state);
 	}
 }
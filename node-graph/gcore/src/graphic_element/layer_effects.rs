@@ -0,0 +1,467 @@
+use crate::raster::{BlendMode, Image};
+use crate::Color;
+
+use dyn_any::{DynAny, StaticType};
+use glam::DVec2;
+
+use core::hash::{Hash, Hasher};
+
+/// The kinds of effect which can be drawn along with the content of a layer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, DynAny)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LayerEffectKind {
+	#[default]
+	DropShadow,
+	InnerShadow,
+	OuterGlow,
+	InnerGlow,
+	Bevel,
+	ColorOverlay,
+	GradientOverlay,
+	Stroke,
+}
+
+impl LayerEffectKind {
+	pub fn list() -> [LayerEffectKind; 8] {
+		[
+			LayerEffectKind::DropShadow,
+			LayerEffectKind::InnerShadow,
+			LayerEffectKind::OuterGlow,
+			LayerEffectKind::InnerGlow,
+			LayerEffectKind::Bevel,
+			LayerEffectKind::ColorOverlay,
+			LayerEffectKind::GradientOverlay,
+			LayerEffectKind::Stroke,
+		]
+	}
+
+	/// Effects which are drawn underneath the content of the layer rather than over it.
+	pub fn is_behind(&self) -> bool {
+		matches!(self, LayerEffectKind::DropShadow | LayerEffectKind::OuterGlow | LayerEffectKind::Stroke)
+	}
+
+	pub fn uses_offset(&self) -> bool {
+		matches!(self, LayerEffectKind::DropShadow | LayerEffectKind::InnerShadow | LayerEffectKind::Bevel)
+	}
+
+	pub fn uses_blur(&self) -> bool {
+		!matches!(self, LayerEffectKind::ColorOverlay | LayerEffectKind::GradientOverlay)
+	}
+
+	pub fn uses_spread(&self) -> bool {
+		matches!(
+			self,
+			LayerEffectKind::DropShadow | LayerEffectKind::InnerShadow | LayerEffectKind::OuterGlow | LayerEffectKind::InnerGlow | LayerEffectKind::Stroke
+		)
+	}
+
+	pub fn uses_secondary_color(&self) -> bool {
+		matches!(self, LayerEffectKind::Bevel | LayerEffectKind::GradientOverlay)
+	}
+}
+
+impl core::fmt::Display for LayerEffectKind {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			LayerEffectKind::DropShadow => write!(f, "Drop Shadow"),
+			LayerEffectKind::InnerShadow => write!(f, "Inner Shadow"),
+			LayerEffectKind::OuterGlow => write!(f, "Outer Glow"),
+			LayerEffectKind::InnerGlow => write!(f, "Inner Glow"),
+			LayerEffectKind::Bevel => write!(f, "Bevel"),
+			LayerEffectKind::ColorOverlay => write!(f, "Color Overlay"),
+			LayerEffectKind::GradientOverlay => write!(f, "Gradient Overlay"),
+			LayerEffectKind::Stroke => write!(f, "Stroke"),
+		}
+	}
+}
+
+/// An effect drawn from the shape of a layer's content, such as a shadow or a glow.
+/// Its distances are in the space of the layer's parent, so they aren't stretched or rotated by the layer's own transform.
+#[derive(Clone, Debug, PartialEq, DynAny)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LayerEffect {
+	pub kind: LayerEffectKind,
+	pub enabled: bool,
+	/// The color of the effect, which is the highlight of a bevel and the start of a gradient overlay.
+	pub color: Color,
+	/// The shadow of a bevel and the end of a gradient overlay.
+	pub secondary_color: Color,
+	/// How the effect is blended with the layer's content and the effects below it.
+	pub blend_mode: BlendMode,
+	/// In range 0..=100
+	pub opacity: f64,
+	/// How far a shadow is moved from the content, or the highlight of a bevel, with its shadow moved the opposite way.
+	pub offset: DVec2,
+	/// The radius over which the effect is softened.
+	pub blur: f64,
+	/// How far the shape of the content is grown before it is blurred, which is the width of a stroke.
+	pub spread: f64,
+	/// The direction of a gradient overlay, in degrees.
+	pub angle: f64,
+}
+
+impl LayerEffect {
+	pub fn new(kind: LayerEffectKind) -> Self {
+		let mut effect = Self {
+			kind,
+			enabled: true,
+			color: Color::BLACK,
+			secondary_color: Color::WHITE,
+			blend_mode: BlendMode::Normal,
+			opacity: 75.,
+			offset: DVec2::ZERO,
+			blur: 10.,
+			spread: 0.,
+			angle: 90.,
+		};
+		match kind {
+			LayerEffectKind::DropShadow | LayerEffectKind::InnerShadow => {
+				effect.blend_mode = BlendMode::Multiply;
+				effect.offset = DVec2::splat(5.);
+			}
+			LayerEffectKind::OuterGlow | LayerEffectKind::InnerGlow => {
+				effect.color = Color::from_rgbf32_unchecked(1., 1., 0.75);
+				effect.blend_mode = BlendMode::Screen;
+			}
+			LayerEffectKind::Bevel => {
+				effect.color = Color::WHITE;
+				effect.secondary_color = Color::BLACK;
+				effect.offset = DVec2::splat(3.);
+				effect.blur = 5.;
+			}
+			LayerEffectKind::ColorOverlay => effect.opacity = 100.,
+			LayerEffectKind::GradientOverlay => {
+				effect.color = Color::WHITE;
+				effect.secondary_color = Color::BLACK;
+				effect.opacity = 100.;
+			}
+			LayerEffectKind::Stroke => {
+				effect.opacity = 100.;
+				effect.blur = 0.;
+				effect.spread = 3.;
+			}
+		}
+		effect
+	}
+
+	/// How far outside of the content the effect may draw.
+	pub fn extent(&self) -> f64 {
+		if !self.enabled || !self.kind.is_behind() {
+			return 0.;
+		}
+		self.offset.length() + self.spread.max(0.) + self.blur.max(0.) * 1.5
+	}
+
+	pub fn map_colors(&self, map: impl Fn(Color) -> Color) -> Self {
+		Self {
+			color: map(self.color),
+			secondary_color: map(self.secondary_color),
+			..self.clone()
+		}
+	}
+}
+
+impl Hash for LayerEffect {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.kind.hash(state);
+		self.enabled.hash(state);
+		self.color.hash(state);
+		self.secondary_color.hash(state);
+		self.blend_mode.hash(state);
+		self.opacity.to_bits().hash(state);
+		self.offset.x.to_bits().hash(state);
+		self.offset.y.to_bits().hash(state);
+		self.blur.to_bits().hash(state);
+		self.spread.to_bits().hash(state);
+		self.angle.to_bits().hash(state);
+	}
+}
+
+/// The image (or the alpha of the image) a filter primitive reads from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterInput {
+	SourceGraphic,
+	SourceAlpha,
+	/// The result of the primitive at this index.
+	Result(usize),
+}
+
+/// The ways two images are combined by [`FilterPrimitive::Composite`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompositeOperator {
+	Over,
+	In,
+}
+
+/// A step of the filter which draws a layer's effects, matching the SVG filter primitive of the same name.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FilterPrimitive {
+	Flood {
+		color: Color,
+		opacity: f64,
+	},
+	GaussianBlur {
+		input: FilterInput,
+		std_deviation: f64,
+	},
+	Offset {
+		input: FilterInput,
+		offset: DVec2,
+	},
+	/// Grows the input by the radius.
+	Dilate {
+		input: FilterInput,
+		radius: f64,
+	},
+	/// Replaces the alpha of the input by its inverse, so its inside becomes transparent and its outside becomes opaque.
+	InvertAlpha {
+		input: FilterInput,
+	},
+	Composite {
+		input: FilterInput,
+		input2: FilterInput,
+		operator: CompositeOperator,
+	},
+	/// Blends the input over `input2`.
+	Blend {
+		input: FilterInput,
+		input2: FilterInput,
+		mode: BlendMode,
+	},
+	/// An image stretched over the bounds, as pixels in the linear color space with premultiplied alpha.
+	Image {
+		image: Image<Color>,
+		bounds: [DVec2; 2],
+	},
+}
+
+/// The steps of a filter drawing the effects along with the content, where the result of the last step is the filtered content.
+/// The effects are drawn in order, so later effects are drawn over earlier ones, but all effects drawn behind the content stay behind it.
+/// The distances of the effects are multiplied by `scale`, and a gradient overlay covers the `bounds` of the content.
+pub fn filter_primitives(effects: &[LayerEffect], scale: f64, bounds: [DVec2; 2]) -> Vec<FilterPrimitive> {
+	let mut primitives = Vec::new();
+	let mut push = |primitive: FilterPrimitive| {
+		primitives.push(primitive);
+		FilterInput::Result(primitives.len() - 1)
+	};
+
+	let enabled = || effects.iter().filter(|effect| effect.enabled && effect.opacity > 0.);
+
+	let mut behind: Option<FilterInput> = None;
+	for effect in enabled().filter(|effect| effect.kind.is_behind()) {
+		for (layer, mode) in effect_layers(effect, scale, bounds, &mut push) {
+			behind = Some(match behind {
+				Some(below) => push(FilterPrimitive::Blend { input: layer, input2: below, mode }),
+				None => layer,
+			});
+		}
+	}
+
+	let mut result = match behind {
+		Some(behind) => push(FilterPrimitive::Composite {
+			input: FilterInput::SourceGraphic,
+			input2: behind,
+			operator: CompositeOperator::Over,
+		}),
+		None => FilterInput::SourceGraphic,
+	};
+	for effect in enabled().filter(|effect| !effect.kind.is_behind()) {
+		for (layer, mode) in effect_layers(effect, scale, bounds, &mut push) {
+			result = push(FilterPrimitive::Blend { input: layer, input2: result, mode });
+		}
+	}
+
+	primitives
+}
+
+/// Adds the primitives drawing an effect, returning the drawn layers and the blend modes to draw them with.
+fn effect_layers(effect: &LayerEffect, scale: f64, bounds: [DVec2; 2], push: &mut impl FnMut(FilterPrimitive) -> FilterInput) -> Vec<(FilterInput, BlendMode)> {
+	let mode = effect.blend_mode;
+	let opacity = effect.opacity / 100.;
+
+	match effect.kind {
+		LayerEffectKind::DropShadow => {
+			let shape = effect_shape(effect, scale, false, effect.offset, push);
+			vec![(fill_shape(shape, effect.color, opacity, false, push), mode)]
+		}
+		LayerEffectKind::OuterGlow | LayerEffectKind::Stroke => {
+			let shape = effect_shape(effect, scale, false, DVec2::ZERO, push);
+			vec![(fill_shape(shape, effect.color, opacity, false, push), mode)]
+		}
+		LayerEffectKind::InnerShadow => {
+			let shape = effect_shape(effect, scale, true, effect.offset, push);
+			vec![(fill_shape(shape, effect.color, opacity, true, push), mode)]
+		}
+		LayerEffectKind::InnerGlow => {
+			let shape = effect_shape(effect, scale, true, DVec2::ZERO, push);
+			vec![(fill_shape(shape, effect.color, opacity, true, push), mode)]
+		}
+		// The highlight and the shadow are inner shadows moved in opposite directions
+		LayerEffectKind::Bevel => {
+			let highlight_shape = effect_shape(effect, scale, true, effect.offset, push);
+			let highlight = fill_shape(highlight_shape, effect.color, opacity, true, push);
+			let shadow_shape = effect_shape(effect, scale, true, -effect.offset, push);
+			let shadow = fill_shape(shadow_shape, effect.secondary_color, opacity, true, push);
+			vec![(highlight, mode), (shadow, mode)]
+		}
+		LayerEffectKind::ColorOverlay => vec![(fill_shape(FilterInput::SourceAlpha, effect.color, opacity, false, push), mode)],
+		LayerEffectKind::GradientOverlay => {
+			let image = push(FilterPrimitive::Image {
+				image: gradient_image(effect.color, effect.secondary_color, effect.angle, opacity, bounds),
+				bounds,
+			});
+			let layer = push(FilterPrimitive::Composite {
+				input: image,
+				input2: FilterInput::SourceAlpha,
+				operator: CompositeOperator::In,
+			});
+			vec![(layer, mode)]
+		}
+	}
+}
+
+/// The shape of the content, or of what's outside of it, grown, softened and moved by the effect.
+fn effect_shape(effect: &LayerEffect, scale: f64, outside: bool, offset: DVec2, push: &mut impl FnMut(FilterPrimitive) -> FilterInput) -> FilterInput {
+	let mut shape = FilterInput::SourceAlpha;
+	if outside {
+		shape = push(FilterPrimitive::InvertAlpha { input: shape });
+	}
+	if effect.kind.uses_spread() && effect.spread > 0. {
+		shape = push(FilterPrimitive::Dilate {
+			input: shape,
+			radius: effect.spread * scale,
+		});
+	}
+	if effect.blur > 0. {
+		shape = push(FilterPrimitive::GaussianBlur {
+			input: shape,
+			std_deviation: effect.blur * scale / 2.,
+		});
+	}
+	if offset != DVec2::ZERO {
+		shape = push(FilterPrimitive::Offset { input: shape, offset: offset * scale });
+	}
+	shape
+}
+
+/// Fills the shape with the color, keeping only the part inside of the content if `inside` is set.
+fn fill_shape(shape: FilterInput, color: Color, opacity: f64, inside: bool, push: &mut impl FnMut(FilterPrimitive) -> FilterInput) -> FilterInput {
+	let flood = push(FilterPrimitive::Flood { color, opacity });
+	let layer = push(FilterPrimitive::Composite {
+		input: flood,
+		input2: shape,
+		operator: CompositeOperator::In,
+	});
+	if !inside {
+		return layer;
+	}
+	push(FilterPrimitive::Composite {
+		input: layer,
+		input2: FilterInput::SourceAlpha,
+		operator: CompositeOperator::In,
+	})
+}
+
+/// The resolution of the image a gradient overlay is drawn with, which is smoothly stretched over the content.
+const GRADIENT_RESOLUTION: u32 = 64;
+
+/// A linear gradient across the bounds in the direction of the angle, running from one corner of the bounds to the opposite one.
+fn gradient_image(start: Color, end: Color, angle: f64, opacity: f64, bounds: [DVec2; 2]) -> Image<Color> {
+	let size = (bounds[1] - bounds[0]).max(DVec2::splat(f64::EPSILON));
+	let direction = DVec2::from_angle(angle.to_radians());
+	let half_length = (size / 2.).dot(direction.abs()).max(f64::EPSILON);
+
+	let data = (0..GRADIENT_RESOLUTION)
+		.flat_map(|y| (0..GRADIENT_RESOLUTION).map(move |x| (DVec2::new(x as f64, y as f64) + 0.5) / GRADIENT_RESOLUTION as f64))
+		.map(|position| {
+			let factor = ((position - 0.5) * size).dot(direction) / half_length / 2. + 0.5;
+			let color = start.lerp(end, factor.clamp(0., 1.) as f32).to_linear_srgb();
+			color.with_alpha(1.).to_associated_alpha(color.a() * opacity as f32)
+		})
+		.collect();
+	Image {
+		width: GRADIENT_RESOLUTION,
+		height: GRADIENT_RESOLUTION,
+		data,
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	const BOUNDS: [DVec2; 2] = [DVec2::ZERO, DVec2::splat(10.)];
+
+	#[test]
+	fn no_effects() {
+		assert!(filter_primitives(&[], 1., BOUNDS).is_empty());
+
+		let mut disabled = LayerEffect::new(LayerEffectKind::DropShadow);
+		disabled.enabled = false;
+		assert!(filter_primitives(&[disabled], 1., BOUNDS).is_empty());
+	}
+
+	#[test]
+	fn drop_shadow_behind_content() {
+		let shadow = LayerEffect::new(LayerEffectKind::DropShadow);
+		let primitives = filter_primitives(std::slice::from_ref(&shadow), 2., BOUNDS);
+
+		assert_eq!(
+			primitives[..3],
+			[
+				FilterPrimitive::GaussianBlur {
+					input: FilterInput::SourceAlpha,
+					std_deviation: shadow.blur
+				},
+				FilterPrimitive::Offset {
+					input: FilterInput::Result(0),
+					offset: shadow.offset * 2.
+				},
+				FilterPrimitive::Flood {
+					color: shadow.color,
+					opacity: shadow.opacity / 100.
+				},
+			]
+		);
+		assert_eq!(
+			primitives.last(),
+			Some(&FilterPrimitive::Composite {
+				input: FilterInput::SourceGraphic,
+				input2: FilterInput::Result(3),
+				operator: CompositeOperator::Over,
+			})
+		);
+	}
+
+	#[test]
+	fn behind_effects_stay_behind() {
+		let overlay = LayerEffect::new(LayerEffectKind::ColorOverlay);
+		let stroke = LayerEffect::new(LayerEffectKind::Stroke);
+		let primitives = filter_primitives(&[overlay, stroke], 1., BOUNDS);
+
+		// The stroke is drawn first, then the content over it, then the overlay blended over both
+		assert!(matches!(primitives[0], FilterPrimitive::Dilate { input: FilterInput::SourceAlpha, .. }));
+		let content = primitives
+			.iter()
+			.position(|primitive| {
+				matches!(
+					primitive,
+					FilterPrimitive::Composite {
+						input: FilterInput::SourceGraphic,
+						..
+					}
+				)
+			})
+			.unwrap();
+		assert!(matches!(primitives.last(), Some(FilterPrimitive::Blend { input2, mode: BlendMode::Normal, .. }) if *input2 == FilterInput::Result(content)));
+	}
+
+	#[test]
+	fn gradient_runs_along_angle() {
+		let image = gradient_image(Color::BLACK, Color::WHITE, 0., 1., BOUNDS);
+		let row = &image.data[..GRADIENT_RESOLUTION as usize];
+		assert!(row.first().unwrap().r() < 0.01 && row.last().unwrap().r() > 0.95);
+		assert!(row.windows(2).all(|pair| pair[0].r() <= pair[1].r()));
+		assert_eq!(image.data[0], image.data[(GRADIENT_RESOLUTION * (GRADIENT_RESOLUTION - 1)) as usize]);
+	}
+}
@@ -1,12 +1,16 @@
+use crate::layer_effects::{filter_primitives, CompositeOperator, FilterInput, FilterPrimitive, LayerEffect};
+use crate::raster::color_management::DisplayTransform;
 use crate::raster::{Image, ImageFrame};
 use crate::uuid::{generate_uuid, ManipulatorGroupId};
-use crate::{vector::VectorData, Artboard, Color, GraphicElementData, GraphicGroup};
+use crate::vector::style::{Stroke, StrokeAlign};
+use crate::{vector::VectorData, Artboard, Color, GraphicElement, GraphicElementData, GraphicGroup};
 use base64::Engine;
 use bezier_rs::Subpath;
 
 pub use quad::Quad;
 
 use glam::{DAffine2, DVec2};
+use std::borrow::Cow;
 
 mod quad;
 
@@ -140,6 +144,7 @@ impl Default for SvgRender {
 	}
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ImageRenderMode {
 	BlobUrl,
 	Canvas,
@@ -152,6 +157,8 @@ pub struct RenderParams {
 	pub image_render_mode: ImageRenderMode,
 	pub culling_bounds: Option<[DVec2; 2]>,
 	pub thumbnail: bool,
+	/// Converts the colors from the document's working space to the display, when they aren't shown unchanged
+	pub display_transform: Option<DisplayTransform>,
 }
 
 impl RenderParams {
@@ -161,8 +168,13 @@ impl RenderParams {
 			image_render_mode,
 			culling_bounds,
 			thumbnail,
+			display_transform: None,
 		}
 	}
+
+	fn display_color(&self, color: Color) -> Color {
+		self.display_transform.as_ref().map_or(color, |transform| transform.apply(color))
+	}
 }
 
 pub fn format_transform_matrix(transform: DAffine2) -> String {
@@ -187,7 +199,23 @@ pub trait GraphicElementRendered {
 
 impl GraphicElementRendered for GraphicGroup {
 	fn render_svg(&self, render: &mut SvgRender, render_params: &RenderParams) {
-		self.iter().for_each(|element| element.graphic_element_data.render_svg(render, render_params))
+		// The elements at the base of a clipping group are given an id, so the clipped elements above them can refer to their content
+		let mut clip_base_ids = vec![None; self.len()];
+		for index in (0..self.len()).filter(|&index| self[index].visible) {
+			if let Some(base) = self.clip_base(index) {
+				clip_base_ids[base].get_or_insert_with(|| format!("clip-base-{}", generate_uuid()));
+			}
+		}
+
+		for (index, element) in self.iter().enumerate().filter(|(_, element)| element.visible) {
+			let clip_to = match self.clip_base(index) {
+				// Hiding the element at the base of a clipping group hides the whole group
+				Some(base) if !self[base].visible => continue,
+				Some(base) => clip_base_ids[base].as_deref(),
+				None => None,
+			};
+			render_element(element, render, render_params, clip_base_ids[index].as_deref(), clip_to);
+		}
 	}
 	fn bounding_box(&self, transform: DAffine2) -> Option<[DVec2; 2]> {
 		self.iter().filter_map(|element| element.graphic_element_data.bounding_box(transform)).reduce(Quad::combine_bounds)
@@ -195,6 +223,268 @@ impl GraphicElementRendered for GraphicGroup {
 	fn add_click_targets(&self, _click_targets: &mut Vec<ClickTarget>) {}
 }
 
+/// Renders an element of a group with its opacity, blend mode, mask and effects, giving its masked content the id `id` and clipping it to the content with the id `clip_to`
+fn render_element(element: &GraphicElement, render: &mut SvgRender, render_params: &RenderParams, id: Option<&str>, clip_to: Option<&str>) {
+	// Masks are placed before the element they apply to rather than in the `<defs />`, since raster masks need the image data of the render
+	let mask = element.mask.as_deref().map(|mask| render_mask(mask, render, render_params));
+	let clip_mask = clip_to.map(|clip_to| {
+		let mask_id = format!("clip-{}", generate_uuid());
+		render.parent_tag(
+			"mask",
+			|attributes| {
+				attributes.push("id", mask_id.clone());
+				attributes.push("mask-type", "alpha");
+			},
+			|render| render.leaf_tag("use", |attributes| attributes.push("href", format!("#{clip_to}"))),
+		);
+		mask_id
+	});
+
+	// Blend modes which CSS doesn't have are drawn as normal
+	let blend_mode = element.blend_mode.to_svg_style_name().filter(|&blend_mode| blend_mode != "normal");
+	let render_content = |render: &mut SvgRender| {
+		if mask.is_none() && id.is_none() {
+			element.graphic_element_data.render_svg(render, render_params);
+			return;
+		}
+		render.parent_tag(
+			"g",
+			|attributes| {
+				if let Some(id) = id {
+					attributes.push("id", id.to_string());
+				}
+				match &mask {
+					Some(MaskReference::ClipPath(mask_id)) => attributes.push("clip-path", format!("url(#{mask_id})")),
+					Some(MaskReference::Mask(mask_id)) => attributes.push("mask", format!("url(#{mask_id})")),
+					None => {}
+				}
+			},
+			|render| element.graphic_element_data.render_svg(render, render_params),
+		);
+	};
+	// The effects are drawn from the masked content, and are faded, blended and clipped along with it
+	let filter = render_effects_filter(element, render, render_params);
+	let render_content = |render: &mut SvgRender| match &filter {
+		Some(filter) => render.parent_tag("g", |attributes| attributes.push("filter", format!("url(#{filter})")), |render| render_content(render)),
+		None => render_content(render),
+	};
+	if element.opacity >= 1. && blend_mode.is_none() && clip_mask.is_none() {
+		render_content(render);
+		return;
+	}
+	render.parent_tag(
+		"g",
+		|attributes| {
+			if element.opacity < 1. {
+				attributes.push("opacity", element.opacity.to_string());
+			}
+			if let Some(blend_mode) = blend_mode {
+				attributes.push("style", format!("mix-blend-mode: {blend_mode};"));
+			}
+			if let Some(clip_mask) = &clip_mask {
+				attributes.push("mask", format!("url(#{clip_mask})"));
+			}
+		},
+		render_content,
+	);
+}
+
+/// Adds an SVG filter drawing the element's enabled effects to the `<defs />`, returning its id
+fn render_effects_filter(element: &GraphicElement, render: &mut SvgRender, render_params: &RenderParams) -> Option<String> {
+	use std::fmt::Write;
+
+	let effects: Vec<_> = element
+		.effects
+		.iter()
+		.filter(|effect| effect.enabled)
+		.map(|effect| effect.map_colors(|color| render_params.display_color(color)))
+		.collect();
+	if effects.is_empty() {
+		return None;
+	}
+	let bounds = element.graphic_element_data.bounding_box(render.transform)?;
+	let scale = render.transform.matrix2.determinant().abs().sqrt();
+	let primitives = filter_primitives(&effects, scale, bounds);
+	if primitives.is_empty() {
+		return None;
+	}
+
+	let extent = effects.iter().map(LayerEffect::extent).fold(0., f64::max) * scale + 1.;
+	let (min, max) = (bounds[0] - extent, bounds[1] + extent);
+	let id = format!("effects-{}", generate_uuid());
+	let defs = &mut render.svg_defs;
+	write!(
+		defs,
+		r#"<filter id="{id}" filterUnits="userSpaceOnUse" x="{}" y="{}" width="{}" height="{}" color-interpolation-filters="sRGB">"#,
+		min.x,
+		min.y,
+		max.x - min.x,
+		max.y - min.y
+	)
+	.unwrap();
+	let input = |input: &FilterInput| match input {
+		FilterInput::SourceGraphic => "SourceGraphic".to_string(),
+		FilterInput::SourceAlpha => "SourceAlpha".to_string(),
+		FilterInput::Result(index) => format!("effect-{index}"),
+	};
+	for (index, primitive) in primitives.iter().enumerate() {
+		let result = format!("effect-{index}");
+		match primitive {
+			FilterPrimitive::Flood { color, opacity } => write!(
+				defs,
+				r##"<feFlood flood-color="#{}" flood-opacity="{}" result="{result}"/>"##,
+				color.rgb_hex(),
+				color.a() as f64 * opacity
+			),
+			FilterPrimitive::GaussianBlur { input: blurred, std_deviation } => {
+				write!(defs, r#"<feGaussianBlur in="{}" stdDeviation="{std_deviation}" result="{result}"/>"#, input(blurred))
+			}
+			FilterPrimitive::Offset { input: moved, offset } => write!(defs, r#"<feOffset in="{}" dx="{}" dy="{}" result="{result}"/>"#, input(moved), offset.x, offset.y),
+			FilterPrimitive::Dilate { input: grown, radius } => write!(defs, r#"<feMorphology in="{}" operator="dilate" radius="{radius}" result="{result}"/>"#, input(grown)),
+			FilterPrimitive::InvertAlpha { input: inverted } => write!(
+				defs,
+				r#"<feComponentTransfer in="{}" result="{result}"><feFuncA type="table" tableValues="1 0"/></feComponentTransfer>"#,
+				input(inverted)
+			),
+			FilterPrimitive::Composite { input: top, input2: bottom, operator } => {
+				let operator = match operator {
+					CompositeOperator::Over => "over",
+					CompositeOperator::In => "in",
+				};
+				write!(defs, r#"<feComposite in="{}" in2="{}" operator="{operator}" result="{result}"/>"#, input(top), input(bottom))
+			}
+			FilterPrimitive::Blend { input: top, input2: bottom, mode } => {
+				// Blend modes which CSS doesn't have are drawn as normal
+				let mode = mode.to_svg_style_name().unwrap_or("normal");
+				write!(defs, r#"<feBlend in="{}" in2="{}" mode="{mode}" result="{result}"/>"#, input(top), input(bottom))
+			}
+			FilterPrimitive::Image { image, bounds: [min, max] } => {
+				let mut href = "data:image/png;base64,".to_string();
+				base64::engine::general_purpose::STANDARD.encode_string(image.to_png(), &mut href);
+				let size = *max - *min;
+				write!(
+					defs,
+					r#"<feImage href="{href}" x="{}" y="{}" width="{}" height="{}" preserveAspectRatio="none" result="{result}"/>"#,
+					min.x, min.y, size.x, size.y
+				)
+			}
+		}
+		.unwrap();
+	}
+	defs.push_str("</filter>");
+
+	Some(id)
+}
+
+/// How an element refers to the mask rendered for it
+enum MaskReference {
+	ClipPath(String),
+	Mask(String),
+}
+
+/// Renders a vector mask as a `<clipPath />` of its shape, and any other mask as a `<mask />` using its luminance
+fn render_mask(mask: &GraphicElementData, render: &mut SvgRender, render_params: &RenderParams) -> MaskReference {
+	let mask_id = format!("mask-{}", generate_uuid());
+	if let GraphicElementData::VectorShape(vector_data) = mask {
+		let mut path = String::new();
+		for subpath in &vector_data.subpaths {
+			let _ = subpath.subpath_to_svg(&mut path, vector_data.transform * render.transform);
+		}
+		render.parent_tag(
+			"clipPath",
+			|attributes| attributes.push("id", mask_id.clone()),
+			|render| render.leaf_tag("path", |attributes| attributes.push("d", path)),
+		);
+		return MaskReference::ClipPath(mask_id);
+	}
+
+	// The mask's values aren't colors shown on the display, so they are kept as they are
+	let mask_params = RenderParams {
+		display_transform: None,
+		..*render_params
+	};
+	render.parent_tag("mask", |attributes| attributes.push("id", mask_id.clone()), |render| mask.render_svg(render, &mask_params));
+	MaskReference::Mask(mask_id)
+}
+
+/// SVG strokes are centered on their path, so a stroke aligned inside or outside of the closed subpaths is drawn at twice its weight and clipped to the inside or masked to the outside of them.
+/// Open subpaths have no inside or outside, so their stroke stays centered.
+fn render_aligned_stroke(subpaths: &[Subpath<ManipulatorGroupId>], transform: DAffine2, stroke: &Stroke, render: &mut SvgRender) {
+	let (mut closed_path, mut open_path) = (String::new(), String::new());
+	for subpath in subpaths {
+		let path = if subpath.closed() { &mut closed_path } else { &mut open_path };
+		let _ = subpath.subpath_to_svg(path, transform);
+	}
+
+	if !closed_path.is_empty() {
+		let id = format!("stroke-align-{}", generate_uuid());
+		let reference = match stroke.align {
+			StrokeAlign::Inside => {
+				render.parent_tag(
+					"clipPath",
+					|attributes| attributes.push("id", id.clone()),
+					|render| render.leaf_tag("path", |attributes| attributes.push("d", closed_path.clone())),
+				);
+				("clip-path", format!("url(#{id})"))
+			}
+			_ => {
+				// The mask covers everything the doubled stroke can reach, including its miters, and hides the inside of the path
+				let [min, max] = subpaths
+					.iter()
+					.filter(|subpath| subpath.closed())
+					.filter_map(|subpath| subpath.bounding_box_with_transform(transform))
+					.reduce(|a, b| [a[0].min(b[0]), a[1].max(b[1])])
+					.unwrap_or_default();
+				let margin = DVec2::splat(stroke.weight * stroke.line_join_miter_limit.max(1.));
+				let (min, size) = (min - margin, max - min + margin * 2.);
+				render.parent_tag(
+					"mask",
+					|attributes| {
+						attributes.push("id", id.clone());
+						attributes.push("maskUnits", "userSpaceOnUse");
+						attributes.push("x", min.x.to_string());
+						attributes.push("y", min.y.to_string());
+						attributes.push("width", size.x.to_string());
+						attributes.push("height", size.y.to_string());
+					},
+					|render| {
+						render.leaf_tag("rect", |attributes| {
+							attributes.push("x", min.x.to_string());
+							attributes.push("y", min.y.to_string());
+							attributes.push("width", size.x.to_string());
+							attributes.push("height", size.y.to_string());
+							attributes.push("fill", "white");
+						});
+						render.leaf_tag("path", |attributes| {
+							attributes.push("d", closed_path.clone());
+							attributes.push("fill", "black");
+						});
+					},
+				);
+				("mask", format!("url(#{id})"))
+			}
+		};
+
+		let doubled_stroke = stroke.clone().with_weight(stroke.weight * 2.);
+		render.leaf_tag("path", |attributes| {
+			attributes.push("class", "vector-data-stroke");
+			attributes.push("d", closed_path);
+			attributes.push("fill", "none");
+			attributes.push_val(doubled_stroke.render());
+			attributes.push(reference.0, reference.1);
+		});
+	}
+
+	if !open_path.is_empty() {
+		render.leaf_tag("path", |attributes| {
+			attributes.push("class", "vector-data-stroke");
+			attributes.push("d", open_path);
+			attributes.push("fill", "none");
+			attributes.push_val(stroke.render());
+		});
+	}
+}
+
 impl GraphicElementRendered for VectorData {
 	fn render_svg(&self, render: &mut SvgRender, render_params: &RenderParams) {
 		let layer_bounds = self.bounding_box().unwrap_or_default();
@@ -208,9 +498,46 @@ impl GraphicElementRendered for VectorData {
 			attributes.push("class", "vector-data");
 			attributes.push("d", path);
 			let render = &mut attributes.0;
-			let style = self.style.render(render_params.view_mode, &mut render.svg_defs, render.transform, layer_bounds, transformed_bounds);
+			let style = match &render_params.display_transform {
+				Some(transform) => self.style.map_colors(|color| transform.apply(color)),
+				None => self.style.clone(),
+			};
+			let style = style.render(render_params.view_mode, &mut render.svg_defs, render.transform, layer_bounds, transformed_bounds);
 			attributes.push_val(style);
 		});
+
+		// SVG strokes have a single width, so strokes with a width profile are drawn as their filled outline on top of the path
+		let profiled_stroke = self.style.stroke().filter(|stroke| stroke.weight > 0. && !stroke.profile.is_uniform());
+		if let Some(stroke) = profiled_stroke.filter(|_| render_params.view_mode != crate::vector::style::ViewMode::Outline) {
+			let mut path = String::new();
+			for subpath in &self.subpaths {
+				// The outline is made in the same space as the fill, where the unscaled stroke weight is drawn
+				let mut subpath = subpath.clone();
+				subpath.apply_transform(self.transform * render.transform);
+				for outline in crate::vector::stroke_outline(&subpath, &stroke) {
+					let _ = outline.subpath_to_svg(&mut path, DAffine2::IDENTITY);
+				}
+			}
+			let color = stroke.color.map(|color| render_params.display_transform.as_ref().map_or(color, |transform| transform.apply(color)));
+			render.leaf_tag("path", |attributes| {
+				attributes.push("class", "vector-data-stroke");
+				attributes.push("d", path);
+				let render = &mut attributes.0;
+				let fill = color.map_or(crate::vector::style::Fill::None, crate::vector::style::Fill::Solid);
+				let fill = fill.render(&mut render.svg_defs, render.transform, layer_bounds, transformed_bounds);
+				attributes.push_val(fill);
+			});
+		}
+
+		let aligned_stroke = self
+			.style
+			.stroke()
+			.filter(|stroke| stroke.weight > 0. && stroke.profile.is_uniform() && stroke.align != StrokeAlign::Center);
+		if let Some(stroke) = aligned_stroke.filter(|_| render_params.view_mode != crate::vector::style::ViewMode::Outline) {
+			let color = stroke.color.map(|color| render_params.display_transform.as_ref().map_or(color, |transform| transform.apply(color)));
+			let stroke = Stroke { color, ..stroke };
+			render_aligned_stroke(&self.subpaths, self.transform * render.transform, &stroke, render);
+		}
 	}
 	fn bounding_box(&self, transform: DAffine2) -> Option<[DVec2; 2]> {
 		self.bounding_box_with_transform(self.transform * transform)
@@ -230,7 +557,7 @@ impl GraphicElementRendered for Artboard {
 		// Background
 		render.leaf_tag("rect", |attributes| {
 			attributes.push("class", "artboard-bg");
-			attributes.push("fill", format!("#{}", self.background.rgba_hex()));
+			attributes.push("fill", format!("#{}", render_params.display_color(self.background).rgba_hex()));
 			attributes.push("x", self.location.x.min(self.location.x + self.dimensions.x).to_string());
 			attributes.push("y", self.location.y.min(self.location.y + self.dimensions.y).to_string());
 			attributes.push("width", self.dimensions.x.abs().to_string());
@@ -294,6 +621,10 @@ impl GraphicElementRendered for ImageFrame<Color> {
 	fn render_svg(&self, render: &mut SvgRender, render_params: &RenderParams) {
 		let transform: String = format_transform_matrix(self.transform * render.transform);
 		let uuid = generate_uuid();
+		let image = match &render_params.display_transform {
+			Some(display_transform) => Cow::Owned(display_transform.apply_to_image(&self.image)),
+			None => Cow::Borrowed(&self.image),
+		};
 
 		match render_params.image_render_mode {
 			ImageRenderMode::BlobUrl => {
@@ -304,10 +635,9 @@ impl GraphicElementRendered for ImageFrame<Color> {
 					attributes.push("transform", transform);
 					attributes.push("href", SvgSegment::BlobUrl(uuid))
 				});
-				render.image_data.push((uuid, self.image.clone()))
+				render.image_data.push((uuid, image.into_owned()))
 			}
 			ImageRenderMode::Base64 => {
-				let image = &self.image;
 				if image.data.is_empty() {
 					return;
 				}
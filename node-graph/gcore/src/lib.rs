@@ -3,6 +3,9 @@
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+// Lets code generated by `node_macro` refer to this crate as `::graphene_core` from inside the crate too
+extern crate self as graphene_core;
+
 #[cfg_attr(feature = "log", macro_use)]
 #[cfg(feature = "log")]
 extern crate log;
@@ -39,12 +42,34 @@ pub mod vector;
 #[cfg(feature = "alloc")]
 pub mod application_io;
 
+#[cfg(feature = "std")]
+pub mod animation;
+
+#[cfg(feature = "std")]
+pub mod registry;
+
 pub mod quantization;
 
 use core::any::TypeId;
 pub use raster::Color;
 pub use types::Cow;
 
+/// Submits the [`registry::NodeMetadata`] generated by `#[node_macro::node_fn]` to the node registry.
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! submit_node_metadata {
+	($metadata:expr) => {
+		$crate::registry::inventory::submit! { $metadata }
+	};
+}
+
+/// Without `std` there is no registry to collect the generated `NodeMetadata`, so it is discarded.
+#[cfg(not(feature = "std"))]
+#[macro_export]
+macro_rules! submit_node_metadata {
+	($metadata:expr) => {};
+}
+
 // pub trait Node: for<'n> NodeIO<'n> {
 /// The node trait allows for defining any node. Nodes can only take one input, however they can store references to other nodes inside the struct.
 /// See `node-graph/README.md` for information on how to define a new node.
@@ -5,7 +5,9 @@ use crate::Node;
 use bytemuck::{Pod, Zeroable};
 use glam::DVec2;
 
-pub use self::color::{Color, Luma, SRGBA8};
+#[cfg(not(target_arch = "spirv"))]
+pub use self::color::RGBA16F;
+pub use self::color::{Color, Luma, LumaAlpha, SRGBA16, SRGBA8};
 
 #[cfg(target_arch = "spirv")]
 use spirv_std::num_traits::float::Float;
@@ -18,8 +20,14 @@ pub mod brightness_contrast;
 pub mod brush_cache;
 pub mod color;
 #[cfg(not(target_arch = "spirv"))]
+pub mod color_management;
+#[cfg(not(target_arch = "spirv"))]
 pub mod curve;
 pub mod discrete_srgb;
+#[cfg(not(target_arch = "spirv"))]
+pub mod flood_fill;
+#[cfg(not(target_arch = "spirv"))]
+pub mod pixel_selection;
 pub use adjustments::*;
 
 pub trait Linear {
@@ -134,7 +142,8 @@ pub trait Pixel: Clone + Pod + Zeroable {
 	}
 	// TODO: use u8 for Color
 	fn from_bytes(bytes: &[u8]) -> Self {
-		*bytemuck::try_from_bytes(bytes).expect("Failed to convert bytes to pixel")
+		// Serialized buffers carry no alignment guarantee, so read the pixel without requiring one
+		bytemuck::pod_read_unaligned(bytes)
 	}
 
 	fn byte_size() -> usize {
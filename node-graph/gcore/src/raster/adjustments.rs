@@ -76,6 +76,28 @@ impl BlendMode {
 			&[BlendMode::Hue, BlendMode::Saturation, BlendMode::Color, BlendMode::Luminosity],
 		]
 	}
+	/// The CSS [`mix-blend-mode`](https://developer.mozilla.org/en-US/docs/Web/CSS/mix-blend-mode) with the same result, or `None` if CSS has no such blend mode.
+	pub fn to_svg_style_name(&self) -> Option<&'static str> {
+		match self {
+			BlendMode::Normal => Some("normal"),
+			BlendMode::Multiply => Some("multiply"),
+			BlendMode::Darken => Some("darken"),
+			BlendMode::ColorBurn => Some("color-burn"),
+			BlendMode::Screen => Some("screen"),
+			BlendMode::Lighten => Some("lighten"),
+			BlendMode::ColorDodge => Some("color-dodge"),
+			BlendMode::Overlay => Some("overlay"),
+			BlendMode::SoftLight => Some("soft-light"),
+			BlendMode::HardLight => Some("hard-light"),
+			BlendMode::Difference => Some("difference"),
+			BlendMode::Exclusion => Some("exclusion"),
+			BlendMode::Hue => Some("hue"),
+			BlendMode::Saturation => Some("saturation"),
+			BlendMode::Color => Some("color"),
+			BlendMode::Luminosity => Some("luminosity"),
+			_ => None,
+		}
+	}
 }
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
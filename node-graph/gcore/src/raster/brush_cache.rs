@@ -5,8 +5,10 @@ use std::sync::Mutex;
 
 use dyn_any::{DynAny, StaticType};
 
+use crate::raster::pixel_selection::PixelSelection;
 use crate::raster::Image;
 use crate::raster::ImageFrame;
+use crate::raster::Luma;
 use crate::vector::brush_stroke::BrushStroke;
 use crate::vector::brush_stroke::BrushStyle;
 use crate::Color;
@@ -25,6 +27,10 @@ struct BrushCacheImpl {
 	// A cache for brush textures.
 	#[cfg_attr(feature = "serde", serde(skip))]
 	brush_texture_cache: HashMap<BrushStyle, Image<Color>>,
+
+	// The pixels selected by the magic wand edits of each selection strokes were painted in, found from the image before the first of those strokes.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	magic_wand_coverage: Vec<(PixelSelection, Vec<ImageFrame<Luma>>)>,
 }
 
 impl BrushCacheImpl {
@@ -32,6 +38,7 @@ impl BrushCacheImpl {
 		// Do background invalidation.
 		if background.transform != self.background.transform || background.image != self.background.image {
 			self.background = background.clone();
+			self.magic_wand_coverage.clear();
 			return BrushPlan {
 				strokes: input.to_vec(),
 				background,
@@ -43,6 +50,7 @@ impl BrushCacheImpl {
 		let blended_strokes = &self.prev_input[..self.prev_input.len().saturating_sub(1)];
 		let num_blended_strokes = blended_strokes.len();
 		if input.get(..num_blended_strokes) != Some(blended_strokes) {
+			self.magic_wand_coverage.clear();
 			return BrushPlan {
 				strokes: input.to_vec(),
 				background,
@@ -172,4 +180,14 @@ impl BrushCache {
 		let mut inner = self.inner.lock().unwrap();
 		inner.brush_texture_cache.insert(style, brush);
 	}
+
+	pub fn get_magic_wand_coverage(&self, selection: &PixelSelection) -> Option<Vec<ImageFrame<Luma>>> {
+		let inner = self.inner.lock().unwrap();
+		inner.magic_wand_coverage.iter().find(|(cached, _)| cached == selection).map(|(_, coverage)| coverage.clone())
+	}
+
+	pub fn store_magic_wand_coverage(&self, selection: PixelSelection, coverage: Vec<ImageFrame<Luma>>) {
+		let mut inner = self.inner.lock().unwrap();
+		inner.magic_wand_coverage.push((selection, coverage));
+	}
 }
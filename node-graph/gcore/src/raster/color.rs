@@ -10,6 +10,9 @@ use spirv_std::num_traits::float::Float;
 #[cfg(target_arch = "spirv")]
 use spirv_std::num_traits::Euclid;
 
+#[cfg(not(target_arch = "spirv"))]
+use half::f16;
+
 use bytemuck::{Pod, Zeroable};
 
 use super::{
@@ -17,10 +20,11 @@ use super::{
 	Alpha, AssociatedAlpha, Luminance, LuminanceMut, Pixel, RGBMut, Rec709Primaries, RGB, SRGB,
 };
 
+/// An 8 bit per channel pixel, laid out the way 8 bit image files and canvas image data store their samples: gamma encoded sRGB channels from `0` to `255` with unassociated alpha.
 #[repr(C)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "std", derive(specta::Type))]
-#[derive(Debug, Default, Clone, Copy, PartialEq, DynAny, Pod, Zeroable)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, DynAny, Pod, Zeroable)]
 pub struct SRGBA8 {
 	red: u8,
 	green: u8,
@@ -28,14 +32,27 @@ pub struct SRGBA8 {
 	alpha: u8,
 }
 
+impl SRGBA8 {
+	#[inline(always)]
+	pub fn new(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+		Self { red, green, blue, alpha }
+	}
+
+	#[inline(always)]
+	pub fn to_array(self) -> [u8; 4] {
+		[self.red, self.green, self.blue, self.alpha]
+	}
+}
+
 impl From<Color> for SRGBA8 {
 	#[inline(always)]
 	fn from(c: Color) -> Self {
+		let color = c.to_unassociated_alpha();
 		Self {
-			red: float_to_srgb_u8(c.r()),
-			green: float_to_srgb_u8(c.g()),
-			blue: float_to_srgb_u8(c.b()),
-			alpha: (c.a() * 255.0) as u8,
+			red: float_to_srgb_u8(color.r()),
+			green: float_to_srgb_u8(color.g()),
+			blue: float_to_srgb_u8(color.b()),
+			alpha: (c.a().clamp(0., 1.) * 255. + 0.5) as u8,
 		}
 	}
 }
@@ -43,11 +60,12 @@ impl From<Color> for SRGBA8 {
 impl From<SRGBA8> for Color {
 	#[inline(always)]
 	fn from(color: SRGBA8) -> Self {
+		let alpha = color.alpha as f32 / 255.0;
 		Self {
-			red: srgb_u8_to_float(color.red),
-			green: srgb_u8_to_float(color.green),
-			blue: srgb_u8_to_float(color.blue),
-			alpha: color.alpha as f32 / 255.0,
+			red: srgb_u8_to_float(color.red) * alpha,
+			green: srgb_u8_to_float(color.green) * alpha,
+			blue: srgb_u8_to_float(color.blue) * alpha,
+			alpha,
 		}
 	}
 }
@@ -137,6 +155,309 @@ impl RGB for Luma {
 
 impl Pixel for Luma {}
 
+impl From<Color> for Luma {
+	#[inline(always)]
+	fn from(color: Color) -> Self {
+		Self(color.luminance_srgb())
+	}
+}
+
+impl From<Luma> for Color {
+	#[inline(always)]
+	fn from(luma: Luma) -> Self {
+		Color::from_luminance(luma.0)
+	}
+}
+
+#[allow(clippy::derived_hash_with_manual_eq)]
+impl Hash for Luma {
+	fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+		self.0.to_bits().hash(state);
+	}
+}
+
+/// A grayscale pixel with an alpha channel, stored premultiplied like [`Color`].
+#[repr(C)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", derive(specta::Type))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, DynAny, Pod, Zeroable)]
+pub struct LumaAlpha {
+	luminance: f32,
+	alpha: f32,
+}
+
+#[allow(clippy::derived_hash_with_manual_eq)]
+impl Hash for LumaAlpha {
+	fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+		self.luminance.to_bits().hash(state);
+		self.alpha.to_bits().hash(state);
+	}
+}
+
+impl LumaAlpha {
+	#[inline(always)]
+	pub fn new(luminance: f32, alpha: f32) -> Self {
+		Self { luminance, alpha }
+	}
+
+	/// Construct a pixel from a gray level which has not been multiplied by its alpha.
+	#[inline(always)]
+	pub fn from_unassociated_alpha(luminance: f32, alpha: f32) -> Self {
+		Self { luminance: luminance * alpha, alpha }
+	}
+}
+
+impl From<Color> for LumaAlpha {
+	#[inline(always)]
+	fn from(color: Color) -> Self {
+		Self {
+			luminance: color.luminance_srgb(),
+			alpha: color.a(),
+		}
+	}
+}
+
+impl From<LumaAlpha> for Color {
+	#[inline(always)]
+	fn from(pixel: LumaAlpha) -> Self {
+		Color::from_rgbaf32_unchecked(pixel.luminance, pixel.luminance, pixel.luminance, pixel.alpha)
+	}
+}
+
+impl Luminance for LumaAlpha {
+	type LuminanceChannel = f32;
+	#[inline(always)]
+	fn luminance(&self) -> f32 {
+		self.luminance
+	}
+}
+
+impl LuminanceMut for LumaAlpha {
+	fn set_luminance(&mut self, luminance: Self::LuminanceChannel) {
+		self.luminance = luminance
+	}
+}
+
+impl RGB for LumaAlpha {
+	type ColorChannel = f32;
+	#[inline(always)]
+	fn red(&self) -> f32 {
+		self.luminance
+	}
+	#[inline(always)]
+	fn green(&self) -> f32 {
+		self.luminance
+	}
+	#[inline(always)]
+	fn blue(&self) -> f32 {
+		self.luminance
+	}
+}
+
+impl Alpha for LumaAlpha {
+	type AlphaChannel = f32;
+	const TRANSPARENT: Self = LumaAlpha { luminance: 0., alpha: 0. };
+
+	#[inline(always)]
+	fn alpha(&self) -> f32 {
+		self.alpha
+	}
+	#[inline(always)]
+	fn multiplied_alpha(&self, alpha: Self::AlphaChannel) -> Self {
+		Self {
+			luminance: self.luminance * alpha,
+			alpha: self.alpha * alpha,
+		}
+	}
+}
+
+impl Pixel for LumaAlpha {}
+
+/// A 16 bit per channel pixel, laid out the way 16 bit PNG and TIFF files store their samples: gamma encoded sRGB channels from `0` to `65535` with unassociated alpha.
+#[repr(C)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", derive(specta::Type))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, DynAny, Pod, Zeroable)]
+pub struct SRGBA16 {
+	red: u16,
+	green: u16,
+	blue: u16,
+	alpha: u16,
+}
+
+impl SRGBA16 {
+	#[inline(always)]
+	pub fn new(red: u16, green: u16, blue: u16, alpha: u16) -> Self {
+		Self { red, green, blue, alpha }
+	}
+
+	#[inline(always)]
+	pub fn to_array(self) -> [u16; 4] {
+		[self.red, self.green, self.blue, self.alpha]
+	}
+}
+
+impl From<Color> for SRGBA16 {
+	#[inline(always)]
+	fn from(color: Color) -> Self {
+		let color = color.to_unassociated_alpha().to_gamma_srgb();
+		let quantize = |channel: f32| (channel.clamp(0., 1.) * 65535. + 0.5) as u16;
+		Self {
+			red: quantize(color.r()),
+			green: quantize(color.g()),
+			blue: quantize(color.b()),
+			alpha: quantize(color.a()),
+		}
+	}
+}
+
+impl From<SRGBA16> for Color {
+	#[inline(always)]
+	fn from(color: SRGBA16) -> Self {
+		let alpha = color.alpha();
+		Color::from_rgbaf32_unchecked(color.red(), color.green(), color.blue(), alpha)
+			.to_linear_srgb()
+			.map_rgb(|channel| channel * alpha)
+	}
+}
+
+impl Luminance for SRGBA16 {
+	type LuminanceChannel = f32;
+	#[inline(always)]
+	fn luminance(&self) -> f32 {
+		0.2126 * self.red() + 0.7152 * self.green() + 0.0722 * self.blue()
+	}
+}
+
+impl RGB for SRGBA16 {
+	type ColorChannel = f32;
+	#[inline(always)]
+	fn red(&self) -> f32 {
+		self.red as f32 / 65535.
+	}
+	#[inline(always)]
+	fn green(&self) -> f32 {
+		self.green as f32 / 65535.
+	}
+	#[inline(always)]
+	fn blue(&self) -> f32 {
+		self.blue as f32 / 65535.
+	}
+}
+
+impl Rec709Primaries for SRGBA16 {}
+impl SRGB for SRGBA16 {}
+
+impl Alpha for SRGBA16 {
+	type AlphaChannel = f32;
+	const TRANSPARENT: Self = SRGBA16 { red: 0, green: 0, blue: 0, alpha: 0 };
+
+	#[inline(always)]
+	fn alpha(&self) -> f32 {
+		self.alpha as f32 / 65535.
+	}
+	fn multiplied_alpha(&self, alpha: Self::AlphaChannel) -> Self {
+		let mut result = *self;
+		result.alpha = (alpha * self.alpha() * 65535.) as u16;
+		result
+	}
+}
+
+impl Pixel for SRGBA16 {}
+
+/// A half precision floating point pixel which holds the same linear, premultiplied values as [`Color`] in half the memory.
+#[cfg(not(target_arch = "spirv"))]
+#[repr(C)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, DynAny, Pod, Zeroable)]
+pub struct RGBA16F {
+	red: f16,
+	green: f16,
+	blue: f16,
+	alpha: f16,
+}
+
+#[cfg(not(target_arch = "spirv"))]
+#[allow(clippy::derived_hash_with_manual_eq)]
+impl Hash for RGBA16F {
+	fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+		self.red.to_bits().hash(state);
+		self.green.to_bits().hash(state);
+		self.blue.to_bits().hash(state);
+		self.alpha.to_bits().hash(state);
+	}
+}
+
+#[cfg(not(target_arch = "spirv"))]
+impl From<Color> for RGBA16F {
+	#[inline(always)]
+	fn from(color: Color) -> Self {
+		Self {
+			red: f16::from_f32(color.r()),
+			green: f16::from_f32(color.g()),
+			blue: f16::from_f32(color.b()),
+			alpha: f16::from_f32(color.a()),
+		}
+	}
+}
+
+#[cfg(not(target_arch = "spirv"))]
+impl From<RGBA16F> for Color {
+	#[inline(always)]
+	fn from(color: RGBA16F) -> Self {
+		Color::from_rgbaf32_unchecked(color.red.to_f32(), color.green.to_f32(), color.blue.to_f32(), color.alpha.to_f32())
+	}
+}
+
+#[cfg(not(target_arch = "spirv"))]
+impl Luminance for RGBA16F {
+	type LuminanceChannel = f32;
+	#[inline(always)]
+	fn luminance(&self) -> f32 {
+		0.2126 * self.red() + 0.7152 * self.green() + 0.0722 * self.blue()
+	}
+}
+
+#[cfg(not(target_arch = "spirv"))]
+impl RGB for RGBA16F {
+	type ColorChannel = f32;
+	#[inline(always)]
+	fn red(&self) -> f32 {
+		self.red.to_f32()
+	}
+	#[inline(always)]
+	fn green(&self) -> f32 {
+		self.green.to_f32()
+	}
+	#[inline(always)]
+	fn blue(&self) -> f32 {
+		self.blue.to_f32()
+	}
+}
+
+#[cfg(not(target_arch = "spirv"))]
+impl Alpha for RGBA16F {
+	type AlphaChannel = f32;
+	const TRANSPARENT: Self = RGBA16F {
+		red: f16::ZERO,
+		green: f16::ZERO,
+		blue: f16::ZERO,
+		alpha: f16::ZERO,
+	};
+
+	#[inline(always)]
+	fn alpha(&self) -> f32 {
+		self.alpha.to_f32()
+	}
+	#[inline(always)]
+	fn multiplied_alpha(&self, alpha: Self::AlphaChannel) -> Self {
+		Color::from(*self).multiplied_alpha(alpha).into()
+	}
+}
+
+#[cfg(not(target_arch = "spirv"))]
+impl Pixel for RGBA16F {}
+
 /// Structure that represents a color.
 /// Internally alpha is stored as `f32` that ranges from `0.0` (transparent) to `1.0` (opaque).
 /// The other components (RGB) are stored as `f32` that range from `0.0` up to `f32::MAX`,
@@ -994,6 +1315,26 @@ impl Color {
 	}
 }
 
+#[test]
+fn srgba8_roundtrip() {
+	// Translucent pixels keep their channels, since they aren't premultiplied until they become colors
+	for alpha in [1, 37, 128, 255] {
+		for channel in 0..=255 {
+			let pixel = SRGBA8::new(channel, 255 - channel, channel / 2, alpha);
+			assert_eq!(SRGBA8::from(Color::from(pixel)), pixel);
+		}
+	}
+}
+
+#[test]
+fn srgba16_roundtrip() {
+	for channels in [[0, 0, 0, 0], [65535, 0, 32768, 65535], [1, 2, 3, 65535], [40000, 20000, 10000, 65535]] {
+		let [red, green, blue, alpha] = channels;
+		let pixel = SRGBA16::new(red, green, blue, alpha);
+		assert_eq!(SRGBA16::from(Color::from(pixel)), pixel);
+	}
+}
+
 #[test]
 fn hsl_roundtrip() {
 	for (red, green, blue) in [
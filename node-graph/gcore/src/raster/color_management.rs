@@ -0,0 +1,838 @@
+//! Color profiles: the working spaces which the colors of a document are stored in, and the ICC profiles of imported images and of printing conditions.
+//!
+//! Colors are converted through the D50 XYZ profile connection space of the ICC specification. Profiles are read when they describe RGB or grayscale colors with
+//! tone curves and a matrix, or CMYK colors with `lut8Type` and `lut16Type` tables (as most version 2 printer profiles do). The `lutAtoBType` tables of
+//! version 4 profiles aren't supported yet.
+
+use super::{Color, Image};
+
+use glam::{DMat3, DVec3};
+use std::sync::Arc;
+
+/// The white point of the ICC profile connection space.
+pub const D50: DVec3 = DVec3::new(0.9642, 1., 0.8249);
+const D65: DVec3 = DVec3::new(0.95047, 1., 1.08883);
+/// The xy chromaticity of the D65 white point.
+pub const D65_CHROMATICITY: (f64, f64) = (0.3127, 0.329);
+
+/// The matrix from linear RGB values with the given primary chromaticities and white point to D50 XYZ, chromatically adapted from the white point with the Bradford transform.
+pub fn rgb_to_xyz_matrix(primaries: [(f64, f64); 3], white: DVec3) -> DMat3 {
+	let primaries = primaries.map(chromaticity_to_xyz);
+	let primaries = DMat3::from_cols(primaries[0], primaries[1], primaries[2]);
+	let scale = primaries.inverse() * white;
+	let to_xyz = primaries * DMat3::from_diagonal(scale);
+
+	let bradford = DMat3::from_cols_array_2d(&[[0.8951, 0.2664, -0.1614], [-0.7502, 1.7135, 0.0367], [0.0389, -0.0685, 1.0296]]).transpose();
+	let adaptation = bradford.inverse() * DMat3::from_diagonal((bradford * D50) / (bradford * white)) * bradford;
+	adaptation * to_xyz
+}
+
+/// The XYZ color of unit luminance with the given xy chromaticity.
+pub fn chromaticity_to_xyz((x, y): (f64, f64)) -> DVec3 {
+	DVec3::new(x / y, 1., (1. - x - y) / y)
+}
+
+/// The RGB color space which the colors of a document are stored in, as linear values without the space's transfer function.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WorkingSpace {
+	#[default]
+	Srgb,
+	DisplayP3,
+	AdobeRgb,
+}
+
+impl WorkingSpace {
+	pub const ALL: [WorkingSpace; 3] = [WorkingSpace::Srgb, WorkingSpace::DisplayP3, WorkingSpace::AdobeRgb];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			WorkingSpace::Srgb => "sRGB",
+			WorkingSpace::DisplayP3 => "Display P3",
+			WorkingSpace::AdobeRgb => "Adobe RGB (1998)",
+		}
+	}
+
+	/// The chromaticities of the red, green, and blue primaries. All three spaces use the [`D65_CHROMATICITY`] white point.
+	pub fn primaries(self) -> [(f64, f64); 3] {
+		match self {
+			WorkingSpace::Srgb => [(0.64, 0.33), (0.3, 0.6), (0.15, 0.06)],
+			WorkingSpace::DisplayP3 => [(0.68, 0.32), (0.265, 0.69), (0.15, 0.06)],
+			WorkingSpace::AdobeRgb => [(0.64, 0.33), (0.21, 0.71), (0.15, 0.06)],
+		}
+	}
+
+	/// The transfer function which encodes the linear values of the space, as used by image files in it.
+	pub fn transfer(self) -> ToneCurve {
+		match self {
+			WorkingSpace::Srgb | WorkingSpace::DisplayP3 => ToneCurve::srgb(),
+			WorkingSpace::AdobeRgb => ToneCurve::Gamma(563. / 256.),
+		}
+	}
+
+	/// The matrix from the linear values of the space to D50 XYZ, chromatically adapted from the space's D65 white point with the Bradford transform.
+	pub fn to_xyz_matrix(self) -> DMat3 {
+		rgb_to_xyz_matrix(self.primaries(), D65)
+	}
+
+	pub fn from_xyz_matrix(self) -> DMat3 {
+		self.to_xyz_matrix().inverse()
+	}
+
+	/// Writes an ICC profile of the space, to be embedded in files whose pixels are in it.
+	pub fn icc_profile(self) -> Vec<u8> {
+		let matrix = self.to_xyz_matrix();
+		let tone_curve = match self.transfer() {
+			ToneCurve::Gamma(gamma) => curve_tag(&[(gamma * 256.).round() as u16]),
+			curve => curve_tag(&(0..1024).map(|index| (curve.eval(index as f64 / 1023.) * 65535.).round() as u16).collect::<Vec<_>>()),
+		};
+		let tags = [
+			(*b"desc", description_tag(self.name())),
+			(*b"cprt", text_tag("No copyright, use freely")),
+			(*b"wtpt", xyz_tag(D50)),
+			(*b"rXYZ", xyz_tag(matrix.x_axis)),
+			(*b"gXYZ", xyz_tag(matrix.y_axis)),
+			(*b"bXYZ", xyz_tag(matrix.z_axis)),
+			(*b"rTRC", tone_curve.clone()),
+			(*b"gTRC", tone_curve.clone()),
+			(*b"bTRC", tone_curve),
+		];
+		write_profile(*b"mntr", *b"RGB ", *b"XYZ ", &tags)
+	}
+}
+
+/// A transfer function mapping encoded values to linear light, both from 0 to 1.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ToneCurve {
+	/// `y = x^gamma`
+	Gamma(f64),
+	/// The ICC parametric curve `y = (a x + b)^g + e` from `d` upwards and `y = c x + f` below it, which every parametric curve type is expressed as.
+	Parametric { g: f64, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64 },
+	/// Samples evenly spaced over the encoded values, which are linearly interpolated. An empty table is the identity.
+	Table(Vec<f64>),
+}
+
+impl ToneCurve {
+	/// The sRGB transfer function, which Display P3 shares.
+	pub fn srgb() -> Self {
+		ToneCurve::Parametric {
+			g: 2.4,
+			a: 1. / 1.055,
+			b: 0.055 / 1.055,
+			c: 1. / 12.92,
+			d: 0.04045,
+			e: 0.,
+			f: 0.,
+		}
+	}
+
+	pub fn eval(&self, x: f64) -> f64 {
+		match *self {
+			ToneCurve::Gamma(gamma) => x.max(0.).powf(gamma),
+			ToneCurve::Parametric { g, a, b, c, d, e, f } => {
+				if x >= d {
+					(a * x + b).max(0.).powf(g) + e
+				} else {
+					c * x + f
+				}
+			}
+			ToneCurve::Table(ref table) => interpolate(table, x),
+		}
+	}
+
+	/// Maps linear light back to the encoded value. Tables are assumed to be increasing.
+	pub fn invert(&self, y: f64) -> f64 {
+		match *self {
+			ToneCurve::Gamma(gamma) => y.max(0.).powf(1. / gamma),
+			ToneCurve::Parametric { g, a, b, c, d, e, f } => {
+				if a != 0. && y >= self.eval(d) {
+					((y - e).max(0.).powf(1. / g) - b) / a
+				} else if c != 0. {
+					(y - f) / c
+				} else {
+					d
+				}
+			}
+			ToneCurve::Table(ref table) => {
+				if table.len() < 2 {
+					return table.first().map_or(y, |_| 0.);
+				}
+				let index = table.partition_point(|&value| value < y);
+				if index == 0 {
+					return 0.;
+				}
+				if index == table.len() {
+					return 1.;
+				}
+				let (low, high) = (table[index - 1], table[index]);
+				let factor = if high > low { (y - low) / (high - low) } else { 0. };
+				(index - 1) as f64 / (table.len() - 1) as f64 + factor / (table.len() - 1) as f64
+			}
+		}
+	}
+}
+
+/// Linearly interpolates evenly spaced samples over the range from 0 to 1.
+fn interpolate(table: &[f64], x: f64) -> f64 {
+	match table.len() {
+		0 => x,
+		1 => table[0],
+		length => {
+			let position = x.clamp(0., 1.) * (length - 1) as f64;
+			let index = (position.floor() as usize).min(length - 2);
+			let factor = position - index as f64;
+			table[index] + (table[index + 1] - table[index]) * factor
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProfileColorSpace {
+	Gray,
+	Rgb,
+	Cmyk,
+}
+
+impl ProfileColorSpace {
+	pub fn channels(self) -> usize {
+		match self {
+			ProfileColorSpace::Gray => 1,
+			ProfileColorSpace::Rgb => 3,
+			ProfileColorSpace::Cmyk => 4,
+		}
+	}
+}
+
+/// A parsed ICC profile, which also keeps its file so it can be saved with a document or embedded in an exported file.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "Vec<u8>", into = "Vec<u8>"))]
+pub struct IccProfile {
+	data: Vec<u8>,
+	description: String,
+	color_space: ProfileColorSpace,
+	model: ProfileModel,
+}
+
+#[derive(Clone, Debug)]
+enum ProfileModel {
+	/// Tone curves followed by a matrix to the profile connection space.
+	MatrixShaper {
+		curves: [ToneCurve; 3],
+		matrix: DMat3,
+	},
+	Gray(ToneCurve),
+	/// Lookup tables in both directions, using the relative colorimetric intent where the profile has it.
+	Lut {
+		lab_pcs: bool,
+		device_to_pcs: Lut,
+		pcs_to_device: Lut,
+	},
+}
+
+impl PartialEq for IccProfile {
+	fn eq(&self, other: &Self) -> bool {
+		self.data == other.data
+	}
+}
+
+impl TryFrom<Vec<u8>> for IccProfile {
+	type Error = String;
+
+	fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+		Self::parse(data)
+	}
+}
+
+impl From<IccProfile> for Vec<u8> {
+	fn from(profile: IccProfile) -> Self {
+		profile.data
+	}
+}
+
+impl IccProfile {
+	pub fn parse(data: Vec<u8>) -> Result<Self, String> {
+		if data.len() < 132 || &data[36..40] != b"acsp" {
+			return Err("The file isn't an ICC profile".to_string());
+		}
+		let color_space = match &data[16..20] {
+			b"GRAY" => ProfileColorSpace::Gray,
+			b"RGB " => ProfileColorSpace::Rgb,
+			b"CMYK" => ProfileColorSpace::Cmyk,
+			other => return Err(format!("Profiles of the {} color space aren't supported", String::from_utf8_lossy(other).trim_end())),
+		};
+		let lab_pcs = match &data[20..24] {
+			b"XYZ " => false,
+			b"Lab " => true,
+			_ => return Err("The profile's connection space is neither XYZ nor Lab".to_string()),
+		};
+
+		let tag_count = read_u32(&data, 128).unwrap_or_default() as usize;
+		let tags = (0..tag_count)
+			.map(|index| {
+				let entry = 132 + index * 12;
+				let signature = data.get(entry..entry + 4)?;
+				let (offset, size) = (read_u32(&data, entry + 4)? as usize, read_u32(&data, entry + 8)? as usize);
+				Some((signature, data.get(offset..offset.checked_add(size)?)?))
+			})
+			.collect::<Option<Vec<_>>>()
+			.ok_or_else(|| "The profile's tag table is truncated".to_string())?;
+		let tag = |signature: &[u8; 4]| tags.iter().find(|(tag_signature, _)| tag_signature == signature).map(|&(_, tag)| tag);
+		let required = |signature: &[u8; 4]| tag(signature).ok_or_else(|| format!("The profile has no '{}' tag", String::from_utf8_lossy(signature)));
+
+		let description = tag(b"desc").and_then(read_description).unwrap_or_else(|| "Untitled Profile".to_string());
+
+		let model = match color_space {
+			ProfileColorSpace::Gray => ProfileModel::Gray(read_curve(required(b"kTRC")?)?),
+			ProfileColorSpace::Rgb => {
+				if lab_pcs || tag(b"rXYZ").is_none() {
+					return Err("Only RGB profiles made of tone curves and a matrix are supported".to_string());
+				}
+				let column = |signature| required(signature).and_then(|tag| read_xyz(tag).ok_or_else(|| "The profile has an invalid colorant".to_string()));
+				ProfileModel::MatrixShaper {
+					curves: [read_curve(required(b"rTRC")?)?, read_curve(required(b"gTRC")?)?, read_curve(required(b"bTRC")?)?],
+					matrix: DMat3::from_cols(column(b"rXYZ")?, column(b"gXYZ")?, column(b"bXYZ")?),
+				}
+			}
+			ProfileColorSpace::Cmyk => {
+				let device_to_pcs = Lut::read(tag(b"A2B1").or_else(|| tag(b"A2B0")).ok_or_else(|| "The profile has no table to convert its colors".to_string())?)?;
+				let pcs_to_device = Lut::read(tag(b"B2A1").or_else(|| tag(b"B2A0")).ok_or_else(|| "The profile has no table to convert colors to it".to_string())?)?;
+				if device_to_pcs.input_curves.len() != 4 || device_to_pcs.output_channels != 3 || pcs_to_device.input_curves.len() != 3 || pcs_to_device.output_channels != 4 {
+					return Err("The profile's tables have the wrong number of channels".to_string());
+				}
+				ProfileModel::Lut {
+					lab_pcs,
+					device_to_pcs,
+					pcs_to_device,
+				}
+			}
+		};
+
+		Ok(Self {
+			data,
+			description,
+			color_space,
+			model,
+		})
+	}
+
+	/// The bytes of the profile file.
+	pub fn data(&self) -> &[u8] {
+		&self.data
+	}
+
+	pub fn description(&self) -> &str {
+		&self.description
+	}
+
+	pub fn color_space(&self) -> ProfileColorSpace {
+		self.color_space
+	}
+
+	/// Converts the profile's device values, each from 0 to 1, to the D50 XYZ profile connection space.
+	pub fn device_to_xyz(&self, device: &[f64]) -> DVec3 {
+		let channel = |index: usize| device.get(index).copied().unwrap_or_default();
+		match &self.model {
+			ProfileModel::MatrixShaper { curves, matrix } => *matrix * DVec3::new(curves[0].eval(channel(0)), curves[1].eval(channel(1)), curves[2].eval(channel(2))),
+			ProfileModel::Gray(curve) => D50 * curve.eval(channel(0)),
+			ProfileModel::Lut { lab_pcs, device_to_pcs, .. } => {
+				let pcs = device_to_pcs.eval(&[channel(0), channel(1), channel(2), channel(3)], false);
+				device_to_pcs.decode_pcs(DVec3::new(pcs[0], pcs[1], pcs[2]), *lab_pcs)
+			}
+		}
+	}
+
+	/// Converts a color in the D50 XYZ profile connection space to the profile's device values, clipping it to the profile's gamut.
+	pub fn xyz_to_device(&self, xyz: DVec3) -> Vec<f64> {
+		let device = match &self.model {
+			ProfileModel::MatrixShaper { curves, matrix } => {
+				let linear = matrix.inverse() * xyz;
+				vec![curves[0].invert(linear.x), curves[1].invert(linear.y), curves[2].invert(linear.z)]
+			}
+			ProfileModel::Gray(curve) => vec![curve.invert(xyz.y)],
+			ProfileModel::Lut { lab_pcs, pcs_to_device, .. } => {
+				let pcs = pcs_to_device.encode_pcs(xyz, *lab_pcs);
+				pcs_to_device.eval(&pcs.to_array(), !lab_pcs)
+			}
+		};
+		device.into_iter().map(|value| value.clamp(0., 1.)).collect()
+	}
+}
+
+/// The `lut8Type` and `lut16Type` tables of a profile, with every value normalized to the range from 0 to 1.
+#[derive(Clone, Debug)]
+struct Lut {
+	/// Only applied when the input is XYZ.
+	matrix: DMat3,
+	input_curves: Vec<Vec<f64>>,
+	grid_points: usize,
+	/// The output values of each grid point, with the last input channel varying fastest.
+	clut: Vec<f64>,
+	output_channels: usize,
+	output_curves: Vec<Vec<f64>>,
+	/// The legacy Lab encoding of `lut16Type` tables places the largest values at 0xFF00 rather than 0xFFFF.
+	lab_scale: f64,
+}
+
+impl Lut {
+	fn read(tag: &[u8]) -> Result<Self, String> {
+		let truncated = || "The profile's lookup table is truncated".to_string();
+		let (sixteen_bit, lab_scale) = match tag.get(0..4) {
+			Some(b"mft1") => (false, 1.),
+			Some(b"mft2") => (true, 65535. / 65280.),
+			Some(b"mAB " | b"mBA ") => return Err("Profiles with version 4 lookup tables aren't supported yet".to_string()),
+			_ => return Err("The profile's lookup table has an unknown type".to_string()),
+		};
+		let &[input_channels, output_channels, grid_points] = tag.get(8..11).ok_or_else(truncated)? else {
+			unreachable!()
+		};
+		let (input_channels, output_channels, grid_points) = (input_channels as usize, output_channels as usize, grid_points as usize);
+		if input_channels == 0 || output_channels == 0 || grid_points < 2 {
+			return Err("The profile's lookup table is empty".to_string());
+		}
+
+		let matrix = (0..9).map(|index| read_s15_fixed16(tag, 12 + index * 4)).collect::<Option<Vec<_>>>().ok_or_else(truncated)?;
+		let matrix = DMat3::from_cols_slice(&matrix).transpose();
+
+		let (input_entries, output_entries, start) = match sixteen_bit {
+			true => (read_u16(tag, 48).ok_or_else(truncated)? as usize, read_u16(tag, 50).ok_or_else(truncated)? as usize, 52),
+			false => (256, 256, 48),
+		};
+		// Each curve needs at least its two end points to be interpolated
+		if input_entries < 2 || output_entries < 2 {
+			return Err("The profile's lookup table has curves with too few entries".to_string());
+		}
+		let clut_length = grid_points
+			.checked_pow(input_channels as u32)
+			.and_then(|length| length.checked_mul(output_channels))
+			.ok_or_else(truncated)?;
+		let value_count = input_channels * input_entries + clut_length + output_channels * output_entries;
+		let values: Vec<f64> = match sixteen_bit {
+			true => (0..value_count).map(|index| read_u16(tag, start + index * 2).map(|value| value as f64 / 65535.)).collect::<Option<_>>(),
+			false => tag.get(start..start + value_count).map(|bytes| bytes.iter().map(|&value| value as f64 / 255.).collect()),
+		}
+		.ok_or_else(truncated)?;
+
+		let (input_tables, rest) = values.split_at(input_channels * input_entries);
+		let (clut, output_tables) = rest.split_at(clut_length);
+		Ok(Self {
+			matrix,
+			input_curves: input_tables.chunks(input_entries).map(<[f64]>::to_vec).collect(),
+			grid_points,
+			clut: clut.to_vec(),
+			output_channels,
+			output_curves: output_tables.chunks(output_entries).map(<[f64]>::to_vec).collect(),
+			lab_scale,
+		})
+	}
+
+	fn eval(&self, input: &[f64], apply_matrix: bool) -> Vec<f64> {
+		let mut input = input.to_vec();
+		if apply_matrix && input.len() == 3 {
+			input = (self.matrix * DVec3::from_slice(&input)).to_array().to_vec();
+		}
+		let input: Vec<f64> = self.input_curves.iter().zip(input).map(|(curve, value)| interpolate(curve, value)).collect();
+
+		// Multilinear interpolation between the grid points surrounding the input
+		let last = self.grid_points - 1;
+		let cells: Vec<(usize, f64)> = input
+			.iter()
+			.map(|value| {
+				let position = value.clamp(0., 1.) * last as f64;
+				let index = (position.floor() as usize).min(last - 1);
+				(index, position - index as f64)
+			})
+			.collect();
+		let mut output = vec![0.; self.output_channels];
+		for corner in 0..1_usize << cells.len() {
+			let mut weight = 1.;
+			let mut offset = 0;
+			for (channel, &(index, factor)) in cells.iter().enumerate() {
+				let upper = corner >> (cells.len() - 1 - channel) & 1 == 1;
+				weight *= if upper { factor } else { 1. - factor };
+				offset = offset * self.grid_points + index + upper as usize;
+			}
+			if weight == 0. {
+				continue;
+			}
+			for (channel, value) in output.iter_mut().enumerate() {
+				*value += weight * self.clut[offset * self.output_channels + channel];
+			}
+		}
+
+		self.output_curves.iter().zip(output).map(|(curve, value)| interpolate(curve, value)).collect()
+	}
+
+	/// Normalizes a color in the profile connection space to the table's input encoding.
+	fn encode_pcs(&self, xyz: DVec3, lab_pcs: bool) -> DVec3 {
+		if !lab_pcs {
+			return xyz * (32768. / 65535.);
+		}
+		let lab = xyz_to_lab(xyz);
+		DVec3::new(lab.x / 100., (lab.y + 128.) / 255., (lab.z + 128.) / 255.) / self.lab_scale
+	}
+
+	fn decode_pcs(&self, encoded: DVec3, lab_pcs: bool) -> DVec3 {
+		if !lab_pcs {
+			return encoded * (65535. / 32768.);
+		}
+		let encoded = encoded * self.lab_scale;
+		lab_to_xyz(DVec3::new(encoded.x * 100., encoded.y * 255. - 128., encoded.z * 255. - 128.))
+	}
+}
+
+fn xyz_to_lab(xyz: DVec3) -> DVec3 {
+	let f = |t: f64| if t > (6. / 29_f64).powi(3) { t.cbrt() } else { t / (3. * (6. / 29_f64).powi(2)) + 4. / 29. };
+	let [x, y, z] = (xyz / D50).to_array().map(f);
+	DVec3::new(116. * y - 16., 500. * (x - y), 200. * (y - z))
+}
+
+fn lab_to_xyz(lab: DVec3) -> DVec3 {
+	let f = |t: f64| if t > 6. / 29. { t.powi(3) } else { 3. * (6. / 29_f64).powi(2) * (t - 4. / 29.) };
+	let y = (lab.x + 16.) / 116.;
+	DVec3::new(f(y + lab.y / 500.), f(y), f(y - lab.z / 200.)) * D50
+}
+
+/// Converts the colors of a document from its working space to the sRGB colors the viewport shows, optionally simulating how they would print with an output profile.
+#[derive(Clone, Debug)]
+pub struct DisplayTransform {
+	working_space: WorkingSpace,
+	proof_profile: Option<Arc<IccProfile>>,
+	to_xyz: DMat3,
+	xyz_to_srgb: DMat3,
+	/// The transform sampled over the working space colors encoded with the sRGB curve, since evaluating an output profile for every pixel is slow.
+	grid: Option<Arc<[DVec3]>>,
+}
+
+/// Samples per channel of the grid of proofed colors.
+const PROOF_GRID_POINTS: usize = 33;
+
+impl PartialEq for DisplayTransform {
+	fn eq(&self, other: &Self) -> bool {
+		let same_profile = match (&self.proof_profile, &other.proof_profile) {
+			(Some(profile), Some(other)) => Arc::ptr_eq(profile, other),
+			(profile, other) => profile.is_none() && other.is_none(),
+		};
+		self.working_space == other.working_space && same_profile
+	}
+}
+
+impl DisplayTransform {
+	pub fn new(working_space: WorkingSpace, proof_profile: Option<Arc<IccProfile>>) -> Self {
+		let mut transform = Self {
+			working_space,
+			proof_profile,
+			to_xyz: working_space.to_xyz_matrix(),
+			xyz_to_srgb: WorkingSpace::Srgb.from_xyz_matrix(),
+			grid: None,
+		};
+		if transform.proof_profile.is_some() {
+			let last = (PROOF_GRID_POINTS - 1) as f64;
+			let samples = (0..PROOF_GRID_POINTS.pow(3)).map(|index| {
+				let [red, green, blue] = [index / PROOF_GRID_POINTS.pow(2), index / PROOF_GRID_POINTS % PROOF_GRID_POINTS, index % PROOF_GRID_POINTS];
+				let encoded = DVec3::new(red as f64, green as f64, blue as f64) / last;
+				transform.transform_rgb(encoded.to_array().map(|channel| Color::srgb_to_linear(channel as f32) as f64).into())
+			});
+			transform.grid = Some(samples.collect());
+		}
+		transform
+	}
+
+	pub fn working_space(&self) -> WorkingSpace {
+		self.working_space
+	}
+
+	pub fn proof_profile(&self) -> Option<&Arc<IccProfile>> {
+		self.proof_profile.as_ref()
+	}
+
+	/// Whether colors are shown unchanged, because the working space is sRGB and no output is simulated.
+	pub fn is_identity(&self) -> bool {
+		self.working_space == WorkingSpace::Srgb && self.proof_profile.is_none()
+	}
+
+	fn transform_rgb(&self, rgb: DVec3) -> DVec3 {
+		let mut xyz = self.to_xyz * rgb;
+		if let Some(profile) = &self.proof_profile {
+			xyz = profile.device_to_xyz(&profile.xyz_to_device(xyz));
+		}
+		(self.xyz_to_srgb * xyz).clamp(DVec3::ZERO, DVec3::ONE)
+	}
+
+	fn sample_grid(grid: &[DVec3], rgb: DVec3) -> DVec3 {
+		let last = PROOF_GRID_POINTS - 1;
+		let cells = rgb.to_array().map(|channel| {
+			let position = Color::linear_to_srgb(channel.clamp(0., 1.) as f32) as f64 * last as f64;
+			let index = (position.floor() as usize).min(last - 1);
+			(index, position - index as f64)
+		});
+		let mut result = DVec3::ZERO;
+		for corner in 0..8 {
+			let upper = [corner & 4 != 0, corner & 2 != 0, corner & 1 != 0];
+			let weight: f64 = cells.iter().zip(upper).map(|(&(_, factor), upper)| if upper { factor } else { 1. - factor }).product();
+			let [red, green, blue] = [0, 1, 2].map(|channel| cells[channel].0 + upper[channel] as usize);
+			result += grid[(red * PROOF_GRID_POINTS + green) * PROOF_GRID_POINTS + blue] * weight;
+		}
+		result
+	}
+
+	pub fn apply(&self, color: Color) -> Color {
+		if self.is_identity() {
+			return color;
+		}
+		let unassociated = color.to_unassociated_alpha();
+		let rgb = DVec3::new(unassociated.r() as f64, unassociated.g() as f64, unassociated.b() as f64);
+		let rgb = match &self.grid {
+			Some(grid) => Self::sample_grid(grid, rgb),
+			None => self.transform_rgb(rgb),
+		};
+		Color::from_unassociated_alpha(rgb.x as f32, rgb.y as f32, rgb.z as f32, color.a())
+	}
+
+	pub fn apply_to_image(&self, image: &Image<Color>) -> Image<Color> {
+		Image {
+			width: image.width,
+			height: image.height,
+			data: image.data.iter().map(|&color| self.apply(color)).collect(),
+		}
+	}
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+	Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+	Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn read_s15_fixed16(data: &[u8], offset: usize) -> Option<f64> {
+	Some(read_u32(data, offset)? as i32 as f64 / 65536.)
+}
+
+fn read_xyz(tag: &[u8]) -> Option<DVec3> {
+	(tag.get(0..4)? == b"XYZ ").then_some(())?;
+	Some(DVec3::new(read_s15_fixed16(tag, 8)?, read_s15_fixed16(tag, 12)?, read_s15_fixed16(tag, 16)?))
+}
+
+fn read_curve(tag: &[u8]) -> Result<ToneCurve, String> {
+	let truncated = || "The profile's tone curve is truncated".to_string();
+	match tag.get(0..4) {
+		Some(b"curv") => {
+			let count = read_u32(tag, 8).ok_or_else(truncated)? as usize;
+			let values = (0..count).map(|index| read_u16(tag, 12 + index * 2)).collect::<Option<Vec<_>>>().ok_or_else(truncated)?;
+			Ok(match values.as_slice() {
+				[] => ToneCurve::Table(Vec::new()),
+				&[gamma] => ToneCurve::Gamma(gamma as f64 / 256.),
+				values => ToneCurve::Table(values.iter().map(|&value| value as f64 / 65535.).collect()),
+			})
+		}
+		Some(b"para") => {
+			let function_type = read_u16(tag, 8).ok_or_else(truncated)?;
+			let parameter_count = [1, 3, 4, 5, 7].get(function_type as usize).ok_or_else(|| format!("Unknown parametric curve type {function_type}"))?;
+			let parameters = (0..*parameter_count)
+				.map(|index| read_s15_fixed16(tag, 12 + index * 4))
+				.collect::<Option<Vec<_>>>()
+				.ok_or_else(truncated)?;
+			Ok(match *parameters.as_slice() {
+				[g] => ToneCurve::Gamma(g),
+				[g, a, b] => ToneCurve::Parametric {
+					g,
+					a,
+					b,
+					c: 0.,
+					d: -b / a,
+					e: 0.,
+					f: 0.,
+				},
+				[g, a, b, c] => ToneCurve::Parametric {
+					g,
+					a,
+					b,
+					c: 0.,
+					d: -b / a,
+					e: c,
+					f: c,
+				},
+				[g, a, b, c, d] => ToneCurve::Parametric { g, a, b, c, d, e: 0., f: 0. },
+				[g, a, b, c, d, e, f] => ToneCurve::Parametric { g, a, b, c, d, e, f },
+				_ => unreachable!(),
+			})
+		}
+		_ => Err("The profile's tone curve has an unknown type".to_string()),
+	}
+}
+
+/// Reads the English text of a version 2 `textDescriptionType` or version 4 `multiLocalizedUnicodeType` tag.
+fn read_description(tag: &[u8]) -> Option<String> {
+	let text = match tag.get(0..4)? {
+		b"desc" => {
+			let length = read_u32(tag, 8)? as usize;
+			String::from_utf8_lossy(tag.get(12..12 + length)?).to_string()
+		}
+		b"mluc" => {
+			let (length, offset) = (read_u32(tag, 20)? as usize, read_u32(tag, 24)? as usize);
+			let units = tag.get(offset..offset + length)?.chunks_exact(2).map(|unit| u16::from_be_bytes([unit[0], unit[1]]));
+			char::decode_utf16(units).map(|character| character.unwrap_or(char::REPLACEMENT_CHARACTER)).collect()
+		}
+		_ => return None,
+	};
+	let text = text.trim_end_matches('\0').trim().to_string();
+	(!text.is_empty()).then_some(text)
+}
+
+fn s15_fixed16(value: f64) -> [u8; 4] {
+	((value * 65536.).round() as i32).to_be_bytes()
+}
+
+fn xyz_tag(xyz: DVec3) -> Vec<u8> {
+	[b"XYZ ".as_slice(), &[0; 4], &s15_fixed16(xyz.x), &s15_fixed16(xyz.y), &s15_fixed16(xyz.z)].concat()
+}
+
+fn curve_tag(values: &[u16]) -> Vec<u8> {
+	let mut tag = [b"curv".as_slice(), &[0; 4], &(values.len() as u32).to_be_bytes()].concat();
+	tag.extend(values.iter().flat_map(|value| value.to_be_bytes()));
+	tag
+}
+
+fn text_tag(text: &str) -> Vec<u8> {
+	[b"text".as_slice(), &[0; 4], text.as_bytes(), &[0]].concat()
+}
+
+fn description_tag(text: &str) -> Vec<u8> {
+	// The ASCII description, followed by empty Unicode and ScriptCode descriptions
+	[b"desc".as_slice(), &[0; 4], &(text.len() as u32 + 1).to_be_bytes(), text.as_bytes(), &[0], &[0; 8], &[0; 3], &[0; 67]].concat()
+}
+
+/// Writes a version 2.1 profile made of the given tags, where tags with identical data share it.
+fn write_profile(class: [u8; 4], color_space: [u8; 4], pcs: [u8; 4], tags: &[([u8; 4], Vec<u8>)]) -> Vec<u8> {
+	let mut header = vec![0; 128];
+	header[8..12].copy_from_slice(&0x0210_0000_u32.to_be_bytes());
+	header[12..16].copy_from_slice(&class);
+	header[16..20].copy_from_slice(&color_space);
+	header[20..24].copy_from_slice(&pcs);
+	// Created on the first of January 2000
+	header[24..30].copy_from_slice(&[0x07, 0xD0, 0, 1, 0, 1]);
+	header[36..40].copy_from_slice(b"acsp");
+	header[68..80].copy_from_slice(&xyz_tag(D50)[8..]);
+
+	let mut table = (tags.len() as u32).to_be_bytes().to_vec();
+	let mut tag_data: Vec<u8> = Vec::new();
+	let data_start = 128 + 4 + tags.len() * 12;
+	let mut written: Vec<(&[u8], usize)> = Vec::new();
+	for (signature, data) in tags {
+		let offset = match written.iter().find(|(existing, _)| *existing == data.as_slice()) {
+			Some(&(_, offset)) => offset,
+			None => {
+				let offset = data_start + tag_data.len();
+				tag_data.extend(data);
+				tag_data.resize(tag_data.len().next_multiple_of(4), 0);
+				written.push((data, offset));
+				offset
+			}
+		};
+		table.extend(signature);
+		table.extend((offset as u32).to_be_bytes());
+		table.extend((data.len() as u32).to_be_bytes());
+	}
+
+	let mut profile = [header, table, tag_data].concat();
+	let size = profile.len() as u32;
+	profile[0..4].copy_from_slice(&size.to_be_bytes());
+	profile
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn assert_close(a: DVec3, b: DVec3, tolerance: f64) {
+		assert!((a - b).abs().max_element() < tolerance, "{a} is not close to {b}");
+	}
+
+	#[test]
+	fn white_maps_to_d50() {
+		for space in WorkingSpace::ALL {
+			assert_close(space.to_xyz_matrix() * DVec3::ONE, D50, 1e-4);
+		}
+	}
+
+	#[test]
+	fn srgb_red_in_display_p3() {
+		let red = WorkingSpace::DisplayP3.from_xyz_matrix() * WorkingSpace::Srgb.to_xyz_matrix() * DVec3::X;
+		assert_close(red, DVec3::new(0.8225, 0.0332, 0.0171), 1e-3);
+	}
+
+	#[test]
+	fn tone_curves_invert() {
+		for curve in [
+			ToneCurve::srgb(),
+			ToneCurve::Gamma(2.2),
+			ToneCurve::Table((0..256).map(|index| (index as f64 / 255.).powi(2)).collect()),
+		] {
+			for encoded in [0., 0.01, 0.2, 0.5, 0.9, 1.] {
+				assert!((curve.invert(curve.eval(encoded)) - encoded).abs() < 1e-3, "{curve:?} doesn't invert at {encoded}");
+			}
+		}
+	}
+
+	#[test]
+	fn working_space_profiles_round_trip() {
+		for space in WorkingSpace::ALL {
+			let profile = IccProfile::parse(space.icc_profile()).unwrap();
+			assert_eq!(profile.description(), space.name());
+			assert_eq!(profile.color_space(), ProfileColorSpace::Rgb);
+
+			let encoded = [0.2, 0.5, 0.9];
+			let expected = space.to_xyz_matrix() * DVec3::from_array(encoded.map(|channel| space.transfer().eval(channel)));
+			assert_close(profile.device_to_xyz(&encoded), expected, 1e-3);
+			assert_close(DVec3::from_slice(&profile.xyz_to_device(expected)), DVec3::from_array(encoded), 1e-3);
+		}
+	}
+
+	/// A CMYK profile whose tables pass the Lab values straight through as cyan, magenta, and yellow, so proofing shouldn't change colors.
+	fn pass_through_cmyk_profile() -> Vec<u8> {
+		let lut = |input_channels: usize, output_channels: usize| {
+			let mut tag = [b"mft2".as_slice(), &[0; 4], &[input_channels as u8, output_channels as u8, 2, 0]].concat();
+			tag.extend([1., 0., 0., 0., 1., 0., 0., 0., 1.].iter().flat_map(|&value| s15_fixed16(value)));
+			tag.extend([2_u16.to_be_bytes(), 2_u16.to_be_bytes()].concat());
+			let identity = [0_u16, 65535];
+			tag.extend((0..input_channels).flat_map(|_| identity).flat_map(u16::to_be_bytes));
+			for corner in 0..1_usize << input_channels {
+				let bits: Vec<_> = (0..input_channels).map(|channel| corner >> (input_channels - 1 - channel) & 1).collect();
+				tag.extend(
+					(0..output_channels)
+						.map(|channel| bits.get(channel).copied().unwrap_or_default() as u16 * 65535)
+						.flat_map(u16::to_be_bytes),
+				);
+			}
+			tag.extend((0..output_channels).flat_map(|_| identity).flat_map(u16::to_be_bytes));
+			tag
+		};
+		let tags = [(*b"desc", description_tag("Pass Through")), (*b"A2B1", lut(4, 3)), (*b"B2A1", lut(3, 4))];
+		write_profile(*b"prtr", *b"CMYK", *b"Lab ", &tags)
+	}
+
+	#[test]
+	fn lookup_tables_with_empty_curves_are_rejected() {
+		for (input_entries, output_entries) in [(0_u16, 2_u16), (2, 0), (1, 2)] {
+			let mut tag = [b"mft2".as_slice(), &[0; 4], &[1, 1, 2, 0]].concat();
+			tag.extend([1., 0., 0., 0., 1., 0., 0., 0., 1.].iter().flat_map(|&value| s15_fixed16(value)));
+			tag.extend([input_entries.to_be_bytes(), output_entries.to_be_bytes()].concat());
+			tag.extend([0_u8; 64]);
+			assert!(Lut::read(&tag).is_err());
+		}
+	}
+
+	#[test]
+	fn proofing_through_cmyk_tables() {
+		let profile = Arc::new(IccProfile::parse(pass_through_cmyk_profile()).unwrap());
+		assert_eq!(profile.color_space(), ProfileColorSpace::Cmyk);
+
+		let transform = DisplayTransform::new(WorkingSpace::Srgb, Some(profile));
+		for color in [Color::from_rgbaf32_unchecked(0.2, 0.4, 0.6, 1.), Color::from_unassociated_alpha(0.9, 0.1, 0.3, 0.5), Color::WHITE] {
+			let proofed = transform.apply(color);
+			let difference = [proofed.r() - color.r(), proofed.g() - color.g(), proofed.b() - color.b(), proofed.a() - color.a()];
+			assert!(difference.iter().all(|difference| difference.abs() < 2e-3), "{color:?} was proofed as {proofed:?}");
+		}
+	}
+}
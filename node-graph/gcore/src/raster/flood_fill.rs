@@ -0,0 +1,224 @@
+use crate::raster::{Image, ImageFrame};
+use crate::Color;
+
+use dyn_any::{DynAny, StaticType};
+use glam::{DAffine2, DVec2};
+
+use core::hash::{Hash, Hasher};
+use std::collections::VecDeque;
+
+/// A single click of the paint bucket. It is stored rather than its result so the fill is recomputed whenever the pixels it was computed from change.
+#[derive(Clone, Debug, PartialEq, DynAny)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FloodFill {
+	/// The clicked location in layer space, which picks the seed pixel.
+	pub position: DVec2,
+	pub color: Color,
+	/// How different a pixel's color may be from the seed pixel's color to still be filled, as a percentage of the largest possible difference.
+	pub tolerance: f64,
+	/// Only fill the pixels connected to the seed pixel, rather than every similar pixel in the image.
+	pub contiguous: bool,
+	/// Partially fill the pixels bordering the filled region to smooth its edge.
+	pub anti_alias: bool,
+	/// Find the region from the layer composited over the pixels below it, rather than from the layer alone.
+	pub sample_merged: bool,
+}
+
+impl Default for FloodFill {
+	fn default() -> Self {
+		Self {
+			position: DVec2::ZERO,
+			color: Color::BLACK,
+			tolerance: 10.,
+			contiguous: true,
+			anti_alias: true,
+			sample_merged: false,
+		}
+	}
+}
+
+impl Hash for FloodFill {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.position.x.to_bits().hash(state);
+		self.position.y.to_bits().hash(state);
+		self.color.hash(state);
+		self.tolerance.to_bits().hash(state);
+		self.contiguous.hash(state);
+		self.anti_alias.hash(state);
+		self.sample_merged.hash(state);
+	}
+}
+
+impl FloodFill {
+	/// The pixel of an image containing the fill's position, if it lies within the image.
+	pub fn seed_pixel(&self, image_frame: &ImageFrame<Color>) -> Option<(u32, u32)> {
+		let image = &image_frame.image;
+		if image.width == 0 || image.height == 0 || image_frame.transform.matrix2.determinant() == 0. {
+			return None;
+		}
+
+		let layer_to_pixels = DAffine2::from_scale(DVec2::new(image.width as f64, image.height as f64)) * image_frame.transform.inverse();
+		let pixel = layer_to_pixels.transform_point2(self.position).floor();
+		let inside = pixel.x >= 0. && pixel.y >= 0. && pixel.x < image.width as f64 && pixel.y < image.height as f64;
+		inside.then_some((pixel.x as u32, pixel.y as u32))
+	}
+
+	/// Computes how much of each pixel is covered by the fill, from 0 to 1, by comparing the colors of `sample` against its seed pixel.
+	pub fn coverage(&self, sample: &Image<Color>, seed: (u32, u32)) -> Vec<f32> {
+		let (width, height) = (sample.width as usize, sample.height as usize);
+		let index = |x: usize, y: usize| y * width + x;
+
+		let seed_color = sample.data[index(seed.0 as usize, seed.1 as usize)];
+		let tolerance = (self.tolerance / 100.).clamp(0., 1.) as f32;
+		let within_tolerance = |color: Color| color_distance(color, seed_color) <= tolerance;
+
+		let mut filled = vec![false; sample.data.len()];
+		if self.contiguous {
+			let mut queue = VecDeque::from([(seed.0 as usize, seed.1 as usize)]);
+			filled[index(seed.0 as usize, seed.1 as usize)] = true;
+
+			while let Some((x, y)) = queue.pop_front() {
+				let neighbors = [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1)), (x, y + 1)];
+				for (x, y) in neighbors {
+					if x >= width || y >= height || filled[index(x, y)] || !within_tolerance(sample.data[index(x, y)]) {
+						continue;
+					}
+					filled[index(x, y)] = true;
+					queue.push_back((x, y));
+				}
+			}
+		} else {
+			for (filled, &color) in filled.iter_mut().zip(&sample.data) {
+				*filled = within_tolerance(color);
+			}
+		}
+
+		let mut coverage = filled.iter().map(|&filled| if filled { 1. } else { 0. }).collect::<Vec<f32>>();
+		if !self.anti_alias {
+			return coverage;
+		}
+
+		// Pixels outside the region are partially covered in proportion to how many of their neighbors are filled
+		for y in 0..height {
+			for x in 0..width {
+				if filled[index(x, y)] {
+					continue;
+				}
+				let mut filled_neighbors = 0;
+				for neighbor_y in y.saturating_sub(1)..(y + 2).min(height) {
+					for neighbor_x in x.saturating_sub(1)..(x + 2).min(width) {
+						filled_neighbors += filled[index(neighbor_x, neighbor_y)] as u32;
+					}
+				}
+				coverage[index(x, y)] = filled_neighbors as f32 / 9.;
+			}
+		}
+
+		coverage
+	}
+
+	/// Fills the region of `image_frame` found by comparing the pixels of `sample`, which must have the same dimensions as `image_frame`.
+	pub fn apply(&self, image_frame: &mut ImageFrame<Color>, sample: &Image<Color>) {
+		let Some(seed) = self.seed_pixel(image_frame) else { return };
+		debug_assert_eq!((sample.width, sample.height), (image_frame.image.width, image_frame.image.height));
+
+		let coverage = self.coverage(sample, seed);
+		for (pixel, coverage) in image_frame.image.data.iter_mut().zip(coverage) {
+			if coverage > 0. {
+				*pixel = pixel.alpha_blend(self.color.apply_opacity(coverage));
+			}
+		}
+	}
+}
+
+/// The distance between two colors with associated alpha, scaled so the largest possible difference is 1.
+fn color_distance(a: Color, b: Color) -> f32 {
+	let (a, b) = (a.components(), b.components());
+	let squared = (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2) + (a.3 - b.3).powi(2);
+	squared.sqrt() / 2.
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn two_regions() -> ImageFrame<Color> {
+		// A 4x4 image with a red left half, a blue right half and a red pixel in the blue half's corner
+		let mut image = Image::new(4, 4, Color::RED);
+		for y in 0..4 {
+			for x in 2..4 {
+				image.data[y * 4 + x] = Color::BLUE;
+			}
+		}
+		image.data[3 * 4 + 3] = Color::RED;
+		ImageFrame {
+			image,
+			transform: DAffine2::from_scale(DVec2::splat(4.)),
+		}
+	}
+
+	fn fill(contiguous: bool) -> FloodFill {
+		FloodFill {
+			position: DVec2::new(0.5, 0.5),
+			color: Color::GREEN,
+			tolerance: 0.,
+			contiguous,
+			anti_alias: false,
+			sample_merged: false,
+		}
+	}
+
+	#[test]
+	fn contiguous_fill_stops_at_edges() {
+		let mut image_frame = two_regions();
+		let sample = image_frame.image.clone();
+		fill(true).apply(&mut image_frame, &sample);
+
+		let green = image_frame.image.data.iter().filter(|&&color| color == Color::GREEN).count();
+		assert_eq!(green, 8);
+		assert_eq!(image_frame.image.data[3 * 4 + 3], Color::RED);
+	}
+
+	#[test]
+	fn global_fill_matches_every_similar_pixel() {
+		let mut image_frame = two_regions();
+		let sample = image_frame.image.clone();
+		fill(false).apply(&mut image_frame, &sample);
+
+		assert_eq!(image_frame.image.data[3 * 4 + 3], Color::GREEN);
+		assert_eq!(image_frame.image.data.iter().filter(|&&color| color == Color::GREEN).count(), 9);
+	}
+
+	#[test]
+	fn tolerance_includes_similar_colors() {
+		let mut image = Image::new(2, 1, Color::BLACK);
+		image.data[1] = Color::from_rgbf32_unchecked(0.1, 0.1, 0.1);
+		let fill = FloodFill { tolerance: 5., ..fill(true) };
+		assert_eq!(fill.coverage(&image, (0, 0)), [1., 0.]);
+		let fill = FloodFill { tolerance: 10., ..fill };
+		assert_eq!(fill.coverage(&image, (0, 0)), [1., 1.]);
+	}
+
+	#[test]
+	fn anti_aliasing_softens_the_edge() {
+		let image_frame = two_regions();
+		let fill = FloodFill { anti_alias: true, ..fill(true) };
+		let coverage = fill.coverage(&image_frame.image, (0, 0));
+
+		assert_eq!(coverage[0], 1.);
+		assert!(coverage[2] > 0. && coverage[2] < 1.);
+		assert_eq!(coverage[3], 0.);
+	}
+
+	#[test]
+	fn seed_outside_image_does_nothing() {
+		let mut image_frame = two_regions();
+		let sample = image_frame.image.clone();
+		FloodFill {
+			position: DVec2::new(-1., 2.),
+			..fill(true)
+		}
+		.apply(&mut image_frame, &sample);
+		assert_eq!(image_frame, two_regions());
+	}
+}
@@ -362,30 +362,41 @@ impl<S: Into<P> + Pixel, P: Pixel> From<Image<S>> for Image<P> {
 	}
 }*/
 
-impl From<ImageFrame<Color>> for ImageFrame<SRGBA8> {
-	fn from(image: ImageFrame<Color>) -> Self {
-		let data = image.image.data.into_iter().map(|x| x.into()).collect();
-		Self {
-			image: Image {
-				data,
-				width: image.image.width,
-				height: image.image.height,
-			},
-			transform: image.transform,
-		}
-	}
-}
+/// Implements conversions between `ImageFrame<Color>` and an `ImageFrame` of another pixel format, converting each pixel through its `From` impls.
+macro_rules! impl_image_frame_conversion {
+	($($pixel:ty),*) => {
+		$(
+			impl From<ImageFrame<Color>> for ImageFrame<$pixel> {
+				fn from(image: ImageFrame<Color>) -> Self {
+					let data = image.image.data.into_iter().map(|x| x.into()).collect();
+					Self {
+						image: Image {
+							data,
+							width: image.image.width,
+							height: image.image.height,
+						},
+						transform: image.transform,
+					}
+				}
+			}
 
-impl From<ImageFrame<SRGBA8>> for ImageFrame<Color> {
-	fn from(image: ImageFrame<SRGBA8>) -> Self {
-		let data = image.image.data.into_iter().map(|x| x.into()).collect();
-		Self {
-			image: Image {
-				data,
-				width: image.image.width,
-				height: image.image.height,
-			},
-			transform: image.transform,
-		}
-	}
+			impl From<ImageFrame<$pixel>> for ImageFrame<Color> {
+				fn from(image: ImageFrame<$pixel>) -> Self {
+					let data = image.image.data.into_iter().map(|x| x.into()).collect();
+					Self {
+						image: Image {
+							data,
+							width: image.image.width,
+							height: image.image.height,
+						},
+						transform: image.transform,
+					}
+				}
+			}
+		)*
+	};
 }
+
+impl_image_frame_conversion!(SRGBA8, SRGBA16, LumaAlpha, Luma);
+#[cfg(not(target_arch = "spirv"))]
+impl_image_frame_conversion!(RGBA16F);
@@ -0,0 +1,657 @@
+use crate::raster::flood_fill::FloodFill;
+use crate::raster::{Image, ImageFrame, Luma, Sample};
+use crate::Color;
+
+use dyn_any::{DynAny, StaticType};
+use glam::{DAffine2, DVec2};
+
+use core::hash::{Hash, Hasher};
+
+/// The number of straight segments approximating the outline of an elliptical selection.
+const ELLIPSE_SEGMENTS: usize = 64;
+/// The largest feather radius or grow amount in pixels of the masked image, which keeps a huge value over a large image from freezing the graph.
+const MAX_EDGE_RADIUS: f64 = 250.;
+
+/// How a new selection edit is combined with the selection made so far.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, DynAny)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SelectionMode {
+	#[default]
+	Replace,
+	Add,
+	Subtract,
+	Intersect,
+}
+
+/// A region drawn by a marquee or lasso, in the space of the selection.
+#[derive(Clone, Debug, PartialEq, DynAny)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SelectionShape {
+	/// The unit square, mapped by the transform.
+	Rectangle { transform: DAffine2 },
+	/// The ellipse inscribed in the unit square, mapped by the transform.
+	Ellipse { transform: DAffine2 },
+	/// A closed polygon, filled with the even-odd rule so a lasso crossing itself leaves holes.
+	Polygon { points: Vec<DVec2> },
+}
+
+impl SelectionShape {
+	/// The closed outline of the shape, with ellipses approximated by straight segments.
+	pub fn outline(&self) -> Vec<DVec2> {
+		match self {
+			SelectionShape::Rectangle { transform } => [DVec2::ZERO, DVec2::X, DVec2::ONE, DVec2::Y].into_iter().map(|corner| transform.transform_point2(corner)).collect(),
+			SelectionShape::Ellipse { transform } => (0..ELLIPSE_SEGMENTS)
+				.map(|index| {
+					let angle = index as f64 / ELLIPSE_SEGMENTS as f64 * core::f64::consts::TAU;
+					transform.transform_point2(DVec2::splat(0.5) + DVec2::from_angle(angle) * 0.5)
+				})
+				.collect(),
+			SelectionShape::Polygon { points } => points.clone(),
+		}
+	}
+
+	pub fn transformed(&self, transform: DAffine2) -> Self {
+		match self {
+			SelectionShape::Rectangle { transform: shape_transform } => SelectionShape::Rectangle {
+				transform: transform * *shape_transform,
+			},
+			SelectionShape::Ellipse { transform: shape_transform } => SelectionShape::Ellipse {
+				transform: transform * *shape_transform,
+			},
+			SelectionShape::Polygon { points } => SelectionShape::Polygon {
+				points: points.iter().map(|&point| transform.transform_point2(point)).collect(),
+			},
+		}
+	}
+}
+
+impl Hash for SelectionShape {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		core::mem::discriminant(self).hash(state);
+		match self {
+			SelectionShape::Rectangle { transform } | SelectionShape::Ellipse { transform } => transform.to_cols_array().iter().for_each(|value| value.to_bits().hash(state)),
+			SelectionShape::Polygon { points } => points.iter().for_each(|point| {
+				point.x.to_bits().hash(state);
+				point.y.to_bits().hash(state);
+			}),
+		}
+	}
+}
+
+/// A single change to a selection. Like a [`FloodFill`], magic wand clicks are stored rather than their result, which is found from the pixels under the selection by [`PixelSelection::magic_wand_coverage`].
+#[derive(Clone, Debug, PartialEq, DynAny)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SelectionEdit {
+	/// Selects the region drawn by a marquee or lasso.
+	Shape { shape: SelectionShape, mode: SelectionMode, anti_alias: bool },
+	/// Selects the pixels with a similar color to the clicked one.
+	MagicWand {
+		position: DVec2,
+		/// How different a pixel's color may be from the clicked pixel's color to still be selected, as a percentage of the largest possible difference.
+		tolerance: f64,
+		/// Only select the pixels connected to the clicked pixel, rather than every similar pixel.
+		contiguous: bool,
+		anti_alias: bool,
+		mode: SelectionMode,
+	},
+	/// Softens the edge of the selection by blurring it over the given radius, in the units of the selection's space.
+	Feather { radius: f64 },
+	/// Expands the selection by the given distance in every direction, or contracts it when negative, in the units of the selection's space.
+	Grow { amount: f64 },
+	/// Selects everything that was unselected, and deselects everything that was selected.
+	Invert,
+}
+
+impl SelectionEdit {
+	pub fn transformed(&self, transform: DAffine2) -> Self {
+		match self {
+			SelectionEdit::Shape { shape, mode, anti_alias } => SelectionEdit::Shape {
+				shape: shape.transformed(transform),
+				mode: *mode,
+				anti_alias: *anti_alias,
+			},
+			SelectionEdit::MagicWand {
+				position,
+				tolerance,
+				contiguous,
+				anti_alias,
+				mode,
+			} => SelectionEdit::MagicWand {
+				position: transform.transform_point2(*position),
+				tolerance: *tolerance,
+				contiguous: *contiguous,
+				anti_alias: *anti_alias,
+				mode: *mode,
+			},
+			SelectionEdit::Feather { radius } => SelectionEdit::Feather {
+				radius: radius * transform.matrix2.determinant().abs().sqrt(),
+			},
+			SelectionEdit::Grow { amount } => SelectionEdit::Grow {
+				amount: amount * transform.matrix2.determinant().abs().sqrt(),
+			},
+			SelectionEdit::Invert => SelectionEdit::Invert,
+		}
+	}
+
+	/// Whether this edit discards the selection made before it.
+	fn replaces(&self) -> bool {
+		matches!(
+			self,
+			SelectionEdit::Shape { mode: SelectionMode::Replace, .. } | SelectionEdit::MagicWand { mode: SelectionMode::Replace, .. }
+		)
+	}
+}
+
+impl Hash for SelectionEdit {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		core::mem::discriminant(self).hash(state);
+		match self {
+			SelectionEdit::Shape { shape, mode, anti_alias } => {
+				shape.hash(state);
+				mode.hash(state);
+				anti_alias.hash(state);
+			}
+			SelectionEdit::MagicWand {
+				position,
+				tolerance,
+				contiguous,
+				anti_alias,
+				mode,
+			} => {
+				position.x.to_bits().hash(state);
+				position.y.to_bits().hash(state);
+				tolerance.to_bits().hash(state);
+				contiguous.hash(state);
+				anti_alias.hash(state);
+				mode.hash(state);
+			}
+			SelectionEdit::Feather { radius } => radius.to_bits().hash(state),
+			SelectionEdit::Grow { amount } => amount.to_bits().hash(state),
+			SelectionEdit::Invert => {}
+		}
+	}
+}
+
+/// A pixel selection, kept as the edits that made it so it can be rasterized into a mask over any image.
+/// An empty selection means nothing has been selected, so the whole image is editable.
+#[derive(Clone, Debug, Default, PartialEq, Hash, DynAny)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PixelSelection {
+	pub edits: Vec<SelectionEdit>,
+}
+
+impl PixelSelection {
+	pub fn is_empty(&self) -> bool {
+		self.edits.is_empty()
+	}
+
+	/// Adds an edit to the selection, forgetting the earlier edits it replaces.
+	pub fn push(&mut self, edit: SelectionEdit) {
+		if edit.replaces() {
+			self.edits.clear();
+		}
+		self.edits.push(edit);
+	}
+
+	/// The same selection in another space, such as the space of the layer it is used to mask.
+	pub fn transformed(&self, transform: DAffine2) -> Self {
+		Self {
+			edits: self.edits.iter().map(|edit| edit.transformed(transform)).collect(),
+		}
+	}
+
+	/// Rasterizes the selection over the pixels of an image with the given size and transform, which maps the unit square into selection space like [`ImageFrame::transform`].
+	/// Magic wand edits compare the colors of `sample`, and select nothing without one.
+	pub fn mask(&self, transform: DAffine2, width: u32, height: u32, sample: Option<&ImageFrame<Color>>) -> ImageFrame<Luma> {
+		let magic_wand_coverage = sample.map(|sample| self.magic_wand_coverage(sample)).unwrap_or_default();
+		self.mask_with_coverage(transform, width, height, &magic_wand_coverage)
+	}
+
+	/// The pixels selected by each magic wand edit, in order, over the pixels of `sample` whose colors they compare.
+	/// Storing these once the selection is made keeps it the same while the image under it is painted over.
+	pub fn magic_wand_coverage(&self, sample: &ImageFrame<Color>) -> Vec<ImageFrame<Luma>> {
+		let (width, height) = (sample.image.width, sample.image.height);
+		let singular = sample.transform.matrix2.determinant() == 0.;
+		let to_pixels = DAffine2::from_scale(DVec2::new(width as f64, height as f64)) * sample.transform.inverse();
+
+		let wand_edits = self.edits.iter().filter_map(|edit| match edit {
+			SelectionEdit::MagicWand {
+				position,
+				tolerance,
+				contiguous,
+				anti_alias,
+				..
+			} => Some((position, tolerance, contiguous, anti_alias)),
+			_ => None,
+		});
+		wand_edits
+			.map(|(position, tolerance, contiguous, anti_alias)| {
+				let seed = to_pixels.transform_point2(*position).floor();
+				let seed_inside = !singular && seed.x >= 0. && seed.y >= 0. && seed.x < width as f64 && seed.y < height as f64;
+
+				let coverage = if seed_inside {
+					let fill = FloodFill {
+						tolerance: *tolerance,
+						contiguous: *contiguous,
+						anti_alias: *anti_alias,
+						..Default::default()
+					};
+					fill.coverage(&sample.image, (seed.x as u32, seed.y as u32))
+				} else {
+					vec![0.; sample.image.data.len()]
+				};
+				ImageFrame {
+					image: Image {
+						width,
+						height,
+						data: coverage.into_iter().map(Luma).collect(),
+					},
+					transform: sample.transform,
+				}
+			})
+			.collect()
+	}
+
+	/// Rasterizes the selection like [`PixelSelection::mask`], with the magic wand edits selecting the pixels stored by [`PixelSelection::magic_wand_coverage`] rather than comparing colors again.
+	pub fn mask_with_coverage(&self, transform: DAffine2, width: u32, height: u32, magic_wand_coverage: &[ImageFrame<Luma>]) -> ImageFrame<Luma> {
+		let (pixel_width, pixel_height) = (width as usize, height as usize);
+		let singular = transform.matrix2.determinant() == 0.;
+
+		let mut mask = vec![if self.is_empty() { 1. } else { 0. }; pixel_width * pixel_height];
+		if !singular && !mask.is_empty() {
+			let to_pixels = DAffine2::from_scale(DVec2::new(width as f64, height as f64)) * transform.inverse();
+			// How many pixels along each axis of the image a distance in selection space spans
+			let pixel_size = to_pixels.inverse().matrix2;
+			let pixel_radius = |distance: f64| (distance.abs() / DVec2::new(pixel_size.x_axis.length(), pixel_size.y_axis.length())).min(DVec2::splat(MAX_EDGE_RADIUS));
+			let mut magic_wand_coverage = magic_wand_coverage.iter();
+
+			for edit in &self.edits {
+				match edit {
+					SelectionEdit::Shape { shape, mode, anti_alias } => {
+						let outline: Vec<_> = shape.outline().into_iter().map(|point| to_pixels.transform_point2(point)).collect();
+						combine(&mut mask, &polygon_coverage(&outline, pixel_width, pixel_height, *anti_alias), *mode);
+					}
+					SelectionEdit::MagicWand { mode, .. } => {
+						let coverage = match magic_wand_coverage.next() {
+							Some(coverage) => resample(coverage, transform, width, height),
+							None => vec![0.; mask.len()],
+						};
+						combine(&mut mask, &coverage, *mode);
+					}
+					SelectionEdit::Feather { radius } => feather(&mut mask, pixel_width, pixel_height, pixel_radius(*radius)),
+					SelectionEdit::Grow { amount } => grow(&mut mask, pixel_width, pixel_height, pixel_radius(*amount), *amount > 0.),
+					SelectionEdit::Invert => mask.iter_mut().for_each(|value| *value = 1. - *value),
+				}
+			}
+		}
+
+		ImageFrame {
+			image: Image {
+				width,
+				height,
+				data: mask.into_iter().map(Luma).collect(),
+			},
+			transform,
+		}
+	}
+}
+
+/// Blends between the original and edited pixels of an image by how selected each pixel is in the mask, so edits only affect the selection.
+/// An empty mask, such as that of an unconnected node input, leaves the edited image unchanged.
+pub fn limit_to_mask(image: &mut ImageFrame<Color>, original: &ImageFrame<Color>, mask: &ImageFrame<Luma>) {
+	if mask.image.width == 0 || mask.image.height == 0 || mask.transform.matrix2.determinant() == 0. {
+		return;
+	}
+
+	let size = DVec2::new(image.image.width as f64, image.image.height as f64);
+	let pixel_to_layer = image.transform * DAffine2::from_scale(size.recip());
+	let area = pixel_to_layer.transform_vector2(DVec2::ONE);
+	for y in 0..image.image.height {
+		for x in 0..image.image.width {
+			let position = pixel_to_layer.transform_point2(DVec2::new(x as f64, y as f64) + 0.5);
+			let selected = mask.sample(position, area).map_or(0., |luma| luma.0.clamp(0., 1.));
+			if selected >= 1. {
+				continue;
+			}
+
+			let original = original.sample(position, area).unwrap_or(Color::TRANSPARENT);
+			let pixel = &mut image.image.data[(y * image.image.width + x) as usize];
+			*pixel = original.lerp(*pixel, selected);
+		}
+	}
+}
+
+/// Applies a selection edit's coverage to the mask made by the edits before it.
+fn combine(mask: &mut [f32], coverage: &[f32], mode: SelectionMode) {
+	for (value, &coverage) in mask.iter_mut().zip(coverage) {
+		*value = match mode {
+			SelectionMode::Replace => coverage,
+			SelectionMode::Add => value.max(coverage),
+			SelectionMode::Subtract => value.min(1. - coverage),
+			SelectionMode::Intersect => value.min(coverage),
+		};
+	}
+}
+
+/// How much of each pixel lies inside the polygon, given in pixel coordinates, by intersecting the polygon with rows of samples.
+/// With anti-aliasing, each pixel is split into several rows and the exact horizontal overlap is measured along each of them.
+fn polygon_coverage(points: &[DVec2], width: usize, height: usize, anti_alias: bool) -> Vec<f32> {
+	let mut coverage = vec![0.; width * height];
+	let rows_per_pixel = if anti_alias { 4 } else { 1 };
+	let weight = 1. / rows_per_pixel as f32;
+
+	let mut crossings = Vec::new();
+	for y in 0..height {
+		let row = &mut coverage[y * width..(y + 1) * width];
+		for sub_row in 0..rows_per_pixel {
+			let sample_y = y as f64 + (sub_row as f64 + 0.5) / rows_per_pixel as f64;
+
+			crossings.clear();
+			for (&start, &end) in points.iter().zip(points.iter().cycle().skip(1)) {
+				if (start.y <= sample_y) != (end.y <= sample_y) {
+					crossings.push(start.x + (sample_y - start.y) / (end.y - start.y) * (end.x - start.x));
+				}
+			}
+			crossings.sort_by(f64::total_cmp);
+
+			for span in crossings.chunks_exact(2) {
+				let (start, end) = (span[0].clamp(0., width as f64), span[1].clamp(0., width as f64));
+				if anti_alias {
+					for (x, value) in row.iter_mut().enumerate().take(end.ceil() as usize).skip(start.floor() as usize) {
+						let overlap = end.min(x as f64 + 1.) - start.max(x as f64);
+						*value += overlap.max(0.) as f32 * weight;
+					}
+				} else {
+					// Pixels whose centers lie within the span
+					let (first, last) = ((start - 0.5).ceil().max(0.) as usize, ((end - 0.5).ceil().max(0.) as usize).min(width));
+					row.iter_mut().take(last).skip(first).for_each(|value| *value += weight);
+				}
+			}
+		}
+	}
+
+	coverage.iter_mut().for_each(|value| *value = value.min(1.));
+	coverage
+}
+
+/// Blurs the mask with a gaussian kernel extending over the radius along each axis, in pixels, extending the pixels at its edges outwards.
+fn feather(mask: &mut [f32], width: usize, height: usize, radius: DVec2) {
+	if mask.is_empty() {
+		return;
+	}
+
+	let blur = |mask: &[f32], radius: f64, stride: usize, length: usize, lines: usize, line_stride: usize| {
+		let extent = radius.ceil() as usize;
+		if extent == 0 {
+			return mask.to_vec();
+		}
+		let sigma = radius / 2.;
+		let kernel: Vec<f32> = (0..=2 * extent).map(|index| (-((index as f64 - extent as f64).powi(2)) / (2. * sigma * sigma)).exp() as f32).collect();
+		let total: f32 = kernel.iter().sum();
+
+		let mut blurred = vec![0.; mask.len()];
+		for line in 0..lines {
+			let base = line * line_stride;
+			for position in 0..length {
+				let sum: f32 = kernel
+					.iter()
+					.enumerate()
+					.map(|(index, weight)| {
+						let neighbor = (position + index).saturating_sub(extent).min(length - 1);
+						weight * mask[base + neighbor * stride]
+					})
+					.sum();
+				blurred[base + position * stride] = sum / total;
+			}
+		}
+		blurred
+	};
+
+	let horizontal = blur(mask, radius.x, 1, width, height, width);
+	let vertical = blur(&horizontal, radius.y, width, height, width, 1);
+	mask.copy_from_slice(&vertical);
+}
+
+/// Takes the largest (when growing) or smallest (when shrinking) value within the distance along each axis, rounded to whole pixels, of each pixel, in a rectangle around it.
+fn grow(mask: &mut [f32], width: usize, height: usize, distance: DVec2, growing: bool) {
+	if mask.is_empty() {
+		return;
+	}
+	let pick = |a: f32, b: f32| if growing { a.max(b) } else { a.min(b) };
+
+	let spread = |mask: &[f32], distance: f64, stride: usize, length: usize, lines: usize, line_stride: usize| {
+		let extent = distance.round() as usize;
+		let mut spread = mask.to_vec();
+		if extent == 0 {
+			return spread;
+		}
+		for line in 0..lines {
+			let base = line * line_stride;
+			for position in 0..length {
+				let (first, last) = (position.saturating_sub(extent), (position + extent).min(length - 1));
+				spread[base + position * stride] = (first..=last).map(|neighbor| mask[base + neighbor * stride]).reduce(pick).unwrap_or_default();
+			}
+		}
+		spread
+	};
+
+	let horizontal = spread(mask, distance.x, 1, width, height, width);
+	let vertical = spread(&horizontal, distance.y, width, height, width, 1);
+	mask.copy_from_slice(&vertical);
+}
+
+/// How selected the coverage is under the center of each pixel of the mask, where it doesn't reach being unselected.
+fn resample(coverage: &ImageFrame<Luma>, transform: DAffine2, width: u32, height: u32) -> Vec<f32> {
+	let pixel_to_layer = transform * DAffine2::from_scale(DVec2::new(width as f64, height as f64).recip());
+	let area = pixel_to_layer.transform_vector2(DVec2::ONE);
+	(0..height)
+		.flat_map(|y| (0..width).map(move |x| DVec2::new(x as f64, y as f64) + 0.5))
+		.map(|position| coverage.sample(pixel_to_layer.transform_point2(position), area).map_or(0., |luma| luma.0))
+		.collect()
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn rectangle(min: DVec2, max: DVec2, mode: SelectionMode) -> SelectionEdit {
+		SelectionEdit::Shape {
+			shape: SelectionShape::Rectangle {
+				transform: DAffine2::from_scale_angle_translation(max - min, 0., min),
+			},
+			mode,
+			anti_alias: false,
+		}
+	}
+
+	/// The selected pixels of a mask over a 4x4 pixel image covering (0, 0) to (4, 4)
+	fn selected(selection: &PixelSelection, sample: Option<&ImageFrame<Color>>) -> Vec<f32> {
+		let mask = selection.mask(DAffine2::from_scale(DVec2::splat(4.)), 4, 4, sample);
+		mask.image.data.into_iter().map(|luma| luma.0).collect()
+	}
+
+	#[test]
+	fn empty_selection_selects_everything() {
+		assert!(selected(&PixelSelection::default(), None).iter().all(|&value| value == 1.));
+	}
+
+	#[test]
+	fn modes_combine_shapes() {
+		let mut selection = PixelSelection::default();
+		selection.push(rectangle(DVec2::ZERO, DVec2::new(2., 4.), SelectionMode::Replace));
+		selection.push(rectangle(DVec2::ZERO, DVec2::new(4., 1.), SelectionMode::Add));
+		selection.push(rectangle(DVec2::new(0., 3.), DVec2::new(1., 4.), SelectionMode::Subtract));
+		#[rustfmt::skip]
+		assert_eq!(selected(&selection, None), [
+			1., 1., 1., 1.,
+			1., 1., 0., 0.,
+			1., 1., 0., 0.,
+			0., 1., 0., 0.,
+		]);
+
+		selection.push(rectangle(DVec2::new(1., 0.), DVec2::new(4., 4.), SelectionMode::Intersect));
+		assert_eq!(selected(&selection, None).iter().sum::<f32>(), 6.);
+
+		// Replacing forgets the earlier edits
+		selection.push(rectangle(DVec2::ZERO, DVec2::ONE, SelectionMode::Replace));
+		assert_eq!(selection.edits.len(), 1);
+	}
+
+	#[test]
+	fn anti_aliased_edges_are_partially_selected() {
+		let selection = PixelSelection {
+			edits: vec![SelectionEdit::Shape {
+				shape: SelectionShape::Polygon {
+					points: vec![DVec2::ZERO, DVec2::new(4., 0.), DVec2::new(0., 4.)],
+				},
+				mode: SelectionMode::Replace,
+				anti_alias: true,
+			}],
+		};
+		let mask = selected(&selection, None);
+		assert_eq!(mask[0], 1.);
+		assert!((mask[3] - 0.5).abs() < 0.1, "{}", mask[3]);
+		assert_eq!(mask[15], 0.);
+		// The triangle covers half of the image
+		assert!((mask.iter().sum::<f32>() - 8.).abs() < 0.1);
+	}
+
+	#[test]
+	fn ellipse_excludes_corners() {
+		let selection = PixelSelection {
+			edits: vec![SelectionEdit::Shape {
+				shape: SelectionShape::Ellipse {
+					transform: DAffine2::from_scale(DVec2::splat(4.)),
+				},
+				mode: SelectionMode::Replace,
+				anti_alias: false,
+			}],
+		};
+		let mask = selected(&selection, None);
+		assert_eq!([mask[0], mask[3], mask[12], mask[15]], [0.; 4]);
+		assert_eq!([mask[5], mask[6], mask[9], mask[10]], [1.; 4]);
+	}
+
+	#[test]
+	fn grow_shrink_and_invert() {
+		let mut selection = PixelSelection::default();
+		selection.push(rectangle(DVec2::ONE, DVec2::splat(2.), SelectionMode::Replace));
+		selection.push(SelectionEdit::Grow { amount: 1. });
+		assert_eq!(selected(&selection, None).iter().sum::<f32>(), 9.);
+
+		// The grown selection touches the edge of the image, which it isn't shrunk away from
+		selection.push(SelectionEdit::Grow { amount: -1. });
+		#[rustfmt::skip]
+		assert_eq!(selected(&selection, None), [
+			1., 1., 0., 0.,
+			1., 1., 0., 0.,
+			0., 0., 0., 0.,
+			0., 0., 0., 0.,
+		]);
+
+		selection.push(SelectionEdit::Invert);
+		assert_eq!(selected(&selection, None).iter().sum::<f32>(), 12.);
+	}
+
+	#[test]
+	fn feather_softens_the_edge() {
+		let mut selection = PixelSelection::default();
+		selection.push(rectangle(DVec2::ZERO, DVec2::new(2., 4.), SelectionMode::Replace));
+		selection.push(SelectionEdit::Feather { radius: 2. });
+		let mask = selected(&selection, None);
+		assert!(mask[0] > mask[1] && mask[1] > mask[2] && mask[2] > mask[3]);
+		assert!(mask[1] < 1. && mask[2] > 0.);
+	}
+
+	#[test]
+	fn edge_distances_are_in_selection_units() {
+		// The mask's pixels are each two units wide, so growing by two units spreads the selection by a single pixel
+		let mut selection = PixelSelection::default();
+		selection.push(rectangle(DVec2::splat(2.), DVec2::splat(4.), SelectionMode::Replace));
+		selection.push(SelectionEdit::Grow { amount: 2. });
+		let mask = selection.mask(DAffine2::from_scale(DVec2::splat(8.)), 4, 4, None);
+		assert_eq!(mask.image.data.iter().map(|luma| luma.0).sum::<f32>(), 9.);
+
+		// Scaling the selection into another space scales the distance with it
+		assert_eq!(selection.transformed(DAffine2::from_scale(DVec2::splat(3.))).edits[1], SelectionEdit::Grow { amount: 6. });
+
+		// Huge distances are limited rather than spread over every pixel of the image for each pixel
+		selection.push(SelectionEdit::Feather { radius: 1e12 });
+		let mask = selection.mask(DAffine2::from_scale(DVec2::splat(8.)), 4, 4, None);
+		assert!(mask.image.data.iter().all(|luma| luma.0.is_finite()));
+	}
+
+	#[test]
+	fn magic_wand_selects_similar_colors() {
+		// A red left half and a blue right half, sampled at twice the mask's resolution
+		let mut image = Image::new(8, 8, Color::RED);
+		for y in 0..8 {
+			for x in 4..8 {
+				image.data[y * 8 + x] = Color::BLUE;
+			}
+		}
+		let sample = ImageFrame {
+			image,
+			transform: DAffine2::from_scale(DVec2::splat(4.)),
+		};
+
+		let mut selection = PixelSelection::default();
+		selection.push(SelectionEdit::MagicWand {
+			position: DVec2::new(3.5, 0.5),
+			tolerance: 0.,
+			contiguous: true,
+			anti_alias: false,
+			mode: SelectionMode::Replace,
+		});
+		let mask = selected(&selection, Some(&sample));
+		assert_eq!(&mask[..4], [0., 0., 1., 1.]);
+		assert_eq!(mask.iter().sum::<f32>(), 8.);
+
+		assert!(selected(&selection, None).iter().all(|&value| value == 0.));
+	}
+
+	#[test]
+	fn stored_magic_wand_coverage_ignores_later_changes() {
+		let mut sample = ImageFrame {
+			image: Image::new(4, 4, Color::RED),
+			transform: DAffine2::from_scale(DVec2::splat(4.)),
+		};
+		sample.image.data[..4].fill(Color::BLUE);
+
+		let mut selection = PixelSelection::default();
+		selection.push(SelectionEdit::MagicWand {
+			position: DVec2::new(0.5, 0.5),
+			tolerance: 0.,
+			contiguous: true,
+			anti_alias: false,
+			mode: SelectionMode::Replace,
+		});
+		let coverage = selection.magic_wand_coverage(&sample);
+		let transform = DAffine2::from_scale(DVec2::splat(4.));
+		assert_eq!(selection.mask_with_coverage(transform, 4, 4, &coverage), selection.mask(transform, 4, 4, Some(&sample)));
+
+		// Painting over the selected pixels changes what a new magic wand click would select, but not the stored selection
+		sample.image.data.fill(Color::BLUE);
+		let mask = selection.mask_with_coverage(transform, 4, 4, &coverage);
+		assert_eq!(mask.image.data.iter().map(|luma| luma.0).sum::<f32>(), 4.);
+		assert_eq!(selected(&selection, Some(&sample)).iter().sum::<f32>(), 16.);
+	}
+
+	#[test]
+	fn limit_to_mask_keeps_unselected_pixels() {
+		let original = ImageFrame {
+			image: Image::new(2, 1, Color::RED),
+			transform: DAffine2::from_scale(DVec2::new(2., 1.)),
+		};
+		let mut edited = ImageFrame {
+			image: Image::new(2, 1, Color::BLUE),
+			..original.clone()
+		};
+		let mut selection = PixelSelection::default();
+		selection.push(rectangle(DVec2::ZERO, DVec2::ONE, SelectionMode::Replace));
+		let mask = selection.mask(original.transform, 2, 1, None);
+
+		limit_to_mask(&mut edited, &original, &mask);
+		assert_eq!(edited.image.data, [Color::BLUE, Color::RED]);
+	}
+}
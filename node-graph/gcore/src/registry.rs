@@ -0,0 +1,262 @@
+//! Type-erased node construction and the registry of nodes which declare their metadata through `#[node_macro::node_fn]`.
+//!
+//! A node function annotated with a `category` (and optionally a display `name` and `output` name) submits a [`NodeMetadata`] entry which is collected at startup.
+//! The node registry picks up its constructor and type signature, and the editor builds the node's blueprint and properties panel from the rest of the metadata:
+//!
+//! ```ignore
+//! #[node_macro::node_fn(SimplifyPathNode, name = "Simplify Path", category = "Vector", output = "Vector")]
+//! fn simplify_path(#[input(default = VectorData::empty())] vector_data: VectorData, #[input(default = 1., unit = "px", min = 0.)] tolerance: f64) -> VectorData {
+//! 	// ...
+//! }
+//! ```
+
+use crate::{Node, NodeIO, NodeIOTypes};
+
+use dyn_any::{DynAny, StaticType};
+
+use core::marker::PhantomData;
+use core::ops::Deref;
+use core::pin::Pin;
+use std::collections::HashSet;
+
+pub use inventory;
+
+pub type DynFuture<'n, T> = Pin<Box<dyn core::future::Future<Output = T> + 'n>>;
+pub type Any<'n> = Box<dyn DynAny<'n> + 'n>;
+pub type FutureAny<'n> = DynFuture<'n, Any<'n>>;
+// TODO: is this safe? This is assumed to be send+sync.
+pub type TypeErasedNode<'n> = dyn for<'i> NodeIO<'i, Any<'i>, Output = FutureAny<'i>> + 'n;
+pub type TypeErasedRef<'n> = &'n TypeErasedNode<'n>;
+pub type TypeErasedBox<'n> = Box<TypeErasedNode<'n>>;
+
+pub type SharedNodeContainer = std::rc::Rc<NodeContainer>;
+
+pub type NodeConstructor = for<'a> fn(Vec<SharedNodeContainer>) -> DynFuture<'static, TypeErasedBox<'static>>;
+
+#[derive(Clone)]
+pub struct NodeContainer {
+	#[cfg(feature = "dealloc_nodes")]
+	pub node: *mut TypeErasedNode<'static>,
+	#[cfg(not(feature = "dealloc_nodes"))]
+	pub node: TypeErasedRef<'static>,
+}
+
+impl Deref for NodeContainer {
+	type Target = TypeErasedNode<'static>;
+
+	#[cfg(feature = "dealloc_nodes")]
+	fn deref(&self) -> &Self::Target {
+		unsafe { &*(self.node as *const TypeErasedNode) }
+		#[cfg(not(feature = "dealloc_nodes"))]
+		self.node
+	}
+	#[cfg(not(feature = "dealloc_nodes"))]
+	fn deref(&self) -> &Self::Target {
+		self.node
+	}
+}
+
+#[cfg(feature = "dealloc_nodes")]
+impl Drop for NodeContainer {
+	fn drop(&mut self) {
+		unsafe { self.dealloc_unchecked() }
+	}
+}
+
+impl core::fmt::Debug for NodeContainer {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("NodeContainer").finish()
+	}
+}
+
+impl NodeContainer {
+	pub fn new(node: TypeErasedBox<'static>) -> SharedNodeContainer {
+		let node = Box::leak(node);
+		Self { node }.into()
+	}
+
+	#[cfg(feature = "dealloc_nodes")]
+	unsafe fn dealloc_unchecked(&mut self) {
+		std::mem::drop(Box::from_raw(self.node));
+	}
+}
+
+pub struct DynAnyNode<I, O, Node> {
+	node: Node,
+	_i: PhantomData<I>,
+	_o: PhantomData<O>,
+}
+
+impl<'input, _I: 'input + StaticType, _O: 'input + StaticType, N: 'input> Node<'input, Any<'input>> for DynAnyNode<_I, _O, N>
+where
+	N: Node<'input, _I, Output = DynFuture<'input, _O>>,
+{
+	type Output = FutureAny<'input>;
+	#[inline]
+	fn eval(&'input self, input: Any<'input>) -> Self::Output {
+		let node_name = core::any::type_name::<N>();
+		let input: Box<_I> = dyn_any::downcast(input).unwrap_or_else(|e| panic!("DynAnyNode Input, {0} in:\n{1}", e, node_name));
+		let output = async move {
+			let result = self.node.eval(*input).await;
+			Box::new(result) as Any<'input>
+		};
+		Box::pin(output)
+	}
+
+	fn reset(&self) {
+		self.node.reset();
+	}
+
+	fn serialize(&self) -> Option<std::sync::Arc<dyn core::any::Any>> {
+		self.node.serialize()
+	}
+}
+impl<'input, _I: 'input + StaticType, _O: 'input + StaticType, N: 'input> DynAnyNode<_I, _O, N>
+where
+	N: Node<'input, _I, Output = DynFuture<'input, _O>>,
+{
+	pub const fn new(node: N) -> Self {
+		Self {
+			node,
+			_i: core::marker::PhantomData,
+			_o: core::marker::PhantomData,
+		}
+	}
+}
+
+pub struct FutureWrapperNode<Node> {
+	node: Node,
+}
+
+impl<'i, T: 'i, N: Node<'i, T>> Node<'i, T> for FutureWrapperNode<N>
+where
+	N: Node<'i, T>,
+{
+	type Output = DynFuture<'i, N::Output>;
+	fn eval(&'i self, input: T) -> Self::Output {
+		Box::pin(async move { self.node.eval(input) })
+	}
+	fn reset(&self) {
+		self.node.reset();
+	}
+
+	fn serialize(&self) -> Option<std::sync::Arc<dyn core::any::Any>> {
+		self.node.serialize()
+	}
+}
+
+impl<N> FutureWrapperNode<N> {
+	pub const fn new(node: N) -> Self {
+		Self { node }
+	}
+}
+
+/// Boxes the input and downcasts the output.
+/// Wraps around a node taking Box<dyn DynAny> and returning Box<dyn DynAny>
+#[derive(Clone)]
+pub struct DowncastBothNode<I, O> {
+	node: SharedNodeContainer,
+	_i: PhantomData<I>,
+	_o: PhantomData<O>,
+}
+impl<'input, O: 'input + StaticType, I: 'input + StaticType> Node<'input, I> for DowncastBothNode<I, O> {
+	type Output = DynFuture<'input, O>;
+	#[inline]
+	fn eval(&'input self, input: I) -> Self::Output {
+		{
+			let node_name = self.node.node_name();
+			let input = Box::new(input);
+			let future = self.node.eval(input);
+			Box::pin(async move {
+				let out = dyn_any::downcast(future.await).unwrap_or_else(|e| panic!("DowncastBothNode Input {e} in: \n{node_name}"));
+				*out
+			})
+		}
+	}
+}
+impl<I, O> DowncastBothNode<I, O> {
+	pub const fn new(node: SharedNodeContainer) -> Self {
+		Self {
+			node,
+			_i: core::marker::PhantomData,
+			_o: core::marker::PhantomData,
+		}
+	}
+}
+
+pub fn downcast_node<I: StaticType, O: StaticType>(n: SharedNodeContainer) -> DowncastBothNode<I, O> {
+	DowncastBothNode::new(n)
+}
+
+/// Describes one input of a registered node, the primary input being the first.
+pub struct FieldMetadata {
+	/// Name shown in the graph and the properties panel.
+	pub name: &'static str,
+	/// Value of the input when the node is first placed.
+	pub default_value: fn() -> Any<'static>,
+	/// Whether the input is connected to the graph rather than edited with a widget when the node is first placed.
+	pub exposed: bool,
+	/// Unit suffix of a number input's widget.
+	pub unit: Option<&'static str>,
+	/// Lower bound of a number input's widget.
+	pub min: Option<f64>,
+	/// Upper bound of a number input's widget.
+	pub max: Option<f64>,
+}
+
+/// Everything needed to construct a node and present it to the user, submitted by `#[node_macro::node_fn]` when it is given a `category`.
+pub struct NodeMetadata {
+	/// The proto node identifier, which is the path to the node struct with a `_` for each of its generic arguments.
+	pub identifier: &'static str,
+	pub display_name: &'static str,
+	pub category: &'static str,
+	pub inputs: &'static [FieldMetadata],
+	pub output_name: &'static str,
+	pub node_io: fn() -> NodeIOTypes,
+	pub constructor: NodeConstructor,
+}
+
+inventory::collect!(NodeMetadata);
+
+/// Iterates over the metadata of every node registered through `#[node_macro::node_fn]`, in no particular order.
+/// Each identifier is only given once, even if the constructors submitting the nodes ran more than once.
+pub fn registered_nodes() -> impl Iterator<Item = &'static NodeMetadata> {
+	let mut identifiers = HashSet::new();
+	inventory::iter::<NodeMetadata>.into_iter().filter(move |metadata| identifiers.insert(metadata.identifier))
+}
+
+/// Looks up the metadata of a registered node by its proto node identifier.
+pub fn node_metadata(identifier: &str) -> Option<&'static NodeMetadata> {
+	registered_nodes().find(|metadata| metadata.identifier == identifier)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::vector::style::LineJoin;
+	use crate::vector::VectorData;
+	use crate::Type;
+
+	#[test]
+	fn node_fn_registers_metadata() {
+		let metadata = node_metadata("graphene_core::vector::vector_nodes::OffsetPathNode<_, _, _>").expect("Offset Path should be registered");
+		assert_eq!(metadata.display_name, "Offset Path");
+		assert_eq!(metadata.category, "Vector");
+		assert_eq!(
+			metadata.inputs.iter().map(|input| input.name).collect::<Vec<_>>(),
+			["Vector Data", "Distance", "Line Join", "Miter Limit"]
+		);
+		assert!(metadata.inputs[0].exposed);
+		assert!(!metadata.inputs[1].exposed);
+		assert_eq!(metadata.inputs[1].unit, Some("px"));
+		assert_eq!(metadata.inputs[3].min, Some(1.));
+
+		let default_join = dyn_any::downcast::<LineJoin>((metadata.inputs[2].default_value)()).unwrap();
+		assert_eq!(*default_join, LineJoin::Miter);
+
+		let node_io = (metadata.node_io)();
+		assert_eq!(node_io.input, Type::new::<VectorData>());
+		assert_eq!(node_io.output, Type::new::<VectorData>());
+		assert_eq!(node_io.parameters.len(), 3);
+	}
+}
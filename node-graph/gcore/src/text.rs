@@ -1,21 +1,96 @@
 mod font_cache;
+mod local_fonts;
 mod to_path;
+mod typesetting;
 
 use crate::application_io::EditorApi;
 pub use font_cache::*;
+pub use local_fonts::*;
 use node_macro::node_fn;
 pub use to_path::*;
+pub use typesetting::{FontFeatures, TextAlignment, TypesettingConfig};
 
 use crate::Node;
 
-pub struct TextGenerator<Text, FontName, Size> {
+pub struct TextGenerator<Text, FontName, Size, LineHeightRatio, CharacterSpacing, WordSpacing, ParagraphSpacing, MaxWidth, Align, Features> {
 	text: Text,
 	font_name: FontName,
 	font_size: Size,
+	line_height_ratio: LineHeightRatio,
+	character_spacing: CharacterSpacing,
+	word_spacing: WordSpacing,
+	paragraph_spacing: ParagraphSpacing,
+	max_width: MaxWidth,
+	align: Align,
+	features: Features,
 }
 
 #[node_fn(TextGenerator)]
-fn generate_text<'a: 'input, T>(editor: EditorApi<'a, T>, text: String, font_name: Font, font_size: f64) -> crate::vector::VectorData {
-	let buzz_face = editor.font_cache.get(&font_name).map(|data| load_face(data));
-	crate::vector::VectorData::from_subpaths(to_path(&text, buzz_face, font_size, None))
+fn generate_text<'a: 'input, T>(
+	editor: EditorApi<'a, T>,
+	text: String,
+	font_name: Font,
+	font_size: f64,
+	line_height_ratio: f64,
+	character_spacing: f64,
+	word_spacing: f64,
+	paragraph_spacing: f64,
+	max_width: f64,
+	align: TextAlignment,
+	features: FontFeatures,
+) -> crate::vector::VectorData {
+	let fonts = editor.font_cache.get_with_fallbacks(&font_name);
+	let faces = fonts.iter().map(|font| font.face()).collect::<Vec<_>>();
+	let config = TypesettingConfig {
+		font_size,
+		line_height_ratio,
+		character_spacing,
+		word_spacing,
+		paragraph_spacing,
+		// A width of zero disables wrapping
+		max_width: (max_width > 0.).then_some(max_width),
+		align,
+		features,
+	};
+	crate::vector::VectorData::from_subpaths(to_path(&text, &faces, &config))
+}
+
+pub struct TextOnPathNode<Path, Text, FontName, Size, StartOffset, Align, Flip, CharacterSpacing> {
+	path: Path,
+	text: Text,
+	font_name: FontName,
+	font_size: Size,
+	start_offset: StartOffset,
+	align: Align,
+	flip: Flip,
+	character_spacing: CharacterSpacing,
+}
+
+#[node_fn(TextOnPathNode)]
+fn generate_text_on_path<'a: 'input, T>(
+	editor: EditorApi<'a, T>,
+	path: crate::vector::VectorData,
+	text: String,
+	font_name: Font,
+	font_size: f64,
+	start_offset: f64,
+	align: TextAlignment,
+	flip: bool,
+	character_spacing: f64,
+) -> crate::vector::VectorData {
+	// The text follows the first subpath, in the same space as the shape it was drawn from
+	let Some(mut subpath) = path.subpaths.into_iter().find(|subpath| subpath.len_segments() > 0) else {
+		return crate::vector::VectorData::empty();
+	};
+	subpath.apply_transform(path.transform);
+
+	let fonts = editor.font_cache.get_with_fallbacks(&font_name);
+	let faces = fonts.iter().map(|font| font.face()).collect::<Vec<_>>();
+	let config = TypesettingConfig {
+		font_size,
+		character_spacing,
+		align,
+		..Default::default()
+	};
+	crate::vector::VectorData::from_subpaths(to_path_along(&text, &faces, &config, &subpath, start_offset, flip))
 }
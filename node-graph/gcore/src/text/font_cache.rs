@@ -1,6 +1,7 @@
 use dyn_any::{DynAny, StaticType};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 /// A font type (storing font family and font style and an optional preview URL)
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq, DynAny, specta::Type)]
@@ -16,15 +17,55 @@ impl Font {
 	}
 }
 
+/// A source of font files which are loaded on demand when a font isn't already in the [FontCache], such as the fonts installed on the system
+pub trait FontProvider: Send + Sync + core::fmt::Debug {
+	/// Every font this provider is able to load
+	fn fonts(&self) -> Vec<Font>;
+	/// Picks the font which best matches the requested family and style, if the provider has that family
+	fn resolve(&self, font: &Font) -> Option<Font>;
+	/// Reads the file data of a font returned by [FontProvider::resolve], along with the index of the face within the file for font collections
+	fn load(&self, font: &Font) -> Option<(Vec<u8>, u32)>;
+}
+
+/// A font face parsed once when it is loaded so that text can be shaped without parsing the font file again on every evaluation.
+/// It owns the font file data the face borrows from, so the data is freed along with the face once no clone of the [FontCache] uses it.
+pub struct FontFace {
+	/// Borrows from `_data`, so it is declared first to be dropped before the data
+	face: rustybuzz::Face<'static>,
+	/// The font file data, which stays at the same address on the heap for as long as the face borrows from it since it is never mutated or replaced
+	_data: Arc<[u8]>,
+}
+impl FontFace {
+	/// Parses a face from font file data, keeping the data alongside it
+	fn parse(data: Vec<u8>, index: u32) -> Option<Arc<Self>> {
+		let data: Arc<[u8]> = data.into();
+		// SAFETY: The slice points into the heap allocation of `data`, which is stored with the face and outlives it, and `face` only lends the face out for the lifetime of `self`
+		let slice: &'static [u8] = unsafe { core::slice::from_raw_parts(data.as_ptr(), data.len()) };
+		let face = rustybuzz::Face::from_slice(slice, index)?;
+		Some(Arc::new(Self { face, _data: data }))
+	}
+
+	/// The parsed face, which can only be borrowed for as long as the font file data it refers to
+	pub fn face(&self) -> &rustybuzz::Face<'_> {
+		&self.face
+	}
+}
+
 /// A cache of all loaded font data and preview urls along with the default font (send from `init_app` in `editor_api.rs`)
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Clone, Default)]
 pub struct FontCache {
-	/// Actual font file data used for rendering a font with ttf_parser and rustybuzz
-	font_file_data: HashMap<Font, Vec<u8>>,
+	/// Parsed faces of the font files sent by the frontend, used for rendering a font with ttf_parser and rustybuzz
+	font_faces: HashMap<Font, Arc<FontFace>>,
 	/// Web font preview URLs used for showing fonts when live editing
 	preview_urls: HashMap<Font, String>,
 	/// The default font (used as a fallback)
 	default_font: Option<Font>,
+	/// Fonts tried in order for characters which are missing from the requested font, such as CJK characters or emoji
+	fallback_fonts: Vec<Font>,
+	/// Sources of fonts which aren't sent by the frontend, such as local font files when running outside the browser
+	providers: Vec<Arc<dyn FontProvider>>,
+	/// Faces loaded from the providers so far, keyed by the font they resolved the request to and shared between clones of the cache
+	provided_faces: Arc<Mutex<HashMap<Font, Option<Arc<FontFace>>>>>,
 }
 impl FontCache {
 	/// Returns the font family name if the font is cached, otherwise returns the default font family name if that is cached
@@ -36,14 +77,52 @@ impl FontCache {
 		}
 	}
 
-	/// Try to get the bytes for a font
-	pub fn get<'a>(&'a self, font: &Font) -> Option<&'a Vec<u8>> {
-		self.resolve_font(font).and_then(|font| self.font_file_data.get(font))
+	/// Try to get the parsed face for a font, loading it from the providers if it wasn't sent by the frontend and otherwise falling back to the default font
+	pub fn get(&self, font: &Font) -> Option<Arc<FontFace>> {
+		self.font_faces
+			.get(font)
+			.cloned()
+			.or_else(|| self.provided_face(font))
+			.or_else(|| self.default_font.as_ref().and_then(|font| self.font_faces.get(font).cloned()))
+	}
+
+	/// The face for the requested font followed by the faces of the fallback fonts which are available, in the order they should be tried for each character
+	pub fn get_with_fallbacks(&self, font: &Font) -> Vec<Arc<FontFace>> {
+		let mut faces: Vec<Arc<FontFace>> = Vec::new();
+		let fallbacks = self.fallback_fonts.iter().filter_map(|font| self.font_faces.get(font).cloned().or_else(|| self.provided_face(font)));
+		for face in self.get(font).into_iter().chain(fallbacks) {
+			if !faces.iter().any(|existing| Arc::ptr_eq(existing, &face)) {
+				faces.push(face);
+			}
+		}
+		faces
+	}
+
+	fn provided_face(&self, font: &Font) -> Option<Arc<FontFace>> {
+		let (provider, resolved) = self.providers.iter().find_map(|provider| Some((provider, provider.resolve(font)?)))?;
+
+		let mut provided_faces = self.provided_faces.lock().unwrap();
+		provided_faces
+			.entry(resolved)
+			.or_insert_with_key(|resolved| {
+				let (data, index) = provider.load(resolved)?;
+				let face = FontFace::parse(data, index);
+				if face.is_none() {
+					log::warn!("Failed to parse the font file of {} {}", resolved.font_family, resolved.font_style);
+				}
+				face
+			})
+			.clone()
 	}
 
 	/// Check if the font is already loaded
 	pub fn loaded_font(&self, font: &Font) -> bool {
-		self.font_file_data.contains_key(font)
+		self.font_faces.contains_key(font)
+	}
+
+	/// Check if the font is loaded or can be loaded from one of the providers, so it doesn't need to be requested from the frontend
+	pub fn available(&self, font: &Font) -> bool {
+		self.loaded_font(font) || self.providers.iter().any(|provider| provider.resolve(font).is_some())
 	}
 
 	/// Insert a new font into the cache
@@ -51,10 +130,27 @@ impl FontCache {
 		if is_default {
 			self.default_font = Some(font.clone());
 		}
-		self.font_file_data.insert(font.clone(), data);
+		if !self.loaded_font(&font) {
+			match FontFace::parse(data, 0) {
+				Some(face) => {
+					self.font_faces.insert(font.clone(), face);
+				}
+				None => log::warn!("Failed to parse the font file of {} {}", font.font_family, font.font_style),
+			}
+		}
 		self.preview_urls.insert(font, perview_url);
 	}
 
+	/// Adds a source of fonts which are loaded when they are first used
+	pub fn add_provider(&mut self, provider: Arc<dyn FontProvider>) {
+		self.providers.push(provider);
+	}
+
+	/// Sets the fonts tried in order for characters missing from the requested font
+	pub fn set_fallback_fonts(&mut self, fallback_fonts: Vec<Font>) {
+		self.fallback_fonts = fallback_fonts;
+	}
+
 	/// Checks if the font cache has a default font
 	pub fn has_default(&self) -> bool {
 		self.default_font.is_some()
@@ -66,6 +162,27 @@ impl FontCache {
 	}
 }
 
+impl core::fmt::Debug for FontCache {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("FontCache")
+			.field("font_faces", &self.font_faces.keys().collect::<Vec<_>>())
+			.field("preview_urls", &self.preview_urls)
+			.field("default_font", &self.default_font)
+			.field("fallback_fonts", &self.fallback_fonts)
+			.field("providers", &self.providers)
+			.finish()
+	}
+}
+
+/// Caches are equal when they contain the same fonts, since the data of a font never changes once it is loaded
+impl PartialEq for FontCache {
+	fn eq(&self, other: &Self) -> bool {
+		let same_fonts = self.font_faces.len() == other.font_faces.len() && self.font_faces.keys().all(|font| other.font_faces.contains_key(font));
+		let same_providers = self.providers.len() == other.providers.len() && self.providers.iter().zip(&other.providers).all(|(a, b)| Arc::ptr_eq(a, b));
+		same_fonts && same_providers && self.preview_urls == other.preview_urls && self.default_font == other.default_font && self.fallback_fonts == other.fallback_fonts
+	}
+}
+
 impl core::hash::Hash for FontCache {
 	fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
 		self.preview_urls.len().hash(state);
@@ -73,7 +190,122 @@ impl core::hash::Hash for FontCache {
 			font.hash(state);
 			url.hash(state)
 		});
-		self.font_file_data.len().hash(state);
-		self.font_file_data.keys().for_each(|font| font.hash(state));
+		self.font_faces.len().hash(state);
+		self.font_faces.keys().for_each(|font| font.hash(state));
+		self.fallback_fonts.hash(state);
+		self.providers.len().hash(state);
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::text::to_path::test::{hebrew_font, latin_font, ALEF};
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	/// Provides fonts from memory, resolving a request to the first font of the same family and counting how many files it loads
+	#[derive(Debug, Default)]
+	struct MockProvider {
+		fonts: Vec<(Font, Vec<u8>)>,
+		loads: AtomicUsize,
+	}
+
+	impl MockProvider {
+		fn new(fonts: impl IntoIterator<Item = (Font, Vec<u8>)>) -> Self {
+			Self {
+				fonts: fonts.into_iter().collect(),
+				loads: AtomicUsize::new(0),
+			}
+		}
+	}
+
+	impl FontProvider for MockProvider {
+		fn fonts(&self) -> Vec<Font> {
+			self.fonts.iter().map(|(font, _)| font.clone()).collect()
+		}
+
+		fn resolve(&self, font: &Font) -> Option<Font> {
+			let (resolved, _) = self.fonts.iter().find(|(candidate, _)| candidate.font_family == font.font_family)?;
+			Some(resolved.clone())
+		}
+
+		fn load(&self, font: &Font) -> Option<(Vec<u8>, u32)> {
+			self.loads.fetch_add(1, Ordering::Relaxed);
+			let (_, data) = self.fonts.iter().find(|(candidate, _)| candidate == font)?;
+			Some((data.clone(), 0))
+		}
+	}
+
+	fn font(family: &str, style: &str) -> Font {
+		Font::new(family.to_string(), style.to_string())
+	}
+
+	/// Whether a face has a glyph for the character
+	fn has_glyph(face: &FontFace, character: char) -> bool {
+		face.face().glyph_index(character).is_some()
+	}
+
+	#[test]
+	fn provided_fonts_are_resolved_and_loaded_once() {
+		let provider = Arc::new(MockProvider::new([(font("Latin", "Bold (700)"), latin_font())]));
+		let mut cache = FontCache::default();
+		cache.add_provider(provider.clone());
+
+		let requested = font("Latin", "Normal (400)");
+		assert!(cache.available(&requested));
+		assert!(!cache.loaded_font(&requested));
+		assert!(!cache.available(&font("Hebrew", "Normal (400)")));
+
+		let face = cache.get(&requested).unwrap();
+		assert!(has_glyph(&face, 'a'));
+		// Other styles of the family resolve to the same font, which is only loaded the first time
+		let again = cache.clone().get(&font("Latin", "Italic (400)")).unwrap();
+		assert!(Arc::ptr_eq(&face, &again));
+		assert_eq!(provider.loads.load(Ordering::Relaxed), 1);
+	}
+
+	#[test]
+	fn unparsable_provided_fonts_are_not_retried() {
+		let provider = Arc::new(MockProvider::new([(font("Broken", "Normal (400)"), b"not a font".to_vec())]));
+		let mut cache = FontCache::default();
+		cache.add_provider(provider.clone());
+
+		assert!(cache.get(&font("Broken", "Normal (400)")).is_none());
+		assert!(cache.get_with_fallbacks(&font("Broken", "Normal (400)")).is_empty());
+		assert_eq!(provider.loads.load(Ordering::Relaxed), 1);
+	}
+
+	#[test]
+	fn fallbacks_follow_the_requested_font_without_duplicates() {
+		let provider = MockProvider::new([(font("Latin", "Normal (400)"), latin_font()), (font("Hebrew", "Normal (400)"), hebrew_font())]);
+		let mut cache = FontCache::default();
+		cache.add_provider(Arc::new(provider));
+		cache.set_fallback_fonts(vec![font("Hebrew", "Normal (400)"), font("Missing", "Normal (400)"), font("Latin", "Normal (400)")]);
+
+		let faces = cache.get_with_fallbacks(&font("Latin", "Bold (700)"));
+		assert_eq!(faces.len(), 2);
+		assert!(has_glyph(&faces[0], 'a'));
+		assert!(has_glyph(&faces[1], ALEF));
+
+		// A font which can't be loaded only has the fallbacks
+		let faces = cache.get_with_fallbacks(&font("Missing", "Normal (400)"));
+		assert_eq!(faces.len(), 2);
+		assert!(has_glyph(&faces[0], ALEF));
+		assert!(has_glyph(&faces[1], 'a'));
+	}
+
+	#[test]
+	fn frontend_fonts_come_before_providers() {
+		let provider = Arc::new(MockProvider::new([(font("Latin", "Normal (400)"), hebrew_font())]));
+		let mut cache = FontCache::default();
+		cache.add_provider(provider.clone());
+		cache.insert(font("Latin", "Normal (400)"), String::new(), latin_font(), true);
+
+		let faces = cache.get_with_fallbacks(&font("Latin", "Normal (400)"));
+		assert_eq!(faces.len(), 1);
+		assert!(has_glyph(&faces[0], 'a'));
+		// Unknown fonts use the default font sent by the frontend
+		assert!(has_glyph(&cache.get(&font("Unknown", "Normal (400)")).unwrap(), 'a'));
+		assert_eq!(provider.loads.load(Ordering::Relaxed), 0);
 	}
 }
@@ -0,0 +1,258 @@
+use super::{Font, FontProvider};
+
+use rustybuzz::ttf_parser::{self, name_id};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Families tried in order for characters missing from the requested font, covering the scripts and emoji of the fonts commonly installed on each platform
+pub const FALLBACK_FAMILIES: &[&str] = &[
+	"Noto Sans",
+	"Noto Sans CJK SC",
+	"Noto Sans Arabic",
+	"Noto Sans Hebrew",
+	"Noto Sans Devanagari",
+	"Noto Emoji",
+	"DejaVu Sans",
+	"Segoe UI",
+	"Segoe UI Symbol",
+	"Microsoft YaHei",
+	"Arial Unicode MS",
+	"Helvetica Neue",
+	"PingFang SC",
+];
+
+/// The regular style of each of the [FALLBACK_FAMILIES], which providers resolve to the closest style they have
+pub fn fallback_fonts() -> Vec<Font> {
+	FALLBACK_FAMILIES.iter().map(|family| Font::new(family.to_string(), style_name(400, false))).collect()
+}
+
+const WEIGHT_NAMES: [(u16, &str); 10] = [
+	(100, "Thin"),
+	(200, "Extra Light"),
+	(300, "Light"),
+	(400, "Normal"),
+	(500, "Medium"),
+	(600, "Semi Bold"),
+	(700, "Bold"),
+	(800, "Extra Bold"),
+	(900, "Black"),
+	(950, "Extra Black"),
+];
+
+/// The name the frontend gives a font style, such as "Semi Bold Italic (600)"
+pub fn style_name(weight: u16, italic: bool) -> String {
+	let (_, weight_name) = WEIGHT_NAMES.iter().min_by_key(|(named_weight, _)| named_weight.abs_diff(weight)).unwrap();
+	let italic = if italic { " Italic" } else { "" };
+	format!("{weight_name}{italic} ({weight})")
+}
+
+/// The weight and whether it is italic of a style named by [style_name], assuming a normal weight if it isn't given
+fn parse_style(style: &str) -> (u16, bool) {
+	let weight = style.rsplit_once('(').and_then(|(_, weight)| weight.trim_end_matches(')').parse().ok()).unwrap_or(400);
+	(weight, style.contains("Italic"))
+}
+
+/// The font of each face in the data of a font file, which holds several faces if it is a font collection
+pub fn fonts_in_file(data: &[u8]) -> Vec<(Font, u32)> {
+	let count = ttf_parser::fonts_in_collection(data).unwrap_or(1);
+	(0..count)
+		.filter_map(|index| {
+			let face = ttf_parser::Face::parse(data, index).ok()?;
+			Some((font_of_face(&face)?, index))
+		})
+		.collect()
+}
+
+fn font_of_face(face: &ttf_parser::Face) -> Option<Font> {
+	let name = |id: u16| face.names().into_iter().filter(|name| name.name_id == id && name.is_unicode()).find_map(|name| name.to_string());
+	let family = name(name_id::TYPOGRAPHIC_FAMILY).or_else(|| name(name_id::FAMILY))?;
+	Some(Font::new(family, style_name(face.weight().to_number(), face.is_italic())))
+}
+
+/// The directories where the operating system and the current user install fonts
+pub fn system_font_directories() -> Vec<PathBuf> {
+	let home = std::env::var_os("HOME").map(PathBuf::from);
+	let mut directories = Vec::new();
+
+	if cfg!(target_os = "windows") {
+		let windows = std::env::var_os("WINDIR").map_or_else(|| PathBuf::from("C:\\Windows"), PathBuf::from);
+		directories.push(windows.join("Fonts"));
+		if let Some(local_app_data) = std::env::var_os("LOCALAPPDATA") {
+			directories.push(PathBuf::from(local_app_data).join("Microsoft").join("Windows").join("Fonts"));
+		}
+	} else if cfg!(target_os = "macos") {
+		directories.extend(["/System/Library/Fonts", "/Library/Fonts"].map(PathBuf::from));
+		directories.extend(home.map(|home| home.join("Library").join("Fonts")));
+	} else {
+		directories.extend(["/usr/share/fonts", "/usr/local/share/fonts"].map(PathBuf::from));
+		let data_home = std::env::var_os("XDG_DATA_HOME")
+			.map(PathBuf::from)
+			.or_else(|| home.as_ref().map(|home| home.join(".local").join("share")));
+		directories.extend(data_home.map(|data_home| data_home.join("fonts")));
+		directories.extend(home.map(|home| home.join(".fonts")));
+	}
+
+	directories
+}
+
+/// A face within a local font file
+#[derive(Debug, Clone, PartialEq)]
+struct LocalFace {
+	path: PathBuf,
+	index: u32,
+	/// Condensed and expanded faces often share the family and style name of the normal width face, which is preferred over them
+	normal_width: bool,
+}
+
+/// Provides the fonts found in a set of local font files and directories, such as the fonts installed on the system
+#[derive(Debug, Default)]
+pub struct LocalFontProvider {
+	/// The font files and directories, which are searched recursively
+	paths: Vec<PathBuf>,
+	/// The faces found in the paths, which are only searched the first time a font is requested
+	faces: Mutex<Option<HashMap<Font, LocalFace>>>,
+}
+
+impl LocalFontProvider {
+	pub fn new(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+		Self {
+			paths: paths.into_iter().collect(),
+			faces: Mutex::new(None),
+		}
+	}
+
+	/// The provider of the fonts installed in the operating system's font directories
+	pub fn system() -> Self {
+		Self::new(system_font_directories())
+	}
+
+	fn with_faces<T>(&self, f: impl FnOnce(&HashMap<Font, LocalFace>) -> T) -> T {
+		let mut faces = self.faces.lock().unwrap();
+		let faces = faces.get_or_insert_with(|| {
+			let mut faces = HashMap::new();
+			let mut visited_directories = HashSet::new();
+			self.paths.iter().for_each(|path| index_path(path, &mut visited_directories, &mut faces));
+			faces
+		});
+		f(faces)
+	}
+}
+
+fn is_font_file(path: &Path) -> bool {
+	let extension = path.extension().and_then(|extension| extension.to_str()).map(|extension| extension.to_ascii_lowercase());
+	matches!(extension.as_deref(), Some("ttf" | "otf" | "ttc" | "otc"))
+}
+
+/// Adds the faces of a font file, or of the font files within a directory and its subdirectories, skipping directories already reached through another path so that symlink cycles end
+fn index_path(path: &Path, visited_directories: &mut HashSet<PathBuf>, faces: &mut HashMap<Font, LocalFace>) {
+	if path.is_dir() {
+		let Ok(canonical) = std::fs::canonicalize(path) else { return };
+		if !visited_directories.insert(canonical) {
+			return;
+		}
+		let Ok(entries) = std::fs::read_dir(path) else { return };
+		let mut paths = entries.filter_map(|entry| Some(entry.ok()?.path())).collect::<Vec<_>>();
+		// Sort so the same face wins on every run when several files share a font name
+		paths.sort();
+		paths.iter().for_each(|path| index_path(path, visited_directories, faces));
+		return;
+	}
+
+	if !is_font_file(path) {
+		return;
+	}
+	let Ok(data) = std::fs::read(path) else { return };
+	for (font, index) in fonts_in_file(&data) {
+		let normal_width = ttf_parser::Face::parse(&data, index).map_or(false, |face| face.width() == ttf_parser::Width::Normal);
+		let face = LocalFace {
+			path: path.to_path_buf(),
+			index,
+			normal_width,
+		};
+		match faces.get(&font) {
+			Some(existing) if existing.normal_width || !normal_width => {}
+			_ => {
+				faces.insert(font, face);
+			}
+		}
+	}
+}
+
+impl FontProvider for LocalFontProvider {
+	fn fonts(&self) -> Vec<Font> {
+		self.with_faces(|faces| faces.keys().cloned().collect())
+	}
+
+	fn resolve(&self, font: &Font) -> Option<Font> {
+		self.with_faces(|faces| {
+			if faces.contains_key(font) {
+				return Some(font.clone());
+			}
+
+			// Otherwise pick the style of the family which is closest in slant and then weight
+			let (weight, italic) = parse_style(&font.font_style);
+			faces
+				.keys()
+				.filter(|candidate| candidate.font_family.eq_ignore_ascii_case(&font.font_family))
+				.min_by_key(|candidate| {
+					let (candidate_weight, candidate_italic) = parse_style(&candidate.font_style);
+					(candidate_italic != italic, candidate_weight.abs_diff(weight), candidate.font_style.clone())
+				})
+				.cloned()
+		})
+	}
+
+	fn load(&self, font: &Font) -> Option<(Vec<u8>, u32)> {
+		let face = self.with_faces(|faces| faces.get(font).cloned())?;
+		let data = std::fs::read(&face.path).ok()?;
+		Some((data, face.index))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn style_names_match_the_frontend() {
+		assert_eq!(style_name(400, false), "Normal (400)");
+		assert_eq!(style_name(600, true), "Semi Bold Italic (600)");
+		assert_eq!(style_name(350, false), "Light (350)");
+		assert_eq!(parse_style("Semi Bold Italic (600)"), (600, true));
+		assert_eq!(parse_style("Normal (400)"), (400, false));
+		assert_eq!(parse_style("Regular"), (400, false));
+	}
+
+	#[test]
+	fn only_font_files_are_indexed() {
+		assert!(is_font_file(Path::new("fonts/Example.TTF")));
+		assert!(is_font_file(Path::new("fonts/Example.otc")));
+		assert!(!is_font_file(Path::new("fonts/Example.woff2")));
+		assert!(fonts_in_file(b"not a font").is_empty());
+	}
+
+	#[test]
+	fn missing_directories_provide_no_fonts() {
+		let provider = LocalFontProvider::new([PathBuf::from("/nonexistent/font/directory")]);
+		assert!(provider.fonts().is_empty());
+		assert_eq!(provider.resolve(&Font::new("Noto Sans".into(), "Normal (400)".into())), None);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn symlink_cycles_are_indexed_once() {
+		let directory = std::env::temp_dir().join(format!("graphite-font-symlink-cycle-{}", std::process::id()));
+		let nested = directory.join("nested");
+		std::fs::create_dir_all(&nested).unwrap();
+		std::os::unix::fs::symlink(&directory, nested.join("parent")).unwrap();
+
+		let mut visited_directories = HashSet::new();
+		let mut faces = HashMap::new();
+		index_path(&directory, &mut visited_directories, &mut faces);
+		std::fs::remove_dir_all(&directory).unwrap();
+
+		assert!(faces.is_empty());
+		assert_eq!(visited_directories.len(), 2);
+	}
+}
@@ -1,24 +1,40 @@
 use crate::uuid::ManipulatorGroupId;
 
-use bezier_rs::{ManipulatorGroup, Subpath};
+use bezier_rs::{ManipulatorGroup, Subpath, SubpathTValue};
 
-use glam::DVec2;
-use rustybuzz::ttf_parser::{GlyphId, OutlineBuilder};
-use rustybuzz::{GlyphBuffer, UnicodeBuffer};
+use super::typesetting::{align_line, break_lines, word_ranges, TypesettingConfig, Word};
+
+use core::ops::{Deref, Range};
+use glam::{DAffine2, DVec2};
+use rustybuzz::ttf_parser::{GlyphId, OutlineBuilder, Tag};
+use rustybuzz::{Direction, Feature, GlyphBuffer, UnicodeBuffer};
+use unicode_bidi::BidiInfo;
 
 struct Builder {
 	current_subpath: Subpath<ManipulatorGroupId>,
 	other_subpaths: Vec<Subpath<ManipulatorGroupId>>,
 	pos: DVec2,
 	offset: DVec2,
+	/// The ascender of the primary face in pixels, which places the top of the first line at the origin
 	ascender: f64,
+	/// The scale from the units of the face being outlined to pixels
 	scale: f64,
+	/// Applied to the laid out glyph, such as to orient it along a path
+	transform: DAffine2,
 	id: ManipulatorGroupId,
 }
 
 impl Builder {
 	fn point(&self, x: f32, y: f32) -> DVec2 {
-		self.pos + self.offset + DVec2::new(x as f64, self.ascender - y as f64) * self.scale
+		self.transform
+			.transform_point2(self.pos + self.offset + DVec2::new(x as f64 * self.scale, self.ascender - y as f64 * self.scale))
+	}
+
+	fn outline_glyph(&mut self, face: &rustybuzz::Face, glyph: GlyphId) {
+		face.outline_glyph(glyph, self);
+		if !self.current_subpath.is_empty() {
+			self.other_subpaths.push(core::mem::replace(&mut self.current_subpath, Subpath::new(Vec::new(), false)));
+		}
 	}
 }
 
@@ -53,125 +69,559 @@ impl OutlineBuilder for Builder {
 	}
 }
 
-fn font_properties(buzz_face: &rustybuzz::Face, font_size: f64) -> (f64, f64, UnicodeBuffer) {
-	let scale = (buzz_face.units_per_em() as f64).recip() * font_size;
-	let line_height = font_size;
-	let buffer = UnicodeBuffer::new();
-	(scale, line_height, buffer)
+/// A glyph positioned relative to the start of its line, before alignment is applied
+struct ShapedGlyph {
+	id: GlyphId,
+	/// The index of the face the glyph comes from within the fallback chain
+	face: usize,
+	x: f64,
+	advance: f64,
+	offset: DVec2,
+	whitespace: bool,
+}
+
+/// A line of glyphs in visual order, which is left to right regardless of the direction of the script
+struct ShapedLine {
+	glyphs: Vec<ShapedGlyph>,
+	width: f64,
+	spaces: usize,
+	y: f64,
+	last_in_paragraph: bool,
 }
 
-fn push_str(buffer: &mut UnicodeBuffer, word: &str, trailing_space: bool) {
-	buffer.push_str(word);
+struct Layout {
+	lines: Vec<ShapedLine>,
+	bounds: DVec2,
+}
 
-	if trailing_space {
-		buffer.push_str(" ");
-	}
+/// Characters which are kept in the same face as the character before them, if it has them, so that clusters aren't split between faces
+fn continues_cluster(character: char) -> bool {
+	character.is_whitespace() || matches!(character, '\u{0300}'..='\u{036F}' | '\u{200C}'..='\u{200D}' | '\u{FE00}'..='\u{FE0F}' | '\u{1F3FB}'..='\u{1F3FF}' | '\u{E0100}'..='\u{E01EF}')
 }
 
-fn wrap_word(line_width: Option<f64>, glyph_buffer: &GlyphBuffer, scale: f64, x_pos: f64) -> bool {
-	if let Some(line_width) = line_width {
-		let word_length: i32 = glyph_buffer.glyph_positions().iter().map(|pos| pos.x_advance).sum();
-		let scaled_word_length = word_length as f64 * scale;
+fn is_whitespace(text: &str, cluster: u32) -> bool {
+	text.get(cluster as usize..).and_then(|rest| rest.chars().next()).map_or(false, char::is_whitespace)
+}
+
+/// Shapes text with a chain of faces, using the first face which has a glyph for each character
+struct Shaper<'a, 'f> {
+	faces: Vec<&'a rustybuzz::Face<'f>>,
+	scales: Vec<f64>,
+	features: Vec<Feature>,
+	config: &'a TypesettingConfig,
+}
 
-		if scaled_word_length + x_pos > line_width {
-			return true;
+impl<'a, 'f> Shaper<'a, 'f> {
+	fn new(faces: Vec<&'a rustybuzz::Face<'f>>, config: &'a TypesettingConfig) -> Self {
+		let scales = faces.iter().map(|face| (face.units_per_em() as f64).recip() * config.font_size).collect();
+		let features = config
+			.features
+			.tags()
+			.into_iter()
+			.map(|(tag, enabled)| Feature::new(Tag::from_bytes(&tag), enabled as u32, ..))
+			.collect();
+		Self { faces, scales, features, config }
+	}
+
+	/// Splits text into the runs drawn with each face, in logical order
+	fn face_runs(&self, text: &str) -> Vec<(usize, Range<usize>)> {
+		let mut runs: Vec<(usize, Range<usize>)> = Vec::new();
+		for (index, character) in text.char_indices() {
+			let covers = |face: usize| self.faces[face].glyph_index(character).is_some();
+			let current = runs.last().map(|(face, _)| *face);
+			let face = match current {
+				Some(current) if continues_cluster(character) && covers(current) => current,
+				// Characters which no face has are drawn with the missing glyph of the current face
+				_ => (0..self.faces.len()).find(|&face| covers(face)).or(current).unwrap_or(0),
+			};
+
+			let end = index + character.len_utf8();
+			match runs.last_mut() {
+				Some((current, range)) if *current == face => range.end = end,
+				_ => runs.push((face, index..end)),
+			}
+		}
+		runs
+	}
+
+	fn shape(&self, face: usize, text: &str, right_to_left: Option<bool>) -> GlyphBuffer {
+		let mut buffer = UnicodeBuffer::new();
+		buffer.push_str(text);
+		if let Some(right_to_left) = right_to_left {
+			buffer.set_direction(if right_to_left { Direction::RightToLeft } else { Direction::LeftToRight });
+		}
+		buffer.guess_segment_properties();
+		rustybuzz::shape(self.faces[face], &self.features, buffer)
+	}
+
+	/// The advance of each glyph of the shaped text, including the character and word spacing
+	fn advances<'b>(&self, face: usize, glyph_buffer: &'b GlyphBuffer, text: &'b str) -> impl Iterator<Item = (f64, bool)> + 'b {
+		let scale = self.scales[face];
+		let TypesettingConfig { character_spacing, word_spacing, .. } = *self.config;
+		glyph_buffer.glyph_positions().iter().zip(glyph_buffer.glyph_infos()).map(move |(position, info)| {
+			let whitespace = is_whitespace(text, info.cluster);
+			let word_spacing = if whitespace { word_spacing } else { 0. };
+			(position.x_advance as f64 * scale + character_spacing + word_spacing, whitespace)
+		})
+	}
+
+	fn measure(&self, text: &str) -> f64 {
+		self.face_runs(text)
+			.into_iter()
+			.map(|(face, range)| {
+				let text = &text[range];
+				let glyph_buffer = self.shape(face, text, None);
+				self.advances(face, &glyph_buffer, text).map(|(advance, _)| advance).sum::<f64>()
+			})
+			.sum()
+	}
+
+	/// Splits a paragraph into the words which lines can be broken between. Words too wide to fit on a line are split into their characters.
+	fn words(&self, paragraph: &str) -> Vec<Word> {
+		let mut words = Vec::new();
+		for (content, full) in word_ranges(paragraph) {
+			let width = self.measure(&paragraph[content.clone()]);
+			let space = self.measure(&paragraph[content.end..full.end]);
+
+			if self.config.max_width.map_or(true, |max_width| width <= max_width) {
+				words.push(Word {
+					range: full,
+					width,
+					width_with_trailing_space: width + space,
+				});
+				continue;
+			}
+
+			for (index, character) in paragraph[content.clone()].char_indices() {
+				let start = content.start + index;
+				let end = start + character.len_utf8();
+				let width = self.measure(&paragraph[start..end]);
+				let (end, space) = if end == content.end { (full.end, space) } else { (end, 0.) };
+				words.push(Word {
+					range: start..end,
+					width,
+					width_with_trailing_space: width + space,
+				});
+			}
+		}
+		words
+	}
+
+	/// Shapes a line of a paragraph, reordering its runs of left-to-right and right-to-left text with the Unicode bidirectional algorithm
+	fn shape_line(&self, bidi: &BidiInfo, line: Range<usize>) -> ShapedLine {
+		let mut glyphs = Vec::new();
+		let mut x = 0.;
+		let mut spaces = 0;
+
+		for paragraph in &bidi.paragraphs {
+			let range = line.start.max(paragraph.range.start)..line.end.min(paragraph.range.end);
+			if range.is_empty() {
+				continue;
+			}
+
+			let (levels, runs) = bidi.visual_runs(paragraph, range);
+			for run in runs {
+				let right_to_left = levels[run.start].is_rtl();
+				let mut face_runs = self.face_runs(&bidi.text[run.clone()]);
+				// The glyphs of each face run are in visual order, so the runs themselves are reversed to be visually ordered too
+				if right_to_left {
+					face_runs.reverse();
+				}
+
+				for (face, range) in face_runs {
+					let text = &bidi.text[run.start + range.start..run.start + range.end];
+					let glyph_buffer = self.shape(face, text, Some(right_to_left));
+					let positions = glyph_buffer.glyph_positions().iter().zip(glyph_buffer.glyph_infos());
+
+					for ((position, info), (advance, whitespace)) in positions.zip(self.advances(face, &glyph_buffer, text)) {
+						glyphs.push(ShapedGlyph {
+							id: GlyphId(info.glyph_id as u16),
+							face,
+							x,
+							advance,
+							offset: DVec2::new(position.x_offset as f64, -position.y_offset as f64) * self.scales[face],
+							whitespace,
+						});
+						x += advance;
+						spaces += whitespace as usize;
+					}
+				}
+			}
+		}
+
+		ShapedLine {
+			glyphs,
+			width: x,
+			spaces,
+			y: 0.,
+			last_in_paragraph: false,
+		}
+	}
+
+	fn layout(&self, str: &str) -> Layout {
+		let line_height = self.config.line_height();
+
+		let mut lines = Vec::new();
+		let mut y = 0.;
+		for paragraph in str.split('\n') {
+			let words = self.words(paragraph);
+			let bidi = BidiInfo::new(paragraph, None);
+
+			let line_ranges = break_lines(&words, self.config.max_width);
+			let line_count = line_ranges.len();
+			for (index, word_range) in line_ranges.into_iter().enumerate() {
+				let start = words.get(word_range.start).map_or(0, |word| word.range.start);
+				let end = words[word_range].last().map_or(start, |word| word.range.end);
+				// Trailing whitespace hangs past the end of the line so it doesn't affect the alignment
+				let end = start + paragraph[start..end].trim_end().len();
+
+				let mut line = self.shape_line(&bidi, start..end);
+				line.y = y;
+				line.last_in_paragraph = index + 1 == line_count;
+				lines.push(line);
+
+				y += line_height;
+			}
+			y += self.config.paragraph_spacing;
+		}
+
+		let widest = lines.iter().map(|line| line.width).fold(0., f64::max);
+		let width = self.config.max_width.unwrap_or(widest);
+		let height = lines.last().map_or(0., |line| line.y + line_height);
+
+		Layout {
+			lines,
+			bounds: DVec2::new(width, height),
 		}
 	}
-	false
 }
 
-pub fn to_path(str: &str, buzz_face: Option<rustybuzz::Face>, font_size: f64, line_width: Option<f64>) -> Vec<Subpath<ManipulatorGroupId>> {
-	let buzz_face = match buzz_face {
-		Some(face) => face,
+/// Converts text to outlines using the first face, or the fallback faces after it for characters the first face doesn't have
+pub fn to_path<'f>(str: &str, faces: &[impl Deref<Target = rustybuzz::Face<'f>>], config: &TypesettingConfig) -> Vec<Subpath<ManipulatorGroupId>> {
+	let Some(primary_face) = faces.first() else {
 		// Show blank layer if font has not loaded
-		None => return vec![],
+		return vec![];
 	};
 
-	let (scale, line_height, mut buffer) = font_properties(&buzz_face, font_size);
+	let shaper = Shaper::new(faces.iter().map(|face| &**face).collect(), config);
+	let layout = shaper.layout(str);
 
 	let mut builder = Builder {
 		current_subpath: Subpath::new(Vec::new(), false),
 		other_subpaths: Vec::new(),
 		pos: DVec2::ZERO,
 		offset: DVec2::ZERO,
-		ascender: (buzz_face.ascender() as f64 / buzz_face.height() as f64) * font_size / scale,
-		scale,
+		ascender: (primary_face.ascender() as f64 / primary_face.height() as f64) * config.font_size,
+		scale: 1.,
+		transform: DAffine2::IDENTITY,
 		id: ManipulatorGroupId::ZERO,
 	};
 
-	for line in str.split('\n') {
-		let length = line.split(' ').count();
-		for (index, word) in line.split(' ').enumerate() {
-			push_str(&mut buffer, word, index != length - 1);
-			let glyph_buffer = rustybuzz::shape(&buzz_face, &[], buffer);
+	for line in &layout.lines {
+		let (offset, extra_space) = align_line(config.align, line.width, layout.bounds.x, line.spaces, line.last_in_paragraph);
+		let mut spaces = 0;
+		for glyph in &line.glyphs {
+			builder.pos = DVec2::new(offset + glyph.x + extra_space * spaces as f64, line.y);
+			builder.offset = glyph.offset;
+			builder.scale = shaper.scales[glyph.face];
+			builder.outline_glyph(shaper.faces[glyph.face], glyph.id);
+			spaces += glyph.whitespace as usize;
+		}
+	}
+	builder.other_subpaths
+}
 
-			if wrap_word(line_width, &glyph_buffer, scale, builder.pos.x) {
-				builder.pos = DVec2::new(0., builder.pos.y + line_height);
-			}
+/// The frame of a glyph centered at a distance along a path, which maps the glyph's baseline onto the tangent of the path.
+/// Closed paths wrap around, while glyphs past either end of an open path have no frame.
+fn frame_along_path(path: &Subpath<ManipulatorGroupId>, segment_lengths: &[f64], distance: f64) -> Option<DAffine2> {
+	let total_length: f64 = segment_lengths.iter().sum();
+	if total_length <= 0. {
+		return None;
+	}
+	let distance = if path.closed() { distance.rem_euclid(total_length) } else { distance };
+	if !(0. ..=total_length).contains(&distance) {
+		return None;
+	}
 
-			for (glyph_position, glyph_info) in glyph_buffer.glyph_positions().iter().zip(glyph_buffer.glyph_infos()) {
-				if let Some(line_width) = line_width {
-					if builder.pos.x + (glyph_position.x_advance as f64 * builder.scale) >= line_width {
-						builder.pos = DVec2::new(0., builder.pos.y + line_height);
-					}
-				}
-				builder.offset = DVec2::new(glyph_position.x_offset as f64, glyph_position.y_offset as f64) * builder.scale;
-				buzz_face.outline_glyph(GlyphId(glyph_info.glyph_id as u16), &mut builder);
-				if !builder.current_subpath.is_empty() {
-					builder.other_subpaths.push(core::mem::replace(&mut builder.current_subpath, Subpath::new(Vec::new(), false)));
-				}
+	let mut start = 0.;
+	for (segment_index, &length) in segment_lengths.iter().enumerate() {
+		if length > 0. && distance <= start + length {
+			// The error is a ratio of the segment length, so it's scaled to keep glyphs within a fraction of a pixel of their place
+			let t = SubpathTValue::EuclideanWithinError {
+				segment_index,
+				t: ((distance - start) / length).clamp(0., 1.),
+				error: 0.01 / length,
+			};
+			let tangent = path.tangent(t);
+			// Rotating the tangent a quarter turn gives the direction below the baseline, since y points down
+			return Some(DAffine2::from_cols(tangent, tangent.perp(), path.evaluate(t)));
+		}
+		start += length;
+	}
+	None
+}
 
-				builder.pos += DVec2::new(glyph_position.x_advance as f64, glyph_position.y_advance as f64) * builder.scale;
-			}
+/// Converts a single line of text to outlines which are placed glyph by glyph along a path, with their baselines following it.
+/// The text starts `start_offset` pixels along the path after applying the alignment of the config, which justifies the text by stretching its spaces.
+/// Flipping places the text on the other side of the path, running in the opposite direction.
+pub fn to_path_along<'f>(
+	str: &str,
+	faces: &[impl Deref<Target = rustybuzz::Face<'f>>],
+	config: &TypesettingConfig,
+	path: &Subpath<ManipulatorGroupId>,
+	start_offset: f64,
+	flip: bool,
+) -> Vec<Subpath<ManipulatorGroupId>> {
+	if faces.is_empty() || path.len_segments() == 0 {
+		return vec![];
+	}
+	let path = if flip { path.reverse() } else { path.clone() };
+	let segment_lengths = path.iter().map(|bezier| bezier.length(None)).collect::<Vec<_>>();
+	let path_length: f64 = segment_lengths.iter().sum();
 
-			buffer = glyph_buffer.clear();
-		}
-		builder.pos = DVec2::new(0., builder.pos.y + line_height);
+	let shaper = Shaper::new(faces.iter().map(|face| &**face).collect(), config);
+	let text = str.replace(['\n', '\r'], " ");
+	let line = shaper.shape_line(&BidiInfo::new(&text, None), 0..text.len());
+
+	let mut builder = Builder {
+		current_subpath: Subpath::new(Vec::new(), false),
+		other_subpaths: Vec::new(),
+		pos: DVec2::ZERO,
+		offset: DVec2::ZERO,
+		ascender: 0.,
+		scale: 1.,
+		transform: DAffine2::IDENTITY,
+		id: ManipulatorGroupId::ZERO,
+	};
+
+	let (offset, extra_space) = align_line(config.align, line.width, path_length - start_offset, line.spaces, false);
+	let mut spaces = 0;
+	for glyph in &line.glyphs {
+		let center = start_offset + offset + glyph.x + extra_space * spaces as f64 + glyph.advance / 2.;
+		spaces += glyph.whitespace as usize;
+		let Some(frame) = frame_along_path(&path, &segment_lengths, center) else { continue };
+
+		// Each glyph is outlined around the center of its advance, which is then placed on the path
+		builder.offset = glyph.offset - DVec2::new(glyph.advance / 2., 0.);
+		builder.scale = shaper.scales[glyph.face];
+		builder.transform = frame;
+		builder.outline_glyph(shaper.faces[glyph.face], glyph.id);
 	}
 	builder.other_subpaths
 }
 
-pub fn bounding_box(str: &str, buzz_face: Option<rustybuzz::Face>, font_size: f64, line_width: Option<f64>) -> DVec2 {
-	let buzz_face = match buzz_face {
-		Some(face) => face,
+pub fn bounding_box<'f>(str: &str, faces: &[impl Deref<Target = rustybuzz::Face<'f>>], config: &TypesettingConfig) -> DVec2 {
+	if faces.is_empty() {
 		// Show blank layer if font has not loaded
-		None => return DVec2::ZERO,
-	};
+		return DVec2::ZERO;
+	}
 
-	let (scale, line_height, mut buffer) = font_properties(&buzz_face, font_size);
+	Shaper::new(faces.iter().map(|face| &**face).collect(), config).layout(str).bounds
+}
 
-	let mut pos = DVec2::ZERO;
-	let mut bounds = DVec2::ZERO;
+#[cfg(test)]
+pub(super) mod test {
+	use super::*;
+	use crate::text::typesetting::FontFeatures;
 
-	for line in str.split('\n') {
-		let length = line.split(' ').count();
-		for (index, word) in line.split(' ').enumerate() {
-			push_str(&mut buffer, word, index != length - 1);
+	pub(crate) const ALEF: char = '\u{05D0}';
+	const BET: char = '\u{05D1}';
+	const FI_LIGATURE: char = '\u{FB01}';
 
-			let glyph_buffer = rustybuzz::shape(&buzz_face, &[], buffer);
+	fn table(tag: &[u8; 4], data: Vec<u8>) -> ([u8; 4], Vec<u8>) {
+		(*tag, data)
+	}
 
-			if wrap_word(line_width, &glyph_buffer, scale, pos.x) {
-				pos = DVec2::new(0., pos.y + line_height);
-			}
+	fn u16s(values: impl IntoIterator<Item = u16>) -> Vec<u8> {
+		values.into_iter().flat_map(u16::to_be_bytes).collect()
+	}
 
-			for glyph_position in glyph_buffer.glyph_positions() {
-				if let Some(line_width) = line_width {
-					if pos.x + (glyph_position.x_advance as f64 * scale) >= line_width {
-						pos = DVec2::new(0., pos.y + line_height);
-					}
+	/// Builds a font with 1000 units per em and no outlines, where glyph 0 is the missing glyph and glyph `n` is the `n`th of `glyphs`, which are characters from the Basic Multilingual Plane with their advance.
+	/// Each pair of characters in `ligatures` is replaced with the glyph of another character by the `liga` feature.
+	fn test_font(glyphs: &[(char, u16)], ligatures: &[([char; 2], char)]) -> Vec<u8> {
+		let glyph_id = |character: char| glyphs.iter().position(|&(glyph, _)| glyph == character).unwrap() as u16 + 1;
+		let glyph_count = glyphs.len() as u16 + 1;
+
+		let mut head = u16s([1, 0, 1, 0, 0, 0, 0x5F0F, 0x3CF5, 0, 1000]);
+		head.extend([0; 16]);
+		head.extend(u16s([0, (-200_i16) as u16, 1000, 800, 0, 8, 2, 0, 0]));
+		let hhea = u16s([1, 0, 800, (-200_i16) as u16, 0, 1000, 0, 0, 1000, 1, 0, 0, 0, 0, 0, 0, 0, glyph_count]);
+		let maxp = u16s([0, 0x5000, glyph_count]);
+		let hmtx = u16s([500, 0].into_iter().chain(glyphs.iter().flat_map(|&(_, advance)| [advance, 0])));
+
+		// A format 4 subtable with a segment for each character, mapping it to its glyph with the delta
+		let mut characters = glyphs.iter().map(|&(character, _)| character as u16).collect::<Vec<_>>();
+		characters.sort_unstable();
+		let segments = characters.len() as u16 + 1;
+		let ends = characters.iter().copied().chain([0xFFFF]);
+		let deltas = characters
+			.iter()
+			.map(|&character| glyph_id(char::from_u32(character as u32).unwrap()).wrapping_sub(character))
+			.chain([1]);
+		let mut subtable = u16s([4, 16 + 8 * segments, 0, segments * 2, 0, 0, 0]);
+		subtable.extend(u16s(ends.clone()));
+		subtable.extend(u16s([0]));
+		subtable.extend(u16s(ends));
+		subtable.extend(u16s(deltas));
+		subtable.extend(u16s((0..segments).map(|_| 0)));
+		let mut cmap = u16s([0, 1, 3, 1, 0, 12]);
+		cmap.extend(subtable);
+
+		let mut tables = vec![table(b"cmap", cmap), table(b"head", head), table(b"hhea", hhea), table(b"hmtx", hmtx), table(b"maxp", maxp)];
+		if !ligatures.is_empty() {
+			// A single `liga` feature for the default script, with a lookup of every ligature starting with each first glyph
+			let mut first_glyphs = ligatures.iter().map(|&([first, _], _)| glyph_id(first)).collect::<Vec<_>>();
+			first_glyphs.sort_unstable();
+			first_glyphs.dedup();
+			let ligature_sets = first_glyphs.iter().map(|&first| {
+				let set = ligatures.iter().filter(|&&([other, _], _)| glyph_id(other) == first).collect::<Vec<_>>();
+				let mut data = u16s([set.len() as u16]);
+				data.extend(u16s((0..set.len() as u16).map(|index| 2 + 2 * set.len() as u16 + 6 * index)));
+				for &&([_, second], ligature) in &set {
+					data.extend(u16s([glyph_id(ligature), 2, glyph_id(second)]));
 				}
-				pos += DVec2::new(glyph_position.x_advance as f64, glyph_position.y_advance as f64) * scale;
+				data
+			});
+			let sets_start = 6 + 2 * first_glyphs.len() as u16;
+			let mut substitution = Vec::new();
+			let mut sets = Vec::new();
+			let mut offsets = Vec::new();
+			for set in ligature_sets {
+				offsets.push(sets_start + sets.len() as u16);
+				sets.extend(set);
 			}
-			bounds = bounds.max(pos + DVec2::new(0., line_height));
-
-			buffer = glyph_buffer.clear();
+			substitution.extend(u16s([1, sets_start + sets.len() as u16, first_glyphs.len() as u16]));
+			substitution.extend(u16s(offsets));
+			substitution.extend(sets);
+			substitution.extend(u16s([1, first_glyphs.len() as u16]));
+			substitution.extend(u16s(first_glyphs));
+
+			let mut gsub = u16s([1, 0, 10, 30, 44]);
+			// Script list with a default language system using the first feature
+			gsub.extend(b"\0\x01DFLT\0\x08\0\x04\0\0\0\0\xFF\xFF\0\x01\0\0");
+			// Feature list
+			gsub.extend(b"\0\x01liga\0\x08\0\0\0\x01\0\0");
+			// Lookup list with a single ligature substitution lookup
+			gsub.extend(u16s([1, 4, 4, 0, 1, 8]));
+			gsub.extend(substitution);
+			tables.push(table(b"GSUB", gsub));
 		}
-		pos = DVec2::new(0., pos.y + line_height);
+		tables.sort_by_key(|(tag, _)| *tag);
+
+		let mut font = u16s([1, 0, tables.len() as u16, 0, 0, 0]);
+		let mut offset = 12 + 16 * tables.len();
+		let mut data = Vec::new();
+		for (tag, table) in &tables {
+			font.extend(tag);
+			font.extend([0; 4]);
+			font.extend((offset as u32).to_be_bytes());
+			font.extend((table.len() as u32).to_be_bytes());
+			let padded = (table.len() + 3) & !3;
+			data.extend(table);
+			data.resize(data.len() + padded - table.len(), 0);
+			offset += padded;
+		}
+		font.extend(data);
+		font
 	}
 
-	bounds
-}
+	pub(crate) fn latin_font() -> Vec<u8> {
+		test_font(&[(' ', 250), ('a', 500), ('b', 600), ('f', 300), ('i', 200), (FI_LIGATURE, 450)], &[(['f', 'i'], FI_LIGATURE)])
+	}
+
+	pub(crate) fn hebrew_font() -> Vec<u8> {
+		test_font(&[(ALEF, 700), (BET, 800)], &[])
+	}
+
+	/// The face, character and position of each glyph of the first line, in units of the font since the font size is 1000 pixels
+	fn shaped_glyphs(faces: &[Vec<u8>], glyphs: &[&[char]], text: &str, config: TypesettingConfig) -> Vec<(usize, Option<char>, f64)> {
+		let faces = faces.iter().map(|data| rustybuzz::Face::from_slice(data, 0).unwrap()).collect::<Vec<_>>();
+		let config = TypesettingConfig { font_size: 1000., ..config };
+		let layout = Shaper::new(faces.iter().collect(), &config).layout(text);
+		let line = layout.lines.into_iter().next().unwrap();
+		line.glyphs
+			.iter()
+			.map(|glyph| (glyph.face, glyph.id.0.checked_sub(1).map(|index| glyphs[glyph.face][index as usize]), glyph.x))
+			.collect()
+	}
+
+	const LATIN_GLYPHS: &[char] = &[' ', 'a', 'b', 'f', 'i', FI_LIGATURE];
+	const HEBREW_GLYPHS: &[char] = &[ALEF, BET];
+
+	#[test]
+	fn right_to_left_runs_are_reordered_in_left_to_right_paragraphs() {
+		let text = format!("ab {ALEF}{BET}");
+		let glyphs = shaped_glyphs(&[latin_font(), hebrew_font()], &[LATIN_GLYPHS, HEBREW_GLYPHS], &text, TypesettingConfig::default());
+		assert_eq!(glyphs, [(0, Some('a'), 0.), (0, Some('b'), 500.), (0, Some(' '), 1100.), (1, Some(BET), 1350.), (1, Some(ALEF), 2150.)]);
+	}
+
+	#[test]
+	fn left_to_right_runs_are_reordered_in_right_to_left_paragraphs() {
+		let text = format!("{ALEF}{BET} ab");
+		let glyphs = shaped_glyphs(&[latin_font(), hebrew_font()], &[LATIN_GLYPHS, HEBREW_GLYPHS], &text, TypesettingConfig::default());
+		// Reading from the right, the Hebrew comes first and the space separates it from the Latin word
+		assert_eq!(glyphs, [(0, Some('a'), 0.), (0, Some('b'), 500.), (0, Some(' '), 1100.), (1, Some(BET), 1350.), (1, Some(ALEF), 2150.)]);
+	}
+
+	#[test]
+	fn characters_missing_from_every_face_use_the_missing_glyph() {
+		let glyphs = shaped_glyphs(&[latin_font(), hebrew_font()], &[LATIN_GLYPHS, HEBREW_GLYPHS], "a\u{4E00}b", TypesettingConfig::default());
+		assert_eq!(glyphs, [(0, Some('a'), 0.), (0, None, 500.), (0, Some('b'), 1000.)]);
 
-pub fn load_face(data: &[u8]) -> rustybuzz::Face {
-	rustybuzz::Face::from_slice(data, 0).expect("Loading font failed")
+		// Without a fallback face, the Hebrew is drawn with the missing glyph of the primary face
+		let glyphs = shaped_glyphs(&[latin_font()], &[LATIN_GLYPHS], &format!("a{ALEF}"), TypesettingConfig::default());
+		assert_eq!(glyphs, [(0, Some('a'), 0.), (0, None, 500.)]);
+	}
+
+	#[test]
+	fn ligatures_follow_the_font_features() {
+		let glyphs = shaped_glyphs(&[latin_font()], &[LATIN_GLYPHS], "fia", TypesettingConfig::default());
+		assert_eq!(glyphs, [(0, Some(FI_LIGATURE), 0.), (0, Some('a'), 450.)]);
+
+		let features = FontFeatures {
+			ligatures: false,
+			..Default::default()
+		};
+		let glyphs = shaped_glyphs(&[latin_font()], &[LATIN_GLYPHS], "fia", TypesettingConfig { features, ..Default::default() });
+		assert_eq!(glyphs, [(0, Some('f'), 0.), (0, Some('i'), 300.), (0, Some('a'), 500.)]);
+	}
+
+	#[test]
+	fn spacing_is_added_to_every_glyph_and_whitespace() {
+		let config = TypesettingConfig {
+			character_spacing: 10.,
+			word_spacing: 100.,
+			..Default::default()
+		};
+		let glyphs = shaped_glyphs(&[latin_font()], &[LATIN_GLYPHS], "a b", config);
+		assert_eq!(glyphs, [(0, Some('a'), 0.), (0, Some(' '), 510.), (0, Some('b'), 870.)]);
+	}
+
+	#[test]
+	fn frames_follow_the_path() {
+		let line = Subpath::new_line(DVec2::ZERO, DVec2::new(100., 0.));
+		let lengths = [100.];
+		let frame = frame_along_path(&line, &lengths, 10.).unwrap();
+		assert!(frame.translation.abs_diff_eq(DVec2::new(10., 0.), 1e-2));
+		assert!(frame.transform_vector2(DVec2::X).abs_diff_eq(DVec2::X, 1e-2));
+		// Below the baseline is below the path
+		assert!(frame.transform_vector2(DVec2::Y).abs_diff_eq(DVec2::Y, 1e-2));
+		assert_eq!(frame_along_path(&line, &lengths, 110.), None);
+		assert_eq!(frame_along_path(&line, &lengths, -1.), None);
+
+		let flipped = frame_along_path(&line.reverse(), &lengths, 10.).unwrap();
+		assert!(flipped.translation.abs_diff_eq(DVec2::new(90., 0.), 1e-2));
+		assert!(flipped.transform_vector2(DVec2::Y).abs_diff_eq(-DVec2::Y, 1e-2));
+	}
+
+	#[test]
+	fn frames_wrap_around_closed_paths() {
+		let square = Subpath::from_anchors([DVec2::ZERO, DVec2::new(10., 0.), DVec2::new(10., 10.), DVec2::new(0., 10.)], true);
+		let lengths = square.iter().map(|bezier| bezier.length(None)).collect::<Vec<_>>();
+		let frame = frame_along_path(&square, &lengths, 45.).unwrap();
+		assert!(frame.translation.abs_diff_eq(DVec2::new(5., 0.), 1e-2));
+		let frame = frame_along_path(&square, &lengths, 15.).unwrap();
+		assert!(frame.translation.abs_diff_eq(DVec2::new(10., 5.), 1e-2));
+		assert!(frame.transform_vector2(DVec2::X).abs_diff_eq(DVec2::Y, 1e-2));
+	}
 }
@@ -0,0 +1,208 @@
+use core::ops::Range;
+
+use dyn_any::{DynAny, StaticType};
+use serde::{Deserialize, Serialize};
+
+/// How each line of a paragraph is positioned horizontally within the width of the text box
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, DynAny, specta::Type)]
+pub enum TextAlignment {
+	#[default]
+	Left,
+	Center,
+	Right,
+	/// Stretches the word spacing so every line except the last of each paragraph fills the full width
+	Justify,
+}
+
+impl TextAlignment {
+	pub fn list() -> [TextAlignment; 4] {
+		[TextAlignment::Left, TextAlignment::Center, TextAlignment::Right, TextAlignment::Justify]
+	}
+}
+
+impl core::fmt::Display for TextAlignment {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			TextAlignment::Left => write!(f, "Left"),
+			TextAlignment::Center => write!(f, "Center"),
+			TextAlignment::Right => write!(f, "Right"),
+			TextAlignment::Justify => write!(f, "Justify"),
+		}
+	}
+}
+
+/// The OpenType features that can be toggled when shaping text, for fonts which provide them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, DynAny, specta::Type)]
+pub struct FontFeatures {
+	/// Standard ligatures (`liga`)
+	pub ligatures: bool,
+	/// Kerning (`kern`)
+	pub kerning: bool,
+	/// Small capitals (`smcp`)
+	pub small_caps: bool,
+	/// Tabular (monospaced) figures (`tnum`)
+	pub tabular_numbers: bool,
+}
+
+impl Default for FontFeatures {
+	fn default() -> Self {
+		Self {
+			ligatures: true,
+			kerning: true,
+			small_caps: false,
+			tabular_numbers: false,
+		}
+	}
+}
+
+impl FontFeatures {
+	/// The OpenType tag of each feature along with whether it is enabled
+	pub fn tags(&self) -> [([u8; 4], bool); 4] {
+		[(*b"liga", self.ligatures), (*b"kern", self.kerning), (*b"smcp", self.small_caps), (*b"tnum", self.tabular_numbers)]
+	}
+}
+
+/// The parameters controlling how a string is laid out into lines of glyphs
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, specta::Type)]
+pub struct TypesettingConfig {
+	pub font_size: f64,
+	/// The distance between baselines as a multiple of the font size (leading)
+	pub line_height_ratio: f64,
+	/// Extra space in pixels added after every glyph (tracking)
+	pub character_spacing: f64,
+	/// Extra space in pixels added to every whitespace character
+	pub word_spacing: f64,
+	/// Extra space in pixels added between paragraphs, which are separated by line breaks
+	pub paragraph_spacing: f64,
+	/// The width at which lines are wrapped, or `None` to only break lines at line breaks
+	pub max_width: Option<f64>,
+	pub align: TextAlignment,
+	pub features: FontFeatures,
+}
+
+impl Default for TypesettingConfig {
+	fn default() -> Self {
+		Self {
+			font_size: 24.,
+			line_height_ratio: 1.,
+			character_spacing: 0.,
+			word_spacing: 0.,
+			paragraph_spacing: 0.,
+			max_width: None,
+			align: TextAlignment::default(),
+			features: FontFeatures::default(),
+		}
+	}
+}
+
+impl TypesettingConfig {
+	pub fn line_height(&self) -> f64 {
+		self.font_size * self.line_height_ratio
+	}
+}
+
+/// A run of text that can't be broken, measured including and excluding its trailing whitespace
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Word {
+	pub range: Range<usize>,
+	pub width: f64,
+	pub width_with_trailing_space: f64,
+}
+
+/// Splits a paragraph into words that end after their trailing whitespace, so that joining the ranges gives back the whole paragraph
+pub(crate) fn word_ranges(paragraph: &str) -> Vec<(Range<usize>, Range<usize>)> {
+	let mut words = Vec::new();
+	let mut start = 0;
+	let mut content_end = None;
+	for (index, character) in paragraph.char_indices() {
+		if character.is_whitespace() {
+			content_end.get_or_insert(index);
+		} else if let Some(end) = content_end.take() {
+			words.push((start..end, start..index));
+			start = index;
+		}
+	}
+	if start < paragraph.len() {
+		let end = content_end.unwrap_or(paragraph.len());
+		words.push((start..end, start..paragraph.len()));
+	}
+	words
+}
+
+/// Greedily breaks words into lines no wider than `max_width`, returning the range of words on each line.
+/// A word which is too wide on its own is put on its own line, so it should be split into smaller words beforehand if that is undesirable.
+pub(crate) fn break_lines(words: &[Word], max_width: Option<f64>) -> Vec<Range<usize>> {
+	let Some(max_width) = max_width else {
+		let all_words = 0..words.len();
+		return vec![all_words];
+	};
+
+	let mut lines = Vec::new();
+	let mut line_start = 0;
+	let mut x = 0.;
+	for (index, word) in words.iter().enumerate() {
+		if index > line_start && x + word.width > max_width {
+			lines.push(line_start..index);
+			line_start = index;
+			x = 0.;
+		}
+		x += word.width_with_trailing_space;
+	}
+	lines.push(line_start..words.len());
+	lines
+}
+
+/// The horizontal offset of a line and the extra space given to each of its whitespace characters to satisfy the alignment
+pub(crate) fn align_line(align: TextAlignment, line_width: f64, available_width: f64, spaces: usize, last_in_paragraph: bool) -> (f64, f64) {
+	let remaining = (available_width - line_width).max(0.);
+	match align {
+		TextAlignment::Left => (0., 0.),
+		TextAlignment::Center => (remaining / 2., 0.),
+		TextAlignment::Right => (remaining, 0.),
+		TextAlignment::Justify if last_in_paragraph || spaces == 0 => (0., 0.),
+		TextAlignment::Justify => (0., remaining / spaces as f64),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn word(width: f64, space: f64) -> Word {
+		Word {
+			range: 0..0,
+			width,
+			width_with_trailing_space: width + space,
+		}
+	}
+
+	#[test]
+	fn words_keep_trailing_whitespace() {
+		let text = "one  two\tthree";
+		let words = word_ranges(text);
+		assert_eq!(words, vec![(0..3, 0..5), (5..8, 5..9), (9..14, 9..14)]);
+		assert_eq!(word_ranges("  lead"), vec![(0..0, 0..2), (2..6, 2..6)]);
+		assert!(word_ranges("").is_empty());
+	}
+
+	#[test]
+	fn greedy_line_breaking() {
+		let words = [word(30., 5.), word(30., 5.), word(30., 5.), word(100., 0.)];
+		let unbroken = break_lines(&words, None);
+		assert_eq!(unbroken.len(), 1);
+		assert_eq!(unbroken[0], 0..4);
+		// The trailing space of the last word on a line doesn't count towards its width
+		assert_eq!(break_lines(&words, Some(65.)), vec![0..2, 2..3, 3..4]);
+		assert_eq!(break_lines(&words, Some(64.)), vec![0..1, 1..2, 2..3, 3..4]);
+	}
+
+	#[test]
+	fn alignment_offsets() {
+		assert_eq!(align_line(TextAlignment::Left, 60., 100., 2, false), (0., 0.));
+		assert_eq!(align_line(TextAlignment::Center, 60., 100., 2, false), (20., 0.));
+		assert_eq!(align_line(TextAlignment::Right, 60., 100., 2, false), (40., 0.));
+		assert_eq!(align_line(TextAlignment::Justify, 60., 100., 2, false), (0., 20.));
+		assert_eq!(align_line(TextAlignment::Justify, 60., 100., 2, true), (0., 0.));
+		assert_eq!(align_line(TextAlignment::Right, 120., 100., 2, false), (0., 0.));
+	}
+}
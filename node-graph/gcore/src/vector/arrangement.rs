@@ -0,0 +1,331 @@
+//! The planar arrangement of a set of paths, which divides the plane into the faces enclosed where the paths cross each other.
+
+use super::vector_nodes::start_direction;
+use crate::uuid::ManipulatorGroupId;
+
+use bezier_rs::{Bezier, Subpath, TValue};
+use glam::DVec2;
+use std::collections::HashMap;
+
+/// The size of the bounding boxes below which two curves are considered to intersect.
+const INTERSECTION_ERROR: f64 = 1e-3;
+/// How close two points must be to be merged into a single vertex of the arrangement.
+const VERTEX_TOLERANCE: f64 = 1e-2;
+/// The number of points sampled from each curve when approximating the area of a face.
+const AREA_SAMPLES: usize = 8;
+
+/// A piece of one of the paths between two vertices, which crosses no other piece.
+struct Edge {
+	bezier: Bezier,
+	start: usize,
+	end: usize,
+}
+
+/// A closed loop of half edges, where half edge `2 * i` follows edge `i` forwards and half edge `2 * i + 1` follows it backwards.
+struct Cycle {
+	half_edges: Vec<usize>,
+	/// The signed area, which is positive for the bounded faces and negative for the outer boundary of each connected group of paths.
+	area: f64,
+	component: usize,
+}
+
+/// The faces formed by a set of open or closed paths. Dangling parts of the paths that enclose nothing are ignored.
+pub struct PlanarArrangement {
+	vertices: Vec<DVec2>,
+	/// The vertices in each cell of a grid whose cells are as wide as the distance within which points are merged, so only the neighboring cells are searched
+	vertex_cells: HashMap<(i64, i64), Vec<usize>>,
+	edges: Vec<Edge>,
+	cycles: Vec<Cycle>,
+}
+
+impl PlanarArrangement {
+	pub fn new(subpaths: &[Subpath<ManipulatorGroupId>]) -> Self {
+		let segments = subpaths.iter().flat_map(|subpath| subpath.iter()).filter(|bezier| !bezier.is_point()).collect::<Vec<_>>();
+		let bounds = segments.iter().map(|bezier| bezier.bounding_box()).collect::<Vec<_>>();
+
+		// Find where each segment is crossed by the other segments and by itself
+		let mut splits = vec![Vec::new(); segments.len()];
+		for (index, bezier) in segments.iter().enumerate() {
+			for [t, other_t] in bezier.self_intersections(Some(INTERSECTION_ERROR)) {
+				splits[index].extend([t, other_t]);
+			}
+			for other_index in index + 1..segments.len() {
+				if !overlapping(bounds[index], bounds[other_index]) {
+					continue;
+				}
+				let other = &segments[other_index];
+				splits[index].extend(bezier.intersections(other, Some(INTERSECTION_ERROR), None));
+				splits[other_index].extend(other.intersections(bezier, Some(INTERSECTION_ERROR), None));
+			}
+		}
+
+		let mut arrangement = Self {
+			vertices: Vec::new(),
+			vertex_cells: HashMap::new(),
+			edges: Vec::new(),
+			cycles: Vec::new(),
+		};
+		for (bezier, mut splits) in segments.into_iter().zip(splits) {
+			splits.retain(|&t| t > 0. && t < 1.);
+			splits.sort_by(|a, b| a.total_cmp(b));
+
+			let mut start_t = 0.;
+			for end_t in splits.into_iter().chain([1.]) {
+				let piece = bezier.trim(TValue::Parametric(start_t), TValue::Parametric(end_t));
+				if piece.start().distance(piece.end()) > VERTEX_TOLERANCE || (end_t == 1. && start_t == 0.) {
+					arrangement.add_edge(piece);
+					start_t = end_t;
+				}
+			}
+		}
+		arrangement.remove_dangling_edges();
+		arrangement.find_cycles();
+
+		arrangement
+	}
+
+	/// The closed paths around each of the bounded faces, without their holes.
+	pub fn faces(&self) -> impl Iterator<Item = Subpath<ManipulatorGroupId>> + '_ {
+		self.cycles.iter().filter(|cycle| cycle.area > 0.).map(|cycle| self.cycle_subpath(cycle))
+	}
+
+	/// The smallest bounded face containing `point`, as the path around it followed by the paths around the groups of paths it surrounds, which wind the other way.
+	pub fn region_at(&self, point: DVec2) -> Option<Vec<Subpath<ManipulatorGroupId>>> {
+		let (face, boundary) = self
+			.cycles
+			.iter()
+			.filter(|cycle| cycle.area > 0.)
+			.map(|cycle| (cycle, self.cycle_subpath(cycle)))
+			.filter(|(_, subpath)| subpath.contains_point(point))
+			.min_by(|(a, _), (b, _)| a.area.total_cmp(&b.area))?;
+
+		// The outer boundaries of other groups of paths inside the face are holes, unless they are themselves inside one of the other holes
+		let inside_face = |cycle: &&Cycle| cycle.area < 0. && cycle.component != face.component && boundary.contains_point(self.cycle_point(cycle));
+		let candidates = self.cycles.iter().filter(inside_face).map(|cycle| (cycle, self.cycle_subpath(cycle))).collect::<Vec<_>>();
+		let holes = candidates.iter().filter(|(cycle, _)| {
+			let point = self.cycle_point(cycle);
+			!candidates.iter().any(|(other, subpath)| other.component != cycle.component && subpath.contains_point(point))
+		});
+
+		Some(core::iter::once(boundary.clone()).chain(holes.map(|(_, subpath)| subpath.clone())).collect())
+	}
+
+	fn vertex(&mut self, point: DVec2) -> usize {
+		let cell = (point / VERTEX_TOLERANCE).floor();
+		let (x, y) = (cell.x as i64, cell.y as i64);
+		let nearby = (-1..=1)
+			.flat_map(|dx| (-1..=1).map(move |dy| (x + dx, y + dy)))
+			.filter_map(|cell| self.vertex_cells.get(&cell))
+			.flatten();
+		// The earliest vertex is merged with, as when every vertex is searched in order
+		if let Some(&index) = nearby.filter(|&&index| self.vertices[index].distance(point) <= VERTEX_TOLERANCE).min() {
+			return index;
+		}
+		self.vertices.push(point);
+		self.vertex_cells.entry((x, y)).or_default().push(self.vertices.len() - 1);
+		self.vertices.len() - 1
+	}
+
+	fn add_edge(&mut self, mut bezier: Bezier) {
+		let (start, end) = (self.vertex(bezier.start()), self.vertex(bezier.end()));
+		// Move the ends onto the merged vertices so the pieces meeting there join up exactly
+		bezier.set_start(self.vertices[start]);
+		bezier.set_end(self.vertices[end]);
+		if start == end && bezier.length(None) <= VERTEX_TOLERANCE * 2. {
+			return;
+		}
+
+		// Paths drawn on top of each other would otherwise form faces with no area between them
+		let middle = bezier.evaluate(TValue::Parametric(0.5));
+		let duplicate = self.edges.iter().any(|edge| {
+			let same_ends = (edge.start, edge.end) == (start, end) || (edge.start, edge.end) == (end, start);
+			same_ends && edge.bezier.evaluate(TValue::Parametric(0.5)).distance(middle) <= VERTEX_TOLERANCE
+		});
+		if !duplicate {
+			self.edges.push(Edge { bezier, start, end });
+		}
+	}
+
+	/// Repeatedly removes the edges with an end that no other edge meets, since they can't be part of the boundary of a face.
+	fn remove_dangling_edges(&mut self) {
+		loop {
+			let mut degrees = vec![0; self.vertices.len()];
+			for edge in &self.edges {
+				degrees[edge.start] += 1;
+				degrees[edge.end] += 1;
+			}
+
+			let count = self.edges.len();
+			self.edges.retain(|edge| degrees[edge.start] > 1 && degrees[edge.end] > 1);
+			if self.edges.len() == count {
+				return;
+			}
+		}
+	}
+
+	fn half_edge_bezier(&self, half_edge: usize) -> Bezier {
+		let bezier = self.edges[half_edge / 2].bezier;
+		if half_edge % 2 == 0 {
+			bezier
+		} else {
+			bezier.reverse()
+		}
+	}
+
+	fn half_edge_end(&self, half_edge: usize) -> usize {
+		let edge = &self.edges[half_edge / 2];
+		if half_edge % 2 == 0 {
+			edge.end
+		} else {
+			edge.start
+		}
+	}
+
+	/// Follows the half edges around each face. On arriving at a vertex, the next half edge is the first one clockwise from the way back.
+	fn find_cycles(&mut self) {
+		let mut outgoing = vec![Vec::new(); self.vertices.len()];
+		for half_edge in 0..self.edges.len() * 2 {
+			let start = self.half_edge_end(half_edge ^ 1);
+			let direction = start_direction(&self.half_edge_bezier(half_edge));
+			outgoing[start].push((direction.y.atan2(direction.x), half_edge));
+		}
+		for half_edges in &mut outgoing {
+			half_edges.sort_by(|a, b| a.0.total_cmp(&b.0));
+		}
+		let next = |half_edge: usize| {
+			let half_edges = &outgoing[self.half_edge_end(half_edge)];
+			let back = half_edges.iter().position(|&(_, outgoing)| outgoing == half_edge ^ 1).unwrap();
+			half_edges[(back + half_edges.len() - 1) % half_edges.len()].1
+		};
+
+		let components = self.components();
+		let mut visited = vec![false; self.edges.len() * 2];
+		let mut cycles = Vec::new();
+		for first in 0..self.edges.len() * 2 {
+			let mut half_edge = first;
+			let mut half_edges = Vec::new();
+			while !visited[half_edge] {
+				visited[half_edge] = true;
+				half_edges.push(half_edge);
+				half_edge = next(half_edge);
+			}
+			if half_edges.is_empty() {
+				continue;
+			}
+
+			let area = self.signed_area(&half_edges);
+			let component = components[self.edges[first / 2].start];
+			cycles.push(Cycle { half_edges, area, component });
+		}
+		self.cycles = cycles;
+	}
+
+	/// Labels each vertex with the group of connected edges it belongs to.
+	fn components(&self) -> Vec<usize> {
+		let mut labels = (0..self.vertices.len()).collect::<Vec<_>>();
+		fn root(labels: &mut [usize], mut vertex: usize) -> usize {
+			while labels[vertex] != vertex {
+				labels[vertex] = labels[labels[vertex]];
+				vertex = labels[vertex];
+			}
+			vertex
+		}
+		for edge in &self.edges {
+			let (start, end) = (root(&mut labels, edge.start), root(&mut labels, edge.end));
+			labels[start] = end;
+		}
+		(0..self.vertices.len()).map(|vertex| root(&mut labels, vertex)).collect()
+	}
+
+	fn signed_area(&self, half_edges: &[usize]) -> f64 {
+		let points = half_edges.iter().flat_map(|&half_edge| {
+			let bezier = self.half_edge_bezier(half_edge);
+			(0..AREA_SAMPLES).map(move |sample| bezier.evaluate(TValue::Parametric(sample as f64 / AREA_SAMPLES as f64)))
+		});
+		let points = points.collect::<Vec<_>>();
+		let next_points = points.iter().cycle().skip(1);
+		points.iter().zip(next_points).map(|(a, b)| a.perp_dot(*b)).sum::<f64>() / 2.
+	}
+
+	fn cycle_subpath(&self, cycle: &Cycle) -> Subpath<ManipulatorGroupId> {
+		let mut beziers = cycle.half_edges.iter().map(|&half_edge| self.half_edge_bezier(half_edge)).collect::<Vec<_>>();
+		// A closed subpath needs at least two segments, so a face bounded by a single loop is split in half
+		if beziers.len() == 1 {
+			beziers = beziers[0].split(TValue::Parametric(0.5)).to_vec();
+		}
+		Subpath::from_beziers(&beziers, true)
+	}
+
+	fn cycle_point(&self, cycle: &Cycle) -> DVec2 {
+		self.half_edge_bezier(cycle.half_edges[0]).start()
+	}
+}
+
+fn overlapping([a_min, a_max]: [DVec2; 2], [b_min, b_max]: [DVec2; 2]) -> bool {
+	let tolerance = DVec2::splat(VERTEX_TOLERANCE);
+	(a_min - tolerance).cmple(b_max).all() && (b_min - tolerance).cmple(a_max).all()
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn line(start: DVec2, end: DVec2) -> Subpath<ManipulatorGroupId> {
+		Subpath::from_anchors([start, end], false)
+	}
+
+	fn area(subpath: &Subpath<ManipulatorGroupId>) -> f64 {
+		let anchors = subpath.anchors();
+		anchors.iter().zip(anchors.iter().cycle().skip(1)).map(|(a, b)| a.perp_dot(*b)).sum::<f64>().abs() / 2.
+	}
+
+	#[test]
+	fn square_split_by_line() {
+		let square = Subpath::new_rect(DVec2::ZERO, DVec2::splat(100.));
+		let arrangement = PlanarArrangement::new(&[square, line(DVec2::new(50., -10.), DVec2::new(50., 110.))]);
+
+		let faces = arrangement.faces().collect::<Vec<_>>();
+		assert_eq!(faces.len(), 2);
+		for face in faces {
+			assert!((area(&face) - 5000.).abs() < 0.1);
+		}
+
+		let region = arrangement.region_at(DVec2::new(75., 50.)).unwrap();
+		assert_eq!(region.len(), 1);
+		assert!(region[0].contains_point(DVec2::new(60., 20.)));
+		assert!(!region[0].contains_point(DVec2::new(40., 20.)));
+		assert!(arrangement.region_at(DVec2::new(150., 50.)).is_none());
+	}
+
+	#[test]
+	fn crossing_open_lines_enclose_triangle() {
+		// Three lines crossing each other with overhanging ends
+		let lines = [
+			line(DVec2::new(-10., 0.), DVec2::new(110., 0.)),
+			line(DVec2::new(-5., -10.), DVec2::new(55., 110.)),
+			line(DVec2::new(105., -10.), DVec2::new(45., 110.)),
+		];
+		let arrangement = PlanarArrangement::new(&lines);
+
+		assert_eq!(arrangement.faces().count(), 1);
+		let region = arrangement.region_at(DVec2::new(50., 20.)).unwrap();
+		assert_eq!(region[0].len(), 3);
+		assert!((area(&region[0]) - 5000.).abs() < 0.1);
+	}
+
+	#[test]
+	fn enclosed_shape_is_a_hole() {
+		let outer = Subpath::new_rect(DVec2::ZERO, DVec2::splat(100.));
+		let inner = Subpath::new_ellipse(DVec2::splat(40.), DVec2::splat(60.));
+		let arrangement = PlanarArrangement::new(&[outer, inner]);
+
+		let region = arrangement.region_at(DVec2::new(10., 10.)).unwrap();
+		assert_eq!(region.len(), 2);
+		let winding = |point| region.iter().flat_map(|subpath| subpath.iter()).map(|bezier| bezier.winding(point)).sum::<i32>();
+		assert_ne!(winding(DVec2::new(10., 10.)), 0);
+		assert_eq!(winding(DVec2::splat(50.)), 0);
+
+		let region = arrangement.region_at(DVec2::splat(50.)).unwrap();
+		assert_eq!(region.len(), 1);
+	}
+}
@@ -1,4 +1,5 @@
 use crate::raster::bbox::AxisAlignedBbox;
+use crate::raster::pixel_selection::PixelSelection;
 use crate::raster::BlendMode;
 use crate::Color;
 
@@ -77,6 +78,9 @@ impl Hash for BrushInputSample {
 pub struct BrushStroke {
 	pub style: BrushStyle,
 	pub trace: Vec<BrushInputSample>,
+	/// The pixel selection, in layer space, which was active when the stroke was painted and outside of which the stroke has no effect.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub selection: Option<PixelSelection>,
 }
 
 impl BrushStroke {
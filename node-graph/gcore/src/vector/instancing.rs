@@ -0,0 +1,301 @@
+use super::vector_nodes::{end_direction, start_direction};
+use super::VectorData;
+use crate::uuid::ManipulatorGroupId;
+use crate::{GraphicElement, GraphicElementData, GraphicGroup, Node};
+
+use bezier_rs::Subpath;
+use glam::{DAffine2, DVec2};
+use rand_chacha::rand_core::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
+
+/// The most points scattered by [poisson_disk_points], which keeps a tiny distance in a large shape from freezing the graph
+const MAX_SCATTER_POINTS: usize = 100_000;
+/// How many random candidates are tried around each point, and for a new starting point, before giving up on finding a free spot
+const SCATTER_ATTEMPTS: usize = 30;
+
+/// Graphical data which copies of can be placed, such as at the points of a [VectorData]
+pub trait Instance: Clone {
+	/// Combines copies of the data, each placed by a transform applied on top of its own transform, into data of the same kind
+	fn copies(&self, transforms: &[DAffine2]) -> Self;
+}
+
+impl Instance for VectorData {
+	fn copies(&self, transforms: &[DAffine2]) -> Self {
+		let mut subpaths = Vec::with_capacity(self.subpaths.len() * transforms.len());
+		for &transform in transforms {
+			for mut subpath in self.subpaths.iter().cloned() {
+				subpath.apply_transform(transform * self.transform);
+				subpaths.push(subpath);
+			}
+		}
+
+		VectorData {
+			subpaths,
+			transform: DAffine2::IDENTITY,
+			style: self.style.clone(),
+			mirror_angle: self.mirror_angle.clone(),
+		}
+	}
+}
+
+impl Instance for GraphicGroup {
+	fn copies(&self, transforms: &[DAffine2]) -> Self {
+		let mut group = GraphicGroup::EMPTY;
+		for &transform in transforms {
+			let mut copy = GraphicElementData::GraphicGroup(self.clone());
+			copy.apply_transform(transform);
+			// Each copy is kept in its own group, so elements clipped to the ones below them stay clipped within their copy
+			group.push(GraphicElement {
+				graphic_element_data: copy,
+				..Default::default()
+			});
+		}
+		group
+	}
+}
+
+/// A random number from 0 up to but not including 1
+fn random_unit(rng: &mut ChaCha8Rng) -> f64 {
+	(rng.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+}
+
+/// The direction of a subpath at one of its anchors, which is halfway between the directions it arrives and leaves in
+fn direction_at_anchor(subpath: &Subpath<ManipulatorGroupId>, index: usize) -> DVec2 {
+	let segments = subpath.len_segments();
+	let incoming = match index {
+		0 if subpath.closed() => segments.checked_sub(1),
+		0 => None,
+		_ => Some(index - 1),
+	};
+	let outgoing = (index < segments).then_some(index);
+
+	let incoming = incoming
+		.and_then(|index| subpath.get_segment(index))
+		.map_or(DVec2::ZERO, |bezier| end_direction(&bezier).normalize_or_zero());
+	let outgoing = outgoing
+		.and_then(|index| subpath.get_segment(index))
+		.map_or(DVec2::ZERO, |bezier| start_direction(&bezier).normalize_or_zero());
+
+	// A path which turns back on itself at the anchor follows the direction it leaves in
+	let direction = incoming + outgoing;
+	if direction.length_squared() > 1e-12 {
+		direction
+	} else {
+		outgoing
+	}
+}
+
+/// The transform placing a copy at each anchor of the points, in the space the points are transformed into.
+/// With `align_to_path`, copies are rotated to follow the direction of the path at their anchor, and each copy is then scaled by a random amount between
+/// the ends of `random_scale` and rotated randomly by up to `random_rotation` degrees either way, which the seed makes repeatable.
+pub fn point_transforms(points: &VectorData, align_to_path: bool, random_scale: [f64; 2], random_rotation: f64, seed: u32) -> Vec<DAffine2> {
+	let mut rng = ChaCha8Rng::seed_from_u64(seed as u64);
+	let mut transforms = Vec::new();
+
+	for subpath in &points.subpaths {
+		for (index, group) in subpath.manipulator_groups().iter().enumerate() {
+			let position = points.transform.transform_point2(group.anchor);
+			let direction = points.transform.transform_vector2(direction_at_anchor(subpath, index));
+			let angle = if align_to_path && direction.length_squared() > 0. { direction.y.atan2(direction.x) } else { 0. };
+
+			// Both random values are drawn for every point, so changing one range doesn't change the other's values
+			let scale = random_scale[0] + (random_scale[1] - random_scale[0]) * random_unit(&mut rng);
+			let rotation = (random_unit(&mut rng) * 2. - 1.) * random_rotation.to_radians();
+
+			transforms.push(DAffine2::from_scale_angle_translation(DVec2::splat(scale), angle + rotation, position));
+		}
+	}
+
+	transforms
+}
+
+/// The points placed so far by Poisson-disk sampling, in a grid of cells small enough to hold at most one point each
+struct ScatterGrid {
+	origin: DVec2,
+	cell_size: f64,
+	min_distance: f64,
+	cells: HashMap<(i64, i64), usize>,
+	points: Vec<DVec2>,
+}
+
+impl ScatterGrid {
+	fn cell(&self, point: DVec2) -> (i64, i64) {
+		let cell = ((point - self.origin) / self.cell_size).floor();
+		(cell.x as i64, cell.y as i64)
+	}
+
+	/// Whether no point is placed closer than the minimum distance, which can only be in the cells up to two away
+	fn is_free(&self, point: DVec2) -> bool {
+		let (x, y) = self.cell(point);
+		(-2..=2)
+			.flat_map(|dx| (-2..=2).map(move |dy| (x + dx, y + dy)))
+			.filter_map(|cell| self.cells.get(&cell))
+			.all(|&index| self.points[index].distance_squared(point) >= self.min_distance * self.min_distance)
+	}
+
+	fn insert(&mut self, point: DVec2) -> usize {
+		let index = self.points.len();
+		self.cells.insert(self.cell(point), index);
+		self.points.push(point);
+		index
+	}
+}
+
+/// Points spread randomly inside a shape with no two closer than `min_distance`, using Bridson's Poisson-disk sampling which the seed makes repeatable.
+/// The shape is filled by the nonzero rule with its open subpaths closed, like a fill. Sampling starts again from new random points until none fit,
+/// so every separate part of the shape is filled.
+pub fn poisson_disk_points(subpaths: &[Subpath<ManipulatorGroupId>], min_distance: f64, seed: u32) -> Vec<DVec2> {
+	let bounds = subpaths.iter().filter_map(Subpath::bounding_box).reduce(|a, b| [a[0].min(b[0]), a[1].max(b[1])]);
+	let Some([min, max]) = bounds else { return Vec::new() };
+	if !(min_distance > 0. && min_distance.is_finite()) {
+		return Vec::new();
+	}
+
+	let closed = subpaths
+		.iter()
+		.map(|subpath| {
+			let mut subpath = subpath.clone();
+			subpath.set_closed(true);
+			subpath
+		})
+		.collect::<Vec<_>>();
+	let winding = |point: DVec2| closed.iter().flat_map(|subpath| subpath.iter()).map(|bezier| bezier.winding(point)).sum::<i32>();
+	let inside = |point: DVec2| point.cmpge(min).all() && point.cmple(max).all() && winding(point) != 0;
+
+	let mut rng = ChaCha8Rng::seed_from_u64(seed as u64);
+	let mut grid = ScatterGrid {
+		origin: min,
+		cell_size: min_distance / core::f64::consts::SQRT_2,
+		min_distance,
+		cells: HashMap::new(),
+		points: Vec::new(),
+	};
+	let mut active = Vec::new();
+
+	while grid.points.len() < MAX_SCATTER_POINTS {
+		if active.is_empty() {
+			let mut random_point = || min + (max - min) * DVec2::new(random_unit(&mut rng), random_unit(&mut rng));
+			let Some(start) = (0..SCATTER_ATTEMPTS).map(|_| random_point()).find(|&point| inside(point) && grid.is_free(point)) else {
+				break;
+			};
+			active.push(grid.insert(start));
+			continue;
+		}
+
+		// Try candidates in the ring between one and two times the distance around a random active point, which is retired once nothing fits around it
+		let active_index = (rng.next_u64() % active.len() as u64) as usize;
+		let center = grid.points[active[active_index]];
+		let mut random_candidate = || center + DVec2::from_angle(random_unit(&mut rng) * core::f64::consts::TAU) * min_distance * (1. + random_unit(&mut rng));
+		match (0..SCATTER_ATTEMPTS).map(|_| random_candidate()).find(|&point| inside(point) && grid.is_free(point)) {
+			Some(point) => active.push(grid.insert(point)),
+			None => {
+				active.swap_remove(active_index);
+			}
+		}
+	}
+
+	grid.points
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CopyToPointsNode<InstanceInput, AlignToPath, RandomScaleMin, RandomScaleMax, RandomRotation, Seed> {
+	instance: InstanceInput,
+	align_to_path: AlignToPath,
+	random_scale_min: RandomScaleMin,
+	random_scale_max: RandomScaleMax,
+	random_rotation: RandomRotation,
+	seed: Seed,
+}
+
+/// Places a copy of the instance at every anchor of the points, which can come from any path or from a point generator such as Scatter Points.
+#[node_macro::node_fn(CopyToPointsNode)]
+fn copy_to_points<InstanceInput: Instance>(
+	points: VectorData,
+	instance: InstanceInput,
+	align_to_path: bool,
+	random_scale_min: f64,
+	random_scale_max: f64,
+	random_rotation: f64,
+	seed: u32,
+) -> InstanceInput {
+	let transforms = point_transforms(&points, align_to_path, [random_scale_min, random_scale_max], random_rotation, seed);
+	instance.copies(&transforms)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ScatterPointsNode<MinDistance, Seed> {
+	min_distance: MinDistance,
+	seed: Seed,
+}
+
+/// Spreads points randomly inside the shape with a minimum distance between them, as a subpath with a single anchor for each point.
+#[node_macro::node_fn(ScatterPointsNode, category = "Vector", output = "Points")]
+fn scatter_points(vector_data: VectorData, #[input(default = 20., unit = "px", min = 1.)] min_distance: f64, #[input(default = 0)] seed: u32) -> VectorData {
+	// The distance is measured in the document space, so the points are scattered in the shape after its transform is applied
+	let subpaths = vector_data
+		.subpaths
+		.iter()
+		.map(|subpath| {
+			let mut subpath = subpath.clone();
+			subpath.apply_transform(vector_data.transform);
+			subpath
+		})
+		.collect::<Vec<_>>();
+
+	let points = poisson_disk_points(&subpaths, min_distance, seed);
+	VectorData::from_subpaths(points.into_iter().map(|point| Subpath::from_anchors([point], false)).collect())
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn copies_are_placed_at_anchors() {
+		let points = VectorData::from_subpath(Subpath::from_anchors([DVec2::ZERO, DVec2::new(100., 0.), DVec2::new(100., 100.)], false));
+		let instance = VectorData::from_subpath(Subpath::new_rect(DVec2::splat(-5.), DVec2::splat(5.)));
+
+		let copies = instance.copies(&point_transforms(&points, false, [1., 1.], 0., 0));
+		assert_eq!(copies.subpaths.len(), 3);
+		let [min, max] = copies.subpaths[2].bounding_box().unwrap();
+		assert!(min.abs_diff_eq(DVec2::new(95., 95.), 1e-9) && max.abs_diff_eq(DVec2::new(105., 105.), 1e-9));
+
+		// Aligned to the path, the copy at the corner faces diagonally between the two directions the path takes there
+		let aligned = point_transforms(&points, true, [1., 1.], 0., 0);
+		assert!(aligned[0].transform_vector2(DVec2::X).abs_diff_eq(DVec2::X, 1e-9));
+		assert!(aligned[1].transform_vector2(DVec2::X).abs_diff_eq(DVec2::new(1., 1.).normalize(), 1e-9));
+		assert!(aligned[2].transform_vector2(DVec2::X).abs_diff_eq(DVec2::Y, 1e-9));
+	}
+
+	#[test]
+	fn random_variation_is_repeatable() {
+		let points = VectorData::from_subpath(Subpath::from_anchors((0..10).map(|x| DVec2::new(x as f64 * 10., 0.)), false));
+		let transforms = point_transforms(&points, false, [0.5, 2.], 45., 7);
+		assert_eq!(transforms, point_transforms(&points, false, [0.5, 2.], 45., 7));
+		assert_ne!(transforms, point_transforms(&points, false, [0.5, 2.], 45., 8));
+
+		for transform in transforms {
+			let scale = transform.transform_vector2(DVec2::X).length();
+			let angle = transform.transform_vector2(DVec2::X).y.atan2(transform.transform_vector2(DVec2::X).x).to_degrees();
+			assert!((0.5..=2.).contains(&scale));
+			assert!(angle.abs() <= 45.);
+		}
+	}
+
+	#[test]
+	fn scattered_points_keep_their_distance_inside_the_shape() {
+		// A square with a square hole wound the other way
+		let outer = Subpath::<ManipulatorGroupId>::new_rect(DVec2::ZERO, DVec2::splat(200.));
+		let hole = Subpath::new_rect(DVec2::splat(50.), DVec2::splat(150.)).reverse();
+		let points = poisson_disk_points(&[outer.clone(), hole.clone()], 10., 3);
+
+		assert!(points.len() > 100);
+		assert_eq!(points, poisson_disk_points(&[outer, hole], 10., 3));
+		for (index, &point) in points.iter().enumerate() {
+			assert!(point.cmpge(DVec2::ZERO).all() && point.cmple(DVec2::splat(200.)).all());
+			assert!(!(point.cmpgt(DVec2::splat(50.)).all() && point.cmplt(DVec2::splat(150.)).all()), "{point} is in the hole");
+			assert!(points[index + 1..].iter().all(|other| other.distance(point) >= 10.));
+		}
+	}
+}
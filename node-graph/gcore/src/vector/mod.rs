@@ -1,3 +1,4 @@
+pub mod arrangement;
 pub mod brush_stroke;
 pub mod consts;
 pub mod generator_nodes;
@@ -19,4 +20,7 @@ pub use id_vec::IdBackedVec;
 mod vector_nodes;
 pub use vector_nodes::*;
 
+mod instancing;
+pub use instancing::*;
+
 pub use bezier_rs;
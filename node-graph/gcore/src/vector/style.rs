@@ -251,6 +251,100 @@ impl Display for LineJoin {
 	}
 }
 
+/// Where a stroke sits relative to its path. Only closed paths have an inside and outside, so strokes on open paths are always centered.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Hash, DynAny, specta::Type)]
+pub enum StrokeAlign {
+	#[default]
+	Center,
+	Inside,
+	Outside,
+}
+
+/// How the width of a stroke varies along its path, as a multiple of the stroke's weight.
+/// Strokes with a width that isn't uniform are drawn by converting them to filled outline geometry, since SVG strokes have a single width.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, DynAny, specta::Type)]
+pub struct StrokeProfile {
+	/// Points of the width curve, each with a position along the normalized length of the path from 0 to 1 as `x` and the width multiplier there as `y`.
+	/// The width is interpolated linearly between the points and held before the first and after the last, so an empty curve has a uniform width.
+	/// They are kept sorted by position so the width can be looked up without sorting them each time.
+	#[serde(deserialize_with = "deserialize_sorted_widths")]
+	widths: Vec<DVec2>,
+	/// The fraction of the length of the path over which the stroke narrows to a point at its start
+	pub taper_start: f64,
+	/// The fraction of the length of the path over which the stroke narrows to a point at its end
+	pub taper_end: f64,
+}
+
+impl core::hash::Hash for StrokeProfile {
+	fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+		self.widths.len().hash(state);
+		self.widths.iter().flat_map(|point| point.to_array()).for_each(|value| value.to_bits().hash(state));
+		self.taper_start.to_bits().hash(state);
+		self.taper_end.to_bits().hash(state);
+	}
+}
+
+fn sort_widths(widths: &mut [DVec2]) {
+	widths.sort_by(|a, b| a.x.total_cmp(&b.x));
+}
+
+fn deserialize_sorted_widths<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<DVec2>, D::Error> {
+	let mut widths = Vec::<DVec2>::deserialize(deserializer)?;
+	sort_widths(&mut widths);
+	Ok(widths)
+}
+
+impl StrokeProfile {
+	pub fn new(mut widths: Vec<DVec2>, taper_start: f64, taper_end: f64) -> Self {
+		sort_widths(&mut widths);
+		Self { widths, taper_start, taper_end }
+	}
+
+	/// The points of the width curve, sorted by their position along the path
+	pub fn widths(&self) -> &[DVec2] {
+		&self.widths
+	}
+
+	/// Whether the stroke has the same width along its whole path, so it can be drawn as a regular SVG stroke
+	pub fn is_uniform(&self) -> bool {
+		self.taper_start <= 0. && self.taper_end <= 0. && self.widths.iter().all(|point| point.y == 1.)
+	}
+
+	/// The width multiplier at a position along the normalized length of the path
+	pub fn width_at(&self, position: f64) -> f64 {
+		let widths = &self.widths;
+		let width = match widths.iter().position(|point| point.x >= position) {
+			None => widths.last().map_or(1., |point| point.y),
+			Some(0) => widths[0].y,
+			Some(index) => {
+				let (previous, next) = (widths[index - 1], widths[index]);
+				let factor = if next.x > previous.x { (position - previous.x) / (next.x - previous.x) } else { 1. };
+				previous.y + (next.y - previous.y) * factor
+			}
+		};
+
+		let taper = |length: f64, distance: f64| if length > 0. { (distance / length).clamp(0., 1.) } else { 1. };
+		width.max(0.) * taper(self.taper_start, position) * taper(self.taper_end, 1. - position)
+	}
+
+	/// The positions strictly between the ends of the path where the width curve or tapers change slope, in increasing order.
+	/// Between neighboring positions the width changes linearly, except where the width curve overlaps a taper.
+	pub fn breakpoints(&self) -> Vec<f64> {
+		let tapers = [self.taper_start, 1. - self.taper_end];
+		let mut positions = self
+			.widths
+			.iter()
+			.map(|point| point.x)
+			.chain(tapers)
+			.filter(|&position| position > 0. && position < 1.)
+			.collect::<Vec<_>>();
+		positions.sort_by(f64::total_cmp);
+		positions.dedup();
+		positions
+	}
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, DynAny, specta::Type)]
 pub struct Stroke {
@@ -263,6 +357,12 @@ pub struct Stroke {
 	pub line_cap: LineCap,
 	pub line_join: LineJoin,
 	pub line_join_miter_limit: f64,
+	/// Documents from before width profiles were added have strokes with a uniform width
+	#[serde(default)]
+	pub profile: StrokeProfile,
+	/// Documents from before alignment was added have centered strokes
+	#[serde(default)]
+	pub align: StrokeAlign,
 }
 
 impl core::hash::Hash for Stroke {
@@ -275,6 +375,8 @@ impl core::hash::Hash for Stroke {
 		self.line_cap.hash(state);
 		self.line_join.hash(state);
 		self.line_join_miter_limit.to_bits().hash(state);
+		self.profile.hash(state);
+		self.align.hash(state);
 	}
 }
 
@@ -288,6 +390,12 @@ impl Stroke {
 			line_cap: LineCap::Butt,
 			line_join: LineJoin::Miter,
 			line_join_miter_limit: 4.,
+			profile: StrokeProfile {
+				widths: Vec::new(),
+				taper_start: 0.,
+				taper_end: 0.,
+			},
+			align: StrokeAlign::Center,
 		}
 	}
 
@@ -387,6 +495,16 @@ impl Stroke {
 		self.line_join_miter_limit = limit;
 		self
 	}
+
+	pub fn with_profile(mut self, profile: StrokeProfile) -> Self {
+		self.profile = profile;
+		self
+	}
+
+	pub fn with_align(mut self, align: StrokeAlign) -> Self {
+		self.align = align;
+		self
+	}
 }
 
 // Having an alpha of 1 to start with leads to a better experience with the properties panel
@@ -400,6 +518,8 @@ impl Default for Stroke {
 			line_cap: LineCap::Butt,
 			line_join: LineJoin::Miter,
 			line_join_miter_limit: 4.,
+			profile: StrokeProfile::default(),
+			align: StrokeAlign::Center,
 		}
 	}
 }
@@ -520,6 +640,23 @@ impl PathStyle {
 		self.stroke = None;
 	}
 
+	/// A copy of the style with every color of its fill and stroke replaced.
+	pub fn map_colors(&self, map: impl Fn(Color) -> Color) -> Self {
+		let fill = match &self.fill {
+			Fill::None => Fill::None,
+			Fill::Solid(color) => Fill::Solid(map(*color)),
+			Fill::Gradient(gradient) => Fill::Gradient(Gradient {
+				positions: gradient.positions.iter().map(|&(position, color)| (position, color.map(&map))).collect(),
+				..gradient.clone()
+			}),
+		};
+		let stroke = self.stroke.as_ref().map(|stroke| Stroke {
+			color: stroke.color.map(&map),
+			..stroke.clone()
+		});
+		Self { stroke, fill }
+	}
+
 	pub fn render(&self, view_mode: ViewMode, svg_defs: &mut String, multiplied_transform: DAffine2, bounds: [DVec2; 2], transformed_bounds: [DVec2; 2]) -> String {
 		let fill_attribute = match (view_mode, &self.fill) {
 			(ViewMode::Outline, _) => Fill::None.render(svg_defs, multiplied_transform, bounds, transformed_bounds),
@@ -527,6 +664,8 @@ impl PathStyle {
 		};
 		let stroke_attribute = match (view_mode, &self.stroke) {
 			(ViewMode::Outline, _) => Stroke::new(Some(LAYER_OUTLINE_STROKE_COLOR), LAYER_OUTLINE_STROKE_WEIGHT).render(),
+			// Strokes with a width profile are drawn separately as filled outlines, and aligned strokes are drawn separately clipped to one side of the path
+			(_, Some(stroke)) if !stroke.profile.is_uniform() || stroke.align != StrokeAlign::Center => String::new(),
 			(_, Some(stroke)) => stroke.render(),
 			(_, None) => String::new(),
 		};
@@ -545,4 +684,6 @@ pub enum ViewMode {
 	Outline,
 	/// Render with normal coloration at the document resolution, showing the pixels when the current viewport resolution is higher
 	Pixels,
+	/// Render with the coloration the document would have when printed with its proof profile
+	SoftProof,
 }
@@ -1,11 +1,16 @@
-use super::style::{Fill, FillType, Gradient, GradientType, Stroke};
+use super::arrangement::PlanarArrangement;
+use super::style::{Fill, FillType, Gradient, GradientType, LineCap, LineJoin, Stroke, StrokeAlign, StrokeProfile};
 use super::VectorData;
+use crate::uuid::ManipulatorGroupId;
 use crate::{Color, Node};
 
-use bezier_rs::{Subpath, SubpathTValue};
+use bezier_rs::{Bezier, Cap, Join, ManipulatorGroup, Subpath, SubpathTValue, TValue};
 use glam::{DAffine2, DVec2};
 use num_traits::Zero;
 
+/// The most dashes cut by [dash_subpath], which keeps a tiny dash pattern on a long path from freezing the graph
+const MAX_DASHES: usize = 100_000;
+
 #[derive(Debug, Clone, Copy)]
 pub struct SetFillNode<FillType, SolidColor, GradientType, Start, End, Transform, Positions> {
 	fill_type: FillType,
@@ -42,7 +47,7 @@ fn set_vector_data_fill(
 }
 
 #[derive(Debug, Clone, Copy)]
-pub struct SetStrokeNode<Color, Weight, DashLengths, DashOffset, LineCap, LineJoin, MiterLimit> {
+pub struct SetStrokeNode<Color, Weight, DashLengths, DashOffset, LineCap, LineJoin, MiterLimit, WidthProfile, TaperStart, TaperEnd, Align> {
 	color: Color,
 	weight: Weight,
 	dash_lengths: DashLengths,
@@ -50,6 +55,10 @@ pub struct SetStrokeNode<Color, Weight, DashLengths, DashOffset, LineCap, LineJo
 	line_cap: LineCap,
 	line_join: LineJoin,
 	miter_limit: MiterLimit,
+	width_profile: WidthProfile,
+	taper_start: TaperStart,
+	taper_end: TaperEnd,
+	align: Align,
 }
 
 #[node_macro::node_fn(SetStrokeNode)]
@@ -62,6 +71,10 @@ fn set_vector_data_stroke(
 	line_cap: super::style::LineCap,
 	line_join: super::style::LineJoin,
 	miter_limit: f32,
+	width_profile: Vec<DVec2>,
+	taper_start: f64,
+	taper_end: f64,
+	align: StrokeAlign,
 ) -> VectorData {
 	vector_data.style.set_stroke(Stroke {
 		color,
@@ -71,6 +84,8 @@ fn set_vector_data_stroke(
 		line_cap,
 		line_join,
 		line_join_miter_limit: miter_limit as f64,
+		profile: StrokeProfile::new(width_profile, taper_start, taper_end),
+		align,
 	});
 	vector_data
 }
@@ -185,3 +200,660 @@ fn spline_from_points(mut vector_data: VectorData) -> VectorData {
 
 	vector_data
 }
+
+fn to_join(line_join: LineJoin, miter_limit: f64) -> Join {
+	match line_join {
+		LineJoin::Miter => Join::Miter(Some(miter_limit)),
+		LineJoin::Bevel => Join::Bevel,
+		LineJoin::Round => Join::Round,
+	}
+}
+
+fn to_cap(line_cap: LineCap) -> Cap {
+	match line_cap {
+		LineCap::Butt => Cap::Butt,
+		LineCap::Round => Cap::Round,
+		LineCap::Square => Cap::Square,
+	}
+}
+
+/// Removes handles which sit on their anchor, such as those of shapes built from anchors alone, so their segments are offset as straight lines instead of degenerate curves.
+fn remove_degenerate_handles(subpath: &mut Subpath<ManipulatorGroupId>) {
+	for index in 0..subpath.len() {
+		let group = &mut subpath[index];
+		let anchor = group.anchor;
+		for handle in [&mut group.in_handle, &mut group.out_handle] {
+			if handle.is_some_and(|handle| handle.abs_diff_eq(anchor, 1e-9)) {
+				*handle = None;
+			}
+		}
+	}
+}
+
+/// Whether the subpath has at least one segment of nonzero length, which is required to offset it.
+fn can_offset(subpath: &Subpath<ManipulatorGroupId>) -> bool {
+	subpath.len_segments() > 0 && !subpath.is_point()
+}
+
+/// Splits the segments of a subpath at positions along its normalized length, returning the split subpath and the position of each of its segments' ends.
+fn split_at_positions(subpath: &Subpath<ManipulatorGroupId>, positions: &[f64]) -> (Subpath<ManipulatorGroupId>, Vec<(f64, f64)>) {
+	let lengths = subpath.iter().map(|bezier| bezier.length(None)).collect::<Vec<_>>();
+	let total_length: f64 = lengths.iter().sum();
+
+	let mut beziers = Vec::new();
+	let mut ranges = Vec::new();
+	let mut start = 0.;
+	for (bezier, length) in subpath.iter().zip(lengths) {
+		let [segment_start, segment_end] = [start, start + length].map(|length| length / total_length);
+		start += length;
+
+		let cuts = positions.iter().copied().filter(|&position| position > segment_start && position < segment_end);
+		let mut boundaries = std::iter::once(segment_start).chain(cuts).chain(std::iter::once(segment_end)).collect::<Vec<_>>();
+		boundaries.dedup();
+		if boundaries.len() < 2 {
+			boundaries = vec![segment_start, segment_end];
+		}
+		for pair in boundaries.windows(2) {
+			let local = |position: f64| TValue::Euclidean(((position - segment_start) / (segment_end - segment_start)).clamp(0., 1.));
+			let piece = if boundaries.len() == 2 { bezier } else { bezier.trim(local(pair[0]), local(pair[1])) };
+			beziers.push(piece);
+			ranges.push((pair[0], pair[1]));
+		}
+	}
+
+	(Subpath::from_beziers(&beziers, subpath.closed()), ranges)
+}
+
+/// The filled geometry covering the area of a stroke along a subpath, which is an outline and, for a closed subpath, the inner outline with the opposite winding.
+/// Strokes with a width profile are split where the width changes slope and then outlined at a graduated distance along each segment.
+pub fn stroke_outline(subpath: &Subpath<ManipulatorGroupId>, stroke: &Stroke) -> Vec<Subpath<ManipulatorGroupId>> {
+	let mut subpath = subpath.clone();
+	remove_degenerate_handles(&mut subpath);
+	if !subpath.is_point() && !can_offset(&subpath) {
+		return Vec::new();
+	}
+
+	// A stroke aligned to one side of a closed subpath covers its whole weight on that side, so it is outlined at twice the distance and only the ring on that side is kept
+	let aligned = subpath.closed() && !subpath.is_point() && stroke.align != StrokeAlign::Center;
+	let half_weight = if aligned { stroke.weight } else { stroke.weight / 2. };
+
+	let join = to_join(stroke.line_join, stroke.line_join_miter_limit);
+	let cap = to_cap(stroke.line_cap);
+	let (outline, inner_outline) = if stroke.profile.is_uniform() || subpath.is_point() {
+		subpath.outline(half_weight, join, cap)
+	} else {
+		let (split, ranges) = split_at_positions(&subpath, &stroke.profile.breakpoints());
+		let distance = |position: f64| stroke.profile.width_at(position) * half_weight;
+		let distances = ranges.into_iter().map(|(start, end)| (distance(start), distance(end))).collect::<Vec<_>>();
+		split.graduated_outline(&distances, join, cap)
+	};
+
+	match inner_outline {
+		Some(inner_outline) if aligned => {
+			// The outline winds along the subpath and the inner outline against it, so the kept ring is paired with the subpath wound the opposite way
+			let area = |subpath: &Subpath<ManipulatorGroupId>| subpath.bounding_box().map_or(0., |[min, max]| (max - min).x * (max - min).y);
+			let outline_is_outside = area(&outline) >= area(&inner_outline);
+			let rings = if outline_is_outside == (stroke.align == StrokeAlign::Outside) {
+				[outline, subpath.reverse()]
+			} else {
+				[inner_outline, subpath]
+			};
+			rings.into_iter().filter(|ring| !ring.is_empty()).collect()
+		}
+		inner_outline => std::iter::once(outline).chain(inner_outline).filter(|outline| !outline.is_empty()).collect(),
+	}
+}
+
+/// Splits a subpath into the open subpaths of the dashes of a dash pattern, measured along its length like an SVG `stroke-dasharray`.
+/// The pattern alternates between the lengths of dashes and of the gaps between them, and is repeated to make an even count if it has an odd number of lengths.
+/// The offset shifts how far into the pattern the subpath starts. A pattern without a positive total length, or one that would cut more than [MAX_DASHES] dashes, leaves the subpath solid.
+pub fn dash_subpath(subpath: &Subpath<ManipulatorGroupId>, dash_lengths: &[f64], dash_offset: f64) -> Vec<Subpath<ManipulatorGroupId>> {
+	let pattern = if dash_lengths.len() % 2 == 1 {
+		[dash_lengths, dash_lengths].concat()
+	} else {
+		dash_lengths.to_vec()
+	};
+	let pattern_length: f64 = pattern.iter().sum();
+	if pattern.iter().any(|&length| length < 0. || !length.is_finite()) || pattern_length <= 0. || !dash_offset.is_finite() {
+		return vec![subpath.clone()];
+	}
+
+	let length = subpath.length(None);
+	if subpath.len_segments() == 0 || length <= 0. {
+		return Vec::new();
+	}
+	let pattern_repeats = (length / pattern_length).ceil() + 1.;
+	if pattern_repeats * (pattern.len() / 2) as f64 > MAX_DASHES as f64 {
+		return vec![subpath.clone()];
+	}
+
+	// Find the element of the pattern the subpath starts in and how much of it remains
+	let mut position_in_pattern = dash_offset.rem_euclid(pattern_length);
+	let mut index = 0;
+	while position_in_pattern >= pattern[index] {
+		position_in_pattern -= pattern[index];
+		index = (index + 1) % pattern.len();
+	}
+	let mut remaining = pattern[index] - position_in_pattern;
+
+	let mut dashes = Vec::new();
+	let mut start = 0.;
+	while start < length {
+		let end = (start + remaining).min(length);
+		if index % 2 == 0 && end > start {
+			// The default error bound is relative to the length of each segment, which is too coarse for placing the ends of short dashes on long segments
+			let [t1, t2] = [start, end].map(|distance| SubpathTValue::GlobalEuclideanWithinError {
+				t: distance / length,
+				error: 0.01 / length,
+			});
+			dashes.push(subpath.trim(t1, t2));
+		}
+		start = end;
+		index = (index + 1) % pattern.len();
+		remaining = pattern[index];
+	}
+	dashes
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DashPathNode<DashLengths, DashOffset> {
+	dash_lengths: DashLengths,
+	dash_offset: DashOffset,
+}
+
+/// Cuts the path into the separate open subpaths of each dash, so the dashes are real geometry instead of only a way of drawing the stroke.
+#[node_macro::node_fn(DashPathNode, category = "Vector", output = "Vector")]
+fn dash_path(mut vector_data: VectorData, #[input(default = vec![10., 5.])] dash_lengths: Vec<f32>, #[input(default = 0., unit = "px")] dash_offset: f64) -> VectorData {
+	let dash_lengths = dash_lengths.into_iter().map(f64::from).collect::<Vec<_>>();
+
+	let mut dashes = Vec::with_capacity(vector_data.subpaths.len());
+	for mut subpath in std::mem::take(&mut vector_data.subpaths) {
+		// The lengths are measured in the document space, so the path is dashed after the transform is applied
+		subpath.apply_transform(vector_data.transform);
+		for mut dash in dash_subpath(&subpath, &dash_lengths, dash_offset) {
+			dash.apply_transform(vector_data.transform.inverse());
+			dashes.push(dash);
+		}
+	}
+
+	vector_data.subpaths = dashes;
+	vector_data
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct OffsetPathNode<Distance, LineJoin, MiterLimit> {
+	distance: Distance,
+	line_join: LineJoin,
+	miter_limit: MiterLimit,
+}
+
+#[node_macro::node_fn(OffsetPathNode, category = "Vector", output = "Vector")]
+fn offset_path(
+	mut vector_data: VectorData,
+	#[input(default = 10., unit = "px")] distance: f64,
+	#[input(default = LineJoin::Miter)] line_join: LineJoin,
+	#[input(default = 4., min = 1.)] miter_limit: f64,
+) -> VectorData {
+	let join = to_join(line_join, miter_limit);
+
+	for subpath in &mut vector_data.subpaths {
+		if !can_offset(subpath) || !distance.is_finite() {
+			continue;
+		}
+
+		// The distance is measured in the document space, so the offset happens after the transform is applied
+		subpath.apply_transform(vector_data.transform);
+		remove_degenerate_handles(subpath);
+		*subpath = subpath.offset(distance, join);
+		subpath.apply_transform(vector_data.transform.inverse());
+	}
+
+	vector_data
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct OutlineStrokeNode;
+
+/// Replaces the stroke with filled geometry covering the same area, including the varying width of a stroke with a width profile, its alignment, and its dashes.
+#[node_macro::node_fn(OutlineStrokeNode, category = "Vector", output = "Vector")]
+fn outline_stroke(mut vector_data: VectorData) -> VectorData {
+	let Some(stroke) = vector_data.style.stroke().filter(|stroke| stroke.weight > 0.) else {
+		return vector_data;
+	};
+
+	let mut outlines = Vec::with_capacity(vector_data.subpaths.len());
+	for mut subpath in std::mem::take(&mut vector_data.subpaths) {
+		if subpath.is_empty() {
+			continue;
+		}
+
+		subpath.apply_transform(vector_data.transform);
+		let pieces = if stroke.dash_lengths.is_empty() {
+			vec![subpath]
+		} else {
+			let dash_lengths = stroke.dash_lengths.iter().copied().map(f64::from).collect::<Vec<_>>();
+			dash_subpath(&subpath, &dash_lengths, stroke.dash_offset)
+		};
+		// A closed subpath produces an outer and an inner outline with opposite windings, which leaves the inside of the ring unfilled
+		for mut outline in pieces.iter().flat_map(|piece| stroke_outline(piece, &stroke)) {
+			outline.apply_transform(vector_data.transform.inverse());
+			outlines.push(outline);
+		}
+	}
+
+	vector_data.subpaths = outlines;
+	vector_data.style.clear_stroke();
+	vector_data.style.set_fill(stroke.color.map_or(Fill::None, Fill::Solid));
+	vector_data
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SimplifyPathNode<Tolerance> {
+	tolerance: Tolerance,
+}
+
+#[node_macro::node_fn(SimplifyPathNode, category = "Vector", output = "Vector")]
+fn simplify_path(mut vector_data: VectorData, #[input(default = 1., unit = "px", min = 0.)] tolerance: f64) -> VectorData {
+	if tolerance <= 0. || !tolerance.is_finite() {
+		return vector_data;
+	}
+
+	for subpath in &mut vector_data.subpaths {
+		if subpath.len_segments() < 2 {
+			continue;
+		}
+
+		// The tolerance is measured in the document space
+		subpath.apply_transform(vector_data.transform);
+		*subpath = simplify_subpath(subpath, tolerance);
+		subpath.apply_transform(vector_data.transform.inverse());
+	}
+
+	vector_data
+}
+
+/// Anchors where the direction changes by more than this angle (in radians) are corners, which are kept by the simplification.
+const SIMPLIFY_CORNER_ANGLE: f64 = 0.2;
+/// The number of points sampled along each segment, which the simplified curves are fit through.
+const SIMPLIFY_SAMPLES_PER_SEGMENT: usize = 16;
+
+/// Replaces the segments between the corners of the subpath with as few cubic curves as stay within `tolerance` of the original shape.
+fn simplify_subpath(subpath: &Subpath<ManipulatorGroupId>, tolerance: f64) -> Subpath<ManipulatorGroupId> {
+	let segments = subpath.iter().filter(|bezier| !bezier.is_point()).collect::<Vec<_>>();
+	if segments.len() < 2 {
+		return subpath.clone();
+	}
+
+	let is_corner = |incoming: &Bezier, outgoing: &Bezier| end_direction(incoming).angle_between(start_direction(outgoing)).abs() > SIMPLIFY_CORNER_ANGLE;
+
+	// Split the segments into runs which start and end at corners (or the ends of an open subpath)
+	let mut corners = (1..segments.len()).filter(|&index| is_corner(&segments[index - 1], &segments[index])).collect::<Vec<_>>();
+	let closed = subpath.closed();
+	let rotation = if closed {
+		if is_corner(&segments[segments.len() - 1], &segments[0]) {
+			0
+		} else {
+			corners.first().copied().unwrap_or(0)
+		}
+	} else {
+		0
+	};
+	let mut ordered_segments = segments[rotation..].to_vec();
+	ordered_segments.extend_from_slice(&segments[..rotation]);
+	corners = corners
+		.into_iter()
+		.map(|index| (index + segments.len() - rotation) % segments.len())
+		.filter(|&index| index != 0)
+		.collect();
+	corners.sort_unstable();
+
+	let mut cubics = Vec::new();
+	let run_bounds = std::iter::once(0)
+		.chain(corners.iter().copied())
+		.zip(corners.iter().copied().chain(std::iter::once(ordered_segments.len())));
+	for (start, end) in run_bounds {
+		let run = &ordered_segments[start..end];
+
+		let mut points = vec![run[0].start()];
+		for bezier in run {
+			points.extend((1..=SIMPLIFY_SAMPLES_PER_SEGMENT).map(|sample| bezier.evaluate(TValue::Parametric(sample as f64 / SIMPLIFY_SAMPLES_PER_SEGMENT as f64))));
+		}
+		points.dedup_by(|a, b| a.abs_diff_eq(*b, 1e-9));
+
+		let start_tangent = start_direction(&run[0]);
+		let end_tangent = -end_direction(&run[run.len() - 1]);
+		fit_cubics(&points, start_tangent, end_tangent, tolerance, &mut cubics);
+	}
+
+	// Join the fitted curves into manipulator groups, sharing the anchor between consecutive curves
+	let mut manipulator_groups: Vec<ManipulatorGroup<ManipulatorGroupId>> = Vec::with_capacity(cubics.len() + 1);
+	for &[start, handle_start, handle_end, end] in &cubics {
+		match manipulator_groups.last_mut() {
+			Some(previous) => previous.out_handle = Some(handle_start),
+			None => manipulator_groups.push(ManipulatorGroup::new(start, None, Some(handle_start))),
+		}
+		manipulator_groups.push(ManipulatorGroup::new(end, Some(handle_end), None));
+	}
+	if closed && manipulator_groups.len() > 1 {
+		let last = manipulator_groups.pop().unwrap();
+		manipulator_groups[0].in_handle = last.in_handle;
+	}
+
+	Subpath::new(manipulator_groups, closed)
+}
+
+/// The direction the curve leaves its start in, which is found slightly along the curve if its handle sits on the anchor.
+pub(super) fn start_direction(bezier: &Bezier) -> DVec2 {
+	let tangent = bezier.tangent(TValue::Parametric(0.));
+	if tangent.length_squared() > 0. {
+		return tangent;
+	}
+	(bezier.evaluate(TValue::Parametric(0.01)) - bezier.start()).normalize_or_zero()
+}
+
+/// The direction the curve arrives at its end in, which is found slightly before the end if its handle sits on the anchor.
+pub(super) fn end_direction(bezier: &Bezier) -> DVec2 {
+	let tangent = bezier.tangent(TValue::Parametric(1.));
+	if tangent.length_squared() > 0. {
+		return tangent;
+	}
+	(bezier.end() - bezier.evaluate(TValue::Parametric(0.99))).normalize_or_zero()
+}
+
+/// Fits cubic curves through the points in order, recursively splitting where a single curve would deviate by more than `tolerance`.
+/// The tangents are unit vectors pointing from each end of the points towards their inside.
+/// This is the algorithm from "An Algorithm for Automatically Fitting Digitized Curves" by Philip J. Schneider in Graphics Gems.
+fn fit_cubics(points: &[DVec2], start_tangent: DVec2, end_tangent: DVec2, tolerance: f64, cubics: &mut Vec<[DVec2; 4]>) {
+	let (first, last) = (points[0], points[points.len() - 1]);
+	let start_tangent = if start_tangent.length_squared() > 0. {
+		start_tangent
+	} else {
+		(points[1] - first).normalize_or_zero()
+	};
+	let end_tangent = if end_tangent.length_squared() > 0. {
+		end_tangent
+	} else {
+		(points[points.len() - 2] - last).normalize_or_zero()
+	};
+
+	if points.len() == 2 {
+		let distance = first.distance(last) / 3.;
+		cubics.push([first, first + start_tangent * distance, last + end_tangent * distance, last]);
+		return;
+	}
+
+	// Parameterize the points by their distance along the polyline through them
+	let mut parameters = Vec::with_capacity(points.len());
+	parameters.push(0.);
+	for pair in points.windows(2) {
+		parameters.push(parameters[parameters.len() - 1] + pair[0].distance(pair[1]));
+	}
+	let total_length = parameters[parameters.len() - 1];
+	if total_length <= 0. {
+		return;
+	}
+	parameters.iter_mut().for_each(|parameter| *parameter /= total_length);
+
+	let tolerance_squared = tolerance * tolerance;
+	let mut cubic = fit_single_cubic(points, &parameters, start_tangent, end_tangent);
+	let (mut max_error, mut split_index) = max_fit_error(points, &parameters, &cubic);
+	if max_error <= tolerance_squared {
+		cubics.push(cubic);
+		return;
+	}
+
+	// When the fit is close, improving the parameters with Newton-Raphson iteration may bring it within the tolerance
+	if max_error <= tolerance_squared * 4. {
+		for _ in 0..4 {
+			parameters = parameters.iter().zip(points).map(|(&parameter, &point)| newton_raphson_root(&cubic, point, parameter)).collect();
+			cubic = fit_single_cubic(points, &parameters, start_tangent, end_tangent);
+			(max_error, split_index) = max_fit_error(points, &parameters, &cubic);
+			if max_error <= tolerance_squared {
+				cubics.push(cubic);
+				return;
+			}
+		}
+	}
+
+	let split_index = split_index.clamp(1, points.len() - 2);
+	let center_tangent = (points[split_index - 1] - points[split_index + 1]).normalize_or_zero();
+	fit_cubics(&points[..=split_index], start_tangent, center_tangent, tolerance, cubics);
+	fit_cubics(&points[split_index..], -center_tangent, end_tangent, tolerance, cubics);
+}
+
+fn evaluate_cubic([p0, p1, p2, p3]: &[DVec2; 4], t: f64) -> DVec2 {
+	let mt = 1. - t;
+	p0 * (mt * mt * mt) + p1 * (3. * mt * mt * t) + p2 * (3. * mt * t * t) + p3 * (t * t * t)
+}
+
+/// Finds the handle lengths along the given tangents which best fit the points in the least squares sense.
+fn fit_single_cubic(points: &[DVec2], parameters: &[f64], start_tangent: DVec2, end_tangent: DVec2) -> [DVec2; 4] {
+	let (first, last) = (points[0], points[points.len() - 1]);
+
+	let (mut c00, mut c01, mut c11, mut x0, mut x1) = (0., 0., 0., 0., 0.);
+	for (&point, &t) in points.iter().zip(parameters) {
+		let mt = 1. - t;
+		let a0 = start_tangent * (3. * mt * mt * t);
+		let a1 = end_tangent * (3. * mt * t * t);
+		c00 += a0.dot(a0);
+		c01 += a0.dot(a1);
+		c11 += a1.dot(a1);
+
+		let difference = point - evaluate_cubic(&[first, first, last, last], t);
+		x0 += a0.dot(difference);
+		x1 += a1.dot(difference);
+	}
+
+	let determinant = c00 * c11 - c01 * c01;
+	let (alpha_start, alpha_end) = if determinant.abs() > 1e-12 {
+		((x0 * c11 - x1 * c01) / determinant, (c00 * x1 - c01 * x0) / determinant)
+	} else {
+		(0., 0.)
+	};
+
+	// Fall back to handles a third of the way along the chord when the solution is degenerate or points the wrong way
+	let chord_third = first.distance(last) / 3.;
+	let epsilon = 1e-6 * chord_third;
+	let (alpha_start, alpha_end) = if alpha_start < epsilon || alpha_end < epsilon {
+		(chord_third, chord_third)
+	} else {
+		(alpha_start, alpha_end)
+	};
+
+	[first, first + start_tangent * alpha_start, last + end_tangent * alpha_end, last]
+}
+
+/// Returns the largest squared distance between a point and the curve at its parameter, along with the index of that point.
+fn max_fit_error(points: &[DVec2], parameters: &[f64], cubic: &[DVec2; 4]) -> (f64, usize) {
+	points
+		.iter()
+		.zip(parameters)
+		.enumerate()
+		.map(|(index, (&point, &t))| (evaluate_cubic(cubic, t).distance_squared(point), index))
+		.fold((0., points.len() / 2), |max, current| if current.0 > max.0 { current } else { max })
+}
+
+/// Improves the parameter of the point on the cubic closest to `point` with a step of Newton's method.
+fn newton_raphson_root(cubic: &[DVec2; 4], point: DVec2, t: f64) -> f64 {
+	let [p0, p1, p2, p3] = *cubic;
+	let first_derivative = [(p1 - p0) * 3., (p2 - p1) * 3., (p3 - p2) * 3.];
+	let second_derivative = [(first_derivative[1] - first_derivative[0]) * 2., (first_derivative[2] - first_derivative[1]) * 2.];
+
+	let mt = 1. - t;
+	let position = evaluate_cubic(cubic, t);
+	let velocity = first_derivative[0] * (mt * mt) + first_derivative[1] * (2. * mt * t) + first_derivative[2] * (t * t);
+	let acceleration = second_derivative[0] * mt + second_derivative[1] * t;
+
+	let numerator = (position - point).dot(velocity);
+	let denominator = velocity.dot(velocity) + (position - point).dot(acceleration);
+	if denominator.abs() < 1e-12 {
+		return t;
+	}
+	(t - numerator / denominator).clamp(0., 1.)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EnclosedRegionNode<Point> {
+	point: Point,
+}
+
+/// The region enclosed by the crossing paths around `point`, which is given before the transform is applied. The paths are replaced by the region, which has no style of its own.
+#[node_macro::node_fn(EnclosedRegionNode, category = "Vector", output = "Vector")]
+fn enclosed_region(vector_data: VectorData, #[input(default = DVec2::ZERO, unit = "px")] point: DVec2) -> VectorData {
+	let subpaths = PlanarArrangement::new(&vector_data.subpaths).region_at(point).unwrap_or_default();
+	VectorData {
+		subpaths,
+		transform: vector_data.transform,
+		..VectorData::empty()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn simplify_merges_collinear_segments() {
+		let anchors = (0..=10).map(|x| DVec2::new(x as f64 * 10., 0.));
+		let subpath = Subpath::<ManipulatorGroupId>::from_anchors(anchors, false);
+
+		let simplified = simplify_subpath(&subpath, 0.1);
+		assert_eq!(simplified.anchors(), vec![DVec2::ZERO, DVec2::new(100., 0.)]);
+	}
+
+	#[test]
+	fn simplify_keeps_corners() {
+		let subpath = Subpath::<ManipulatorGroupId>::new_rect(DVec2::ZERO, DVec2::splat(100.));
+
+		let simplified = simplify_subpath(&subpath, 1.);
+		assert_eq!(simplified.len(), 4);
+		assert!(simplified.closed());
+		for anchor in subpath.anchors() {
+			assert!(simplified.anchors().iter().any(|simplified_anchor| simplified_anchor.abs_diff_eq(anchor, 1e-9)));
+		}
+	}
+
+	#[test]
+	fn simplify_stays_within_tolerance() {
+		// A dense polyline approximating a quarter circle
+		let anchors = (0..=90).map(|degrees| DVec2::from_angle((degrees as f64).to_radians()) * 100.);
+		let subpath = Subpath::<ManipulatorGroupId>::from_anchors(anchors, false);
+
+		let tolerance = 0.5;
+		let simplified = simplify_subpath(&subpath, tolerance);
+		assert!(simplified.len() < 10);
+		for step in 0..=100 {
+			let point = simplified.evaluate(SubpathTValue::GlobalParametric(step as f64 / 100.));
+			assert!((point.length() - 100.).abs() < tolerance * 2.);
+		}
+	}
+
+	#[test]
+	fn outline_stroke_fills_with_stroke_color() {
+		let mut vector_data = VectorData::from_subpath(Subpath::new(
+			vec![ManipulatorGroup::new(DVec2::ZERO, None, None), ManipulatorGroup::new(DVec2::new(100., 0.), None, None)],
+			false,
+		));
+		vector_data.style.set_stroke(Stroke::new(Some(Color::RED), 10.));
+
+		let outlined = OutlineStrokeNode.eval(vector_data);
+		assert_eq!(outlined.style.stroke(), None);
+		assert_eq!(*outlined.style.fill(), Fill::Solid(Color::RED));
+		let [min, max] = outlined.bounding_box().unwrap();
+		assert!(min.abs_diff_eq(DVec2::new(0., -5.), 1e-6));
+		assert!(max.abs_diff_eq(DVec2::new(100., 5.), 1e-6));
+	}
+
+	#[test]
+	fn width_profiles_vary_the_outline() {
+		let subpath = Subpath::<ManipulatorGroupId>::from_anchors([DVec2::ZERO, DVec2::new(100., 0.)], false);
+		let profile = StrokeProfile::new(vec![DVec2::new(0., 1.), DVec2::new(0.5, 3.), DVec2::new(1., 1.)], 0., 0.);
+		assert_eq!(profile.width_at(0.25), 2.);
+		assert_eq!(profile.breakpoints(), vec![0.5]);
+		let stroke = Stroke::new(Some(Color::RED), 10.).with_profile(profile);
+
+		let outlines = stroke_outline(&subpath, &stroke);
+		assert_eq!(outlines.len(), 1);
+		let [min, max] = outlines[0].bounding_box().unwrap();
+		assert!(min.abs_diff_eq(DVec2::new(0., -15.), 0.1));
+		assert!(max.abs_diff_eq(DVec2::new(100., 15.), 0.1));
+		// The outline is narrow at the ends, where the width multiplier is 1
+		assert!(outlines[0].anchors().iter().all(|anchor| anchor.x > 1. || anchor.y.abs() < 5.1));
+	}
+
+	#[test]
+	fn tapers_narrow_the_ends() {
+		let profile = StrokeProfile::new(Vec::new(), 0.25, 0.5);
+		assert!(!profile.is_uniform());
+		assert!(StrokeProfile::default().is_uniform());
+		assert_eq!(profile.width_at(0.), 0.);
+		assert_eq!(profile.width_at(0.125), 0.5);
+		assert_eq!(profile.width_at(0.3), 1.);
+		assert_eq!(profile.width_at(0.75), 0.5);
+		assert_eq!(profile.breakpoints(), vec![0.25, 0.5]);
+
+		let subpath = Subpath::<ManipulatorGroupId>::from_anchors([DVec2::ZERO, DVec2::new(100., 0.)], false);
+		let stroke = Stroke::new(Some(Color::RED), 10.).with_line_cap(LineCap::Round).with_profile(profile);
+		let outlines = stroke_outline(&subpath, &stroke);
+		let [min, max] = outlines[0].bounding_box().unwrap();
+		// Tapered ends aren't capped
+		assert!(min.abs_diff_eq(DVec2::new(0., -5.), 0.1));
+		assert!(max.abs_diff_eq(DVec2::new(100., 5.), 0.1));
+	}
+
+	fn dash_extents(dashes: &[Subpath<ManipulatorGroupId>]) -> Vec<(f64, f64)> {
+		dashes.iter().map(|dash| dash.bounding_box().unwrap()).map(|[min, max]| (min.x, max.x)).collect()
+	}
+
+	#[test]
+	fn dashes_follow_the_pattern() {
+		let subpath = Subpath::<ManipulatorGroupId>::from_anchors([DVec2::ZERO, DVec2::new(100., 0.)], false);
+		let close = |dashes: Vec<(f64, f64)>, expected: &[(f64, f64)]| {
+			assert_eq!(dashes.len(), expected.len(), "{dashes:?}");
+			assert!(dashes.iter().zip(expected).all(|(a, b)| (a.0 - b.0).abs() < 0.01 && (a.1 - b.1).abs() < 0.01), "{dashes:?}");
+		};
+
+		close(dash_extents(&dash_subpath(&subpath, &[20., 10.], 0.)), &[(0., 20.), (30., 50.), (60., 80.), (90., 100.)]);
+		// The offset starts the subpath 25 units into the pattern, which is partway through the first gap
+		close(dash_extents(&dash_subpath(&subpath, &[20., 10.], 25.)), &[(5., 25.), (35., 55.), (65., 85.), (95., 100.)]);
+		// An odd number of lengths is repeated, so each length is used for both a dash and a gap
+		close(dash_extents(&dash_subpath(&subpath, &[30.], 0.)), &[(0., 30.), (60., 90.)]);
+		// A pattern without length leaves the subpath solid
+		close(dash_extents(&dash_subpath(&subpath, &[0., 0.], 0.)), &[(0., 100.)]);
+		// So does a pattern too small to cut into a reasonable number of dashes
+		close(dash_extents(&dash_subpath(&subpath, &[0.0001], 0.)), &[(0., 100.)]);
+	}
+
+	#[test]
+	fn dashes_of_closed_subpaths_are_open() {
+		let square = Subpath::<ManipulatorGroupId>::new_rect(DVec2::ZERO, DVec2::splat(100.));
+		let dashes = dash_subpath(&square, &[50., 50.], 0.);
+		assert_eq!(dashes.len(), 4);
+		assert!(dashes.iter().all(|dash| !dash.closed()));
+		assert!(dashes[1].anchors()[0].abs_diff_eq(DVec2::new(100., 0.), 1e-3));
+	}
+
+	#[test]
+	fn aligned_strokes_sit_on_one_side_of_closed_subpaths() {
+		let square = Subpath::<ManipulatorGroupId>::new_rect(DVec2::ZERO, DVec2::splat(100.));
+		let bounds = |align: StrokeAlign| {
+			let outlines = stroke_outline(&square, &Stroke::new(Some(Color::RED), 10.).with_align(align));
+			outlines.iter().map(|outline| outline.bounding_box().unwrap()).collect::<Vec<_>>()
+		};
+
+		let inside = bounds(StrokeAlign::Inside);
+		assert_eq!(inside.len(), 2);
+		assert!(inside.iter().any(|[min, max]| min.abs_diff_eq(DVec2::splat(10.), 1e-6) && max.abs_diff_eq(DVec2::splat(90.), 1e-6)));
+		assert!(inside.iter().any(|[min, max]| min.abs_diff_eq(DVec2::ZERO, 1e-6) && max.abs_diff_eq(DVec2::splat(100.), 1e-6)));
+
+		let outside = bounds(StrokeAlign::Outside);
+		assert!(outside.iter().any(|[min, max]| min.abs_diff_eq(DVec2::splat(-10.), 1e-6) && max.abs_diff_eq(DVec2::splat(110.), 1e-6)));
+		assert!(outside.iter().any(|[min, max]| min.abs_diff_eq(DVec2::ZERO, 1e-6) && max.abs_diff_eq(DVec2::splat(100.), 1e-6)));
+
+		// Open subpaths have no inside, so their stroke stays centered
+		let line = Subpath::<ManipulatorGroupId>::from_anchors([DVec2::ZERO, DVec2::new(100., 0.)], false);
+		let outlines = stroke_outline(&line, &Stroke::new(Some(Color::RED), 10.).with_align(StrokeAlign::Inside));
+		let [min, max] = outlines[0].bounding_box().unwrap();
+		assert!(min.abs_diff_eq(DVec2::new(0., -5.), 1e-6) && max.abs_diff_eq(DVec2::new(100., 5.), 1e-6));
+	}
+}
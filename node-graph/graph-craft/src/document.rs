@@ -1045,6 +1045,22 @@ impl NodeNetwork {
 		}
 	}
 
+	/// Replaces every keyframed input value, including those in nested networks, with its value at the given time in seconds.
+	pub fn sample_animated_inputs(&mut self, time: f64) {
+		for node in self.nodes.values_mut() {
+			if let DocumentNodeImplementation::Network(network) = &mut node.implementation {
+				network.sample_animated_inputs(time);
+			}
+			for input in &mut node.inputs {
+				if let NodeInput::Value { tagged_value, .. } = input {
+					if let Some(sampled) = tagged_value.sample_animation(time) {
+						*tagged_value = sampled;
+					}
+				}
+			}
+		}
+	}
+
 	/// Creates a proto network for evaluating each output of this network.
 	pub fn into_proto_networks(self) -> impl Iterator<Item = ProtoNetwork> {
 		let mut nodes: Vec<_> = self.nodes.into_iter().map(|(id, node)| (id, node.resolve_proto_node())).collect();
@@ -1405,6 +1421,40 @@ mod test {
 		assert_eq!(ids, vec![11, 10010], "Should only contain identity and values");
 	}
 
+	#[test]
+	fn sample_nested_animated_inputs() {
+		use graphene_core::animation::{Interpolation, KeyframeTrack};
+
+		let mut track = KeyframeTrack::new(0.);
+		track.insert(2., 10., Interpolation::Linear);
+		let animated_node = DocumentNode {
+			name: "Animated".into(),
+			inputs: vec![NodeInput::value(TaggedValue::AnimatedF64(track), false)],
+			implementation: DocumentNodeImplementation::Unresolved("graphene_core::ops::IdNode".into()),
+			..Default::default()
+		};
+		let mut network = NodeNetwork {
+			inputs: vec![],
+			outputs: vec![NodeOutput::new(0, 0)],
+			nodes: [(
+				0,
+				DocumentNode {
+					name: "Nested".into(),
+					inputs: vec![],
+					implementation: DocumentNodeImplementation::Network(NodeNetwork::value_network(animated_node)),
+					..Default::default()
+				},
+			)]
+			.into_iter()
+			.collect(),
+			..Default::default()
+		};
+
+		network.sample_animated_inputs(0.5);
+		let nested_network = network.nodes[&0].implementation.get_network().unwrap();
+		assert_eq!(nested_network.nodes[&0].inputs, vec![NodeInput::value(TaggedValue::F64(2.5), false)]);
+	}
+
 	// TODO: Write more tests
 	/*
 	#[test]
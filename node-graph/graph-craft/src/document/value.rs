@@ -3,6 +3,7 @@ use crate::graphene_compiler::Any;
 pub use crate::imaginate_input::{ImaginateCache, ImaginateController, ImaginateMaskStartingFill, ImaginateSamplingMethod};
 use crate::proto::{Any as DAny, FutureAny};
 
+use graphene_core::animation::{Interpolation, KeyframeTrack};
 use graphene_core::raster::brush_cache::BrushCache;
 use graphene_core::raster::{BlendMode, LuminanceCalculation};
 use graphene_core::{Color, Node, Type};
@@ -29,6 +30,11 @@ pub enum TaggedValue {
 	Image(graphene_core::raster::Image<Color>),
 	ImaginateCache(ImaginateCache),
 	ImageFrame(graphene_core::raster::ImageFrame<Color>),
+	ImageFrameSRGBA8(graphene_core::raster::ImageFrame<graphene_core::raster::SRGBA8>),
+	ImageFrameSRGBA16(graphene_core::raster::ImageFrame<graphene_core::raster::SRGBA16>),
+	ImageFrameRGBA16F(graphene_core::raster::ImageFrame<graphene_core::raster::RGBA16F>),
+	ImageFrameLumaAlpha(graphene_core::raster::ImageFrame<graphene_core::raster::LumaAlpha>),
+	ImageFrameLuma(graphene_core::raster::ImageFrame<graphene_core::raster::Luma>),
 	Color(graphene_core::raster::color::Color),
 	Subpaths(Vec<bezier_rs::Subpath<graphene_core::uuid::ManipulatorGroupId>>),
 	RcSubpath(Arc<bezier_rs::Subpath<graphene_core::uuid::ManipulatorGroupId>>),
@@ -49,6 +55,7 @@ pub enum TaggedValue {
 	SelectiveColorChoice(graphene_core::raster::SelectiveColorChoice),
 	LineCap(graphene_core::vector::style::LineCap),
 	LineJoin(graphene_core::vector::style::LineJoin),
+	StrokeAlign(graphene_core::vector::style::StrokeAlign),
 	FillType(graphene_core::vector::style::FillType),
 	GradientType(graphene_core::vector::style::GradientType),
 	GradientPositions(Vec<(f64, Option<graphene_core::Color>)>),
@@ -58,6 +65,11 @@ pub enum TaggedValue {
 	Font(graphene_core::text::Font),
 	BrushStrokes(Vec<graphene_core::vector::brush_stroke::BrushStroke>),
 	BrushCache(BrushCache),
+	FloodFills(Vec<graphene_core::raster::flood_fill::FloodFill>),
+	LayerEffects(Vec<graphene_core::layer_effects::LayerEffect>),
+	TextAlignment(graphene_core::text::TextAlignment),
+	FontFeatures(graphene_core::text::FontFeatures),
+	PixelSelection(graphene_core::raster::pixel_selection::PixelSelection),
 	Segments(Vec<graphene_core::raster::ImageFrame<Color>>),
 	DocumentNode(DocumentNode),
 	GraphicGroup(graphene_core::GraphicGroup),
@@ -67,6 +79,10 @@ pub enum TaggedValue {
 	SurfaceFrame(graphene_core::SurfaceFrame),
 	Footprint(graphene_core::transform::Footprint),
 	RenderOutput(RenderOutput),
+	AnimatedF64(KeyframeTrack<f64>),
+	AnimatedDVec2(KeyframeTrack<DVec2>),
+	AnimatedColor(KeyframeTrack<Color>),
+	AnimatedDAffine2(KeyframeTrack<DAffine2>),
 }
 
 #[allow(clippy::derived_hash_with_manual_eq)]
@@ -99,6 +115,11 @@ impl Hash for TaggedValue {
 			Self::ImaginateController(s) => s.hash(state),
 			Self::LayerPath(p) => p.hash(state),
 			Self::ImageFrame(i) => i.hash(state),
+			Self::ImageFrameSRGBA8(i) => i.hash(state),
+			Self::ImageFrameSRGBA16(i) => i.hash(state),
+			Self::ImageFrameRGBA16F(i) => i.hash(state),
+			Self::ImageFrameLumaAlpha(i) => i.hash(state),
+			Self::ImageFrameLuma(i) => i.hash(state),
 			Self::VectorData(vector_data) => vector_data.hash(state),
 			Self::Fill(fill) => fill.hash(state),
 			Self::Stroke(stroke) => stroke.hash(state),
@@ -110,6 +131,7 @@ impl Hash for TaggedValue {
 			Self::SelectiveColorChoice(selective_color_choice) => selective_color_choice.hash(state),
 			Self::LineCap(line_cap) => line_cap.hash(state),
 			Self::LineJoin(line_join) => line_join.hash(state),
+			Self::StrokeAlign(align) => align.hash(state),
 			Self::FillType(fill_type) => fill_type.hash(state),
 			Self::GradientType(gradient_type) => gradient_type.hash(state),
 			Self::GradientPositions(gradient_positions) => {
@@ -125,6 +147,11 @@ impl Hash for TaggedValue {
 			Self::Font(font) => font.hash(state),
 			Self::BrushStrokes(brush_strokes) => brush_strokes.hash(state),
 			Self::BrushCache(brush_cache) => brush_cache.hash(state),
+			Self::FloodFills(flood_fills) => flood_fills.hash(state),
+			Self::LayerEffects(layer_effects) => layer_effects.hash(state),
+			Self::TextAlignment(text_alignment) => text_alignment.hash(state),
+			Self::FontFeatures(font_features) => font_features.hash(state),
+			Self::PixelSelection(pixel_selection) => pixel_selection.hash(state),
 			Self::Segments(segments) => {
 				for segment in segments {
 					segment.hash(state)
@@ -138,6 +165,10 @@ impl Hash for TaggedValue {
 			Self::SurfaceFrame(surface_id) => surface_id.hash(state),
 			Self::Footprint(footprint) => footprint.hash(state),
 			Self::RenderOutput(render_output) => render_output.hash(state),
+			Self::AnimatedF64(track) => track.hash(state),
+			Self::AnimatedDVec2(track) => track.hash(state),
+			Self::AnimatedColor(track) => track.hash(state),
+			Self::AnimatedDAffine2(track) => track.hash(state),
 		}
 	}
 }
@@ -158,6 +189,11 @@ impl<'a> TaggedValue {
 			TaggedValue::Image(x) => Box::new(x),
 			TaggedValue::ImaginateCache(x) => Box::new(x),
 			TaggedValue::ImageFrame(x) => Box::new(x),
+			TaggedValue::ImageFrameSRGBA8(x) => Box::new(x),
+			TaggedValue::ImageFrameSRGBA16(x) => Box::new(x),
+			TaggedValue::ImageFrameRGBA16F(x) => Box::new(x),
+			TaggedValue::ImageFrameLumaAlpha(x) => Box::new(x),
+			TaggedValue::ImageFrameLuma(x) => Box::new(x),
 			TaggedValue::Color(x) => Box::new(x),
 			TaggedValue::Subpaths(x) => Box::new(x),
 			TaggedValue::RcSubpath(x) => Box::new(x),
@@ -178,6 +214,7 @@ impl<'a> TaggedValue {
 			TaggedValue::SelectiveColorChoice(x) => Box::new(x),
 			TaggedValue::LineCap(x) => Box::new(x),
 			TaggedValue::LineJoin(x) => Box::new(x),
+			TaggedValue::StrokeAlign(x) => Box::new(x),
 			TaggedValue::FillType(x) => Box::new(x),
 			TaggedValue::GradientType(x) => Box::new(x),
 			TaggedValue::GradientPositions(x) => Box::new(x),
@@ -187,6 +224,11 @@ impl<'a> TaggedValue {
 			TaggedValue::Font(x) => Box::new(x),
 			TaggedValue::BrushStrokes(x) => Box::new(x),
 			TaggedValue::BrushCache(x) => Box::new(x),
+			TaggedValue::FloodFills(x) => Box::new(x),
+			TaggedValue::LayerEffects(x) => Box::new(x),
+			TaggedValue::TextAlignment(x) => Box::new(x),
+			TaggedValue::FontFeatures(x) => Box::new(x),
+			TaggedValue::PixelSelection(x) => Box::new(x),
 			TaggedValue::Segments(x) => Box::new(x),
 			TaggedValue::DocumentNode(x) => Box::new(x),
 			TaggedValue::GraphicGroup(x) => Box::new(x),
@@ -196,6 +238,46 @@ impl<'a> TaggedValue {
 			TaggedValue::SurfaceFrame(x) => Box::new(x),
 			TaggedValue::Footprint(x) => Box::new(x),
 			TaggedValue::RenderOutput(x) => Box::new(x),
+			// Animated values are normally replaced by `sample_animation` before compilation, otherwise they hold their value at the start of the timeline
+			TaggedValue::AnimatedF64(x) => Box::new(x.sample(0.).unwrap_or_default()),
+			TaggedValue::AnimatedDVec2(x) => Box::new(x.sample(0.).unwrap_or_default()),
+			TaggedValue::AnimatedColor(x) => Box::new(x.sample(0.).unwrap_or_default()),
+			TaggedValue::AnimatedDAffine2(x) => Box::new(x.sample(0.).unwrap_or_default()),
+		}
+	}
+
+	/// Evaluates a keyframed value at the given time in seconds, returning `None` if the value isn't animated.
+	pub fn sample_animation(&self, time: f64) -> Option<TaggedValue> {
+		match self {
+			TaggedValue::AnimatedF64(track) => Some(TaggedValue::F64(track.sample(time).unwrap_or_default())),
+			TaggedValue::AnimatedDVec2(track) => Some(TaggedValue::DVec2(track.sample(time).unwrap_or_default())),
+			TaggedValue::AnimatedColor(track) => Some(TaggedValue::Color(track.sample(time).unwrap_or_default())),
+			TaggedValue::AnimatedDAffine2(track) => Some(TaggedValue::DAffine2(track.sample(time).unwrap_or_default())),
+			_ => None,
+		}
+	}
+
+	/// Adds a keyframe holding `value` at the given time in seconds if this is a keyframed value of the same type, returning whether it was added.
+	pub fn insert_keyframe(&mut self, time: f64, value: &TaggedValue) -> bool {
+		match (self, value) {
+			(TaggedValue::AnimatedF64(track), TaggedValue::F64(value)) => track.insert(time, *value, Interpolation::default()),
+			(TaggedValue::AnimatedDVec2(track), TaggedValue::DVec2(value)) => track.insert(time, *value, Interpolation::default()),
+			(TaggedValue::AnimatedColor(track), TaggedValue::Color(value)) => track.insert(time, *value, Interpolation::default()),
+			(TaggedValue::AnimatedDAffine2(track), TaggedValue::DAffine2(value)) => track.insert(time, *value, Interpolation::default()),
+			_ => return false,
+		}
+		true
+	}
+
+	/// Converts a constant value into a keyframed one holding that value, returning `None` for types which can't be animated.
+	pub fn into_animated(self) -> Option<TaggedValue> {
+		match self {
+			TaggedValue::F64(x) => Some(TaggedValue::AnimatedF64(KeyframeTrack::new(x))),
+			TaggedValue::DVec2(x) => Some(TaggedValue::AnimatedDVec2(KeyframeTrack::new(x))),
+			TaggedValue::Color(x) => Some(TaggedValue::AnimatedColor(KeyframeTrack::new(x))),
+			TaggedValue::DAffine2(x) => Some(TaggedValue::AnimatedDAffine2(KeyframeTrack::new(x))),
+			animated @ (TaggedValue::AnimatedF64(_) | TaggedValue::AnimatedDVec2(_) | TaggedValue::AnimatedColor(_) | TaggedValue::AnimatedDAffine2(_)) => Some(animated),
+			_ => None,
 		}
 	}
 
@@ -226,6 +308,11 @@ impl<'a> TaggedValue {
 			TaggedValue::Image(_) => concrete!(graphene_core::raster::Image<Color>),
 			TaggedValue::ImaginateCache(_) => concrete!(ImaginateCache),
 			TaggedValue::ImageFrame(_) => concrete!(graphene_core::raster::ImageFrame<Color>),
+			TaggedValue::ImageFrameSRGBA8(_) => concrete!(graphene_core::raster::ImageFrame<graphene_core::raster::SRGBA8>),
+			TaggedValue::ImageFrameSRGBA16(_) => concrete!(graphene_core::raster::ImageFrame<graphene_core::raster::SRGBA16>),
+			TaggedValue::ImageFrameRGBA16F(_) => concrete!(graphene_core::raster::ImageFrame<graphene_core::raster::RGBA16F>),
+			TaggedValue::ImageFrameLumaAlpha(_) => concrete!(graphene_core::raster::ImageFrame<graphene_core::raster::LumaAlpha>),
+			TaggedValue::ImageFrameLuma(_) => concrete!(graphene_core::raster::ImageFrame<graphene_core::raster::Luma>),
 			TaggedValue::Color(_) => concrete!(graphene_core::raster::Color),
 			TaggedValue::Subpaths(_) => concrete!(Vec<bezier_rs::Subpath<graphene_core::uuid::ManipulatorGroupId>>),
 			TaggedValue::RcSubpath(_) => concrete!(Arc<bezier_rs::Subpath<graphene_core::uuid::ManipulatorGroupId>>),
@@ -247,6 +334,7 @@ impl<'a> TaggedValue {
 			TaggedValue::SelectiveColorChoice(_) => concrete!(graphene_core::raster::SelectiveColorChoice),
 			TaggedValue::LineCap(_) => concrete!(graphene_core::vector::style::LineCap),
 			TaggedValue::LineJoin(_) => concrete!(graphene_core::vector::style::LineJoin),
+			TaggedValue::StrokeAlign(_) => concrete!(graphene_core::vector::style::StrokeAlign),
 			TaggedValue::FillType(_) => concrete!(graphene_core::vector::style::FillType),
 			TaggedValue::GradientType(_) => concrete!(graphene_core::vector::style::GradientType),
 			TaggedValue::GradientPositions(_) => concrete!(Vec<(f64, Option<graphene_core::Color>)>),
@@ -256,6 +344,11 @@ impl<'a> TaggedValue {
 			TaggedValue::Font(_) => concrete!(graphene_core::text::Font),
 			TaggedValue::BrushStrokes(_) => concrete!(Vec<graphene_core::vector::brush_stroke::BrushStroke>),
 			TaggedValue::BrushCache(_) => concrete!(BrushCache),
+			TaggedValue::FloodFills(_) => concrete!(Vec<graphene_core::raster::flood_fill::FloodFill>),
+			TaggedValue::LayerEffects(_) => concrete!(Vec<graphene_core::layer_effects::LayerEffect>),
+			TaggedValue::TextAlignment(_) => concrete!(graphene_core::text::TextAlignment),
+			TaggedValue::FontFeatures(_) => concrete!(graphene_core::text::FontFeatures),
+			TaggedValue::PixelSelection(_) => concrete!(graphene_core::raster::pixel_selection::PixelSelection),
 			TaggedValue::Segments(_) => concrete!(graphene_core::raster::IndexNode<Vec<graphene_core::raster::ImageFrame<Color>>>),
 			TaggedValue::DocumentNode(_) => concrete!(crate::document::DocumentNode),
 			TaggedValue::GraphicGroup(_) => concrete!(graphene_core::GraphicGroup),
@@ -265,6 +358,10 @@ impl<'a> TaggedValue {
 			TaggedValue::SurfaceFrame(_) => concrete!(graphene_core::SurfaceFrame),
 			TaggedValue::Footprint(_) => concrete!(graphene_core::transform::Footprint),
 			TaggedValue::RenderOutput(_) => concrete!(RenderOutput),
+			TaggedValue::AnimatedF64(_) => concrete!(f64),
+			TaggedValue::AnimatedDVec2(_) => concrete!(DVec2),
+			TaggedValue::AnimatedColor(_) => concrete!(graphene_core::raster::Color),
+			TaggedValue::AnimatedDAffine2(_) => concrete!(DAffine2),
 		}
 	}
 
@@ -284,6 +381,11 @@ impl<'a> TaggedValue {
 			x if x == TypeId::of::<graphene_core::raster::Image<Color>>() => Ok(TaggedValue::Image(*downcast(input).unwrap())),
 			x if x == TypeId::of::<ImaginateCache>() => Ok(TaggedValue::ImaginateCache(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::raster::ImageFrame<Color>>() => Ok(TaggedValue::ImageFrame(*downcast(input).unwrap())),
+			x if x == TypeId::of::<graphene_core::raster::ImageFrame<graphene_core::raster::SRGBA8>>() => Ok(TaggedValue::ImageFrameSRGBA8(*downcast(input).unwrap())),
+			x if x == TypeId::of::<graphene_core::raster::ImageFrame<graphene_core::raster::SRGBA16>>() => Ok(TaggedValue::ImageFrameSRGBA16(*downcast(input).unwrap())),
+			x if x == TypeId::of::<graphene_core::raster::ImageFrame<graphene_core::raster::RGBA16F>>() => Ok(TaggedValue::ImageFrameRGBA16F(*downcast(input).unwrap())),
+			x if x == TypeId::of::<graphene_core::raster::ImageFrame<graphene_core::raster::LumaAlpha>>() => Ok(TaggedValue::ImageFrameLumaAlpha(*downcast(input).unwrap())),
+			x if x == TypeId::of::<graphene_core::raster::ImageFrame<graphene_core::raster::Luma>>() => Ok(TaggedValue::ImageFrameLuma(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::raster::Color>() => Ok(TaggedValue::Color(*downcast(input).unwrap())),
 			x if x == TypeId::of::<Vec<bezier_rs::Subpath<graphene_core::uuid::ManipulatorGroupId>>>() => Ok(TaggedValue::Subpaths(*downcast(input).unwrap())),
 			x if x == TypeId::of::<Arc<bezier_rs::Subpath<graphene_core::uuid::ManipulatorGroupId>>>() => Ok(TaggedValue::RcSubpath(*downcast(input).unwrap())),
@@ -305,6 +407,7 @@ impl<'a> TaggedValue {
 			x if x == TypeId::of::<graphene_core::raster::SelectiveColorChoice>() => Ok(TaggedValue::SelectiveColorChoice(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::vector::style::LineCap>() => Ok(TaggedValue::LineCap(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::vector::style::LineJoin>() => Ok(TaggedValue::LineJoin(*downcast(input).unwrap())),
+			x if x == TypeId::of::<graphene_core::vector::style::StrokeAlign>() => Ok(TaggedValue::StrokeAlign(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::vector::style::FillType>() => Ok(TaggedValue::FillType(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::vector::style::GradientType>() => Ok(TaggedValue::GradientType(*downcast(input).unwrap())),
 			x if x == TypeId::of::<Vec<(f64, Option<graphene_core::Color>)>>() => Ok(TaggedValue::GradientPositions(*downcast(input).unwrap())),
@@ -314,6 +417,11 @@ impl<'a> TaggedValue {
 			x if x == TypeId::of::<graphene_core::text::Font>() => Ok(TaggedValue::Font(*downcast(input).unwrap())),
 			x if x == TypeId::of::<Vec<graphene_core::vector::brush_stroke::BrushStroke>>() => Ok(TaggedValue::BrushStrokes(*downcast(input).unwrap())),
 			x if x == TypeId::of::<BrushCache>() => Ok(TaggedValue::BrushCache(*downcast(input).unwrap())),
+			x if x == TypeId::of::<Vec<graphene_core::raster::flood_fill::FloodFill>>() => Ok(TaggedValue::FloodFills(*downcast(input).unwrap())),
+			x if x == TypeId::of::<Vec<graphene_core::layer_effects::LayerEffect>>() => Ok(TaggedValue::LayerEffects(*downcast(input).unwrap())),
+			x if x == TypeId::of::<graphene_core::text::TextAlignment>() => Ok(TaggedValue::TextAlignment(*downcast(input).unwrap())),
+			x if x == TypeId::of::<graphene_core::text::FontFeatures>() => Ok(TaggedValue::FontFeatures(*downcast(input).unwrap())),
+			x if x == TypeId::of::<graphene_core::raster::pixel_selection::PixelSelection>() => Ok(TaggedValue::PixelSelection(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::raster::IndexNode<Vec<graphene_core::raster::ImageFrame<Color>>>>() => Ok(TaggedValue::Segments(*downcast(input).unwrap())),
 			x if x == TypeId::of::<crate::document::DocumentNode>() => Ok(TaggedValue::DocumentNode(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::GraphicGroup>() => Ok(TaggedValue::GraphicGroup(*downcast(input).unwrap())),
@@ -353,4 +461,6 @@ pub enum RenderOutput {
 	CanvasFrame(graphene_core::SurfaceFrame),
 	Svg(String),
 	Raster(Vec<u8>),
+	/// The bytes of a PDF file, or why the artwork couldn't be written as one
+	Pdf(Result<Vec<u8>, String>),
 }
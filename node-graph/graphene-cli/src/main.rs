@@ -12,7 +12,7 @@ use graph_craft::{
 };
 use graphene_core::{
 	application_io::{ApplicationIo, NodeGraphUpdateSender},
-	text::FontCache,
+	text::{fallback_fonts, FontCache, LocalFontProvider},
 };
 use graphene_std::wasm_application_io::{WasmApplicationIo, WasmEditorApi};
 use interpreted_executor::dynamic_executor::DynamicExecutor;
@@ -48,13 +48,20 @@ async fn main() -> Result<(), Box<dyn Error>> {
 		device.poll(wgpu::Maintain::Poll);
 	});
 
+	// Text is rendered with the fonts installed on the system since there is no frontend to load them
+	let mut font_cache = FontCache::default();
+	font_cache.add_provider(Arc::new(LocalFontProvider::system()));
+	font_cache.set_fallback_fonts(fallback_fonts());
+
 	let editor_api = WasmEditorApi {
 		image_frame: None,
-		font_cache: &FontCache::default(),
+		font_cache: &font_cache,
 		application_io: &application_io,
 		node_graph_message_sender: &UpdateLogger {},
 		imaginate_preferences: &ImaginatePreferences::default(),
 		render_config: graphene_core::application_io::RenderConfig::default(),
+		working_space: Default::default(),
+		display_transform: None,
 	};
 
 	loop {
@@ -209,6 +216,8 @@ mod test {
 			node_graph_message_sender: &UpdateLogger {},
 			imaginate_preferences: &ImaginatePreferences::default(),
 			render_config: graphene_core::application_io::RenderConfig::default(),
+			working_space: Default::default(),
+			display_transform: None,
 		};
 		let result = (&executor).execute(editor_api.clone()).await.unwrap();
 		println!("result: {result:?}");
@@ -226,6 +235,8 @@ mod test {
 			node_graph_message_sender: &UpdateLogger {},
 			imaginate_preferences: &ImaginatePreferences::default(),
 			render_config: graphene_core::application_io::RenderConfig::default(),
+			working_space: Default::default(),
+			display_transform: None,
 		};
 		let result = (&executor).execute(editor_api.clone()).await.unwrap();
 		println!("result: {result:?}");
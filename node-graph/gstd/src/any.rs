@@ -1,53 +1,11 @@
 use dyn_any::StaticType;
 pub use graph_craft::proto::{Any, NodeContainer, TypeErasedBox, TypeErasedNode};
 use graph_craft::proto::{DynFuture, FutureAny, SharedNodeContainer};
+pub use graphene_core::registry::{downcast_node, DowncastBothNode, DynAnyNode, FutureWrapperNode};
 use graphene_core::NodeIO;
 pub use graphene_core::{generic, ops, Node};
 use std::marker::PhantomData;
 
-pub struct DynAnyNode<I, O, Node> {
-	node: Node,
-	_i: PhantomData<I>,
-	_o: PhantomData<O>,
-}
-
-impl<'input, _I: 'input + StaticType, _O: 'input + StaticType, N: 'input> Node<'input, Any<'input>> for DynAnyNode<_I, _O, N>
-where
-	N: Node<'input, _I, Output = DynFuture<'input, _O>>,
-{
-	type Output = FutureAny<'input>;
-	#[inline]
-	fn eval(&'input self, input: Any<'input>) -> Self::Output {
-		let node_name = core::any::type_name::<N>();
-		let input: Box<_I> = dyn_any::downcast(input).unwrap_or_else(|e| panic!("DynAnyNode Input, {0} in:\n{1}", e, node_name));
-		let output = async move {
-			let result = self.node.eval(*input).await;
-			Box::new(result) as Any<'input>
-		};
-		Box::pin(output)
-	}
-
-	fn reset(&self) {
-		self.node.reset();
-	}
-
-	fn serialize(&self) -> Option<std::sync::Arc<dyn core::any::Any>> {
-		self.node.serialize()
-	}
-}
-impl<'input, _I: 'input + StaticType, _O: 'input + StaticType, N: 'input> DynAnyNode<_I, _O, N>
-where
-	N: Node<'input, _I, Output = DynFuture<'input, _O>>,
-{
-	pub const fn new(node: N) -> Self {
-		Self {
-			node,
-			_i: core::marker::PhantomData,
-			_o: core::marker::PhantomData,
-		}
-	}
-}
-
 pub struct DynAnyRefNode<I, O, Node> {
 	node: Node,
 	_i: PhantomData<(I, O)>,
@@ -99,33 +57,6 @@ impl<_I, _O, S0> DynAnyInRefNode<_I, _O, S0> {
 	}
 }
 
-pub struct FutureWrapperNode<Node> {
-	node: Node,
-}
-
-impl<'i, T: 'i, N: Node<'i, T>> Node<'i, T> for FutureWrapperNode<N>
-where
-	N: Node<'i, T>,
-{
-	type Output = DynFuture<'i, N::Output>;
-	fn eval(&'i self, input: T) -> Self::Output {
-		Box::pin(async move { self.node.eval(input) })
-	}
-	fn reset(&self) {
-		self.node.reset();
-	}
-
-	fn serialize(&self) -> Option<std::sync::Arc<dyn core::any::Any>> {
-		self.node.serialize()
-	}
-}
-
-impl<N> FutureWrapperNode<N> {
-	pub const fn new(node: N) -> Self {
-		Self { node }
-	}
-}
-
 pub trait IntoTypeErasedNode<'n> {
 	fn into_type_erased(self) -> TypeErasedBox<'n>;
 }
@@ -160,38 +91,6 @@ where
 	*out
 }
 
-/// Boxes the input and downcasts the output.
-/// Wraps around a node taking Box<dyn DynAny> and returning Box<dyn DynAny>
-#[derive(Clone)]
-pub struct DowncastBothNode<I, O> {
-	node: SharedNodeContainer,
-	_i: PhantomData<I>,
-	_o: PhantomData<O>,
-}
-impl<'input, O: 'input + StaticType, I: 'input + StaticType> Node<'input, I> for DowncastBothNode<I, O> {
-	type Output = DynFuture<'input, O>;
-	#[inline]
-	fn eval(&'input self, input: I) -> Self::Output {
-		{
-			let node_name = self.node.node_name();
-			let input = Box::new(input);
-			let future = self.node.eval(input);
-			Box::pin(async move {
-				let out = dyn_any::downcast(future.await).unwrap_or_else(|e| panic!("DowncastBothNode Input {e} in: \n{node_name}"));
-				*out
-			})
-		}
-	}
-}
-impl<I, O> DowncastBothNode<I, O> {
-	pub const fn new(node: SharedNodeContainer) -> Self {
-		Self {
-			node,
-			_i: core::marker::PhantomData,
-			_o: core::marker::PhantomData,
-		}
-	}
-}
 /// Boxes the input and downcasts the output.
 /// Wraps around a node taking Box<dyn DynAny> and returning Box<dyn DynAny>
 #[derive(Clone)]
@@ -243,9 +142,6 @@ impl ComposeTypeErased {
 pub fn input_node<O: StaticType>(n: SharedNodeContainer) -> DowncastBothNode<(), O> {
 	downcast_node(n)
 }
-pub fn downcast_node<I: StaticType, O: StaticType>(n: SharedNodeContainer) -> DowncastBothNode<I, O> {
-	DowncastBothNode::new(n)
-}
 
 pub struct PanicNode<I, O>(PhantomData<I>, PhantomData<O>);
 
@@ -272,7 +168,7 @@ mod test {
 	pub fn dyn_input_invalid_eval_panic() {
 		//let add = DynAnyNode::new(AddNode::new()).into_type_erased();
 		//add.eval(Box::new(&("32", 32u32)));
-		let dyn_any = DynAnyNode::<(u32, u32), u32, _>::new(FutureWrapperNode { node: AddNode::new() });
+		let dyn_any = DynAnyNode::<(u32, u32), u32, _>::new(FutureWrapperNode::new(AddNode::new()));
 		let type_erased = Box::new(dyn_any) as TypeErasedBox;
 		let _ref_type_erased = type_erased.as_ref();
 		//let type_erased = Box::pin(dyn_any) as TypeErasedBox<'_>;
@@ -283,7 +179,7 @@ mod test {
 	pub fn dyn_input_compose() {
 		//let add = DynAnyNode::new(AddNode::new()).into_type_erased();
 		//add.eval(Box::new(&("32", 32u32)));
-		let dyn_any = DynAnyNode::<(u32, u32), u32, _>::new(FutureWrapperNode { node: AddNode::new() });
+		let dyn_any = DynAnyNode::<(u32, u32), u32, _>::new(FutureWrapperNode::new(AddNode::new()));
 		let type_erased = Box::new(dyn_any) as TypeErasedBox<'_>;
 		type_erased.eval(Box::new((4u32, 2u32)));
 		let id_node = FutureWrapperNode::new(IdNode::new());
@@ -3,7 +3,8 @@ use crate::raster::{blend_image_closure, BlendImageTupleNode, EmptyImageNode, Ex
 use graphene_core::raster::adjustments::blend_colors;
 use graphene_core::raster::bbox::{AxisAlignedBbox, Bbox};
 use graphene_core::raster::brush_cache::BrushCache;
-use graphene_core::raster::{Alpha, Color, Image, ImageFrame, Pixel, Sample};
+use graphene_core::raster::pixel_selection::{limit_to_mask, PixelSelection};
+use graphene_core::raster::{Alpha, Color, Image, ImageFrame, Luma, Pixel, Sample};
 use graphene_core::raster::{BlendMode, BlendNode};
 use graphene_core::transform::{Transform, TransformMut};
 use graphene_core::value::{ClonedNode, CopiedNode, OnceCellNode, ValueNode};
@@ -347,6 +348,11 @@ async fn brush(image: ImageFrame<Color>, bounds: ImageFrame<Color>, strokes: Vec
 			cache.cache_results(core::mem::take(&mut draw_strokes), actual_image.clone(), stroke_texture.clone());
 		}
 
+		let stroke_texture = match &stroke.selection {
+			Some(selection) => mask_to_selection(stroke_texture, selection, &actual_image, &cache),
+			None => stroke_texture,
+		};
+
 		// TODO: Is this the correct way to do opacity in blending?
 		actual_image = blend_with_mode(actual_image, stroke_texture, stroke.style.blend_mode, stroke.style.color.a() * 100.0);
 	}
@@ -366,6 +372,7 @@ async fn brush(image: ImageFrame<Color>, bounds: ImageFrame<Color>, strokes: Vec
 				tex
 			});
 			let positions: Vec<_> = stroke.compute_blit_points().into_iter().collect();
+			let unmasked = stroke.selection.as_ref().map(|_| erase_restore_mask.clone());
 
 			match stroke.style.blend_mode {
 				BlendMode::Erase => {
@@ -383,6 +390,12 @@ async fn brush(image: ImageFrame<Color>, bounds: ImageFrame<Color>, strokes: Vec
 
 				_ => unreachable!(),
 			}
+
+			if let (Some(selection), Some(unmasked)) = (&stroke.selection, unmasked) {
+				let (width, height) = (erase_restore_mask.image.width, erase_restore_mask.image.height);
+				let mask = selection_mask(selection, erase_restore_mask.transform, width, height, &actual_image, &cache);
+				limit_to_mask(&mut erase_restore_mask, &unmasked, &mask);
+			}
 		}
 
 		let blend_params = BlendNode::new(CopiedNode::new(BlendMode::MultiplyAlpha), CopiedNode::new(100.0));
@@ -392,6 +405,26 @@ async fn brush(image: ImageFrame<Color>, bounds: ImageFrame<Color>, strokes: Vec
 	actual_image
 }
 
+/// Fades out the parts of a stroke's texture outside of the selection it was painted in.
+fn mask_to_selection(mut texture: ImageFrame<Color>, selection: &PixelSelection, sample: &ImageFrame<Color>, cache: &BrushCache) -> ImageFrame<Color> {
+	let mask = selection_mask(selection, texture.transform, texture.image.width, texture.image.height, sample, cache);
+	for (pixel, selected) in texture.image.data.iter_mut().zip(mask.image.data) {
+		*pixel = pixel.multiplied_alpha(selected.0);
+	}
+	texture
+}
+
+/// Rasterizes the selection a stroke was painted in. Its magic wand edits compare the colors of `sample` the first time the selection is used, and are stored in the cache
+/// so the strokes painted in it afterwards, which change the colors under it, are masked by the same pixels.
+fn selection_mask(selection: &PixelSelection, transform: DAffine2, width: u32, height: u32, sample: &ImageFrame<Color>, cache: &BrushCache) -> ImageFrame<Luma> {
+	let magic_wand_coverage = cache.get_magic_wand_coverage(selection).unwrap_or_else(|| {
+		let coverage = selection.magic_wand_coverage(sample);
+		cache.store_magic_wand_coverage(selection.clone(), coverage.clone());
+		coverage
+	});
+	selection.mask_with_coverage(transform, width, height, &magic_wand_coverage)
+}
+
 #[cfg(test)]
 mod test {
 	use super::*;
@@ -0,0 +1,189 @@
+//! Reading and writing the ICC profiles embedded in PNG and JPEG files, and converting the colors of images from their profile to a document's working space.
+
+use graphene_core::raster::color_management::{IccProfile, ProfileColorSpace, WorkingSpace};
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use glam::{DMat3, DVec3};
+use std::io::{Read, Write};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+/// Identifies the APP2 segments of a JPEG file which hold the parts of its ICC profile.
+const JPEG_ICC_MARKER: &[u8] = b"ICC_PROFILE\0";
+/// The most profile data which fits in a single JPEG segment after its length and identifier.
+const JPEG_ICC_CHUNK_SIZE: usize = 65519;
+
+/// The chunks of a PNG file as their type and data, or `None` if it isn't a PNG file.
+fn png_chunks(data: &[u8]) -> Option<Vec<([u8; 4], &[u8])>> {
+	let mut rest = data.strip_prefix(PNG_SIGNATURE)?;
+	let mut chunks = Vec::new();
+	while rest.len() >= 12 {
+		let length = u32::from_be_bytes(rest[0..4].try_into().ok()?) as usize;
+		let chunk_type = rest[4..8].try_into().ok()?;
+		let chunk_data = rest.get(8..8 + length)?;
+		chunks.push((chunk_type, chunk_data));
+		rest = rest.get(12 + length..)?;
+	}
+	Some(chunks)
+}
+
+/// A JPEG segment as its marker and data.
+type JpegSegment<'a> = (u8, &'a [u8]);
+
+/// The segments of a JPEG file before its image data, or `None` if it isn't a JPEG file.
+fn jpeg_segments(data: &[u8]) -> Option<(Vec<JpegSegment<'_>>, &[u8])> {
+	let mut rest = data.strip_prefix(&[0xFF, 0xD8])?;
+	let mut segments = Vec::new();
+	while let [0xFF, marker, ..] = *rest {
+		// Start of scan, after which the compressed image data follows
+		if marker == 0xDA {
+			break;
+		}
+		let length = u16::from_be_bytes(rest.get(2..4)?.try_into().ok()?) as usize;
+		segments.push((marker, rest.get(4..2 + length)?));
+		rest = &rest[2 + length..];
+	}
+	Some((segments, rest))
+}
+
+/// Reads the ICC profile embedded in a PNG or JPEG file.
+pub fn read_embedded_profile(data: &[u8]) -> Option<Vec<u8>> {
+	if let Some(chunks) = png_chunks(data) {
+		let (_, chunk) = chunks.into_iter().take_while(|(chunk_type, _)| chunk_type != b"IDAT").find(|(chunk_type, _)| chunk_type == b"iCCP")?;
+		// The profile name and compression method precede the compressed profile
+		let name_end = chunk.iter().position(|&byte| byte == 0)?;
+		let mut profile = Vec::new();
+		ZlibDecoder::new(chunk.get(name_end + 2..)?).read_to_end(&mut profile).ok()?;
+		return Some(profile);
+	}
+
+	let (segments, _) = jpeg_segments(data)?;
+	let mut parts: Vec<(u8, &[u8])> = segments
+		.into_iter()
+		.filter_map(|(marker, segment)| (marker == 0xE2).then_some(segment)?.strip_prefix(JPEG_ICC_MARKER))
+		.filter_map(|part| Some((*part.first()?, part.get(2..)?)))
+		.collect();
+	if parts.is_empty() {
+		return None;
+	}
+	parts.sort_by_key(|&(sequence_number, _)| sequence_number);
+	Some(parts.into_iter().flat_map(|(_, part)| part.iter().copied()).collect())
+}
+
+/// Embeds an ICC profile in a PNG or JPEG file, replacing any profile it already has.
+pub fn embed_profile(data: &[u8], profile: &[u8], name: &str) -> Result<Vec<u8>, String> {
+	if let Some(chunks) = png_chunks(data) {
+		let mut compressed = Vec::new();
+		compressed.extend(name.bytes().filter(|byte| (0x20..0x7F).contains(byte)).take(79));
+		compressed.extend([0, 0]);
+		let mut encoder = ZlibEncoder::new(compressed, flate2::Compression::default());
+		encoder.write_all(profile).map_err(|error| format!("Failed to compress the color profile: {error}"))?;
+		let icc_chunk = encoder.finish().map_err(|error| format!("Failed to compress the color profile: {error}"))?;
+
+		let mut output = PNG_SIGNATURE.to_vec();
+		let mut write_chunk = |chunk_type: &[u8; 4], chunk_data: &[u8]| {
+			output.extend((chunk_data.len() as u32).to_be_bytes());
+			output.extend(chunk_type);
+			output.extend(chunk_data);
+			let mut crc = crc32fast::Hasher::new();
+			crc.update(chunk_type);
+			crc.update(chunk_data);
+			output.extend(crc.finalize().to_be_bytes());
+		};
+		for (chunk_type, chunk_data) in chunks {
+			// The profile replaces the chunks which otherwise describe the colors as sRGB
+			if matches!(&chunk_type, b"iCCP" | b"sRGB") {
+				continue;
+			}
+			write_chunk(&chunk_type, chunk_data);
+			if &chunk_type == b"IHDR" {
+				write_chunk(b"iCCP", &icc_chunk);
+			}
+		}
+		return Ok(output);
+	}
+
+	let (segments, image_data) = jpeg_segments(data).ok_or_else(|| "Only PNG and JPEG files can have a color profile embedded".to_string())?;
+	let parts = profile.chunks(JPEG_ICC_CHUNK_SIZE).collect::<Vec<_>>();
+	if parts.len() > 255 {
+		return Err("The color profile is too large to embed in a JPEG file".to_string());
+	}
+
+	let mut output = vec![0xFF, 0xD8];
+	let mut write_segment = |marker: u8, segment: &[u8]| {
+		output.extend([0xFF, marker]);
+		output.extend((segment.len() as u16 + 2).to_be_bytes());
+		output.extend(segment);
+	};
+	let is_profile = |&(marker, segment): &(u8, &[u8])| marker == 0xE2 && segment.starts_with(JPEG_ICC_MARKER);
+	let mut segments = segments.into_iter().filter(|segment| !is_profile(segment)).peekable();
+	// The profile follows the JFIF header, which has to come first
+	if let Some((marker, segment)) = segments.next_if(|&(marker, _)| marker == 0xE0) {
+		write_segment(marker, segment);
+	}
+	for (index, part) in parts.iter().enumerate() {
+		write_segment(0xE2, &[JPEG_ICC_MARKER, &[index as u8 + 1, parts.len() as u8], part].concat());
+	}
+	for (marker, segment) in segments {
+		write_segment(marker, segment);
+	}
+	output.extend(image_data);
+	Ok(output)
+}
+
+/// Converts the colors of an image from its embedded profile to linear values in a working space.
+pub struct ProfileConversion {
+	profile: IccProfile,
+	from_xyz: DMat3,
+}
+
+impl ProfileConversion {
+	pub fn new(profile: IccProfile, working_space: WorkingSpace) -> Result<Self, String> {
+		if profile.color_space() == ProfileColorSpace::Cmyk {
+			return Err("CMYK images aren't supported".to_string());
+		}
+		Ok(Self {
+			profile,
+			from_xyz: working_space.from_xyz_matrix(),
+		})
+	}
+
+	/// Reads the profile embedded in an image file, if it has one which can be used to convert its colors.
+	pub fn from_embedded_profile(data: &[u8], working_space: WorkingSpace) -> Option<Self> {
+		let profile = IccProfile::parse(read_embedded_profile(data)?).ok()?;
+		Self::new(profile, working_space).ok()
+	}
+
+	/// Converts the channels of a pixel as stored in the file, from 0 to 1. Grayscale profiles only use the first channel and give a gray color.
+	pub fn convert(&self, encoded: [f64; 3]) -> DVec3 {
+		self.from_xyz * self.profile.device_to_xyz(&encoded)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn embedded_profiles_round_trip() {
+		let profile = WorkingSpace::DisplayP3.icc_profile();
+		let image = image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(2, 2, image::Rgba([255, 0, 0, 255])));
+
+		for format in [image::ImageOutputFormat::Png, image::ImageOutputFormat::Jpeg(90)] {
+			let mut encoded = Vec::new();
+			image.write_to(&mut std::io::Cursor::new(&mut encoded), format).unwrap();
+
+			let embedded = embed_profile(&encoded, &profile, "Display P3").unwrap();
+			assert_eq!(read_embedded_profile(&embedded), Some(profile.clone()));
+			assert!(image::load_from_memory(&embedded).is_ok());
+		}
+	}
+
+	#[test]
+	fn converts_from_embedded_profile() {
+		let conversion = ProfileConversion::new(IccProfile::parse(WorkingSpace::AdobeRgb.icc_profile()).unwrap(), WorkingSpace::AdobeRgb).unwrap();
+		let converted = conversion.convert([0.5, 0.25, 1.]);
+		let expected = DVec3::new(0.5, 0.25, 1.).to_array().map(|channel| WorkingSpace::AdobeRgb.transfer().eval(channel));
+		assert!((converted - DVec3::from_array(expected)).abs().max_element() < 1e-3);
+	}
+}
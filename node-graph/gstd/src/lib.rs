@@ -23,6 +23,10 @@ pub mod image_segmentation;
 
 pub mod brush;
 
+pub mod color_profile;
+
+pub mod pdf;
+
 #[cfg(feature = "wasm")]
 pub mod wasm_application_io;
 
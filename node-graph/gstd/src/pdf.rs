@@ -0,0 +1,881 @@
+//! Writing graphic elements as PDF documents of vector paths, gradients and images, with a page for each artboard.
+//!
+//! One unit of the document is one point of the PDF. Text is written as the outlines it is rendered with, and blend modes which PDF doesn't have are drawn as normal.
+//! Strokes with a width profile are written as their filled outlines, and strokes aligned inside or outside of their path are clipped to that side. Vector masks become clipping paths, while other masks and clipping groups become soft masks. PDF has nothing like the filters which draw layer effects, so artwork with effects isn't written.
+
+use graphene_core::raster::{BlendMode, ImageFrame};
+use graphene_core::renderer::{GraphicElementRendered, RenderParams};
+use graphene_core::vector::style::{Fill, Gradient, GradientType, LineJoin, Stroke, StrokeAlign};
+use graphene_core::vector::{stroke_outline, VectorData};
+use graphene_core::{Artboard, Color, GraphicElement, GraphicElementData, GraphicGroup};
+
+use bezier_rs::{BezierHandles, Subpath};
+use flate2::write::ZlibEncoder;
+use glam::{DAffine2, DVec2};
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use std::io::Write;
+
+const CATALOG_ID: usize = 1;
+const PAGES_ID: usize = 2;
+/// Every page and group shares one dictionary of the graphics states, shadings and images they use.
+const RESOURCES_ID: usize = 3;
+
+/// A graphic element which can be drawn into the pages of a PDF document.
+pub trait PdfRendered {
+	/// Appends the operators drawing the element, in document coordinates, to the content of the page or group being written.
+	fn render_pdf(&self, render: &mut PdfRender, render_params: &RenderParams);
+
+	/// Adds the artboards within the element, each of which is written as its own page.
+	fn collect_artboards<'a>(&'a self, _artboards: &mut Vec<&'a Artboard>) {}
+}
+
+/// The objects of a PDF document being written, and the content of the page or group being drawn.
+pub struct PdfRender {
+	/// The body of each object, where object `n` is at index `n - 1`. Objects are reserved as `None` until the end of the document when their contents are known.
+	objects: Vec<Option<Vec<u8>>>,
+	page_ids: Vec<usize>,
+	/// The area of the document shown by the page being written
+	page_bounds: [DVec2; 2],
+	/// The operators of the page or group being drawn
+	pub content: String,
+	graphics_states: Vec<String>,
+	shadings: Vec<usize>,
+	x_objects: Vec<usize>,
+	/// The names of the layers with effects, which PDF can't draw, so the document isn't written rather than written without them
+	layers_with_effects: Vec<String>,
+}
+
+impl Default for PdfRender {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl PdfRender {
+	pub fn new() -> Self {
+		Self {
+			objects: vec![None; RESOURCES_ID],
+			page_ids: Vec::new(),
+			page_bounds: [DVec2::ZERO, DVec2::ONE],
+			content: String::new(),
+			graphics_states: Vec::new(),
+			shadings: Vec::new(),
+			x_objects: Vec::new(),
+			layers_with_effects: Vec::new(),
+		}
+	}
+
+	fn add_object(&mut self, body: Vec<u8>) -> usize {
+		self.objects.push(Some(body));
+		self.objects.len()
+	}
+
+	/// Adds a stream compressed with Deflate, described by the entries of its dictionary other than its filter and length.
+	fn add_stream(&mut self, dictionary: &str, data: &[u8]) -> usize {
+		let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+		encoder.write_all(data).expect("Writing to a Vec can't fail");
+		let compressed = encoder.finish().expect("Writing to a Vec can't fail");
+
+		let mut body = format!("<< {dictionary} /Filter /FlateDecode /Length {} >>\nstream\n", compressed.len()).into_bytes();
+		body.extend_from_slice(&compressed);
+		body.extend_from_slice(b"\nendstream");
+		self.add_object(body)
+	}
+
+	/// The resource name of a graphics state with the entries of the given dictionary, which is shared by the states with the same entries.
+	pub fn graphics_state(&mut self, entries: String) -> String {
+		let index = self.graphics_states.iter().position(|existing| *existing == entries).unwrap_or_else(|| {
+			self.graphics_states.push(entries);
+			self.graphics_states.len() - 1
+		});
+		format!("GS{index}")
+	}
+
+	fn shading(&mut self, id: usize) -> String {
+		self.shadings.push(id);
+		format!("Sh{}", self.shadings.len() - 1)
+	}
+
+	fn x_object(&mut self, id: usize) -> String {
+		self.x_objects.push(id);
+		format!("X{}", self.x_objects.len() - 1)
+	}
+
+	/// The bounding box of a form, which covers the page being written since anything outside of it isn't seen.
+	fn form_bounding_box(&self) -> String {
+		let [min, max] = self.page_bounds;
+		format!("[{} {} {} {}]", number(min.x), number(min.y), number(max.x), number(max.y))
+	}
+
+	/// Draws into a form of a transparency group with the given entries, returning its object number.
+	fn form(&mut self, group_entries: &str, draw: impl FnOnce(&mut Self)) -> usize {
+		let outer_content = std::mem::take(&mut self.content);
+		draw(self);
+		let content = std::mem::replace(&mut self.content, outer_content);
+
+		let dictionary = format!(
+			"/Type /XObject /Subtype /Form /BBox {} /Group << /S /Transparency{group_entries} >> /Resources {RESOURCES_ID} 0 R",
+			self.form_bounding_box()
+		);
+		self.add_stream(&dictionary, content.as_bytes())
+	}
+
+	/// Draws into a transparency group, returning its resource name, so it can be composited as a whole with an opacity or blend mode.
+	pub fn group(&mut self, draw: impl FnOnce(&mut Self)) -> String {
+		let id = self.form("", draw);
+		self.x_object(id)
+	}
+
+	/// Draws a soft mask, returning the graphics state entry applying it, which masks by the luminance of what's drawn, or by its alpha when `alpha` is set.
+	/// Where nothing is drawn, the mask is black and transparent, so it hides what it's applied to.
+	pub fn soft_mask(&mut self, alpha: bool, draw: impl FnOnce(&mut Self)) -> String {
+		let (subtype, group_entries) = if alpha { ("Alpha", "") } else { ("Luminosity", " /CS /DeviceRGB") };
+		let id = self.form(group_entries, draw);
+		format!("/SMask << /S /{subtype} /G {id} 0 R >>")
+	}
+
+	/// Writes a page showing the given area of the document, drawn with document coordinates where the y axis points down.
+	pub fn page(&mut self, bounds: [DVec2; 2], draw: impl FnOnce(&mut Self)) {
+		let [min, max] = [bounds[0].min(bounds[1]), bounds[0].max(bounds[1])];
+		// Pages can't be empty, so lines and points are given some area
+		let max = max.max(min + DVec2::ONE);
+		self.page_bounds = [min, max];
+
+		self.content = format!("q 1 0 0 -1 {} {} cm\n", number(-min.x), number(max.y));
+		draw(self);
+		self.content.push_str("Q\n");
+		let content = std::mem::take(&mut self.content);
+		let content_id = self.add_stream("", content.as_bytes());
+
+		let size = max - min;
+		let page = format!(
+			"<< /Type /Page /Parent {PAGES_ID} 0 R /MediaBox [0 0 {} {}] /Resources {RESOURCES_ID} 0 R /Contents {content_id} 0 R >>",
+			number(size.x),
+			number(size.y)
+		);
+		let page_id = self.add_object(page.into_bytes());
+		self.page_ids.push(page_id);
+	}
+
+	/// Completes the document with its catalog, page tree and resources, and returns the file.
+	pub fn finish(mut self) -> Vec<u8> {
+		self.objects[CATALOG_ID - 1] = Some(format!("<< /Type /Catalog /Pages {PAGES_ID} 0 R >>").into_bytes());
+
+		let kids = self.page_ids.iter().map(|id| format!("{id} 0 R")).collect::<Vec<_>>().join(" ");
+		self.objects[PAGES_ID - 1] = Some(format!("<< /Type /Pages /Kids [{kids}] /Count {} >>", self.page_ids.len()).into_bytes());
+
+		let mut resources = String::from("<< /ProcSet [/PDF /ImageC /ImageB]");
+		if !self.graphics_states.is_empty() {
+			resources.push_str(" /ExtGState <<");
+			for (index, entries) in self.graphics_states.iter().enumerate() {
+				let _ = write!(resources, " /GS{index} << /Type /ExtGState {entries} >>");
+			}
+			resources.push_str(" >>");
+		}
+		if !self.shadings.is_empty() {
+			resources.push_str(" /Shading <<");
+			for (index, id) in self.shadings.iter().enumerate() {
+				let _ = write!(resources, " /Sh{index} {id} 0 R");
+			}
+			resources.push_str(" >>");
+		}
+		if !self.x_objects.is_empty() {
+			resources.push_str(" /XObject <<");
+			for (index, id) in self.x_objects.iter().enumerate() {
+				let _ = write!(resources, " /X{index} {id} 0 R");
+			}
+			resources.push_str(" >>");
+		}
+		resources.push_str(" >>");
+		self.objects[RESOURCES_ID - 1] = Some(resources.into_bytes());
+
+		// The binary comment marks the file as binary for the programs which guess
+		let mut file = b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n".to_vec();
+		let mut offsets = Vec::with_capacity(self.objects.len());
+		for (index, body) in self.objects.into_iter().enumerate() {
+			offsets.push(file.len());
+			file.extend_from_slice(format!("{} 0 obj\n", index + 1).as_bytes());
+			file.extend_from_slice(&body.unwrap_or_else(|| b"null".to_vec()));
+			file.extend_from_slice(b"\nendobj\n");
+		}
+
+		let cross_reference_offset = file.len();
+		let mut cross_reference = format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1);
+		for offset in offsets.iter() {
+			let _ = writeln!(cross_reference, "{offset:010} 00000 n ");
+		}
+		let _ = write!(
+			cross_reference,
+			"trailer\n<< /Size {} /Root {CATALOG_ID} 0 R >>\nstartxref\n{cross_reference_offset}\n%%EOF\n",
+			offsets.len() + 1
+		);
+		file.extend_from_slice(cross_reference.as_bytes());
+		file
+	}
+}
+
+/// Writes the element as a PDF file, with a page for each of its artboards, or a single page fitting all of it when there are none.
+/// Fails when the artwork has layer effects, which PDF can't draw.
+pub fn write_pdf(data: &(impl GraphicElementRendered + PdfRendered), render_params: &RenderParams) -> Result<Vec<u8>, String> {
+	let mut render = PdfRender::new();
+
+	let mut artboards = Vec::new();
+	data.collect_artboards(&mut artboards);
+	if artboards.is_empty() {
+		let bounds = data.bounding_box(DAffine2::IDENTITY).unwrap_or([DVec2::ZERO, DVec2::ONE]);
+		render.page(bounds, |render| data.render_pdf(render, render_params));
+	}
+	for artboard in artboards {
+		let location = artboard.location.as_dvec2();
+		render.page([location, location + artboard.dimensions.as_dvec2()], |render| artboard.render_pdf(render, render_params));
+	}
+
+	if !render.layers_with_effects.is_empty() {
+		return Err(format!(
+			"PDF files can't show layer effects, such as shadows and glows. Disable the effects of these layers to export the document as a PDF: {}.",
+			render.layers_with_effects.join(", ")
+		));
+	}
+	Ok(render.finish())
+}
+
+/// Formats a number in as few characters as keep it exact to a ten thousandth, since PDF numbers can't be written in scientific notation.
+fn number(value: f64) -> String {
+	let rounded = (value * 10000.).round() / 10000.;
+	if rounded == 0. || !rounded.is_finite() {
+		return "0".to_string();
+	}
+	let formatted = format!("{rounded:.4}");
+	formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn color_components(color: Color) -> String {
+	[color.r(), color.g(), color.b()].map(|channel| number(channel.clamp(0., 1.) as f64)).join(" ")
+}
+
+fn display_color(color: Color, render_params: &RenderParams) -> Color {
+	render_params.display_transform.as_ref().map_or(color, |transform| transform.apply(color))
+}
+
+/// The name PDF gives the blend mode, or `None` when it's drawn as normal.
+fn blend_mode_name(blend_mode: BlendMode) -> Option<String> {
+	let css_name = blend_mode.to_svg_style_name().filter(|&name| name != "normal")?;
+	Some(css_name.split('-').map(|word| word[..1].to_uppercase() + &word[1..]).collect())
+}
+
+/// Appends the path construction operators of a subpath, with its points placed by the transform.
+fn write_subpath(path: &mut String, subpath: &Subpath<graphene_core::uuid::ManipulatorGroupId>, transform: DAffine2) {
+	let point = |position: DVec2| {
+		let position = transform.transform_point2(position);
+		format!("{} {}", number(position.x), number(position.y))
+	};
+
+	let _ = writeln!(path, "{} m", point(subpath.manipulator_groups()[0].anchor));
+	for bezier in subpath.iter() {
+		let _ = match bezier.handles {
+			BezierHandles::Linear => writeln!(path, "{} l", point(bezier.end)),
+			// Handles which sit on their anchors make a straight line
+			BezierHandles::Cubic { handle_start, handle_end } if handle_start == bezier.start && handle_end == bezier.end => writeln!(path, "{} l", point(bezier.end)),
+			// PDF only has cubic curves, which can exactly represent quadratic ones
+			BezierHandles::Quadratic { handle } => {
+				let handle_start = bezier.start + (handle - bezier.start) * (2. / 3.);
+				let handle_end = bezier.end + (handle - bezier.end) * (2. / 3.);
+				writeln!(path, "{} {} {} c", point(handle_start), point(handle_end), point(bezier.end))
+			}
+			BezierHandles::Cubic { handle_start, handle_end } => writeln!(path, "{} {} {} c", point(handle_start), point(handle_end), point(bezier.end)),
+		};
+	}
+	if subpath.closed() {
+		path.push_str("h\n");
+	}
+}
+
+/// Adds the function interpolating the given color components of the gradient stops, which are spread over the whole domain.
+fn stops_function(stops: &[(f64, Vec<f32>)]) -> String {
+	let components = |values: &[f32]| values.iter().map(|&value| number(value.clamp(0., 1.) as f64)).collect::<Vec<_>>().join(" ");
+	let interpolation = |start: &[f32], end: &[f32]| format!("<< /FunctionType 2 /Domain [0 1] /C0 [{}] /C1 [{}] /N 1 >>", components(start), components(end));
+
+	if let [(_, start), (_, end)] = stops {
+		return interpolation(start, end);
+	}
+	let functions = stops.windows(2).map(|pair| interpolation(&pair[0].1, &pair[1].1)).collect::<Vec<_>>().join(" ");
+	let bounds = stops[1..stops.len() - 1].iter().map(|(position, _)| number(*position)).collect::<Vec<_>>().join(" ");
+	let encode = vec!["0 1"; stops.len() - 1].join(" ");
+	format!("<< /FunctionType 3 /Domain [0 1] /Functions [{functions}] /Bounds [{bounds}] /Encode [{encode}] >>")
+}
+
+impl PdfRender {
+	/// Fills the current path with the gradient, which is placed over the bounds of the path like the SVG renderer places it.
+	fn fill_gradient(&mut self, path: &str, gradient: &Gradient, bounds: [DVec2; 2], render_params: &RenderParams) {
+		let mut stops: Vec<_> = gradient
+			.positions
+			.iter()
+			.filter_map(|&(position, color)| Some((position.clamp(0., 1.), display_color(color?, render_params))))
+			.collect();
+		let (Some(&(first_position, first_color)), Some(&(last_position, last_color))) = (stops.first(), stops.last()) else {
+			return;
+		};
+		// The stitched functions have to span the whole gradient, so the end colors are extended to its ends
+		if first_position > 0. {
+			stops.insert(0, (0., first_color));
+		}
+		if last_position < 1. || stops.len() == 1 {
+			stops.push((1., last_color));
+		}
+
+		let bound_transform = DAffine2::from_scale_angle_translation(bounds[1] - bounds[0], 0., bounds[0]);
+		let (start, end) = (bound_transform.transform_point2(gradient.start), bound_transform.transform_point2(gradient.end));
+		let coordinates = match gradient.gradient_type {
+			GradientType::Linear => format!("/ShadingType 2 /Coords [{} {} {} {}]", number(start.x), number(start.y), number(end.x), number(end.y)),
+			GradientType::Radial => format!("/ShadingType 3 /Coords [{x} {y} 0 {x} {y} {}]", number(start.distance(end)), x = number(start.x), y = number(start.y)),
+		};
+
+		let colors: Vec<_> = stops.iter().map(|(position, color)| (*position, vec![color.r(), color.g(), color.b()])).collect();
+		let shading = format!("<< {coordinates} /ColorSpace /DeviceRGB /Function {} /Extend [true true] >>", stops_function(&colors));
+		let shading_id = self.add_object(shading.into_bytes());
+		let shading = self.shading(shading_id);
+
+		let _ = write!(self.content, "q\n{path}W n\n");
+		// The transparency of the stops is drawn by a mask of the same gradient in shades of gray
+		if stops.iter().any(|(_, color)| color.a() < 1.) {
+			let alphas: Vec<_> = stops.iter().map(|(position, color)| (*position, vec![color.a()])).collect();
+			let mask_shading = format!("<< {coordinates} /ColorSpace /DeviceGray /Function {} /Extend [true true] >>", stops_function(&alphas));
+			let mask_shading_id = self.add_object(mask_shading.into_bytes());
+			let mask_shading = self.shading(mask_shading_id);
+
+			let dictionary = format!(
+				"/Type /XObject /Subtype /Form /BBox {} /Group << /S /Transparency /CS /DeviceGray >> /Resources {RESOURCES_ID} 0 R",
+				self.form_bounding_box()
+			);
+			let mask_id = self.add_stream(&dictionary, format!("/{mask_shading} sh\n").as_bytes());
+			let graphics_state = self.graphics_state(format!("/SMask << /S /Luminosity /G {mask_id} 0 R >>"));
+			let _ = writeln!(self.content, "/{graphics_state} gs");
+		}
+		let _ = write!(self.content, "/{shading} sh\nQ\n");
+	}
+
+	fn fill_path(&mut self, path: &str, color: Color) {
+		if path.is_empty() {
+			return;
+		}
+		self.content.push_str("q\n");
+		if color.a() < 1. {
+			let graphics_state = self.graphics_state(format!("/ca {}", number(color.a() as f64)));
+			let _ = writeln!(self.content, "/{graphics_state} gs");
+		}
+		let _ = write!(self.content, "{} rg\n{path}f\nQ\n", color_components(color));
+	}
+
+	/// PDF strokes are centered on their path, so like in the SVG render, a stroke aligned inside or outside of the closed path is drawn at twice its weight and clipped to the inside or masked to the outside of it.
+	fn stroke_aligned_path(&mut self, closed_path: &str, stroke: &Stroke, color: Color) {
+		if closed_path.is_empty() {
+			return;
+		}
+		self.content.push_str("q\n");
+		if stroke.align == StrokeAlign::Inside {
+			let _ = write!(self.content, "{closed_path}W n\n");
+		} else {
+			// The mask is white everywhere on the page except inside of the path
+			let [min, max] = self.page_bounds;
+			let page = format!("{} {} {} {} re\n", number(min.x), number(min.y), number(max.x - min.x), number(max.y - min.y));
+			let soft_mask = self.soft_mask(false, |render| {
+				let _ = write!(render.content, "1 g\n{page}f\n0 g\n{closed_path}f\n");
+			});
+			let graphics_state = self.graphics_state(soft_mask);
+			let _ = writeln!(self.content, "/{graphics_state} gs");
+		}
+		self.stroke_path(closed_path, &stroke.clone().with_weight(stroke.weight * 2.), color);
+		self.content.push_str("Q\n");
+	}
+
+	fn stroke_path(&mut self, path: &str, stroke: &Stroke, color: Color) {
+		self.content.push_str("q\n");
+		if color.a() < 1. {
+			let graphics_state = self.graphics_state(format!("/CA {}", number(color.a() as f64)));
+			let _ = writeln!(self.content, "/{graphics_state} gs");
+		}
+		let line_join = match stroke.line_join {
+			LineJoin::Miter => 0,
+			LineJoin::Round => 1,
+			LineJoin::Bevel => 2,
+		};
+		let dash_lengths = stroke.dash_lengths.iter().map(|&length| number(length as f64)).collect::<Vec<_>>().join(" ");
+		let _ = write!(
+			self.content,
+			"{} RG\n{} w\n{} J\n{line_join} j\n{} M\n[{dash_lengths}] {} d\n{path}S\nQ\n",
+			color_components(color),
+			number(stroke.weight),
+			stroke.line_cap_index(),
+			number(stroke.line_join_miter_limit.max(1.)),
+			number(stroke.dash_offset)
+		);
+	}
+}
+
+impl PdfRendered for GraphicGroup {
+	fn render_pdf(&self, render: &mut PdfRender, render_params: &RenderParams) {
+		for (index, element) in self.iter().enumerate().filter(|(_, element)| element.visible) {
+			let clip_base = match self.clip_base(index) {
+				// Hiding the element at the base of a clipping group hides the whole group
+				Some(base) if !self[base].visible => continue,
+				Some(base) => Some(&self[base]),
+				None => None,
+			};
+			render_element(element, clip_base, render, render_params);
+		}
+	}
+
+	fn collect_artboards<'a>(&'a self, artboards: &mut Vec<&'a Artboard>) {
+		for element in self.iter().filter(|element| element.visible) {
+			element.graphic_element_data.collect_artboards(artboards);
+		}
+	}
+}
+
+/// Draws an element of a group with its opacity and blend mode, and clipped to the masked content of the element at the base of its clipping group.
+fn render_element(element: &GraphicElement, clip_base: Option<&GraphicElement>, render: &mut PdfRender, render_params: &RenderParams) {
+	if element.effects.iter().any(|effect| effect.enabled && effect.opacity > 0.) {
+		let name = if element.name.is_empty() { "Untitled" } else { &element.name };
+		render.layers_with_effects.push(format!("\"{name}\""));
+	}
+
+	let mut entries = Vec::new();
+	if element.opacity < 1. {
+		let opacity = number(element.opacity.max(0.) as f64);
+		entries.push(format!("/ca {opacity} /CA {opacity}"));
+	}
+	if let Some(blend_mode) = blend_mode_name(element.blend_mode) {
+		entries.push(format!("/BM /{blend_mode}"));
+	}
+	if let Some(base) = clip_base {
+		entries.push(render.soft_mask(true, |render| render_masked_content(base, render, render_params)));
+	}
+	if entries.is_empty() {
+		render_masked_content(element, render, render_params);
+		return;
+	}
+
+	let graphics_state = render.graphics_state(entries.join(" "));
+	let group = render.group(|render| render_masked_content(element, render, render_params));
+	let _ = writeln!(render.content, "q\n/{graphics_state} gs\n/{group} Do\nQ");
+}
+
+/// Draws the content of an element, clipped to the shape of its vector mask or masked by the luminance of any other mask.
+fn render_masked_content(element: &GraphicElement, render: &mut PdfRender, render_params: &RenderParams) {
+	let Some(mask) = element.mask.as_deref() else {
+		element.graphic_element_data.render_pdf(render, render_params);
+		return;
+	};
+
+	if let GraphicElementData::VectorShape(vector_data) = mask {
+		let mut path = String::new();
+		for subpath in vector_data.subpaths.iter().filter(|subpath| !subpath.is_empty()) {
+			write_subpath(&mut path, subpath, vector_data.transform);
+		}
+		// A mask without a shape hides the whole element
+		if path.is_empty() {
+			return;
+		}
+		let _ = write!(render.content, "q\n{path}W n\n");
+		element.graphic_element_data.render_pdf(render, render_params);
+		render.content.push_str("Q\n");
+		return;
+	}
+
+	// The mask's values aren't colors shown on the display, so they are kept as they are
+	let mask_params = RenderParams {
+		display_transform: None,
+		..*render_params
+	};
+	let soft_mask = render.soft_mask(false, |render| mask.render_pdf(render, &mask_params));
+	let graphics_state = render.graphics_state(soft_mask);
+	// The content is masked as a whole, rather than each of its overlapping parts being masked separately
+	let group = render.group(|render| element.graphic_element_data.render_pdf(render, render_params));
+	let _ = writeln!(render.content, "q\n/{graphics_state} gs\n/{group} Do\nQ");
+}
+
+impl PdfRendered for VectorData {
+	fn render_pdf(&self, render: &mut PdfRender, render_params: &RenderParams) {
+		let mut path = String::new();
+		for subpath in self.subpaths.iter().filter(|subpath| !subpath.is_empty()) {
+			write_subpath(&mut path, subpath, self.transform);
+		}
+		if path.is_empty() {
+			return;
+		}
+
+		match self.style.fill() {
+			Fill::None => {}
+			Fill::Solid(color) => render.fill_path(&path, display_color(*color, render_params)),
+			Fill::Gradient(gradient) => render.fill_gradient(&path, gradient, self.bounding_box().unwrap_or_default(), render_params),
+		}
+
+		let stroke = self.style.stroke().filter(|stroke| stroke.weight > 0.);
+		let Some((stroke, color)) = stroke.as_ref().and_then(|stroke| Some((stroke, display_color(stroke.color?, render_params)))) else {
+			return;
+		};
+		if stroke.profile.is_uniform() && stroke.align == StrokeAlign::Center {
+			render.stroke_path(&path, stroke, color);
+			return;
+		}
+		if stroke.profile.is_uniform() {
+			let (mut closed_path, mut open_path) = (String::new(), String::new());
+			for subpath in self.subpaths.iter().filter(|subpath| !subpath.is_empty()) {
+				write_subpath(if subpath.closed() { &mut closed_path } else { &mut open_path }, subpath, self.transform);
+			}
+			render.stroke_aligned_path(&closed_path, stroke, color);
+			// Open subpaths have no inside or outside, so their stroke stays centered
+			if !open_path.is_empty() {
+				render.stroke_path(&open_path, stroke, color);
+			}
+			return;
+		}
+
+		// PDF strokes have a single width, so strokes with a width profile are drawn as their filled outline like in the SVG render
+		let mut outline = String::new();
+		for subpath in self.subpaths.iter().filter(|subpath| !subpath.is_empty()) {
+			let mut subpath = subpath.clone();
+			subpath.apply_transform(self.transform);
+			for outline_subpath in stroke_outline(&subpath, stroke).iter().filter(|subpath| !subpath.is_empty()) {
+				write_subpath(&mut outline, outline_subpath, DAffine2::IDENTITY);
+			}
+		}
+		render.fill_path(&outline, color);
+	}
+}
+
+impl PdfRendered for ImageFrame<Color> {
+	fn render_pdf(&self, render: &mut PdfRender, render_params: &RenderParams) {
+		if self.image.data.is_empty() || self.transform.matrix2.determinant() == 0. {
+			return;
+		}
+		let image = match &render_params.display_transform {
+			Some(display_transform) => Cow::Owned(display_transform.apply_to_image(&self.image)),
+			None => Cow::Borrowed(&self.image),
+		};
+
+		let (pixels, width, height) = image.to_flat_u8();
+		let rgb: Vec<_> = pixels.chunks_exact(4).flat_map(|pixel| [pixel[0], pixel[1], pixel[2]]).collect();
+		let alpha: Vec<_> = pixels.chunks_exact(4).map(|pixel| pixel[3]).collect();
+
+		let mut dictionary = format!("/Type /XObject /Subtype /Image /Width {width} /Height {height} /ColorSpace /DeviceRGB /BitsPerComponent 8");
+		if alpha.iter().any(|&alpha| alpha < 255) {
+			let mask_id = render.add_stream(
+				&format!("/Type /XObject /Subtype /Image /Width {width} /Height {height} /ColorSpace /DeviceGray /BitsPerComponent 8"),
+				&alpha,
+			);
+			let _ = write!(dictionary, " /SMask {mask_id} 0 R");
+		}
+		let image_id = render.add_stream(&dictionary, &rgb);
+		let image = render.x_object(image_id);
+
+		// Images are drawn into the unit square with their first row at the top, which is where the document's y axis starts
+		let transform = self.transform * DAffine2::from_cols_array(&[1., 0., 0., -1., 0., 1.]);
+		let matrix = transform.to_cols_array().map(number).join(" ");
+		let _ = writeln!(render.content, "q\n{matrix} cm\n/{image} Do\nQ");
+	}
+}
+
+impl PdfRendered for Artboard {
+	fn render_pdf(&self, render: &mut PdfRender, render_params: &RenderParams) {
+		let location = self.location.min(self.location + self.dimensions).as_dvec2();
+		let dimensions = self.dimensions.abs().as_dvec2();
+		let rectangle = format!("{} {} {} {} re\n", number(location.x), number(location.y), number(dimensions.x), number(dimensions.y));
+
+		let background = display_color(self.background, render_params);
+		render.content.push_str("q\n");
+		if background.a() > 0. {
+			if background.a() < 1. {
+				let graphics_state = render.graphics_state(format!("/ca {}", number(background.a() as f64)));
+				let _ = writeln!(render.content, "/{graphics_state} gs");
+			}
+			let _ = write!(render.content, "{} rg\n{rectangle}f\n", color_components(background));
+		}
+		render.content.push_str("Q\nq\n");
+		if self.clip {
+			let _ = writeln!(render.content, "{rectangle}W n");
+		}
+		self.graphic_group.render_pdf(render, render_params);
+		render.content.push_str("Q\n");
+	}
+
+	fn collect_artboards<'a>(&'a self, artboards: &mut Vec<&'a Artboard>) {
+		artboards.push(self);
+	}
+}
+
+impl PdfRendered for GraphicElementData {
+	fn render_pdf(&self, render: &mut PdfRender, render_params: &RenderParams) {
+		match self {
+			GraphicElementData::VectorShape(vector_data) => vector_data.render_pdf(render, render_params),
+			GraphicElementData::ImageFrame(image_frame) => image_frame.render_pdf(render, render_params),
+			// Text is rendered as vector shapes, so text elements don't have anything to draw
+			GraphicElementData::Text(_) => {}
+			GraphicElementData::GraphicGroup(graphic_group) => graphic_group.render_pdf(render, render_params),
+			GraphicElementData::Artboard(artboard) => artboard.render_pdf(render, render_params),
+		}
+	}
+
+	fn collect_artboards<'a>(&'a self, artboards: &mut Vec<&'a Artboard>) {
+		match self {
+			GraphicElementData::GraphicGroup(graphic_group) => graphic_group.collect_artboards(artboards),
+			GraphicElementData::Artboard(artboard) => artboard.collect_artboards(artboards),
+			_ => {}
+		}
+	}
+}
+
+/// The values which are displayed as text by the SVG renderer have no artwork to write.
+macro_rules! empty_pdf_rendered {
+	($($ty:ty),*) => {
+		$(impl PdfRendered for $ty {
+			fn render_pdf(&self, _render: &mut PdfRender, _render_params: &RenderParams) {}
+		})*
+	};
+}
+empty_pdf_rendered!(String, bool, f32, f64, Option<Color>);
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use graphene_core::layer_effects::{LayerEffect, LayerEffectKind};
+	use graphene_core::raster::Image;
+	use graphene_core::vector::style::{PathStyle, StrokeProfile, ViewMode};
+	use graphene_core::GraphicElement;
+
+	use flate2::read::ZlibDecoder;
+	use glam::IVec2;
+	use std::io::Read;
+
+	fn render_params() -> RenderParams {
+		RenderParams::new(ViewMode::Normal, graphene_core::renderer::ImageRenderMode::Base64, None, false)
+	}
+
+	/// The object of the file with the given number, and the decompressed data of its stream if it has one.
+	fn object(file: &[u8], id: usize) -> (String, Vec<u8>) {
+		let header = format!("\n{id} 0 obj\n");
+		let start = file.windows(header.len()).position(|window| window == header.as_bytes()).expect("Missing object") + header.len();
+		let length = file[start..].windows(8).position(|window| window == b"\nendobj\n").unwrap();
+		let body = &file[start..start + length];
+		let Some(stream_start) = body.windows(7).position(|window| window == b"stream\n") else {
+			return (String::from_utf8_lossy(body).to_string(), Vec::new());
+		};
+		let mut data = Vec::new();
+		ZlibDecoder::new(&body[stream_start + 7..body.len() - 10]).read_to_end(&mut data).unwrap();
+		(String::from_utf8_lossy(&body[..stream_start]).to_string(), data)
+	}
+
+	fn element(graphic_element_data: GraphicElementData) -> GraphicElement {
+		GraphicElement {
+			graphic_element_data,
+			..Default::default()
+		}
+	}
+
+	fn group(elements: impl IntoIterator<Item = GraphicElement>) -> GraphicGroup {
+		let mut group = GraphicGroup::EMPTY;
+		group.extend(elements);
+		group
+	}
+
+	#[test]
+	fn cross_reference_table_points_at_the_objects() {
+		let file = write_pdf(&GraphicGroup::EMPTY, &render_params()).unwrap();
+		let trailer = String::from_utf8_lossy(&file[file.len() - 100..]).to_string();
+		let start = trailer.rfind("startxref\n").unwrap() + 10;
+		let cross_reference_offset: usize = trailer[start..].lines().next().unwrap().parse().unwrap();
+
+		let cross_reference = String::from_utf8(file[cross_reference_offset..].to_vec()).unwrap();
+		assert!(cross_reference.starts_with("xref\n0 6\n"));
+		let entries = cross_reference.lines().skip(3).take_while(|line| !line.starts_with("trailer"));
+		for (index, entry) in entries.enumerate() {
+			let offset: usize = entry[..10].parse().unwrap();
+			assert!(file[offset..].starts_with(format!("{} 0 obj\n", index + 1).as_bytes()));
+		}
+		assert!(cross_reference.ends_with("%%EOF\n"));
+	}
+
+	#[test]
+	fn shapes_are_written_as_vector_paths() {
+		let mut vector_data = VectorData::from_subpath(Subpath::new_rect(DVec2::new(10., 20.), DVec2::new(30., 60.)));
+		vector_data.style = PathStyle::new(Some(Stroke::new(Some(Color::BLACK), 2.)), Fill::Solid(Color::from_rgbaf32_unchecked(1., 0., 0., 0.5)));
+		let file = write_pdf(&group([element(vector_data.into())]), &render_params()).unwrap();
+		let (page, _) = object(&file, 5);
+		assert!(page.contains("/MediaBox [0 0 20 40]"));
+		let (_, content) = object(&file, 4);
+		let content = String::from_utf8(content).unwrap();
+		assert!(content.starts_with("q 1 0 0 -1 -10 60 cm\n"));
+		assert!(content.contains("10 20 m\n30 20 l\n30 60 l\n10 60 l\n10 20 l\nh\nf\n"));
+		assert!(content.contains("1 0 0 rg"));
+		assert!(content.contains("/GS0 gs"));
+		assert!(content.contains("2 w\n"));
+		assert!(content.contains("h\nS\n"));
+		let (resources, _) = object(&file, RESOURCES_ID);
+		assert!(resources.contains("/GS0 << /Type /ExtGState /ca 0.5 >>"));
+	}
+
+	#[test]
+	fn strokes_with_a_width_profile_are_filled_outlines() {
+		let mut vector_data = VectorData::from_subpath(Subpath::new_line(DVec2::ZERO, DVec2::new(100., 0.)));
+		let stroke = Stroke::new(Some(Color::BLACK), 10.).with_profile(StrokeProfile::new(Vec::new(), 0.5, 0.));
+		vector_data.style = PathStyle::new(Some(stroke), Fill::None);
+
+		let file = write_pdf(&group([element(vector_data.into())]), &render_params()).unwrap();
+		let (_, content) = object(&file, 4);
+		let content = String::from_utf8(content).unwrap();
+		assert!(content.contains("0 0 0 rg\n"));
+		assert!(content.ends_with("h\nf\nQ\nQ\n"));
+		assert!(!content.contains(" w\n"));
+	}
+
+	#[test]
+	fn aligned_strokes_are_doubled_and_clipped_to_one_side() {
+		let aligned = |align: StrokeAlign| {
+			let mut vector_data = VectorData::from_subpath(Subpath::new_rect(DVec2::ZERO, DVec2::splat(10.)));
+			let stroke = Stroke {
+				align,
+				..Stroke::new(Some(Color::BLACK), 2.)
+			};
+			vector_data.style = PathStyle::new(Some(stroke), Fill::None);
+			write_pdf(&group([element(vector_data.into())]), &render_params()).unwrap()
+		};
+		let rectangle = "0 0 m\n10 0 l\n10 10 l\n0 10 l\n0 0 l\nh\n";
+
+		let file = aligned(StrokeAlign::Inside);
+		let (_, content) = object(&file, 4);
+		let content = String::from_utf8(content).unwrap();
+		assert!(content.contains(&format!("q\n{rectangle}W n\nq\n0 0 0 RG\n4 w\n")));
+
+		let file = aligned(StrokeAlign::Outside);
+		let (_, mask) = object(&file, 4);
+		assert_eq!(String::from_utf8(mask).unwrap(), format!("1 g\n0 0 10 10 re\nf\n0 g\n{rectangle}f\n"));
+		let (resources, _) = object(&file, RESOURCES_ID);
+		assert!(resources.contains("/GS0 << /Type /ExtGState /SMask << /S /Luminosity /G 4 0 R >> >>"));
+		let (_, content) = object(&file, 5);
+		assert!(String::from_utf8(content).unwrap().contains("q\n/GS0 gs\nq\n0 0 0 RG\n4 w\n"));
+	}
+
+	#[test]
+	fn artboards_are_written_as_pages() {
+		let artboards = [(IVec2::new(0, 0), IVec2::new(100, 50)), (IVec2::new(200, 0), IVec2::new(30, 40))].map(|(location, dimensions)| element(Artboard::new(location, dimensions).into()));
+		let file = write_pdf(&group(artboards), &render_params()).unwrap();
+
+		let (pages, _) = object(&file, PAGES_ID);
+		assert!(pages.contains("/Count 2"));
+		assert!(object(&file, 5).0.contains("/MediaBox [0 0 100 50]"));
+		assert!(object(&file, 7).0.contains("/MediaBox [0 0 30 40]"));
+		let (_, content) = object(&file, 6);
+		assert!(String::from_utf8(content).unwrap().starts_with("q 1 0 0 -1 -200 40 cm\n"));
+	}
+
+	#[test]
+	fn translucent_layers_are_drawn_as_groups() {
+		let vector_data = VectorData::from_subpath(Subpath::new_rect(DVec2::ZERO, DVec2::ONE));
+		let mut layer = element(vector_data.into());
+		layer.opacity = 0.25;
+		layer.blend_mode = BlendMode::ColorDodge;
+
+		let file = write_pdf(&group([layer]), &render_params()).unwrap();
+		let (resources, _) = object(&file, RESOURCES_ID);
+		assert!(resources.contains("/GS0 << /Type /ExtGState /ca 0.25 /CA 0.25 /BM /ColorDodge >>"));
+		assert!(resources.contains("/XObject << /X0 4 0 R >>"));
+		let (group, _) = object(&file, 4);
+		assert!(group.contains("/Subtype /Form"));
+		assert!(group.contains("/Group << /S /Transparency >>"));
+	}
+
+	#[test]
+	fn vector_masks_clip_the_content() {
+		let mut layer = element(VectorData::from_subpath(Subpath::new_rect(DVec2::ZERO, DVec2::splat(10.))).into());
+		layer.mask = Some(Box::new(VectorData::from_subpath(Subpath::new_rect(DVec2::ZERO, DVec2::splat(5.))).into()));
+
+		let file = write_pdf(&group([layer]), &render_params()).unwrap();
+		let (_, content) = object(&file, 4);
+		assert!(String::from_utf8(content).unwrap().contains("q\n0 0 m\n5 0 l\n5 5 l\n0 5 l\n0 0 l\nh\nW n\n"));
+	}
+
+	#[test]
+	fn raster_masks_use_their_luminance() {
+		let mut layer = element(VectorData::from_subpath(Subpath::new_rect(DVec2::ZERO, DVec2::ONE)).into());
+		let mask = VectorData::from_subpath(Subpath::new_rect(DVec2::ZERO, DVec2::ONE));
+		layer.mask = Some(Box::new(GraphicElementData::GraphicGroup(group([element(mask.into())]))));
+
+		let file = write_pdf(&group([layer]), &render_params()).unwrap();
+		let (resources, _) = object(&file, RESOURCES_ID);
+		assert!(resources.contains("/GS0 << /Type /ExtGState /SMask << /S /Luminosity /G 4 0 R >> >>"));
+		assert!(object(&file, 4).0.contains("/Group << /S /Transparency /CS /DeviceRGB >>"));
+	}
+
+	#[test]
+	fn clipped_layers_are_masked_by_the_alpha_of_their_base() {
+		let rectangle = || VectorData::from_subpath(Subpath::new_rect(DVec2::ZERO, DVec2::ONE));
+		let mut clipped = element(rectangle().into());
+		clipped.clip = true;
+		let mut hidden_base = element(rectangle().into());
+		hidden_base.visible = false;
+		let mut hidden_clipped = element(rectangle().into());
+		hidden_clipped.clip = true;
+
+		let file = write_pdf(&group([element(rectangle().into()), clipped, hidden_base, hidden_clipped]), &render_params()).unwrap();
+		let (resources, _) = object(&file, RESOURCES_ID);
+		assert!(resources.contains("/GS0 << /Type /ExtGState /SMask << /S /Alpha /G 4 0 R >> >>"));
+		// The element clipped to a hidden base isn't drawn
+		assert!(resources.contains("/XObject << /X0 5 0 R >>"));
+	}
+
+	#[test]
+	fn layer_effects_stop_the_export() {
+		let mut layer = element(VectorData::from_subpath(Subpath::new_rect(DVec2::ZERO, DVec2::ONE)).into());
+		layer.name = "Shadowed".to_string();
+		layer.effects.push(LayerEffect::new(LayerEffectKind::DropShadow));
+		let mut disabled = layer.clone();
+		disabled.name = "Disabled".to_string();
+		disabled.effects[0].enabled = false;
+
+		let error = write_pdf(&group([layer, disabled.clone()]), &render_params()).unwrap_err();
+		assert!(error.ends_with(": \"Shadowed\"."));
+		assert!(write_pdf(&group([disabled]), &render_params()).is_ok());
+	}
+
+	#[test]
+	fn images_are_embedded_with_their_transparency() {
+		let image = Image {
+			width: 2,
+			height: 1,
+			data: vec![Color::from_rgbaf32_unchecked(1., 0., 0., 1.), Color::TRANSPARENT],
+		};
+		let image_frame = ImageFrame {
+			image,
+			transform: DAffine2::from_scale(DVec2::new(2., 1.)),
+		};
+
+		let file = write_pdf(&group([element(image_frame.into())]), &render_params()).unwrap();
+		let (mask, alpha) = object(&file, 4);
+		assert!(mask.contains("/ColorSpace /DeviceGray"));
+		assert_eq!(alpha, [255, 0]);
+		let (image, rgb) = object(&file, 5);
+		assert!(image.contains("/Width 2 /Height 1 /ColorSpace /DeviceRGB /BitsPerComponent 8 /SMask 4 0 R"));
+		assert_eq!(rgb[..3], [255, 0, 0]);
+		let (_, content) = object(&file, 6);
+		assert!(String::from_utf8(content).unwrap().contains("2 0 0 -1 0 1 cm\n/X0 Do"));
+	}
+
+	#[test]
+	fn gradient_stops_are_stitched() {
+		let stops = [(0., vec![0.]), (0.25, vec![0.5]), (1., vec![1.])];
+		assert_eq!(
+			stops_function(&stops),
+			"<< /FunctionType 3 /Domain [0 1] /Functions [<< /FunctionType 2 /Domain [0 1] /C0 [0] /C1 [0.5] /N 1 >> << /FunctionType 2 /Domain [0 1] /C0 [0.5] /C1 [1] /N 1 >>] /Bounds [0.25] /Encode [0 1 0 1] >>"
+		);
+	}
+
+	#[test]
+	fn numbers_are_written_without_exponents() {
+		assert_eq!(number(1e-7), "0");
+		assert_eq!(number(-0.), "0");
+		assert_eq!(number(1e20), "100000000000000000000");
+		assert_eq!(number(-2.50001), "-2.5");
+		assert_eq!(number(3.), "3");
+	}
+}
@@ -2,11 +2,13 @@ use dyn_any::{DynAny, StaticType};
 use glam::{DAffine2, DVec2, Vec2};
 use graph_craft::imaginate_input::{ImaginateController, ImaginateMaskStartingFill, ImaginateSamplingMethod};
 use graph_craft::proto::DynFuture;
-use graphene_core::raster::{Alpha, BlendMode, BlendNode, Image, ImageFrame, Linear, LinearChannel, Luminance, NoiseType, Pixel, RGBMut, Raster, RasterMut, RedGreenBlue, Sample};
+use graphene_core::raster::{Alpha, BlendMode, BlendNode, Image, ImageFrame, Linear, LinearChannel, Luma, Luminance, NoiseType, Pixel, RGBMut, Raster, RasterMut, RedGreenBlue, Sample};
 use graphene_core::transform::{Footprint, Transform};
 
 use crate::wasm_application_io::WasmEditorApi;
 use graphene_core::raster::bbox::{AxisAlignedBbox, Bbox};
+use graphene_core::raster::flood_fill::FloodFill;
+use graphene_core::raster::pixel_selection::{limit_to_mask, PixelSelection};
 use graphene_core::value::CopiedNode;
 use graphene_core::{Color, Node};
 
@@ -420,6 +422,78 @@ fn extend_image_to_bounds_node(image: ImageFrame<Color>, bounds: DAffine2) -> Im
 	}
 }
 
+#[derive(Debug, Clone)]
+pub struct FloodFillNode<SampleSource, Fills> {
+	sample_source: SampleSource,
+	fills: Fills,
+}
+
+#[node_macro::node_fn(FloodFillNode)]
+fn flood_fill_node(image: ImageFrame<Color>, sample_source: ImageFrame<Color>, fills: Vec<FloodFill>) -> ImageFrame<Color> {
+	let has_sample_source = sample_source.image.width > 0 && sample_source.image.height > 0 && sample_source.transform.matrix2.determinant() != 0.;
+	let sample_merged = has_sample_source && fills.iter().any(|fill| fill.sample_merged);
+
+	// Fills which sample the pixels below the layer can spread over all of them, so the layer is grown to cover them
+	let mut image = if sample_merged {
+		ExtendImageToBoundsNode::new(CopiedNode::new(sample_source.transform)).eval(image)
+	} else {
+		image
+	};
+
+	for fill in &fills {
+		let sample = if fill.sample_merged && has_sample_source {
+			merged_sample(&image, &sample_source)
+		} else {
+			image.image.clone()
+		};
+		fill.apply(&mut image, &sample);
+	}
+
+	image
+}
+
+/// Composites the image over the pixels of `below` which are under each of its pixels.
+fn merged_sample(image: &ImageFrame<Color>, below: &ImageFrame<Color>) -> Image<Color> {
+	let pixels_to_layer = image.transform * DAffine2::from_scale(DVec2::new(1. / image.image.width as f64, 1. / image.image.height as f64));
+	let pixel_area = pixels_to_layer.transform_vector2(DVec2::ONE);
+
+	let mut sample = image.image.clone();
+	for y in 0..sample.height {
+		for x in 0..sample.width {
+			let center = pixels_to_layer.transform_point2(DVec2::new(x as f64 + 0.5, y as f64 + 0.5));
+			let pixel = &mut sample.data[(y * sample.width + x) as usize];
+			if let Some(below) = Sample::sample(below, center, pixel_area) {
+				*pixel = below.alpha_blend(*pixel);
+			}
+		}
+	}
+	sample
+}
+
+#[derive(Debug, Clone)]
+pub struct SelectionMaskNode<Selection> {
+	selection: Selection,
+}
+
+/// Rasterizes a pixel selection into a mask over the pixels of the image, whose colors are also what magic wand edits compare.
+#[node_macro::node_fn(SelectionMaskNode)]
+fn selection_mask_node(image: ImageFrame<Color>, selection: PixelSelection) -> ImageFrame<Luma> {
+	selection.mask(image.transform, image.image.width, image.image.height, Some(&image))
+}
+
+#[derive(Debug, Clone)]
+pub struct LimitToMaskNode<Original, Mask> {
+	original: Original,
+	mask: Mask,
+}
+
+/// Restricts the changes made to the original image, such as by an adjustment, to the parts of it which are selected by the mask.
+#[node_macro::node_fn(LimitToMaskNode)]
+fn limit_to_mask_node(mut image: ImageFrame<Color>, original: ImageFrame<Color>, mask: ImageFrame<Luma>) -> ImageFrame<Color> {
+	limit_to_mask(&mut image, &original, &mask);
+	image
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct MergeBoundingBoxNode<Data> {
 	_data: PhantomData<Data>,
@@ -630,6 +704,41 @@ fn map_color(iter: usize, max_iter: usize) -> Color {
 
 #[cfg(test)]
 mod test {
+	use super::*;
+	use graphene_core::value::ClonedNode;
+
+	#[test]
+	fn flood_fill_samples_merged_pixels() {
+		// A transparent layer over a background which is red on its left half and blue on its right half
+		let mut background = Image::new(4, 2, Color::RED);
+		for y in 0..2 {
+			for x in 2..4 {
+				background.data[y * 4 + x] = Color::BLUE;
+			}
+		}
+		let background = ImageFrame {
+			image: background,
+			transform: DAffine2::from_scale(DVec2::new(4., 2.)),
+		};
+		let fill = FloodFill {
+			position: DVec2::new(0.5, 0.5),
+			color: Color::GREEN,
+			tolerance: 0.,
+			anti_alias: false,
+			sample_merged: true,
+			..Default::default()
+		};
+
+		let node = FloodFillNode::new(ClonedNode::new(background), ClonedNode::new(vec![fill.clone()]));
+		let filled = node.eval(ImageFrame::empty());
+		assert_eq!((filled.image.width, filled.image.height), (4, 2));
+		assert_eq!(filled.image.data.iter().filter(|&&color| color == Color::GREEN).count(), 4);
+		assert_eq!(filled.image.data[3], Color::TRANSPARENT);
+
+		// Without sampling the pixels below, the empty layer has nothing to fill
+		let node = FloodFillNode::new(ClonedNode::new(ImageFrame::empty()), ClonedNode::new(vec![FloodFill { sample_merged: false, ..fill }]));
+		assert_eq!(node.eval(ImageFrame::empty()).image.data.len(), 0);
+	}
 
 	#[test]
 	fn load_image() {
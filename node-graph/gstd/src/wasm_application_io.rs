@@ -1,8 +1,12 @@
 use std::cell::RefCell;
 
+use crate::color_profile::ProfileConversion;
+use crate::pdf::PdfRendered;
+
 use core::future::Future;
 use dyn_any::StaticType;
 use graphene_core::application_io::{ApplicationError, ApplicationIo, ExportFormat, ResourceFuture, SurfaceHandle, SurfaceHandleFrame, SurfaceId};
+use graphene_core::raster::color_management::WorkingSpace;
 use graphene_core::raster::Image;
 use graphene_core::renderer::{GraphicElementRendered, RenderParams, SvgRender};
 use graphene_core::transform::Footprint;
@@ -268,15 +272,32 @@ async fn load_resource_node<'a: 'input>(editor: WasmEditorApi<'a>, url: String)
 	editor.application_io.load_resource(url).unwrap().await.unwrap()
 }
 
-pub struct DecodeImageNode;
+pub struct DecodeImageNode<EditorApi> {
+	editor_api: EditorApi,
+}
 
 #[node_macro::node_fn(DecodeImageNode)]
-fn decode_image_node<'a: 'input>(data: Arc<[u8]>) -> ImageFrame<Color> {
+fn decode_image_node<'a: 'input>(data: Arc<[u8]>, editor_api: WasmEditorApi<'a>) -> ImageFrame<Color> {
 	let image = image::load_from_memory(data.as_ref()).expect("Failed to decode image");
 	let image = image.to_rgba32f();
+	// Images are converted from their embedded profile to the document's working space, and otherwise assumed to be sRGB
+	let working_space = editor_api.working_space;
+	let conversion = ProfileConversion::from_embedded_profile(data.as_ref(), working_space);
+	let from_srgb = (working_space != WorkingSpace::Srgb).then(|| (working_space.from_xyz_matrix() * WorkingSpace::Srgb.to_xyz_matrix()).as_mat3());
+	let to_linear = |pixel: &[f32]| match (&conversion, from_srgb) {
+		(Some(conversion), _) => conversion.convert([pixel[0], pixel[1], pixel[2]].map(|channel| channel as f64)).as_vec3().to_array(),
+		(None, Some(matrix)) => (matrix * glam::Vec3::from_array([pixel[0], pixel[1], pixel[2]].map(Color::srgb_to_linear))).to_array(),
+		(None, None) => [pixel[0], pixel[1], pixel[2]].map(Color::srgb_to_linear),
+	};
 	let image = ImageFrame {
 		image: Image {
-			data: image.chunks(4).map(|pixel| Color::from_unassociated_alpha(pixel[0], pixel[1], pixel[2], pixel[3])).collect(),
+			data: image
+				.chunks(4)
+				.map(|pixel| {
+					let [red, green, blue] = to_linear(pixel);
+					Color::from_unassociated_alpha(red, green, blue, pixel[3])
+				})
+				.collect(),
 			width: image.width(),
 			height: image.height(),
 		},
@@ -298,6 +319,10 @@ fn render_svg(data: impl GraphicElementRendered, mut render: SvgRender, render_p
 	RenderOutput::Svg(render.svg.to_string())
 }
 
+fn render_pdf(data: impl GraphicElementRendered + PdfRendered, render_params: RenderParams) -> RenderOutput {
+	RenderOutput::Pdf(crate::pdf::write_pdf(&data, &render_params))
+}
+
 #[cfg(any(feature = "resvg", feature = "vello"))]
 fn render_canvas(
 	data: impl GraphicElementRendered,
@@ -352,7 +377,7 @@ fn render_canvas(
 }
 
 // Render with the data node taking in Footprint.
-impl<'input, 'a: 'input, T: 'input + GraphicElementRendered, F: 'input + Future<Output = T>, Data: 'input, Surface: 'input, SurfaceFuture: 'input> Node<'input, WasmEditorApi<'a>>
+impl<'input, 'a: 'input, T: 'input + GraphicElementRendered + PdfRendered, F: 'input + Future<Output = T>, Data: 'input, Surface: 'input, SurfaceFuture: 'input> Node<'input, WasmEditorApi<'a>>
 	for RenderNode<Data, Surface, Footprint>
 where
 	Data: Node<'input, Footprint, Output = F>,
@@ -365,11 +390,13 @@ where
 	fn eval(&'input self, editor: WasmEditorApi<'a>) -> Self::Output {
 		Box::pin(async move {
 			let footprint = editor.render_config.viewport;
-			let render_params = RenderParams::new(ViewMode::Normal, graphene_core::renderer::ImageRenderMode::Base64, None, false);
+			let mut render_params = RenderParams::new(ViewMode::Normal, graphene_core::renderer::ImageRenderMode::Base64, None, false);
+			render_params.display_transform = editor.display_transform.cloned();
 
 			let output_format = editor.render_config.export_format;
 			match output_format {
 				ExportFormat::Svg => render_svg(self.data.eval(footprint).await, SvgRender::new(), render_params, footprint),
+				ExportFormat::Pdf => render_pdf(self.data.eval(footprint).await, render_params),
 				#[cfg(any(feature = "resvg", feature = "vello"))]
 				ExportFormat::Canvas => render_canvas(self.data.eval(footprint).await, SvgRender::new(), render_params, footprint, editor, self.surface_handle.eval(()).await),
 				_ => todo!("Non-SVG render output for {output_format:?}"),
@@ -379,7 +406,7 @@ where
 }
 
 // Render with the data node taking in ().
-impl<'input, 'a: 'input, T: 'input + GraphicElementRendered, F: 'input + Future<Output = T>, Data: 'input, Surface: 'input, SurfaceFuture: 'input> Node<'input, WasmEditorApi<'a>>
+impl<'input, 'a: 'input, T: 'input + GraphicElementRendered + PdfRendered, F: 'input + Future<Output = T>, Data: 'input, Surface: 'input, SurfaceFuture: 'input> Node<'input, WasmEditorApi<'a>>
 	for RenderNode<Data, Surface, ()>
 where
 	Data: Node<'input, (), Output = F>,
@@ -393,11 +420,13 @@ where
 			use graphene_core::renderer::ImageRenderMode;
 
 			let footprint = editor.render_config.viewport;
-			let render_params = RenderParams::new(ViewMode::Normal, ImageRenderMode::Base64, None, false);
+			let mut render_params = RenderParams::new(ViewMode::Normal, ImageRenderMode::Base64, None, false);
+			render_params.display_transform = editor.display_transform.cloned();
 
 			let output_format = editor.render_config.export_format;
 			match output_format {
 				ExportFormat::Svg => render_svg(self.data.eval(()).await, SvgRender::new(), render_params, footprint),
+				ExportFormat::Pdf => render_pdf(self.data.eval(()).await, render_params),
 				#[cfg(any(feature = "resvg", feature = "vello"))]
 				ExportFormat::Canvas => render_canvas(self.data.eval(()).await, SvgRender::new(), render_params, footprint, editor, self.surface_handle.eval(()).await),
 				_ => todo!("Non-SVG render output for {output_format:?}"),
@@ -1,10 +1,13 @@
 use graph_craft::imaginate_input::{ImaginateController, ImaginateMaskStartingFill, ImaginateSamplingMethod};
 use graph_craft::proto::{NodeConstructor, TypeErasedBox};
+use graphene_core::layer_effects::LayerEffect;
 use graphene_core::ops::IdNode;
 use graphene_core::quantization::{PackedPixel, QuantizationChannels};
 
 use graphene_core::raster::brush_cache::BrushCache;
 use graphene_core::raster::color::Color;
+use graphene_core::raster::flood_fill::FloodFill;
+use graphene_core::raster::pixel_selection::PixelSelection;
 use graphene_core::structural::Then;
 use graphene_core::transform::Footprint;
 use graphene_core::value::{ClonedNode, CopiedNode, ValueNode};
@@ -271,8 +274,17 @@ fn node_registry() -> HashMap<NodeIdentifier, HashMap<NodeIOTypes, NodeConstruct
 		register_node!(graphene_core::logic::LogicAndNode<_>, input: bool, params: [bool]),
 		register_node!(graphene_core::logic::LogicXorNode<_>, input: bool, params: [bool]),
 		register_node!(graphene_core::logic::LogicNotNode, input: bool, params: []),
+		async_node!(graphene_core::ops::IntoNode<_, ImageFrame<Color>>, input: ImageFrame<Color>, output: ImageFrame<Color>, params: []),
 		async_node!(graphene_core::ops::IntoNode<_, ImageFrame<SRGBA8>>, input: ImageFrame<Color>, output: ImageFrame<SRGBA8>, params: []),
 		async_node!(graphene_core::ops::IntoNode<_, ImageFrame<Color>>, input: ImageFrame<SRGBA8>, output: ImageFrame<Color>, params: []),
+		async_node!(graphene_core::ops::IntoNode<_, ImageFrame<SRGBA16>>, input: ImageFrame<Color>, output: ImageFrame<SRGBA16>, params: []),
+		async_node!(graphene_core::ops::IntoNode<_, ImageFrame<Color>>, input: ImageFrame<SRGBA16>, output: ImageFrame<Color>, params: []),
+		async_node!(graphene_core::ops::IntoNode<_, ImageFrame<RGBA16F>>, input: ImageFrame<Color>, output: ImageFrame<RGBA16F>, params: []),
+		async_node!(graphene_core::ops::IntoNode<_, ImageFrame<Color>>, input: ImageFrame<RGBA16F>, output: ImageFrame<Color>, params: []),
+		async_node!(graphene_core::ops::IntoNode<_, ImageFrame<LumaAlpha>>, input: ImageFrame<Color>, output: ImageFrame<LumaAlpha>, params: []),
+		async_node!(graphene_core::ops::IntoNode<_, ImageFrame<Color>>, input: ImageFrame<LumaAlpha>, output: ImageFrame<Color>, params: []),
+		async_node!(graphene_core::ops::IntoNode<_, ImageFrame<Luma>>, input: ImageFrame<Color>, output: ImageFrame<Luma>, params: []),
+		async_node!(graphene_core::ops::IntoNode<_, ImageFrame<Color>>, input: ImageFrame<Luma>, output: ImageFrame<Color>, params: []),
 		async_node!(graphene_core::ops::IntoNode<_, GraphicGroup>, input: ImageFrame<Color>, output: GraphicGroup, params: []),
 		async_node!(graphene_core::ops::IntoNode<_, GraphicGroup>, input: VectorData, output: GraphicGroup, params: []),
 		async_node!(graphene_core::ops::IntoNode<_, GraphicGroup>, input: GraphicGroup, output: GraphicGroup, params: []),
@@ -331,7 +343,7 @@ fn node_registry() -> HashMap<NodeIdentifier, HashMap<NodeIOTypes, NodeConstruct
 		register_node!(graphene_core::memo::MonitorNode<_>, input: VectorData, params: []),
 		register_node!(graphene_core::memo::MonitorNode<_>, input: graphene_core::GraphicElementData, params: []),
 		async_node!(graphene_std::wasm_application_io::LoadResourceNode<_>, input: WasmEditorApi, output: Arc<[u8]>, params: [String]),
-		register_node!(graphene_std::wasm_application_io::DecodeImageNode, input: Arc<[u8]>, params: []),
+		register_node!(graphene_std::wasm_application_io::DecodeImageNode<_>, input: Arc<[u8]>, params: [WasmEditorApi]),
 		async_node!(graphene_std::wasm_application_io::CreateSurfaceNode, input: WasmEditorApi, output: Arc<SurfaceHandle<<graphene_std::wasm_application_io::WasmApplicationIo as graphene_core::application_io::ApplicationIo>::Surface>>, params: []),
 		async_node!(
 			graphene_std::wasm_application_io::DrawImageFrameNode<_>,
@@ -433,6 +445,9 @@ fn node_registry() -> HashMap<NodeIdentifier, HashMap<NodeIOTypes, NodeConstruct
 		)],
 		register_node!(graphene_std::brush::IntoIterNode<_>, input: &Vec<BrushStroke>, params: []),
 		async_node!(graphene_std::brush::BrushNode<_, _, _>, input: ImageFrame<Color>, output: ImageFrame<Color>, params: [ImageFrame<Color>, Vec<BrushStroke>, BrushCache]),
+		register_node!(graphene_std::raster::FloodFillNode<_, _>, input: ImageFrame<Color>, params: [ImageFrame<Color>, Vec<FloodFill>]),
+		register_node!(graphene_std::raster::SelectionMaskNode<_>, input: ImageFrame<Color>, params: [PixelSelection]),
+		register_node!(graphene_std::raster::LimitToMaskNode<_, _>, input: ImageFrame<Color>, params: [ImageFrame<Color>, ImageFrame<Luma>]),
 		// Filters
 		raster_node!(graphene_core::raster::LuminanceNode<_>, params: [LuminanceCalculation]),
 		raster_node!(graphene_core::raster::ExtractChannelNode<_>, params: [RedGreenBlue]),
@@ -747,10 +762,12 @@ fn node_registry() -> HashMap<NodeIdentifier, HashMap<NodeIOTypes, NodeConstruct
 		register_node!(graphene_core::transform::SetTransformNode<_>, input: VectorData, params: [DAffine2]),
 		register_node!(graphene_core::transform::SetTransformNode<_>, input: ImageFrame<Color>, params: [DAffine2]),
 		register_node!(graphene_core::vector::SetFillNode<_, _, _, _, _, _, _>, input: VectorData, params: [graphene_core::vector::style::FillType, Option<graphene_core::Color>, graphene_core::vector::style::GradientType, DVec2, DVec2, DAffine2, Vec<(f64, Option<graphene_core::Color>)>]),
-		register_node!(graphene_core::vector::SetStrokeNode<_, _, _, _, _, _, _>, input: VectorData, params: [Option<graphene_core::Color>, f32, Vec<f32>, f32, graphene_core::vector::style::LineCap, graphene_core::vector::style::LineJoin, f32]),
+		register_node!(graphene_core::vector::SetStrokeNode<_, _, _, _, _, _, _, _, _, _, _>, input: VectorData, params: [Option<graphene_core::Color>, f32, Vec<f32>, f32, graphene_core::vector::style::LineCap, graphene_core::vector::style::LineJoin, f32, Vec<DVec2>, f64, f64, graphene_core::vector::style::StrokeAlign]),
 		register_node!(graphene_core::vector::RepeatNode<_, _>, input: VectorData, params: [DVec2, u32]),
 		register_node!(graphene_core::vector::BoundingBoxNode, input: VectorData, params: []),
 		register_node!(graphene_core::vector::CircularRepeatNode<_, _, _>, input: VectorData, params: [f32, f32, u32]),
+		register_node!(graphene_core::vector::CopyToPointsNode<_, _, _, _, _, _>, input: VectorData, params: [VectorData, bool, f64, f64, f64, u32]),
+		register_node!(graphene_core::vector::CopyToPointsNode<_, _, _, _, _, _>, input: VectorData, params: [GraphicGroup, bool, f64, f64, f64, u32]),
 		vec![(
 			NodeIdentifier::new("graphene_core::transform::CullNode<_>"),
 			|args| {
@@ -806,10 +823,20 @@ fn node_registry() -> HashMap<NodeIdentifier, HashMap<NodeIOTypes, NodeConstruct
 			input: Vec<graphene_core::vector::bezier_rs::Subpath<graphene_core::uuid::ManipulatorGroupId>>,
 			params: [Vec<graphene_core::uuid::ManipulatorGroupId>]
 		),
-		register_node!(graphene_core::text::TextGenerator<_, _, _>, input: WasmEditorApi, params: [String, graphene_core::text::Font, f64]),
+		register_node!(
+			graphene_core::text::TextGenerator<_, _, _, _, _, _, _, _, _, _>,
+			input: WasmEditorApi,
+			params: [String, graphene_core::text::Font, f64, f64, f64, f64, f64, f64, graphene_core::text::TextAlignment, graphene_core::text::FontFeatures]
+		),
+		register_node!(
+			graphene_core::text::TextOnPathNode<_, _, _, _, _, _, _, _>,
+			input: WasmEditorApi,
+			params: [VectorData, String, graphene_core::text::Font, f64, f64, graphene_core::text::TextAlignment, bool, f64]
+		),
 		register_node!(graphene_std::brush::VectorPointsNode, input: VectorData, params: []),
 		register_node!(graphene_core::ExtractImageFrame, input: WasmEditorApi, params: []),
-		async_node!(graphene_core::ConstructLayerNode<_, _, _, _, _, _, _, _>, input: Footprint, output: GraphicGroup, fn_params: [Footprint => graphene_core::GraphicElementData, () => String, () => BlendMode, () => f32,  () => bool, () => bool, () => bool, Footprint => GraphicGroup]),
+		register_node!(graphene_core::animation::TimeNode, input: WasmEditorApi, params: []),
+		async_node!(graphene_core::ConstructLayerNode<_, _, _, _, _, _, _, _, _, _, _>, input: Footprint, output: GraphicGroup, fn_params: [Footprint => graphene_core::GraphicElementData, () => String, () => BlendMode, () => f32,  () => bool, () => bool, () => bool, Footprint => GraphicGroup, Footprint => graphene_core::GraphicElementData, () => bool, () => Vec<LayerEffect>]),
 		register_node!(graphene_core::ToGraphicElementData, input: graphene_core::vector::VectorData, params: []),
 		register_node!(graphene_core::ToGraphicElementData, input: ImageFrame<Color>, params: []),
 		register_node!(graphene_core::ToGraphicElementData, input: GraphicGroup, params: []),
@@ -825,6 +852,10 @@ fn node_registry() -> HashMap<NodeIdentifier, HashMap<NodeIOTypes, NodeConstruct
 		let nid = NodeIdentifier { name: Cow::Owned(new_name) };
 		map.entry(nid).or_default().insert(types.clone(), c);
 	}
+	// Nodes declared with a `category` in `#[node_macro::node_fn]` register themselves
+	for metadata in graphene_core::registry::registered_nodes() {
+		map.entry(NodeIdentifier::new(metadata.identifier)).or_default().insert((metadata.node_io)(), metadata.constructor);
+	}
 	map
 }
 
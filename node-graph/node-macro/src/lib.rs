@@ -6,11 +6,29 @@ use syn::{
 	PredicateType, ReturnType, Token, TraitBound, Type, TypeImplTrait, TypeParam, TypeParamBound, TypeTuple, WhereClause, WherePredicate,
 };
 
+mod registration;
+
+use registration::{node_registration, take_input_attributes, NodeFnArgs};
+
 #[proc_macro_attribute]
 pub fn node_fn(attr: TokenStream, item: TokenStream) -> TokenStream {
+	let args = parse_macro_input!(attr as NodeFnArgs);
+	let mut function = parse_macro_input!(item as ItemFn);
+	let input_attributes = match take_input_attributes(&mut function) {
+		Ok(input_attributes) => input_attributes,
+		Err(error) => return error.to_compile_error().into(),
+	};
+
+	let attr: TokenStream = args.node.to_token_stream().into();
+	let item: TokenStream = function.to_token_stream().into();
 	let mut imp = node_impl_proxy(attr.clone(), item.clone());
 	let new = node_new_impl(attr, item);
 	imp.extend(new);
+
+	if let Some(registration) = &args.registration {
+		let registration = node_registration(&args.node, registration, &function, &input_attributes).unwrap_or_else(|error| error.to_compile_error());
+		imp.extend(TokenStream::from(registration));
+	}
 	imp
 }
 #[proc_macro_attribute]
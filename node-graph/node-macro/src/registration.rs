@@ -0,0 +1,235 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::{Error, Expr, FnArg, Ident, ItemFn, LitBool, LitStr, Pat, PathArguments, PathSegment, ReturnType, Token, Type};
+
+/// The arguments of `#[node_fn(NodeName, name = "Display Name", category = "Category", output = "Output Name")]`, where everything after the node struct is optional.
+/// Giving a `category` registers the node so it doesn't need to be listed by hand in the node registry and the editor's node types.
+pub struct NodeFnArgs {
+	pub node: PathSegment,
+	pub registration: Option<Registration>,
+}
+
+pub struct Registration {
+	name: Option<LitStr>,
+	category: LitStr,
+	output: Option<LitStr>,
+}
+
+impl Parse for NodeFnArgs {
+	fn parse(input: ParseStream) -> syn::Result<Self> {
+		let node = input.parse()?;
+
+		let (mut name, mut category, mut output) = (None, None, None);
+		while !input.is_empty() {
+			input.parse::<Token![,]>()?;
+			if input.is_empty() {
+				break;
+			}
+
+			let key: Ident = input.parse()?;
+			input.parse::<Token![=]>()?;
+			let value: LitStr = input.parse()?;
+			match key.to_string().as_str() {
+				"name" => name = Some(value),
+				"category" => category = Some(value),
+				"output" => output = Some(value),
+				_ => return Err(Error::new(key.span(), "Expected `name`, `category` or `output`")),
+			}
+		}
+
+		let registration = match category {
+			Some(category) => Some(Registration { name, category, output }),
+			None => {
+				if let Some(argument) = name.or(output) {
+					return Err(Error::new(argument.span(), "Registering a node requires a `category`"));
+				}
+				None
+			}
+		};
+
+		Ok(Self { node, registration })
+	}
+}
+
+/// The metadata given to an input of the node function through `#[input(name = "Name", default = 1., exposed, unit = "px", min = 0., max = 100.)]`.
+#[derive(Default)]
+pub struct InputAttributes {
+	name: Option<LitStr>,
+	default: Option<Expr>,
+	exposed: Option<bool>,
+	unit: Option<LitStr>,
+	min: Option<Expr>,
+	max: Option<Expr>,
+}
+
+/// Removes the `#[input(...)]` attributes from the function's inputs, which would otherwise be rejected by the compiler, and returns their contents in order.
+pub fn take_input_attributes(function: &mut ItemFn) -> syn::Result<Vec<InputAttributes>> {
+	let mut inputs = Vec::new();
+
+	for argument in function.sig.inputs.iter_mut() {
+		let FnArg::Typed(typed) = argument else { continue };
+
+		let (input_attributes, other_attributes) = typed.attrs.drain(..).partition::<Vec<_>, _>(|attribute| attribute.path().is_ident("input"));
+		typed.attrs = other_attributes;
+
+		let mut attributes = InputAttributes::default();
+		for attribute in input_attributes {
+			attribute.parse_nested_meta(|meta| {
+				let key = meta.path.get_ident().map(|ident| ident.to_string()).unwrap_or_default();
+				match key.as_str() {
+					"name" => attributes.name = Some(meta.value()?.parse()?),
+					"default" => attributes.default = Some(meta.value()?.parse()?),
+					"exposed" => attributes.exposed = Some(if meta.input.peek(Token![=]) { meta.value()?.parse::<LitBool>()?.value } else { true }),
+					"unit" => attributes.unit = Some(meta.value()?.parse()?),
+					"min" => attributes.min = Some(meta.value()?.parse()?),
+					"max" => attributes.max = Some(meta.value()?.parse()?),
+					_ => return Err(meta.error("Expected `name`, `default`, `exposed`, `unit`, `min` or `max`")),
+				}
+				Ok(())
+			})?;
+		}
+		inputs.push(attributes);
+	}
+
+	Ok(inputs)
+}
+
+/// Generates the submission of the node's `graphene_core::registry::NodeMetadata`, including the constructor used by the node registry.
+pub fn node_registration(node: &PathSegment, registration: &Registration, function: &ItemFn, inputs: &[InputAttributes]) -> syn::Result<TokenStream2> {
+	if !matches!(node.arguments, PathArguments::None) {
+		return Err(Error::new_spanned(&node.arguments, "Registered nodes can't have generic arguments besides their inputs"));
+	}
+	if !function.sig.generics.params.is_empty() {
+		return Err(Error::new_spanned(
+			&function.sig.generics,
+			"Registered nodes must use concrete types, generic nodes still need a `register_node!` entry for each type",
+		));
+	}
+
+	let typed_inputs = function
+		.sig
+		.inputs
+		.iter()
+		.filter_map(|argument| if let FnArg::Typed(typed) = argument { Some(typed) } else { None })
+		.collect::<Vec<_>>();
+	let Some((primary_input, parameters)) = typed_inputs.split_first() else {
+		return Err(Error::new_spanned(&function.sig, "Primary input required - set to `()` if not needed."));
+	};
+	if let Some(parameter) = parameters.iter().find(|parameter| matches!(*parameter.ty, Type::ImplTrait(_))) {
+		return Err(Error::new_spanned(&parameter.ty, "Registered nodes can't take `impl Node` parameters"));
+	}
+
+	let node_name = &node.ident;
+	let identifier = if parameters.is_empty() {
+		node_name.to_string()
+	} else {
+		format!("{node_name}<{}>", vec!["_"; parameters.len()].join(", "))
+	};
+	let display_name = registration.name.as_ref().map(LitStr::value).unwrap_or_else(|| title_case(&function.sig.ident.to_string()));
+	let category = &registration.category;
+	let output_name = registration.output.as_ref().map(LitStr::value).unwrap_or_else(|| "Output".to_string());
+
+	let primary_type = &primary_input.ty;
+	let output_type = match &function.sig.output {
+		ReturnType::Type(_, ty) => ty.to_token_stream(),
+		ReturnType::Default => quote!(()),
+	};
+	let parameter_types = parameters.iter().map(|parameter| &parameter.ty).collect::<Vec<_>>();
+
+	let fields = typed_inputs
+		.iter()
+		.zip(inputs)
+		.enumerate()
+		.map(|(index, (input, attributes))| {
+			let Pat::Ident(pat_ident) = &*input.pat else {
+				return Err(Error::new_spanned(&input.pat, "Expected ident for node input"));
+			};
+			let name = attributes.name.as_ref().map(LitStr::value).unwrap_or_else(|| title_case(&pat_ident.ident.to_string()));
+			let ty = &input.ty;
+			let default = attributes
+				.default
+				.as_ref()
+				.map(ToTokens::to_token_stream)
+				.unwrap_or_else(|| quote!(<#ty as ::core::default::Default>::default()));
+			let exposed = attributes.exposed.unwrap_or(index == 0);
+			let unit = optional(attributes.unit.as_ref());
+			let min = optional(attributes.min.as_ref());
+			let max = optional(attributes.max.as_ref());
+
+			Ok(quote! {
+				::graphene_core::registry::FieldMetadata {
+					name: #name,
+					default_value: || {
+						let value: #ty = #default;
+						Box::new(value) as ::graphene_core::registry::Any<'static>
+					},
+					exposed: #exposed,
+					unit: #unit,
+					min: #min,
+					max: #max,
+				}
+			})
+		})
+		.collect::<syn::Result<Vec<_>>>()?;
+
+	// Like `register_node!`, the parameters are evaluated once when the node is constructed
+	let constructor_arguments = parameter_types.iter().map(|ty| {
+		quote! {{
+			let node = ::graphene_core::registry::downcast_node::<(), #ty>(args.pop().expect("Not enough arguments provided to construct node"));
+			let value = ::graphene_core::Node::eval(&node, ()).await;
+			::graphene_core::value::ClonedNode::new(value)
+		}}
+	});
+	let wrap_future = if function.sig.asyncness.is_some() {
+		quote!()
+	} else {
+		quote!(let node = ::graphene_core::registry::FutureWrapperNode::new(node);)
+	};
+
+	Ok(quote! {
+		::graphene_core::submit_node_metadata!(::graphene_core::registry::NodeMetadata {
+			identifier: concat!(module_path!(), "::", #identifier),
+			display_name: #display_name,
+			category: #category,
+			inputs: &[#(#fields),*],
+			output_name: #output_name,
+			node_io: || {
+				::graphene_core::NodeIOTypes::new(
+					::graphene_core::Type::new::<#primary_type>(),
+					::graphene_core::Type::new::<#output_type>(),
+					vec![#(::graphene_core::Type::Fn(Box::new(::graphene_core::Type::new::<()>()), Box::new(::graphene_core::Type::new::<#parameter_types>()))),*],
+				)
+			},
+			constructor: |mut args| {
+				Box::pin(async move {
+					args.reverse();
+					let node = #node_name::new(#(#constructor_arguments),*);
+					#wrap_future
+					let any: ::graphene_core::registry::DynAnyNode<#primary_type, _, _> = ::graphene_core::registry::DynAnyNode::new(node);
+					Box::new(any) as ::graphene_core::registry::TypeErasedBox<'static>
+				})
+			},
+		});
+	})
+}
+
+fn optional<T: ToTokens>(value: Option<&T>) -> TokenStream2 {
+	match value {
+		Some(value) => quote!(Some(#value)),
+		None => quote!(None),
+	}
+}
+
+/// Turns an identifier such as `miter_limit` into a name such as "Miter Limit".
+fn title_case(identifier: &str) -> String {
+	identifier
+		.split('_')
+		.filter(|word| !word.is_empty())
+		.map(|word| {
+			let mut chars = word.chars();
+			chars.next().map(|first| first.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+		})
+		.collect::<Vec<_>>()
+		.join(" ")
+}